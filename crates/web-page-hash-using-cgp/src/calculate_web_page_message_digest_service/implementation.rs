@@ -2,8 +2,10 @@ use super::interface::{
     CalculateWebPageMessageDigestService, CalculateWebPageMessageDigestServiceComponent,
     HasDigestType,
 };
-use crate::http_client_service::{GetUrl, HasUrlType, IntoChunkStream};
+use crate::http_client_service::{GetUrl, HasUrlType, IntoChunkStream, ResponseMetadata};
 use crate::message_digest_service::{IntoDigestOctets, NewDigestCalculator};
+use crate::progress_reporter_service::ProgressReporter;
+use crate::response_policy_service::{ResponsePolicy, ResponsePolicyViolation};
 use cgp::prelude::*;
 use futures_util::StreamExt;
 use std::io::Write;
@@ -13,14 +15,22 @@ pub struct CalculateWebPageMessageDigestServiceObject;
 #[cgp_impl(CalculateWebPageMessageDigestServiceObject)]
 impl<Context> CalculateWebPageMessageDigestService for Context
 where
-    Context: HasUrlType + HasDigestType + HasErrorType + GetUrl + NewDigestCalculator,
+    Context: HasUrlType
+        + HasDigestType
+        + HasErrorType
+        + GetUrl
+        + NewDigestCalculator
+        + ProgressReporter
+        + ResponsePolicy,
+    <Context as HasUrlType>::Url: AsRef<str>,
     <Context as HasErrorType>::Error: From<<Context as GetUrl>::Error>
         + From<<<Context as GetUrl>::HttpResponse as IntoChunkStream>::Error>
         + From<<Context as NewDigestCalculator>::Error>
         + From<<<Context as NewDigestCalculator>::DigestCalculator as IntoDigestOctets>::Error>
+        + From<ResponsePolicyViolation>
         + From<std::io::Error>
         + Send,
-    <Context as GetUrl>::HttpResponse: IntoChunkStream,
+    <Context as GetUrl>::HttpResponse: IntoChunkStream + ResponseMetadata,
     <Context as GetUrl>::HttpResponse: Sync,
     <Context as GetUrl>::Error: Sync,
     <Context as NewDigestCalculator>::DigestCalculator:
@@ -31,13 +41,35 @@ where
         url: &Context::Url,
     ) -> Result<<Context as HasDigestType>::Digest, <Context as HasErrorType>::Error> {
         let mut digest_calculator = context.new_digest_calculator()?;
-        let mut chunk_stream = context.get_url(url).await?.into_chunk_stream();
+        let response = context.get_url(url).await?;
+        context.check_response_metadata(&response)?;
+        let content_length = response.content_length();
+        let mut chunk_stream = response.into_chunk_stream();
+        context.report_started(url.as_ref(), content_length);
+        let finish_progress_on_drop = FinishProgressOnDrop(context);
+
+        let mut byte_count = 0;
 
         while let Some(chunk_result) = chunk_stream.next().await {
             let chunk = chunk_result?;
+            byte_count += chunk.len() as u64;
+            context.check_body_size(byte_count)?;
             digest_calculator.write_all(&chunk)?;
+            context.report_bytes_written(chunk.len() as u64);
         }
 
+        drop(finish_progress_on_drop);
+
         Ok(digest_calculator.into_digest_octets()?)
     }
 }
+
+/// Reports that streaming finished when dropped, so that it's reported also
+/// when streaming fails
+struct FinishProgressOnDrop<'a, Context: ProgressReporter>(&'a Context);
+
+impl<Context: ProgressReporter> Drop for FinishProgressOnDrop<'_, Context> {
+    fn drop(&mut self) {
+        self.0.report_finished();
+    }
+}
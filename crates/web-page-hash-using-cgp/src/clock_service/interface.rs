@@ -0,0 +1,18 @@
+use cgp::prelude::*;
+use std::time::{Duration, SystemTime};
+
+/// A service telling the time, e.g., for measuring elapsed time
+#[cgp_component(ClockService)]
+pub trait Clock {
+    /// Returns the current wall-clock time
+    fn now(&self) -> SystemTime;
+}
+
+/// A service waiting, e.g., for timeouts
+///
+/// Time-dependent behavior runs on virtual time, when both the [Clock], and
+/// the sleeper follow it (see [UseManualClock](super::UseManualClock)).
+#[cgp_component(SleeperService)]
+pub trait Sleeper {
+    fn sleep(&self, duration: Duration) -> impl Future<Output = ()>;
+}
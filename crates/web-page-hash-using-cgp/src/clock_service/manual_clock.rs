@@ -0,0 +1,130 @@
+use super::interface::{
+    ClockService, ClockServiceComponent, SleeperService, SleeperServiceComponent,
+};
+use cgp::prelude::*;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::task::{Poll, Waker};
+use std::time::{Duration, SystemTime};
+
+/// A time that advances only when [ManualClock::advance] is called, e.g., by
+/// a test
+///
+/// Sleeping finishes once the time is advanced past the end of the sleep.
+/// Clones share the same time.
+#[derive(Clone, Debug)]
+pub struct ManualClock {
+    state: Arc<Mutex<ManualClockState>>,
+}
+
+#[derive(Debug)]
+struct ManualClockState {
+    now: SystemTime,
+    /// The identifiers, and end times of unfinished sleeps, and the wakers of
+    /// the tasks last polling them
+    sleeps: Vec<(u64, SystemTime, Waker)>,
+    next_sleep_id: u64,
+}
+
+impl ManualClock {
+    pub fn new(now: SystemTime) -> Self {
+        ManualClock {
+            state: Arc::new(Mutex::new(ManualClockState {
+                now,
+                sleeps: Vec::new(),
+                next_sleep_id: 0,
+            })),
+        }
+    }
+
+    pub fn now(&self) -> SystemTime {
+        self.state
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .now
+    }
+
+    /// Waits until the time is advanced by `duration`
+    pub async fn sleep(&self, duration: Duration) {
+        let (sleep_id, sleep_end) = {
+            let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+            let sleep_id = state.next_sleep_id;
+            state.next_sleep_id += 1;
+
+            (sleep_id, state.now + duration)
+        };
+
+        std::future::poll_fn(|context| {
+            let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+            if state.now >= sleep_end {
+                return Poll::Ready(());
+            }
+            // Only the waker of the latest poll is woken, so a sleep polled
+            // repeatedly, e.g., by `select`, is registered once:
+            match state.sleeps.iter_mut().find(|(id, _, _)| *id == sleep_id) {
+                Some((_, _, waker)) => waker.clone_from(context.waker()),
+                None => state
+                    .sleeps
+                    .push((sleep_id, sleep_end, context.waker().clone())),
+            }
+
+            Poll::Pending
+        })
+        .await
+    }
+
+    /// Advances the time, and wakes the tasks whose sleeps end by then
+    pub fn advance(&self, duration: Duration) {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        state.now += duration;
+        let now = state.now;
+        let (finished_sleeps, sleeps) = std::mem::take(&mut state.sleeps)
+            .into_iter()
+            .partition(|(_, sleep_end, _)| *sleep_end <= now);
+        state.sleeps = sleeps;
+        // Woken tasks may poll again right away, so the lock is released
+        // first:
+        drop(state);
+
+        for (_, _, waker) in finished_sleeps {
+            waker.wake();
+        }
+    }
+}
+
+impl Default for ManualClock {
+    /// Returns a clock starting at the Unix epoch
+    fn default() -> Self {
+        ManualClock::new(SystemTime::UNIX_EPOCH)
+    }
+}
+
+/// A context holding the [ManualClock] followed by [UseManualClock]
+#[cgp_auto_getter]
+pub trait HasManualClock {
+    fn manual_clock(&self) -> &ManualClock;
+}
+
+/// A [ClockService], and [SleeperService] provider following the
+/// [ManualClock] of the context, e.g., for running time-dependent behavior
+/// in tests without real sleeps
+pub struct UseManualClock;
+
+#[cgp_impl(UseManualClock)]
+impl<Context> ClockService for Context
+where
+    Context: HasManualClock,
+{
+    fn now(context: &Context) -> SystemTime {
+        context.manual_clock().now()
+    }
+}
+
+#[cgp_impl(UseManualClock)]
+impl<Context> SleeperService for Context
+where
+    Context: HasManualClock,
+{
+    async fn sleep(context: &Context, duration: Duration) {
+        context.manual_clock().sleep(duration).await
+    }
+}
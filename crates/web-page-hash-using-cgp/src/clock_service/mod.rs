@@ -0,0 +1,8 @@
+pub mod interface;
+pub use interface::*;
+
+pub mod manual_clock;
+pub use manual_clock::*;
+
+pub mod tokio_clock;
+pub use tokio_clock::*;
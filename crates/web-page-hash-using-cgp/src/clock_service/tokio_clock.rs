@@ -0,0 +1,34 @@
+use super::interface::{
+    ClockService, ClockServiceComponent, SleeperService, SleeperServiceComponent,
+};
+use cgp::prelude::*;
+use std::sync::OnceLock;
+use std::time::{Duration, SystemTime};
+use tokio::time::Instant;
+
+/// A [ClockService], and [SleeperService] provider backed by the Tokio timer
+///
+/// The current time advances with the Tokio clock from the wall-clock time
+/// when the time was first told.  Both waiting, and the current time follow
+/// virtual time, when the Tokio clock is paused (see `tokio::time::pause`),
+/// e.g., in tests.
+pub struct UseTokioClock;
+
+#[cgp_impl(UseTokioClock)]
+impl<Context> ClockService for Context {
+    fn now(_context: &Context) -> SystemTime {
+        static ORIGIN: OnceLock<(SystemTime, Instant)> = OnceLock::new();
+
+        let (origin_time, origin_instant) =
+            ORIGIN.get_or_init(|| (SystemTime::now(), Instant::now()));
+
+        *origin_time + origin_instant.elapsed()
+    }
+}
+
+#[cgp_impl(UseTokioClock)]
+impl<Context> SleeperService for Context {
+    async fn sleep(_context: &Context, duration: Duration) {
+        tokio::time::sleep(duration).await
+    }
+}
@@ -0,0 +1,96 @@
+use super::interface::{
+    Credential, CredentialError, CredentialProviderService, CredentialProviderServiceComponent,
+};
+use super::netrc::Netrc;
+use cgp::prelude::*;
+use std::path::Path;
+
+/// The environment variable holding a `Bearer` token
+pub const BEARER_TOKEN_ENVIRONMENT_VARIABLE: &str = "WEB_PAGE_HASH_BEARER_TOKEN";
+/// The environment variable holding a `Basic` authentication user name
+pub const USERNAME_ENVIRONMENT_VARIABLE: &str = "WEB_PAGE_HASH_USERNAME";
+/// The environment variable holding a `Basic` authentication password
+pub const PASSWORD_ENVIRONMENT_VARIABLE: &str = "WEB_PAGE_HASH_PASSWORD";
+
+/// Credentials read by [ConfiguredCredentialProvider] from a context field
+#[derive(Clone, Debug, Default)]
+pub enum Credentials {
+    /// Requests are sent without credentials
+    #[default]
+    None,
+    /// The same credential is sent with all requests
+    Static(Credential),
+    /// Credentials are looked up by host name in a `.netrc` file
+    Netrc(Netrc),
+}
+
+impl Credentials {
+    /// Sends a `Bearer` token with all requests
+    pub fn bearer_token(token: &str) -> Result<Self, CredentialError> {
+        Ok(Credentials::Static(Credential::bearer(token)?))
+    }
+
+    /// Sends a user name, and an optional password with all requests using
+    /// `Basic` authentication
+    pub fn basic_auth(username: &str, password: Option<&str>) -> Self {
+        Credentials::Static(Credential::basic(username, password))
+    }
+
+    /// Looks up credentials in a `.netrc` file
+    pub fn from_netrc_file(path: &Path) -> Result<Self, CredentialError> {
+        Ok(Credentials::Netrc(Netrc::from_file(path)?))
+    }
+
+    /// Looks up credentials in the [Netrc::default_file_path] file, when it
+    /// exists
+    pub fn from_default_netrc_file() -> Result<Self, CredentialError> {
+        Ok(Credentials::Netrc(Netrc::from_default_file()?))
+    }
+
+    /// Reads credentials from environment variables
+    ///
+    /// A token in `WEB_PAGE_HASH_BEARER_TOKEN` takes precedence over a user
+    /// name in `WEB_PAGE_HASH_USERNAME`, and a password in
+    /// `WEB_PAGE_HASH_PASSWORD`.  Requests are sent without credentials
+    /// when neither is set.
+    pub fn from_environment() -> Result<Self, CredentialError> {
+        let variable = |name| std::env::var(name).ok().filter(|value| !value.is_empty());
+
+        if let Some(token) = variable(BEARER_TOKEN_ENVIRONMENT_VARIABLE) {
+            return Credentials::bearer_token(&token);
+        }
+
+        Ok(match variable(USERNAME_ENVIRONMENT_VARIABLE) {
+            Some(username) => Credentials::basic_auth(
+                &username,
+                variable(PASSWORD_ENVIRONMENT_VARIABLE).as_deref(),
+            ),
+            None => Credentials::None,
+        })
+    }
+}
+
+/// A context holding the credentials provided by
+/// [ConfiguredCredentialProvider]
+#[cgp_auto_getter]
+pub trait HasCredentials {
+    fn credentials(&self) -> &Credentials;
+}
+
+/// A [CredentialProviderService] provider reading [Credentials] from the
+/// context
+pub struct ConfiguredCredentialProvider;
+
+#[cgp_impl(ConfiguredCredentialProvider)]
+impl<Context> CredentialProviderService for Context
+where
+    Context: HasCredentials,
+{
+    fn look_up_credential(context: &Context, url: &str) -> Option<Credential> {
+        match context.credentials() {
+            Credentials::None => None,
+            Credentials::Static(credential) => Some(credential.clone()),
+            Credentials::Netrc(netrc) => netrc.look_up_credential(url),
+        }
+    }
+}
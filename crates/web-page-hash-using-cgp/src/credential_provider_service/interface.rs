@@ -0,0 +1,111 @@
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use cgp::prelude::*;
+use http::HeaderValue;
+use std::borrow::Cow;
+use std::fmt;
+use std::path::PathBuf;
+
+/// Credentials sent in the `Authorization` header of a request
+///
+/// The header value is marked as sensitive, and it's never formatted, so
+/// that it can't end up in traces, or error messages.
+#[derive(Clone)]
+pub struct Credential {
+    authorization: HeaderValue,
+}
+
+impl Credential {
+    /// Creates a `Bearer` token credential
+    pub fn bearer(token: &str) -> Result<Self, CredentialError> {
+        let authorization = HeaderValue::try_from(format!("Bearer {}", token))
+            .map_err(|_| CredentialError::InvalidToken)?;
+
+        Ok(Credential::from_authorization(authorization))
+    }
+
+    /// Creates a `Basic` credential
+    pub fn basic(username: &str, password: Option<&str>) -> Self {
+        let user_pass = format!("{}:{}", username, password.unwrap_or_default());
+        let authorization = HeaderValue::try_from(format!("Basic {}", STANDARD.encode(user_pass)))
+            .expect("base64 is a valid header value");
+
+        Credential::from_authorization(authorization)
+    }
+
+    fn from_authorization(mut authorization: HeaderValue) -> Self {
+        authorization.set_sensitive(true);
+
+        Credential { authorization }
+    }
+
+    /// Returns the `Authorization` header value
+    pub fn authorization(&self) -> &HeaderValue {
+        &self.authorization
+    }
+}
+
+impl fmt::Debug for Credential {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Credential(<redacted>)")
+    }
+}
+
+/// A component providing the credentials for requesting a URL
+#[cgp_component(CredentialProviderService)]
+pub trait LookUpCredential {
+    /// Returns [None] when the URL should be requested without credentials
+    fn look_up_credential(&self, url: &str) -> Option<Credential>;
+}
+
+/// Removes the user name, and password from a URL, so that it can be logged
+///
+/// Text that isn't a valid URL is returned as is.
+pub fn redact_url_credentials(url: &str) -> Cow<'_, str> {
+    match reqwest::Url::parse(url) {
+        Ok(mut parsed_url)
+            if !parsed_url.username().is_empty() || parsed_url.password().is_some() =>
+        {
+            let _ = parsed_url.set_username("");
+            let _ = parsed_url.set_password(None);
+
+            Cow::Owned(parsed_url.into())
+        }
+        _ => Cow::Borrowed(url),
+    }
+}
+
+/// An error reading credentials
+///
+/// Errors never include the credentials themselves.
+#[derive(Debug)]
+pub enum CredentialError {
+    InvalidToken,
+    ReadNetrc(PathBuf, std::io::Error),
+    InvalidNetrc(PathBuf, String),
+}
+
+impl fmt::Display for CredentialError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CredentialError::InvalidToken => {
+                write!(f, "the bearer token isn't a valid header value")
+            }
+            CredentialError::ReadNetrc(path, _) => {
+                write!(f, "could not read netrc file {}", path.display())
+            }
+            CredentialError::InvalidNetrc(path, message) => {
+                write!(f, "invalid netrc file {}: {}", path.display(), message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for CredentialError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            CredentialError::ReadNetrc(_, error) => Some(error),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,11 @@
+pub mod interface;
+pub use interface::*;
+
+pub mod configured_credential_provider;
+pub use configured_credential_provider::*;
+
+pub mod netrc;
+pub use netrc::*;
+
+pub mod no_credential_provider;
+pub use no_credential_provider::*;
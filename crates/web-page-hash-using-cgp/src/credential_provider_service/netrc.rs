@@ -0,0 +1,138 @@
+use super::interface::{Credential, CredentialError};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// The environment variable naming a `.netrc` file
+pub const NETRC_ENVIRONMENT_VARIABLE: &str = "NETRC";
+
+/// `Basic` authentication credentials by host name from a `.netrc` file
+///
+/// The `machine`, `default`, `login`, and `password` tokens are used.
+/// `account` tokens, and `macdef` macro definitions are skipped.
+#[derive(Clone)]
+pub struct Netrc {
+    entries: Vec<NetrcEntry>,
+}
+
+/// A `machine`, or `default` entry of a `.netrc` file
+#[derive(Clone, Default)]
+struct NetrcEntry {
+    /// [None] for the `default` entry
+    machine: Option<String>,
+    login: Option<String>,
+    password: Option<String>,
+}
+
+impl Netrc {
+    /// Parses a `.netrc` file
+    pub fn from_file(path: &Path) -> Result<Self, CredentialError> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|error| CredentialError::ReadNetrc(path.to_path_buf(), error))?;
+
+        Netrc::parse(&text)
+            .map_err(|message| CredentialError::InvalidNetrc(path.to_path_buf(), message))
+    }
+
+    /// Returns `$NETRC`, or `~/.netrc`
+    pub fn default_file_path() -> Option<PathBuf> {
+        std::env::var_os(NETRC_ENVIRONMENT_VARIABLE)
+            .filter(|path| !path.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".netrc")))
+    }
+
+    /// Parses the [Netrc::default_file_path] file, when
+    /// it exists, or else provides no credentials
+    pub fn from_default_file() -> Result<Self, CredentialError> {
+        match Netrc::default_file_path() {
+            Some(path) if path.is_file() => Netrc::from_file(&path),
+            _ => Ok(Netrc {
+                entries: Vec::new(),
+            }),
+        }
+    }
+
+    fn parse(text: &str) -> Result<Self, String> {
+        let mut entries = Vec::new();
+        let mut in_macro_definition = false;
+        let mut tokens = Vec::new();
+
+        for line in text.lines() {
+            // A macro definition ends with an empty line:
+            if in_macro_definition {
+                in_macro_definition = !line.trim().is_empty();
+                continue;
+            }
+            for token in line.split_whitespace() {
+                if token == "macdef" {
+                    in_macro_definition = true;
+                    break;
+                }
+                tokens.push(token);
+            }
+        }
+
+        let mut tokens = tokens.into_iter();
+        while let Some(token) = tokens.next() {
+            let mut value = || {
+                tokens
+                    .next()
+                    .ok_or_else(|| format!("missing value after `{}`", token))
+            };
+
+            match token {
+                "machine" => entries.push(NetrcEntry {
+                    machine: Some(value()?.to_string()),
+                    ..NetrcEntry::default()
+                }),
+                "default" => entries.push(NetrcEntry::default()),
+                "login" | "password" | "account" => {
+                    let value = value()?.to_string();
+                    let entry = entries
+                        .last_mut()
+                        .ok_or_else(|| format!("`{}` before `machine`, or `default`", token))?;
+                    match token {
+                        "login" => entry.login = Some(value),
+                        "password" => entry.password = Some(value),
+                        _ => {}
+                    }
+                }
+                _ => return Err(format!("unknown token `{}`", token)),
+            }
+        }
+
+        Ok(Netrc { entries })
+    }
+
+    /// Returns the credentials for the host of a URL, or else the `default`
+    /// ones
+    pub fn look_up_credential(&self, url: &str) -> Option<Credential> {
+        let url = reqwest::Url::parse(url).ok()?;
+        let host = url.host_str()?;
+        let entry = self
+            .entries
+            .iter()
+            .find(|entry| {
+                entry
+                    .machine
+                    .as_deref()
+                    .is_some_and(|machine| machine.eq_ignore_ascii_case(host))
+            })
+            .or_else(|| self.entries.iter().find(|entry| entry.machine.is_none()));
+
+        entry.and_then(|entry| {
+            entry
+                .login
+                .as_deref()
+                .map(|login| Credential::basic(login, entry.password.as_deref()))
+        })
+    }
+}
+
+impl fmt::Debug for Netrc {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Netrc")
+            .field("entry_count", &self.entries.len())
+            .finish_non_exhaustive()
+    }
+}
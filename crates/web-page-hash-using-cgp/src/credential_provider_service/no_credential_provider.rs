@@ -0,0 +1,12 @@
+use super::interface::{Credential, CredentialProviderService, CredentialProviderServiceComponent};
+use cgp::prelude::*;
+
+/// A [CredentialProviderService] provider that never provides credentials
+pub struct NoCredentialProvider;
+
+#[cgp_impl(NoCredentialProvider)]
+impl<Context> CredentialProviderService for Context {
+    fn look_up_credential(_context: &Context, _url: &str) -> Option<Credential> {
+        None
+    }
+}
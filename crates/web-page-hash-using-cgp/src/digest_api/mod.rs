@@ -0,0 +1,2 @@
+pub mod digest_response;
+pub use digest_response::*;
@@ -0,0 +1,64 @@
+use super::cassette::{Cassette, CassetteInteraction};
+use cgp::prelude::*;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// A [Cassette] read by `RecordingGetUrl`, and `ReplayGetUrl` providers from
+/// a context field, and the file it's saved to
+///
+/// The default is an empty cassette that isn't saved.
+#[derive(Clone, Debug, Default)]
+pub struct CassetteFile {
+    cassette: Arc<Mutex<Cassette>>,
+    path: Option<PathBuf>,
+}
+
+impl CassetteFile {
+    /// Opens an existing cassette file for replaying responses
+    pub fn open(path: &Path) -> io::Result<Self> {
+        Ok(CassetteFile {
+            cassette: Arc::new(Mutex::new(Cassette::load(path)?)),
+            path: Some(path.to_path_buf()),
+        })
+    }
+
+    /// Opens a cassette file for recording responses, which is created, if
+    /// it doesn't exist
+    pub fn open_or_create(path: &Path) -> io::Result<Self> {
+        Ok(CassetteFile {
+            cassette: Arc::new(Mutex::new(Cassette::load_or_default(path)?)),
+            path: Some(path.to_path_buf()),
+        })
+    }
+
+    /// Returns the recorded response for `url`, if there's one
+    pub fn interaction(&self, url: &str) -> Option<CassetteInteraction> {
+        self.lock().interaction(url).cloned()
+    }
+
+    /// Adds an interaction, replacing any recorded for the same URL, and
+    /// saves the cassette
+    pub fn record(&self, interaction: CassetteInteraction) -> io::Result<()> {
+        let mut cassette = self.lock();
+        cassette.record(interaction);
+
+        match &self.path {
+            Some(path) => cassette.save(path),
+            None => Ok(()),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Cassette> {
+        self.cassette
+            .lock()
+            .expect("the cassette lock isn't poisoned")
+    }
+}
+
+/// A context holding the cassette used by `RecordingGetUrl`, and
+/// `ReplayGetUrl` providers
+#[cgp_auto_getter]
+pub trait HasCassetteFile {
+    fn cassette_file(&self) -> &CassetteFile;
+}
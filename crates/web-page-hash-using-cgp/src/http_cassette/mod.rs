@@ -0,0 +1,5 @@
+pub mod cassette;
+pub use cassette::*;
+
+pub mod cassette_file;
+pub use cassette_file::*;
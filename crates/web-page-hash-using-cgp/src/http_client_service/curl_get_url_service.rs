@@ -0,0 +1,212 @@
+use super::interface::{
+    GetUrlService, GetUrlServiceComponent, HasRequestHeaders, HasUrlType, IntoChunkStream,
+    ResponseMetadata, authorized_request_headers,
+};
+use crate::configuration_service::HttpClientConfiguration;
+use crate::credential_provider_service::LookUpCredential;
+use bytes::Bytes;
+use cgp::prelude::*;
+use curl::easy::{Easy2, Handler, List, WriteError};
+use futures_core::stream::Stream;
+use http::header::{HeaderName, HeaderValue};
+use http::{HeaderMap, StatusCode};
+use std::fmt;
+use tokio::sync::{mpsc, oneshot};
+
+const CHUNK_CHANNEL_CAPACITY: usize = 16;
+const MAX_REDIRECTIONS: u32 = 10;
+
+/// An injectable HTTP client service implemented using the [curl] crate
+///
+/// Each request is performed by a `libcurl` easy handle on a thread dedicated
+/// to blocking operations.  The response is returned as soon as its headers
+/// are received, and its body is streamed while the transfer continues.
+pub struct CurlGetUrlService;
+
+#[cgp_impl(CurlGetUrlService)]
+impl<Context> GetUrlService for Context
+where
+    Context: HasUrlType + HttpClientConfiguration + HasRequestHeaders + LookUpCredential,
+    Context::Url: AsRef<str>,
+{
+    type HttpResponse = CurlHttpResponse;
+    type Error = CurlGetUrlError;
+
+    async fn get_url(
+        context: &Context,
+        url: &Context::Url,
+    ) -> Result<Self::HttpResponse, Self::Error> {
+        let mut header_list = List::new();
+        for (name, value) in &authorized_request_headers(context, url.as_ref()) {
+            let value = value.to_str().map_err(|_| CurlGetUrlError::InvalidHeader)?;
+            header_list.append(&format!("{}: {}", name, value))?;
+        }
+
+        let (head_sender, head_receiver) = oneshot::channel();
+        let (body_sender, body_receiver) = mpsc::channel(CHUNK_CHANNEL_CAPACITY);
+        let mut easy = Easy2::new(CurlHandler {
+            head: CurlResponseHead {
+                status: StatusCode::OK,
+                headers: HeaderMap::new(),
+            },
+            head_sender: Some(head_sender),
+            body_sender,
+        });
+        easy.url(url.as_ref())?;
+        easy.http_headers(header_list)?;
+        easy.follow_location(true)?;
+        easy.max_redirections(MAX_REDIRECTIONS)?;
+        easy.useragent(context.user_agent())?;
+        if let Some(connect_timeout) = context.connect_timeout() {
+            easy.connect_timeout(connect_timeout)?;
+        }
+        if let Some(timeout) = context.timeout() {
+            easy.timeout(timeout)?;
+        }
+        if let Some(proxy) = context.proxy() {
+            easy.proxy(proxy)?;
+        }
+
+        tokio::task::spawn_blocking(move || {
+            let perform_result = easy.perform();
+            let handler = easy.get_mut();
+
+            match (perform_result, handler.head_sender.take()) {
+                (Ok(()), Some(head_sender)) => {
+                    // An empty body:
+                    let _ = head_sender.send(Ok(handler.head.clone()));
+                }
+                (Err(error), Some(head_sender)) => {
+                    let _ = head_sender.send(Err(CurlGetUrlError::Curl(error)));
+                }
+                // A write error means that the body stream was dropped:
+                (Err(error), None) if !error.is_write_error() => {
+                    let _ = handler
+                        .body_sender
+                        .blocking_send(Err(CurlGetUrlError::Curl(error)));
+                }
+                _ => {}
+            }
+        });
+
+        let head = head_receiver
+            .await
+            .map_err(|_| CurlGetUrlError::Aborted)??;
+
+        Ok(CurlHttpResponse {
+            head,
+            body_receiver,
+        })
+    }
+}
+
+/// A response returned by [CurlGetUrlService]
+#[derive(Debug)]
+pub struct CurlHttpResponse {
+    head: CurlResponseHead,
+    body_receiver: mpsc::Receiver<Result<Bytes, CurlGetUrlError>>,
+}
+
+impl ResponseMetadata for CurlHttpResponse {
+    fn status(&self) -> StatusCode {
+        self.head.status
+    }
+
+    fn headers(&self) -> &HeaderMap {
+        &self.head.headers
+    }
+}
+
+impl IntoChunkStream for CurlHttpResponse {
+    type Error = CurlGetUrlError;
+
+    fn into_chunk_stream(self) -> impl Stream<Item = Result<Bytes, Self::Error>> + Unpin {
+        let mut body_receiver = self.body_receiver;
+
+        futures_util::stream::poll_fn(move |context| body_receiver.poll_recv(context))
+    }
+}
+
+#[derive(Clone, Debug)]
+struct CurlResponseHead {
+    status: StatusCode,
+    headers: HeaderMap,
+}
+
+struct CurlHandler {
+    head: CurlResponseHead,
+    /// Sends the head of the last response, when its body starts
+    head_sender: Option<oneshot::Sender<Result<CurlResponseHead, CurlGetUrlError>>>,
+    body_sender: mpsc::Sender<Result<Bytes, CurlGetUrlError>>,
+}
+
+impl Handler for CurlHandler {
+    fn header(&mut self, data: &[u8]) -> bool {
+        let line = String::from_utf8_lossy(data);
+        let line = line.trim_end();
+
+        if let Some(status_line) = line.strip_prefix("HTTP/") {
+            // The status line of a new response, e.g., after a redirect:
+            let status_code = status_line.split_whitespace().nth(1).unwrap_or_default();
+            self.head.status = StatusCode::from_bytes(status_code.as_bytes())
+                .unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+            self.head.headers.clear();
+        } else if let Some((name, value)) = line.split_once(':')
+            && let (Ok(name), Ok(value)) = (
+                HeaderName::from_bytes(name.trim().as_bytes()),
+                HeaderValue::from_str(value.trim()),
+            )
+        {
+            self.head.headers.append(name, value);
+        }
+
+        true
+    }
+
+    fn write(&mut self, data: &[u8]) -> Result<usize, WriteError> {
+        if let Some(head_sender) = self.head_sender.take() {
+            let _ = head_sender.send(Ok(self.head.clone()));
+        }
+
+        match self
+            .body_sender
+            .blocking_send(Ok(Bytes::copy_from_slice(data)))
+        {
+            Ok(()) => Ok(data.len()),
+            // Writing less than `data.len()` aborts the transfer:
+            Err(_) => Ok(0),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum CurlGetUrlError {
+    Curl(curl::Error),
+    Aborted,
+    InvalidHeader,
+}
+
+impl From<curl::Error> for CurlGetUrlError {
+    fn from(error: curl::Error) -> Self {
+        CurlGetUrlError::Curl(error)
+    }
+}
+
+impl fmt::Display for CurlGetUrlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CurlGetUrlError::Curl(error) => write!(f, "HTTP request failed: {}", error),
+            CurlGetUrlError::Aborted => write!(f, "HTTP request was aborted"),
+            CurlGetUrlError::InvalidHeader => write!(f, "request header value isn't text"),
+        }
+    }
+}
+
+impl std::error::Error for CurlGetUrlError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            CurlGetUrlError::Curl(error) => Some(error),
+            CurlGetUrlError::Aborted | CurlGetUrlError::InvalidHeader => None,
+        }
+    }
+}
@@ -0,0 +1,171 @@
+use super::interface::{
+    GetUrlService, GetUrlServiceComponent, HasRequestHeaders, HasUrlType, IntoChunkStream,
+    ResponseMetadata, authorized_request_headers,
+};
+use crate::clock_service::Sleeper;
+use crate::configuration_service::HttpClientConfiguration;
+use crate::credential_provider_service::LookUpCredential;
+use bytes::Bytes;
+use cgp::prelude::*;
+use futures_core::stream::Stream;
+use futures_util::StreamExt;
+use futures_util::future::{self, Either};
+use http::{HeaderMap, StatusCode};
+use http_body_util::{BodyStream, Empty};
+use hyper::body::{Body, Incoming};
+use hyper_rustls::{HttpsConnector, HttpsConnectorBuilder};
+use hyper_util::client::legacy::{Client, connect::HttpConnector};
+use hyper_util::rt::TokioExecutor;
+use std::fmt;
+use std::pin::pin;
+use std::sync::OnceLock;
+
+/// A [hyper] client establishing TLS connections with [rustls]
+pub type HyperClient = Client<HttpsConnector<HttpConnector>, Empty<Bytes>>;
+
+/// A getter of the [HyperClient] of a context, which [HyperGetUrlService]
+/// builds on the first request, so that connections are reused by later
+/// requests
+#[cgp_auto_getter]
+pub trait HasHyperClient {
+    fn hyper_client(&self) -> &OnceLock<HyperClient>;
+}
+
+/// An injectable HTTP client service implemented using the [hyper] crate
+///
+/// Redirects are not followed.  A redirect response is returned as is.  A
+/// configured timeout, waited for by the [Sleeper] of the context, limits the
+/// time until the response headers are received, and proxies aren't
+/// supported.  TLS connections are established with [rustls], trusting the
+/// Mozilla root certificates.
+pub struct HyperGetUrlService;
+
+#[cgp_impl(HyperGetUrlService)]
+impl<Context> GetUrlService for Context
+where
+    Context: HasUrlType
+        + HttpClientConfiguration
+        + HasRequestHeaders
+        + LookUpCredential
+        + Sleeper
+        + HasHyperClient,
+    Context::Url: AsRef<str>,
+{
+    type HttpResponse = http::Response<Incoming>;
+    type Error = HyperGetUrlError;
+
+    async fn get_url(
+        context: &Context,
+        url: &Context::Url,
+    ) -> Result<Self::HttpResponse, Self::Error> {
+        if context.proxy().is_some() {
+            return Err(HyperGetUrlError::ProxyUnsupported);
+        }
+        let client = match context.hyper_client().get() {
+            Some(client) => client,
+            None => {
+                let client = new_hyper_client(context)?;
+                context.hyper_client().get_or_init(|| client)
+            }
+        };
+        let mut request = http::Request::get(url.as_ref())
+            .header(http::header::USER_AGENT, context.user_agent())
+            .body(Empty::<Bytes>::new())
+            .map_err(HyperGetUrlError::Request)?;
+        request
+            .headers_mut()
+            .extend(authorized_request_headers(context, url.as_ref()));
+        let response_future = client.request(request);
+
+        match context.timeout() {
+            Some(timeout) => {
+                match future::select(pin!(response_future), pin!(context.sleep(timeout))).await {
+                    Either::Left((response_result, _)) => response_result,
+                    Either::Right(_) => return Err(HyperGetUrlError::TimedOut),
+                }
+            }
+            None => response_future.await,
+        }
+        .map_err(HyperGetUrlError::Client)
+    }
+}
+
+/// Returns a client connecting with the configured timeout
+fn new_hyper_client(
+    configuration: &impl HttpClientConfiguration,
+) -> Result<HyperClient, HyperGetUrlError> {
+    let mut http_connector = HttpConnector::new();
+    // HTTPS URLs are handled by the wrapping `HttpsConnector`:
+    http_connector.enforce_http(false);
+    http_connector.set_connect_timeout(configuration.connect_timeout());
+    let https_connector = HttpsConnectorBuilder::new()
+        .with_provider_and_webpki_roots(rustls::crypto::ring::default_provider())
+        .map_err(HyperGetUrlError::Tls)?
+        .https_or_http()
+        .enable_http1()
+        .wrap_connector(http_connector);
+
+    Ok(Client::builder(TokioExecutor::new()).build(https_connector))
+}
+
+impl IntoChunkStream for http::Response<Incoming> {
+    type Error = hyper::Error;
+
+    fn into_chunk_stream(self) -> impl Stream<Item = Result<Bytes, Self::Error>> + Unpin {
+        // Skip non-data frames, such as trailers:
+        BodyStream::new(self.into_body()).filter_map(|frame_result| {
+            future::ready(match frame_result {
+                Ok(frame) => frame.into_data().ok().map(Ok),
+                Err(error) => Some(Err(error)),
+            })
+        })
+    }
+}
+
+impl ResponseMetadata for http::Response<Incoming> {
+    fn status(&self) -> StatusCode {
+        http::Response::status(self)
+    }
+
+    fn headers(&self) -> &HeaderMap {
+        http::Response::headers(self)
+    }
+
+    fn content_length(&self) -> Option<u64> {
+        self.body().size_hint().exact()
+    }
+}
+
+#[derive(Debug)]
+pub enum HyperGetUrlError {
+    Request(http::Error),
+    Tls(rustls::Error),
+    Client(hyper_util::client::legacy::Error),
+    TimedOut,
+    ProxyUnsupported,
+}
+
+impl fmt::Display for HyperGetUrlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HyperGetUrlError::Request(error) => write!(f, "invalid HTTP request: {}", error),
+            HyperGetUrlError::Tls(error) => write!(f, "invalid TLS configuration: {}", error),
+            HyperGetUrlError::Client(error) => write!(f, "HTTP request failed: {}", error),
+            HyperGetUrlError::TimedOut => write!(f, "HTTP request timed out"),
+            HyperGetUrlError::ProxyUnsupported => {
+                write!(f, "proxies aren't supported by the hyper HTTP client")
+            }
+        }
+    }
+}
+
+impl std::error::Error for HyperGetUrlError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            HyperGetUrlError::Request(error) => Some(error),
+            HyperGetUrlError::Tls(error) => Some(error),
+            HyperGetUrlError::Client(error) => Some(error),
+            _ => None,
+        }
+    }
+}
@@ -1,6 +1,9 @@
+use crate::credential_provider_service::LookUpCredential;
 use bytes::Bytes;
 use cgp::prelude::*;
 use futures_core::stream::Stream;
+use http::header::AUTHORIZATION;
+use http::{HeaderMap, StatusCode};
 
 pub trait IntoChunkStream {
     type Error;
@@ -8,11 +11,60 @@ pub trait IntoChunkStream {
     fn into_chunk_stream(self) -> impl Stream<Item = Result<Bytes, Self::Error>> + Unpin;
 }
 
+/// The status, and headers of a response, available before its body is
+/// streamed
+pub trait ResponseMetadata {
+    fn status(&self) -> StatusCode;
+
+    fn headers(&self) -> &HeaderMap;
+
+    /// Returns the `Content-Type` header value
+    fn content_type(&self) -> Option<&str> {
+        self.headers()
+            .get(http::header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+    }
+
+    /// Returns the length of the response body, if known
+    fn content_length(&self) -> Option<u64> {
+        self.headers()
+            .get(http::header::CONTENT_LENGTH)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.parse().ok())
+    }
+}
+
 #[cgp_type]
 pub trait HasUrlType {
     type Url;
 }
 
+/// A context holding additional headers sent by `GetUrlService` providers
+#[cgp_auto_getter]
+pub trait HasRequestHeaders {
+    fn request_headers(&self) -> &HeaderMap;
+}
+
+/// Returns the additional request headers of a context, and the
+/// `Authorization` header looked up by its credential provider
+///
+/// An `Authorization` header among the additional headers takes precedence
+/// over looked up credentials.
+pub fn authorized_request_headers<Context>(context: &Context, url: &str) -> HeaderMap
+where
+    Context: HasRequestHeaders + LookUpCredential,
+{
+    let mut request_headers = context.request_headers().clone();
+
+    if !request_headers.contains_key(AUTHORIZATION)
+        && let Some(credential) = context.look_up_credential(url)
+    {
+        request_headers.insert(AUTHORIZATION, credential.authorization().clone());
+    }
+
+    request_headers
+}
+
 /// A function trait providing a `get_url` function for [HttpClientService]
 #[cgp_component(GetUrlService)]
 pub trait GetUrl: HasUrlType {
@@ -1,5 +1,26 @@
 pub mod interface;
 pub use interface::*;
 
+#[cfg(feature = "curl")]
+pub mod curl_get_url_service;
+#[cfg(feature = "curl")]
+pub use curl_get_url_service::*;
+
+#[cfg(feature = "hyper")]
+pub mod hyper_get_url_service;
+#[cfg(feature = "hyper")]
+pub use hyper_get_url_service::*;
+
+pub mod recording_get_url_service;
+pub use recording_get_url_service::*;
+
+pub mod replay_get_url_service;
+pub use replay_get_url_service::*;
+
 pub mod reqwest_http_client_service;
 pub use reqwest_http_client_service::*;
+
+#[cfg(feature = "ureq")]
+pub mod ureq_get_url_service;
+#[cfg(feature = "ureq")]
+pub use ureq_get_url_service::*;
@@ -0,0 +1,199 @@
+use super::interface::{
+    GetUrlService, GetUrlServiceComponent, HasUrlType, IntoChunkStream, ResponseMetadata,
+};
+use crate::credential_provider_service::redact_url_credentials;
+use crate::http_cassette::{CassetteFile, CassetteInteraction, HasCassetteFile};
+use bytes::Bytes;
+use cgp::prelude::*;
+use futures_core::stream::Stream;
+use futures_util::StreamExt;
+use http::header::{AUTHORIZATION, COOKIE, HeaderName, PROXY_AUTHORIZATION, SET_COOKIE};
+use http::{HeaderMap, StatusCode};
+use std::fmt;
+use std::io;
+use std::marker::PhantomData;
+use std::pin::Pin;
+use std::task::{Context as TaskContext, Poll};
+
+/// The response headers that may carry credentials, e.g., session cookies,
+/// which aren't recorded
+const UNRECORDED_HEADERS: [HeaderName; 5] = [
+    SET_COOKIE,
+    HeaderName::from_static("set-cookie2"),
+    COOKIE,
+    AUTHORIZATION,
+    PROXY_AUTHORIZATION,
+];
+
+/// A provider decorating a `GetUrlService` provider by recording responses
+/// into the [CassetteFile] of the context
+///
+/// It's bound in place of the decorated provider, e.g.,
+/// `GetUrlServiceComponent: RecordingGetUrl<ReqwestHttpClientService>`.  A
+/// response is recorded, and the cassette file is written, once its body
+/// has been fully streamed.  URLs are recorded without credentials, and
+/// responses without headers that may carry them, such as `Set-Cookie`.
+pub struct RecordingGetUrl<InnerProvider>(pub PhantomData<InnerProvider>);
+
+#[cgp_impl(RecordingGetUrl<InnerProvider>)]
+impl<Context, InnerProvider> GetUrlService for Context
+where
+    Context: HasUrlType + HasCassetteFile,
+    Context::Url: AsRef<str>,
+    InnerProvider: GetUrlService<Context>,
+    InnerProvider::HttpResponse: ResponseMetadata,
+{
+    type HttpResponse = RecordingHttpResponse<InnerProvider::HttpResponse>;
+    type Error = InnerProvider::Error;
+
+    async fn get_url(
+        context: &Context,
+        url: &Context::Url,
+    ) -> Result<Self::HttpResponse, Self::Error> {
+        let response = InnerProvider::get_url(context, url).await?;
+        let recorder = CassetteRecorder {
+            cassette_file: context.cassette_file().clone(),
+            url: redact_url_credentials(url.as_ref()).into_owned(),
+            status: response.status(),
+            headers: recorded_headers(response.headers()),
+            chunks: Vec::new(),
+        };
+
+        Ok(RecordingHttpResponse { response, recorder })
+    }
+}
+
+/// A response returned by a [RecordingGetUrl] provider
+#[derive(Debug)]
+pub struct RecordingHttpResponse<HttpResponse> {
+    response: HttpResponse,
+    recorder: CassetteRecorder,
+}
+
+impl<HttpResponse: ResponseMetadata> ResponseMetadata for RecordingHttpResponse<HttpResponse> {
+    fn status(&self) -> StatusCode {
+        self.response.status()
+    }
+
+    fn headers(&self) -> &HeaderMap {
+        self.response.headers()
+    }
+
+    fn content_type(&self) -> Option<&str> {
+        self.response.content_type()
+    }
+
+    fn content_length(&self) -> Option<u64> {
+        self.response.content_length()
+    }
+}
+
+impl<HttpResponse: IntoChunkStream> IntoChunkStream for RecordingHttpResponse<HttpResponse> {
+    type Error = RecordingGetUrlError<HttpResponse::Error>;
+
+    fn into_chunk_stream(self) -> impl Stream<Item = Result<Bytes, Self::Error>> + Unpin {
+        RecordingChunkStream {
+            chunk_stream: self.response.into_chunk_stream(),
+            recorder: Some(self.recorder),
+        }
+    }
+}
+
+/// Returns the response headers without the [UNRECORDED_HEADERS]
+fn recorded_headers(headers: &HeaderMap) -> HeaderMap {
+    let mut recorded_headers = headers.clone();
+    for name in &UNRECORDED_HEADERS {
+        recorded_headers.remove(name);
+    }
+
+    recorded_headers
+}
+
+/// Collects the chunks of a response body, and records the response once
+/// the body is complete
+#[derive(Debug)]
+struct CassetteRecorder {
+    cassette_file: CassetteFile,
+    url: String,
+    status: StatusCode,
+    headers: HeaderMap,
+    chunks: Vec<Bytes>,
+}
+
+impl CassetteRecorder {
+    fn commit(self) -> io::Result<()> {
+        self.cassette_file.record(CassetteInteraction::new(
+            self.url,
+            self.status,
+            self.headers,
+            self.chunks,
+        ))
+    }
+}
+
+struct RecordingChunkStream<ChunkStream> {
+    chunk_stream: ChunkStream,
+    /// [None] after the body is recorded, or fails
+    recorder: Option<CassetteRecorder>,
+}
+
+impl<ChunkStream, Error> Stream for RecordingChunkStream<ChunkStream>
+where
+    ChunkStream: Stream<Item = Result<Bytes, Error>> + Unpin,
+{
+    type Item = Result<Bytes, RecordingGetUrlError<Error>>;
+
+    fn poll_next(self: Pin<&mut Self>, context: &mut TaskContext<'_>) -> Poll<Option<Self::Item>> {
+        let recording_chunk_stream = self.get_mut();
+
+        match recording_chunk_stream.chunk_stream.poll_next_unpin(context) {
+            Poll::Ready(Some(Ok(chunk))) => {
+                if let Some(recorder) = &mut recording_chunk_stream.recorder {
+                    recorder.chunks.push(chunk.clone());
+                }
+                Poll::Ready(Some(Ok(chunk)))
+            }
+            Poll::Ready(Some(Err(error))) => {
+                // An incomplete body isn't recorded:
+                recording_chunk_stream.recorder.take();
+                Poll::Ready(Some(Err(RecordingGetUrlError::Http(error))))
+            }
+            Poll::Ready(None) => match recording_chunk_stream
+                .recorder
+                .take()
+                .map(CassetteRecorder::commit)
+            {
+                Some(Err(error)) => Poll::Ready(Some(Err(RecordingGetUrlError::Io(error)))),
+                _ => Poll::Ready(None),
+            },
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+/// An error streaming a response body, or writing the cassette file
+#[derive(Debug)]
+pub enum RecordingGetUrlError<HttpError> {
+    Http(HttpError),
+    Io(io::Error),
+}
+
+impl<HttpError: fmt::Display> fmt::Display for RecordingGetUrlError<HttpError> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RecordingGetUrlError::Http(error) => error.fmt(f),
+            RecordingGetUrlError::Io(error) => {
+                write!(f, "could not write the cassette file: {}", error)
+            }
+        }
+    }
+}
+
+impl<HttpError: std::error::Error + 'static> std::error::Error for RecordingGetUrlError<HttpError> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            RecordingGetUrlError::Http(error) => Some(error),
+            RecordingGetUrlError::Io(error) => Some(error),
+        }
+    }
+}
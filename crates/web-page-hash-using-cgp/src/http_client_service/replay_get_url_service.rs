@@ -0,0 +1,85 @@
+use super::interface::{
+    GetUrlService, GetUrlServiceComponent, HasUrlType, IntoChunkStream, ResponseMetadata,
+};
+use crate::credential_provider_service::redact_url_credentials;
+use crate::http_cassette::{CassetteInteraction, HasCassetteFile};
+use bytes::Bytes;
+use cgp::prelude::*;
+use futures_core::stream::Stream;
+use http::{HeaderMap, StatusCode};
+use std::convert::Infallible;
+use std::fmt;
+
+/// A `GetUrlService` provider serving responses recorded by a
+/// [super::RecordingGetUrl] provider from the
+/// [crate::http_cassette::CassetteFile] of the context, without sending
+/// requests
+///
+/// Response bodies are streamed in the chunks they were received in.
+/// Requesting a URL that wasn't recorded fails.
+pub struct ReplayGetUrl;
+
+#[cgp_impl(ReplayGetUrl)]
+impl<Context> GetUrlService for Context
+where
+    Context: HasUrlType + HasCassetteFile,
+    Context::Url: AsRef<str>,
+{
+    type HttpResponse = ReplayHttpResponse;
+    type Error = ReplayGetUrlError;
+
+    async fn get_url(
+        context: &Context,
+        url: &Context::Url,
+    ) -> Result<Self::HttpResponse, Self::Error> {
+        let url = redact_url_credentials(url.as_ref());
+
+        context
+            .cassette_file()
+            .interaction(&url)
+            .map(|interaction| ReplayHttpResponse { interaction })
+            .ok_or_else(|| ReplayGetUrlError::NotRecorded(url.into_owned()))
+    }
+}
+
+/// A response returned by [ReplayGetUrl]
+#[derive(Debug)]
+pub struct ReplayHttpResponse {
+    interaction: CassetteInteraction,
+}
+
+impl ResponseMetadata for ReplayHttpResponse {
+    fn status(&self) -> StatusCode {
+        self.interaction.status()
+    }
+
+    fn headers(&self) -> &HeaderMap {
+        self.interaction.headers()
+    }
+}
+
+impl IntoChunkStream for ReplayHttpResponse {
+    type Error = Infallible;
+
+    fn into_chunk_stream(self) -> impl Stream<Item = Result<Bytes, Self::Error>> + Unpin {
+        futures_util::stream::iter(self.interaction.chunks().to_vec().into_iter().map(Ok))
+    }
+}
+
+#[derive(Debug)]
+pub enum ReplayGetUrlError {
+    /// The cassette has no response for the URL
+    NotRecorded(String),
+}
+
+impl fmt::Display for ReplayGetUrlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReplayGetUrlError::NotRecorded(url) => {
+                write!(f, "no response is recorded for {}", url)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ReplayGetUrlError {}
@@ -1,8 +1,23 @@
-use super::interface::{GetUrlService, GetUrlServiceComponent, HasUrlType, IntoChunkStream};
+use super::interface::{
+    GetUrlService, GetUrlServiceComponent, HasRequestHeaders, HasUrlType, IntoChunkStream,
+    ResponseMetadata, authorized_request_headers,
+};
+use crate::configuration_service::HttpClientConfiguration;
+use crate::credential_provider_service::LookUpCredential;
 use bytes::Bytes;
 use cgp::prelude::*;
 use futures_core::stream::Stream;
+use http::{HeaderMap, StatusCode};
 use reqwest;
+use std::sync::OnceLock;
+
+/// A getter of the [reqwest::Client] of a context, which
+/// [ReqwestHttpClientService] builds on the first request, so that
+/// connections are reused by later requests
+#[cgp_auto_getter]
+pub trait HasReqwestClient {
+    fn reqwest_client(&self) -> &OnceLock<reqwest::Client>;
+}
 
 /// An injectable HTTP client service implemented using the [reqwest] crate
 #[derive(Debug, Default)]
@@ -21,19 +36,50 @@ impl HasUrlType for ReqwestHttpClientService {
 #[cgp_impl(ReqwestHttpClientService)]
 impl<Context> GetUrlService for Context
 where
-    Context: HasUrlType,
-    Context::Url: reqwest::IntoUrl + Clone,
+    Context: HasUrlType
+        + HttpClientConfiguration
+        + HasRequestHeaders
+        + LookUpCredential
+        + HasReqwestClient,
+    Context::Url: reqwest::IntoUrl + AsRef<str> + Clone,
 {
     type HttpResponse = reqwest::Response;
     type Error = reqwest::Error;
 
     async fn get_url(
-        _context: &Context,
+        context: &Context,
         url: &Context::Url,
     ) -> Result<Self::HttpResponse, Self::Error> {
+        let client = match context.reqwest_client().get() {
+            Some(client) => client,
+            None => {
+                let client = new_reqwest_client(context)?;
+                context.reqwest_client().get_or_init(|| client)
+            }
+        };
+        let headers = authorized_request_headers(context, url.as_ref());
         let url: Context::Url = url.clone();
-        reqwest::get(url).await
+
+        client.get(url).headers(headers).send().await
+    }
+}
+
+/// Returns a client with the configured timeouts, user agent, and proxy
+fn new_reqwest_client(
+    configuration: &impl HttpClientConfiguration,
+) -> Result<reqwest::Client, reqwest::Error> {
+    let mut client_builder = reqwest::Client::builder().user_agent(configuration.user_agent());
+    if let Some(connect_timeout) = configuration.connect_timeout() {
+        client_builder = client_builder.connect_timeout(connect_timeout);
+    }
+    if let Some(timeout) = configuration.timeout() {
+        client_builder = client_builder.timeout(timeout);
+    }
+    if let Some(proxy) = configuration.proxy() {
+        client_builder = client_builder.proxy(reqwest::Proxy::all(proxy)?);
     }
+
+    client_builder.build()
 }
 
 impl IntoChunkStream for reqwest::Response {
@@ -43,3 +89,17 @@ impl IntoChunkStream for reqwest::Response {
         reqwest::Response::bytes_stream(self)
     }
 }
+
+impl ResponseMetadata for reqwest::Response {
+    fn status(&self) -> StatusCode {
+        reqwest::Response::status(self)
+    }
+
+    fn headers(&self) -> &HeaderMap {
+        reqwest::Response::headers(self)
+    }
+
+    fn content_length(&self) -> Option<u64> {
+        reqwest::Response::content_length(self)
+    }
+}
@@ -0,0 +1,130 @@
+use super::interface::{
+    GetUrlService, GetUrlServiceComponent, HasRequestHeaders, HasUrlType, IntoChunkStream,
+    ResponseMetadata, authorized_request_headers,
+};
+use crate::configuration_service::HttpClientConfiguration;
+use crate::credential_provider_service::LookUpCredential;
+use bytes::Bytes;
+use cgp::prelude::*;
+use futures_core::stream::Stream;
+use http::{HeaderMap, StatusCode};
+use std::io::{self, Read};
+use std::sync::OnceLock;
+
+const CHUNK_SIZE: usize = 64 * 1024;
+const CHUNK_CHANNEL_CAPACITY: usize = 4;
+
+/// A getter of the [ureq::Agent] of a context, which [UreqGetUrlService]
+/// builds on the first request, so that connections are reused by later
+/// requests
+#[cgp_auto_getter]
+pub trait HasUreqAgent {
+    fn ureq_agent(&self) -> &OnceLock<ureq::Agent>;
+}
+
+/// An injectable HTTP client service implemented using the blocking [ureq]
+/// crate
+///
+/// Blocking calls are made on threads dedicated to blocking operations with
+/// [tokio::task::spawn_blocking].
+pub struct UreqGetUrlService;
+
+#[cgp_impl(UreqGetUrlService)]
+impl<Context> GetUrlService for Context
+where
+    Context:
+        HasUrlType + HttpClientConfiguration + HasRequestHeaders + LookUpCredential + HasUreqAgent,
+    Context::Url: AsRef<str>,
+{
+    type HttpResponse = http::Response<ureq::Body>;
+    type Error = ureq::Error;
+
+    async fn get_url(
+        context: &Context,
+        url: &Context::Url,
+    ) -> Result<Self::HttpResponse, Self::Error> {
+        let headers = authorized_request_headers(context, url.as_ref());
+        let url = url.as_ref().to_string();
+        // Agents share their connection pool with their clones:
+        let agent = match context.ureq_agent().get() {
+            Some(agent) => agent.clone(),
+            None => {
+                let agent = new_ureq_agent(context)?;
+                context.ureq_agent().get_or_init(|| agent).clone()
+            }
+        };
+
+        tokio::task::spawn_blocking(move || {
+            let mut request = agent.get(&url);
+            for (name, value) in &headers {
+                request = request.header(name, value);
+            }
+
+            request.call()
+        })
+        .await
+        .map_err(|join_error| ureq::Error::Io(io::Error::other(join_error)))?
+    }
+}
+
+/// Returns an agent with the configured timeouts, user agent, and proxy
+fn new_ureq_agent(
+    configuration: &impl HttpClientConfiguration,
+) -> Result<ureq::Agent, ureq::Error> {
+    let proxy = configuration.proxy().map(ureq::Proxy::new).transpose()?;
+    let config = ureq::Agent::config_builder()
+        // Error statuses are returned as responses, like other services do:
+        .http_status_as_error(false)
+        .timeout_connect(configuration.connect_timeout())
+        .timeout_global(configuration.timeout())
+        .user_agent(configuration.user_agent())
+        .proxy(proxy)
+        .build();
+
+    Ok(ureq::Agent::new_with_config(config))
+}
+
+impl IntoChunkStream for http::Response<ureq::Body> {
+    type Error = io::Error;
+
+    fn into_chunk_stream(self) -> impl Stream<Item = Result<Bytes, Self::Error>> + Unpin {
+        let (sender, mut receiver) = tokio::sync::mpsc::channel(CHUNK_CHANNEL_CAPACITY);
+        let mut body_reader = self.into_body().into_reader();
+
+        tokio::task::spawn_blocking(move || {
+            loop {
+                let mut chunk = vec![0; CHUNK_SIZE];
+                let chunk_result = match body_reader.read(&mut chunk) {
+                    Ok(0) => break,
+                    Ok(length) => {
+                        chunk.truncate(length);
+                        Ok(Bytes::from(chunk))
+                    }
+                    Err(error) => Err(error),
+                };
+                let is_error = chunk_result.is_err();
+
+                // Stop reading if the stream was dropped:
+                if sender.blocking_send(chunk_result).is_err() || is_error {
+                    break;
+                }
+            }
+        });
+
+        futures_util::stream::poll_fn(move |context| receiver.poll_recv(context))
+    }
+}
+
+impl ResponseMetadata for http::Response<ureq::Body> {
+    fn status(&self) -> StatusCode {
+        http::Response::status(self)
+    }
+
+    fn headers(&self) -> &HeaderMap {
+        http::Response::headers(self)
+    }
+
+    fn content_length(&self) -> Option<u64> {
+        self.body().content_length()
+    }
+}
@@ -1,52 +1,490 @@
+use anyhow::Context as _;
+use axum::extract::rejection::{JsonRejection, QueryRejection};
+use axum::extract::{Json, Query, State};
+use axum::routing::get;
 use cgp::core::error::ErrorTypeProviderComponent;
 use cgp::prelude::*;
 use cgp_error_anyhow::UseAnyhowError;
-use clap::Parser;
+use clap::{Args as ClapArgs, Parser, Subcommand, ValueEnum};
+use futures_util::StreamExt;
+use http::{HeaderMap, HeaderName, HeaderValue};
+use indicatif::ProgressBar;
+use std::marker::PhantomData;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+use tokio::sync::Semaphore;
+use tracing_subscriber::EnvFilter;
 use web_page_hash_using_cgp::{
     calculate_web_page_message_digest_service::{
         CalculateWebPageMessageDigest, CalculateWebPageMessageDigestServiceComponent,
-        CalculateWebPageMessageDigestServiceObject, DigestTypeProviderComponent,
+        CalculateWebPageMessageDigestServiceObject, DigestTypeProviderComponent, HasDigestType,
     },
+    clock_service::{ClockServiceComponent, SleeperServiceComponent, UseTokioClock},
+    configuration_service::{
+        ConfigurationLayer, HttpClientConfigurationServiceComponent, LayeredConfiguration,
+        LayeredConfigurationService, ResponsePolicyConfigurationServiceComponent,
+    },
+    credential_provider_service::{
+        ConfiguredCredentialProvider, CredentialError, CredentialProviderServiceComponent,
+        Credentials,
+    },
+    digest_api::{DigestError, DigestErrorKind, DigestQuery, DigestResponse},
+    http_cassette::CassetteFile,
     http_client_service::{
-        GetUrlServiceComponent, ReqwestHttpClientService, UrlTypeProviderComponent,
+        GetUrlServiceComponent, HasUrlType, RecordingGetUrl, ReplayGetUrl,
+        ReqwestHttpClientService, UrlTypeProviderComponent,
     },
     message_digest_service::{
         NewDigestCalculatorServiceComponent, Sha3_256BitMessageDigestService,
     },
+    progress_reporter_service::{
+        ByteCountingProgressReporter, IndicatifProgressReporter, NoProgressReporter, ProgressEvent,
+        ProgressReporterServiceComponent, RecordingProgressReporter,
+    },
+    response_policy_service::{ConfiguredResponsePolicy, ResponsePolicyServiceComponent},
+    tracing_decorator::Traced,
 };
 
+#[cfg(feature = "curl")]
+use web_page_hash_using_cgp::http_client_service::CurlGetUrlService;
+#[cfg(feature = "ureq")]
+use web_page_hash_using_cgp::http_client_service::UreqGetUrlService;
+#[cfg(feature = "hyper")]
+use web_page_hash_using_cgp::http_client_service::{HyperClient, HyperGetUrlService};
+
 /// Prints the 256-bit SHA-3 message digest of a Web page
 ///
 /// This is a tiny demo app using the Context-Generic Programming design
 /// option for Depedency Injection in Rust.
 #[derive(Debug, Parser)]
-#[command(version)]
+#[command(version, subcommand_negates_reqs = true)]
 struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    #[arg(long, required = true)]
+    url: Option<String>,
+
+    /// The HTTP client library used for fetching the Web page
+    #[arg(long, value_enum, default_value_t = HttpBackend::Reqwest)]
+    http_backend: HttpBackend,
+
+    /// How progress of downloading the Web page is reported
+    #[arg(long, value_enum, default_value_t = ProgressDisplay::Bar)]
+    progress: ProgressDisplay,
+
+    /// Emit traces of fetching, streaming, and hashing the Web page on
+    /// standard error in this format
+    ///
+    /// Traces are filtered by the `RUST_LOG` environment variable, with
+    /// `tracing-subscriber` `EnvFilter` syntax (e.g., `debug`), when it's set.
+    #[arg(long, value_enum)]
+    log_format: Option<LogFormat>,
+
+    /// Record the response into this cassette file, e.g., a test fixture,
+    /// keeping responses recorded earlier for other URLs
+    #[arg(long, value_name = "CASSETTE", conflicts_with = "replay")]
+    record: Option<PathBuf>,
+
+    /// Serve the response recorded with `--record` from this cassette file
+    /// instead of sending a request
+    #[arg(long, value_name = "CASSETTE")]
+    replay: Option<PathBuf>,
+
+    #[command(flatten)]
+    configuration_args: ConfigurationArgs,
+
+    #[command(flatten)]
+    request_args: RequestArgs,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Serves message digests of Web pages over HTTP, e.g., as a sidecar
+    ///
+    /// `GET /digest?url=URL[&alg=sha3-256]` returns the digest of a Web page
+    /// as a JSON object with the `url`, `algorithm`, `digest`, `size`, and
+    /// `error` fields.  `POST /digest` with a JSON array of `{"url": URL,
+    /// "alg": ALGORITHM}` objects returns an array of digests.  `GET /healthz`
+    /// returns `{"status": "ok"}`.  Requests that can't be parsed are answered
+    /// with an `error` object alone.
+    ///
+    /// Other options, e.g., `--http-backend`, are given before `serve`.
+    Serve(ServeArgs),
+}
+
+#[derive(Debug, ClapArgs)]
+struct ServeArgs {
+    /// The address to listen on
+    #[arg(long, default_value = "127.0.0.1:8080")]
+    listen: SocketAddr,
+
+    /// The maximum number of Web pages in a `POST /digest` batch
+    #[arg(long, default_value_t = 100, value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..))]
+    max_batch_size: usize,
+
+    /// The number of Web pages fetched at once across all requests
+    #[arg(long, default_value_t = 4, value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..))]
+    concurrency: usize,
+}
+
+/// Settings overriding the configuration file, and `WEB_PAGE_HASH_*`
+/// environment variables
+#[derive(Debug, ClapArgs)]
+struct ConfigurationArgs {
+    /// A TOML configuration file [default: $WEB_PAGE_HASH_CONFIG, or
+    /// $XDG_CONFIG_HOME/web-page-hash/config.toml]
+    #[arg(long)]
+    config: Option<PathBuf>,
+
+    /// The maximum time for establishing a connection in seconds, or 0 for
+    /// no limit
+    #[arg(long)]
+    connect_timeout: Option<u64>,
+
+    /// The maximum time for a request in seconds, or 0 for no limit
     #[arg(long)]
-    url: String,
+    timeout: Option<u64>,
+
+    /// The `User-Agent` header value sent with requests
+    #[arg(long)]
+    user_agent: Option<String>,
+
+    /// The URL of a proxy for all requests
+    #[arg(long)]
+    proxy: Option<String>,
+
+    /// Fail instead of hashing response bodies larger than this size in
+    /// bytes
+    #[arg(long)]
+    max_body_size: Option<u64>,
+
+    /// Hash bodies of `4xx`, and `5xx` responses instead of failing
+    #[arg(long, overrides_with = "no_allow_error_status")]
+    allow_error_status: bool,
+
+    /// Fail on `4xx`, and `5xx` responses, even when the configuration
+    /// file, or environment variables allow them
+    #[arg(long, overrides_with = "allow_error_status")]
+    no_allow_error_status: bool,
+
+    /// Fail instead of hashing responses of other media types, e.g.,
+    /// `text/html`, or `image/*`
+    ///
+    /// Can be given more than once.
+    #[arg(long = "allow-content-type", value_name = "MEDIA_TYPE")]
+    allowed_content_types: Vec<String>,
+
+    /// Fail instead of hashing responses of this media type
+    ///
+    /// Can be given more than once.
+    #[arg(long = "deny-content-type", value_name = "MEDIA_TYPE")]
+    denied_content_types: Vec<String>,
+}
+
+impl ConfigurationArgs {
+    /// Returns the settings given as arguments, overriding all other
+    /// configuration layers
+    fn configuration_layer(&self) -> ConfigurationLayer {
+        ConfigurationLayer {
+            connect_timeout: self.connect_timeout,
+            timeout: self.timeout,
+            user_agent: self.user_agent.clone(),
+            proxy: self.proxy.clone(),
+            max_body_size: self.max_body_size,
+            allow_error_status: self
+                .allow_error_status
+                .then_some(true)
+                .or(self.no_allow_error_status.then_some(false)),
+            allowed_content_types: (!self.allowed_content_types.is_empty())
+                .then(|| self.allowed_content_types.clone()),
+            denied_content_types: (!self.denied_content_types.is_empty())
+                .then(|| self.denied_content_types.clone()),
+        }
+    }
+}
+
+/// Additional request headers, and credentials
+#[derive(Debug, ClapArgs)]
+struct RequestArgs {
+    /// An additional request header, e.g., `X-Api-Key: <key>`
+    ///
+    /// Can be given more than once.
+    #[arg(long = "header", value_name = "NAME:VALUE", value_parser = parse_header)]
+    headers: Vec<(HeaderName, HeaderValue)>,
+
+    /// Send this `Bearer` token with requests
+    ///
+    /// Prefer setting `WEB_PAGE_HASH_BEARER_TOKEN`, since arguments are
+    /// visible to other users.
+    #[arg(long, conflicts_with_all = ["user", "netrc", "netrc_file"])]
+    bearer_token: Option<String>,
+
+    /// Send this user name, and optional password with requests using
+    /// `Basic` authentication
+    #[arg(long, value_name = "USER[:PASSWORD]", conflicts_with_all = ["netrc", "netrc_file"])]
+    user: Option<String>,
+
+    /// Look up `Basic` authentication credentials by host name in $NETRC,
+    /// or ~/.netrc
+    #[arg(long, conflicts_with = "netrc_file")]
+    netrc: bool,
+
+    /// Look up `Basic` authentication credentials by host name in this
+    /// `.netrc` file
+    #[arg(long)]
+    netrc_file: Option<PathBuf>,
+}
+
+impl RequestArgs {
+    /// Returns the credentials selected by the arguments
+    ///
+    /// Without credential arguments, credentials are read from
+    /// `WEB_PAGE_HASH_BEARER_TOKEN`, or `WEB_PAGE_HASH_USERNAME`, and
+    /// `WEB_PAGE_HASH_PASSWORD`, when they're set.
+    fn credentials(&self) -> Result<Credentials, CredentialError> {
+        match (&self.bearer_token, &self.user, &self.netrc_file) {
+            (Some(token), _, _) => Credentials::bearer_token(token),
+            (None, Some(user), _) => {
+                let (username, password) = match user.split_once(':') {
+                    Some((username, password)) => (username, Some(password)),
+                    None => (user.as_str(), None),
+                };
+
+                Ok(Credentials::basic_auth(username, password))
+            }
+            (None, None, Some(netrc_file)) => Credentials::from_netrc_file(netrc_file),
+            (None, None, None) if self.netrc => Credentials::from_default_netrc_file(),
+            (None, None, None) => Credentials::from_environment(),
+        }
+    }
+
+    /// Returns the additional request headers
+    fn request_headers(&self) -> HeaderMap {
+        let mut request_headers = HeaderMap::new();
+        for (name, value) in &self.headers {
+            request_headers.append(name, value.clone());
+        }
+
+        request_headers
+    }
+}
+
+/// Parses a `NAME:VALUE` request header
+///
+/// Values are marked as sensitive, since they may hold credentials, e.g., API
+/// keys.
+fn parse_header(header: &str) -> Result<(HeaderName, HeaderValue), String> {
+    let (name, value) = header
+        .split_once(':')
+        .ok_or_else(|| "expected NAME:VALUE".to_string())?;
+    let name = HeaderName::try_from(name.trim()).map_err(|error| error.to_string())?;
+    let mut value = HeaderValue::try_from(value.trim()).map_err(|error| error.to_string())?;
+    value.set_sensitive(true);
+
+    Ok((name, value))
+}
+
+/// Formats of traces emitted on standard error
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum LogFormat {
+    /// Multi-line, human-readable events
+    Pretty,
+    /// One JSON object per event, including the spans it's in
+    Json,
+}
+
+/// Providers that can be bound to `GetUrlServiceComponent`
+///
+/// Providers other than `reqwest` are available when the crate is built with
+/// the respective cargo feature.
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum HttpBackend {
+    Reqwest,
+    #[cfg(feature = "hyper")]
+    Hyper,
+    #[cfg(feature = "ureq")]
+    Ureq,
+    #[cfg(feature = "curl")]
+    Curl,
+}
+
+/// Providers that can be bound to `ProgressReporterServiceComponent`
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum ProgressDisplay {
+    /// A progress bar on standard error, when it's a terminal
+    Bar,
+    /// No progress reporting
+    None,
 }
 
 type Digest = [u8; 32];
 
-#[derive(Debug, Default)]
-struct Services;
+/// Traces emitted with `--log-format`, unless `RUST_LOG` is set
+const DEFAULT_LOG_FILTER: &str = "web_page_hash_using_cgp=info";
+
+/// The name of the only message digest algorithm served by `serve`
+const ALGORITHM: &str = "sha3-256";
+
+/// The data held by [Services], and read by its providers
+struct ServiceSettings {
+    layered_configuration: LayeredConfiguration,
+    credentials: Credentials,
+    request_headers: HeaderMap,
+    cassette_file: CassetteFile,
+}
+
+/// A context binding `GetUrlServiceComponent`, and
+/// `ProgressReporterServiceComponent` to the providers selected at run time
+///
+/// The HTTP client, message digest, and calculate Web page message digest
+/// providers are decorated with [Traced] providers.  The HTTP client, and
+/// calculate Web page message digest providers read their settings from the
+/// layered configuration.  The HTTP client providers send the request
+/// headers, and the credentials held by the context, with the clients it
+/// holds, which are built on the first request.  [RecordingGetUrl], and
+/// [ReplayGetUrl] providers use the cassette file held by the context.
+/// [RecordingProgressReporter] records progress events in the context, e.g.,
+/// for checking them in tests.
+#[derive(HasField)]
+struct Services<HttpClientProvider, ProgressReporterProvider> {
+    layered_configuration: LayeredConfiguration,
+    credentials: Credentials,
+    request_headers: HeaderMap,
+    cassette_file: CassetteFile,
+    reqwest_client: OnceLock<reqwest::Client>,
+    #[cfg(feature = "hyper")]
+    hyper_client: OnceLock<HyperClient>,
+    #[cfg(feature = "ureq")]
+    ureq_agent: OnceLock<ureq::Agent>,
+    progress_bar: Mutex<Option<ProgressBar>>,
+    progress_events: Mutex<Vec<ProgressEvent>>,
+    _providers: PhantomData<(HttpClientProvider, ProgressReporterProvider)>,
+}
+
+impl<HttpClientProvider, ProgressReporterProvider>
+    Services<HttpClientProvider, ProgressReporterProvider>
+{
+    fn new(service_settings: ServiceSettings) -> Self {
+        let ServiceSettings {
+            layered_configuration,
+            credentials,
+            request_headers,
+            cassette_file,
+        } = service_settings;
 
-impl Services {
-    fn new() -> Self {
-        Services {}
+        Services {
+            layered_configuration,
+            credentials,
+            request_headers,
+            cassette_file,
+            reqwest_client: OnceLock::new(),
+            #[cfg(feature = "hyper")]
+            hyper_client: OnceLock::new(),
+            #[cfg(feature = "ureq")]
+            ureq_agent: OnceLock::new(),
+            progress_bar: Mutex::new(None),
+            progress_events: Mutex::new(Vec::new()),
+            _providers: PhantomData,
+        }
     }
 }
 
-delegate_and_check_components! {
-    CanUseServices for Services;
-    Services {
+delegate_components! {
+    <HttpClientProvider, ProgressReporterProvider>
+    Services<HttpClientProvider, ProgressReporterProvider> {
         ErrorTypeProviderComponent: UseAnyhowError,
-        NewDigestCalculatorServiceComponent: Sha3_256BitMessageDigestService,
+        ClockServiceComponent: UseTokioClock,
+        SleeperServiceComponent: UseTokioClock,
+        HttpClientConfigurationServiceComponent: LayeredConfigurationService,
+        ResponsePolicyConfigurationServiceComponent: LayeredConfigurationService,
+        CredentialProviderServiceComponent: ConfiguredCredentialProvider,
+        ResponsePolicyServiceComponent: ConfiguredResponsePolicy,
+        NewDigestCalculatorServiceComponent: Traced<Sha3_256BitMessageDigestService>,
         UrlTypeProviderComponent: UseType<reqwest::Url>,
         DigestTypeProviderComponent: UseType<Digest>,
-        GetUrlServiceComponent: ReqwestHttpClientService,
+        GetUrlServiceComponent: Traced<HttpClientProvider>,
+        ProgressReporterServiceComponent: ProgressReporterProvider,
         CalculateWebPageMessageDigestServiceComponent:
-            CalculateWebPageMessageDigestServiceObject,
+            Traced<CalculateWebPageMessageDigestServiceObject>,
+    }
+}
+
+check_components! {
+    CanUseServices for Services<ReqwestHttpClientService, IndicatifProgressReporter> {
+        ErrorTypeProviderComponent,
+        ClockServiceComponent,
+        SleeperServiceComponent,
+        HttpClientConfigurationServiceComponent,
+        ResponsePolicyConfigurationServiceComponent,
+        CredentialProviderServiceComponent,
+        ResponsePolicyServiceComponent,
+        NewDigestCalculatorServiceComponent,
+        UrlTypeProviderComponent,
+        DigestTypeProviderComponent,
+        GetUrlServiceComponent,
+        ProgressReporterServiceComponent,
+        CalculateWebPageMessageDigestServiceComponent,
+    }
+}
+
+check_components! {
+    CanUseQuietServices for Services<ReqwestHttpClientService, NoProgressReporter> {
+        ProgressReporterServiceComponent,
+        CalculateWebPageMessageDigestServiceComponent,
+    }
+}
+
+check_components! {
+    CanUseServingServices for Services<ReqwestHttpClientService, ByteCountingProgressReporter> {
+        ProgressReporterServiceComponent,
+        CalculateWebPageMessageDigestServiceComponent,
+    }
+}
+
+check_components! {
+    CanUseRecordingProgressServices for Services<ReplayGetUrl, RecordingProgressReporter> {
+        ProgressReporterServiceComponent,
+        CalculateWebPageMessageDigestServiceComponent,
+    }
+}
+
+check_components! {
+    CanUseRecordingServices for Services<RecordingGetUrl<ReqwestHttpClientService>, IndicatifProgressReporter> {
+        GetUrlServiceComponent,
+        CalculateWebPageMessageDigestServiceComponent,
+    }
+}
+
+check_components! {
+    CanUseReplayServices for Services<ReplayGetUrl, IndicatifProgressReporter> {
+        GetUrlServiceComponent,
+        CalculateWebPageMessageDigestServiceComponent,
+    }
+}
+
+#[cfg(feature = "hyper")]
+check_components! {
+    CanUseHyperServices for Services<HyperGetUrlService, IndicatifProgressReporter> {
+        GetUrlServiceComponent,
+        CalculateWebPageMessageDigestServiceComponent,
+    }
+}
+
+#[cfg(feature = "ureq")]
+check_components! {
+    CanUseUreqServices for Services<UreqGetUrlService, IndicatifProgressReporter> {
+        GetUrlServiceComponent,
+        CalculateWebPageMessageDigestServiceComponent,
+    }
+}
+
+#[cfg(feature = "curl")]
+check_components! {
+    CanUseCurlServices for Services<CurlGetUrlService, IndicatifProgressReporter> {
+        GetUrlServiceComponent,
+        CalculateWebPageMessageDigestServiceComponent,
     }
 }
 
@@ -55,23 +493,914 @@ struct HexFormatted<'a>(&'a [u8]);
 impl std::fmt::LowerHex for HexFormatted<'_> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
         for octet in self.0 {
-            write!(f, "{:x}", octet)?;
+            write!(f, "{:02x}", octet)?;
         }
 
         Ok(())
     }
 }
 
+async fn print_web_page_message_digest<Context>(
+    services: &Context,
+    url: &reqwest::Url,
+) -> Result<(), anyhow::Error>
+where
+    Context: CalculateWebPageMessageDigest
+        + HasUrlType<Url = reqwest::Url>
+        + HasDigestType<Digest = Digest>
+        + HasErrorType<Error = anyhow::Error>,
+{
+    println!("Fetching: {}", url);
+    let digest = services.calculate_web_page_message_digest(url).await?;
+    println!("256-bit SHA-3: 0x{:x}", HexFormatted(&digest));
+
+    Ok(())
+}
+
+/// Selects the `ProgressReporterServiceComponent` provider, and prints the
+/// digest
+async fn print_with_progress_reporter<HttpClientProvider>(
+    service_settings: ServiceSettings,
+    progress: ProgressDisplay,
+    url: &reqwest::Url,
+) -> Result<(), anyhow::Error>
+where
+    Services<HttpClientProvider, IndicatifProgressReporter>: CalculateWebPageMessageDigest
+        + HasUrlType<Url = reqwest::Url>
+        + HasDigestType<Digest = Digest>
+        + HasErrorType<Error = anyhow::Error>,
+    Services<HttpClientProvider, NoProgressReporter>: CalculateWebPageMessageDigest
+        + HasUrlType<Url = reqwest::Url>
+        + HasDigestType<Digest = Digest>
+        + HasErrorType<Error = anyhow::Error>,
+{
+    match progress {
+        ProgressDisplay::Bar => {
+            let services =
+                Services::<HttpClientProvider, IndicatifProgressReporter>::new(service_settings);
+            print_web_page_message_digest(&services, url).await
+        }
+        ProgressDisplay::None => {
+            let services =
+                Services::<HttpClientProvider, NoProgressReporter>::new(service_settings);
+            print_web_page_message_digest(&services, url).await
+        }
+    }
+}
+
+/// Decorates the selected `GetUrlServiceComponent` provider with a
+/// [RecordingGetUrl] provider, when responses are recorded, and prints the
+/// digest
+async fn print_with_recording<HttpClientProvider>(
+    service_settings: ServiceSettings,
+    recording: bool,
+    progress: ProgressDisplay,
+    url: &reqwest::Url,
+) -> Result<(), anyhow::Error>
+where
+    Services<HttpClientProvider, IndicatifProgressReporter>: CalculateWebPageMessageDigest
+        + HasUrlType<Url = reqwest::Url>
+        + HasDigestType<Digest = Digest>
+        + HasErrorType<Error = anyhow::Error>,
+    Services<HttpClientProvider, NoProgressReporter>: CalculateWebPageMessageDigest
+        + HasUrlType<Url = reqwest::Url>
+        + HasDigestType<Digest = Digest>
+        + HasErrorType<Error = anyhow::Error>,
+    Services<RecordingGetUrl<HttpClientProvider>, IndicatifProgressReporter>:
+        CalculateWebPageMessageDigest
+            + HasUrlType<Url = reqwest::Url>
+            + HasDigestType<Digest = Digest>
+            + HasErrorType<Error = anyhow::Error>,
+    Services<RecordingGetUrl<HttpClientProvider>, NoProgressReporter>:
+        CalculateWebPageMessageDigest
+            + HasUrlType<Url = reqwest::Url>
+            + HasDigestType<Digest = Digest>
+            + HasErrorType<Error = anyhow::Error>,
+{
+    if recording {
+        print_with_progress_reporter::<RecordingGetUrl<HttpClientProvider>>(
+            service_settings,
+            progress,
+            url,
+        )
+        .await
+    } else {
+        print_with_progress_reporter::<HttpClientProvider>(service_settings, progress, url).await
+    }
+}
+
+/// Serves message digests calculated by a [Services] context shared by all
+/// requests, as the router state
+async fn serve<Context>(services: Context, serve_args: &ServeArgs) -> Result<(), anyhow::Error>
+where
+    Context: CalculateWebPageMessageDigest
+        + HasUrlType<Url = reqwest::Url>
+        + HasDigestType<Digest = Digest>
+        + HasErrorType<Error = anyhow::Error>
+        + Send
+        + Sync
+        + 'static,
+{
+    let router = router(Arc::new(ServeState {
+        services: Arc::new(services),
+        max_batch_size: serve_args.max_batch_size,
+        concurrency: serve_args.concurrency,
+        permits: Semaphore::new(serve_args.concurrency),
+    }));
+
+    let listener = tokio::net::TcpListener::bind(serve_args.listen)
+        .await
+        .with_context(|| format!("couldn't listen on {}", serve_args.listen))?;
+    eprintln!("Listening on: http://{}", listener.local_addr()?);
+    axum::serve(listener, router).await?;
+
+    Ok(())
+}
+
+fn router<Context>(serve_state: Arc<ServeState<Context>>) -> axum::Router
+where
+    Context: CalculateWebPageMessageDigest
+        + HasUrlType<Url = reqwest::Url>
+        + HasDigestType<Digest = Digest>
+        + HasErrorType<Error = anyhow::Error>
+        + Send
+        + Sync
+        + 'static,
+{
+    axum::Router::new()
+        .route(
+            "/digest",
+            get(get_digest::<Context>).post(post_digests::<Context>),
+        )
+        .route("/healthz", get(get_health))
+        .with_state(serve_state)
+}
+
+/// Decorates the selected `GetUrlServiceComponent` provider with a
+/// [RecordingGetUrl] provider, when responses are recorded, and serves
+/// digests
+async fn serve_with_recording<HttpClientProvider>(
+    service_settings: ServiceSettings,
+    recording: bool,
+    serve_args: &ServeArgs,
+) -> Result<(), anyhow::Error>
+where
+    Services<HttpClientProvider, ByteCountingProgressReporter>: CalculateWebPageMessageDigest
+        + HasUrlType<Url = reqwest::Url>
+        + HasDigestType<Digest = Digest>
+        + HasErrorType<Error = anyhow::Error>
+        + Send
+        + Sync
+        + 'static,
+    Services<RecordingGetUrl<HttpClientProvider>, ByteCountingProgressReporter>:
+        CalculateWebPageMessageDigest
+            + HasUrlType<Url = reqwest::Url>
+            + HasDigestType<Digest = Digest>
+            + HasErrorType<Error = anyhow::Error>
+            + Send
+            + Sync
+            + 'static,
+{
+    if recording {
+        let services =
+            Services::<RecordingGetUrl<HttpClientProvider>, ByteCountingProgressReporter>::new(
+                service_settings,
+            );
+        serve(services, serve_args).await
+    } else {
+        let services =
+            Services::<HttpClientProvider, ByteCountingProgressReporter>::new(service_settings);
+        serve(services, serve_args).await
+    }
+}
+
+/// The state shared by the handlers of [serve]
+struct ServeState<Context> {
+    services: Arc<Context>,
+    max_batch_size: usize,
+    /// The number of digests of a batch calculated at once
+    concurrency: usize,
+    /// Permits of calculating a digest, so that concurrent requests don't
+    /// fetch more than `concurrency` Web pages at once
+    permits: Semaphore,
+}
+
+async fn get_digest<Context>(
+    State(serve_state): State<Arc<ServeState<Context>>>,
+    digest_query: Result<Query<DigestQuery>, QueryRejection>,
+) -> Result<DigestResponse, DigestError>
+where
+    Context: CalculateWebPageMessageDigest
+        + HasUrlType<Url = reqwest::Url>
+        + HasDigestType<Digest = Digest>
+        + HasErrorType<Error = anyhow::Error>
+        + Send
+        + Sync
+        + 'static,
+{
+    let Query(digest_query) = digest_query?;
+
+    Ok(calculate_digest_response(&serve_state, digest_query).await)
+}
+
+/// Calculates the digests of a batch of Web pages concurrently, and returns
+/// them in the order of the batch
+///
+/// Each digest has its own error, so the batch succeeds even when some
+/// digests fail.  Batches longer than the maximum batch size are rejected
+/// as a whole.
+async fn post_digests<Context>(
+    State(serve_state): State<Arc<ServeState<Context>>>,
+    digest_queries: Result<Json<Vec<DigestQuery>>, JsonRejection>,
+) -> Result<Json<Vec<DigestResponse>>, DigestError>
+where
+    Context: CalculateWebPageMessageDigest
+        + HasUrlType<Url = reqwest::Url>
+        + HasDigestType<Digest = Digest>
+        + HasErrorType<Error = anyhow::Error>
+        + Send
+        + Sync
+        + 'static,
+{
+    let Json(digest_queries) = digest_queries?;
+    if digest_queries.len() > serve_state.max_batch_size {
+        return Err(DigestError::new(
+            DigestErrorKind::BatchTooLarge,
+            format!(
+                "the batch has {} Web pages (at most {} are allowed)",
+                digest_queries.len(),
+                serve_state.max_batch_size
+            ),
+        ));
+    }
+
+    let digest_responses = futures_util::stream::iter(digest_queries)
+        .map(|digest_query| calculate_digest_response(&serve_state, digest_query))
+        .buffered(serve_state.concurrency)
+        .collect()
+        .await;
+
+    Ok(Json(digest_responses))
+}
+
+async fn get_health() -> Json<serde_json::Value> {
+    Json(serde_json::json!({ "status": "ok" }))
+}
+
+/// Calculates a digest with the shared context, counting the size of the
+/// Web page with its [ByteCountingProgressReporter] provider
+///
+/// The futures of CGP providers aren't required to be `Send`, so each digest
+/// is calculated on a blocking thread, instead of in the `Send` future of an
+/// axum handler.
+async fn calculate_digest_response<Context>(
+    serve_state: &ServeState<Context>,
+    digest_query: DigestQuery,
+) -> DigestResponse
+where
+    Context: CalculateWebPageMessageDigest
+        + HasUrlType<Url = reqwest::Url>
+        + HasDigestType<Digest = Digest>
+        + HasErrorType<Error = anyhow::Error>
+        + Send
+        + Sync
+        + 'static,
+{
+    let DigestQuery { url, alg } = digest_query;
+    let algorithm = alg.unwrap_or_else(|| ALGORITHM.to_string());
+    if !algorithm.eq_ignore_ascii_case(ALGORITHM) {
+        let error = DigestError::new(
+            DigestErrorKind::UnsupportedAlgorithm,
+            format!(
+                "unsupported algorithm: {} (only {} is supported)",
+                algorithm, ALGORITHM
+            ),
+        );
+        return DigestResponse::failed(url, algorithm, error);
+    }
+    let algorithm = ALGORITHM.to_string();
+    let parsed_url = match reqwest::Url::parse(&url) {
+        Ok(parsed_url) => parsed_url,
+        Err(error) => {
+            let error = DigestError::new(
+                DigestErrorKind::InvalidUrl,
+                format!("invalid URL: {}", error),
+            );
+            return DigestResponse::failed(url, algorithm, error);
+        }
+    };
+
+    let services = serve_state.services.clone();
+    let _permit = serve_state
+        .permits
+        .acquire()
+        .await
+        .expect("the semaphore is never closed");
+    let runtime = tokio::runtime::Handle::current();
+    let calculation = tokio::task::spawn_blocking(move || {
+        runtime.block_on(ByteCountingProgressReporter::count_bytes(
+            services.calculate_web_page_message_digest(&parsed_url),
+        ))
+    });
+
+    match calculation.await {
+        Ok((Ok(digest), size)) => DigestResponse::calculated(url, algorithm, &digest, size),
+        Ok((Err(error), _)) => {
+            DigestResponse::failed(url, algorithm, DigestError::from_anyhow(&error))
+        }
+        Err(join_error) => DigestResponse::failed(
+            url,
+            algorithm,
+            DigestError::new(DigestErrorKind::Internal, join_error.to_string()),
+        ),
+    }
+}
+
+/// Opens the cassette file named by `--record`, or `--replay`
+fn open_cassette_file(
+    record: Option<&Path>,
+    replay: Option<&Path>,
+) -> Result<CassetteFile, anyhow::Error> {
+    match (record, replay) {
+        (Some(path), _) => CassetteFile::open_or_create(path)
+            .with_context(|| format!("couldn't read {}", path.display())),
+        (None, Some(path)) => {
+            CassetteFile::open(path).with_context(|| format!("couldn't read {}", path.display()))
+        }
+        (None, None) => Ok(CassetteFile::default()),
+    }
+}
+
+/// Installs a [tracing] subscriber printing traces on standard error
+fn init_tracing(log_format: LogFormat) {
+    let env_filter =
+        EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(DEFAULT_LOG_FILTER));
+    let subscriber = tracing_subscriber::fmt()
+        .with_env_filter(env_filter)
+        .with_writer(std::io::stderr);
+
+    match log_format {
+        LogFormat::Pretty => subscriber.pretty().init(),
+        LogFormat::Json => subscriber.json().with_span_list(true).init(),
+    }
+}
+
 #[tokio::main]
 async fn main() -> Result<(), anyhow::Error> {
     let args = Args::parse();
 
-    let url = reqwest::Url::parse(&args.url)?;
-    let services = Services::new();
+    if let Some(log_format) = args.log_format {
+        init_tracing(log_format);
+    }
 
-    println!("Fetching: {}", url);
-    let digest = services.calculate_web_page_message_digest(&url).await?;
-    println!("256-bit SHA-3: 0x{:x}", HexFormatted(&digest));
+    let layered_configuration = LayeredConfiguration::load(
+        args.configuration_args.config.as_deref(),
+        args.configuration_args.configuration_layer(),
+    )?;
+    let service_settings = ServiceSettings {
+        layered_configuration,
+        credentials: args.request_args.credentials()?,
+        request_headers: args.request_args.request_headers(),
+        cassette_file: open_cassette_file(args.record.as_deref(), args.replay.as_deref())?,
+    };
+    let recording = args.record.is_some();
 
-    Ok(())
+    if let Some(Command::Serve(serve_args)) = &args.command {
+        if args.replay.is_some() {
+            let services =
+                Services::<ReplayGetUrl, ByteCountingProgressReporter>::new(service_settings);
+            return serve(services, serve_args).await;
+        }
+
+        return match args.http_backend {
+            HttpBackend::Reqwest => {
+                serve_with_recording::<ReqwestHttpClientService>(
+                    service_settings,
+                    recording,
+                    serve_args,
+                )
+                .await
+            }
+            #[cfg(feature = "hyper")]
+            HttpBackend::Hyper => {
+                serve_with_recording::<HyperGetUrlService>(service_settings, recording, serve_args)
+                    .await
+            }
+            #[cfg(feature = "ureq")]
+            HttpBackend::Ureq => {
+                serve_with_recording::<UreqGetUrlService>(service_settings, recording, serve_args)
+                    .await
+            }
+            #[cfg(feature = "curl")]
+            HttpBackend::Curl => {
+                serve_with_recording::<CurlGetUrlService>(service_settings, recording, serve_args)
+                    .await
+            }
+        };
+    }
+    let url = reqwest::Url::parse(
+        args.url
+            .as_deref()
+            .expect("clap requires --url without a subcommand"),
+    )?;
+    let progress = args.progress;
+
+    if args.replay.is_some() {
+        return print_with_progress_reporter::<ReplayGetUrl>(service_settings, progress, &url)
+            .await;
+    }
+
+    match args.http_backend {
+        HttpBackend::Reqwest => {
+            print_with_recording::<ReqwestHttpClientService>(
+                service_settings,
+                recording,
+                progress,
+                &url,
+            )
+            .await
+        }
+        #[cfg(feature = "hyper")]
+        HttpBackend::Hyper => {
+            print_with_recording::<HyperGetUrlService>(service_settings, recording, progress, &url)
+                .await
+        }
+        #[cfg(feature = "ureq")]
+        HttpBackend::Ureq => {
+            print_with_recording::<UreqGetUrlService>(service_settings, recording, progress, &url)
+                .await
+        }
+        #[cfg(feature = "curl")]
+        HttpBackend::Curl => {
+            print_with_recording::<CurlGetUrlService>(service_settings, recording, progress, &url)
+                .await
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytes::Bytes;
+    use http::StatusCode;
+    use tower::ServiceExt;
+    use web_page_hash_using_cgp::credential_provider_service::Credential;
+    use web_page_hash_using_cgp::http_cassette::CassetteInteraction;
+
+    const URL: &str = "http://example.com/page";
+
+    /// Calculates the digest of a Web page replayed in chunks, and returns
+    /// the progress events reported meanwhile
+    async fn report_progress(
+        configuration_layer: ConfigurationLayer,
+        headers: HeaderMap,
+    ) -> (Result<Digest, anyhow::Error>, Vec<ProgressEvent>) {
+        let cassette_file = CassetteFile::default();
+        cassette_file
+            .record(CassetteInteraction::new(
+                URL.to_string(),
+                StatusCode::OK,
+                headers,
+                vec![Bytes::from_static(b"<p>page"), Bytes::from_static(b"</p>")],
+            ))
+            .unwrap();
+        let services = Services::<ReplayGetUrl, RecordingProgressReporter>::new(ServiceSettings {
+            layered_configuration: LayeredConfiguration::new([configuration_layer]),
+            credentials: Credentials::None,
+            request_headers: HeaderMap::new(),
+            cassette_file,
+        });
+
+        let digest_result = services
+            .calculate_web_page_message_digest(&reqwest::Url::parse(URL).unwrap())
+            .await;
+
+        (
+            digest_result,
+            services.progress_events.into_inner().unwrap(),
+        )
+    }
+
+    #[tokio::test]
+    async fn progress_is_reported_for_each_chunk() {
+        let headers =
+            HeaderMap::from_iter([(http::header::CONTENT_LENGTH, HeaderValue::from_static("11"))]);
+
+        let (digest_result, events) = report_progress(ConfigurationLayer::default(), headers).await;
+
+        assert!(digest_result.is_ok());
+        assert_eq!(
+            events,
+            [
+                ProgressEvent::Started {
+                    url: URL.to_string(),
+                    expected_length: Some(11),
+                },
+                ProgressEvent::BytesWritten(7),
+                ProgressEvent::BytesWritten(4),
+                ProgressEvent::Finished,
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn progress_is_finished_when_streaming_fails() {
+        let configuration_layer = ConfigurationLayer {
+            max_body_size: Some(8),
+            ..ConfigurationLayer::default()
+        };
+
+        let (digest_result, events) = report_progress(configuration_layer, HeaderMap::new()).await;
+
+        assert!(digest_result.is_err());
+        assert_eq!(
+            events,
+            [
+                ProgressEvent::Started {
+                    url: URL.to_string(),
+                    expected_length: None,
+                },
+                ProgressEvent::BytesWritten(7),
+                ProgressEvent::Finished,
+            ]
+        );
+    }
+
+    /// Returns the state of a server replaying a Web page
+    fn serve_state(
+        max_batch_size: usize,
+        concurrency: usize,
+    ) -> Arc<ServeState<Services<ReplayGetUrl, ByteCountingProgressReporter>>> {
+        let cassette_file = CassetteFile::default();
+        cassette_file
+            .record(CassetteInteraction::new(
+                URL.to_string(),
+                StatusCode::OK,
+                HeaderMap::new(),
+                vec![Bytes::from_static(b"<p>page</p>")],
+            ))
+            .unwrap();
+        let services =
+            Services::<ReplayGetUrl, ByteCountingProgressReporter>::new(ServiceSettings {
+                layered_configuration: LayeredConfiguration::new([ConfigurationLayer::default()]),
+                credentials: Credentials::None,
+                request_headers: HeaderMap::new(),
+                cassette_file,
+            });
+
+        Arc::new(ServeState {
+            services: Arc::new(services),
+            max_batch_size,
+            concurrency,
+            permits: Semaphore::new(concurrency),
+        })
+    }
+
+    fn digest_query(url: &str) -> DigestQuery {
+        DigestQuery {
+            url: url.to_string(),
+            alg: None,
+        }
+    }
+
+    // Digests are calculated on blocking threads driving the runtime, so
+    // the runtime's drivers need a thread of their own:
+    #[tokio::test(flavor = "multi_thread")]
+    async fn batch_digests_are_returned_in_the_order_of_the_batch() {
+        let digest_queries = vec![
+            digest_query(URL),
+            digest_query("not a URL"),
+            digest_query(URL),
+        ];
+
+        let Json(digest_responses) =
+            post_digests(State(serve_state(3, 2)), Ok(Json(digest_queries)))
+                .await
+                .unwrap();
+
+        let urls_and_error_kinds = digest_responses
+            .iter()
+            .map(|digest_response| {
+                (
+                    digest_response.url.as_str(),
+                    digest_response.error.as_ref().map(|error| error.kind),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            urls_and_error_kinds,
+            [
+                (URL, None),
+                ("not a URL", Some(DigestErrorKind::InvalidUrl)),
+                (URL, None),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn batches_longer_than_the_maximum_batch_size_are_rejected() {
+        let digest_queries = vec![digest_query(URL), digest_query(URL)];
+
+        let Err(error) = post_digests(State(serve_state(1, 2)), Ok(Json(digest_queries))).await
+        else {
+            panic!("the batch wasn't rejected");
+        };
+
+        assert_eq!(error.kind, DigestErrorKind::BatchTooLarge);
+        assert_eq!(error.kind.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    }
+
+    /// Sends a request to the router of a server, and returns the status,
+    /// and the JSON body of the response
+    async fn send(
+        serve_state: Arc<ServeState<Services<ReplayGetUrl, ByteCountingProgressReporter>>>,
+        request: http::Request<axum::body::Body>,
+    ) -> (StatusCode, serde_json::Value) {
+        let response = router(serve_state).oneshot(request).await.unwrap();
+        let status = response.status();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    fn get(uri: &str) -> http::Request<axum::body::Body> {
+        http::Request::get(uri)
+            .body(axum::body::Body::empty())
+            .unwrap()
+    }
+
+    fn post(content_type: Option<&str>, body: &str) -> http::Request<axum::body::Body> {
+        let mut request = http::Request::post("/digest");
+        if let Some(content_type) = content_type {
+            request = request.header(http::header::CONTENT_TYPE, content_type);
+        }
+
+        request
+            .body(axum::body::Body::from(body.to_string()))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn health_is_reported() {
+        let (status, body) = send(serve_state(1, 1), get("/healthz")).await;
+
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, serde_json::json!({ "status": "ok" }));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn digests_are_served() {
+        let (status, body) = send(
+            serve_state(1, 1),
+            get("/digest?url=http%3A%2F%2Fexample.com%2Fpage&alg=SHA3-256"),
+        )
+        .await;
+
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["url"], URL);
+        assert_eq!(body["algorithm"], ALGORITHM);
+        assert_eq!(body["size"], 11);
+        assert_eq!(body["digest"].as_str().unwrap().len(), 64);
+        assert_eq!(body["error"], serde_json::Value::Null);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn digest_errors_are_mapped_to_statuses() {
+        let (status, body) = send(serve_state(1, 1), get("/digest?url=not%20a%20URL")).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["url"], "not a URL");
+        assert_eq!(body["error"]["kind"], "invalid_url");
+
+        let (status, body) = send(
+            serve_state(1, 1),
+            get("/digest?url=http%3A%2F%2Fexample.com%2Fpage&alg=md5"),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"]["kind"], "unsupported_algorithm");
+
+        // The cassette has no other Web page:
+        let (status, body) = send(
+            serve_state(1, 1),
+            get("/digest?url=http%3A%2F%2Fexample.com%2Fother"),
+        )
+        .await;
+        assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(body["error"]["kind"], "internal");
+    }
+
+    #[tokio::test]
+    async fn unparsable_requests_are_answered_with_digest_errors() {
+        for (request, expected_status, expected_kind) in [
+            (get("/digest"), StatusCode::BAD_REQUEST, "invalid_request"),
+            (
+                post(Some("application/json"), "[{\"alg\": \"sha3-256\"}]"),
+                StatusCode::BAD_REQUEST,
+                "invalid_request",
+            ),
+            (
+                post(Some("application/json"), "[{"),
+                StatusCode::BAD_REQUEST,
+                "invalid_request",
+            ),
+            (
+                post(None, "[]"),
+                StatusCode::UNSUPPORTED_MEDIA_TYPE,
+                "unsupported_media_type",
+            ),
+        ] {
+            let (status, body) = send(serve_state(1, 1), request).await;
+
+            assert_eq!(status, expected_status, "{body}");
+            assert_eq!(body["kind"], expected_kind, "{body}");
+            assert!(!body["message"].as_str().unwrap().is_empty(), "{body}");
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn batches_are_served_in_order() {
+        let (status, body) = send(
+            serve_state(3, 2),
+            post(
+                Some("application/json"),
+                &serde_json::json!([
+                    { "url": URL },
+                    { "url": "not a URL" },
+                    { "url": URL, "alg": "sha3-256" },
+                ])
+                .to_string(),
+            ),
+        )
+        .await;
+
+        assert_eq!(status, StatusCode::OK);
+        let urls_and_error_kinds = body
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|digest_response| {
+                (
+                    digest_response["url"].as_str().unwrap(),
+                    digest_response["error"]["kind"].as_str(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            urls_and_error_kinds,
+            [(URL, None), ("not a URL", Some("invalid_url")), (URL, None)]
+        );
+
+        let (status, body) = send(
+            serve_state(1, 2),
+            post(
+                Some("application/json"),
+                "[{\"url\": \"a\"}, {\"url\": \"b\"}]",
+            ),
+        )
+        .await;
+        assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+        assert_eq!(body["kind"], "batch_too_large");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn web_pages_are_fetched_within_the_concurrency_across_requests() {
+        let serve_state = serve_state(1, 1);
+        let permit = serve_state.permits.acquire().await.unwrap();
+
+        let mut response = Box::pin(send(
+            serve_state.clone(),
+            get("/digest?url=http%3A%2F%2Fexample.com%2Fpage"),
+        ));
+        // Another request holds the only permit:
+        assert!(
+            tokio::time::timeout(std::time::Duration::from_millis(100), &mut response)
+                .await
+                .is_err()
+        );
+        drop(permit);
+
+        let (status, _) = response.await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    #[test]
+    fn hex_formatted_pads_octets_below_0x10() {
+        let octets = [0x00, 0x01, 0x0f, 0x10, 0xab, 0xff];
+
+        assert_eq!(format!("{:x}", HexFormatted(&octets)), "00010f10abff");
+    }
+
+    fn request_args() -> RequestArgs {
+        RequestArgs {
+            headers: Vec::new(),
+            bearer_token: None,
+            user: None,
+            netrc: false,
+            netrc_file: None,
+        }
+    }
+
+    /// Writes a `.netrc` file named after a test into the temporary
+    /// directory
+    fn netrc_file(name: &str, text: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!(
+            "web-page-hash-cgp-{}-{}.netrc",
+            name,
+            std::process::id()
+        ));
+        std::fs::write(&path, text).unwrap();
+
+        path
+    }
+
+    /// Returns the `Authorization` header value sent to `url` with the
+    /// credentials selected by the arguments
+    fn authorization(request_args: &RequestArgs, url: &str) -> Option<HeaderValue> {
+        let credential = match request_args.credentials().unwrap() {
+            Credentials::None => None,
+            Credentials::Static(credential) => Some(credential),
+            Credentials::Netrc(netrc) => netrc.look_up_credential(url),
+        };
+
+        credential.map(|credential| credential.authorization().clone())
+    }
+
+    fn basic(username: &str, password: Option<&str>) -> Option<HeaderValue> {
+        Some(
+            Credential::basic(username, password)
+                .authorization()
+                .clone(),
+        )
+    }
+
+    #[test]
+    fn netrc_credentials_are_looked_up_by_host_name_or_default() {
+        let path = netrc_file(
+            "host",
+            "machine EXAMPLE.com login alice password secret\n\
+             macdef init\n\
+             machine example.net login mallory\n\
+             \n\
+             default login anonymous\n",
+        );
+        let request_args = RequestArgs {
+            netrc_file: Some(path.clone()),
+            ..request_args()
+        };
+
+        assert_eq!(
+            authorization(&request_args, URL),
+            basic("alice", Some("secret"))
+        );
+        assert_eq!(
+            authorization(&request_args, "http://example.net/"),
+            basic("anonymous", None)
+        );
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn invalid_netrc_files_are_rejected() {
+        let path = netrc_file("invalid", "login alice\n");
+        let request_args = RequestArgs {
+            netrc_file: Some(path.clone()),
+            ..request_args()
+        };
+
+        assert!(matches!(
+            request_args.credentials(),
+            Err(CredentialError::InvalidNetrc(_, _))
+        ));
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn credential_arguments_take_precedence_over_netrc_files() {
+        let path = netrc_file("precedence", "default login alice password secret\n");
+        let user_args = RequestArgs {
+            user: Some("bob:hunter2".to_string()),
+            netrc: true,
+            netrc_file: Some(path.clone()),
+            ..request_args()
+        };
+        let bearer_token_args = RequestArgs {
+            bearer_token: Some("token".to_string()),
+            user: Some("bob:hunter2".to_string()),
+            netrc: true,
+            netrc_file: Some(path.clone()),
+            ..request_args()
+        };
+
+        assert_eq!(
+            authorization(&user_args, URL),
+            basic("bob", Some("hunter2"))
+        );
+        assert_eq!(
+            authorization(&bearer_token_args, URL),
+            Some(Credential::bearer("token").unwrap().authorization().clone())
+        );
+        std::fs::remove_file(path).unwrap();
+    }
 }
@@ -0,0 +1,43 @@
+use super::interface::{ProgressReporterService, ProgressReporterServiceComponent};
+use cgp::prelude::*;
+use std::cell::Cell;
+
+tokio::task_local! {
+    static BYTE_COUNT: Cell<u64>;
+}
+
+/// A [ProgressReporterService] provider that counts the hashed response body
+/// octets, e.g., for reporting the size of a Web page
+///
+/// Octets are counted per task by [ByteCountingProgressReporter::count_bytes],
+/// so that a context shared by concurrent calculations, e.g., of a server,
+/// reports the size of each Web page separately.  Octets reported outside of
+/// [ByteCountingProgressReporter::count_bytes] aren't counted.
+pub struct ByteCountingProgressReporter;
+
+impl ByteCountingProgressReporter {
+    /// Runs a future, and returns its output with the number of octets
+    /// reported while it ran
+    pub async fn count_bytes<Output>(future: impl Future<Output = Output>) -> (Output, u64) {
+        BYTE_COUNT
+            .scope(Cell::new(0), async {
+                let output = future.await;
+
+                (output, BYTE_COUNT.with(Cell::get))
+            })
+            .await
+    }
+}
+
+#[cgp_impl(ByteCountingProgressReporter)]
+impl<Context> ProgressReporterService for Context {
+    fn report_started(_context: &Context, _url: &str, _expected_length: Option<u64>) {}
+
+    fn report_bytes_written(_context: &Context, byte_count: u64) {
+        let _ = BYTE_COUNT.try_with(|counted_byte_count| {
+            counted_byte_count.set(counted_byte_count.get() + byte_count)
+        });
+    }
+
+    fn report_finished(_context: &Context) {}
+}
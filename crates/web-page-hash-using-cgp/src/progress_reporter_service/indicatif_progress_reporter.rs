@@ -0,0 +1,69 @@
+use super::interface::{ProgressReporterService, ProgressReporterServiceComponent};
+use crate::credential_provider_service::redact_url_credentials;
+use cgp::prelude::*;
+use indicatif::{ProgressBar, ProgressStyle};
+use std::sync::{Mutex, PoisonError};
+
+const BAR_TEMPLATE: &str =
+    "{msg}\n{wide_bar} {bytes}/{total_bytes} ({bytes_per_sec}, {eta} remaining)";
+const SPINNER_TEMPLATE: &str = "{spinner} {msg} {bytes} ({bytes_per_sec})";
+
+/// A context holding the progress bar drawn by [IndicatifProgressReporter]
+#[cgp_auto_getter]
+pub trait HasProgressBar {
+    fn progress_bar(&self) -> &Mutex<Option<ProgressBar>>;
+}
+
+/// A [ProgressReporterService] provider drawing a progress bar on the
+/// terminal with the [indicatif] crate
+///
+/// A spinner is drawn instead, when the length of the response body isn't
+/// known.  The bar is drawn on standard error, only when it's a terminal, and
+/// it's cleared when streaming finishes.
+pub struct IndicatifProgressReporter;
+
+#[cgp_impl(IndicatifProgressReporter)]
+impl<Context> ProgressReporterService for Context
+where
+    Context: HasProgressBar,
+{
+    fn report_started(context: &Context, url: &str, expected_length: Option<u64>) {
+        let progress_bar = match expected_length {
+            Some(expected_length) => ProgressBar::new(expected_length).with_style(
+                ProgressStyle::with_template(BAR_TEMPLATE)
+                    .unwrap_or_else(|_| ProgressStyle::default_bar()),
+            ),
+            None => ProgressBar::new_spinner().with_style(
+                ProgressStyle::with_template(SPINNER_TEMPLATE)
+                    .unwrap_or_else(|_| ProgressStyle::default_spinner()),
+            ),
+        };
+        progress_bar.set_message(redact_url_credentials(url).into_owned());
+
+        *context
+            .progress_bar()
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner) = Some(progress_bar);
+    }
+
+    fn report_bytes_written(context: &Context, byte_count: u64) {
+        if let Some(progress_bar) = &*context
+            .progress_bar()
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+        {
+            progress_bar.inc(byte_count);
+        }
+    }
+
+    fn report_finished(context: &Context) {
+        if let Some(progress_bar) = context
+            .progress_bar()
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .take()
+        {
+            progress_bar.finish_and_clear();
+        }
+    }
+}
@@ -0,0 +1,16 @@
+use cgp::prelude::*;
+
+/// A service that reports progress of streaming response bodies
+#[cgp_component(ProgressReporterService)]
+pub trait ProgressReporter {
+    /// Reports that streaming a response body started
+    ///
+    /// `expected_length` is the `Content-Length` of the response, if known.
+    fn report_started(&self, url: &str, expected_length: Option<u64>);
+
+    /// Reports that a chunk of a response body was hashed
+    fn report_bytes_written(&self, byte_count: u64);
+
+    /// Reports that streaming a response body finished, successfully, or not
+    fn report_finished(&self);
+}
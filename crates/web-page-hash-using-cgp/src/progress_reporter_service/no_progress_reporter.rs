@@ -0,0 +1,14 @@
+use super::interface::{ProgressReporterService, ProgressReporterServiceComponent};
+use cgp::prelude::*;
+
+/// A [ProgressReporterService] provider that doesn't report anything
+pub struct NoProgressReporter;
+
+#[cgp_impl(NoProgressReporter)]
+impl<Context> ProgressReporterService for Context {
+    fn report_started(_context: &Context, _url: &str, _expected_length: Option<u64>) {}
+
+    fn report_bytes_written(_context: &Context, _byte_count: u64) {}
+
+    fn report_finished(_context: &Context) {}
+}
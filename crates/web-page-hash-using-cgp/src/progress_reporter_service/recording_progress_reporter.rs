@@ -0,0 +1,56 @@
+use super::interface::{ProgressReporterService, ProgressReporterServiceComponent};
+use cgp::prelude::*;
+use std::sync::{Mutex, PoisonError};
+
+/// A call to a [ProgressReporterService] function
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ProgressEvent {
+    Started {
+        url: String,
+        expected_length: Option<u64>,
+    },
+    BytesWritten(u64),
+    Finished,
+}
+
+/// A context holding the events recorded by [RecordingProgressReporter]
+#[cgp_auto_getter]
+pub trait HasProgressEvents {
+    fn progress_events(&self) -> &Mutex<Vec<ProgressEvent>>;
+}
+
+/// A [ProgressReporterService] provider that records reported events in the
+/// context, e.g., for checking them in tests
+pub struct RecordingProgressReporter;
+
+#[cgp_impl(RecordingProgressReporter)]
+impl<Context> ProgressReporterService for Context
+where
+    Context: HasProgressEvents,
+{
+    fn report_started(context: &Context, url: &str, expected_length: Option<u64>) {
+        record(
+            context,
+            ProgressEvent::Started {
+                url: url.to_string(),
+                expected_length,
+            },
+        );
+    }
+
+    fn report_bytes_written(context: &Context, byte_count: u64) {
+        record(context, ProgressEvent::BytesWritten(byte_count));
+    }
+
+    fn report_finished(context: &Context) {
+        record(context, ProgressEvent::Finished);
+    }
+}
+
+fn record<Context: HasProgressEvents>(context: &Context, event: ProgressEvent) {
+    context
+        .progress_events()
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .push(event);
+}
@@ -0,0 +1,92 @@
+use super::interface::{
+    ResponsePolicyService, ResponsePolicyServiceComponent, ResponsePolicyViolation,
+};
+use crate::configuration_service::ResponsePolicyConfiguration;
+use crate::http_client_service::ResponseMetadata;
+use cgp::prelude::*;
+
+/// A [ResponsePolicyService] provider checking the status, the media type,
+/// and the body size of responses against the settings of the context
+///
+/// Media types are matched case-insensitively, ignoring parameters, such as
+/// `charset`.  A `type/*` pattern matches all subtypes of `type`.  Denied
+/// media types take precedence over allowed ones.
+pub struct ConfiguredResponsePolicy;
+
+#[cgp_impl(ConfiguredResponsePolicy)]
+impl<Context> ResponsePolicyService for Context
+where
+    Context: ResponsePolicyConfiguration,
+{
+    fn check_response_metadata<Response: ResponseMetadata>(
+        context: &Context,
+        response: &Response,
+    ) -> Result<(), ResponsePolicyViolation> {
+        let status = response.status();
+        if !context.allow_error_status() && (status.is_client_error() || status.is_server_error()) {
+            return Err(ResponsePolicyViolation::ErrorStatus(status));
+        }
+
+        match response.content_type() {
+            Some(content_type)
+                if context
+                    .denied_content_types()
+                    .iter()
+                    .any(|pattern| media_type_matches(pattern, content_type)) =>
+            {
+                return Err(ResponsePolicyViolation::ContentTypeDenied(
+                    content_type.to_string(),
+                ));
+            }
+            Some(content_type)
+                if !context.allowed_content_types().is_empty()
+                    && !context
+                        .allowed_content_types()
+                        .iter()
+                        .any(|pattern| media_type_matches(pattern, content_type)) =>
+            {
+                return Err(ResponsePolicyViolation::ContentTypeNotAllowed(
+                    content_type.to_string(),
+                ));
+            }
+            None if !context.allowed_content_types().is_empty() => {
+                return Err(ResponsePolicyViolation::MissingContentType);
+            }
+            _ => {}
+        }
+
+        if let (Some(max_body_size), Some(content_length)) =
+            (context.max_body_size(), response.content_length())
+            && content_length > max_body_size
+        {
+            return Err(ResponsePolicyViolation::ContentLengthExceeded {
+                content_length,
+                max_body_size,
+            });
+        }
+
+        Ok(())
+    }
+
+    fn check_body_size(context: &Context, byte_count: u64) -> Result<(), ResponsePolicyViolation> {
+        match context.max_body_size() {
+            Some(max_body_size) if byte_count > max_body_size => {
+                Err(ResponsePolicyViolation::BodySizeLimitExceeded { max_body_size })
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Returns whether a `Content-Type` header value matches a media type
+/// pattern, such as `text/html`, or `image/*`
+fn media_type_matches(pattern: &str, content_type: &str) -> bool {
+    let media_type = content_type.split(';').next().unwrap_or_default().trim();
+
+    match pattern.trim().strip_suffix("/*") {
+        Some(pattern_type) => media_type
+            .split_once('/')
+            .is_some_and(|(media_type_type, _)| media_type_type.eq_ignore_ascii_case(pattern_type)),
+        None => media_type.eq_ignore_ascii_case(pattern.trim()),
+    }
+}
@@ -0,0 +1,76 @@
+use crate::http_client_service::ResponseMetadata;
+use cgp::prelude::*;
+use http::StatusCode;
+use std::fmt;
+
+/// A component deciding which responses are hashed
+#[cgp_component(ResponsePolicyService)]
+pub trait ResponsePolicy {
+    /// Checks a response before its body is streamed
+    fn check_response_metadata<Response: ResponseMetadata>(
+        &self,
+        response: &Response,
+    ) -> Result<(), ResponsePolicyViolation>;
+
+    /// Checks the number of body octets streamed so far, after each chunk
+    fn check_body_size(&self, byte_count: u64) -> Result<(), ResponsePolicyViolation>;
+}
+
+/// The error returned when a response is rejected by a [ResponsePolicy]
+#[derive(Debug)]
+pub enum ResponsePolicyViolation {
+    ErrorStatus(StatusCode),
+    /// A list of allowed media types is configured, but the response has no
+    /// `Content-Type`
+    MissingContentType,
+    ContentTypeNotAllowed(String),
+    ContentTypeDenied(String),
+    /// The `Content-Length` of the response is larger than the maximum body
+    /// size
+    ContentLengthExceeded {
+        content_length: u64,
+        max_body_size: u64,
+    },
+    /// More octets than the maximum body size were streamed
+    BodySizeLimitExceeded {
+        max_body_size: u64,
+    },
+}
+
+impl fmt::Display for ResponsePolicyViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ResponsePolicyViolation::ErrorStatus(status) => {
+                write!(f, "the response has an error status: {}", status)
+            }
+            ResponsePolicyViolation::MissingContentType => {
+                write!(f, "the response has no Content-Type")
+            }
+            ResponsePolicyViolation::ContentTypeNotAllowed(content_type) => {
+                write!(
+                    f,
+                    "the response Content-Type isn't allowed: {}",
+                    content_type
+                )
+            }
+            ResponsePolicyViolation::ContentTypeDenied(content_type) => {
+                write!(f, "the response Content-Type is denied: {}", content_type)
+            }
+            ResponsePolicyViolation::ContentLengthExceeded {
+                content_length,
+                max_body_size,
+            } => write!(
+                f,
+                "the response Content-Length of {} bytes is larger than the maximum body size of {} bytes",
+                content_length, max_body_size
+            ),
+            ResponsePolicyViolation::BodySizeLimitExceeded { max_body_size } => write!(
+                f,
+                "the response body is larger than the maximum body size of {} bytes",
+                max_body_size
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ResponsePolicyViolation {}
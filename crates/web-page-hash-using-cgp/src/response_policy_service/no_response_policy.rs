@@ -0,0 +1,25 @@
+use super::interface::{
+    ResponsePolicyService, ResponsePolicyServiceComponent, ResponsePolicyViolation,
+};
+use crate::http_client_service::ResponseMetadata;
+use cgp::prelude::*;
+
+/// A [ResponsePolicyService] provider accepting all responses
+pub struct NoResponsePolicy;
+
+#[cgp_impl(NoResponsePolicy)]
+impl<Context> ResponsePolicyService for Context {
+    fn check_response_metadata<Response: ResponseMetadata>(
+        _context: &Context,
+        _response: &Response,
+    ) -> Result<(), ResponsePolicyViolation> {
+        Ok(())
+    }
+
+    fn check_body_size(
+        _context: &Context,
+        _byte_count: u64,
+    ) -> Result<(), ResponsePolicyViolation> {
+        Ok(())
+    }
+}
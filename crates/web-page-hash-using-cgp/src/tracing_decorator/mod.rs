@@ -0,0 +1,10 @@
+pub mod traced;
+pub use traced::*;
+
+pub mod traced_calculate_web_page_message_digest_service;
+
+pub mod traced_http_client_service;
+pub use traced_http_client_service::*;
+
+pub mod traced_message_digest_service;
+pub use traced_message_digest_service::*;
@@ -0,0 +1,40 @@
+use std::marker::PhantomData;
+use std::time::Duration;
+
+/// A provider decorating another provider by emitting [tracing] spans, and
+/// events for calls to it
+///
+/// It's bound to a component in place of the decorated provider, e.g.,
+/// `GetUrlServiceComponent: Traced<ReqwestHttpClientService>`.  Decorated
+/// providers emit the following spans, and an event when the traced phase
+/// finishes, with its duration in `duration_ms`, or the error details in
+/// `error`:
+///
+/// * `calculate_web_page_message_digest`, with the `url` field, from
+///   `CalculateWebPageMessageDigestService` providers;
+/// * `fetch`, with the `url` field, from `GetUrlService` providers, and the
+///   `stream` span of streaming the response body, with the streamed
+///   `byte_count`;
+/// * `digest`, from `NewDigestCalculatorService` providers, with the hashed
+///   `byte_count`.
+///
+/// Spans of providers called by a decorated calculate Web page message digest
+/// provider through the context are nested in its span.
+pub struct Traced<InnerProvider>(pub PhantomData<InnerProvider>);
+
+pub(crate) fn duration_as_millis(duration: Duration) -> u64 {
+    duration.as_millis().try_into().unwrap_or(u64::MAX)
+}
+
+/// Returns the description of an error followed by descriptions of the
+/// errors that caused it, outermost first
+pub(crate) fn error_details(error: &dyn std::error::Error) -> String {
+    std::iter::successors(error.source(), |cause| cause.source()).fold(
+        error.to_string(),
+        |mut details, cause| {
+            details.push_str(": ");
+            details.push_str(&cause.to_string());
+            details
+        },
+    )
+}
@@ -0,0 +1,58 @@
+use super::traced::{Traced, duration_as_millis};
+use crate::calculate_web_page_message_digest_service::{
+    CalculateWebPageMessageDigestService, CalculateWebPageMessageDigestServiceComponent,
+    HasDigestType,
+};
+use crate::clock_service::Clock;
+use crate::credential_provider_service::redact_url_credentials;
+use crate::http_client_service::HasUrlType;
+use cgp::prelude::*;
+use std::fmt;
+use tracing::Instrument;
+
+#[cgp_impl(Traced<InnerProvider>)]
+impl<Context, InnerProvider> CalculateWebPageMessageDigestService for Context
+where
+    Context: HasUrlType + HasDigestType + HasErrorType + Clock,
+    <Context as HasUrlType>::Url: AsRef<str>,
+    <Context as HasDigestType>::Digest: AsRef<[u8]>,
+    <Context as HasErrorType>::Error: fmt::Display,
+    InnerProvider: CalculateWebPageMessageDigestService<Context>,
+{
+    async fn calculate_web_page_message_digest(
+        context: &Context,
+        url: &Context::Url,
+    ) -> Result<<Context as HasDigestType>::Digest, <Context as HasErrorType>::Error> {
+        let span = tracing::info_span!(
+            "calculate_web_page_message_digest",
+            url = redact_url_credentials(url.as_ref()).as_ref()
+        );
+
+        async {
+            let started = context.now();
+            let digest_result =
+                InnerProvider::calculate_web_page_message_digest(context, url).await;
+            let duration_ms =
+                duration_as_millis(context.now().duration_since(started).unwrap_or_default());
+
+            match &digest_result {
+                Ok(digest) => tracing::info!(
+                    digest = hex::encode(digest),
+                    duration_ms,
+                    "calculated the Web page message digest"
+                ),
+                // The alternate format includes causes, e.g., of
+                // [anyhow::Error]s:
+                Err(error) => tracing::error!(
+                    error = format!("{:#}", error),
+                    duration_ms,
+                    "calculating the Web page message digest failed"
+                ),
+            }
+
+            digest_result
+        }
+        .instrument(span)
+        .await
+    }
+}
@@ -0,0 +1,144 @@
+use super::traced::{Traced, duration_as_millis, error_details};
+use crate::credential_provider_service::redact_url_credentials;
+use crate::http_client_service::{
+    GetUrlService, GetUrlServiceComponent, HasUrlType, IntoChunkStream, ResponseMetadata,
+};
+use bytes::Bytes;
+use cgp::prelude::*;
+use futures_core::stream::Stream;
+use futures_util::StreamExt;
+use http::{HeaderMap, StatusCode};
+use std::pin::Pin;
+use std::task::{Context as TaskContext, Poll};
+use std::time::{Duration, Instant};
+use tracing::{Instrument, Span};
+
+#[cgp_impl(Traced<InnerProvider>)]
+impl<Context, InnerProvider> GetUrlService for Context
+where
+    Context: HasUrlType,
+    Context::Url: AsRef<str>,
+    InnerProvider: GetUrlService<Context>,
+    InnerProvider::Error: std::error::Error,
+{
+    type HttpResponse = TracedHttpResponse<InnerProvider::HttpResponse>;
+    type Error = InnerProvider::Error;
+
+    async fn get_url(
+        context: &Context,
+        url: &Context::Url,
+    ) -> Result<Self::HttpResponse, Self::Error> {
+        let redacted_url = redact_url_credentials(url.as_ref());
+
+        async {
+            let started = Instant::now();
+            let response_result = InnerProvider::get_url(context, url).await;
+            let duration_ms = duration_as_millis(started.elapsed());
+
+            match response_result {
+                Ok(response) => {
+                    tracing::info!(duration_ms, "fetched the response headers");
+
+                    Ok(TracedHttpResponse {
+                        response,
+                        url: redacted_url.to_string(),
+                    })
+                }
+                Err(error) => {
+                    tracing::error!(error = error_details(&error), duration_ms, "fetch failed");
+
+                    Err(error)
+                }
+            }
+        }
+        .instrument(tracing::info_span!("fetch", url = redacted_url.as_ref()))
+        .await
+    }
+}
+
+/// A response returned by a [Traced] `GetUrlService` provider
+///
+/// Its chunk stream is traced in a `stream` span.
+#[derive(Debug)]
+pub struct TracedHttpResponse<HttpResponse> {
+    response: HttpResponse,
+    url: String,
+}
+
+impl<HttpResponse: ResponseMetadata> ResponseMetadata for TracedHttpResponse<HttpResponse> {
+    fn status(&self) -> StatusCode {
+        self.response.status()
+    }
+
+    fn headers(&self) -> &HeaderMap {
+        self.response.headers()
+    }
+
+    fn content_type(&self) -> Option<&str> {
+        self.response.content_type()
+    }
+
+    fn content_length(&self) -> Option<u64> {
+        self.response.content_length()
+    }
+}
+
+impl<HttpResponse> IntoChunkStream for TracedHttpResponse<HttpResponse>
+where
+    HttpResponse: IntoChunkStream,
+    HttpResponse::Error: std::error::Error,
+{
+    type Error = HttpResponse::Error;
+
+    fn into_chunk_stream(self) -> impl Stream<Item = Result<Bytes, Self::Error>> + Unpin {
+        TracedChunkStream {
+            chunk_stream: self.response.into_chunk_stream(),
+            span: tracing::info_span!("stream", url = self.url),
+            byte_count: 0,
+            streaming_duration: Duration::ZERO,
+        }
+    }
+}
+
+struct TracedChunkStream<ChunkStream> {
+    chunk_stream: ChunkStream,
+    span: Span,
+    byte_count: u64,
+    /// The time spent polling the stream, excluding the time spent
+    /// processing its chunks
+    streaming_duration: Duration,
+}
+
+impl<ChunkStream, Error> Stream for TracedChunkStream<ChunkStream>
+where
+    ChunkStream: Stream<Item = Result<Bytes, Error>> + Unpin,
+    Error: std::error::Error,
+{
+    type Item = Result<Bytes, Error>;
+
+    fn poll_next(self: Pin<&mut Self>, context: &mut TaskContext<'_>) -> Poll<Option<Self::Item>> {
+        let traced_chunk_stream = self.get_mut();
+        let _entered_span = traced_chunk_stream.span.enter();
+        let started = Instant::now();
+        let poll = traced_chunk_stream.chunk_stream.poll_next_unpin(context);
+        traced_chunk_stream.streaming_duration += started.elapsed();
+        let byte_count = traced_chunk_stream.byte_count;
+        let duration_ms = duration_as_millis(traced_chunk_stream.streaming_duration);
+
+        match &poll {
+            Poll::Ready(Some(Ok(chunk))) => traced_chunk_stream.byte_count += chunk.len() as u64,
+            Poll::Ready(Some(Err(error))) => tracing::error!(
+                byte_count,
+                error = error_details(error),
+                duration_ms,
+                "streaming the response body failed"
+            ),
+            Poll::Ready(None) => {
+                tracing::info!(byte_count, duration_ms, "streamed the response body")
+            }
+            Poll::Pending => {}
+        }
+
+        poll
+    }
+}
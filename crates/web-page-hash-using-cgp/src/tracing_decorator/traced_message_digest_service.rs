@@ -0,0 +1,106 @@
+use super::traced::{Traced, duration_as_millis, error_details};
+use crate::message_digest_service::{
+    IntoDigestOctets, NewDigestCalculatorService, NewDigestCalculatorServiceComponent,
+};
+use cgp::prelude::*;
+use std::io::{self, Write};
+use std::time::{Duration, Instant};
+use tracing::Span;
+
+#[cgp_impl(Traced<InnerProvider>)]
+impl<Context, InnerProvider> NewDigestCalculatorService for Context
+where
+    InnerProvider: NewDigestCalculatorService<Context>,
+    InnerProvider::Error: std::error::Error,
+{
+    type DigestCalculator = TracedDigestCalculator<InnerProvider::DigestCalculator>;
+    type Error = InnerProvider::Error;
+
+    fn new_digest_calculator(context: &Context) -> Result<Self::DigestCalculator, Self::Error> {
+        let span = tracing::info_span!("digest");
+
+        match InnerProvider::new_digest_calculator(context) {
+            Ok(digest_calculator) => Ok(TracedDigestCalculator {
+                digest_calculator,
+                span,
+                byte_count: 0,
+                hashing_duration: Duration::ZERO,
+            }),
+            Err(error) => {
+                span.in_scope(|| {
+                    tracing::error!(
+                        error = error_details(&error),
+                        "creating a digest calculator failed"
+                    )
+                });
+
+                Err(error)
+            }
+        }
+    }
+}
+
+/// A digest calculator returned by a [Traced] `NewDigestCalculatorService`
+/// provider
+///
+/// The number of hashed bytes, and the time spent hashing them are traced in
+/// a `digest` span, when the digest is calculated.
+#[derive(Debug)]
+pub struct TracedDigestCalculator<DigestCalculator> {
+    digest_calculator: DigestCalculator,
+    span: Span,
+    byte_count: u64,
+    hashing_duration: Duration,
+}
+
+impl<DigestCalculator: Write> Write for TracedDigestCalculator<DigestCalculator> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let started = Instant::now();
+        let write_result = self.digest_calculator.write(buf);
+        self.hashing_duration += started.elapsed();
+
+        if let Ok(length) = write_result {
+            self.byte_count += length as u64;
+        }
+
+        write_result
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.digest_calculator.flush()
+    }
+}
+
+impl<DigestCalculator> IntoDigestOctets for TracedDigestCalculator<DigestCalculator>
+where
+    DigestCalculator: IntoDigestOctets,
+    DigestCalculator::Error: std::error::Error,
+{
+    type DigestOctets = DigestCalculator::DigestOctets;
+    type Error = DigestCalculator::Error;
+
+    fn into_digest_octets(self) -> Result<Self::DigestOctets, Self::Error> {
+        let TracedDigestCalculator {
+            digest_calculator,
+            span,
+            byte_count,
+            hashing_duration,
+        } = self;
+        let _entered_span = span.enter();
+        let started = Instant::now();
+        let digest_result = digest_calculator.into_digest_octets();
+        let duration_ms = duration_as_millis(hashing_duration + started.elapsed());
+
+        match &digest_result {
+            Ok(_) => tracing::info!(byte_count, duration_ms, "calculated the digest"),
+            Err(error) => tracing::error!(
+                byte_count,
+                error = error_details(error),
+                duration_ms,
+                "calculating the digest failed"
+            ),
+        }
+
+        digest_result
+    }
+}
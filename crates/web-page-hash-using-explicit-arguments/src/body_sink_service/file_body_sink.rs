@@ -0,0 +1,289 @@
+use super::interface::{
+    BodyCheckpoint, BodySink, CheckpointBody, CommitBody, NewBodyWriter, ResumeBodyWriter,
+};
+use crate::digest_encoding::ExpectedDigest;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// The file name used for URLs whose path doesn't end in a file name
+const DEFAULT_FILE_NAME: &str = "index.html";
+
+/// Where a [FileBodySink] saves response bodies
+#[derive(Clone, Debug)]
+pub enum BodyDestination {
+    /// Every body is saved to this file
+    File(PathBuf),
+    /// Each body is saved in this directory, named after the last segment of
+    /// the URL path
+    Directory(PathBuf),
+}
+
+/// A [BodySink] saving response bodies to files
+///
+/// A body is written to a `.partial` file next to its destination, and
+/// renamed to the destination once its digest is final, so that the
+/// destination never holds an incomplete body.  With an expected digest the
+/// body is kept only if its digest matches, and with a required final URL
+/// only if it was served from that URL after following redirects.
+///
+/// A checkpointed body is kept in its `.partial` file after an interruption,
+/// with the checkpoint in a `.checkpoint` file next to it, so that a later
+/// run can resume writing it.
+#[derive(Debug)]
+pub struct FileBodySink {
+    destination: BodyDestination,
+    expected_digest: Option<ExpectedDigest>,
+    required_final_url: Option<url::Url>,
+}
+
+impl FileBodySink {
+    pub fn new(destination: BodyDestination, expected_digest: Option<ExpectedDigest>) -> Self {
+        FileBodySink {
+            destination,
+            expected_digest,
+            required_final_url: None,
+        }
+    }
+
+    /// Returns this sink keeping only bodies served from `required_final_url`
+    /// after following redirects
+    pub fn with_required_final_url(self, required_final_url: url::Url) -> Self {
+        FileBodySink {
+            required_final_url: Some(required_final_url),
+            ..self
+        }
+    }
+
+    fn destination_path(&self, url: &str) -> io::Result<PathBuf> {
+        match &self.destination {
+            BodyDestination::File(path) => Ok(path.clone()),
+            BodyDestination::Directory(directory) => Ok(directory.join(file_name_from_url(url)?)),
+        }
+    }
+}
+
+impl NewBodyWriter for FileBodySink {
+    type BodyWriter = FileBodyWriter;
+    type Error = io::Error;
+
+    fn new_body_writer(&self, url: &str) -> Result<Self::BodyWriter, Self::Error> {
+        let path = self.destination_path(url)?;
+        let partial_path = partial_path(&path);
+        // A new body replaces the checkpointed one:
+        remove_file_if_exists(&checkpoint_path(&partial_path))?;
+        let partial_file = File::create(&partial_path)?;
+
+        Ok(FileBodyWriter {
+            path,
+            partial_path,
+            partial_file,
+            expected_digest: self.expected_digest.clone(),
+            required_final_url: self.required_final_url.clone(),
+            checkpointed: false,
+            committed: false,
+        })
+    }
+}
+
+impl ResumeBodyWriter for FileBodySink {
+    /// Returns [None] also when the checkpoint is corrupt, belongs to another
+    /// URL, or is ahead of its partial file
+    fn resume_body_writer(
+        &self,
+        url: &str,
+    ) -> Result<Option<(Self::BodyWriter, BodyCheckpoint)>, Self::Error> {
+        let path = self.destination_path(url)?;
+        let partial_path = partial_path(&path);
+        let Some(checkpoint) = read_checkpoint(&checkpoint_path(&partial_path))? else {
+            return Ok(None);
+        };
+        if checkpoint.url != url {
+            return Ok(None);
+        }
+        let mut partial_file = match File::options().write(true).open(&partial_path) {
+            Ok(partial_file) => partial_file,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(error) => return Err(error),
+        };
+        if partial_file.metadata()?.len() < checkpoint.byte_count {
+            return Ok(None);
+        }
+        // Octets written after the checkpoint are received again:
+        partial_file.set_len(checkpoint.byte_count)?;
+        io::Seek::seek(&mut partial_file, io::SeekFrom::End(0))?;
+
+        Ok(Some((
+            FileBodyWriter {
+                path,
+                partial_path,
+                partial_file,
+                expected_digest: self.expected_digest.clone(),
+                required_final_url: self.required_final_url.clone(),
+                checkpointed: true,
+                committed: false,
+            },
+            checkpoint,
+        )))
+    }
+}
+
+impl BodySink for FileBodySink {}
+
+/// A body writer returned by [FileBodySink]
+#[derive(Debug)]
+pub struct FileBodyWriter {
+    path: PathBuf,
+    partial_path: PathBuf,
+    partial_file: File,
+    expected_digest: Option<ExpectedDigest>,
+    required_final_url: Option<url::Url>,
+    checkpointed: bool,
+    committed: bool,
+}
+
+impl Write for FileBodyWriter {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        self.partial_file.write(data)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.partial_file.flush()
+    }
+}
+
+impl CommitBody for FileBodyWriter {
+    type Error = io::Error;
+
+    /// Renames the partial file to the destination, unless none of `digests`
+    /// matches the expected digest, or `final_url` isn't the required final
+    /// URL, in which case the body is discarded
+    fn commit_body(mut self, digests: &[&[u8]], final_url: &str) -> Result<(), Self::Error> {
+        // A complete body is never resumed, whether it's kept or not:
+        self.checkpointed = false;
+        remove_file_if_exists(&checkpoint_path(&self.partial_path))?;
+
+        if let Some(expected_digest) = &self.expected_digest
+            && !digests.iter().any(|digest| expected_digest.matches(digest))
+        {
+            return Ok(());
+        }
+        if let Some(required_final_url) = &self.required_final_url
+            && url::Url::parse(final_url).ok().as_ref() != Some(required_final_url)
+        {
+            return Ok(());
+        }
+
+        self.partial_file.sync_all()?;
+        fs::rename(&self.partial_path, &self.path)?;
+        self.committed = true;
+
+        Ok(())
+    }
+}
+
+impl CheckpointBody for FileBodyWriter {
+    type Error = io::Error;
+
+    /// Saves `checkpoint` after the body written so far is on disk, replacing
+    /// any previous checkpoint
+    fn checkpoint_body(&mut self, checkpoint: &BodyCheckpoint) -> Result<(), Self::Error> {
+        self.partial_file.sync_data()?;
+        write_checkpoint(&checkpoint_path(&self.partial_path), checkpoint)?;
+        self.checkpointed = true;
+
+        Ok(())
+    }
+}
+
+impl Drop for FileBodyWriter {
+    fn drop(&mut self) {
+        if !self.committed && !self.checkpointed {
+            // An incomplete, or unexpected body must not be kept, unless it
+            // can be resumed.  There's nothing useful to do if removing it
+            // fails.
+            let _ = fs::remove_file(&self.partial_path);
+        }
+    }
+}
+
+/// A [BodyCheckpoint] as saved in a `.checkpoint` file
+#[derive(Debug, Deserialize, Serialize)]
+struct CheckpointFile {
+    url: String,
+    validator: String,
+    byte_count: u64,
+    /// The hexadecimal digest calculator state
+    digest_state: String,
+}
+
+fn read_checkpoint(checkpoint_path: &Path) -> io::Result<Option<BodyCheckpoint>> {
+    let json = match fs::read(checkpoint_path) {
+        Ok(json) => json,
+        Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(error) => return Err(error),
+    };
+    // A corrupt checkpoint is treated as a missing one:
+    let Ok(checkpoint_file) = serde_json::from_slice::<CheckpointFile>(&json) else {
+        return Ok(None);
+    };
+    let Ok(digest_state) = hex::decode(&checkpoint_file.digest_state) else {
+        return Ok(None);
+    };
+
+    Ok(Some(BodyCheckpoint {
+        url: checkpoint_file.url,
+        validator: checkpoint_file.validator,
+        byte_count: checkpoint_file.byte_count,
+        digest_state,
+    }))
+}
+
+fn write_checkpoint(checkpoint_path: &Path, checkpoint: &BodyCheckpoint) -> io::Result<()> {
+    let checkpoint_file = CheckpointFile {
+        url: checkpoint.url.clone(),
+        validator: checkpoint.validator.clone(),
+        byte_count: checkpoint.byte_count,
+        digest_state: hex::encode(&checkpoint.digest_state),
+    };
+    let partial_checkpoint_path = partial_path(checkpoint_path);
+
+    fs::write(
+        &partial_checkpoint_path,
+        serde_json::to_vec_pretty(&checkpoint_file)?,
+    )?;
+    fs::rename(partial_checkpoint_path, checkpoint_path)
+}
+
+fn remove_file_if_exists(path: &Path) -> io::Result<()> {
+    match fs::remove_file(path) {
+        Err(error) if error.kind() != io::ErrorKind::NotFound => Err(error),
+        _ => Ok(()),
+    }
+}
+
+/// Returns the last segment of the URL path, or [DEFAULT_FILE_NAME], when
+/// it's empty
+fn file_name_from_url(url: &str) -> io::Result<String> {
+    let url =
+        url::Url::parse(url).map_err(|error| io::Error::new(io::ErrorKind::InvalidInput, error))?;
+    let file_name = url
+        .path_segments()
+        .and_then(|mut segments| segments.next_back())
+        .filter(|segment| !matches!(*segment, "" | "." | ".."))
+        .unwrap_or(DEFAULT_FILE_NAME);
+
+    Ok(file_name.to_string())
+}
+
+fn partial_path(path: &Path) -> PathBuf {
+    let mut partial_path = path.as_os_str().to_owned();
+    partial_path.push(".partial");
+
+    PathBuf::from(partial_path)
+}
+
+fn checkpoint_path(partial_path: &Path) -> PathBuf {
+    partial_path.with_extension("checkpoint")
+}
@@ -0,0 +1,72 @@
+/// A function trait creating a writer for the body of a response
+///
+/// A body writer receives the same chunks as the digest calculator, and is
+/// committed once the digest is final.
+pub trait NewBodyWriter {
+    type BodyWriter;
+    type Error;
+
+    fn new_body_writer(&self, url: &str) -> Result<Self::BodyWriter, Self::Error>;
+
+    /// Returns whether bodies are kept, so that they need streaming even
+    /// when their digest is memoized
+    fn keeps_bodies(&self) -> bool {
+        true
+    }
+}
+
+/// A function trait for finishing a fully written response body
+///
+/// The body writer decides, given the final message digests, e.g., one per
+/// algorithm, and the URL the body was served from after following
+/// redirects, whether the body is kept.
+/// A body writer dropped without being committed discards the body, e.g.,
+/// when streaming fails.
+pub trait CommitBody {
+    type Error;
+
+    fn commit_body(self, digests: &[&[u8]], final_url: &str) -> Result<(), Self::Error>;
+}
+
+/// The progress of an interrupted response body, saved next to its
+/// partially written body
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BodyCheckpoint {
+    pub url: String,
+    /// The validator of the response (see
+    /// [ResponseMetadata::validator](crate::http_client_service::ResponseMetadata::validator)),
+    /// so that the rest of the body is requested only if it hasn't changed
+    pub validator: String,
+    /// The number of body octets written before the checkpoint
+    pub byte_count: u64,
+    /// The saved state of the digest calculator after `byte_count` octets
+    /// (see [SaveDigestState](crate::message_digest_service::SaveDigestState))
+    pub digest_state: Vec<u8>,
+}
+
+/// A function trait resuming a body writer from its last checkpoint
+///
+/// Returns [None], when there's no checkpoint for `url`.  The returned body
+/// writer continues after the `byte_count` octets of the checkpoint.
+pub trait ResumeBodyWriter: NewBodyWriter {
+    fn resume_body_writer(
+        &self,
+        url: &str,
+    ) -> Result<Option<(Self::BodyWriter, BodyCheckpoint)>, Self::Error>;
+}
+
+/// A function trait saving the progress of a partially written body, so that
+/// it can be resumed with [ResumeBodyWriter] after an interruption
+pub trait CheckpointBody {
+    type Error;
+
+    fn checkpoint_body(&mut self, checkpoint: &BodyCheckpoint) -> Result<(), Self::Error>;
+}
+
+// The full service is composed of its method, and function traits:
+/// An injectable service that receives response bodies while they're hashed
+pub trait BodySink: NewBodyWriter
+where
+    <Self as NewBodyWriter>::BodyWriter: CommitBody + std::io::Write,
+{
+}
@@ -0,0 +1,115 @@
+use super::interface::{
+    BodyCheckpoint, BodySink, CheckpointBody, CommitBody, NewBodyWriter, ResumeBodyWriter,
+};
+use std::convert::Infallible;
+use std::io;
+use std::sync::{Arc, Mutex, PoisonError};
+
+/// A response body committed to a [MemoryBodySink]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CapturedBody {
+    pub url: String,
+    pub body: Vec<u8>,
+    pub digests: Vec<Vec<u8>>,
+}
+
+/// A [BodySink] capturing committed response bodies in memory, e.g., for
+/// checking them in tests, or extracting links while crawling
+///
+/// Clones share the captured bodies.
+#[derive(Clone, Debug, Default)]
+pub struct MemoryBodySink {
+    bodies: Arc<Mutex<Vec<CapturedBody>>>,
+}
+
+impl MemoryBodySink {
+    pub fn new() -> Self {
+        MemoryBodySink {
+            bodies: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    /// Returns the bodies committed so far, in order
+    pub fn bodies(&self) -> Vec<CapturedBody> {
+        self.bodies
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Removes, and returns the body committed last for `url`, if any
+    pub fn take_body(&self, url: &str) -> Option<CapturedBody> {
+        let mut bodies = self.bodies.lock().unwrap_or_else(PoisonError::into_inner);
+        let index = bodies.iter().rposition(|captured| captured.url == url)?;
+
+        Some(bodies.remove(index))
+    }
+}
+
+impl NewBodyWriter for MemoryBodySink {
+    type BodyWriter = MemoryBodyWriter;
+    type Error = Infallible;
+
+    fn new_body_writer(&self, url: &str) -> Result<Self::BodyWriter, Self::Error> {
+        Ok(MemoryBodyWriter {
+            bodies: self.bodies.clone(),
+            url: url.to_string(),
+            body: Vec::new(),
+        })
+    }
+}
+
+impl ResumeBodyWriter for MemoryBodySink {
+    /// Returns [None], since uncommitted bodies aren't kept
+    fn resume_body_writer(
+        &self,
+        _url: &str,
+    ) -> Result<Option<(Self::BodyWriter, BodyCheckpoint)>, Self::Error> {
+        Ok(None)
+    }
+}
+
+impl BodySink for MemoryBodySink {}
+
+/// A body writer returned by [MemoryBodySink]
+#[derive(Debug)]
+pub struct MemoryBodyWriter {
+    bodies: Arc<Mutex<Vec<CapturedBody>>>,
+    url: String,
+    body: Vec<u8>,
+}
+
+impl io::Write for MemoryBodyWriter {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        self.body.write(data)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl CheckpointBody for MemoryBodyWriter {
+    type Error = Infallible;
+
+    fn checkpoint_body(&mut self, _checkpoint: &BodyCheckpoint) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+impl CommitBody for MemoryBodyWriter {
+    type Error = Infallible;
+
+    fn commit_body(self, digests: &[&[u8]], _final_url: &str) -> Result<(), Self::Error> {
+        self.bodies
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push(CapturedBody {
+                url: self.url,
+                body: self.body,
+                digests: digests.iter().map(|digest| digest.to_vec()).collect(),
+            });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,14 @@
+pub mod interface;
+pub use interface::*;
+
+pub mod file_body_sink;
+pub use file_body_sink::*;
+
+pub mod memory_body_sink;
+pub use memory_body_sink::*;
+
+pub mod no_body_sink;
+pub use no_body_sink::*;
+
+pub mod selectable_body_sink;
+pub use selectable_body_sink::*;
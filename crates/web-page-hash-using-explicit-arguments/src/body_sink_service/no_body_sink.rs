@@ -0,0 +1,56 @@
+use super::interface::{
+    BodyCheckpoint, BodySink, CheckpointBody, CommitBody, NewBodyWriter, ResumeBodyWriter,
+};
+use std::convert::Infallible;
+use std::io;
+
+impl CommitBody for io::Sink {
+    type Error = Infallible;
+
+    fn commit_body(self, _digests: &[&[u8]], _final_url: &str) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+impl CheckpointBody for io::Sink {
+    type Error = Infallible;
+
+    fn checkpoint_body(&mut self, _checkpoint: &BodyCheckpoint) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+/// A [BodySink] discarding response bodies
+#[derive(Debug, Default)]
+pub struct NoBodySink {}
+
+impl NoBodySink {
+    pub fn new() -> Self {
+        NoBodySink {}
+    }
+}
+
+impl NewBodyWriter for NoBodySink {
+    type BodyWriter = io::Sink;
+    type Error = Infallible;
+
+    fn new_body_writer(&self, _url: &str) -> Result<Self::BodyWriter, Self::Error> {
+        Ok(io::sink())
+    }
+
+    fn keeps_bodies(&self) -> bool {
+        false
+    }
+}
+
+impl ResumeBodyWriter for NoBodySink {
+    /// Returns [None], since there are no bodies to resume
+    fn resume_body_writer(
+        &self,
+        _url: &str,
+    ) -> Result<Option<(Self::BodyWriter, BodyCheckpoint)>, Self::Error> {
+        Ok(None)
+    }
+}
+
+impl BodySink for NoBodySink {}
@@ -0,0 +1,137 @@
+use super::file_body_sink::{FileBodySink, FileBodyWriter};
+use super::interface::{
+    BodyCheckpoint, BodySink, CheckpointBody, CommitBody, NewBodyWriter, ResumeBodyWriter,
+};
+use super::memory_body_sink::{MemoryBodySink, MemoryBodyWriter};
+use super::no_body_sink::NoBodySink;
+use std::io::{self, Write};
+
+/// A [BodySink] selected at run time
+#[derive(Debug)]
+pub enum SelectableBodySink {
+    None(NoBodySink),
+    File(FileBodySink),
+    Memory(MemoryBodySink),
+}
+
+impl NewBodyWriter for SelectableBodySink {
+    type BodyWriter = SelectableBodyWriter;
+    type Error = io::Error;
+
+    fn new_body_writer(&self, url: &str) -> Result<Self::BodyWriter, Self::Error> {
+        Ok(match self {
+            SelectableBodySink::None(sink) => {
+                let Ok(body_writer) = sink.new_body_writer(url);
+                SelectableBodyWriter::None(body_writer)
+            }
+            SelectableBodySink::File(sink) => {
+                SelectableBodyWriter::File(sink.new_body_writer(url)?)
+            }
+            SelectableBodySink::Memory(sink) => {
+                let Ok(body_writer) = sink.new_body_writer(url);
+                SelectableBodyWriter::Memory(body_writer)
+            }
+        })
+    }
+
+    fn keeps_bodies(&self) -> bool {
+        match self {
+            SelectableBodySink::None(sink) => sink.keeps_bodies(),
+            SelectableBodySink::File(sink) => sink.keeps_bodies(),
+            SelectableBodySink::Memory(sink) => sink.keeps_bodies(),
+        }
+    }
+}
+
+impl ResumeBodyWriter for SelectableBodySink {
+    fn resume_body_writer(
+        &self,
+        url: &str,
+    ) -> Result<Option<(Self::BodyWriter, BodyCheckpoint)>, Self::Error> {
+        match self {
+            SelectableBodySink::None(sink) => {
+                let Ok(resumed) = sink.resume_body_writer(url);
+                Ok(resumed.map(|(body_writer, checkpoint)| {
+                    (SelectableBodyWriter::None(body_writer), checkpoint)
+                }))
+            }
+            SelectableBodySink::File(sink) => {
+                Ok(sink
+                    .resume_body_writer(url)?
+                    .map(|(body_writer, checkpoint)| {
+                        (SelectableBodyWriter::File(body_writer), checkpoint)
+                    }))
+            }
+            SelectableBodySink::Memory(sink) => {
+                let Ok(resumed) = sink.resume_body_writer(url);
+                Ok(resumed.map(|(body_writer, checkpoint)| {
+                    (SelectableBodyWriter::Memory(body_writer), checkpoint)
+                }))
+            }
+        }
+    }
+}
+
+impl BodySink for SelectableBodySink {}
+
+/// A body writer returned by [SelectableBodySink]
+#[derive(Debug)]
+pub enum SelectableBodyWriter {
+    None(io::Sink),
+    File(FileBodyWriter),
+    Memory(MemoryBodyWriter),
+}
+
+impl Write for SelectableBodyWriter {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        match self {
+            SelectableBodyWriter::None(body_writer) => body_writer.write(data),
+            SelectableBodyWriter::File(body_writer) => body_writer.write(data),
+            SelectableBodyWriter::Memory(body_writer) => body_writer.write(data),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            SelectableBodyWriter::None(body_writer) => body_writer.flush(),
+            SelectableBodyWriter::File(body_writer) => body_writer.flush(),
+            SelectableBodyWriter::Memory(body_writer) => body_writer.flush(),
+        }
+    }
+}
+
+impl CommitBody for SelectableBodyWriter {
+    type Error = io::Error;
+
+    fn commit_body(self, digests: &[&[u8]], final_url: &str) -> Result<(), Self::Error> {
+        match self {
+            SelectableBodyWriter::None(body_writer) => {
+                let Ok(()) = body_writer.commit_body(digests, final_url);
+                Ok(())
+            }
+            SelectableBodyWriter::File(body_writer) => body_writer.commit_body(digests, final_url),
+            SelectableBodyWriter::Memory(body_writer) => {
+                let Ok(()) = body_writer.commit_body(digests, final_url);
+                Ok(())
+            }
+        }
+    }
+}
+
+impl CheckpointBody for SelectableBodyWriter {
+    type Error = io::Error;
+
+    fn checkpoint_body(&mut self, checkpoint: &BodyCheckpoint) -> Result<(), Self::Error> {
+        match self {
+            SelectableBodyWriter::None(body_writer) => {
+                let Ok(()) = body_writer.checkpoint_body(checkpoint);
+                Ok(())
+            }
+            SelectableBodyWriter::File(body_writer) => body_writer.checkpoint_body(checkpoint),
+            SelectableBodyWriter::Memory(body_writer) => {
+                let Ok(()) = body_writer.checkpoint_body(checkpoint);
+                Ok(())
+            }
+        }
+    }
+}
@@ -1,10 +1,29 @@
-use super::interface::CalculateWebPageMessageDigest;
-use crate::http_client_service::{GetUrl, IntoChunkStream};
-use crate::message_digest_service::{IntoDigestOctets, NewDigestCalculator};
+use super::interface::{
+    CalculateWebPageMessageDigest, CalculateWebPageMessageDigestWithMetadata, WebPageMessageDigest,
+};
+use crate::body_sink_service::{
+    BodyCheckpoint, CheckpointBody, CommitBody, NewBodyWriter, ResumeBodyWriter,
+};
+use crate::content_decoder_service::{DecodeBody, NewBodyDecoder, RawContentDecoder};
+use crate::content_transformer_service::{
+    IdentityContentTransformer, NewContentTransform, TransformContent,
+};
+use crate::digest_memo_service::{MemoizeDigest, RecallDigest};
+use crate::http_client_service::{GetUrl, GetUrlRange, IntoChunkStream, ResponseMetadata};
+use crate::message_digest_service::{
+    DigestAlgorithmName, DigestParts, IntoDigestOctets, NewDigestCalculator,
+    RestoreDigestCalculator, SaveDigestState,
+};
+use crate::progress_reporter_service::{ReportBytesWritten, ReportFinished, ReportStarted};
+use crate::response_policy_service::{CheckBodySize, CheckResponseMetadata};
 use futures_util::StreamExt;
+use http::StatusCode;
 use std::io::Write;
 use std::marker::PhantomData;
 
+/// The number of body octets between checkpoints of a resumable body (1 MiB)
+const CHECKPOINT_INTERVAL: u64 = 1024 * 1024;
+
 #[derive(Debug)]
 pub struct CalculateWebPageMessageDigestService<
     Url,
@@ -12,9 +31,21 @@ pub struct CalculateWebPageMessageDigestService<
     Error: Sync,
     HttpClientService: GetUrl<Url = Url>,
     MessageDigestService: NewDigestCalculator + Send,
+    DigestMemoService,
+    ProgressReporter,
+    ResponsePolicy,
+    BodySink,
+    ContentTransformer = IdentityContentTransformer,
+    ContentDecoder = RawContentDecoder,
 > {
     http_client_service: HttpClientService,
     message_digest_service: MessageDigestService,
+    digest_memo_service: DigestMemoService,
+    progress_reporter: ProgressReporter,
+    response_policy: ResponsePolicy,
+    body_sink: BodySink,
+    content_transformer: ContentTransformer,
+    content_decoder: ContentDecoder,
     _digest_type_marker: PhantomData<Digest>,
     _error_type_marker: PhantomData<Error>,
 }
@@ -25,6 +56,10 @@ impl<
     Error: Sync,
     HttpClientService: GetUrl<Url = Url>,
     MessageDigestService: NewDigestCalculator + Send,
+    DigestMemoService,
+    ProgressReporter,
+    ResponsePolicy,
+    BodySink,
 >
     CalculateWebPageMessageDigestService<
         Url,
@@ -32,11 +67,22 @@ impl<
         Error,
         HttpClientService,
         MessageDigestService,
+        DigestMemoService,
+        ProgressReporter,
+        ResponsePolicy,
+        BodySink,
+        IdentityContentTransformer,
+        RawContentDecoder,
     >
 {
+    /// Returns a service hashing response bodies as they are
     pub fn new(
         http_client_service: HttpClientService,
         message_digest_service: MessageDigestService,
+        digest_memo_service: DigestMemoService,
+        progress_reporter: ProgressReporter,
+        response_policy: ResponsePolicy,
+        body_sink: BodySink,
     ) -> Self {
         CalculateWebPageMessageDigestService::<
             Url,
@@ -44,9 +90,119 @@ impl<
             Error,
             HttpClientService,
             MessageDigestService,
+            DigestMemoService,
+            ProgressReporter,
+            ResponsePolicy,
+            BodySink,
         > {
             http_client_service,
             message_digest_service,
+            digest_memo_service,
+            progress_reporter,
+            response_policy,
+            body_sink,
+            content_transformer: IdentityContentTransformer::new(),
+            content_decoder: RawContentDecoder::new(),
+            _digest_type_marker: PhantomData,
+            _error_type_marker: PhantomData,
+        }
+    }
+}
+
+impl<
+    Url,
+    Digest,
+    Error: Sync,
+    HttpClientService: GetUrl<Url = Url>,
+    MessageDigestService: NewDigestCalculator + Send,
+    DigestMemoService,
+    ProgressReporter,
+    ResponsePolicy,
+    BodySink,
+    ContentTransformer,
+    ContentDecoder,
+>
+    CalculateWebPageMessageDigestService<
+        Url,
+        Digest,
+        Error,
+        HttpClientService,
+        MessageDigestService,
+        DigestMemoService,
+        ProgressReporter,
+        ResponsePolicy,
+        BodySink,
+        ContentTransformer,
+        ContentDecoder,
+    >
+{
+    /// Returns this service transforming response bodies with a content
+    /// transformer before they're hashed
+    ///
+    /// Digests of transformed bodies aren't memoized, and transformed bodies
+    /// aren't checkpointed, or resumed, since the state of content transforms
+    /// isn't saved.  Body sinks still receive the raw bodies.
+    pub fn with_content_transformer<OtherContentTransformer>(
+        self,
+        content_transformer: OtherContentTransformer,
+    ) -> CalculateWebPageMessageDigestService<
+        Url,
+        Digest,
+        Error,
+        HttpClientService,
+        MessageDigestService,
+        DigestMemoService,
+        ProgressReporter,
+        ResponsePolicy,
+        BodySink,
+        OtherContentTransformer,
+        ContentDecoder,
+    > {
+        CalculateWebPageMessageDigestService {
+            http_client_service: self.http_client_service,
+            message_digest_service: self.message_digest_service,
+            digest_memo_service: self.digest_memo_service,
+            progress_reporter: self.progress_reporter,
+            response_policy: self.response_policy,
+            body_sink: self.body_sink,
+            content_transformer,
+            content_decoder: self.content_decoder,
+            _digest_type_marker: PhantomData,
+            _error_type_marker: PhantomData,
+        }
+    }
+
+    /// Returns this service decoding response bodies with a content decoder
+    /// before they're transformed, and hashed
+    ///
+    /// Digests of decoded bodies aren't memoized, and decoded bodies aren't
+    /// checkpointed, or resumed, since the state of body decoders isn't
+    /// saved.  Body sinks receive the decoded bodies.
+    pub fn with_content_decoder<OtherContentDecoder>(
+        self,
+        content_decoder: OtherContentDecoder,
+    ) -> CalculateWebPageMessageDigestService<
+        Url,
+        Digest,
+        Error,
+        HttpClientService,
+        MessageDigestService,
+        DigestMemoService,
+        ProgressReporter,
+        ResponsePolicy,
+        BodySink,
+        ContentTransformer,
+        OtherContentDecoder,
+    > {
+        CalculateWebPageMessageDigestService {
+            http_client_service: self.http_client_service,
+            message_digest_service: self.message_digest_service,
+            digest_memo_service: self.digest_memo_service,
+            progress_reporter: self.progress_reporter,
+            response_policy: self.response_policy,
+            body_sink: self.body_sink,
+            content_transformer: self.content_transformer,
+            content_decoder,
             _digest_type_marker: PhantomData,
             _error_type_marker: PhantomData,
         }
@@ -54,11 +210,17 @@ impl<
 }
 
 impl<
-    Url:Sync,
-    Digest: Send+Sync,
+    Url: AsRef<str> + Sync,
+    Digest: AsRef<[u8]> + DigestParts + for<'a> TryFrom<&'a [u8]> + Send + Sync,
     Error: Sync,
-    HttpClientService: GetUrl<Url = Url>+Sync,
-    MessageDigestService: NewDigestCalculator + Send+Sync,
+    HttpClientService: GetUrl<Url = Url> + GetUrlRange + Sync,
+    MessageDigestService: RestoreDigestCalculator + DigestAlgorithmName + Send+Sync,
+    DigestMemoService: RecallDigest + MemoizeDigest + Send + Sync,
+    ProgressReporter: ReportStarted + ReportBytesWritten + ReportFinished + Send + Sync,
+    ResponsePolicy: CheckResponseMetadata + CheckBodySize + Send + Sync,
+    BodySink: ResumeBodyWriter + Send + Sync,
+    ContentTransformer: NewContentTransform + Send + Sync,
+    ContentDecoder: NewBodyDecoder + Send + Sync,
 > CalculateWebPageMessageDigest
     for CalculateWebPageMessageDigestService<
         Url,
@@ -66,18 +228,35 @@ impl<
         Error,
         HttpClientService,
         MessageDigestService,
+        DigestMemoService,
+        ProgressReporter,
+        ResponsePolicy,
+        BodySink,
+        ContentTransformer,
+        ContentDecoder,
     >
 where
     Error: From<<HttpClientService as GetUrl>::Error>
         + From<<<HttpClientService as GetUrl>::HttpResponse as IntoChunkStream>::Error>
         + From<<MessageDigestService as NewDigestCalculator>::Error>
         + From<<<MessageDigestService as NewDigestCalculator>::DigestCalculator as IntoDigestOctets>::Error>
+        + From<<DigestMemoService as RecallDigest>::Error>
+        + From<<DigestMemoService as MemoizeDigest>::Error>
+        + From<<ResponsePolicy as CheckResponseMetadata>::Error>
+        + From<<ResponsePolicy as CheckBodySize>::Error>
+        + From<<BodySink as NewBodyWriter>::Error>
+        + From<<<BodySink as NewBodyWriter>::BodyWriter as CommitBody>::Error>
+        + From<<<BodySink as NewBodyWriter>::BodyWriter as CheckpointBody>::Error>
+        + From<<ContentDecoder as NewBodyDecoder>::Error>
         + From<std::io::Error>
         + Send,
-    HttpClientService::HttpResponse: IntoChunkStream,
+    HttpClientService::HttpResponse: IntoChunkStream + ResponseMetadata,
     <HttpClientService as GetUrl>::HttpResponse: Sync,
     <HttpClientService as GetUrl>::Error: Sync,
-    MessageDigestService::DigestCalculator: IntoDigestOctets<DigestOctets = Digest> + Write + Send + Sync,
+    MessageDigestService::DigestCalculator: IntoDigestOctets<DigestOctets = Digest> + SaveDigestState + Write + Send + Sync,
+    BodySink::BodyWriter: CommitBody + CheckpointBody + Write + Send,
+    ContentTransformer::ContentTransform: Send,
+    ContentDecoder::BodyDecoder: Send,
 {
     type Url = Url;
     type Digest = Digest;
@@ -87,18 +266,265 @@ where
         &self,
         url: &Self::Url,
     ) -> Result<Self::Digest, Self::Error> {
-        let mut digest_calculator = self.message_digest_service.new_digest_calculator()?;
+        self.calculate_web_page_message_digest_with_metadata(url)
+            .await
+            .map(|web_page_message_digest| web_page_message_digest.digest)
+    }
+}
+
+impl<
+    Url: AsRef<str> + Sync,
+    Digest: AsRef<[u8]> + DigestParts + for<'a> TryFrom<&'a [u8]> + Send + Sync,
+    Error: Sync,
+    HttpClientService: GetUrl<Url = Url> + GetUrlRange + Sync,
+    MessageDigestService: RestoreDigestCalculator + DigestAlgorithmName + Send+Sync,
+    DigestMemoService: RecallDigest + MemoizeDigest + Send + Sync,
+    ProgressReporter: ReportStarted + ReportBytesWritten + ReportFinished + Send + Sync,
+    ResponsePolicy: CheckResponseMetadata + CheckBodySize + Send + Sync,
+    BodySink: ResumeBodyWriter + Send + Sync,
+    ContentTransformer: NewContentTransform + Send + Sync,
+    ContentDecoder: NewBodyDecoder + Send + Sync,
+> CalculateWebPageMessageDigestWithMetadata
+    for CalculateWebPageMessageDigestService<
+        Url,
+        Digest,
+        Error,
+        HttpClientService,
+        MessageDigestService,
+        DigestMemoService,
+        ProgressReporter,
+        ResponsePolicy,
+        BodySink,
+        ContentTransformer,
+        ContentDecoder,
+    >
+where
+    Error: From<<HttpClientService as GetUrl>::Error>
+        + From<<<HttpClientService as GetUrl>::HttpResponse as IntoChunkStream>::Error>
+        + From<<MessageDigestService as NewDigestCalculator>::Error>
+        + From<<<MessageDigestService as NewDigestCalculator>::DigestCalculator as IntoDigestOctets>::Error>
+        + From<<DigestMemoService as RecallDigest>::Error>
+        + From<<DigestMemoService as MemoizeDigest>::Error>
+        + From<<ResponsePolicy as CheckResponseMetadata>::Error>
+        + From<<ResponsePolicy as CheckBodySize>::Error>
+        + From<<BodySink as NewBodyWriter>::Error>
+        + From<<<BodySink as NewBodyWriter>::BodyWriter as CommitBody>::Error>
+        + From<<<BodySink as NewBodyWriter>::BodyWriter as CheckpointBody>::Error>
+        + From<<ContentDecoder as NewBodyDecoder>::Error>
+        + From<std::io::Error>
+        + Send,
+    HttpClientService::HttpResponse: IntoChunkStream + ResponseMetadata,
+    <HttpClientService as GetUrl>::HttpResponse: Sync,
+    <HttpClientService as GetUrl>::Error: Sync,
+    MessageDigestService::DigestCalculator: IntoDigestOctets<DigestOctets = Digest> + SaveDigestState + Write + Send + Sync,
+    BodySink::BodyWriter: CommitBody + CheckpointBody + Write + Send,
+    ContentTransformer::ContentTransform: Send,
+    ContentDecoder::BodyDecoder: Send,
+{
+    async fn calculate_web_page_message_digest_with_metadata(
+        &self,
+        url: &Self::Url,
+    ) -> Result<WebPageMessageDigest<Self::Digest>, Self::Error> {
+        let algorithm = self.message_digest_service.digest_algorithm_name();
         let http_client_service = &self.http_client_service;
-        let mut chunk_stream = http_client_service
-            .get_url(url)
-            .await?
-            .into_chunk_stream();
+        let transforms_content = self.content_transformer.transforms_content();
+        let mut resumable = None;
+
+        // A body interrupted after a checkpoint is resumed with a `Range`
+        // request, when both the body writer, and the digest calculator can
+        // be restored, and the body isn't transformed, since the state of
+        // content transforms isn't saved:
+        let resumable_body = if transforms_content {
+            None
+        } else {
+            self.body_sink.resume_body_writer(url.as_ref())?
+        };
+        if let Some((body_writer, checkpoint)) = resumable_body
+            && let Some(digest_calculator) = self
+                .message_digest_service
+                .restore_digest_calculator(&checkpoint.digest_state)
+        {
+            let response = http_client_service
+                .get_url_range(url, checkpoint.byte_count, &checkpoint.validator)
+                .await?;
+            resumable = Some((response, body_writer, digest_calculator, checkpoint.byte_count));
+        }
+
+        let (response, resumed) = match resumable {
+            // Encoded bodies aren't resumed when decoded, since the state of
+            // body decoders isn't saved:
+            Some((response, body_writer, digest_calculator, byte_count))
+                if response.status() == StatusCode::PARTIAL_CONTENT
+                    && response.content_range_start() == Some(byte_count)
+                    && matches!(
+                        self.content_decoder
+                            .new_body_decoder(response.content_encoding()),
+                        Ok(None)
+                    ) =>
+            {
+                (response, Some((body_writer, digest_calculator, byte_count)))
+            }
+            // The body changed, or the server ignored the `Range` header, so
+            // the full body has been received:
+            Some((response, ..)) if response.status() != StatusCode::PARTIAL_CONTENT => {
+                (response, None)
+            }
+            _ => (http_client_service.get_url(url).await?, None),
+        };
+        self.response_policy.check_response_metadata(&response)?;
+        let mut body_decoder = self
+            .content_decoder
+            .new_body_decoder(response.content_encoding())?;
+        let body_encoding = self.content_decoder.body_encoding();
+        let content_encoding = response.content_encoding().map(str::to_string);
+        let hashes_transferred_body = !transforms_content && body_decoder.is_none();
+        let validator = response.validator().map(str::to_string);
+        let cache_key = response.cache_key().map(str::to_string);
+        let content_type = response.content_type().map(str::to_string);
+        let final_url = response
+            .final_url()
+            .unwrap_or_else(|| url.as_ref().to_string());
+        let redirects = response.redirects();
+        let certificate_fingerprint = response.certificate_fingerprint();
+        let status = match resumed {
+            Some(_) => StatusCode::OK,
+            None => response.status(),
+        };
+
+        // Memoized digests are of bodies as they're transferred, and can't
+        // be used when the body itself is kept:
+        if hashes_transferred_body
+            && !self.body_sink.keeps_bodies()
+            && !self.message_digest_service.needs_message()
+            && let (Some(cache_key), Some(validator), None) = (&cache_key, &validator, &resumed)
+        {
+            let memoized_digest =
+                self.digest_memo_service
+                    .recall_digest(cache_key, validator, algorithm)?;
+
+            if let Some(Ok(digest)) = memoized_digest.as_deref().map(Digest::try_from) {
+                return Ok(WebPageMessageDigest {
+                    digest,
+                    final_url,
+                    redirects,
+                    certificate_fingerprint,
+                    status,
+                    content_type,
+                    content_encoding,
+                    body_encoding,
+                    byte_count: response.content_length(),
+                });
+            }
+        }
+
+        let (mut body_writer, mut digest_calculator, mut byte_count) = match resumed {
+            Some(resumed) => resumed,
+            None => (
+                self.body_sink.new_body_writer(url.as_ref())?,
+                self.message_digest_service.new_digest_calculator()?,
+                0,
+            ),
+        };
+        // Only an untransformed, and undecoded body with a strong validator
+        // can be resumed with `If-Range`:
+        let checkpoint_validator = validator
+            .as_deref()
+            .filter(|validator| !validator.starts_with("W/") && hashes_transferred_body);
+        let mut content_transform = self
+            .content_transformer
+            .new_content_transform(content_type.as_deref());
+        let content_length = response
+            .content_length()
+            .map(|content_length| byte_count + content_length);
+        let mut checkpointed_byte_count = byte_count;
+        let mut hashed_byte_count = byte_count;
+        let mut chunk_stream = response.into_chunk_stream();
+        self.progress_reporter
+            .report_started(url.as_ref(), content_length);
+        let finish_progress_on_drop = FinishProgressOnDrop(&self.progress_reporter);
+        if byte_count > 0 {
+            self.progress_reporter.report_bytes_written(byte_count);
+        }
 
         while let Some(chunk_result) = chunk_stream.next().await {
             let chunk = chunk_result?;
-            digest_calculator.write_all(&chunk)?;
+            byte_count += chunk.len() as u64;
+            // Decoded octets are checked, and hashed in pieces, so that a
+            // highly compressed chunk isn't decoded whole:
+            let mut hash_octets = |octets: &[u8]| -> Result<(), Error> {
+                hashed_byte_count += octets.len() as u64;
+                self.response_policy.check_body_size(hashed_byte_count)?;
+                digest_calculator.write_all(&content_transform.transform_chunk(octets))?;
+                body_writer.write_all(octets)?;
+                Ok(())
+            };
+            match &mut body_decoder {
+                Some(body_decoder) => body_decoder.decode_chunk(&chunk, &mut hash_octets)?,
+                None => hash_octets(&chunk)?,
+            }
+            self.progress_reporter
+                .report_bytes_written(chunk.len() as u64);
+
+            if let Some(validator) = checkpoint_validator
+                && byte_count - checkpointed_byte_count >= CHECKPOINT_INTERVAL
+                && let Some(digest_state) = digest_calculator.save_digest_state()
+            {
+                body_writer.checkpoint_body(&BodyCheckpoint {
+                    url: url.as_ref().to_string(),
+                    validator: validator.to_string(),
+                    byte_count,
+                    digest_state,
+                })?;
+                checkpointed_byte_count = byte_count;
+            }
         }
 
-        Ok(digest_calculator.into_digest_octets()?)
+        drop(finish_progress_on_drop);
+        if let Some(body_decoder) = &mut body_decoder {
+            body_decoder.finish_body(&mut |octets: &[u8]| -> Result<(), Error> {
+                hashed_byte_count += octets.len() as u64;
+                self.response_policy.check_body_size(hashed_byte_count)?;
+                digest_calculator.write_all(&content_transform.transform_chunk(octets))?;
+                body_writer.write_all(octets)?;
+                Ok(())
+            })?;
+        }
+        digest_calculator.write_all(&content_transform.finish_content())?;
+
+        let digest = digest_calculator.into_digest_octets()?;
+        body_writer.commit_body(&digest.digest_parts(), &final_url)?;
+
+        if hashes_transferred_body
+            && let (Some(cache_key), Some(validator)) = (&cache_key, &validator)
+        {
+            self.digest_memo_service.memoize_digest(
+                cache_key,
+                validator,
+                algorithm,
+                digest.as_ref(),
+            )?;
+        }
+
+        Ok(WebPageMessageDigest {
+            digest,
+            final_url,
+            redirects,
+            certificate_fingerprint,
+            status,
+            content_type,
+            content_encoding,
+            body_encoding,
+            byte_count: Some(hashed_byte_count),
+        })
+    }
+}
+
+/// Reports that streaming finished when dropped, so that it's reported also
+/// when streaming fails
+struct FinishProgressOnDrop<'a, ProgressReporter: ReportFinished>(&'a ProgressReporter);
+
+impl<ProgressReporter: ReportFinished> Drop for FinishProgressOnDrop<'_, ProgressReporter> {
+    fn drop(&mut self) {
+        self.0.report_finished();
     }
 }
@@ -1,3 +1,7 @@
+use crate::content_decoder_service::BodyEncoding;
+use crate::http_client_service::{CertificateFingerprint, Redirect};
+use http::StatusCode;
+
 /// See <https://blog.rust-lang.org/2023/12/21/async-fn-rpit-in-traits/>, and
 /// <https://smallcultfollowing.com/babysteps/blog/2019/10/26/async-fn-in-traits-are-hard/>
 /// for more backgroun on traits containing `async` functions.
@@ -12,3 +16,62 @@ pub trait ThreadLocalCalculateWebPageMessageDigest {
         url: &Self::Url,
     ) -> impl Future<Output = Result<Self::Digest, Self::Error>>;
 }
+
+/// A message digest of a Web page, with metadata of the response it was
+/// calculated from
+#[derive(Clone, Debug)]
+pub struct WebPageMessageDigest<Digest> {
+    pub digest: Digest,
+    /// The URL of the response, after following redirects
+    pub final_url: String,
+    /// The redirects followed before the response, starting with the
+    /// requested URL
+    pub redirects: Vec<Redirect>,
+    /// The SHA-256 fingerprint of the server's certificate, when the
+    /// response was received over TLS, and the HTTP client service exposes
+    /// it
+    pub certificate_fingerprint: Option<CertificateFingerprint>,
+    pub status: StatusCode,
+    pub content_type: Option<String>,
+    /// The `Content-Encoding` header value of the response
+    pub content_encoding: Option<String>,
+    /// Whether the body was hashed as it was transferred, or decoded from
+    /// its content encoding
+    pub body_encoding: BodyEncoding,
+    /// The number of octets hashed, or the `Content-Length` of the response,
+    /// when a memoized digest was used
+    pub byte_count: Option<u64>,
+}
+
+impl<Digest> WebPageMessageDigest<Digest> {
+    /// Returns the metadata with another digest of the same response, e.g.,
+    /// one of several digests calculated from it
+    pub fn with_digest<OtherDigest>(
+        self,
+        digest: OtherDigest,
+    ) -> WebPageMessageDigest<OtherDigest> {
+        WebPageMessageDigest {
+            digest,
+            final_url: self.final_url,
+            redirects: self.redirects,
+            certificate_fingerprint: self.certificate_fingerprint,
+            status: self.status,
+            content_type: self.content_type,
+            content_encoding: self.content_encoding,
+            body_encoding: self.body_encoding,
+            byte_count: self.byte_count,
+        }
+    }
+}
+
+/// A function trait providing the digest of a Web page together with
+/// metadata, e.g., for machine-readable reports
+#[trait_variant::make(CalculateWebPageMessageDigestWithMetadata: Send)]
+pub trait ThreadLocalCalculateWebPageMessageDigestWithMetadata:
+    CalculateWebPageMessageDigest
+{
+    fn calculate_web_page_message_digest_with_metadata(
+        &self,
+        url: &Self::Url,
+    ) -> impl Future<Output = Result<WebPageMessageDigest<Self::Digest>, Self::Error>>;
+}
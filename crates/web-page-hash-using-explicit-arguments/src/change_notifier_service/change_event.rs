@@ -0,0 +1,75 @@
+use crate::digest_record::RecordError;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::time::SystemTime;
+use time::OffsetDateTime;
+use time::format_description::well_known::Rfc3339;
+
+/// A change of a watched Web page, detected by comparing its digest with the
+/// digest of the previous poll
+///
+/// Serialized as, e.g.:
+///
+/// ```json
+/// {
+///   "kind": "changed",
+///   "url": "https://example.com/",
+///   "algorithm": "SHA3-256",
+///   "previous_digest": "<lowercase hexadecimal digits>",
+///   "digest": "<lowercase hexadecimal digits>",
+///   "detected_at": "2025-01-01T00:00:00Z",
+///   "error": null
+/// }
+/// ```
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct ChangeEvent {
+    pub kind: ChangeKind,
+    /// The watched URL
+    pub url: String,
+    /// The BSD-tag style name of the message digest algorithm (e.g.,
+    /// `SHA3-256`)
+    pub algorithm: String,
+    /// The hexadecimal digest last seen, or `null` for a new Web page
+    pub previous_digest: Option<String>,
+    /// The hexadecimal digest, or `null`, when it couldn't be calculated
+    pub digest: Option<String>,
+    /// The RFC 3339 time of the poll detecting the change
+    pub detected_at: String,
+    /// The error preventing calculating the digest, for `failed` events
+    pub error: Option<RecordError>,
+}
+
+/// Kinds of [ChangeEvent]s
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeKind {
+    /// The first digest of a Web page not watched before
+    Added,
+    /// The digest differs from the previous digest
+    Changed,
+    /// The digest couldn't be calculated, after it could be in the previous
+    /// poll
+    Failed,
+    /// The digest could be calculated again, and is unchanged
+    Recovered,
+}
+
+impl fmt::Display for ChangeKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ChangeKind::Added => "added",
+            ChangeKind::Changed => "changed",
+            ChangeKind::Failed => "failed",
+            ChangeKind::Recovered => "recovered",
+        })
+    }
+}
+
+/// Formats a time as an RFC 3339 UTC timestamp (e.g., `2025-01-01T00:00:00Z`)
+pub fn format_timestamp(time: SystemTime) -> String {
+    OffsetDateTime::from(time)
+        .replace_nanosecond(0)
+        .expect("zero nanoseconds are valid")
+        .format(&Rfc3339)
+        .expect("a UTC time can be formatted as RFC 3339")
+}
@@ -0,0 +1,16 @@
+use super::change_event::ChangeEvent;
+
+/// A function trait reporting a change of a watched Web page
+#[trait_variant::make(NotifyChange: Send)]
+pub trait ThreadLocalNotifyChange {
+    type Error;
+
+    fn notify_change(
+        &self,
+        change_event: &ChangeEvent,
+    ) -> impl Future<Output = Result<(), Self::Error>>;
+}
+
+// The full service is composed of its method, and function traits:
+/// An injectable service reporting changes of watched Web pages
+pub trait ChangeNotifier: NotifyChange {}
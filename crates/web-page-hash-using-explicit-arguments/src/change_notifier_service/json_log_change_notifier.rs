@@ -0,0 +1,44 @@
+use super::change_event::ChangeEvent;
+use super::interface::{ChangeNotifier, NotifyChange};
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// A [ChangeNotifier] appending each change as a line of JSON (see
+/// [ChangeEvent]) to a log file, or printing it on standard output, when the
+/// path is `-`
+#[derive(Debug)]
+pub struct JsonLogChangeNotifier {
+    path: PathBuf,
+}
+
+impl JsonLogChangeNotifier {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        JsonLogChangeNotifier { path: path.into() }
+    }
+}
+
+impl NotifyChange for JsonLogChangeNotifier {
+    type Error = io::Error;
+
+    async fn notify_change(&self, change_event: &ChangeEvent) -> Result<(), Self::Error> {
+        let mut line = serde_json::to_vec(change_event)?;
+        line.push(b'\n');
+
+        if self.path == Path::new("-") {
+            let mut stdout = io::stdout().lock();
+            stdout.write_all(&line)?;
+            return stdout.flush();
+        }
+
+        // The line is appended with a single write, so that concurrent
+        // writers don't interleave lines:
+        File::options()
+            .create(true)
+            .append(true)
+            .open(&self.path)?
+            .write_all(&line)
+    }
+}
+
+impl ChangeNotifier for JsonLogChangeNotifier {}
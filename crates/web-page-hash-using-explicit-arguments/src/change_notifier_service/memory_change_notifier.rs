@@ -0,0 +1,42 @@
+use super::change_event::ChangeEvent;
+use super::interface::{ChangeNotifier, NotifyChange};
+use std::convert::Infallible;
+use std::sync::{Arc, Mutex, PoisonError};
+
+/// A [ChangeNotifier] capturing change events in memory, e.g., for checking
+/// them in tests
+#[derive(Clone, Debug, Default)]
+pub struct MemoryChangeNotifier {
+    change_events: Arc<Mutex<Vec<ChangeEvent>>>,
+}
+
+impl MemoryChangeNotifier {
+    pub fn new() -> Self {
+        MemoryChangeNotifier {
+            change_events: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    /// Returns the change events notified so far, in order
+    pub fn change_events(&self) -> Vec<ChangeEvent> {
+        self.change_events
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+}
+
+impl NotifyChange for MemoryChangeNotifier {
+    type Error = Infallible;
+
+    async fn notify_change(&self, change_event: &ChangeEvent) -> Result<(), Self::Error> {
+        self.change_events
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push(change_event.clone());
+
+        Ok(())
+    }
+}
+
+impl ChangeNotifier for MemoryChangeNotifier {}
@@ -0,0 +1,20 @@
+pub mod interface;
+pub use interface::*;
+
+pub mod change_event;
+pub use change_event::*;
+
+pub mod json_log_change_notifier;
+pub use json_log_change_notifier::*;
+
+pub mod memory_change_notifier;
+pub use memory_change_notifier::*;
+
+pub mod selectable_change_notifier;
+pub use selectable_change_notifier::*;
+
+pub mod stdout_change_notifier;
+pub use stdout_change_notifier::*;
+
+pub mod webhook_change_notifier;
+pub use webhook_change_notifier::*;
@@ -0,0 +1,75 @@
+use super::change_event::ChangeEvent;
+use super::interface::{ChangeNotifier, NotifyChange};
+use super::json_log_change_notifier::JsonLogChangeNotifier;
+use super::stdout_change_notifier::StdoutChangeNotifier;
+use super::webhook_change_notifier::WebhookChangeNotifier;
+use std::io;
+use std::path::Path;
+
+/// A [ChangeNotifier] selected at run time
+#[derive(Debug)]
+pub enum SelectableChangeNotifier {
+    Stdout(StdoutChangeNotifier),
+    JsonLog(JsonLogChangeNotifier),
+    Webhook(WebhookChangeNotifier),
+}
+
+impl SelectableChangeNotifier {
+    /// Returns a notifier appending to the JSON log at `json_log_path`, or
+    /// posting to `webhook_url`, or printing changes on standard output,
+    /// when neither is given
+    pub fn new(json_log_path: Option<&Path>, webhook_url: Option<&reqwest::Url>) -> Self {
+        match (json_log_path, webhook_url) {
+            (Some(path), _) => SelectableChangeNotifier::JsonLog(JsonLogChangeNotifier::new(path)),
+            (None, Some(webhook_url)) => {
+                SelectableChangeNotifier::Webhook(WebhookChangeNotifier::new(webhook_url.clone()))
+            }
+            (None, None) => SelectableChangeNotifier::Stdout(StdoutChangeNotifier::new()),
+        }
+    }
+}
+
+impl NotifyChange for SelectableChangeNotifier {
+    type Error = io::Error;
+
+    async fn notify_change(&self, change_event: &ChangeEvent) -> Result<(), Self::Error> {
+        match self {
+            SelectableChangeNotifier::Stdout(notifier) => {
+                let Ok(()) = notifier.notify_change(change_event).await;
+                Ok(())
+            }
+            SelectableChangeNotifier::JsonLog(notifier) => {
+                notifier.notify_change(change_event).await
+            }
+            SelectableChangeNotifier::Webhook(notifier) => notifier
+                .notify_change(change_event)
+                .await
+                .map_err(io::Error::other),
+        }
+    }
+}
+
+impl ChangeNotifier for SelectableChangeNotifier {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn json_logs_take_precedence_over_webhooks_and_stdout() {
+        let webhook_url = "https://example.com/hook".parse::<reqwest::Url>().unwrap();
+
+        assert!(matches!(
+            SelectableChangeNotifier::new(Some(Path::new("-")), Some(&webhook_url)),
+            SelectableChangeNotifier::JsonLog(_)
+        ));
+        assert!(matches!(
+            SelectableChangeNotifier::new(None, Some(&webhook_url)),
+            SelectableChangeNotifier::Webhook(_)
+        ));
+        assert!(matches!(
+            SelectableChangeNotifier::new(None, None),
+            SelectableChangeNotifier::Stdout(_)
+        ));
+    }
+}
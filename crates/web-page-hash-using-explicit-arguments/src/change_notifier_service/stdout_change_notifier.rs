@@ -0,0 +1,48 @@
+use super::change_event::{ChangeEvent, ChangeKind};
+use super::interface::{ChangeNotifier, NotifyChange};
+use std::convert::Infallible;
+
+/// A [ChangeNotifier] printing a line for each change on standard output,
+/// e.g.:
+///
+/// ```text
+/// 2025-01-01T00:00:00Z changed https://example.com/ SHA3-256 <previous> -> <digest>
+/// ```
+#[derive(Debug, Default)]
+pub struct StdoutChangeNotifier {}
+
+impl StdoutChangeNotifier {
+    pub fn new() -> Self {
+        StdoutChangeNotifier {}
+    }
+}
+
+impl NotifyChange for StdoutChangeNotifier {
+    type Error = Infallible;
+
+    async fn notify_change(&self, change_event: &ChangeEvent) -> Result<(), Self::Error> {
+        let ChangeEvent {
+            kind,
+            url,
+            algorithm,
+            previous_digest,
+            digest,
+            detected_at,
+            error,
+        } = change_event;
+        let details = match (kind, previous_digest, digest, error) {
+            (ChangeKind::Changed, Some(previous_digest), Some(digest), _) => {
+                format!("{} -> {}", previous_digest, digest)
+            }
+            (_, _, Some(digest), _) => digest.clone(),
+            (_, _, None, Some(error)) => error.message.clone(),
+            (_, _, None, None) => String::new(),
+        };
+
+        println!("{} {} {} {} {}", detected_at, kind, url, algorithm, details);
+
+        Ok(())
+    }
+}
+
+impl ChangeNotifier for StdoutChangeNotifier {}
@@ -0,0 +1,43 @@
+use super::change_event::ChangeEvent;
+use super::interface::{ChangeNotifier, NotifyChange};
+use http::header::CONTENT_TYPE;
+use reqwest;
+
+/// A [ChangeNotifier] posting each change as JSON (see [ChangeEvent]) to a
+/// webhook URL, e.g., of a local chat, or alerting service
+///
+/// A response with a `4xx`, or `5xx` status is an error.
+#[derive(Debug)]
+pub struct WebhookChangeNotifier {
+    client: reqwest::Client,
+    webhook_url: reqwest::Url,
+}
+
+impl WebhookChangeNotifier {
+    pub fn new(webhook_url: reqwest::Url) -> Self {
+        WebhookChangeNotifier {
+            client: reqwest::Client::new(),
+            webhook_url,
+        }
+    }
+}
+
+impl NotifyChange for WebhookChangeNotifier {
+    type Error = reqwest::Error;
+
+    async fn notify_change(&self, change_event: &ChangeEvent) -> Result<(), Self::Error> {
+        let body = serde_json::to_vec(change_event).expect("a change event can be serialized");
+
+        self.client
+            .post(self.webhook_url.clone())
+            .header(CONTENT_TYPE, "application/json")
+            .body(body)
+            .send()
+            .await?
+            .error_for_status()?;
+
+        Ok(())
+    }
+}
+
+impl ChangeNotifier for WebhookChangeNotifier {}
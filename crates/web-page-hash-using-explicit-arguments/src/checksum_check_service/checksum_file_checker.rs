@@ -0,0 +1,309 @@
+use crate::digest_encoding::ChecksumLine;
+use crate::digest_record::{OutputFormat, WebPageDigestRecord};
+use crate::message_digest_service::MessageDigestAlgorithm;
+use std::io::{self, Write};
+
+/// Checks the digests of the Web pages listed in a checksum file, reporting
+/// as `sha3sum --check` does
+///
+/// The record of each listed Web page is calculated by a function given to
+/// [ChecksumFileChecker::check], as [WebPageWatcher] does, so that any
+/// binding of the calculate Web page message digest service can be used.
+/// `OK`, or `FAILED` is written for each properly formatted line, or a
+/// record in the `json`, and `ndjson` formats, followed by warnings
+/// counting the failures.
+///
+/// [WebPageWatcher]: crate::watch_service::WebPageWatcher
+#[derive(Debug)]
+pub struct ChecksumFileChecker {
+    default_algorithm: MessageDigestAlgorithm,
+    output: OutputFormat,
+    quiet: bool,
+    status: bool,
+}
+
+/// The counts of the lines of a checksum file checked by a
+/// [ChecksumFileChecker]
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct ChecksumCheckSummary {
+    /// The number of properly formatted lines
+    pub checked_count: usize,
+    pub improperly_formatted_count: usize,
+    /// The number of Web pages that couldn't be hashed
+    pub unreadable_count: usize,
+    /// The number of digests not matching their lines
+    pub mismatched_count: usize,
+}
+
+impl ChecksumCheckSummary {
+    /// Returns whether any line was checked, and every checked Web page
+    /// matched its line
+    pub fn passed(&self) -> bool {
+        self.checked_count > 0 && self.unreadable_count == 0 && self.mismatched_count == 0
+    }
+}
+
+impl ChecksumFileChecker {
+    /// Returns a checker of GNU lines with `default_algorithm`, writing only
+    /// failures, when `quiet`, and nothing, when `status`
+    pub fn new(
+        default_algorithm: MessageDigestAlgorithm,
+        output: OutputFormat,
+        quiet: bool,
+        status: bool,
+    ) -> Self {
+        ChecksumFileChecker {
+            default_algorithm,
+            output,
+            quiet,
+            status,
+        }
+    }
+
+    /// Checks each line of a checksum file named `name`
+    ///
+    /// `calculate_record` returns the record of the Web page of a line,
+    /// checked against its digest.  Results are written into `writer`, and
+    /// errors, and warnings into `diagnostics`.
+    pub async fn check<CalculateRecord>(
+        &self,
+        name: &str,
+        checksum_file: &str,
+        mut calculate_record: CalculateRecord,
+        writer: &mut impl Write,
+        diagnostics: &mut impl Write,
+    ) -> io::Result<ChecksumCheckSummary>
+    where
+        CalculateRecord: AsyncFnMut(&ChecksumLine) -> WebPageDigestRecord,
+    {
+        let mut summary = ChecksumCheckSummary::default();
+        let mut records = Vec::new();
+
+        for line in checksum_file.lines() {
+            let Ok(checksum_line) = ChecksumLine::parse(line, self.default_algorithm) else {
+                summary.improperly_formatted_count += 1;
+                continue;
+            };
+            summary.checked_count += 1;
+
+            let record = calculate_record(&checksum_line).await;
+            match (&record.error, record.matches_expected) {
+                (Some(_), _) => summary.unreadable_count += 1,
+                (None, Some(false)) => summary.mismatched_count += 1,
+                _ => {}
+            }
+
+            match self.output {
+                _ if self.status => {}
+                OutputFormat::Json => records.push(record),
+                OutputFormat::Ndjson => self.output.write_record(writer, &record)?,
+                _ => match (&record.error, record.matches_expected) {
+                    (Some(error), _) => {
+                        writeln!(diagnostics, "{}: {}", checksum_line.url(), error.message)?;
+                        writeln!(writer, "{}: FAILED open or read", checksum_line.url())?;
+                    }
+                    (None, Some(false)) => writeln!(writer, "{}: FAILED", checksum_line.url())?,
+                    _ if self.quiet => {}
+                    _ => writeln!(writer, "{}: OK", checksum_line.url())?,
+                },
+            }
+        }
+
+        if self.output == OutputFormat::Json && !self.status {
+            self.output.write_records(writer, diagnostics, &records)?;
+        }
+
+        if summary.checked_count == 0 {
+            writeln!(
+                diagnostics,
+                "{}: no properly formatted checksum lines found",
+                name
+            )?;
+        } else if !self.status {
+            self.write_warnings(diagnostics, &summary)?;
+        }
+
+        Ok(summary)
+    }
+
+    fn write_warnings(
+        &self,
+        diagnostics: &mut impl Write,
+        summary: &ChecksumCheckSummary,
+    ) -> io::Result<()> {
+        if summary.improperly_formatted_count > 0 {
+            writeln!(
+                diagnostics,
+                "WARNING: {} {} improperly formatted",
+                summary.improperly_formatted_count,
+                pluralize(summary.improperly_formatted_count, "line is", "lines are")
+            )?;
+        }
+        if summary.unreadable_count > 0 {
+            writeln!(
+                diagnostics,
+                "WARNING: {} listed {} not be read",
+                summary.unreadable_count,
+                pluralize(
+                    summary.unreadable_count,
+                    "Web page could",
+                    "Web pages could"
+                )
+            )?;
+        }
+        if summary.mismatched_count > 0 {
+            writeln!(
+                diagnostics,
+                "WARNING: {} computed {} NOT match",
+                summary.mismatched_count,
+                pluralize(summary.mismatched_count, "checksum did", "checksums did")
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+fn pluralize(count: usize, singular: &'static str, plural: &'static str) -> &'static str {
+    if count == 1 { singular } else { plural }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calculate_web_page_message_digest_service::WebPageMessageDigest;
+    use crate::content_decoder_service::BodyEncoding;
+    use http::StatusCode;
+    use std::time::Duration;
+
+    fn checksum_file() -> String {
+        let hex_digest = "0f".repeat(32);
+
+        ["ok", "changed", "missing"]
+            .iter()
+            .map(|path| format!("{}  https://example.com/{}\n", hex_digest, path))
+            .chain(["not a checksum line\n".to_string()])
+            .collect()
+    }
+
+    fn record(checksum_line: &ChecksumLine) -> WebPageDigestRecord {
+        let url = checksum_line.url().to_string();
+        let algorithm = checksum_line.algorithm();
+
+        if url.ends_with("missing") {
+            return WebPageDigestRecord::failed(
+                url,
+                algorithm,
+                &io::Error::other("not found"),
+                Duration::ZERO,
+            );
+        }
+
+        let web_page_message_digest = WebPageMessageDigest {
+            digest: checksum_line.digest().to_vec(),
+            final_url: url.clone(),
+            redirects: Vec::new(),
+            certificate_fingerprint: None,
+            status: StatusCode::OK,
+            content_type: None,
+            content_encoding: None,
+            body_encoding: BodyEncoding::Raw,
+            byte_count: Some(0),
+        };
+        let mut record = WebPageDigestRecord::new(
+            url.clone(),
+            algorithm,
+            &web_page_message_digest,
+            Duration::ZERO,
+        );
+        record.matches_expected = Some(url.ends_with("ok"));
+
+        record
+    }
+
+    async fn check(
+        checker: ChecksumFileChecker,
+        checksum_file: &str,
+    ) -> (ChecksumCheckSummary, String, String) {
+        let mut writer = Vec::new();
+        let mut diagnostics = Vec::new();
+
+        let summary = checker
+            .check(
+                "SUMS",
+                checksum_file,
+                async |checksum_line| record(checksum_line),
+                &mut writer,
+                &mut diagnostics,
+            )
+            .await
+            .unwrap();
+
+        (
+            summary,
+            String::from_utf8(writer).unwrap(),
+            String::from_utf8(diagnostics).unwrap(),
+        )
+    }
+
+    fn checker(quiet: bool, status: bool) -> ChecksumFileChecker {
+        ChecksumFileChecker::new(
+            MessageDigestAlgorithm::Sha3_256,
+            OutputFormat::Text,
+            quiet,
+            status,
+        )
+    }
+
+    #[tokio::test]
+    async fn lines_are_reported_as_sha3sum_does() {
+        let (summary, output, diagnostics) = check(checker(false, false), &checksum_file()).await;
+
+        assert_eq!(
+            summary,
+            ChecksumCheckSummary {
+                checked_count: 3,
+                improperly_formatted_count: 1,
+                unreadable_count: 1,
+                mismatched_count: 1,
+            }
+        );
+        assert!(!summary.passed());
+        assert_eq!(
+            output,
+            "https://example.com/ok: OK\n\
+             https://example.com/changed: FAILED\n\
+             https://example.com/missing: FAILED open or read\n"
+        );
+        assert_eq!(
+            diagnostics,
+            "https://example.com/missing: not found\n\
+             WARNING: 1 line is improperly formatted\n\
+             WARNING: 1 listed Web page could not be read\n\
+             WARNING: 1 computed checksum did NOT match\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn quiet_checks_only_report_failures_and_status_checks_nothing() {
+        let (_, output, _) = check(checker(true, false), &checksum_file()).await;
+        assert!(!output.contains(": OK"), "{output}");
+        assert!(output.contains(": FAILED"), "{output}");
+
+        let (summary, output, diagnostics) = check(checker(false, true), &checksum_file()).await;
+        assert!(!summary.passed());
+        assert_eq!((output.as_str(), diagnostics.as_str()), ("", ""));
+    }
+
+    #[tokio::test]
+    async fn files_without_checksum_lines_fail() {
+        let (summary, output, diagnostics) = check(checker(false, false), "\n").await;
+
+        assert!(!summary.passed());
+        assert_eq!(output, "");
+        assert_eq!(
+            diagnostics,
+            "SUMS: no properly formatted checksum lines found\n"
+        );
+    }
+}
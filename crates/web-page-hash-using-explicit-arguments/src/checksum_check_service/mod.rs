@@ -0,0 +1,2 @@
+pub mod checksum_file_checker;
+pub use checksum_file_checker::*;
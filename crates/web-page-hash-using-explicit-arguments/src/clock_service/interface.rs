@@ -0,0 +1,23 @@
+use std::time::{Duration, SystemTime};
+
+/// A function trait returning the current wall-clock time
+pub trait Now {
+    fn now(&self) -> SystemTime;
+}
+
+/// A function trait waiting for a duration, e.g., between polls of a Web
+/// page
+#[trait_variant::make(Sleep: Send)]
+pub trait ThreadLocalSleep {
+    fn sleep(&self, duration: Duration) -> impl Future<Output = ()>;
+}
+
+// The full services are composed of their method, and function traits:
+/// An injectable service telling the time, e.g., for measuring elapsed time
+pub trait Clock: Now {}
+
+/// An injectable service waiting, e.g., for intervals between polls
+///
+/// Time-dependent behavior runs on virtual time, when both the [Clock], and
+/// the sleeper follow it (see [ManualClock](super::ManualClock)).
+pub trait Sleeper: Sleep {}
@@ -0,0 +1,79 @@
+use crate::content_decoder_service::BodyEncoding;
+use crate::content_transformer_service::{ContentMask, ContentNormalization};
+use crate::http_client_service::{CertificateFingerprint, RedirectPolicy};
+use crate::message_digest_service::MessageDigestAlgorithm;
+use std::time::Duration;
+
+/// A function trait providing settings of HTTP client services
+pub trait HttpClientConfiguration {
+    /// The maximum time for establishing a connection, or [None] for no limit
+    fn connect_timeout(&self) -> Option<Duration>;
+
+    /// The maximum time for a request, including streaming the response
+    /// body, or [None] for no limit
+    fn timeout(&self) -> Option<Duration>;
+
+    /// The `User-Agent` header value sent with requests
+    fn user_agent(&self) -> &str;
+
+    /// The URL of a proxy for all requests, or [None] for the system default
+    fn proxy(&self) -> Option<&str>;
+
+    /// Which redirects are followed, and how many
+    fn redirect_policy(&self) -> RedirectPolicy;
+
+    /// SHA-256 fingerprints of the only server certificates trusted, or an
+    /// empty list for trusting certificates issued by certificate
+    /// authorities
+    fn pinned_certificates(&self) -> &[CertificateFingerprint];
+}
+
+/// A function trait providing settings of response policy services
+pub trait ResponsePolicyConfiguration {
+    /// The maximum number of response body octets hashed, or [None] for no
+    /// limit
+    fn max_body_size(&self) -> Option<u64>;
+
+    /// Whether bodies of `4xx`, and `5xx` responses are hashed
+    fn allow_error_status(&self) -> bool;
+
+    /// Media types of the responses hashed (e.g., `text/html`, or `image/*`),
+    /// or an empty list for any media type
+    fn allowed_content_types(&self) -> &[String];
+
+    /// Media types of the responses that are never hashed
+    fn denied_content_types(&self) -> &[String];
+}
+
+/// A function trait providing settings of message digest services
+pub trait MessageDigestConfiguration {
+    /// The algorithm used when none is requested otherwise
+    fn default_algorithm(&self) -> MessageDigestAlgorithm;
+}
+
+/// A function trait providing settings of content decoder services
+pub trait ContentDecoderConfiguration {
+    /// Whether response bodies are hashed as they're transferred, or decoded
+    /// from their content encodings
+    fn body_encoding(&self) -> BodyEncoding;
+}
+
+/// A function trait providing settings of content transformer services
+pub trait ContentTransformerConfiguration {
+    /// Normalizations of response bodies before they're hashed
+    fn content_normalizations(&self) -> &[ContentNormalization];
+
+    /// Masks applied to response bodies before they're hashed
+    fn content_masks(&self) -> &[ContentMask];
+}
+
+// The full service is composed of its method, and function traits:
+/// An injectable service providing settings of other services
+pub trait ConfigurationService:
+    ContentDecoderConfiguration
+    + ContentTransformerConfiguration
+    + HttpClientConfiguration
+    + MessageDigestConfiguration
+    + ResponsePolicyConfiguration
+{
+}
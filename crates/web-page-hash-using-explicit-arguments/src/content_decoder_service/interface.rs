@@ -0,0 +1,117 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::io;
+use std::str::FromStr;
+
+/// The form in which response bodies are hashed
+///
+/// It's deserialized from any name that [BodyEncoding::from_str] recognizes.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(try_from = "String", into = "&'static str")]
+pub enum BodyEncoding {
+    /// Bodies are hashed as they're transferred, e.g., gzip-compressed
+    #[default]
+    Raw,
+    /// Bodies are decoded from their `Content-Encoding` before they're hashed
+    Decoded,
+}
+
+impl BodyEncoding {
+    /// Returns the name of the form (e.g., `raw`)
+    pub fn name(self) -> &'static str {
+        match self {
+            BodyEncoding::Raw => "raw",
+            BodyEncoding::Decoded => "decoded",
+        }
+    }
+}
+
+impl fmt::Display for BodyEncoding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for BodyEncoding {
+    type Err = UnknownBodyEncoding;
+
+    /// Parses a form name ignoring case
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name.to_ascii_lowercase().as_str() {
+            "raw" => Ok(BodyEncoding::Raw),
+            "decoded" => Ok(BodyEncoding::Decoded),
+            _ => Err(UnknownBodyEncoding(name.to_string())),
+        }
+    }
+}
+
+impl TryFrom<String> for BodyEncoding {
+    type Error = UnknownBodyEncoding;
+
+    fn try_from(name: String) -> Result<Self, Self::Error> {
+        name.parse()
+    }
+}
+
+impl From<BodyEncoding> for &'static str {
+    fn from(body_encoding: BodyEncoding) -> Self {
+        body_encoding.name()
+    }
+}
+
+#[derive(Debug)]
+pub struct UnknownBodyEncoding(pub String);
+
+impl fmt::Display for UnknownBodyEncoding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown encoding {} (expected raw, or decoded)", self.0)
+    }
+}
+
+impl std::error::Error for UnknownBodyEncoding {}
+
+/// A function trait decoding the chunks of one response body from its
+/// content encoding
+///
+/// Decoded octets are passed to a sink in pieces of a bounded size, so that
+/// decoding stops as soon as the sink fails, e.g., because the decoded body
+/// is too large, instead of a highly compressed chunk being decoded whole.
+pub trait DecodeBody {
+    /// Passes the decoded octets of the next chunk of the body to `sink`
+    fn decode_chunk<Error: From<io::Error>>(
+        &mut self,
+        chunk: &[u8],
+        sink: &mut dyn FnMut(&[u8]) -> Result<(), Error>,
+    ) -> Result<(), Error>;
+
+    /// Passes the decoded octets held back at the end of the body to `sink`
+    ///
+    /// It fails, when the body is detectably truncated.
+    fn finish_body<Error: From<io::Error>>(
+        &mut self,
+        sink: &mut dyn FnMut(&[u8]) -> Result<(), Error>,
+    ) -> Result<(), Error>;
+}
+
+/// A function trait creating a [DecodeBody] for a response body
+pub trait NewBodyDecoder {
+    type BodyDecoder: DecodeBody;
+    type Error;
+
+    /// Returns a decoder for a body with the `Content-Encoding` header value
+    /// `content_encoding`, or [None], when the body is hashed as it's
+    /// transferred
+    fn new_body_decoder(
+        &self,
+        content_encoding: Option<&str>,
+    ) -> Result<Option<Self::BodyDecoder>, Self::Error>;
+
+    /// Returns the form in which bodies are hashed
+    fn body_encoding(&self) -> BodyEncoding;
+}
+
+// The full service is composed of its method, and function traits:
+/// An injectable service decoding response bodies from their content
+/// encoding before they're hashed, so that digests don't depend on whether
+/// an HTTP client library decompresses bodies
+pub trait ContentDecoder: NewBodyDecoder {}
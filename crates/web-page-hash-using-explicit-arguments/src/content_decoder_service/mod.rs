@@ -0,0 +1,11 @@
+pub mod interface;
+pub use interface::*;
+
+pub mod raw_content_decoder;
+pub use raw_content_decoder::*;
+
+pub mod selectable_content_decoder;
+pub use selectable_content_decoder::*;
+
+pub mod streaming_content_decoder;
+pub use streaming_content_decoder::*;
@@ -0,0 +1,31 @@
+use super::interface::{BodyEncoding, ContentDecoder, NewBodyDecoder};
+use super::streaming_content_decoder::StreamingBodyDecoder;
+use std::convert::Infallible;
+
+/// A [ContentDecoder] hashing response bodies as they're transferred
+#[derive(Clone, Debug, Default)]
+pub struct RawContentDecoder {}
+
+impl RawContentDecoder {
+    pub fn new() -> Self {
+        RawContentDecoder {}
+    }
+}
+
+impl NewBodyDecoder for RawContentDecoder {
+    type BodyDecoder = StreamingBodyDecoder;
+    type Error = Infallible;
+
+    fn new_body_decoder(
+        &self,
+        _content_encoding: Option<&str>,
+    ) -> Result<Option<Self::BodyDecoder>, Self::Error> {
+        Ok(None)
+    }
+
+    fn body_encoding(&self) -> BodyEncoding {
+        BodyEncoding::Raw
+    }
+}
+
+impl ContentDecoder for RawContentDecoder {}
@@ -0,0 +1,53 @@
+use super::interface::{BodyEncoding, ContentDecoder, NewBodyDecoder};
+use super::raw_content_decoder::RawContentDecoder;
+use super::streaming_content_decoder::{
+    StreamingBodyDecoder, StreamingContentDecoder, UnsupportedContentEncoding,
+};
+
+/// A [ContentDecoder] selected at run time
+#[derive(Clone, Debug)]
+pub enum SelectableContentDecoder {
+    Raw(RawContentDecoder),
+    Decoded(StreamingContentDecoder),
+}
+
+impl SelectableContentDecoder {
+    /// Returns the content decoder hashing bodies in the given form
+    pub fn new(body_encoding: BodyEncoding) -> Self {
+        match body_encoding {
+            BodyEncoding::Raw => SelectableContentDecoder::Raw(RawContentDecoder::new()),
+            BodyEncoding::Decoded => {
+                SelectableContentDecoder::Decoded(StreamingContentDecoder::new())
+            }
+        }
+    }
+}
+
+impl NewBodyDecoder for SelectableContentDecoder {
+    type BodyDecoder = StreamingBodyDecoder;
+    type Error = UnsupportedContentEncoding;
+
+    fn new_body_decoder(
+        &self,
+        content_encoding: Option<&str>,
+    ) -> Result<Option<Self::BodyDecoder>, Self::Error> {
+        match self {
+            SelectableContentDecoder::Raw(decoder) => {
+                let Ok(body_decoder) = decoder.new_body_decoder(content_encoding);
+                Ok(body_decoder)
+            }
+            SelectableContentDecoder::Decoded(decoder) => {
+                decoder.new_body_decoder(content_encoding)
+            }
+        }
+    }
+
+    fn body_encoding(&self) -> BodyEncoding {
+        match self {
+            SelectableContentDecoder::Raw(decoder) => decoder.body_encoding(),
+            SelectableContentDecoder::Decoded(decoder) => decoder.body_encoding(),
+        }
+    }
+}
+
+impl ContentDecoder for SelectableContentDecoder {}
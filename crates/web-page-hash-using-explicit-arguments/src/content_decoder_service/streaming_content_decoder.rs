@@ -0,0 +1,454 @@
+use super::interface::{BodyEncoding, ContentDecoder, DecodeBody, NewBodyDecoder};
+use brotli_decompressor::{BrotliDecompressStream, BrotliResult, BrotliState, StandardAlloc};
+use flate2::write::MultiGzDecoder;
+use flate2::{Decompress, FlushDecompress, Status};
+use std::fmt;
+use std::io::{self, Write};
+use zstd::stream::raw::{InBuffer, Operation, OutBuffer};
+
+/// The size of the output buffers of `deflate`, brotli, and zstd decoders,
+/// which limits the size of the pieces of decoded octets passed to sinks
+///
+/// `gzip` decoders pass pieces of the size of their own output buffers
+/// (32 KiB).
+const DECODER_OUTPUT_SIZE: usize = 64 * 1024;
+
+/// A [ContentDecoder] decoding `gzip`, `deflate`, `br`, and `zstd` content
+/// codings while bodies are streamed
+///
+/// Content codings listed in a `Content-Encoding` header are decoded in the
+/// reverse order of their application, `x-gzip` is an alias of `gzip`, and
+/// `identity` is ignored.  `deflate` bodies are decoded both with, and
+/// without the zlib wrapper required by RFC 9110, since some servers omit
+/// it.
+#[derive(Clone, Debug, Default)]
+pub struct StreamingContentDecoder {}
+
+impl StreamingContentDecoder {
+    pub fn new() -> Self {
+        StreamingContentDecoder {}
+    }
+}
+
+impl NewBodyDecoder for StreamingContentDecoder {
+    type BodyDecoder = StreamingBodyDecoder;
+    type Error = UnsupportedContentEncoding;
+
+    fn new_body_decoder(
+        &self,
+        content_encoding: Option<&str>,
+    ) -> Result<Option<Self::BodyDecoder>, Self::Error> {
+        let mut coding_decoders = content_encoding
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|content_coding| {
+                !content_coding.is_empty() && !content_coding.eq_ignore_ascii_case("identity")
+            })
+            .map(CodingDecoder::new)
+            .collect::<Result<Vec<_>, _>>()?;
+        coding_decoders.reverse();
+
+        Ok((!coding_decoders.is_empty()).then_some(StreamingBodyDecoder { coding_decoders }))
+    }
+
+    fn body_encoding(&self) -> BodyEncoding {
+        BodyEncoding::Decoded
+    }
+}
+
+impl ContentDecoder for StreamingContentDecoder {}
+
+/// A body decoder returned by [StreamingContentDecoder]
+#[derive(Debug)]
+pub struct StreamingBodyDecoder {
+    /// Decoders in the order they're applied
+    coding_decoders: Vec<CodingDecoder>,
+}
+
+impl DecodeBody for StreamingBodyDecoder {
+    fn decode_chunk<Error: From<io::Error>>(
+        &mut self,
+        chunk: &[u8],
+        sink: &mut dyn FnMut(&[u8]) -> Result<(), Error>,
+    ) -> Result<(), Error> {
+        decode_through(&mut self.coding_decoders, chunk, sink)
+    }
+
+    /// Passes the octets held back by each decoder through the following
+    /// decoders, before they're finished
+    fn finish_body<Error: From<io::Error>>(
+        &mut self,
+        sink: &mut dyn FnMut(&[u8]) -> Result<(), Error>,
+    ) -> Result<(), Error> {
+        finish_through(&mut self.coding_decoders, sink)
+    }
+}
+
+/// Passes `octets` through the decoders, and the octets decoded by the last
+/// one to `sink`
+fn decode_through<Error: From<io::Error>>(
+    coding_decoders: &mut [CodingDecoder],
+    octets: &[u8],
+    sink: &mut dyn FnMut(&[u8]) -> Result<(), Error>,
+) -> Result<(), Error> {
+    match coding_decoders.split_first_mut() {
+        Some((coding_decoder, following_decoders)) => coding_decoder
+            .decode(octets, &mut |decoded_octets| {
+                decode_through(following_decoders, decoded_octets, sink)
+            }),
+        None => sink(octets),
+    }
+}
+
+/// Finishes the decoders in order, passing the octets held back by each one
+/// through the following decoders
+fn finish_through<Error: From<io::Error>>(
+    coding_decoders: &mut [CodingDecoder],
+    sink: &mut dyn FnMut(&[u8]) -> Result<(), Error>,
+) -> Result<(), Error> {
+    let Some((coding_decoder, following_decoders)) = coding_decoders.split_first_mut() else {
+        return Ok(());
+    };
+    coding_decoder
+        .finish(&mut |decoded_octets| decode_through(following_decoders, decoded_octets, sink))?;
+
+    finish_through(following_decoders, sink)
+}
+
+/// A decoder of a single content coding passing decoded octets to a sink
+enum CodingDecoder {
+    Gzip(MultiGzDecoder<Vec<u8>>),
+    /// A `deflate` decoder, before the first two octets tell whether the
+    /// zlib wrapper is present
+    DeflateStart(Vec<u8>),
+    Deflate(DeflateDecoder),
+    Brotli(Box<BrotliDecoder>),
+    Zstd(ZstdDecoder),
+}
+
+impl CodingDecoder {
+    fn new(content_coding: &str) -> Result<Self, UnsupportedContentEncoding> {
+        match content_coding.to_ascii_lowercase().as_str() {
+            "gzip" | "x-gzip" => Ok(CodingDecoder::Gzip(MultiGzDecoder::new(Vec::new()))),
+            "deflate" => Ok(CodingDecoder::DeflateStart(Vec::new())),
+            "br" => Ok(CodingDecoder::Brotli(Box::new(BrotliDecoder::new()))),
+            "zstd" => ZstdDecoder::new()
+                .map(CodingDecoder::Zstd)
+                .map_err(|_| UnsupportedContentEncoding(content_coding.to_string())),
+            _ => Err(UnsupportedContentEncoding(content_coding.to_string())),
+        }
+    }
+
+    fn decode<Error: From<io::Error>>(
+        &mut self,
+        octets: &[u8],
+        sink: &mut dyn FnMut(&[u8]) -> Result<(), Error>,
+    ) -> Result<(), Error> {
+        match self {
+            CodingDecoder::Gzip(decoder) => write_in_pieces(decoder, octets, sink),
+            CodingDecoder::DeflateStart(start) => {
+                start.extend_from_slice(octets);
+                match *start.as_slice() {
+                    [first, second, ..] => {
+                        let start = std::mem::take(start);
+                        // A zlib header has the deflate method, and is a
+                        // multiple of 31 (see RFC 1950, section 2.2):
+                        let zlib_header =
+                            first & 0x0f == 8 && u16::from_be_bytes([first, second]) % 31 == 0;
+                        *self = CodingDecoder::Deflate(DeflateDecoder::new(zlib_header));
+
+                        self.decode(&start, sink)
+                    }
+                    _ => Ok(()),
+                }
+            }
+            CodingDecoder::Deflate(decoder) => decoder.decode(octets, sink),
+            CodingDecoder::Brotli(decoder) => decoder.decode(octets, sink),
+            CodingDecoder::Zstd(decoder) => decoder.decode(octets, sink),
+        }
+    }
+
+    fn finish<Error: From<io::Error>>(
+        &mut self,
+        sink: &mut dyn FnMut(&[u8]) -> Result<(), Error>,
+    ) -> Result<(), Error> {
+        match self {
+            CodingDecoder::Gzip(decoder) => {
+                decoder.try_finish()?;
+                pass_decoded_octets(decoder.get_mut(), sink)
+            }
+            CodingDecoder::DeflateStart(start) if start.is_empty() => Ok(()),
+            CodingDecoder::DeflateStart(_) => Err(truncated("deflate stream").into()),
+            CodingDecoder::Deflate(decoder) => decoder.finish(),
+            CodingDecoder::Brotli(decoder) => decoder.finish(),
+            CodingDecoder::Zstd(decoder) => decoder.finish(),
+        }
+    }
+}
+
+/// Writes `octets` into a `gzip` decoder, passing its buffer to `sink` after
+/// each write, which decodes at most about the size of the decoder's own
+/// output buffer
+fn write_in_pieces<Error: From<io::Error>>(
+    decoder: &mut MultiGzDecoder<Vec<u8>>,
+    mut octets: &[u8],
+    sink: &mut dyn FnMut(&[u8]) -> Result<(), Error>,
+) -> Result<(), Error> {
+    while !octets.is_empty() {
+        let length = decoder.write(octets)?;
+        if length == 0 {
+            return Err(io::Error::from(io::ErrorKind::WriteZero).into());
+        }
+        octets = &octets[length..];
+        pass_decoded_octets(decoder.get_mut(), sink)?;
+    }
+
+    Ok(())
+}
+
+/// Passes the octets of a buffer to `sink`, and empties it, so that its
+/// allocation is reused
+fn pass_decoded_octets<Error>(
+    buffer: &mut Vec<u8>,
+    sink: &mut dyn FnMut(&[u8]) -> Result<(), Error>,
+) -> Result<(), Error> {
+    if !buffer.is_empty() {
+        sink(buffer)?;
+        buffer.clear();
+    }
+
+    Ok(())
+}
+
+/// A `deflate` decoder, which decodes into an output buffer of a bounded
+/// size
+///
+/// Streams are checked for being complete, unlike with the [flate2] writers.
+struct DeflateDecoder {
+    decompress: Decompress,
+    output: Vec<u8>,
+    finished: bool,
+}
+
+impl DeflateDecoder {
+    fn new(zlib_header: bool) -> Self {
+        DeflateDecoder {
+            decompress: Decompress::new(zlib_header),
+            output: vec![0; DECODER_OUTPUT_SIZE],
+            finished: false,
+        }
+    }
+
+    fn decode<Error: From<io::Error>>(
+        &mut self,
+        mut octets: &[u8],
+        sink: &mut dyn FnMut(&[u8]) -> Result<(), Error>,
+    ) -> Result<(), Error> {
+        while !self.finished {
+            let total_in = self.decompress.total_in();
+            let total_out = self.decompress.total_out();
+            let status = self
+                .decompress
+                .decompress(octets, &mut self.output, FlushDecompress::None)
+                .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+            let consumed_length = (self.decompress.total_in() - total_in) as usize;
+            let length = (self.decompress.total_out() - total_out) as usize;
+            octets = &octets[consumed_length..];
+            if length > 0 {
+                sink(&self.output[..length])?;
+            }
+            self.finished = status == Status::StreamEnd;
+            // The decoder holds back no octets, unless it filled the output
+            // buffer:
+            if octets.is_empty() && length < self.output.len() {
+                return Ok(());
+            }
+            if consumed_length == 0 && length == 0 && !self.finished {
+                return Err(invalid_data("invalid deflate stream").into());
+            }
+        }
+
+        match octets.is_empty() {
+            true => Ok(()),
+            false => Err(invalid_data("data after the end of a deflate stream").into()),
+        }
+    }
+
+    fn finish<Error: From<io::Error>>(&mut self) -> Result<(), Error> {
+        match self.finished {
+            true => Ok(()),
+            false => Err(truncated("deflate stream").into()),
+        }
+    }
+}
+
+/// A `br` decoder, which decodes into an output buffer of a bounded size
+struct BrotliDecoder {
+    state: BrotliState<StandardAlloc, StandardAlloc, StandardAlloc>,
+    output: Vec<u8>,
+    total_out: usize,
+    received: bool,
+    finished: bool,
+}
+
+impl BrotliDecoder {
+    fn new() -> Self {
+        BrotliDecoder {
+            state: BrotliState::new(
+                StandardAlloc::default(),
+                StandardAlloc::default(),
+                StandardAlloc::default(),
+            ),
+            output: vec![0; DECODER_OUTPUT_SIZE],
+            total_out: 0,
+            received: false,
+            finished: false,
+        }
+    }
+
+    fn decode<Error: From<io::Error>>(
+        &mut self,
+        octets: &[u8],
+        sink: &mut dyn FnMut(&[u8]) -> Result<(), Error>,
+    ) -> Result<(), Error> {
+        let mut available_in = octets.len();
+        let mut input_offset = 0;
+        self.received |= !octets.is_empty();
+
+        while !self.finished {
+            let mut available_out = self.output.len();
+            let mut output_offset = 0;
+            let result = BrotliDecompressStream(
+                &mut available_in,
+                &mut input_offset,
+                octets,
+                &mut available_out,
+                &mut output_offset,
+                &mut self.output,
+                &mut self.total_out,
+                &mut self.state,
+            );
+            if output_offset > 0 {
+                sink(&self.output[..output_offset])?;
+            }
+            match result {
+                BrotliResult::NeedsMoreOutput => continue,
+                BrotliResult::NeedsMoreInput => return Ok(()),
+                BrotliResult::ResultSuccess => self.finished = true,
+                BrotliResult::ResultFailure => {
+                    return Err(invalid_data("invalid brotli stream").into());
+                }
+            }
+        }
+
+        match available_in {
+            0 => Ok(()),
+            _ => Err(invalid_data("data after the end of a brotli stream").into()),
+        }
+    }
+
+    fn finish<Error: From<io::Error>>(&mut self) -> Result<(), Error> {
+        match self.received && !self.finished {
+            true => Err(truncated("brotli stream").into()),
+            false => Ok(()),
+        }
+    }
+}
+
+/// A `zstd` decoder, which decodes into an output buffer of a bounded size
+///
+/// Frames are checked for being complete, unlike with
+/// [zstd::stream::write::Decoder].
+struct ZstdDecoder {
+    decoder: zstd::stream::raw::Decoder<'static>,
+    output: Vec<u8>,
+    received: bool,
+    /// Whether the last frame is complete, so that following octets start a
+    /// new frame
+    frame_finished: bool,
+}
+
+impl ZstdDecoder {
+    fn new() -> io::Result<Self> {
+        Ok(ZstdDecoder {
+            decoder: zstd::stream::raw::Decoder::new()?,
+            output: vec![0; DECODER_OUTPUT_SIZE],
+            received: false,
+            frame_finished: false,
+        })
+    }
+
+    fn decode<Error: From<io::Error>>(
+        &mut self,
+        octets: &[u8],
+        sink: &mut dyn FnMut(&[u8]) -> Result<(), Error>,
+    ) -> Result<(), Error> {
+        let mut input = InBuffer::around(octets);
+        self.received |= !octets.is_empty();
+
+        loop {
+            if self.frame_finished {
+                if input.pos() == octets.len() {
+                    return Ok(());
+                }
+                // Concatenated frames are decoded one after another:
+                self.decoder.reinit()?;
+                self.frame_finished = false;
+            }
+            let mut output = OutBuffer::around(self.output.as_mut_slice());
+            let hint = self.decoder.run(&mut input, &mut output)?;
+            let length = output.pos();
+            self.frame_finished = hint == 0;
+            if length > 0 {
+                sink(&self.output[..length])?;
+            }
+            // The decoder holds back no octets, unless it filled the output
+            // buffer:
+            if input.pos() == octets.len() && length < self.output.len() {
+                return Ok(());
+            }
+        }
+    }
+
+    fn finish<Error: From<io::Error>>(&mut self) -> Result<(), Error> {
+        match self.received && !self.frame_finished {
+            true => Err(truncated("zstd frame").into()),
+            false => Ok(()),
+        }
+    }
+}
+
+fn truncated(stream: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::UnexpectedEof,
+        format!("truncated {}", stream),
+    )
+}
+
+fn invalid_data(message: &'static str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+impl fmt::Debug for CodingDecoder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            CodingDecoder::Gzip(_) => "Gzip",
+            CodingDecoder::DeflateStart(_) => "DeflateStart",
+            CodingDecoder::Deflate(_) => "Deflate",
+            CodingDecoder::Brotli(_) => "Brotli",
+            CodingDecoder::Zstd(_) => "Zstd",
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct UnsupportedContentEncoding(pub String);
+
+impl fmt::Display for UnsupportedContentEncoding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unsupported content encoding {}", self.0)
+    }
+}
+
+impl std::error::Error for UnsupportedContentEncoding {}
@@ -0,0 +1,76 @@
+use super::interface::{ContentTransformer, NewContentTransform, TransformContent};
+use std::borrow::Cow;
+
+/// A [ContentTransformer] passing response bodies through transformers in
+/// order
+///
+/// Without transformers, bodies are hashed as they are.
+#[derive(Clone, Debug)]
+pub struct ChainedContentTransformer<Transformer> {
+    transformers: Vec<Transformer>,
+}
+
+impl<Transformer> ChainedContentTransformer<Transformer> {
+    pub fn new(transformers: Vec<Transformer>) -> Self {
+        ChainedContentTransformer { transformers }
+    }
+}
+
+impl<Transformer: NewContentTransform> NewContentTransform
+    for ChainedContentTransformer<Transformer>
+{
+    type ContentTransform = ChainedContentTransform<Transformer::ContentTransform>;
+
+    fn new_content_transform(&self, content_type: Option<&str>) -> Self::ContentTransform {
+        ChainedContentTransform {
+            transforms: self
+                .transformers
+                .iter()
+                .map(|transformer| transformer.new_content_transform(content_type))
+                .collect(),
+        }
+    }
+
+    fn transforms_content(&self) -> bool {
+        self.transformers
+            .iter()
+            .any(NewContentTransform::transforms_content)
+    }
+}
+
+impl<Transformer: NewContentTransform> ContentTransformer
+    for ChainedContentTransformer<Transformer>
+{
+}
+
+/// A transform returned by [ChainedContentTransformer]
+#[derive(Clone, Debug)]
+pub struct ChainedContentTransform<Transform> {
+    transforms: Vec<Transform>,
+}
+
+impl<Transform: TransformContent> TransformContent for ChainedContentTransform<Transform> {
+    fn transform_chunk<'a>(&mut self, chunk: &'a [u8]) -> Cow<'a, [u8]> {
+        let mut content = Cow::Borrowed(chunk);
+        for transform in &mut self.transforms {
+            content = match content {
+                Cow::Borrowed(content) => transform.transform_chunk(content),
+                Cow::Owned(content) => Cow::Owned(transform.transform_chunk(&content).into_owned()),
+            };
+        }
+
+        content
+    }
+
+    /// Passes the octets held back by each transform through the following
+    /// transforms, before they're finished
+    fn finish_content(&mut self) -> Vec<u8> {
+        let mut content = Vec::new();
+        for transform in &mut self.transforms {
+            content = transform.transform_chunk(&content).into_owned();
+            content.extend(transform.finish_content());
+        }
+
+        content
+    }
+}
@@ -0,0 +1,336 @@
+use super::interface::{ContentTransformer, NewContentTransform, TransformContent};
+use std::borrow::Cow;
+
+const COMMENT_START: &[u8] = b"<!--";
+const COMMENT_END: &[u8] = b"-->";
+const SCRIPT_START: &[u8] = b"<script";
+const SCRIPT_END: &[u8] = b"</script";
+
+/// A [ContentTransformer] canonicalizing HTML by dropping comments, and
+/// `script` elements, which often hold timestamps, and nonces
+///
+/// Tags are matched ignoring case, quoted attribute values of `script` start
+/// tags may hold `>`, and `<!-->`, and `<!--->` are empty comments.  Bodies
+/// of other media types than `text/html`, and `application/xhtml+xml` are
+/// hashed as they are, while bodies without a `Content-Type` are treated as
+/// HTML.
+#[derive(Clone, Debug, Default)]
+pub struct HtmlContentTransformer {}
+
+impl HtmlContentTransformer {
+    pub fn new() -> Self {
+        HtmlContentTransformer {}
+    }
+}
+
+impl NewContentTransform for HtmlContentTransformer {
+    type ContentTransform = HtmlContentTransform;
+
+    fn new_content_transform(&self, content_type: Option<&str>) -> Self::ContentTransform {
+        let is_html = content_type.is_none_or(|content_type| {
+            let media_type = content_type.split(';').next().unwrap_or_default().trim();
+
+            media_type.eq_ignore_ascii_case("text/html")
+                || media_type.eq_ignore_ascii_case("application/xhtml+xml")
+        });
+
+        HtmlContentTransform {
+            is_html,
+            state: HtmlState::Text,
+            pending: Vec::new(),
+        }
+    }
+
+    fn transforms_content(&self) -> bool {
+        true
+    }
+}
+
+impl ContentTransformer for HtmlContentTransformer {}
+
+/// Where in the markup an [HtmlContentTransform] is
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum HtmlState {
+    Text,
+    /// Right after `<!--`, where `>`, or `->` end an empty comment
+    CommentStart,
+    Comment,
+    ScriptStartTag,
+    /// After `=` in a `script` start tag
+    ScriptBeforeAttributeValue,
+    /// Within an attribute value quoted by the octet
+    ScriptQuotedAttributeValue(u8),
+    ScriptUnquotedAttributeValue,
+    Script,
+    ScriptEndTag,
+}
+
+/// A transform returned by [HtmlContentTransformer]
+#[derive(Clone, Debug)]
+pub struct HtmlContentTransform {
+    is_html: bool,
+    state: HtmlState,
+    /// Octets held back, since they may start, or end dropped markup
+    pending: Vec<u8>,
+}
+
+impl HtmlContentTransform {
+    /// Writes the kept octets of `input` into `output`, and returns the
+    /// number of octets consumed
+    ///
+    /// Unless `at_end`, octets that may start, or end dropped markup are left
+    /// unconsumed.
+    fn canonicalize(&mut self, input: &[u8], output: &mut Vec<u8>, at_end: bool) -> usize {
+        let mut position = 0;
+
+        loop {
+            let rest = &input[position..];
+            let (markup, next_state) = match self.state {
+                HtmlState::Text => {
+                    let Some(start) = rest.iter().position(|&octet| octet == b'<') else {
+                        output.extend_from_slice(rest);
+                        return input.len();
+                    };
+                    output.extend_from_slice(&rest[..start]);
+                    position += start;
+                    let tag = &input[position..];
+
+                    if starts_with_ignoring_case(tag, COMMENT_START) {
+                        position += COMMENT_START.len();
+                        self.state = HtmlState::CommentStart;
+                    } else if starts_with_ignoring_case(tag, SCRIPT_START)
+                        && tag.get(SCRIPT_START.len()).is_some_and(ends_tag_name)
+                    {
+                        position += SCRIPT_START.len();
+                        self.state = HtmlState::ScriptStartTag;
+                    } else if !at_end
+                        && (COMMENT_START.len() > tag.len()
+                            && starts_with_ignoring_case(COMMENT_START, tag)
+                            || SCRIPT_START.len() >= tag.len()
+                                && starts_with_ignoring_case(SCRIPT_START, tag))
+                    {
+                        return position;
+                    } else {
+                        output.push(b'<');
+                        position += 1;
+                    }
+                    continue;
+                }
+                HtmlState::CommentStart => {
+                    if let Some(end) = [&b">"[..], b"->"]
+                        .iter()
+                        .find(|comment_end| rest.starts_with(comment_end))
+                    {
+                        position += end.len();
+                        self.state = HtmlState::Text;
+                    } else if !at_end && b"->".starts_with(rest) {
+                        return position;
+                    } else {
+                        self.state = HtmlState::Comment;
+                    }
+                    continue;
+                }
+                HtmlState::Comment => (COMMENT_END, HtmlState::Text),
+                // Quotes only start attribute values after `=`:
+                HtmlState::ScriptStartTag => {
+                    let Some(end) = rest.iter().position(|&octet| matches!(octet, b'>' | b'='))
+                    else {
+                        return input.len();
+                    };
+                    position += end + 1;
+                    self.state = match rest[end] {
+                        b'>' => HtmlState::Script,
+                        _ => HtmlState::ScriptBeforeAttributeValue,
+                    };
+                    continue;
+                }
+                HtmlState::ScriptBeforeAttributeValue => {
+                    let Some(start) = rest.iter().position(|octet| !is_whitespace(octet)) else {
+                        return input.len();
+                    };
+                    position += start;
+                    self.state = match rest[start] {
+                        quote @ (b'"' | b'\'') => {
+                            position += 1;
+                            HtmlState::ScriptQuotedAttributeValue(quote)
+                        }
+                        b'>' => HtmlState::ScriptStartTag,
+                        _ => HtmlState::ScriptUnquotedAttributeValue,
+                    };
+                    continue;
+                }
+                HtmlState::ScriptQuotedAttributeValue(quote) => {
+                    let Some(end) = rest.iter().position(|&octet| octet == quote) else {
+                        return input.len();
+                    };
+                    position += end + 1;
+                    self.state = HtmlState::ScriptStartTag;
+                    continue;
+                }
+                // `>` ends both the value, and the tag:
+                HtmlState::ScriptUnquotedAttributeValue => {
+                    let Some(end) = rest
+                        .iter()
+                        .position(|octet| *octet == b'>' || is_whitespace(octet))
+                    else {
+                        return input.len();
+                    };
+                    position += end;
+                    self.state = HtmlState::ScriptStartTag;
+                    continue;
+                }
+                HtmlState::Script => (SCRIPT_END, HtmlState::ScriptEndTag),
+                HtmlState::ScriptEndTag => (&b">"[..], HtmlState::Text),
+            };
+
+            // The octets up to the end of dropped markup are skipped, but the
+            // octets that may start its end are held back:
+            match find_ignoring_case(rest, markup) {
+                Some(end) => {
+                    position += end + markup.len();
+                    self.state = next_state;
+                }
+                None if at_end => return input.len(),
+                None => return input.len().saturating_sub(markup.len() - 1).max(position),
+            }
+        }
+    }
+}
+
+impl TransformContent for HtmlContentTransform {
+    fn transform_chunk<'a>(&mut self, chunk: &'a [u8]) -> Cow<'a, [u8]> {
+        if !self.is_html {
+            return Cow::Borrowed(chunk);
+        }
+
+        let mut input = std::mem::take(&mut self.pending);
+        input.extend_from_slice(chunk);
+        let mut output = Vec::with_capacity(input.len());
+        let consumed = self.canonicalize(&input, &mut output, false);
+        input.drain(..consumed);
+        self.pending = input;
+
+        Cow::Owned(output)
+    }
+
+    fn finish_content(&mut self) -> Vec<u8> {
+        let input = std::mem::take(&mut self.pending);
+        let mut output = Vec::with_capacity(input.len());
+        self.canonicalize(&input, &mut output, true);
+
+        output
+    }
+}
+
+fn starts_with_ignoring_case(haystack: &[u8], prefix: &[u8]) -> bool {
+    haystack
+        .get(..prefix.len())
+        .is_some_and(|start| start.eq_ignore_ascii_case(prefix))
+}
+
+fn find_ignoring_case(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window.eq_ignore_ascii_case(needle))
+}
+
+/// Returns whether an octet ends a tag name, e.g., `script` in `<script>`
+fn ends_tag_name(octet: &u8) -> bool {
+    matches!(octet, b'>' | b'/') || is_whitespace(octet)
+}
+
+/// Returns whether an octet is ASCII whitespace as HTML defines it
+fn is_whitespace(octet: &u8) -> bool {
+    matches!(octet, b' ' | b'\t' | b'\n' | 0x0c | b'\r')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Transforms `body` split into chunks of every size, and returns the
+    /// output, which must not depend on the chunk size
+    fn transform(content_type: Option<&str>, body: &str) -> String {
+        let transformer = HtmlContentTransformer::new();
+        let outputs = (1..=body.len().max(1))
+            .map(|chunk_size| {
+                let mut transform = transformer.new_content_transform(content_type);
+                let mut output = Vec::new();
+                for chunk in body.as_bytes().chunks(chunk_size) {
+                    output.extend_from_slice(&transform.transform_chunk(chunk));
+                }
+                output.extend(transform.finish_content());
+
+                String::from_utf8(output).unwrap()
+            })
+            .collect::<Vec<_>>();
+        for (chunk_size, output) in (1..).zip(&outputs) {
+            assert_eq!(output, &outputs[0], "chunks of {chunk_size} octets");
+        }
+
+        outputs[0].clone()
+    }
+
+    #[test]
+    fn comments_and_scripts_are_dropped() {
+        assert_eq!(
+            transform(
+                Some("text/html; charset=utf-8"),
+                "<p>a<!-- 2026-10-18 -->b</p><SCRIPT>var t = 1;</Script >c"
+            ),
+            "<p>ab</p>c"
+        );
+        assert_eq!(
+            transform(None, "<script\nsrc=app.js></script><!---->d"),
+            "d"
+        );
+    }
+
+    #[test]
+    fn other_tags_starting_like_dropped_markup_are_kept() {
+        assert_eq!(
+            transform(None, "<scripts>a</scripts><!-x-><b>"),
+            "<scripts>a</scripts><!-x-><b>"
+        );
+        // Incomplete markup at the end of the body is kept:
+        assert_eq!(transform(None, "a<scr"), "a<scr");
+        assert_eq!(transform(None, "a<!-"), "a<!-");
+    }
+
+    #[test]
+    fn quoted_attribute_values_of_script_start_tags_may_hold_tag_ends() {
+        assert_eq!(
+            transform(
+                None,
+                r#"a<script data-x="1>2" data-y = '3>4' async>x > y</script>b"#
+            ),
+            "ab"
+        );
+        // Quotes within unquoted values, or names don't start values:
+        assert_eq!(transform(None, r#"a<script data-x=1"2>x</script>b"#), "ab");
+        assert_eq!(transform(None, r#"a<script "x>y"</script>b"#), "ab");
+        assert_eq!(transform(None, "a<script async=>x</script>b"), "ab");
+    }
+
+    #[test]
+    fn abruptly_closed_comments_are_empty() {
+        assert_eq!(transform(None, "a<!-->b<!--->c"), "abc");
+        assert_eq!(transform(None, "a<!---->b<!-- -> --->c"), "abc");
+    }
+
+    #[test]
+    fn unterminated_comments_and_scripts_are_dropped() {
+        assert_eq!(transform(None, "a<!-- b"), "a");
+        assert_eq!(transform(None, "a<!--"), "a");
+        assert_eq!(transform(None, "a<script>b</scrip"), "a");
+        assert_eq!(transform(None, r#"a<script x="b>"#), "a");
+    }
+
+    #[test]
+    fn bodies_of_other_media_types_are_kept() {
+        let body = "a<!-- b --><script>c</script>";
+
+        assert_eq!(transform(Some("text/plain"), body), body);
+        assert_eq!(transform(Some("application/xhtml+xml"), body), "a");
+    }
+}
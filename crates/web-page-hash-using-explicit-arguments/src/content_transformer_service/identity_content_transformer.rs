@@ -0,0 +1,40 @@
+use super::interface::{ContentTransformer, NewContentTransform, TransformContent};
+use std::borrow::Cow;
+
+/// A [ContentTransformer] hashing response bodies as they are
+#[derive(Clone, Debug, Default)]
+pub struct IdentityContentTransformer {}
+
+impl IdentityContentTransformer {
+    pub fn new() -> Self {
+        IdentityContentTransformer {}
+    }
+}
+
+impl NewContentTransform for IdentityContentTransformer {
+    type ContentTransform = IdentityContentTransform;
+
+    fn new_content_transform(&self, _content_type: Option<&str>) -> Self::ContentTransform {
+        IdentityContentTransform {}
+    }
+
+    fn transforms_content(&self) -> bool {
+        false
+    }
+}
+
+impl ContentTransformer for IdentityContentTransformer {}
+
+/// A transform returned by [IdentityContentTransformer]
+#[derive(Clone, Debug, Default)]
+pub struct IdentityContentTransform {}
+
+impl TransformContent for IdentityContentTransform {
+    fn transform_chunk<'a>(&mut self, chunk: &'a [u8]) -> Cow<'a, [u8]> {
+        Cow::Borrowed(chunk)
+    }
+
+    fn finish_content(&mut self) -> Vec<u8> {
+        Vec::new()
+    }
+}
@@ -0,0 +1,34 @@
+use std::borrow::Cow;
+
+/// A function trait transforming the chunks of one response body before
+/// they're hashed
+///
+/// A transform may hold back the end of a chunk, e.g., a partial match,
+/// until the following chunks, or the end of the body show what it becomes,
+/// so that digests don't depend on how the body is split into chunks.
+pub trait TransformContent {
+    /// Returns the transformed octets of the next chunk of the body
+    fn transform_chunk<'a>(&mut self, chunk: &'a [u8]) -> Cow<'a, [u8]>;
+
+    /// Returns the transformed octets held back at the end of the body
+    fn finish_content(&mut self) -> Vec<u8>;
+}
+
+/// A function trait creating a [TransformContent] for a response body
+pub trait NewContentTransform {
+    type ContentTransform: TransformContent;
+
+    /// Returns a transform for a body of the `Content-Type` header value
+    /// `content_type`, if known
+    fn new_content_transform(&self, content_type: Option<&str>) -> Self::ContentTransform;
+
+    /// Returns whether bodies are transformed at all, i.e., whether their
+    /// digests may differ from the digests of the raw bodies
+    fn transforms_content(&self) -> bool;
+}
+
+// The full service is composed of its method, and function traits:
+/// An injectable service normalizing response bodies before they're hashed,
+/// so that the digests of dynamic Web pages change only when their content
+/// does
+pub trait ContentTransformer: NewContentTransform {}
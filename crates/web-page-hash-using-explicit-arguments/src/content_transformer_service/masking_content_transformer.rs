@@ -0,0 +1,208 @@
+use super::interface::{ContentTransformer, NewContentTransform, TransformContent};
+use regex::bytes::Regex;
+use serde::Deserialize;
+use std::borrow::Cow;
+use std::sync::Arc;
+
+/// A regular expression, and the replacement of its matches in response
+/// bodies
+///
+/// It's deserialized from a `pattern`, and an optional `replacement`, which
+/// defaults to an empty string.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(try_from = "ContentMaskSetting")]
+pub struct ContentMask {
+    pattern: Regex,
+    replacement: Vec<u8>,
+}
+
+impl ContentMask {
+    /// Returns a mask replacing matches of `pattern` with `replacement`, in
+    /// which `$1`, or `$name` expand to capture groups
+    pub fn new(pattern: &str, replacement: &str) -> Result<Self, regex::Error> {
+        Ok(ContentMask {
+            pattern: Regex::new(pattern)?,
+            replacement: replacement.as_bytes().to_vec(),
+        })
+    }
+
+    pub fn pattern(&self) -> &str {
+        self.pattern.as_str()
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ContentMaskSetting {
+    pattern: String,
+    #[serde(default)]
+    replacement: String,
+}
+
+impl TryFrom<ContentMaskSetting> for ContentMask {
+    type Error = regex::Error;
+
+    fn try_from(setting: ContentMaskSetting) -> Result<Self, Self::Error> {
+        ContentMask::new(&setting.pattern, &setting.replacement)
+    }
+}
+
+/// A [ContentTransformer] replacing matches of regular expressions, e.g.,
+/// timestamps, and nonces
+///
+/// Bodies are masked line by line, in the order of the masks, so matches
+/// never span lines.  A line is held back until its end arrives.
+#[derive(Clone, Debug, Default)]
+pub struct MaskingContentTransformer {
+    masks: Arc<[ContentMask]>,
+}
+
+impl MaskingContentTransformer {
+    pub fn new(masks: Vec<ContentMask>) -> Self {
+        MaskingContentTransformer {
+            masks: masks.into(),
+        }
+    }
+}
+
+impl NewContentTransform for MaskingContentTransformer {
+    type ContentTransform = MaskingContentTransform;
+
+    fn new_content_transform(&self, _content_type: Option<&str>) -> Self::ContentTransform {
+        MaskingContentTransform {
+            masks: self.masks.clone(),
+            line: Vec::new(),
+        }
+    }
+
+    fn transforms_content(&self) -> bool {
+        !self.masks.is_empty()
+    }
+}
+
+impl ContentTransformer for MaskingContentTransformer {}
+
+/// A transform returned by [MaskingContentTransformer]
+#[derive(Clone, Debug)]
+pub struct MaskingContentTransform {
+    masks: Arc<[ContentMask]>,
+    /// The start of a line, which hasn't ended yet
+    line: Vec<u8>,
+}
+
+impl MaskingContentTransform {
+    fn mask_lines(&self, lines: &[u8]) -> Vec<u8> {
+        let mut output = Vec::with_capacity(lines.len());
+
+        for line in lines.split_inclusive(|&octet| octet == b'\n') {
+            let mut masked_line = Cow::Borrowed(line);
+            for mask in self.masks.iter() {
+                if let Cow::Owned(replaced_line) = mask
+                    .pattern
+                    .replace_all(&masked_line, mask.replacement.as_slice())
+                {
+                    masked_line = Cow::Owned(replaced_line);
+                }
+            }
+            output.extend_from_slice(&masked_line);
+        }
+
+        output
+    }
+}
+
+impl TransformContent for MaskingContentTransform {
+    fn transform_chunk<'a>(&mut self, chunk: &'a [u8]) -> Cow<'a, [u8]> {
+        let Some(line_end) = chunk.iter().rposition(|&octet| octet == b'\n') else {
+            self.line.extend_from_slice(chunk);
+            return Cow::Borrowed(&[]);
+        };
+
+        let mut lines = std::mem::replace(&mut self.line, chunk[line_end + 1..].to_vec());
+        lines.extend_from_slice(&chunk[..=line_end]);
+
+        Cow::Owned(self.mask_lines(&lines))
+    }
+
+    fn finish_content(&mut self) -> Vec<u8> {
+        let line = std::mem::take(&mut self.line);
+
+        self.mask_lines(&line)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Masks `body` split into chunks of every size, and returns the output,
+    /// which must not depend on the chunk size
+    fn transform(masks: &[ContentMask], body: &str) -> String {
+        let transformer = MaskingContentTransformer::new(masks.to_vec());
+        let outputs = (1..=body.len().max(1))
+            .map(|chunk_size| {
+                let mut transform = transformer.new_content_transform(None);
+                let mut output = Vec::new();
+                for chunk in body.as_bytes().chunks(chunk_size) {
+                    output.extend_from_slice(&transform.transform_chunk(chunk));
+                }
+                output.extend(transform.finish_content());
+
+                String::from_utf8(output).unwrap()
+            })
+            .collect::<Vec<_>>();
+        for (chunk_size, output) in (1..).zip(&outputs) {
+            assert_eq!(output, &outputs[0], "chunks of {chunk_size} octets");
+        }
+
+        outputs[0].clone()
+    }
+
+    #[test]
+    fn matches_are_replaced_in_the_order_of_the_masks() {
+        let masks = [
+            ContentMask::new(r"nonce-[0-9a-f]+", "nonce-X").unwrap(),
+            ContentMask::new(r"(\d{4})-\d{2}-\d{2}", "$1").unwrap(),
+            ContentMask::new(r"nonce-X", "").unwrap(),
+        ];
+
+        assert_eq!(
+            transform(&masks, "a nonce-0f3a b 2026-10-18\n2025-01-02 c"),
+            "a  b 2026\n2025 c"
+        );
+    }
+
+    #[test]
+    fn matches_never_span_lines() {
+        let masks = [ContentMask::new(r"a\s+b", "ab").unwrap()];
+
+        assert_eq!(transform(&masks, "a b\na\nb"), "ab\na\nb");
+    }
+
+    #[test]
+    fn bodies_are_only_transformed_with_masks() {
+        assert!(!MaskingContentTransformer::new(Vec::new()).transforms_content());
+        assert_eq!(transform(&[], "a\nb"), "a\nb");
+    }
+
+    #[test]
+    fn masks_are_deserialized_with_an_empty_default_replacement() {
+        #[derive(Deserialize)]
+        struct Settings {
+            masks: Vec<ContentMask>,
+        }
+
+        let settings: Settings = toml::from_str(
+            "[[masks]]\npattern = '\\d+'\n\n[[masks]]\npattern = 'x'\nreplacement = 'y'\n",
+        )
+        .unwrap();
+
+        assert_eq!(settings.masks[0].pattern(), r"\d+");
+        assert_eq!(transform(&settings.masks, "a1x2"), "ay");
+        assert!(
+            toml::from_str::<Settings>("[[masks]]\npattern = '('\n").is_err(),
+            "invalid patterns fail to deserialize"
+        );
+        assert!(toml::from_str::<Settings>("[[masks]]\npattern = 'a'\nflags = 'i'\n").is_err());
+    }
+}
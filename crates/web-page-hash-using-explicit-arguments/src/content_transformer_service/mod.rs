@@ -0,0 +1,20 @@
+pub mod chained_content_transformer;
+pub use chained_content_transformer::*;
+
+pub mod html_content_transformer;
+pub use html_content_transformer::*;
+
+pub mod identity_content_transformer;
+pub use identity_content_transformer::*;
+
+pub mod interface;
+pub use interface::*;
+
+pub mod masking_content_transformer;
+pub use masking_content_transformer::*;
+
+pub mod selectable_content_transformer;
+pub use selectable_content_transformer::*;
+
+pub mod whitespace_content_transformer;
+pub use whitespace_content_transformer::*;
@@ -0,0 +1,170 @@
+use super::chained_content_transformer::ChainedContentTransformer;
+use super::html_content_transformer::{HtmlContentTransform, HtmlContentTransformer};
+use super::interface::{ContentTransformer, NewContentTransform, TransformContent};
+use super::masking_content_transformer::{MaskingContentTransform, MaskingContentTransformer};
+use super::whitespace_content_transformer::{
+    WhitespaceContentTransform, WhitespaceContentTransformer,
+};
+use crate::configuration_service::ContentTransformerConfiguration;
+use serde::Deserialize;
+use std::borrow::Cow;
+use std::fmt;
+use std::str::FromStr;
+
+/// Normalizations of response bodies that can be enabled by name
+///
+/// It's deserialized from any name that [ContentNormalization::from_str]
+/// recognizes.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq)]
+#[serde(try_from = "String")]
+pub enum ContentNormalization {
+    Whitespace,
+    Html,
+}
+
+impl ContentNormalization {
+    /// Returns the name of the normalization (e.g., `whitespace`)
+    pub fn name(self) -> &'static str {
+        match self {
+            ContentNormalization::Whitespace => "whitespace",
+            ContentNormalization::Html => "html",
+        }
+    }
+}
+
+impl fmt::Display for ContentNormalization {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for ContentNormalization {
+    type Err = UnknownContentNormalization;
+
+    /// Parses a normalization name ignoring case
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name.to_ascii_lowercase().as_str() {
+            "whitespace" => Ok(ContentNormalization::Whitespace),
+            "html" => Ok(ContentNormalization::Html),
+            _ => Err(UnknownContentNormalization(name.to_string())),
+        }
+    }
+}
+
+impl TryFrom<String> for ContentNormalization {
+    type Error = UnknownContentNormalization;
+
+    fn try_from(name: String) -> Result<Self, Self::Error> {
+        name.parse()
+    }
+}
+
+#[derive(Debug)]
+pub struct UnknownContentNormalization(pub String);
+
+impl fmt::Display for UnknownContentNormalization {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "unknown normalization {} (expected whitespace, or html)",
+            self.0
+        )
+    }
+}
+
+impl std::error::Error for UnknownContentNormalization {}
+
+/// A [ContentTransformer] selected at run time
+#[derive(Clone, Debug)]
+pub enum SelectableContentTransformer {
+    Whitespace(WhitespaceContentTransformer),
+    Html(HtmlContentTransformer),
+    Masking(MaskingContentTransformer),
+}
+
+impl ChainedContentTransformer<SelectableContentTransformer> {
+    /// Returns the chain of the configured transformers
+    ///
+    /// HTML is canonicalized first, then masks are applied, and whitespace is
+    /// normalized last, whatever the order of the configured normalizations.
+    pub fn from_configuration(configuration: &impl ContentTransformerConfiguration) -> Self {
+        let normalizations = configuration.content_normalizations();
+        let mut transformers = Vec::new();
+
+        if normalizations.contains(&ContentNormalization::Html) {
+            transformers.push(SelectableContentTransformer::Html(
+                HtmlContentTransformer::new(),
+            ));
+        }
+        if !configuration.content_masks().is_empty() {
+            transformers.push(SelectableContentTransformer::Masking(
+                MaskingContentTransformer::new(configuration.content_masks().to_vec()),
+            ));
+        }
+        if normalizations.contains(&ContentNormalization::Whitespace) {
+            transformers.push(SelectableContentTransformer::Whitespace(
+                WhitespaceContentTransformer::new(),
+            ));
+        }
+
+        ChainedContentTransformer::new(transformers)
+    }
+}
+
+impl NewContentTransform for SelectableContentTransformer {
+    type ContentTransform = SelectableContentTransform;
+
+    fn new_content_transform(&self, content_type: Option<&str>) -> Self::ContentTransform {
+        match self {
+            SelectableContentTransformer::Whitespace(transformer) => {
+                SelectableContentTransform::Whitespace(
+                    transformer.new_content_transform(content_type),
+                )
+            }
+            SelectableContentTransformer::Html(transformer) => {
+                SelectableContentTransform::Html(transformer.new_content_transform(content_type))
+            }
+            SelectableContentTransformer::Masking(transformer) => {
+                SelectableContentTransform::Masking(transformer.new_content_transform(content_type))
+            }
+        }
+    }
+
+    fn transforms_content(&self) -> bool {
+        match self {
+            SelectableContentTransformer::Whitespace(transformer) => {
+                transformer.transforms_content()
+            }
+            SelectableContentTransformer::Html(transformer) => transformer.transforms_content(),
+            SelectableContentTransformer::Masking(transformer) => transformer.transforms_content(),
+        }
+    }
+}
+
+impl ContentTransformer for SelectableContentTransformer {}
+
+/// A transform returned by [SelectableContentTransformer]
+#[derive(Clone, Debug)]
+pub enum SelectableContentTransform {
+    Whitespace(WhitespaceContentTransform),
+    Html(HtmlContentTransform),
+    Masking(MaskingContentTransform),
+}
+
+impl TransformContent for SelectableContentTransform {
+    fn transform_chunk<'a>(&mut self, chunk: &'a [u8]) -> Cow<'a, [u8]> {
+        match self {
+            SelectableContentTransform::Whitespace(transform) => transform.transform_chunk(chunk),
+            SelectableContentTransform::Html(transform) => transform.transform_chunk(chunk),
+            SelectableContentTransform::Masking(transform) => transform.transform_chunk(chunk),
+        }
+    }
+
+    fn finish_content(&mut self) -> Vec<u8> {
+        match self {
+            SelectableContentTransform::Whitespace(transform) => transform.finish_content(),
+            SelectableContentTransform::Html(transform) => transform.finish_content(),
+            SelectableContentTransform::Masking(transform) => transform.finish_content(),
+        }
+    }
+}
@@ -0,0 +1,115 @@
+use super::interface::{ContentTransformer, NewContentTransform, TransformContent};
+use std::borrow::Cow;
+
+/// A [ContentTransformer] normalizing whitespace, and line endings
+///
+/// `CRLF`, and `CR` line endings become `LF`, runs of spaces, tabs, vertical
+/// tabs, and form feeds become a single space, and such whitespace at the
+/// end of lines, and of the body is dropped.
+#[derive(Clone, Debug, Default)]
+pub struct WhitespaceContentTransformer {}
+
+impl WhitespaceContentTransformer {
+    pub fn new() -> Self {
+        WhitespaceContentTransformer {}
+    }
+}
+
+impl NewContentTransform for WhitespaceContentTransformer {
+    type ContentTransform = WhitespaceContentTransform;
+
+    fn new_content_transform(&self, _content_type: Option<&str>) -> Self::ContentTransform {
+        WhitespaceContentTransform::default()
+    }
+
+    fn transforms_content(&self) -> bool {
+        true
+    }
+}
+
+impl ContentTransformer for WhitespaceContentTransformer {}
+
+/// A transform returned by [WhitespaceContentTransformer]
+#[derive(Clone, Debug, Default)]
+pub struct WhitespaceContentTransform {
+    /// Whether whitespace was skipped since the last written octet
+    skipped_whitespace: bool,
+    /// Whether the last octet was a `CR`, so that a following `LF` belongs
+    /// to the same line ending
+    after_carriage_return: bool,
+}
+
+impl TransformContent for WhitespaceContentTransform {
+    fn transform_chunk<'a>(&mut self, chunk: &'a [u8]) -> Cow<'a, [u8]> {
+        let mut output = Vec::with_capacity(chunk.len());
+
+        for &octet in chunk {
+            let after_carriage_return = std::mem::take(&mut self.after_carriage_return);
+
+            match octet {
+                b'\n' if after_carriage_return => {}
+                b'\r' | b'\n' => {
+                    self.skipped_whitespace = false;
+                    self.after_carriage_return = octet == b'\r';
+                    output.push(b'\n');
+                }
+                b' ' | b'\t' | 0x0b | 0x0c => self.skipped_whitespace = true,
+                _ => {
+                    if std::mem::take(&mut self.skipped_whitespace) {
+                        output.push(b' ');
+                    }
+                    output.push(octet);
+                }
+            }
+        }
+
+        Cow::Owned(output)
+    }
+
+    fn finish_content(&mut self) -> Vec<u8> {
+        Vec::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Transforms `body` split into chunks of every size, and returns the
+    /// output, which must not depend on the chunk size
+    fn transform(body: &str) -> String {
+        let outputs = (1..=body.len().max(1))
+            .map(|chunk_size| {
+                let mut transform = WhitespaceContentTransformer::new().new_content_transform(None);
+                let mut output = Vec::new();
+                for chunk in body.as_bytes().chunks(chunk_size) {
+                    output.extend_from_slice(&transform.transform_chunk(chunk));
+                }
+                output.extend(transform.finish_content());
+
+                String::from_utf8(output).unwrap()
+            })
+            .collect::<Vec<_>>();
+        for (chunk_size, output) in (1..).zip(&outputs) {
+            assert_eq!(output, &outputs[0], "chunks of {chunk_size} octets");
+        }
+
+        outputs[0].clone()
+    }
+
+    #[test]
+    fn line_endings_become_line_feeds() {
+        assert_eq!(transform("a\r\nb\rc\nd\r\r\ne"), "a\nb\nc\nd\n\ne");
+    }
+
+    #[test]
+    fn runs_of_whitespace_become_a_single_space() {
+        assert_eq!(transform("a \t\x0b\x0c b\t\tc"), "a b c");
+    }
+
+    #[test]
+    fn whitespace_at_the_ends_of_lines_and_the_body_is_dropped() {
+        assert_eq!(transform("a  \r\n\tb\t\n  "), "a\n b\n");
+        assert_eq!(transform(" \t"), "");
+    }
+}
@@ -0,0 +1,27 @@
+/// The Web pages a crawl follows links to
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum CrawlScope {
+    /// Web pages with the same scheme, host, and port
+    Origin(url::Origin),
+    /// Web pages with URLs starting with a prefix, e.g.,
+    /// `https://example.com/docs/`
+    Prefix(String),
+}
+
+impl CrawlScope {
+    /// Returns the Web pages starting with `prefix`, when it's given, or
+    /// else the Web pages of the origin of `start_url`
+    pub fn new(start_url: &url::Url, prefix: Option<String>) -> Self {
+        match prefix {
+            Some(prefix) => CrawlScope::Prefix(prefix),
+            None => CrawlScope::Origin(start_url.origin()),
+        }
+    }
+
+    pub fn contains(&self, url: &url::Url) -> bool {
+        match self {
+            CrawlScope::Origin(origin) => url.origin() == *origin,
+            CrawlScope::Prefix(prefix) => url.as_str().starts_with(prefix.as_str()),
+        }
+    }
+}
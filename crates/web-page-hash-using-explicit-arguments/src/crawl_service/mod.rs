@@ -0,0 +1,8 @@
+pub mod crawl_scope;
+pub use crawl_scope::*;
+
+pub mod robots_txt;
+pub use robots_txt::*;
+
+pub mod web_site_crawler;
+pub use web_site_crawler::*;
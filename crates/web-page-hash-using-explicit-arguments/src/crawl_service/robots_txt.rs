@@ -0,0 +1,172 @@
+use super::web_site_crawler::CalculatedPage;
+
+/// The rules of a `robots.txt` file (RFC 9309) for one user agent
+///
+/// The rules of the groups naming the product token of the user agent are
+/// used, or else the rules of the `*` groups.  The longest matching rule
+/// decides whether a path may be crawled, and `allow` wins ties.  Rules may
+/// have `*` wildcards, and end with `$` to match the end of the path.  The
+/// default has no rules, and allows crawling everything, e.g., when a site
+/// has no `robots.txt` file.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct RobotsTxt {
+    rules: Vec<RobotsRule>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct RobotsRule {
+    allow: bool,
+    pattern: String,
+}
+
+impl RobotsTxt {
+    /// Returns the URL of the `robots.txt` file governing a URL
+    pub fn url(url: &url::Url) -> Result<url::Url, url::ParseError> {
+        url.join("/robots.txt")
+    }
+
+    /// Fetches the `robots.txt` file governing a URL with `calculate_page`,
+    /// as a [WebSiteCrawler] calculates Web pages, and parses the groups
+    /// applying to the product token of `user_agent`
+    ///
+    /// Crawling isn't restricted, when the file can't be fetched.
+    ///
+    /// [WebSiteCrawler]: super::WebSiteCrawler
+    pub async fn fetch<CalculatePage>(
+        url: &url::Url,
+        user_agent: &str,
+        calculate_page: CalculatePage,
+    ) -> Result<Self, url::ParseError>
+    where
+        CalculatePage: AsyncFnOnce(&url::Url) -> CalculatedPage,
+    {
+        let robots_txt_url = RobotsTxt::url(url)?;
+        let calculated_page = calculate_page(&robots_txt_url).await;
+        let record = calculated_page.records.first();
+
+        if let Some(error) = record.and_then(|record| record.error.as_ref()) {
+            tracing::info!(
+                url = %robots_txt_url,
+                error = error.message,
+                "robots.txt couldn't be fetched"
+            );
+            return Ok(RobotsTxt::default());
+        }
+        let product_token = user_agent.split('/').next().unwrap_or_default();
+
+        Ok(
+            match (
+                record.and_then(|record| record.status),
+                calculated_page.body,
+            ) {
+                (Some(status), Some(body)) if (200..300).contains(&status) => {
+                    RobotsTxt::parse(&String::from_utf8_lossy(&body), product_token)
+                }
+                _ => RobotsTxt::default(),
+            },
+        )
+    }
+
+    /// Parses the groups of a `robots.txt` file that apply to the product
+    /// token of a user agent (e.g., `web-page-hash`)
+    pub fn parse(text: &str, product_token: &str) -> Self {
+        let mut product_rules = None::<Vec<RobotsRule>>;
+        let mut any_rules = None::<Vec<RobotsRule>>;
+        let mut group_user_agents = Vec::<String>::new();
+        let mut in_group_rules = false;
+
+        for line in text.lines() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            let Some((key, value)) = line.split_once(':') else {
+                continue;
+            };
+            let value = value.trim();
+
+            match key.trim().to_ascii_lowercase().as_str() {
+                "user-agent" => {
+                    // A user agent after rules starts another group:
+                    if in_group_rules {
+                        group_user_agents.clear();
+                        in_group_rules = false;
+                    }
+                    group_user_agents.push(value.to_ascii_lowercase());
+                }
+                key @ ("allow" | "disallow") => {
+                    in_group_rules = true;
+                    // An empty `disallow` allows everything:
+                    if value.is_empty() {
+                        continue;
+                    }
+                    let rule = RobotsRule {
+                        allow: key == "allow",
+                        pattern: value.to_string(),
+                    };
+
+                    if group_user_agents
+                        .iter()
+                        .any(|user_agent| user_agent.eq_ignore_ascii_case(product_token))
+                    {
+                        product_rules.get_or_insert_default().push(rule.clone());
+                    }
+                    if group_user_agents.iter().any(|user_agent| user_agent == "*") {
+                        any_rules.get_or_insert_default().push(rule);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        RobotsTxt {
+            rules: product_rules.or(any_rules).unwrap_or_default(),
+        }
+    }
+
+    /// Returns whether a URL may be crawled
+    ///
+    /// The path, and query of the URL are matched, and `/robots.txt` is always
+    /// allowed.
+    pub fn allows(&self, url: &url::Url) -> bool {
+        let path = match url.query() {
+            Some(query) => format!("{}?{}", url.path(), query),
+            None => url.path().to_string(),
+        };
+        if path == "/robots.txt" {
+            return true;
+        }
+
+        self.rules
+            .iter()
+            .filter(|rule| matches_pattern(&rule.pattern, &path))
+            .max_by_key(|rule| (rule.pattern.len(), rule.allow))
+            .is_none_or(|rule| rule.allow)
+    }
+}
+
+/// Matches a path against a rule pattern with `*` wildcards, and an optional
+/// `$` end anchor
+fn matches_pattern(pattern: &str, path: &str) -> bool {
+    let (pattern, is_anchored) = match pattern.strip_suffix('$') {
+        Some(pattern) => (pattern, true),
+        None => (pattern, false),
+    };
+    let mut parts = pattern.split('*');
+    let prefix = parts.next().unwrap_or_default();
+    let Some(mut rest) = path.strip_prefix(prefix) else {
+        return false;
+    };
+    let parts = parts.collect::<Vec<_>>();
+
+    for (index, part) in parts.iter().enumerate() {
+        // The last part of an anchored pattern matches the end of the path,
+        // while other parts match their first occurrence:
+        if is_anchored && index == parts.len() - 1 {
+            return rest.ends_with(part);
+        }
+        let Some(offset) = rest.find(part) else {
+            return false;
+        };
+        rest = &rest[offset + part.len()..];
+    }
+
+    !is_anchored || rest.is_empty()
+}
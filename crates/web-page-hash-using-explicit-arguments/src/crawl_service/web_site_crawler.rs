@@ -0,0 +1,149 @@
+use super::crawl_scope::CrawlScope;
+use super::robots_txt::RobotsTxt;
+use crate::digest_record::WebPageDigestRecord;
+use crate::link_extractor_service::ExtractLinks;
+use futures_util::StreamExt;
+use std::collections::HashSet;
+
+/// A Web page calculated while crawling
+#[derive(Clone, Debug, Default)]
+pub struct CalculatedPage {
+    /// The records of the Web page for each algorithm
+    pub records: Vec<WebPageDigestRecord>,
+    /// The response body, which links are extracted from, or [None], when
+    /// it couldn't be received
+    pub body: Option<Vec<u8>>,
+}
+
+/// An entry of the manifest of a crawl
+#[derive(Clone, Debug)]
+pub struct CrawledPage {
+    pub url: url::Url,
+    /// The number of links followed from the first Web page
+    pub depth: u32,
+    /// The records of the Web page for each algorithm
+    pub records: Vec<WebPageDigestRecord>,
+}
+
+/// Crawls a Web site from a Web page, following the links a [LinkExtractor]
+/// finds within a [CrawlScope], and calculating the digests of each reached
+/// Web page
+///
+/// Web pages are crawled breadth first, one depth at a time, so that each
+/// Web page is reached by its shortest chain of links.  Up to `concurrency`
+/// Web pages of a depth are calculated at once.  Web pages disallowed by
+/// `robots.txt`, beyond the maximum depth, or beyond the page limit aren't
+/// calculated.  Web pages are calculated by a function given to
+/// [WebSiteCrawler::crawl], as [WebPageWatcher] does, so that any binding of
+/// the calculate Web page message digest service can be used.
+///
+/// [LinkExtractor]: crate::link_extractor_service::LinkExtractor
+/// [WebPageWatcher]: crate::watch_service::WebPageWatcher
+#[derive(Debug)]
+pub struct WebSiteCrawler<LinkExtractor> {
+    link_extractor: LinkExtractor,
+    scope: CrawlScope,
+    robots_txt: RobotsTxt,
+    max_depth: u32,
+    page_limit: usize,
+    concurrency: usize,
+}
+
+impl<LinkExtractor: ExtractLinks> WebSiteCrawler<LinkExtractor> {
+    pub fn new(
+        link_extractor: LinkExtractor,
+        scope: CrawlScope,
+        robots_txt: RobotsTxt,
+        max_depth: u32,
+        page_limit: usize,
+        concurrency: usize,
+    ) -> Self {
+        WebSiteCrawler {
+            link_extractor,
+            scope,
+            robots_txt,
+            max_depth,
+            page_limit,
+            concurrency: concurrency.max(1),
+        }
+    }
+
+    /// Crawls from `start_url`, and returns the manifest of the calculated
+    /// Web pages in the order they were reached
+    ///
+    /// `calculate_page` returns the records of a Web page, and its body.
+    /// The start URL is calculated even if it's out of scope, unless
+    /// `robots.txt` disallows it.
+    pub async fn crawl<CalculatePage>(
+        &self,
+        start_url: &url::Url,
+        calculate_page: CalculatePage,
+    ) -> Vec<CrawledPage>
+    where
+        CalculatePage: AsyncFn(&url::Url) -> CalculatedPage,
+    {
+        let mut start_url = start_url.clone();
+        start_url.set_fragment(None);
+        let mut manifest = Vec::new();
+        if !self.robots_txt.allows(&start_url) || self.page_limit == 0 {
+            return manifest;
+        }
+        let mut seen_urls = HashSet::from([start_url.to_string()]);
+        let mut scheduled_count = 1;
+        let mut depth_urls = vec![start_url];
+        let calculate_page = &calculate_page;
+
+        for depth in 0..=self.max_depth {
+            let mut calculated_pages = futures_util::stream::iter(depth_urls)
+                .map(|url| async move {
+                    let calculated_page = calculate_page(&url).await;
+
+                    (url, calculated_page)
+                })
+                .buffered(self.concurrency);
+            let mut next_depth_urls = Vec::new();
+
+            while let Some((url, calculated_page)) = calculated_pages.next().await {
+                if depth < self.max_depth
+                    && let Some(body) = &calculated_page.body
+                {
+                    // Links are relative to the URL after redirects:
+                    let record = calculated_page.records.first();
+                    let base_url = record
+                        .and_then(|record| record.final_url.as_deref())
+                        .and_then(|final_url| url::Url::parse(final_url).ok())
+                        .unwrap_or_else(|| url.clone());
+                    seen_urls.insert(base_url.to_string());
+                    let content_type = record.and_then(|record| record.content_type.as_deref());
+
+                    for link in self
+                        .link_extractor
+                        .extract_links(&base_url, content_type, body)
+                    {
+                        if scheduled_count < self.page_limit
+                            && self.scope.contains(&link)
+                            && self.robots_txt.allows(&link)
+                            && seen_urls.insert(link.to_string())
+                        {
+                            next_depth_urls.push(link);
+                            scheduled_count += 1;
+                        }
+                    }
+                }
+
+                manifest.push(CrawledPage {
+                    url,
+                    depth,
+                    records: calculated_page.records,
+                });
+            }
+
+            if next_depth_urls.is_empty() {
+                break;
+            }
+            depth_urls = next_depth_urls;
+        }
+
+        manifest
+    }
+}
@@ -0,0 +1,56 @@
+use super::basic_auth_credential_provider::BasicAuthCredentialProvider;
+use super::bearer_token_credential_provider::BearerTokenCredentialProvider;
+use super::environment_credential_provider::EnvironmentCredentialProvider;
+use super::interface::{Credential, CredentialError, CredentialProvider, LookUpCredential};
+use super::netrc_credential_provider::NetrcCredentialProvider;
+use super::no_credential_provider::NoCredentialProvider;
+
+/// A [CredentialProvider] selected at run time
+#[derive(Debug)]
+pub enum SelectableCredentialProvider {
+    None(NoCredentialProvider),
+    BearerToken(BearerTokenCredentialProvider),
+    BasicAuth(BasicAuthCredentialProvider),
+    Netrc(NetrcCredentialProvider),
+    Environment(EnvironmentCredentialProvider),
+}
+
+impl LookUpCredential for SelectableCredentialProvider {
+    type Error = CredentialError;
+
+    fn look_up_credential(&self, url: &str) -> Result<Option<Credential>, Self::Error> {
+        match self {
+            SelectableCredentialProvider::None(provider) => Ok(provider.look_up_credential(url)?),
+            SelectableCredentialProvider::BearerToken(provider) => {
+                Ok(provider.look_up_credential(url)?)
+            }
+            SelectableCredentialProvider::BasicAuth(provider) => {
+                Ok(provider.look_up_credential(url)?)
+            }
+            SelectableCredentialProvider::Netrc(provider) => Ok(provider.look_up_credential(url)?),
+            SelectableCredentialProvider::Environment(provider) => provider.look_up_credential(url),
+        }
+    }
+
+    fn look_up_redirect_credential(&self, url: &str) -> Result<Option<Credential>, Self::Error> {
+        match self {
+            SelectableCredentialProvider::None(provider) => {
+                Ok(provider.look_up_redirect_credential(url)?)
+            }
+            SelectableCredentialProvider::BearerToken(provider) => {
+                Ok(provider.look_up_redirect_credential(url)?)
+            }
+            SelectableCredentialProvider::BasicAuth(provider) => {
+                Ok(provider.look_up_redirect_credential(url)?)
+            }
+            SelectableCredentialProvider::Netrc(provider) => {
+                Ok(provider.look_up_redirect_credential(url)?)
+            }
+            SelectableCredentialProvider::Environment(provider) => {
+                provider.look_up_redirect_credential(url)
+            }
+        }
+    }
+}
+
+impl CredentialProvider for SelectableCredentialProvider {}
@@ -0,0 +1,337 @@
+use super::expected_digest::ExpectedDigest;
+use crate::message_digest_service::MessageDigestAlgorithm;
+use std::fmt;
+use subtle::ConstantTimeEq;
+
+/// A line of a coreutils-style checksum file, naming a Web page by its URL
+///
+/// Two formats are supported, as written by, e.g., `sha3sum`, and
+/// `sha256sum`:
+///
+/// * GNU: `<hex>  <url>`, or `<hex> *<url>`;
+/// * BSD-tag: `<algorithm> (<url>) = <hex>` (e.g., `SHA3-256 (<url>) = <hex>`).
+///
+/// Names containing backslashes, or newlines are escaped as coreutils does,
+/// by prefixing the line with a backslash.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ChecksumLine {
+    algorithm: MessageDigestAlgorithm,
+    digest: Vec<u8>,
+    url: String,
+}
+
+/// The format of a [ChecksumLine]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ChecksumLineFormat {
+    Gnu,
+    Bsd,
+}
+
+impl ChecksumLine {
+    pub fn new(algorithm: MessageDigestAlgorithm, digest: Vec<u8>, url: String) -> Self {
+        ChecksumLine {
+            algorithm,
+            digest,
+            url,
+        }
+    }
+
+    /// Parses a line in either format
+    ///
+    /// `default_algorithm` is the algorithm of GNU lines, which don't name
+    /// one.
+    pub fn parse(
+        line: &str,
+        default_algorithm: MessageDigestAlgorithm,
+    ) -> Result<Self, ParseChecksumLineError> {
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        let (is_escaped, line) = match line.strip_prefix('\\') {
+            Some(line) => (true, line),
+            None => (false, line),
+        };
+
+        let (algorithm, hex_digest, name) = match parse_bsd_line(line) {
+            Some((algorithm, name, hex_digest)) => (algorithm, hex_digest, name),
+            None => {
+                let (hex_digest, name) = parse_gnu_line(line).ok_or(ParseChecksumLineError)?;
+                (default_algorithm, hex_digest, name)
+            }
+        };
+
+        if hex_digest.len() != 2 * algorithm.digest_length() {
+            return Err(ParseChecksumLineError);
+        }
+        let digest = hex::decode(hex_digest).map_err(|_| ParseChecksumLineError)?;
+        let url = if is_escaped {
+            unescape(name).ok_or(ParseChecksumLineError)?
+        } else {
+            name.to_string()
+        };
+
+        Ok(ChecksumLine {
+            algorithm,
+            digest,
+            url,
+        })
+    }
+
+    pub fn algorithm(&self) -> MessageDigestAlgorithm {
+        self.algorithm
+    }
+
+    pub fn digest(&self) -> &[u8] {
+        &self.digest
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Compares `actual_digest` with the listed digest in constant time
+    pub fn matches(&self, actual_digest: &[u8]) -> bool {
+        self.digest.ct_eq(actual_digest).into()
+    }
+
+    /// Returns the listed digest as an expected digest
+    pub fn expected_digest(&self) -> ExpectedDigest {
+        ExpectedDigest::new(Some(self.algorithm), self.digest.clone())
+    }
+
+    /// Returns the line in a format, without a line terminator
+    pub fn format(&self, format: ChecksumLineFormat) -> String {
+        let (prefix, url) = escape(&self.url);
+        let hex_digest = hex::encode(&self.digest);
+
+        match format {
+            ChecksumLineFormat::Gnu => format!("{}{}  {}", prefix, hex_digest, url),
+            ChecksumLineFormat::Bsd => {
+                format!("{}{} ({}) = {}", prefix, self.algorithm, url, hex_digest)
+            }
+        }
+    }
+}
+
+fn parse_bsd_line(line: &str) -> Option<(MessageDigestAlgorithm, &str, &str)> {
+    let (algorithm_name, rest) = line.split_once(" (")?;
+    let algorithm = algorithm_name.parse().ok()?;
+    let (name, hex_digest) = rest.rsplit_once(") = ")?;
+
+    Some((algorithm, name, hex_digest))
+}
+
+fn parse_gnu_line(line: &str) -> Option<(&str, &str)> {
+    let (hex_digest, rest) = line.split_once(' ')?;
+    // A space, or an asterisk marks text, or binary mode, which are the same
+    // for Web pages:
+    let name = rest.strip_prefix([' ', '*'])?;
+
+    (!hex_digest.is_empty() && !name.is_empty()).then_some((hex_digest, name))
+}
+
+fn escape(name: &str) -> (&'static str, String) {
+    if name.contains(['\\', '\n', '\r']) {
+        let name = name
+            .replace('\\', "\\\\")
+            .replace('\n', "\\n")
+            .replace('\r', "\\r");
+        ("\\", name)
+    } else {
+        ("", name.to_string())
+    }
+}
+
+fn unescape(name: &str) -> Option<String> {
+    let mut unescaped_name = String::with_capacity(name.len());
+    let mut characters = name.chars();
+
+    while let Some(character) = characters.next() {
+        if character != '\\' {
+            unescaped_name.push(character);
+            continue;
+        }
+
+        match characters.next()? {
+            '\\' => unescaped_name.push('\\'),
+            'n' => unescaped_name.push('\n'),
+            'r' => unescaped_name.push('\r'),
+            _ => return None,
+        }
+    }
+
+    Some(unescaped_name)
+}
+
+#[derive(Debug)]
+pub struct ParseChecksumLineError;
+
+impl fmt::Display for ParseChecksumLineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "improperly formatted checksum line")
+    }
+}
+
+impl std::error::Error for ParseChecksumLineError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SHA3_256_HEX: &str = "a7ffc6f8bf1ed76651c14756a061d662f580ff4de43b49fa82d80a4b80f8434a";
+    const SHA256_HEX: &str = "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+
+    fn parse(line: &str) -> Result<ChecksumLine, ParseChecksumLineError> {
+        ChecksumLine::parse(line, MessageDigestAlgorithm::Sha3_256)
+    }
+
+    fn checksum_line(
+        algorithm: MessageDigestAlgorithm,
+        hex_digest: &str,
+        url: &str,
+    ) -> ChecksumLine {
+        ChecksumLine::new(algorithm, hex::decode(hex_digest).unwrap(), url.to_string())
+    }
+
+    #[test]
+    fn gnu_lines_have_the_default_algorithm() {
+        let expected_line = checksum_line(
+            MessageDigestAlgorithm::Sha3_256,
+            SHA3_256_HEX,
+            "https://example.com/a b",
+        );
+
+        assert_eq!(
+            parse(&format!("{}  https://example.com/a b", SHA3_256_HEX)).unwrap(),
+            expected_line
+        );
+        // Binary mode, and CRLF line endings:
+        assert_eq!(
+            parse(&format!("{} *https://example.com/a b\r", SHA3_256_HEX)).unwrap(),
+            expected_line
+        );
+        assert_eq!(
+            ChecksumLine::parse(
+                &format!("{}  https://example.com/", SHA256_HEX),
+                MessageDigestAlgorithm::Sha256
+            )
+            .unwrap()
+            .algorithm(),
+            MessageDigestAlgorithm::Sha256
+        );
+    }
+
+    #[test]
+    fn bsd_lines_name_their_algorithm() {
+        assert_eq!(
+            parse(&format!(
+                "SHA256 (https://example.com/(1)) = {}",
+                SHA256_HEX
+            ))
+            .unwrap(),
+            checksum_line(
+                MessageDigestAlgorithm::Sha256,
+                SHA256_HEX,
+                "https://example.com/(1)"
+            )
+        );
+        assert_eq!(
+            parse(&format!(
+                "SHA3-256 (https://example.com/) = {}",
+                SHA3_256_HEX
+            ))
+            .unwrap()
+            .algorithm(),
+            MessageDigestAlgorithm::Sha3_256
+        );
+    }
+
+    #[test]
+    fn escaped_names_are_unescaped() {
+        let url = "https://example.com/a\\b\nc\rd";
+        let expected_line = checksum_line(MessageDigestAlgorithm::Sha3_256, SHA3_256_HEX, url);
+
+        assert_eq!(
+            parse(&format!(
+                "\\{}  https://example.com/a\\\\b\\nc\\rd",
+                SHA3_256_HEX
+            ))
+            .unwrap(),
+            expected_line
+        );
+        assert_eq!(
+            parse(&format!(
+                "\\SHA3-256 (https://example.com/a\\\\b\\nc\\rd) = {}",
+                SHA3_256_HEX
+            ))
+            .unwrap(),
+            expected_line
+        );
+        // Unescaped lines keep backslashes:
+        assert_eq!(
+            parse(&format!("{}  https://example.com/a\\nb", SHA3_256_HEX))
+                .unwrap()
+                .url(),
+            "https://example.com/a\\nb"
+        );
+        assert!(parse(&format!("\\{}  https://example.com/a\\tb", SHA3_256_HEX)).is_err());
+        assert!(parse(&format!("\\{}  https://example.com/a\\", SHA3_256_HEX)).is_err());
+    }
+
+    #[test]
+    fn lines_are_formatted_as_they_are_parsed() {
+        for url in ["https://example.com/", "https://example.com/a\\b\nc"] {
+            let line = checksum_line(MessageDigestAlgorithm::Sha256, SHA256_HEX, url);
+
+            for format in [ChecksumLineFormat::Gnu, ChecksumLineFormat::Bsd] {
+                let formatted_line = line.format(format);
+
+                assert!(!formatted_line.contains('\n'), "{formatted_line}");
+                assert_eq!(
+                    ChecksumLine::parse(&formatted_line, MessageDigestAlgorithm::Sha256).unwrap(),
+                    line
+                );
+            }
+        }
+        assert_eq!(
+            checksum_line(
+                MessageDigestAlgorithm::Sha256,
+                SHA256_HEX,
+                "https://example.com/"
+            )
+            .format(ChecksumLineFormat::Bsd),
+            format!("SHA256 (https://example.com/) = {}", SHA256_HEX)
+        );
+    }
+
+    #[test]
+    fn improperly_formatted_lines_are_rejected() {
+        for line in [
+            String::new(),
+            "# A comment".to_string(),
+            SHA3_256_HEX.to_string(),
+            format!("{} https://example.com/", SHA3_256_HEX),
+            format!("{}  ", SHA3_256_HEX),
+            // Digests of another length than the algorithm's:
+            format!("{}  https://example.com/", &SHA3_256_HEX[2..]),
+            format!("SHA512 (https://example.com/) = {}", SHA3_256_HEX),
+            format!("{}  https://example.com/", SHA3_256_HEX.replace('a', "g")),
+            format!("MD5 (https://example.com/) = {}", SHA3_256_HEX),
+        ] {
+            assert!(parse(&line).is_err(), "{line}");
+        }
+    }
+
+    #[test]
+    fn only_equal_digests_match() {
+        let line = checksum_line(
+            MessageDigestAlgorithm::Sha256,
+            SHA256_HEX,
+            "https://example.com/",
+        );
+        let digest = hex::decode(SHA256_HEX).unwrap();
+
+        assert!(line.matches(&digest));
+        assert!(line.expected_digest().matches(&digest));
+        assert!(!line.matches(&digest[1..]));
+        assert!(!line.matches(&hex::decode(SHA3_256_HEX).unwrap()));
+    }
+}
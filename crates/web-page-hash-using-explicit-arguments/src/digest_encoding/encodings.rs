@@ -0,0 +1,45 @@
+use crate::message_digest_service::MessageDigestAlgorithm;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+
+/// Returns a digest as lowercase hexadecimal digits
+pub fn to_hex(digest: &[u8]) -> String {
+    hex::encode(digest)
+}
+
+/// Returns a digest encoded in standard, padded base64
+pub fn to_base64(digest: &[u8]) -> String {
+    STANDARD.encode(digest)
+}
+
+/// Returns a digest as Subresource Integrity metadata (e.g.,
+/// `sha384-<base64>`)
+///
+/// Returns [None] for algorithms not allowed in Subresource Integrity
+/// metadata.
+pub fn to_sri(algorithm: MessageDigestAlgorithm, digest: &[u8]) -> Option<String> {
+    algorithm
+        .sri_name()
+        .map(|sri_name| format!("{}-{}", sri_name, to_base64(digest)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hexadecimal_digests_pad_octets_below_0x10() {
+        let octets = [0x00, 0x01, 0x0f, 0x10, 0xab, 0xff];
+
+        assert_eq!(to_hex(&octets), "00010f10abff");
+    }
+
+    #[test]
+    fn sri_metadata_is_only_returned_for_allowed_algorithms() {
+        assert_eq!(
+            to_sri(MessageDigestAlgorithm::Sha256, &[0xff; 3]).as_deref(),
+            Some("sha256-////")
+        );
+        assert_eq!(to_sri(MessageDigestAlgorithm::Sha3_256, &[0xff; 3]), None);
+    }
+}
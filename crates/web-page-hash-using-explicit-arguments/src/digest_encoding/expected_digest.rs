@@ -0,0 +1,289 @@
+use super::encodings::to_hex;
+use crate::message_digest_service::{MessageDigestAlgorithm, UnknownMessageDigestAlgorithm};
+use base64::Engine;
+use base64::engine::general_purpose::{STANDARD, STANDARD_NO_PAD, URL_SAFE, URL_SAFE_NO_PAD};
+use std::fmt;
+use std::io::{self, Write};
+use subtle::ConstantTimeEq;
+
+/// A message digest that a calculated digest is expected to match
+///
+/// Can be parsed from:
+///
+/// * hexadecimal digits, optionally prefixed with `0x`;
+/// * base64, or URL-safe base64 with, or without padding;
+/// * an `<algorithm>:<hex, or base64>` string (e.g., `sha256:<hex>`);
+/// * a Subresource Integrity string (e.g., `sha384-<base64>`).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ExpectedDigest {
+    algorithm: Option<MessageDigestAlgorithm>,
+    octets: Vec<u8>,
+}
+
+impl ExpectedDigest {
+    pub fn new(algorithm: Option<MessageDigestAlgorithm>, octets: Vec<u8>) -> Self {
+        ExpectedDigest { algorithm, octets }
+    }
+
+    /// Parses an expected digest
+    ///
+    /// `default_algorithm` determines the expected digest length when `text`
+    /// doesn't name an algorithm.
+    pub fn parse(
+        text: &str,
+        default_algorithm: MessageDigestAlgorithm,
+    ) -> Result<Self, ParseExpectedDigestError> {
+        let text = text.trim();
+        let (algorithm, encoded_digest) = match split_algorithm_prefix(text)? {
+            Some((algorithm, encoded_digest)) => (Some(algorithm), encoded_digest),
+            None => (None, text),
+        };
+        let digest_length = algorithm.unwrap_or(default_algorithm).digest_length();
+        let octets = decode_digest(encoded_digest, digest_length).ok_or_else(|| {
+            ParseExpectedDigestError::InvalidEncoding {
+                digest: text.to_string(),
+                digest_length,
+            }
+        })?;
+
+        Ok(ExpectedDigest { algorithm, octets })
+    }
+
+    /// Returns the algorithm named in the parsed text, if any
+    pub fn algorithm(&self) -> Option<MessageDigestAlgorithm> {
+        self.algorithm
+    }
+
+    pub fn octets(&self) -> &[u8] {
+        &self.octets
+    }
+
+    /// Compares `actual_digest` with the expected digest in constant time
+    pub fn matches(&self, actual_digest: &[u8]) -> bool {
+        self.octets.ct_eq(actual_digest).into()
+    }
+
+    /// Writes a report of `actual_digest` of the Web page at `url` not
+    /// matching the expected digest
+    pub fn write_mismatch(
+        &self,
+        diagnostics: &mut impl Write,
+        url: &str,
+        algorithm: MessageDigestAlgorithm,
+        actual_digest: &[u8],
+    ) -> io::Result<()> {
+        writeln!(diagnostics, "Message digest mismatch: {}", url)?;
+        writeln!(
+            diagnostics,
+            "  Algorithm: {} ({})",
+            algorithm.name(),
+            algorithm.description()
+        )?;
+        writeln!(diagnostics, "  Expected:  0x{}", to_hex(&self.octets))?;
+        writeln!(diagnostics, "  Actual:    0x{}", to_hex(actual_digest))
+    }
+}
+
+fn split_algorithm_prefix(
+    text: &str,
+) -> Result<Option<(MessageDigestAlgorithm, &str)>, ParseExpectedDigestError> {
+    if let Some((algorithm_name, encoded_digest)) = text.split_once(':') {
+        let algorithm = algorithm_name
+            .parse()
+            .map_err(ParseExpectedDigestError::UnknownAlgorithm)?;
+
+        return Ok(Some((algorithm, encoded_digest)));
+    }
+
+    // Subresource Integrity metadata (`sha256-`, `sha384-`, or `sha512-`):
+    for algorithm in MessageDigestAlgorithm::ALL {
+        if let Some(sri_name) = algorithm.sri_name()
+            && let Some(encoded_digest) = text
+                .strip_prefix(sri_name)
+                .and_then(|rest| rest.strip_prefix('-'))
+        {
+            return Ok(Some((algorithm, encoded_digest)));
+        }
+    }
+
+    Ok(None)
+}
+
+fn decode_digest(encoded_digest: &str, digest_length: usize) -> Option<Vec<u8>> {
+    let hex_digits = encoded_digest
+        .strip_prefix("0x")
+        .or_else(|| encoded_digest.strip_prefix("0X"))
+        .unwrap_or(encoded_digest);
+
+    if hex_digits.len() == 2 * digest_length
+        && let Ok(octets) = hex::decode(hex_digits)
+    {
+        return Some(octets);
+    }
+
+    [STANDARD, STANDARD_NO_PAD, URL_SAFE, URL_SAFE_NO_PAD]
+        .iter()
+        .filter_map(|engine| engine.decode(encoded_digest).ok())
+        .find(|octets| octets.len() == digest_length)
+}
+
+#[derive(Debug)]
+pub enum ParseExpectedDigestError {
+    UnknownAlgorithm(UnknownMessageDigestAlgorithm),
+    InvalidEncoding {
+        digest: String,
+        digest_length: usize,
+    },
+}
+
+impl fmt::Display for ParseExpectedDigestError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseExpectedDigestError::UnknownAlgorithm(error) => error.fmt(f),
+            ParseExpectedDigestError::InvalidEncoding {
+                digest,
+                digest_length,
+            } => write!(
+                f,
+                "{} isn't a hexadecimal, or base64 encoded {}-octet digest",
+                digest, digest_length
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ParseExpectedDigestError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ParseExpectedDigestError::UnknownAlgorithm(error) => Some(error),
+            ParseExpectedDigestError::InvalidEncoding { .. } => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The digests of an empty message:
+    const SHA3_256_HEX: &str = "a7ffc6f8bf1ed76651c14756a061d662f580ff4de43b49fa82d80a4b80f8434a";
+    const SHA256_HEX: &str = "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+    const SHA256_BASE64: &str = "47DEQpj8HBSa+/TImW+5JCeuQeRkm5NMpJWZG3hSuFU=";
+    const SHA384_BASE64: &str = "OLBgp1GsljhM2TJ+sbHjaiH9txEUvgdDTAzHv2P24donTt6/529l+9Ua0vFImLlb";
+    const SHA512_URL_SAFE_BASE64: &str =
+        "z4PhNX7vuL3xVChQ1m2AB9Yg5AULVxXcg_SpIdNs6c5H0NE8XYXysP-DGNKHfuwvY7kxvUdBeoGlODJ6-SfaPg";
+
+    fn parse(text: &str) -> Result<ExpectedDigest, ParseExpectedDigestError> {
+        ExpectedDigest::parse(text, MessageDigestAlgorithm::Sha3_256)
+    }
+
+    #[test]
+    fn hexadecimal_digests_are_parsed() {
+        let expected_digest = ExpectedDigest::new(None, hex::decode(SHA3_256_HEX).unwrap());
+
+        assert_eq!(parse(SHA3_256_HEX).unwrap(), expected_digest);
+        assert_eq!(
+            parse(&format!(" 0x{}\n", SHA3_256_HEX.to_uppercase())).unwrap(),
+            expected_digest
+        );
+        assert_eq!(
+            parse(&format!("0X{}", SHA3_256_HEX)).unwrap(),
+            expected_digest
+        );
+    }
+
+    #[test]
+    fn base64_digests_are_parsed() {
+        let octets = hex::decode(SHA256_HEX).unwrap();
+        let url_safe_base64 = SHA256_BASE64.replace('+', "-").replace('/', "_");
+
+        for text in [
+            SHA256_BASE64,
+            SHA256_BASE64.trim_end_matches('='),
+            &url_safe_base64,
+            url_safe_base64.trim_end_matches('='),
+        ] {
+            assert_eq!(parse(text).unwrap().octets(), octets, "{text}");
+        }
+    }
+
+    #[test]
+    fn algorithm_prefixes_name_the_algorithm() {
+        let expected_digest = parse(&format!("sha256:{}", SHA256_HEX)).unwrap();
+        assert_eq!(
+            expected_digest.algorithm(),
+            Some(MessageDigestAlgorithm::Sha256)
+        );
+        assert_eq!(expected_digest.octets(), hex::decode(SHA256_HEX).unwrap());
+
+        let expected_digest = parse(&format!("SHA-384:{}", SHA384_BASE64)).unwrap();
+        assert_eq!(
+            expected_digest.algorithm(),
+            Some(MessageDigestAlgorithm::Sha384)
+        );
+        assert_eq!(expected_digest.octets().len(), 48);
+
+        assert!(matches!(
+            parse(&format!("md5:{}", SHA256_HEX)),
+            Err(ParseExpectedDigestError::UnknownAlgorithm(_))
+        ));
+    }
+
+    #[test]
+    fn subresource_integrity_metadata_names_the_algorithm() {
+        let expected_digest = parse(&format!("sha384-{}", SHA384_BASE64)).unwrap();
+        assert_eq!(
+            expected_digest.algorithm(),
+            Some(MessageDigestAlgorithm::Sha384)
+        );
+        assert_eq!(expected_digest.octets().len(), 48);
+
+        let expected_digest = parse(&format!("sha512-{}", SHA512_URL_SAFE_BASE64)).unwrap();
+        assert_eq!(
+            expected_digest.algorithm(),
+            Some(MessageDigestAlgorithm::Sha512)
+        );
+        assert_eq!(expected_digest.octets().len(), 64);
+    }
+
+    #[test]
+    fn digests_of_another_length_than_the_algorithm_are_invalid() {
+        // A SHA-256 digest named as a SHA-512, or SHA-384 digest:
+        for text in [
+            format!("sha512:{}", SHA256_HEX),
+            format!("sha512-{}", SHA256_BASE64),
+            format!("sha384:{}", SHA256_BASE64),
+        ] {
+            assert!(
+                matches!(
+                    parse(&text),
+                    Err(ParseExpectedDigestError::InvalidEncoding { digest_length, .. })
+                        if digest_length != 32
+                ),
+                "{text}"
+            );
+        }
+        // The default algorithm determines the length without a prefix:
+        assert!(parse(SHA384_BASE64).is_err());
+        assert!(ExpectedDigest::parse(SHA384_BASE64, MessageDigestAlgorithm::Sha384).is_ok());
+        assert!(parse(&SHA3_256_HEX[2..]).is_err());
+        assert!(parse(&SHA3_256_HEX.replace('a', "g")).is_err());
+    }
+
+    #[test]
+    fn only_equal_digests_match() {
+        let octets = hex::decode(SHA3_256_HEX).unwrap();
+        let expected_digest = ExpectedDigest::new(None, octets.clone());
+
+        assert!(expected_digest.matches(&octets));
+        for index in [0, octets.len() / 2, octets.len() - 1] {
+            let mut other_octets = octets.clone();
+            other_octets[index] ^= 1;
+            assert!(!expected_digest.matches(&other_octets), "{index}");
+        }
+        // Prefixes, and extensions of the digest don't match:
+        assert!(!expected_digest.matches(&octets[..octets.len() - 1]));
+        assert!(!expected_digest.matches(&[octets.as_slice(), &[0]].concat()));
+        assert!(!expected_digest.matches(&[]));
+    }
+}
@@ -0,0 +1,8 @@
+pub mod checksum_line;
+pub use checksum_line::*;
+
+pub mod encodings;
+pub use encodings::*;
+
+pub mod expected_digest;
+pub use expected_digest::*;
@@ -0,0 +1,49 @@
+use super::interface::{DigestMemoService, MemoizeDigest, RecallDigest};
+use crate::http_cache::HttpCache;
+
+/// A [DigestMemoService] storing digests next to response bodies in an
+/// [HttpCache]
+///
+/// Combined with [crate::http_client_service::CachingGetUrl] a
+/// `304 Not Modified` response doesn't need its cached body to be hashed
+/// again.
+#[derive(Debug)]
+pub struct HttpCacheDigestMemoService {
+    cache: HttpCache,
+}
+
+impl HttpCacheDigestMemoService {
+    pub fn new(cache: HttpCache) -> Self {
+        HttpCacheDigestMemoService { cache }
+    }
+}
+
+impl RecallDigest for HttpCacheDigestMemoService {
+    type Error = std::io::Error;
+
+    fn recall_digest(
+        &self,
+        cache_key: &str,
+        validator: &str,
+        algorithm: &str,
+    ) -> Result<Option<Vec<u8>>, Self::Error> {
+        self.cache.recall_digest(cache_key, validator, algorithm)
+    }
+}
+
+impl MemoizeDigest for HttpCacheDigestMemoService {
+    type Error = std::io::Error;
+
+    fn memoize_digest(
+        &self,
+        cache_key: &str,
+        validator: &str,
+        algorithm: &str,
+        digest: &[u8],
+    ) -> Result<(), Self::Error> {
+        self.cache
+            .memoize_digest(cache_key, validator, algorithm, digest)
+    }
+}
+
+impl DigestMemoService for HttpCacheDigestMemoService {}
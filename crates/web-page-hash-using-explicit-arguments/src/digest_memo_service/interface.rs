@@ -0,0 +1,33 @@
+/// A function trait for recalling a previously calculated message digest
+///
+/// A digest is memoized per cache key of the response (see
+/// [crate::http_client_service::ResponseMetadata::cache_key]), response
+/// validator (see [crate::http_client_service::ResponseMetadata::validator]),
+/// and digest algorithm name.
+pub trait RecallDigest {
+    type Error;
+
+    fn recall_digest(
+        &self,
+        cache_key: &str,
+        validator: &str,
+        algorithm: &str,
+    ) -> Result<Option<Vec<u8>>, Self::Error>;
+}
+
+/// A function trait for memoizing a calculated message digest
+pub trait MemoizeDigest {
+    type Error;
+
+    fn memoize_digest(
+        &self,
+        cache_key: &str,
+        validator: &str,
+        algorithm: &str,
+        digest: &[u8],
+    ) -> Result<(), Self::Error>;
+}
+
+// The full service is composed of its method, and function traits:
+/// An injectable service that remembers message digests of response bodies
+pub trait DigestMemoService: RecallDigest + MemoizeDigest {}
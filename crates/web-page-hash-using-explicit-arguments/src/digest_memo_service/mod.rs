@@ -0,0 +1,8 @@
+pub mod interface;
+pub use interface::*;
+
+pub mod http_cache_digest_memo_service;
+pub use http_cache_digest_memo_service::*;
+
+pub mod no_digest_memo_service;
+pub use no_digest_memo_service::*;
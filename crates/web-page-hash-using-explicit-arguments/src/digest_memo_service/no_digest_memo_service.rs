@@ -0,0 +1,41 @@
+use super::interface::{DigestMemoService, MemoizeDigest, RecallDigest};
+use std::convert::Infallible;
+
+/// A [DigestMemoService] that doesn't remember any digests
+#[derive(Debug, Default)]
+pub struct NoDigestMemoService {}
+
+impl NoDigestMemoService {
+    pub fn new() -> Self {
+        NoDigestMemoService {}
+    }
+}
+
+impl RecallDigest for NoDigestMemoService {
+    type Error = Infallible;
+
+    fn recall_digest(
+        &self,
+        _cache_key: &str,
+        _validator: &str,
+        _algorithm: &str,
+    ) -> Result<Option<Vec<u8>>, Self::Error> {
+        Ok(None)
+    }
+}
+
+impl MemoizeDigest for NoDigestMemoService {
+    type Error = Infallible;
+
+    fn memoize_digest(
+        &self,
+        _cache_key: &str,
+        _validator: &str,
+        _algorithm: &str,
+        _digest: &[u8],
+    ) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+impl DigestMemoService for NoDigestMemoService {}
@@ -0,0 +1,8 @@
+pub mod web_page_digest_record;
+pub use web_page_digest_record::*;
+
+pub mod subresource_integrity_record;
+pub use subresource_integrity_record::*;
+
+pub mod output_format;
+pub use output_format::*;
@@ -0,0 +1,381 @@
+use super::web_page_digest_record::WebPageDigestRecord;
+use crate::calculate_web_page_message_digest_service::WebPageMessageDigest;
+use crate::digest_encoding::{ChecksumLine, ChecksumLineFormat, to_hex};
+use crate::message_digest_service::MessageDigestAlgorithm;
+use std::fmt;
+use std::io::{self, Write};
+use std::str::FromStr;
+
+/// Formats of printed digests
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum OutputFormat {
+    /// The digest prefixed with the algorithm description
+    #[default]
+    Text,
+    /// A GNU checksum line (`<hex>  <url>`), as printed by `sha3sum`
+    Gnu,
+    /// A BSD-tag checksum line (`SHA3-256 (<url>) = <hex>`), as printed by
+    /// `sha3sum --tag`
+    Bsd,
+    /// A pretty-printed JSON record (see [WebPageDigestRecord]), or an array
+    /// of records
+    Json,
+    /// One JSON record (see [WebPageDigestRecord]) per line
+    Ndjson,
+}
+
+impl OutputFormat {
+    /// Returns the name of the format (e.g., `ndjson`)
+    pub fn name(self) -> &'static str {
+        match self {
+            OutputFormat::Text => "text",
+            OutputFormat::Gnu => "gnu",
+            OutputFormat::Bsd => "bsd",
+            OutputFormat::Json => "json",
+            OutputFormat::Ndjson => "ndjson",
+        }
+    }
+
+    /// Returns whether digests are printed as [WebPageDigestRecord]s
+    pub fn prints_records(self) -> bool {
+        matches!(self, OutputFormat::Json | OutputFormat::Ndjson)
+    }
+
+    /// Writes the line of a digest, e.g., of a Merkle tree root over leaves
+    /// of `merkle_leaf_size`, without the URL in the `text` format
+    ///
+    /// Records are written instead in the `json`, and `ndjson` formats.
+    pub fn write_digest(
+        self,
+        writer: &mut impl Write,
+        checksum_line: &ChecksumLine,
+        merkle_leaf_size: Option<u64>,
+    ) -> io::Result<()> {
+        let algorithm = checksum_line.algorithm();
+        let hex_digest = to_hex(checksum_line.digest());
+
+        match (self, merkle_leaf_size) {
+            (OutputFormat::Text, Some(leaf_size)) => writeln!(
+                writer,
+                "{} Merkle tree root ({}-byte leaves): 0x{}",
+                algorithm.description(),
+                leaf_size,
+                hex_digest
+            ),
+            (OutputFormat::Text, None) => {
+                writeln!(writer, "{}: 0x{}", algorithm.description(), hex_digest)
+            }
+            (OutputFormat::Bsd, _) => {
+                writeln!(writer, "{}", checksum_line.format(ChecksumLineFormat::Bsd))
+            }
+            _ => writeln!(writer, "{}", checksum_line.format(ChecksumLineFormat::Gnu)),
+        }
+    }
+
+    /// Writes the redirects, the final URL, the server certificate, and the
+    /// content encoding of a response in the `text` format, and nothing in
+    /// other formats
+    pub fn write_response_details<Digest>(
+        self,
+        writer: &mut impl Write,
+        web_page_message_digest: &WebPageMessageDigest<Digest>,
+    ) -> io::Result<()> {
+        if self != OutputFormat::Text {
+            return Ok(());
+        }
+
+        for redirect in &web_page_message_digest.redirects {
+            writeln!(writer, "Redirected: {} ({})", redirect.url, redirect.status)?;
+        }
+        if !web_page_message_digest.redirects.is_empty() {
+            writeln!(writer, "Final URL: {}", web_page_message_digest.final_url)?;
+        }
+        if let Some(certificate_fingerprint) = &web_page_message_digest.certificate_fingerprint {
+            writeln!(writer, "Certificate SHA-256: {}", certificate_fingerprint)?;
+        }
+        if let Some(content_encoding) = &web_page_message_digest.content_encoding {
+            writeln!(
+                writer,
+                "Content encoding: {} (hashed {})",
+                content_encoding, web_page_message_digest.body_encoding
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Writes a record pretty-printed in the `json` format, and on one line
+    /// otherwise
+    pub fn write_record(
+        self,
+        writer: &mut impl Write,
+        record: &WebPageDigestRecord,
+    ) -> io::Result<()> {
+        match self {
+            OutputFormat::Json => serde_json::to_writer_pretty(&mut *writer, record)?,
+            _ => serde_json::to_writer(&mut *writer, record)?,
+        }
+
+        writeln!(writer)
+    }
+
+    /// Writes records as a JSON array, one record per line, or the line of
+    /// each digest prefixed with its URL in the `text` format, e.g., a
+    /// manifest of several Web pages
+    ///
+    /// The errors of failed records are written into `diagnostics` in the
+    /// `text`, `gnu`, and `bsd` formats.
+    pub fn write_records(
+        self,
+        writer: &mut impl Write,
+        diagnostics: &mut impl Write,
+        records: &[WebPageDigestRecord],
+    ) -> io::Result<()> {
+        if self == OutputFormat::Json {
+            serde_json::to_writer_pretty(&mut *writer, records)?;
+            return writeln!(writer);
+        }
+
+        for record in records {
+            if self == OutputFormat::Ndjson {
+                self.write_record(writer, record)?;
+                continue;
+            }
+
+            let digest = match (&record.digest, &record.error) {
+                (Some(digest), None) => digest,
+                (_, Some(error)) => {
+                    writeln!(diagnostics, "{}: {}", record.url, error.message)?;
+                    continue;
+                }
+                (None, None) => continue,
+            };
+            let algorithm = record
+                .algorithm
+                .parse::<MessageDigestAlgorithm>()
+                .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+            let digest = hex::decode(&digest.hex)
+                .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+
+            if self == OutputFormat::Text {
+                write!(writer, "{}: ", record.url)?;
+            }
+            self.write_digest(
+                writer,
+                &ChecksumLine::new(algorithm, digest, record.url.clone()),
+                None,
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for OutputFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for OutputFormat {
+    type Err = UnknownOutputFormat;
+
+    /// Parses a format name ignoring case
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name.to_ascii_lowercase().as_str() {
+            "text" => Ok(OutputFormat::Text),
+            "gnu" => Ok(OutputFormat::Gnu),
+            "bsd" => Ok(OutputFormat::Bsd),
+            "json" => Ok(OutputFormat::Json),
+            "ndjson" => Ok(OutputFormat::Ndjson),
+            _ => Err(UnknownOutputFormat(name.to_string())),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct UnknownOutputFormat(pub String);
+
+impl fmt::Display for UnknownOutputFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "unknown output format {} (expected text, gnu, bsd, json, or ndjson)",
+            self.0
+        )
+    }
+}
+
+impl std::error::Error for UnknownOutputFormat {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::content_decoder_service::BodyEncoding;
+    use crate::http_client_service::Redirect;
+    use http::StatusCode;
+    use std::time::Duration;
+
+    const URL: &str = "https://example.com/a b";
+
+    fn checksum_line() -> ChecksumLine {
+        ChecksumLine::new(
+            MessageDigestAlgorithm::Sha256,
+            vec![0x0f; 32],
+            URL.to_string(),
+        )
+    }
+
+    fn written(write: impl FnOnce(&mut Vec<u8>) -> io::Result<()>) -> String {
+        let mut output = Vec::new();
+        write(&mut output).unwrap();
+
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn digests_are_written_as_text_or_checksum_lines() {
+        let hex_digest = "0f".repeat(32);
+
+        assert_eq!(
+            written(|writer| OutputFormat::Text.write_digest(writer, &checksum_line(), None)),
+            format!("256-bit SHA-2: 0x{}\n", hex_digest)
+        );
+        assert_eq!(
+            written(|writer| OutputFormat::Text.write_digest(writer, &checksum_line(), Some(4))),
+            format!(
+                "256-bit SHA-2 Merkle tree root (4-byte leaves): 0x{}\n",
+                hex_digest
+            )
+        );
+        assert_eq!(
+            written(|writer| OutputFormat::Gnu.write_digest(writer, &checksum_line(), Some(4))),
+            format!("{}  {}\n", hex_digest, URL)
+        );
+        assert_eq!(
+            written(|writer| OutputFormat::Bsd.write_digest(writer, &checksum_line(), None)),
+            format!("SHA256 ({}) = {}\n", URL, hex_digest)
+        );
+    }
+
+    #[test]
+    fn response_details_are_only_written_as_text() {
+        let web_page_message_digest = WebPageMessageDigest {
+            digest: (),
+            final_url: "https://www.example.com/".to_string(),
+            redirects: vec![Redirect {
+                url: "https://example.com/".to_string(),
+                status: 301,
+            }],
+            certificate_fingerprint: None,
+            status: StatusCode::OK,
+            content_type: None,
+            content_encoding: Some("gzip".to_string()),
+            body_encoding: BodyEncoding::Raw,
+            byte_count: None,
+        };
+
+        assert_eq!(
+            written(|writer| {
+                OutputFormat::Text.write_response_details(writer, &web_page_message_digest)
+            }),
+            "Redirected: https://example.com/ (301)\n\
+             Final URL: https://www.example.com/\n\
+             Content encoding: gzip (hashed raw)\n"
+        );
+        assert_eq!(
+            written(|writer| {
+                OutputFormat::Gnu.write_response_details(writer, &web_page_message_digest)
+            }),
+            ""
+        );
+    }
+
+    fn records() -> Vec<WebPageDigestRecord> {
+        let web_page_message_digest = WebPageMessageDigest {
+            digest: vec![0x0f; 32],
+            final_url: URL.to_string(),
+            redirects: Vec::new(),
+            certificate_fingerprint: None,
+            status: StatusCode::OK,
+            content_type: None,
+            content_encoding: None,
+            body_encoding: BodyEncoding::Raw,
+            byte_count: Some(0),
+        };
+
+        vec![
+            WebPageDigestRecord::new(
+                URL.to_string(),
+                MessageDigestAlgorithm::Sha256,
+                &web_page_message_digest,
+                Duration::ZERO,
+            ),
+            WebPageDigestRecord::failed(
+                "https://example.com/missing".to_string(),
+                MessageDigestAlgorithm::Sha256,
+                &io::Error::other("not found"),
+                Duration::ZERO,
+            ),
+        ]
+    }
+
+    #[test]
+    fn failed_records_are_written_as_diagnostics_in_manifests() {
+        let mut diagnostics = Vec::new();
+
+        let manifest = written(|writer| {
+            OutputFormat::Text.write_records(writer, &mut diagnostics, &records())
+        });
+
+        assert_eq!(
+            manifest,
+            format!("{}: 256-bit SHA-2: 0x{}\n", URL, "0f".repeat(32))
+        );
+        assert_eq!(
+            String::from_utf8(diagnostics).unwrap(),
+            "https://example.com/missing: not found\n"
+        );
+    }
+
+    #[test]
+    fn records_are_written_as_a_json_array_or_lines() {
+        let records = records();
+
+        let json =
+            written(|writer| OutputFormat::Json.write_records(writer, &mut io::sink(), &records));
+        assert_eq!(
+            serde_json::from_str::<Vec<WebPageDigestRecord>>(&json).unwrap(),
+            records
+        );
+
+        let ndjson =
+            written(|writer| OutputFormat::Ndjson.write_records(writer, &mut io::sink(), &records));
+        let lines = ndjson
+            .lines()
+            .map(|line| serde_json::from_str::<WebPageDigestRecord>(line).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(lines, records);
+    }
+
+    #[test]
+    fn format_names_are_parsed_ignoring_case() {
+        for output_format in [
+            OutputFormat::Text,
+            OutputFormat::Gnu,
+            OutputFormat::Bsd,
+            OutputFormat::Json,
+            OutputFormat::Ndjson,
+        ] {
+            assert_eq!(
+                output_format
+                    .name()
+                    .to_ascii_uppercase()
+                    .parse::<OutputFormat>()
+                    .unwrap(),
+                output_format
+            );
+        }
+        assert!("yaml".parse::<OutputFormat>().is_err());
+    }
+}
@@ -0,0 +1,71 @@
+use super::RecordError;
+use crate::subresource_integrity::{HtmlSubresource, SubresourceKind};
+use serde::{Deserialize, Serialize};
+
+/// A machine-readable record of hashing one subresource of a Web page
+///
+/// Printed as an array by `sri --output json`.  Every field is always
+/// present, with `null` for values that aren't known:
+///
+/// ```json
+/// {
+///   "kind": "script",
+///   "url": "https://example.com/app.js",
+///   "integrity": "sha384-<base64>",
+///   "existing_integrity": "sha384-<base64>",
+///   "matches_existing": true,
+///   "error": null
+/// }
+/// ```
+///
+/// Exactly one of `integrity`, and `error` is `null`.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct SubresourceIntegrityRecord {
+    pub kind: SubresourceKind,
+    /// The URL of the subresource, resolved against the base URL of the Web
+    /// page
+    pub url: String,
+    /// The Subresource Integrity metadata of the subresource
+    pub integrity: Option<String>,
+    /// The `integrity` attribute value in the Web page, if any
+    pub existing_integrity: Option<String>,
+    /// Whether the `integrity` attribute value in the Web page matches the
+    /// subresource, when it could be checked
+    pub matches_existing: Option<bool>,
+    pub error: Option<RecordError>,
+}
+
+impl SubresourceIntegrityRecord {
+    /// Returns a record of a hashed subresource
+    pub fn new(
+        subresource: &HtmlSubresource,
+        url: String,
+        integrity: String,
+        matches_existing: Option<bool>,
+    ) -> Self {
+        SubresourceIntegrityRecord {
+            kind: subresource.kind,
+            url,
+            integrity: Some(integrity),
+            existing_integrity: subresource.integrity.clone(),
+            matches_existing,
+            error: None,
+        }
+    }
+
+    /// Returns a record of a failure to hash a subresource
+    pub fn failed(
+        subresource: &HtmlSubresource,
+        url: String,
+        error: &(dyn std::error::Error + 'static),
+    ) -> Self {
+        SubresourceIntegrityRecord {
+            kind: subresource.kind,
+            url,
+            integrity: None,
+            existing_integrity: subresource.integrity.clone(),
+            matches_existing: None,
+            error: Some(RecordError::new(error)),
+        }
+    }
+}
@@ -0,0 +1,369 @@
+use crate::calculate_web_page_message_digest_service::WebPageMessageDigest;
+use crate::content_decoder_service::BodyEncoding;
+use crate::digest_encoding::{ExpectedDigest, to_base64, to_hex, to_sri};
+use crate::http_client_service::{CertificateFingerprint, Redirect};
+use crate::message_digest_service::{FanOutDigest, MessageDigestAlgorithm};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// A machine-readable record of hashing one Web page
+///
+/// Printed by `--output json`, and, one record per line, by
+/// `--output ndjson`.  Every field is always present, with `null` for
+/// values that aren't known, e.g., because hashing failed:
+///
+/// ```json
+/// {
+///   "url": "https://example.com/",
+///   "final_url": "https://www.example.com/",
+///   "redirects": [{ "url": "https://example.com/", "status": 301 }],
+///   "certificate_sha256": "<lowercase hexadecimal digits>",
+///   "algorithm": "SHA3-256",
+///   "digest": {
+///     "hex": "<lowercase hexadecimal digits>",
+///     "base64": "<standard, padded base64>",
+///     "sri": null
+///   },
+///   "byte_count": 1256,
+///   "status": 200,
+///   "content_type": "text/html",
+///   "content_encoding": "gzip",
+///   "encoding": "decoded",
+///   "elapsed_ms": 153,
+///   "matches_expected": null,
+///   "error": null
+/// }
+/// ```
+///
+/// Exactly one of `digest`, and `error` is `null`.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct WebPageDigestRecord {
+    /// The requested URL
+    pub url: String,
+    /// The URL of the response, after following redirects
+    pub final_url: Option<String>,
+    /// The redirects followed before the response, starting with the
+    /// requested URL
+    pub redirects: Vec<Redirect>,
+    /// The SHA-256 fingerprint of the server's certificate, when the
+    /// response was received over TLS
+    pub certificate_sha256: Option<CertificateFingerprint>,
+    /// The BSD-tag style name of the message digest algorithm (e.g.,
+    /// `SHA3-256`, or `SHA256`)
+    pub algorithm: String,
+    pub digest: Option<DigestEncodings>,
+    /// The number of octets hashed, or the `Content-Length` of the response,
+    /// when a memoized digest was used
+    pub byte_count: Option<u64>,
+    /// The HTTP status code of the response
+    pub status: Option<u16>,
+    /// The `Content-Type` header value of the response
+    pub content_type: Option<String>,
+    /// The `Content-Encoding` header value of the response
+    pub content_encoding: Option<String>,
+    /// Whether the body was hashed as it was transferred (`raw`), or decoded
+    /// from its content encoding (`decoded`)
+    pub encoding: Option<BodyEncoding>,
+    /// The time spent fetching, and hashing the Web page in milliseconds
+    pub elapsed_ms: u64,
+    /// Whether the digest matches an expected digest, when one was given
+    pub matches_expected: Option<bool>,
+    pub error: Option<RecordError>,
+}
+
+impl WebPageDigestRecord {
+    /// Returns a record of a successfully calculated digest
+    pub fn new(
+        url: String,
+        algorithm: MessageDigestAlgorithm,
+        web_page_message_digest: &WebPageMessageDigest<Vec<u8>>,
+        elapsed: Duration,
+    ) -> Self {
+        WebPageDigestRecord {
+            url,
+            final_url: Some(web_page_message_digest.final_url.clone()),
+            redirects: web_page_message_digest.redirects.clone(),
+            certificate_sha256: web_page_message_digest.certificate_fingerprint,
+            algorithm: algorithm.name().to_string(),
+            digest: Some(DigestEncodings::new(
+                algorithm,
+                &web_page_message_digest.digest,
+            )),
+            byte_count: web_page_message_digest.byte_count,
+            status: Some(web_page_message_digest.status.as_u16()),
+            content_type: web_page_message_digest.content_type.clone(),
+            content_encoding: web_page_message_digest.content_encoding.clone(),
+            encoding: Some(web_page_message_digest.body_encoding),
+            elapsed_ms: duration_as_millis(elapsed),
+            matches_expected: None,
+            error: None,
+        }
+    }
+
+    /// Returns a record of a failure to calculate a digest
+    pub fn failed(
+        url: String,
+        algorithm: MessageDigestAlgorithm,
+        error: &(dyn std::error::Error + 'static),
+        elapsed: Duration,
+    ) -> Self {
+        WebPageDigestRecord {
+            url,
+            final_url: None,
+            redirects: Vec::new(),
+            certificate_sha256: None,
+            algorithm: algorithm.name().to_string(),
+            digest: None,
+            byte_count: None,
+            status: None,
+            content_type: None,
+            content_encoding: None,
+            encoding: None,
+            elapsed_ms: duration_as_millis(elapsed),
+            matches_expected: None,
+            error: Some(RecordError::new(error)),
+        }
+    }
+
+    /// Returns the records of the digests of `algorithms` calculated from one
+    /// download, or of the failure to calculate them
+    ///
+    /// Whether each digest matches `expected_digest` is recorded, when it's
+    /// given.
+    pub fn for_algorithms(
+        url: &str,
+        algorithms: &[MessageDigestAlgorithm],
+        digest_result: Result<
+            &WebPageMessageDigest<FanOutDigest>,
+            &(dyn std::error::Error + 'static),
+        >,
+        elapsed: Duration,
+        expected_digest: Option<&ExpectedDigest>,
+    ) -> Vec<Self> {
+        match digest_result {
+            Ok(web_page_message_digest) => algorithms
+                .iter()
+                .copied()
+                .zip(web_page_message_digest.digest.digests())
+                .map(|(algorithm, digest)| {
+                    let web_page_message_digest =
+                        web_page_message_digest.clone().with_digest(digest.clone());
+                    let mut record = WebPageDigestRecord::new(
+                        url.to_string(),
+                        algorithm,
+                        &web_page_message_digest,
+                        elapsed,
+                    );
+                    record.matches_expected = expected_digest.map(|expected_digest| {
+                        expected_digest.matches(&web_page_message_digest.digest)
+                    });
+
+                    record
+                })
+                .collect(),
+            Err(error) => algorithms
+                .iter()
+                .map(|&algorithm| {
+                    WebPageDigestRecord::failed(url.to_string(), algorithm, error, elapsed)
+                })
+                .collect(),
+        }
+    }
+}
+
+/// A message digest in several encodings
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct DigestEncodings {
+    /// Lowercase hexadecimal digits
+    pub hex: String,
+    /// Standard, padded base64
+    pub base64: String,
+    /// Subresource Integrity metadata (e.g., `sha384-<base64>`), or `null`
+    /// for algorithms not allowed in Subresource Integrity metadata
+    pub sri: Option<String>,
+}
+
+impl DigestEncodings {
+    pub fn new(algorithm: MessageDigestAlgorithm, digest: &[u8]) -> Self {
+        DigestEncodings {
+            hex: to_hex(digest),
+            base64: to_base64(digest),
+            sri: to_sri(algorithm, digest),
+        }
+    }
+}
+
+/// An error that prevented calculating a digest
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct RecordError {
+    /// A description of the error
+    pub message: String,
+    /// Descriptions of the errors that caused it, outermost first
+    pub causes: Vec<String>,
+}
+
+impl RecordError {
+    pub fn new(error: &(dyn std::error::Error + 'static)) -> Self {
+        let causes = std::iter::successors(error.source(), |cause| cause.source())
+            .map(ToString::to_string)
+            .collect();
+
+        RecordError {
+            message: error.to_string(),
+            causes,
+        }
+    }
+}
+
+fn duration_as_millis(duration: Duration) -> u64 {
+    duration.as_millis().try_into().unwrap_or(u64::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use http::StatusCode;
+
+    fn record() -> WebPageDigestRecord {
+        let web_page_message_digest = WebPageMessageDigest {
+            // The SHA-256 digest of an empty body:
+            digest: hex::decode("e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855")
+                .unwrap(),
+            final_url: "https://www.example.com/".to_string(),
+            redirects: vec![Redirect {
+                url: "https://example.com/".to_string(),
+                status: 301,
+            }],
+            certificate_fingerprint: Some(CertificateFingerprint::of(b"certificate")),
+            status: StatusCode::OK,
+            content_type: Some("text/html".to_string()),
+            content_encoding: Some("gzip".to_string()),
+            body_encoding: BodyEncoding::Decoded,
+            byte_count: Some(0),
+        };
+
+        WebPageDigestRecord::new(
+            "https://example.com/".to_string(),
+            MessageDigestAlgorithm::Sha256,
+            &web_page_message_digest,
+            Duration::from_micros(153_900),
+        )
+    }
+
+    fn failed_record() -> WebPageDigestRecord {
+        let error = anyhow::Error::new(std::io::Error::other("connection reset"))
+            .context("couldn't receive the body");
+
+        WebPageDigestRecord::failed(
+            "https://example.com/".to_string(),
+            MessageDigestAlgorithm::Sha3_256,
+            error.as_ref(),
+            Duration::from_millis(12),
+        )
+    }
+
+    #[test]
+    fn records_are_serialized_as_json() {
+        assert_eq!(
+            serde_json::to_string_pretty(&record()).unwrap(),
+            r#"{
+  "url": "https://example.com/",
+  "final_url": "https://www.example.com/",
+  "redirects": [
+    {
+      "url": "https://example.com/",
+      "status": 301
+    }
+  ],
+  "certificate_sha256": "03d66dd08835c1ca3f128cceacd1f31ac94163096b20f445ae84285bc0832d72",
+  "algorithm": "SHA256",
+  "digest": {
+    "hex": "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855",
+    "base64": "47DEQpj8HBSa+/TImW+5JCeuQeRkm5NMpJWZG3hSuFU=",
+    "sri": "sha256-47DEQpj8HBSa+/TImW+5JCeuQeRkm5NMpJWZG3hSuFU="
+  },
+  "byte_count": 0,
+  "status": 200,
+  "content_type": "text/html",
+  "content_encoding": "gzip",
+  "encoding": "decoded",
+  "elapsed_ms": 153,
+  "matches_expected": null,
+  "error": null
+}"#
+        );
+    }
+
+    #[test]
+    fn failed_records_are_serialized_as_json() {
+        assert_eq!(
+            serde_json::to_string_pretty(&failed_record()).unwrap(),
+            r#"{
+  "url": "https://example.com/",
+  "final_url": null,
+  "redirects": [],
+  "certificate_sha256": null,
+  "algorithm": "SHA3-256",
+  "digest": null,
+  "byte_count": null,
+  "status": null,
+  "content_type": null,
+  "content_encoding": null,
+  "encoding": null,
+  "elapsed_ms": 12,
+  "matches_expected": null,
+  "error": {
+    "message": "couldn't receive the body",
+    "causes": [
+      "connection reset"
+    ]
+  }
+}"#
+        );
+    }
+
+    #[test]
+    fn records_are_serialized_as_ndjson_lines() {
+        let mut record = record();
+        record.matches_expected = Some(false);
+
+        assert_eq!(
+            serde_json::to_string(&record).unwrap(),
+            concat!(
+                r#"{"url":"https://example.com/","final_url":"https://www.example.com/","#,
+                r#""redirects":[{"url":"https://example.com/","status":301}],"#,
+                r#""certificate_sha256":"03d66dd08835c1ca3f128cceacd1f31ac94163096b20f445ae84285bc0832d72","#,
+                r#""algorithm":"SHA256","digest":{"#,
+                r#""hex":"e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855","#,
+                r#""base64":"47DEQpj8HBSa+/TImW+5JCeuQeRkm5NMpJWZG3hSuFU=","#,
+                r#""sri":"sha256-47DEQpj8HBSa+/TImW+5JCeuQeRkm5NMpJWZG3hSuFU="},"#,
+                r#""byte_count":0,"status":200,"content_type":"text/html","#,
+                r#""content_encoding":"gzip","encoding":"decoded","elapsed_ms":153,"#,
+                r#""matches_expected":false,"error":null}"#
+            )
+        );
+        assert_eq!(
+            serde_json::to_string(&failed_record()).unwrap(),
+            concat!(
+                r#"{"url":"https://example.com/","final_url":null,"redirects":[],"#,
+                r#""certificate_sha256":null,"algorithm":"SHA3-256","digest":null,"#,
+                r#""byte_count":null,"status":null,"content_type":null,"#,
+                r#""content_encoding":null,"encoding":null,"elapsed_ms":12,"#,
+                r#""matches_expected":null,"#,
+                r#""error":{"message":"couldn't receive the body","causes":["connection reset"]}}"#
+            )
+        );
+    }
+
+    #[test]
+    fn records_are_deserialized_as_they_are_serialized() {
+        for record in [record(), failed_record()] {
+            let json = serde_json::to_string(&record).unwrap();
+
+            assert_eq!(
+                serde_json::from_str::<WebPageDigestRecord>(&json).unwrap(),
+                record
+            );
+        }
+    }
+}
@@ -0,0 +1,48 @@
+use crate::change_notifier_service::format_timestamp;
+use crate::message_digest_service::MessageDigestAlgorithm;
+use serde::{Deserialize, Serialize};
+use std::time::SystemTime;
+
+/// A statement that a Web page had a digest at a time, which is signed by
+/// [DigestSigner](super::DigestSigner)s
+///
+/// Its canonical form, which is signed, is compact JSON with the fields in
+/// this order, e.g.:
+///
+/// ```json
+/// {"url":"https://example.com/","algorithm":"SHA3-256","digest":"<lowercase hexadecimal digits>","timestamp":"2025-01-01T00:00:00Z"}
+/// ```
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct DigestStatement {
+    /// The hashed URL
+    pub url: String,
+    /// The BSD-tag style name of the message digest algorithm (e.g.,
+    /// `SHA3-256`)
+    pub algorithm: String,
+    /// The hexadecimal digest
+    pub digest: String,
+    /// The RFC 3339 time of signing
+    pub timestamp: String,
+}
+
+impl DigestStatement {
+    pub fn new(
+        url: String,
+        algorithm: MessageDigestAlgorithm,
+        digest: &[u8],
+        time: SystemTime,
+    ) -> Self {
+        DigestStatement {
+            url,
+            algorithm: algorithm.name().to_string(),
+            digest: hex::encode(digest),
+            timestamp: format_timestamp(time),
+        }
+    }
+
+    /// Returns the canonical form of this statement, which is signed
+    pub fn canonical_bytes(&self) -> Vec<u8> {
+        serde_json::to_vec(self).expect("a statement of strings can be serialized")
+    }
+}
@@ -0,0 +1,117 @@
+use super::digest_statement::DigestStatement;
+use super::interface::{
+    DigestSigner, DigestVerifier, SignDigestStatement, SignatureError, VerifyDigestSignature,
+};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use ed25519_dalek::pkcs8::{DecodePrivateKey, DecodePublicKey};
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// A [DigestSigner] signing with an Ed25519 key from a PKCS #8 PEM file, as
+/// written by `openssl genpkey -algorithm ed25519`
+///
+/// Signatures are pretty-printed JSON, e.g.:
+///
+/// ```json
+/// {
+///   "statement": {
+///     "url": "https://example.com/",
+///     "algorithm": "SHA3-256",
+///     "digest": "<lowercase hexadecimal digits>",
+///     "timestamp": "2025-01-01T00:00:00Z"
+///   },
+///   "signature": "<standard, padded base64>"
+/// }
+/// ```
+#[derive(Debug)]
+pub struct Ed25519DigestSigner {
+    signing_key: SigningKey,
+}
+
+impl Ed25519DigestSigner {
+    pub fn new(signing_key: SigningKey) -> Self {
+        Ed25519DigestSigner { signing_key }
+    }
+
+    pub fn from_pem_file(path: &Path) -> Result<Self, SignatureError> {
+        let pem = std::fs::read_to_string(path)
+            .map_err(|error| SignatureError::ReadKey(path.to_path_buf(), error))?;
+        let signing_key = SigningKey::from_pkcs8_pem(&pem)
+            .map_err(|error| SignatureError::InvalidKey(path.to_path_buf(), error.to_string()))?;
+
+        Ok(Ed25519DigestSigner::new(signing_key))
+    }
+}
+
+impl SignDigestStatement for Ed25519DigestSigner {
+    type Error = SignatureError;
+
+    fn sign_digest_statement(&self, statement: &DigestStatement) -> Result<String, Self::Error> {
+        let signature = self.signing_key.sign(&statement.canonical_bytes());
+        let detached_signature = Ed25519DetachedSignature {
+            statement: statement.clone(),
+            signature: STANDARD.encode(signature.to_bytes()),
+        };
+
+        Ok(format!(
+            "{}\n",
+            serde_json::to_string_pretty(&detached_signature)
+                .expect("a signature of strings can be serialized")
+        ))
+    }
+}
+
+impl DigestSigner for Ed25519DigestSigner {}
+
+/// A [DigestVerifier] verifying signatures of [Ed25519DigestSigner]s with a
+/// public key from a PEM file, as written by `openssl pkey -pubout`
+#[derive(Debug)]
+pub struct Ed25519DigestVerifier {
+    verifying_key: VerifyingKey,
+}
+
+impl Ed25519DigestVerifier {
+    pub fn new(verifying_key: VerifyingKey) -> Self {
+        Ed25519DigestVerifier { verifying_key }
+    }
+
+    pub fn from_pem_file(path: &Path) -> Result<Self, SignatureError> {
+        let pem = std::fs::read_to_string(path)
+            .map_err(|error| SignatureError::ReadKey(path.to_path_buf(), error))?;
+        let verifying_key = VerifyingKey::from_public_key_pem(&pem)
+            .map_err(|error| SignatureError::InvalidKey(path.to_path_buf(), error.to_string()))?;
+
+        Ok(Ed25519DigestVerifier::new(verifying_key))
+    }
+}
+
+impl VerifyDigestSignature for Ed25519DigestVerifier {
+    type Error = SignatureError;
+
+    fn verify_digest_signature(&self, signature: &str) -> Result<DigestStatement, Self::Error> {
+        let detached_signature: Ed25519DetachedSignature = serde_json::from_str(signature)
+            .map_err(|error| SignatureError::InvalidSignature(error.to_string()))?;
+        let signature_octets = STANDARD
+            .decode(&detached_signature.signature)
+            .map_err(|error| SignatureError::InvalidSignature(error.to_string()))?;
+        let signature = Signature::from_slice(&signature_octets)
+            .map_err(|error| SignatureError::InvalidSignature(error.to_string()))?;
+
+        self.verifying_key
+            .verify(&detached_signature.statement.canonical_bytes(), &signature)
+            .map_err(|_| SignatureError::BadSignature)?;
+
+        Ok(detached_signature.statement)
+    }
+}
+
+impl DigestVerifier for Ed25519DigestVerifier {}
+
+#[derive(Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+struct Ed25519DetachedSignature {
+    statement: DigestStatement,
+    signature: String,
+}
@@ -0,0 +1,78 @@
+use super::digest_statement::DigestStatement;
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+
+/// A function trait signing statements of calculated digests
+pub trait SignDigestStatement {
+    type Error;
+
+    /// Returns the text of a detached signature over the canonical form of
+    /// the statement, which also holds the statement
+    fn sign_digest_statement(&self, statement: &DigestStatement) -> Result<String, Self::Error>;
+}
+
+// The full service is composed of its method, and function traits:
+/// An injectable service signing digests, e.g., for supply-chain
+/// attestations
+pub trait DigestSigner: SignDigestStatement {}
+
+/// A function trait verifying detached signatures of digest statements
+pub trait VerifyDigestSignature {
+    type Error;
+
+    /// Returns the statement signed by a detached signature, when the
+    /// signature is valid
+    fn verify_digest_signature(&self, signature: &str) -> Result<DigestStatement, Self::Error>;
+}
+
+// The full service is composed of its method, and function traits:
+/// An injectable service verifying signatures written by [DigestSigner]s
+pub trait DigestVerifier: VerifyDigestSignature {}
+
+/// An error reading keys, or signing, or verifying digest statements
+///
+/// Errors never include the keys themselves.
+#[derive(Debug)]
+pub enum SignatureError {
+    ReadKey(PathBuf, io::Error),
+    InvalidKey(PathBuf, String),
+    EncryptedKey(PathBuf),
+    InvalidSignature(String),
+    KeyMismatch,
+    BadSignature,
+}
+
+impl fmt::Display for SignatureError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SignatureError::ReadKey(path, _) => {
+                write!(f, "could not read key file {}", path.display())
+            }
+            SignatureError::InvalidKey(path, message) => {
+                write!(f, "invalid key file {}: {}", path.display(), message)
+            }
+            SignatureError::EncryptedKey(path) => write!(
+                f,
+                "the minisign secret key {} is encrypted, which isn't supported (create one with `minisign -G -W`)",
+                path.display()
+            ),
+            SignatureError::InvalidSignature(message) => {
+                write!(f, "invalid signature: {}", message)
+            }
+            SignatureError::KeyMismatch => {
+                write!(f, "the signature was made with another key")
+            }
+            SignatureError::BadSignature => write!(f, "the signature doesn't match the statement"),
+        }
+    }
+}
+
+impl std::error::Error for SignatureError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SignatureError::ReadKey(_, error) => Some(error),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,348 @@
+use super::digest_statement::DigestStatement;
+use super::interface::{
+    DigestSigner, DigestVerifier, SignDigestStatement, SignatureError, VerifyDigestSignature,
+};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use ed25519_dalek::{SIGNATURE_LENGTH, Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use std::path::Path;
+
+/// The minisign algorithm of Ed25519 signatures over whole messages
+const SIGNATURE_ALGORITHM: &[u8] = b"Ed";
+/// The minisign algorithm of Ed25519 signatures over BLAKE2b-512 digests of
+/// messages
+const PREHASHED_SIGNATURE_ALGORITHM: &[u8] = b"ED";
+const KEY_ID_LENGTH: usize = 8;
+/// The length of a decoded secret key: the signature, KDF, and checksum
+/// algorithms, the KDF salt, and limits, the key ID, the Ed25519 key pair,
+/// and the checksum
+const SECRET_KEY_LENGTH: usize = 2 + 2 + 2 + 32 + 8 + 8 + KEY_ID_LENGTH + 64 + 32;
+/// The offset of the key ID in a decoded secret key
+const SECRET_KEY_ID_OFFSET: usize = 2 + 2 + 2 + 32 + 8 + 8;
+/// The length of a decoded public key: the signature algorithm, the key ID,
+/// and the Ed25519 public key
+const PUBLIC_KEY_LENGTH: usize = 2 + KEY_ID_LENGTH + 32;
+const UNTRUSTED_COMMENT_PREFIX: &str = "untrusted comment:";
+const TRUSTED_COMMENT_PREFIX: &str = "trusted comment: ";
+
+/// A [DigestSigner] writing minisign signatures with an unencrypted minisign
+/// secret key, as written by `minisign -G -W`
+///
+/// The signed message is the canonical statement, which is also the trusted
+/// comment, so that a signature can be checked with `minisign -V` against a
+/// file holding the canonical statement.  Signatures are of whole messages
+/// (the legacy `Ed` algorithm), since minisign accepts them, and prehashing
+/// is for large files.
+#[derive(Debug)]
+pub struct MinisignDigestSigner {
+    key_id: [u8; KEY_ID_LENGTH],
+    signing_key: SigningKey,
+}
+
+impl MinisignDigestSigner {
+    pub fn new(key_id: [u8; KEY_ID_LENGTH], signing_key: SigningKey) -> Self {
+        MinisignDigestSigner {
+            key_id,
+            signing_key,
+        }
+    }
+
+    /// Reads a minisign secret key file
+    ///
+    /// The checksum of the key isn't checked, since it's a BLAKE2b digest.
+    /// Instead, its public key is checked against its secret key.
+    pub fn from_key_file(path: &Path) -> Result<Self, SignatureError> {
+        let invalid_key =
+            |message: &str| SignatureError::InvalidKey(path.to_path_buf(), message.to_string());
+        let text = std::fs::read_to_string(path)
+            .map_err(|error| SignatureError::ReadKey(path.to_path_buf(), error))?;
+        let key = decode_key(&text).map_err(|message| invalid_key(&message))?;
+
+        if key.len() != SECRET_KEY_LENGTH {
+            return Err(invalid_key("not a minisign secret key"));
+        }
+        if &key[..2] != SIGNATURE_ALGORITHM {
+            return Err(invalid_key("unsupported signature algorithm"));
+        }
+        if key[2..4] != [0, 0] {
+            return Err(SignatureError::EncryptedKey(path.to_path_buf()));
+        }
+        let key_id = key[SECRET_KEY_ID_OFFSET..][..KEY_ID_LENGTH]
+            .try_into()
+            .expect("the key ID has a fixed length");
+        let key_pair = key[SECRET_KEY_ID_OFFSET + KEY_ID_LENGTH..][..64]
+            .try_into()
+            .expect("the key pair has a fixed length");
+        let signing_key = SigningKey::from_keypair_bytes(key_pair)
+            .map_err(|_| invalid_key("the public key doesn't match the secret key"))?;
+
+        Ok(MinisignDigestSigner::new(key_id, signing_key))
+    }
+}
+
+impl SignDigestStatement for MinisignDigestSigner {
+    type Error = SignatureError;
+
+    fn sign_digest_statement(&self, statement: &DigestStatement) -> Result<String, Self::Error> {
+        let trusted_comment = String::from_utf8(statement.canonical_bytes())
+            .expect("the canonical statement is JSON");
+        let signature = self.signing_key.sign(trusted_comment.as_bytes()).to_bytes();
+        let global_signature = self
+            .signing_key
+            .sign(&[signature.as_slice(), trusted_comment.as_bytes()].concat());
+
+        Ok(format!(
+            "{} signature of a web-page-hash digest statement\n{}\n{}{}\n{}\n",
+            UNTRUSTED_COMMENT_PREFIX,
+            STANDARD.encode([SIGNATURE_ALGORITHM, &self.key_id, &signature].concat()),
+            TRUSTED_COMMENT_PREFIX,
+            trusted_comment,
+            STANDARD.encode(global_signature.to_bytes())
+        ))
+    }
+}
+
+impl DigestSigner for MinisignDigestSigner {}
+
+/// A [DigestVerifier] verifying minisign signatures of
+/// [MinisignDigestSigner]s with a minisign public key
+#[derive(Debug)]
+pub struct MinisignDigestVerifier {
+    key_id: [u8; KEY_ID_LENGTH],
+    verifying_key: VerifyingKey,
+}
+
+impl MinisignDigestVerifier {
+    pub fn new(key_id: [u8; KEY_ID_LENGTH], verifying_key: VerifyingKey) -> Self {
+        MinisignDigestVerifier {
+            key_id,
+            verifying_key,
+        }
+    }
+
+    /// Reads a minisign public key file, as written by `minisign -G`
+    pub fn from_key_file(path: &Path) -> Result<Self, SignatureError> {
+        let invalid_key =
+            |message: &str| SignatureError::InvalidKey(path.to_path_buf(), message.to_string());
+        let text = std::fs::read_to_string(path)
+            .map_err(|error| SignatureError::ReadKey(path.to_path_buf(), error))?;
+        let key = decode_key(&text).map_err(|message| invalid_key(&message))?;
+
+        if key.len() != PUBLIC_KEY_LENGTH {
+            return Err(invalid_key("not a minisign public key"));
+        }
+        if &key[..2] != SIGNATURE_ALGORITHM {
+            return Err(invalid_key("unsupported signature algorithm"));
+        }
+        let key_id = key[2..][..KEY_ID_LENGTH]
+            .try_into()
+            .expect("the key ID has a fixed length");
+        let public_key = key[2 + KEY_ID_LENGTH..]
+            .try_into()
+            .expect("the public key has a fixed length");
+        let verifying_key = VerifyingKey::from_bytes(public_key)
+            .map_err(|_| invalid_key("not an Ed25519 public key"))?;
+
+        Ok(MinisignDigestVerifier::new(key_id, verifying_key))
+    }
+}
+
+impl MinisignDigestVerifier {
+    /// Verifies a minisign signature of `message`, and returns its trusted
+    /// comment
+    fn verify_message<'a>(
+        &self,
+        signature: &MinisignSignature<'a>,
+        message: &[u8],
+    ) -> Result<&'a str, SignatureError> {
+        if signature.key_id != self.key_id {
+            return Err(SignatureError::KeyMismatch);
+        }
+
+        self.verifying_key
+            .verify(message, &signature.signature)
+            .map_err(|_| SignatureError::BadSignature)?;
+        self.verifying_key
+            .verify(
+                &[
+                    signature.signature.to_bytes().as_slice(),
+                    signature.trusted_comment.as_bytes(),
+                ]
+                .concat(),
+                &signature.global_signature,
+            )
+            .map_err(|_| SignatureError::BadSignature)?;
+
+        Ok(signature.trusted_comment)
+    }
+}
+
+impl VerifyDigestSignature for MinisignDigestVerifier {
+    type Error = SignatureError;
+
+    fn verify_digest_signature(&self, signature: &str) -> Result<DigestStatement, Self::Error> {
+        let signature = MinisignSignature::parse(signature)?;
+        let statement: DigestStatement =
+            serde_json::from_str(signature.trusted_comment).map_err(|error| {
+                SignatureError::InvalidSignature(format!(
+                    "the trusted comment isn't a digest statement: {}",
+                    error
+                ))
+            })?;
+
+        self.verify_message(&signature, &statement.canonical_bytes())?;
+
+        Ok(statement)
+    }
+}
+
+impl DigestVerifier for MinisignDigestVerifier {}
+
+/// The parts of a minisign signature of the legacy `Ed` algorithm
+struct MinisignSignature<'a> {
+    key_id: [u8; KEY_ID_LENGTH],
+    signature: Signature,
+    trusted_comment: &'a str,
+    /// The signature of the signature, and the trusted comment
+    global_signature: Signature,
+}
+
+impl<'a> MinisignSignature<'a> {
+    fn parse(signature: &'a str) -> Result<Self, SignatureError> {
+        let invalid_signature =
+            |message: &str| SignatureError::InvalidSignature(message.to_string());
+        let mut lines = signature.lines();
+        let (
+            Some(untrusted_comment),
+            Some(signature),
+            Some(trusted_comment),
+            Some(global_signature),
+        ) = (lines.next(), lines.next(), lines.next(), lines.next())
+        else {
+            return Err(invalid_signature("not a minisign signature"));
+        };
+        if !untrusted_comment.starts_with(UNTRUSTED_COMMENT_PREFIX) {
+            return Err(invalid_signature("missing the untrusted comment"));
+        }
+        let trusted_comment = trusted_comment
+            .strip_prefix(TRUSTED_COMMENT_PREFIX)
+            .ok_or_else(|| invalid_signature("missing the trusted comment"))?;
+        let signature = STANDARD
+            .decode(signature)
+            .map_err(|error| invalid_signature(&error.to_string()))?;
+        let global_signature = STANDARD
+            .decode(global_signature)
+            .map_err(|error| invalid_signature(&error.to_string()))?;
+
+        if signature.len() != 2 + KEY_ID_LENGTH + SIGNATURE_LENGTH {
+            return Err(invalid_signature("unexpected signature length"));
+        }
+        match &signature[..2] {
+            SIGNATURE_ALGORITHM => {}
+            PREHASHED_SIGNATURE_ALGORITHM => {
+                return Err(invalid_signature("prehashed signatures aren't supported"));
+            }
+            _ => return Err(invalid_signature("unsupported signature algorithm")),
+        }
+
+        Ok(MinisignSignature {
+            key_id: signature[2..][..KEY_ID_LENGTH]
+                .try_into()
+                .expect("the key ID has a fixed length"),
+            signature: Signature::from_slice(&signature[2 + KEY_ID_LENGTH..])
+                .expect("the signature has a fixed length"),
+            trusted_comment,
+            global_signature: Signature::from_slice(&global_signature)
+                .map_err(|error| invalid_signature(&error.to_string()))?,
+        })
+    }
+}
+
+/// Decodes the base64 line of a minisign key file, skipping its untrusted
+/// comment
+fn decode_key(text: &str) -> Result<Vec<u8>, String> {
+    let line = text
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty() && !line.starts_with(UNTRUSTED_COMMENT_PREFIX))
+        .ok_or_else(|| "the file is empty".to_string())?;
+
+    STANDARD.decode(line).map_err(|error| error.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A public key, and signatures of the message `test` written by the
+    // minisign tool, as published with the `minisign-verify` crate:
+    const PUBLIC_KEY_FILE: &str = "\
+        untrusted comment: minisign public key E7620F1842B4E81F\n\
+        RWQf6LRCGA9i53mlYecO4IzT51TGPpvWucNSCh1CBM0QTaLn73Y7GFO3\n";
+    const SIGNATURE: &str = "\
+        untrusted comment: signature from minisign secret key\n\
+        RWQf6LRCGA9i59SLOFxz6NxvASXDJeRtuZykwQepbDEGt87ig1BNpWaVWuNrm73YiIiJbq71Wi+dP9eKL8OC351vwIasSSbXxwA=\n\
+        trusted comment: timestamp:1555779966\tfile:test\n\
+        QtKMXWyYcwdpZAlPF7tE2ENJkRd1ujvKjlj1m9RtHTBnZPa5WKU5uWRs5GoP5M/VqE81QFuMKI5k/SfNQUaOAA==\n";
+    const PREHASHED_SIGNATURE: &str = "\
+        untrusted comment: signature from minisign secret key\n\
+        RUQf6LRCGA9i559r3g7V1qNyJDApGip8MfqcadIgT9CuhV3EMhHoN1mGTkUidF/z7SrlQgXdy8ofjb7bNJJylDOocrCo8KLzZwo=\n\
+        trusted comment: timestamp:1556193335\tfile:test\n\
+        y/rUw2y8/hOUYjZU71eHp/Wo1KZ40fGy2VJEDl34XMJM+TX48Ss/17u3IvIfbVR1FkZZSNCisQbuQY+bHwhEBg==\n";
+
+    fn verifier() -> MinisignDigestVerifier {
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("minisign.pub");
+        std::fs::write(&path, PUBLIC_KEY_FILE).unwrap();
+
+        MinisignDigestVerifier::from_key_file(&path).unwrap()
+    }
+
+    #[test]
+    fn public_keys_of_the_minisign_tool_are_read() {
+        let verifier = verifier();
+
+        // The tool prints key IDs as little-endian hexadecimal numbers:
+        let mut key_id = verifier.key_id;
+        key_id.reverse();
+        assert_eq!(hex::encode_upper(key_id), "E7620F1842B4E81F");
+    }
+
+    #[test]
+    fn signatures_of_the_minisign_tool_are_verified() {
+        let verifier = verifier();
+        let signature = MinisignSignature::parse(SIGNATURE).unwrap();
+
+        assert_eq!(
+            verifier.verify_message(&signature, b"test").unwrap(),
+            "timestamp:1555779966\tfile:test"
+        );
+        assert!(matches!(
+            verifier.verify_message(&signature, b"tesT"),
+            Err(SignatureError::BadSignature)
+        ));
+
+        let tampered_signature = SIGNATURE.replace("file:test", "file:tesT");
+        let tampered_signature = MinisignSignature::parse(&tampered_signature).unwrap();
+        assert!(matches!(
+            verifier.verify_message(&tampered_signature, b"test"),
+            Err(SignatureError::BadSignature)
+        ));
+    }
+
+    #[test]
+    fn prehashed_signatures_of_the_minisign_tool_are_rejected() {
+        assert!(matches!(
+            MinisignSignature::parse(PREHASHED_SIGNATURE),
+            Err(SignatureError::InvalidSignature(message)) if message.contains("prehashed")
+        ));
+    }
+
+    #[test]
+    fn signatures_of_the_minisign_tool_arent_digest_statements() {
+        assert!(matches!(
+            verifier().verify_digest_signature(SIGNATURE),
+            Err(SignatureError::InvalidSignature(message)) if message.contains("digest statement")
+        ));
+    }
+}
@@ -0,0 +1,17 @@
+pub mod interface;
+pub use interface::*;
+
+pub mod digest_statement;
+pub use digest_statement::*;
+
+pub mod ed25519_digest_signer;
+pub use ed25519_digest_signer::*;
+
+pub mod minisign_digest_signer;
+pub use minisign_digest_signer::*;
+
+pub mod selectable_digest_signer;
+pub use selectable_digest_signer::*;
+
+pub mod signature_file;
+pub use signature_file::*;
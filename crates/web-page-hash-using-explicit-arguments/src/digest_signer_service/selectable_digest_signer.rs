@@ -0,0 +1,84 @@
+use super::digest_statement::DigestStatement;
+use super::ed25519_digest_signer::{Ed25519DigestSigner, Ed25519DigestVerifier};
+use super::interface::{
+    DigestSigner, DigestVerifier, SignDigestStatement, SignatureError, VerifyDigestSignature,
+};
+use super::minisign_digest_signer::{MinisignDigestSigner, MinisignDigestVerifier};
+use std::path::Path;
+
+/// The first line of PEM key files
+const PEM_PREFIX: &str = "-----BEGIN";
+
+/// A [DigestSigner] selected at run time
+#[derive(Debug)]
+pub enum SelectableDigestSigner {
+    Ed25519(Ed25519DigestSigner),
+    Minisign(MinisignDigestSigner),
+}
+
+impl SelectableDigestSigner {
+    /// Reads a PEM Ed25519 key file, or else a minisign secret key file
+    pub fn from_key_file(path: &Path) -> Result<Self, SignatureError> {
+        Ok(if is_pem_file(path)? {
+            SelectableDigestSigner::Ed25519(Ed25519DigestSigner::from_pem_file(path)?)
+        } else {
+            SelectableDigestSigner::Minisign(MinisignDigestSigner::from_key_file(path)?)
+        })
+    }
+}
+
+impl SignDigestStatement for SelectableDigestSigner {
+    type Error = SignatureError;
+
+    fn sign_digest_statement(&self, statement: &DigestStatement) -> Result<String, Self::Error> {
+        match self {
+            SelectableDigestSigner::Ed25519(signer) => signer.sign_digest_statement(statement),
+            SelectableDigestSigner::Minisign(signer) => signer.sign_digest_statement(statement),
+        }
+    }
+}
+
+impl DigestSigner for SelectableDigestSigner {}
+
+/// A [DigestVerifier] selected at run time
+#[derive(Debug)]
+pub enum SelectableDigestVerifier {
+    Ed25519(Ed25519DigestVerifier),
+    Minisign(MinisignDigestVerifier),
+}
+
+impl SelectableDigestVerifier {
+    /// Reads a PEM Ed25519 public key file, or else a minisign public key
+    /// file
+    pub fn from_key_file(path: &Path) -> Result<Self, SignatureError> {
+        Ok(if is_pem_file(path)? {
+            SelectableDigestVerifier::Ed25519(Ed25519DigestVerifier::from_pem_file(path)?)
+        } else {
+            SelectableDigestVerifier::Minisign(MinisignDigestVerifier::from_key_file(path)?)
+        })
+    }
+}
+
+impl VerifyDigestSignature for SelectableDigestVerifier {
+    type Error = SignatureError;
+
+    fn verify_digest_signature(&self, signature: &str) -> Result<DigestStatement, Self::Error> {
+        match self {
+            SelectableDigestVerifier::Ed25519(verifier) => {
+                verifier.verify_digest_signature(signature)
+            }
+            SelectableDigestVerifier::Minisign(verifier) => {
+                verifier.verify_digest_signature(signature)
+            }
+        }
+    }
+}
+
+impl DigestVerifier for SelectableDigestVerifier {}
+
+fn is_pem_file(path: &Path) -> Result<bool, SignatureError> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|error| SignatureError::ReadKey(path.to_path_buf(), error))?;
+
+    Ok(text.trim_start().starts_with(PEM_PREFIX))
+}
@@ -0,0 +1,267 @@
+use super::digest_statement::DigestStatement;
+use super::interface::{SignDigestStatement, VerifyDigestSignature};
+use crate::digest_encoding::ExpectedDigest;
+use crate::message_digest_service::MessageDigestAlgorithm;
+use std::fmt;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// Signs a statement, and writes the detached signature into a signature
+/// file
+pub fn write_signature_file<DigestSigner>(
+    digest_signer: &DigestSigner,
+    statement: &DigestStatement,
+    path: &Path,
+) -> Result<(), SignatureFileError>
+where
+    DigestSigner: SignDigestStatement,
+    DigestSigner::Error: std::error::Error + Send + Sync + 'static,
+{
+    let signature = digest_signer
+        .sign_digest_statement(statement)
+        .map_err(|error| SignatureFileError::Signature(Box::new(error)))?;
+
+    fs::write(path, signature).map_err(|error| SignatureFileError::Write(path.to_owned(), error))
+}
+
+/// A digest signed by a verified signature file, which a Web page is
+/// expected to still have
+#[derive(Clone, Debug)]
+pub struct SignedDigest {
+    pub url: url::Url,
+    pub algorithm: MessageDigestAlgorithm,
+    pub expected_digest: ExpectedDigest,
+    /// The RFC 3339 time of signing
+    pub timestamp: String,
+}
+
+impl SignedDigest {
+    /// Reads a signature file, and returns the digest of its verified
+    /// statement
+    ///
+    /// `url`, and `algorithm`, when given, must be the signed ones.
+    pub fn read_signature_file<DigestVerifier>(
+        digest_verifier: &DigestVerifier,
+        path: &Path,
+        url: Option<&str>,
+        algorithm: Option<MessageDigestAlgorithm>,
+    ) -> Result<Self, SignatureFileError>
+    where
+        DigestVerifier: VerifyDigestSignature,
+        DigestVerifier::Error: std::error::Error + Send + Sync + 'static,
+    {
+        let signature = fs::read_to_string(path)
+            .map_err(|error| SignatureFileError::Read(path.to_owned(), error))?;
+        let statement = digest_verifier
+            .verify_digest_signature(&signature)
+            .map_err(|error| SignatureFileError::Signature(Box::new(error)))?;
+
+        let signed_url = url::Url::parse(&statement.url)
+            .map_err(|error| SignatureFileError::InvalidStatement(Box::new(error)))?;
+        if let Some(url) = url
+            && url::Url::parse(url)
+                .map_err(|error| SignatureFileError::InvalidUrl(Box::new(error)))?
+                != signed_url
+        {
+            return Err(SignatureFileError::UrlMismatch {
+                signed_url,
+                url: url.to_string(),
+            });
+        }
+        let signed_algorithm = statement
+            .algorithm
+            .parse::<MessageDigestAlgorithm>()
+            .map_err(|error| SignatureFileError::InvalidStatement(Box::new(error)))?;
+        if let Some(algorithm) = algorithm
+            && algorithm != signed_algorithm
+        {
+            return Err(SignatureFileError::AlgorithmMismatch {
+                signed_algorithm,
+                algorithm,
+            });
+        }
+        let digest = hex::decode(&statement.digest)
+            .map_err(|error| SignatureFileError::InvalidStatement(Box::new(error)))?;
+
+        Ok(SignedDigest {
+            url: signed_url,
+            algorithm: signed_algorithm,
+            expected_digest: ExpectedDigest::new(Some(signed_algorithm), digest),
+            timestamp: statement.timestamp,
+        })
+    }
+
+    /// Writes `OK`, when `digest` is the signed digest, or a report of the
+    /// mismatch into `diagnostics`, and returns whether it matches
+    pub fn write_report(
+        &self,
+        writer: &mut impl Write,
+        diagnostics: &mut impl Write,
+        digest: &[u8],
+    ) -> io::Result<bool> {
+        if !self.expected_digest.matches(digest) {
+            self.expected_digest.write_mismatch(
+                diagnostics,
+                self.url.as_str(),
+                self.algorithm,
+                digest,
+            )?;
+
+            return Ok(false);
+        }
+
+        writeln!(
+            writer,
+            "{}: OK ({}, signed at {})",
+            self.url,
+            self.algorithm.name(),
+            self.timestamp
+        )?;
+
+        Ok(true)
+    }
+}
+
+/// An error reading, or writing signature files
+#[derive(Debug)]
+pub enum SignatureFileError {
+    Read(PathBuf, io::Error),
+    Write(PathBuf, io::Error),
+    /// Signing, or verifying the statement failed
+    Signature(Box<dyn std::error::Error + Send + Sync>),
+    /// The signed URL, algorithm, or digest can't be parsed
+    InvalidStatement(Box<dyn std::error::Error + Send + Sync>),
+    /// The URL required to be signed can't be parsed
+    InvalidUrl(Box<dyn std::error::Error + Send + Sync>),
+    UrlMismatch {
+        signed_url: url::Url,
+        url: String,
+    },
+    AlgorithmMismatch {
+        signed_algorithm: MessageDigestAlgorithm,
+        algorithm: MessageDigestAlgorithm,
+    },
+}
+
+impl fmt::Display for SignatureFileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SignatureFileError::Read(path, _) => write!(f, "couldn't read {}", path.display()),
+            SignatureFileError::Write(path, _) => write!(f, "couldn't write {}", path.display()),
+            SignatureFileError::Signature(error) => error.fmt(f),
+            SignatureFileError::InvalidStatement(_) => {
+                write!(f, "the signed statement is invalid")
+            }
+            SignatureFileError::InvalidUrl(error) => error.fmt(f),
+            SignatureFileError::UrlMismatch { signed_url, url } => {
+                write!(f, "the signature is for {}, not {}", signed_url, url)
+            }
+            SignatureFileError::AlgorithmMismatch {
+                signed_algorithm,
+                algorithm,
+            } => write!(
+                f,
+                "the signed digest is a {} digest, but --algorithm is {}",
+                signed_algorithm, algorithm
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SignatureFileError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SignatureFileError::Read(_, error) | SignatureFileError::Write(_, error) => Some(error),
+            SignatureFileError::Signature(error) | SignatureFileError::InvalidUrl(error) => {
+                error.source()
+            }
+            SignatureFileError::InvalidStatement(error) => Some(error.as_ref()),
+            SignatureFileError::UrlMismatch { .. }
+            | SignatureFileError::AlgorithmMismatch { .. } => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::digest_signer_service::{Ed25519DigestSigner, Ed25519DigestVerifier};
+    use ed25519_dalek::SigningKey;
+    use std::time::SystemTime;
+
+    const URL: &str = "https://example.com/";
+
+    fn signature_file(directory: &Path) -> (PathBuf, Ed25519DigestVerifier) {
+        let signing_key = SigningKey::from_bytes(&[7; 32]);
+        let digest_verifier = Ed25519DigestVerifier::new(signing_key.verifying_key());
+        let statement = DigestStatement::new(
+            URL.to_string(),
+            MessageDigestAlgorithm::Sha256,
+            &[0x0f; 32],
+            SystemTime::UNIX_EPOCH,
+        );
+        let path = directory.join("digest.sig");
+
+        write_signature_file(&Ed25519DigestSigner::new(signing_key), &statement, &path).unwrap();
+
+        (path, digest_verifier)
+    }
+
+    #[test]
+    fn signed_digests_are_read_and_reported() {
+        let directory = tempfile::tempdir().unwrap();
+        let (path, digest_verifier) = signature_file(directory.path());
+
+        let signed_digest =
+            SignedDigest::read_signature_file(&digest_verifier, &path, Some(URL), None).unwrap();
+        assert_eq!(signed_digest.algorithm, MessageDigestAlgorithm::Sha256);
+
+        let mut writer = Vec::new();
+        let mut diagnostics = Vec::new();
+        assert!(
+            signed_digest
+                .write_report(&mut writer, &mut diagnostics, &[0x0f; 32])
+                .unwrap()
+        );
+        assert_eq!(
+            String::from_utf8(writer).unwrap(),
+            "https://example.com/: OK (SHA256, signed at 1970-01-01T00:00:00Z)\n"
+        );
+        assert!(
+            !signed_digest
+                .write_report(&mut io::sink(), &mut diagnostics, &[0; 32])
+                .unwrap()
+        );
+        assert!(
+            String::from_utf8(diagnostics)
+                .unwrap()
+                .starts_with("Message digest mismatch: https://example.com/\n")
+        );
+    }
+
+    #[test]
+    fn signed_urls_and_algorithms_must_match_the_given_ones() {
+        let directory = tempfile::tempdir().unwrap();
+        let (path, digest_verifier) = signature_file(directory.path());
+
+        assert!(matches!(
+            SignedDigest::read_signature_file(
+                &digest_verifier,
+                &path,
+                Some("https://example.org/"),
+                None
+            ),
+            Err(SignatureFileError::UrlMismatch { .. })
+        ));
+        assert!(matches!(
+            SignedDigest::read_signature_file(
+                &digest_verifier,
+                &path,
+                None,
+                Some(MessageDigestAlgorithm::Sha3_256)
+            ),
+            Err(SignatureFileError::AlgorithmMismatch { .. })
+        ));
+    }
+}
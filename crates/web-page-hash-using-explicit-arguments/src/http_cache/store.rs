@@ -0,0 +1,484 @@
+use http::header::{
+    CONTENT_ENCODING, CONTENT_LENGTH, CONTENT_TYPE, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH,
+    LAST_MODIFIED,
+};
+use http::{HeaderMap, HeaderValue};
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Sha3_256};
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+const BODY_FILE_NAME: &str = "body";
+const PARTIAL_BODY_FILE_NAME: &str = "body.partial";
+const METADATA_FILE_NAME: &str = "metadata.json";
+
+/// The default limit for the total size of a cache directory (256 MiB)
+pub const DEFAULT_HTTP_CACHE_MAX_SIZE: u64 = 256 * 1024 * 1024;
+
+/// The length of the hexadecimal entry keys
+const ENTRY_KEY_LENGTH: usize = 64;
+
+/// A local directory storing HTTP response bodies, and their validators
+///
+/// Each cached URL has a sub-directory named after the 256-bit SHA-3 digest
+/// of the URL, and the request headers, since they may change the response
+/// (e.g., `Accept-Encoding`, or `Authorization`).  It contains the response
+/// body, and a metadata file with the `ETag`, and `Last-Modified` validators
+/// of the response, as well as any message digests of the body memoized by
+/// algorithm name.  The name of the sub-directory is the key of the entry,
+/// which is returned with cached responses, so that digests are memoized for
+/// the response they were calculated from.
+#[derive(Clone, Debug)]
+pub struct HttpCache {
+    directory: PathBuf,
+    max_size: u64,
+    /// The total size of the entries, which is scanned when the first entry
+    /// is committed, and then updated by each commit, so that the directory
+    /// is only scanned again for evicting entries
+    size: Arc<Mutex<Option<u64>>>,
+}
+
+impl HttpCache {
+    pub fn new(directory: impl Into<PathBuf>, max_size: u64) -> Self {
+        HttpCache {
+            directory: directory.into(),
+            max_size,
+            size: Arc::default(),
+        }
+    }
+
+    /// Returns `$XDG_CACHE_HOME/web-page-hash`, or `$HOME/.cache/web-page-hash`
+    pub fn default_directory() -> Option<PathBuf> {
+        let cache_home = std::env::var_os("XDG_CACHE_HOME")
+            .filter(|value| !value.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".cache")))?;
+
+        Some(cache_home.join("web-page-hash"))
+    }
+
+    pub fn directory(&self) -> &Path {
+        &self.directory
+    }
+
+    /// Returns the cached response for `url` requested with
+    /// `request_headers`, if there's one
+    pub fn lookup(
+        &self,
+        url: &str,
+        request_headers: &HeaderMap,
+    ) -> io::Result<Option<HttpCacheEntry>> {
+        let key = entry_key(url, request_headers);
+        let entry_directory = self.directory.join(&key);
+        let metadata = match read_metadata(&entry_directory)? {
+            Some(metadata) if metadata.url == url => metadata,
+            _ => return Ok(None),
+        };
+        let body_path = entry_directory.join(BODY_FILE_NAME);
+
+        if !body_path.is_file() {
+            return Ok(None);
+        }
+        // The modification time of the metadata file is used for evicting
+        // least recently used entries:
+        File::options()
+            .append(true)
+            .open(entry_directory.join(METADATA_FILE_NAME))?
+            .set_modified(SystemTime::now())?;
+
+        Ok(Some(HttpCacheEntry {
+            key,
+            body_path,
+            metadata,
+        }))
+    }
+
+    /// Starts storing a new response body for `url`
+    ///
+    /// The previously cached body (if any) is replaced only when
+    /// [HttpCacheEntryWriter::commit] is called.
+    pub fn begin_store(
+        &self,
+        url: &str,
+        request_headers: &HeaderMap,
+        response_headers: &HeaderMap,
+    ) -> io::Result<HttpCacheEntryWriter> {
+        let key = entry_key(url, request_headers);
+        let entry_directory = self.directory.join(&key);
+        fs::create_dir_all(&entry_directory)?;
+        let partial_body_path = entry_directory.join(PARTIAL_BODY_FILE_NAME);
+        let partial_body = File::create(&partial_body_path)?;
+
+        Ok(HttpCacheEntryWriter {
+            cache: self.clone(),
+            key,
+            entry_directory,
+            partial_body_path,
+            partial_body,
+            metadata: HttpCacheEntryMetadata {
+                url: url.to_string(),
+                etag: header_string(response_headers, ETAG),
+                last_modified: header_string(response_headers, LAST_MODIFIED),
+                content_type: header_string(response_headers, CONTENT_TYPE),
+                content_encoding: header_string(response_headers, CONTENT_ENCODING),
+                digests: BTreeMap::new(),
+            },
+            committed: false,
+        })
+    }
+
+    /// Returns a message digest memoized for a cached response body
+    ///
+    /// The digest is returned only if the entry with the given `key` (see
+    /// [HttpCacheEntry::key]) still has the given `validator`.
+    pub fn recall_digest(
+        &self,
+        key: &str,
+        validator: &str,
+        algorithm: &str,
+    ) -> io::Result<Option<Vec<u8>>> {
+        let Some(entry_directory) = self.entry_directory(key) else {
+            return Ok(None);
+        };
+        let metadata = match read_metadata(&entry_directory)? {
+            Some(metadata) if metadata.validator() == Some(validator) => metadata,
+            _ => return Ok(None),
+        };
+
+        Ok(metadata
+            .digests
+            .get(algorithm)
+            .and_then(|digest| hex::decode(digest).ok()))
+    }
+
+    /// Memoizes a message digest of a cached response body
+    ///
+    /// Does nothing, unless the entry with the given `key` still has the
+    /// given `validator`.
+    pub fn memoize_digest(
+        &self,
+        key: &str,
+        validator: &str,
+        algorithm: &str,
+        digest: &[u8],
+    ) -> io::Result<()> {
+        let Some(entry_directory) = self.entry_directory(key) else {
+            return Ok(());
+        };
+        let mut metadata = match read_metadata(&entry_directory)? {
+            Some(metadata) if metadata.validator() == Some(validator) => metadata,
+            _ => return Ok(()),
+        };
+
+        metadata
+            .digests
+            .insert(algorithm.to_string(), hex::encode(digest));
+
+        write_metadata(&entry_directory, &metadata)
+    }
+
+    /// Removes least recently used entries until the cache fits in its
+    /// maximum size
+    ///
+    /// Entries are removed until the cache fits in 7/8 of its maximum size,
+    /// so that the directory isn't scanned again by the following commits.
+    pub fn evict(&self) -> io::Result<()> {
+        let mut size = self
+            .size
+            .lock()
+            .expect("the cache size lock isn't poisoned");
+
+        *size = Some(self.evict_scanned_entries()?);
+
+        Ok(())
+    }
+
+    /// Updates the total size of the cache with the size of a committed
+    /// entry, and evicts entries if it no longer fits
+    fn add_committed_size(&self, previous_entry_size: u64, entry_size: u64) -> io::Result<()> {
+        let mut size = self
+            .size
+            .lock()
+            .expect("the cache size lock isn't poisoned");
+        let updated_size = match *size {
+            Some(size) => (size + entry_size).saturating_sub(previous_entry_size),
+            None => self.scan_size()?,
+        };
+
+        *size = Some(if updated_size > self.max_size {
+            self.evict_scanned_entries()?
+        } else {
+            updated_size
+        });
+
+        Ok(())
+    }
+
+    /// Returns the total size of the entries
+    fn scan_size(&self) -> io::Result<u64> {
+        Ok(self.scan_entries()?.iter().map(|(_, size, _)| size).sum())
+    }
+
+    /// Evicts least recently used entries, and returns the total size of the
+    /// remaining ones
+    fn evict_scanned_entries(&self) -> io::Result<u64> {
+        let mut entries = self.scan_entries()?;
+        let mut total_size = entries.iter().map(|(_, size, _)| size).sum::<u64>();
+        entries.sort();
+
+        if total_size <= self.max_size {
+            return Ok(total_size);
+        }
+        let target_size = self.max_size - self.max_size / 8;
+        for (_, size, entry_directory) in entries {
+            if total_size <= target_size {
+                break;
+            }
+            fs::remove_dir_all(&entry_directory)?;
+            total_size -= size;
+        }
+
+        Ok(total_size)
+    }
+
+    /// Returns the last use, size, and directory of each entry
+    fn scan_entries(&self) -> io::Result<Vec<(SystemTime, u64, PathBuf)>> {
+        let read_dir = match fs::read_dir(&self.directory) {
+            Ok(read_dir) => read_dir,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(error) => return Err(error),
+        };
+        let mut entries = Vec::new();
+
+        for dir_entry in read_dir {
+            let entry_directory = dir_entry?.path();
+            if !entry_directory.is_dir() {
+                continue;
+            }
+            let (size, last_used) = entry_size_and_last_use(&entry_directory)?;
+            entries.push((last_used, size, entry_directory));
+        }
+
+        Ok(entries)
+    }
+
+    /// Returns the directory of the entry with the given key, unless the key
+    /// isn't one returned by the cache
+    fn entry_directory(&self, key: &str) -> Option<PathBuf> {
+        let is_entry_key =
+            key.len() == ENTRY_KEY_LENGTH && key.bytes().all(|octet| octet.is_ascii_hexdigit());
+
+        is_entry_key.then(|| self.directory.join(key))
+    }
+}
+
+/// Returns the hexadecimal 256-bit SHA-3 digest of the URL, and the request
+/// headers sorted by name, and value
+///
+/// A request without headers is keyed by the digest of its URL alone.
+fn entry_key(url: &str, request_headers: &HeaderMap) -> String {
+    let mut headers = request_headers
+        .iter()
+        .map(|(name, value)| (name.as_str(), value.as_bytes()))
+        .collect::<Vec<_>>();
+    headers.sort();
+    let mut hasher = Sha3_256::new();
+
+    hasher.update(url.as_bytes());
+    for (name, value) in headers {
+        hasher.update(b"\n");
+        hasher.update(name.as_bytes());
+        hasher.update(b": ");
+        hasher.update(value);
+    }
+
+    hex::encode(hasher.finalize())
+}
+
+/// A cached HTTP response
+#[derive(Debug)]
+pub struct HttpCacheEntry {
+    key: String,
+    body_path: PathBuf,
+    metadata: HttpCacheEntryMetadata,
+}
+
+impl HttpCacheEntry {
+    /// Returns the key of the entry, which memoized digests are stored by
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Returns `If-None-Match`, and `If-Modified-Since` headers for
+    /// revalidating the entry
+    pub fn conditional_request_headers(&self) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+
+        insert_header(&mut headers, IF_NONE_MATCH, self.metadata.etag.as_deref());
+        insert_header(
+            &mut headers,
+            IF_MODIFIED_SINCE,
+            self.metadata.last_modified.as_deref(),
+        );
+
+        headers
+    }
+
+    /// Returns the stored headers of the cached response, and the length of
+    /// the stored body as `Content-Length`
+    pub fn response_headers(&self) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+
+        insert_header(&mut headers, ETAG, self.metadata.etag.as_deref());
+        insert_header(
+            &mut headers,
+            LAST_MODIFIED,
+            self.metadata.last_modified.as_deref(),
+        );
+        insert_header(
+            &mut headers,
+            CONTENT_TYPE,
+            self.metadata.content_type.as_deref(),
+        );
+        insert_header(
+            &mut headers,
+            CONTENT_ENCODING,
+            self.metadata.content_encoding.as_deref(),
+        );
+        if let Ok(body_metadata) = fs::metadata(&self.body_path) {
+            headers.insert(CONTENT_LENGTH, HeaderValue::from(body_metadata.len()));
+        }
+
+        headers
+    }
+
+    pub fn open_body(&self) -> io::Result<File> {
+        File::open(&self.body_path)
+    }
+}
+
+/// Writes a response body into an [HttpCache] while it's being received
+#[derive(Debug)]
+pub struct HttpCacheEntryWriter {
+    cache: HttpCache,
+    key: String,
+    entry_directory: PathBuf,
+    partial_body_path: PathBuf,
+    partial_body: File,
+    metadata: HttpCacheEntryMetadata,
+    committed: bool,
+}
+
+impl HttpCacheEntryWriter {
+    /// Returns the key of the entry being stored (see [HttpCacheEntry::key])
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    pub fn write_chunk(&mut self, chunk: &[u8]) -> io::Result<()> {
+        self.partial_body.write_all(chunk)
+    }
+
+    /// Replaces the cached response with the fully received one, and evicts
+    /// entries that no longer fit in the cache
+    pub fn commit(mut self) -> io::Result<()> {
+        let (previous_entry_size, _) = entry_size_and_last_use(&self.entry_directory)?;
+        self.partial_body.flush()?;
+        fs::rename(
+            &self.partial_body_path,
+            self.entry_directory.join(BODY_FILE_NAME),
+        )?;
+        write_metadata(&self.entry_directory, &self.metadata)?;
+        self.committed = true;
+        let (entry_size, _) = entry_size_and_last_use(&self.entry_directory)?;
+
+        self.cache
+            .add_committed_size(previous_entry_size, entry_size)
+    }
+}
+
+impl Drop for HttpCacheEntryWriter {
+    fn drop(&mut self) {
+        if !self.committed {
+            // An incomplete body must not be served later.  There's nothing
+            // useful to do if removing it fails.
+            let _ = fs::remove_file(&self.partial_body_path);
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+struct HttpCacheEntryMetadata {
+    url: String,
+    etag: Option<String>,
+    last_modified: Option<String>,
+    content_type: Option<String>,
+    content_encoding: Option<String>,
+    /// Hexadecimal message digests of the body by algorithm name
+    digests: BTreeMap<String, String>,
+}
+
+impl HttpCacheEntryMetadata {
+    fn validator(&self) -> Option<&str> {
+        self.etag.as_deref().or(self.last_modified.as_deref())
+    }
+}
+
+fn read_metadata(entry_directory: &Path) -> io::Result<Option<HttpCacheEntryMetadata>> {
+    let json = match fs::read(entry_directory.join(METADATA_FILE_NAME)) {
+        Ok(json) => json,
+        Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(error) => return Err(error),
+    };
+
+    // A corrupt metadata file is treated as a cache miss:
+    Ok(serde_json::from_slice(&json).ok())
+}
+
+fn write_metadata(entry_directory: &Path, metadata: &HttpCacheEntryMetadata) -> io::Result<()> {
+    let partial_path = entry_directory.join(format!("{METADATA_FILE_NAME}.partial"));
+
+    fs::write(&partial_path, serde_json::to_vec_pretty(metadata)?)?;
+    fs::rename(partial_path, entry_directory.join(METADATA_FILE_NAME))
+}
+
+/// Returns the size of the committed files of an entry, and the time it was
+/// last used
+///
+/// Partial files of bodies being stored aren't counted, so that committing
+/// an entry changes its size only by the difference of the committed files.
+fn entry_size_and_last_use(entry_directory: &Path) -> io::Result<(u64, SystemTime)> {
+    let mut size = 0;
+    let mut last_used = SystemTime::UNIX_EPOCH;
+
+    for file_name in [BODY_FILE_NAME, METADATA_FILE_NAME] {
+        let metadata = match fs::metadata(entry_directory.join(file_name)) {
+            Ok(metadata) => metadata,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => continue,
+            Err(error) => return Err(error),
+        };
+        size += metadata.len();
+        if file_name == METADATA_FILE_NAME {
+            last_used = metadata.modified()?;
+        }
+    }
+
+    Ok((size, last_used))
+}
+
+fn header_string(headers: &HeaderMap, name: http::header::HeaderName) -> Option<String> {
+    headers
+        .get(name)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string)
+}
+
+fn insert_header(headers: &mut HeaderMap, name: http::header::HeaderName, value: Option<&str>) {
+    if let Some(value) = value.and_then(|value| HeaderValue::from_str(value).ok()) {
+        headers.insert(name, value);
+    }
+}
@@ -0,0 +1,115 @@
+use super::interface::{GetUrl, GetUrlWithHeaders};
+use crate::credential_provider_service::LookUpCredential;
+use http::HeaderMap;
+use http::header::AUTHORIZATION;
+use std::fmt;
+
+/// A [GetUrl] decorator sending additional request headers, and credentials
+/// looked up by a [crate::credential_provider_service::CredentialProvider]
+///
+/// An `Authorization` header among the additional headers takes precedence
+/// over looked up credentials.  Headers passed to
+/// [GetUrlWithHeaders::get_url_with_headers] take precedence over both.
+#[derive(Debug)]
+pub struct AuthenticatingGetUrl<HttpClientService, CredentialProvider> {
+    http_client_service: HttpClientService,
+    credential_provider: CredentialProvider,
+    headers: HeaderMap,
+}
+
+impl<HttpClientService, CredentialProvider>
+    AuthenticatingGetUrl<HttpClientService, CredentialProvider>
+{
+    pub fn new(
+        http_client_service: HttpClientService,
+        credential_provider: CredentialProvider,
+        headers: HeaderMap,
+    ) -> Self {
+        AuthenticatingGetUrl {
+            http_client_service,
+            credential_provider,
+            headers,
+        }
+    }
+}
+
+impl<HttpClientService, CredentialProvider> GetUrl
+    for AuthenticatingGetUrl<HttpClientService, CredentialProvider>
+where
+    HttpClientService: GetUrlWithHeaders + Sync,
+    HttpClientService::Url: AsRef<str> + Sync,
+    CredentialProvider: LookUpCredential + Send + Sync,
+{
+    type Url = HttpClientService::Url;
+    type HttpResponse = HttpClientService::HttpResponse;
+    type Error = AuthenticatingGetUrlError<HttpClientService::Error, CredentialProvider::Error>;
+
+    async fn get_url(&self, url: &Self::Url) -> Result<Self::HttpResponse, Self::Error> {
+        self.get_url_with_headers(url, &HeaderMap::new()).await
+    }
+}
+
+impl<HttpClientService, CredentialProvider> GetUrlWithHeaders
+    for AuthenticatingGetUrl<HttpClientService, CredentialProvider>
+where
+    HttpClientService: GetUrlWithHeaders + Sync,
+    HttpClientService::Url: AsRef<str> + Sync,
+    CredentialProvider: LookUpCredential + Send + Sync,
+{
+    async fn get_url_with_headers(
+        &self,
+        url: &Self::Url,
+        headers: &HeaderMap,
+    ) -> Result<Self::HttpResponse, Self::Error> {
+        let mut request_headers = self.headers.clone();
+
+        if !request_headers.contains_key(AUTHORIZATION)
+            && let Some(credential) = self
+                .credential_provider
+                .look_up_credential(url.as_ref())
+                .map_err(AuthenticatingGetUrlError::Credential)?
+        {
+            request_headers.insert(AUTHORIZATION, credential.authorization().clone());
+        }
+        // Replaces, rather than appends to the values of the same headers:
+        request_headers.extend(headers.clone());
+
+        self.http_client_service
+            .get_url_with_headers(url, &request_headers)
+            .await
+            .map_err(AuthenticatingGetUrlError::Http)
+    }
+}
+
+#[derive(Debug)]
+pub enum AuthenticatingGetUrlError<HttpError, CredentialError> {
+    Http(HttpError),
+    Credential(CredentialError),
+}
+
+impl<HttpError: fmt::Display, CredentialError: fmt::Display> fmt::Display
+    for AuthenticatingGetUrlError<HttpError, CredentialError>
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AuthenticatingGetUrlError::Http(error) => error.fmt(f),
+            AuthenticatingGetUrlError::Credential(error) => {
+                write!(f, "looking up credentials failed: {}", error)
+            }
+        }
+    }
+}
+
+impl<HttpError, CredentialError> std::error::Error
+    for AuthenticatingGetUrlError<HttpError, CredentialError>
+where
+    HttpError: std::error::Error + 'static,
+    CredentialError: std::error::Error + 'static,
+{
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            AuthenticatingGetUrlError::Http(error) => Some(error),
+            AuthenticatingGetUrlError::Credential(error) => Some(error),
+        }
+    }
+}
@@ -0,0 +1,287 @@
+use super::interface::{GetUrl, GetUrlWithHeaders, IntoChunkStream, ResponseMetadata};
+use super::redirect_policy::Redirect;
+use crate::http_cache::{HttpCache, HttpCacheEntryWriter};
+use bytes::{Bytes, BytesMut};
+use futures_core::stream::Stream;
+use futures_util::StreamExt;
+use http::{HeaderMap, StatusCode};
+use std::fmt;
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tokio::fs::File;
+use tokio::io::{AsyncRead, ReadBuf};
+
+const CACHED_BODY_CHUNK_SIZE: usize = 64 * 1024;
+
+/// A [GetUrl] decorator storing response bodies in an [HttpCache]
+///
+/// Responses are cached by URL, and request headers, so credentials need
+/// adding by a decorator of it, e.g.,
+/// [AuthenticatingGetUrl](super::AuthenticatingGetUrl).  Requests that have
+/// a cached response are sent with `If-None-Match`, and `If-Modified-Since`
+/// headers.  When the server responds with `304 Not Modified` the cached
+/// body is streamed instead.
+#[derive(Debug)]
+pub struct CachingGetUrl<HttpClientService> {
+    http_client_service: HttpClientService,
+    cache: HttpCache,
+}
+
+impl<HttpClientService> CachingGetUrl<HttpClientService> {
+    pub fn new(http_client_service: HttpClientService, cache: HttpCache) -> Self {
+        CachingGetUrl {
+            http_client_service,
+            cache,
+        }
+    }
+}
+
+impl<HttpClientService> GetUrl for CachingGetUrl<HttpClientService>
+where
+    HttpClientService: GetUrlWithHeaders + Sync,
+    HttpClientService::Url: AsRef<str> + Sync,
+    HttpClientService::HttpResponse: ResponseMetadata,
+{
+    type Url = HttpClientService::Url;
+    type HttpResponse = CachedHttpResponse<HttpClientService::HttpResponse>;
+    type Error = CachingGetUrlError<HttpClientService::Error>;
+
+    async fn get_url(&self, url: &Self::Url) -> Result<Self::HttpResponse, Self::Error> {
+        self.get_url_with_headers(url, &HeaderMap::new()).await
+    }
+}
+
+impl<HttpClientService> GetUrlWithHeaders for CachingGetUrl<HttpClientService>
+where
+    HttpClientService: GetUrlWithHeaders + Sync,
+    HttpClientService::Url: AsRef<str> + Sync,
+    HttpClientService::HttpResponse: ResponseMetadata,
+{
+    async fn get_url_with_headers(
+        &self,
+        url: &Self::Url,
+        headers: &HeaderMap,
+    ) -> Result<Self::HttpResponse, Self::Error> {
+        // An unreadable cache entry is treated as a cache miss:
+        let cached_entry = self.cache.lookup(url.as_ref(), headers).ok().flatten();
+        let mut request_headers = headers.clone();
+
+        if let Some(cached_entry) = &cached_entry {
+            request_headers.extend(cached_entry.conditional_request_headers());
+        }
+
+        let response = self
+            .http_client_service
+            .get_url_with_headers(url, &request_headers)
+            .await
+            .map_err(CachingGetUrlError::Http)?;
+
+        match (response.status(), cached_entry) {
+            (StatusCode::NOT_MODIFIED, Some(cached_entry)) => {
+                Ok(CachedHttpResponse::Cached(CachedResponse {
+                    cache_key: cached_entry.key().to_string(),
+                    final_url: response.final_url(),
+                    redirects: response.redirects(),
+                    peer_certificates: response.peer_certificates(),
+                    headers: cached_entry.response_headers(),
+                    body: File::from_std(cached_entry.open_body().map_err(CachingGetUrlError::Io)?),
+                }))
+            }
+            (StatusCode::OK, _) if response.validator().is_some() => {
+                let writer = self
+                    .cache
+                    .begin_store(url.as_ref(), headers, response.headers())
+                    .map_err(CachingGetUrlError::Io)?;
+
+                Ok(CachedHttpResponse::Storing(response, writer))
+            }
+            _ => Ok(CachedHttpResponse::Uncached(response)),
+        }
+    }
+}
+
+/// A response returned by [CachingGetUrl]
+#[derive(Debug)]
+pub enum CachedHttpResponse<HttpResponse> {
+    /// A response that can't be revalidated later, so it isn't cached
+    Uncached(HttpResponse),
+    /// A response whose body is stored in the cache while it's streamed
+    Storing(HttpResponse, HttpCacheEntryWriter),
+    /// A previously cached response confirmed by a `304 Not Modified`
+    Cached(CachedResponse),
+}
+
+#[derive(Debug)]
+pub struct CachedResponse {
+    cache_key: String,
+    final_url: Option<String>,
+    redirects: Vec<Redirect>,
+    peer_certificates: Vec<Vec<u8>>,
+    headers: HeaderMap,
+    body: File,
+}
+
+impl<HttpResponse: ResponseMetadata> ResponseMetadata for CachedHttpResponse<HttpResponse> {
+    fn status(&self) -> StatusCode {
+        match self {
+            CachedHttpResponse::Uncached(response) | CachedHttpResponse::Storing(response, _) => {
+                response.status()
+            }
+            CachedHttpResponse::Cached(_) => StatusCode::OK,
+        }
+    }
+
+    fn headers(&self) -> &HeaderMap {
+        match self {
+            CachedHttpResponse::Uncached(response) | CachedHttpResponse::Storing(response, _) => {
+                response.headers()
+            }
+            CachedHttpResponse::Cached(cached_response) => &cached_response.headers,
+        }
+    }
+
+    fn final_url(&self) -> Option<String> {
+        match self {
+            CachedHttpResponse::Uncached(response) | CachedHttpResponse::Storing(response, _) => {
+                response.final_url()
+            }
+            CachedHttpResponse::Cached(cached_response) => cached_response.final_url.clone(),
+        }
+    }
+
+    fn redirects(&self) -> Vec<Redirect> {
+        match self {
+            CachedHttpResponse::Uncached(response) | CachedHttpResponse::Storing(response, _) => {
+                response.redirects()
+            }
+            CachedHttpResponse::Cached(cached_response) => cached_response.redirects.clone(),
+        }
+    }
+
+    fn peer_certificates(&self) -> Vec<Vec<u8>> {
+        match self {
+            CachedHttpResponse::Uncached(response) | CachedHttpResponse::Storing(response, _) => {
+                response.peer_certificates()
+            }
+            CachedHttpResponse::Cached(cached_response) => {
+                cached_response.peer_certificates.clone()
+            }
+        }
+    }
+
+    fn cache_key(&self) -> Option<&str> {
+        match self {
+            CachedHttpResponse::Uncached(response) => response.cache_key(),
+            CachedHttpResponse::Storing(_, writer) => Some(writer.key()),
+            CachedHttpResponse::Cached(cached_response) => Some(&cached_response.cache_key),
+        }
+    }
+}
+
+impl<HttpResponse: IntoChunkStream> IntoChunkStream for CachedHttpResponse<HttpResponse> {
+    type Error = CachingGetUrlError<HttpResponse::Error>;
+
+    fn into_chunk_stream(self) -> impl Stream<Item = Result<Bytes, Self::Error>> + Unpin {
+        match self {
+            CachedHttpResponse::Uncached(response) => {
+                CachedChunkStream::Uncached(response.into_chunk_stream())
+            }
+            CachedHttpResponse::Storing(response, writer) => {
+                CachedChunkStream::Storing(response.into_chunk_stream(), Some(Box::new(writer)))
+            }
+            CachedHttpResponse::Cached(cached_response) => {
+                CachedChunkStream::Cached(cached_response.body, BytesMut::new())
+            }
+        }
+    }
+}
+
+enum CachedChunkStream<ChunkStream> {
+    Uncached(ChunkStream),
+    Storing(ChunkStream, Option<Box<HttpCacheEntryWriter>>),
+    /// The cached body, and the buffer its chunks are read into, which is
+    /// reused once the previous chunk is dropped
+    Cached(File, BytesMut),
+}
+
+impl<ChunkStream, Error> Stream for CachedChunkStream<ChunkStream>
+where
+    ChunkStream: Stream<Item = Result<Bytes, Error>> + Unpin,
+{
+    type Item = Result<Bytes, CachingGetUrlError<Error>>;
+
+    fn poll_next(self: Pin<&mut Self>, context: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        match self.get_mut() {
+            CachedChunkStream::Uncached(chunk_stream) => chunk_stream
+                .poll_next_unpin(context)
+                .map(|item| item.map(|result| result.map_err(CachingGetUrlError::Http))),
+            CachedChunkStream::Storing(chunk_stream, writer) => {
+                match chunk_stream.poll_next_unpin(context) {
+                    Poll::Ready(Some(Ok(chunk))) => {
+                        if let Some(writer) = writer
+                            && let Err(error) = writer.write_chunk(&chunk)
+                        {
+                            return Poll::Ready(Some(Err(CachingGetUrlError::Io(error))));
+                        }
+                        Poll::Ready(Some(Ok(chunk)))
+                    }
+                    Poll::Ready(Some(Err(error))) => {
+                        // Drop the incomplete body:
+                        writer.take();
+                        Poll::Ready(Some(Err(CachingGetUrlError::Http(error))))
+                    }
+                    Poll::Ready(None) => match writer.take().map(|writer| writer.commit()) {
+                        Some(Err(error)) => Poll::Ready(Some(Err(CachingGetUrlError::Io(error)))),
+                        _ => Poll::Ready(None),
+                    },
+                    Poll::Pending => Poll::Pending,
+                }
+            }
+            CachedChunkStream::Cached(body, buffer) => {
+                buffer.resize(CACHED_BODY_CHUNK_SIZE, 0);
+                let mut read_buf = ReadBuf::new(buffer);
+
+                match Pin::new(body).poll_read(context, &mut read_buf) {
+                    Poll::Ready(Ok(())) => match read_buf.filled().len() {
+                        0 => Poll::Ready(None),
+                        length => {
+                            buffer.truncate(length);
+                            Poll::Ready(Some(Ok(buffer.split().freeze())))
+                        }
+                    },
+                    Poll::Ready(Err(error)) => {
+                        Poll::Ready(Some(Err(CachingGetUrlError::Io(error))))
+                    }
+                    Poll::Pending => Poll::Pending,
+                }
+            }
+        }
+    }
+}
+
+/// An error from a [CachingGetUrl] decorated service, or from accessing its
+/// cache
+#[derive(Debug)]
+pub enum CachingGetUrlError<HttpError> {
+    Http(HttpError),
+    Io(io::Error),
+}
+
+impl<HttpError: fmt::Display> fmt::Display for CachingGetUrlError<HttpError> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CachingGetUrlError::Http(error) => error.fmt(f),
+            CachingGetUrlError::Io(error) => write!(f, "HTTP cache error: {}", error),
+        }
+    }
+}
+
+impl<HttpError: std::error::Error + 'static> std::error::Error for CachingGetUrlError<HttpError> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            CachingGetUrlError::Http(error) => Some(error),
+            CachingGetUrlError::Io(error) => Some(error),
+        }
+    }
+}
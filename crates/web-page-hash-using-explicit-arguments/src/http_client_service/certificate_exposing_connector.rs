@@ -0,0 +1,137 @@
+use super::certificate_fingerprint::CertificateFingerprint;
+use super::pinned_certificate_verifier::pinned_certificate_tls_config;
+use hyper::rt::{Read, ReadBufCursor, Write};
+use hyper_rustls::{ConfigBuilderExt, HttpsConnector, HttpsConnectorBuilder, MaybeHttpsStream};
+use hyper_util::client::legacy::connect::{Connected, Connection, HttpConnector};
+use hyper_util::rt::TokioIo;
+use rustls::ClientConfig;
+use std::io;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use tokio::net::TcpStream;
+use tower_service::Service;
+
+/// A [hyper_util] connector establishing TLS connections with [rustls],
+/// which exposes the server certificates on responses
+///
+/// Server certificates are verified against the Mozilla root certificates
+/// bundled with `webpki-roots`, unless certificates are pinned.  Then, only
+/// server certificates with pinned SHA-256 fingerprints are trusted,
+/// whoever issued them, e.g., self-signed certificates, and the connection
+/// fails before a request is sent (see [pinned_certificate_tls_config]).
+/// Responses carry the certificates in a [PeerCertificates] extension.
+#[derive(Clone, Debug)]
+pub struct CertificateExposingConnector {
+    https_connector: HttpsConnector<HttpConnector>,
+}
+
+impl CertificateExposingConnector {
+    pub fn new(
+        http_connector: HttpConnector,
+        pinned_certificates: &[CertificateFingerprint],
+    ) -> Self {
+        let tls_config = if pinned_certificates.is_empty() {
+            ClientConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+                .with_safe_default_protocol_versions()
+                .expect("the ring provider supports the default protocol versions")
+                .with_webpki_roots()
+                .with_no_client_auth()
+        } else {
+            pinned_certificate_tls_config(pinned_certificates)
+        };
+
+        CertificateExposingConnector {
+            https_connector: HttpsConnectorBuilder::new()
+                .with_tls_config(tls_config)
+                .https_or_http()
+                .enable_http1()
+                .wrap_connector(http_connector),
+        }
+    }
+}
+
+impl Service<http::Uri> for CertificateExposingConnector {
+    type Response = CertificateExposingStream;
+    type Error = Box<dyn std::error::Error + Send + Sync>;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.https_connector.poll_ready(cx)
+    }
+
+    fn call(&mut self, uri: http::Uri) -> Self::Future {
+        let connecting = self.https_connector.call(uri);
+
+        Box::pin(async move { Ok(CertificateExposingStream(connecting.await?)) })
+    }
+}
+
+/// The DER-encoded certificates a server presented, starting with its own
+/// certificate, as an extension of responses received over TLS
+#[derive(Clone, Debug)]
+pub struct PeerCertificates(pub Vec<Vec<u8>>);
+
+/// A connection established by a [CertificateExposingConnector]
+#[derive(Debug)]
+pub struct CertificateExposingStream(MaybeHttpsStream<TokioIo<TcpStream>>);
+
+impl Connection for CertificateExposingStream {
+    fn connected(&self) -> Connected {
+        let connected = self.0.connected();
+        match &self.0 {
+            MaybeHttpsStream::Http(_) => connected,
+            MaybeHttpsStream::Https(stream) => {
+                let (_, connection) = stream.inner().get_ref();
+                let peer_certificates = connection
+                    .peer_certificates()
+                    .unwrap_or_default()
+                    .iter()
+                    .map(|certificate| certificate.to_vec())
+                    .collect();
+
+                connected.extra(PeerCertificates(peer_certificates))
+            }
+        }
+    }
+}
+
+impl Read for CertificateExposingStream {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: ReadBufCursor<'_>,
+    ) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.0).poll_read(cx, buf)
+    }
+}
+
+impl Write for CertificateExposingStream {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.0).poll_write(cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.0).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.0).poll_shutdown(cx)
+    }
+
+    fn is_write_vectored(&self) -> bool {
+        self.0.is_write_vectored()
+    }
+
+    fn poll_write_vectored(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[io::IoSlice<'_>],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.0).poll_write_vectored(cx, bufs)
+    }
+}
@@ -0,0 +1,74 @@
+use crate::digest_encoding::to_hex;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::str::FromStr;
+
+/// The SHA-256 fingerprint of a DER-encoded X.509 certificate
+///
+/// It's displayed as lowercase hexadecimal digits, and parsed from
+/// hexadecimal digits optionally separated by colons, as printed by
+/// `openssl x509 -noout -fingerprint -sha256`.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct CertificateFingerprint([u8; 32]);
+
+impl CertificateFingerprint {
+    /// Returns the fingerprint of a DER-encoded certificate
+    pub fn of(certificate: &[u8]) -> Self {
+        CertificateFingerprint(Sha256::digest(certificate).into())
+    }
+
+    pub fn octets(&self) -> &[u8; 32] {
+        &self.0
+    }
+}
+
+impl fmt::Display for CertificateFingerprint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&to_hex(&self.0))
+    }
+}
+
+impl FromStr for CertificateFingerprint {
+    type Err = InvalidCertificateFingerprint;
+
+    fn from_str(fingerprint: &str) -> Result<Self, Self::Err> {
+        let digits = fingerprint.trim().replace(':', "");
+
+        hex::decode(digits)
+            .ok()
+            .and_then(|octets| octets.try_into().ok())
+            .map(CertificateFingerprint)
+            .ok_or_else(|| InvalidCertificateFingerprint(fingerprint.to_string()))
+    }
+}
+
+impl TryFrom<String> for CertificateFingerprint {
+    type Error = InvalidCertificateFingerprint;
+
+    fn try_from(fingerprint: String) -> Result<Self, Self::Error> {
+        fingerprint.parse()
+    }
+}
+
+impl From<CertificateFingerprint> for String {
+    fn from(fingerprint: CertificateFingerprint) -> Self {
+        fingerprint.to_string()
+    }
+}
+
+#[derive(Debug)]
+pub struct InvalidCertificateFingerprint(pub String);
+
+impl fmt::Display for InvalidCertificateFingerprint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid certificate fingerprint {} (expected 64 hexadecimal digits)",
+            self.0
+        )
+    }
+}
+
+impl std::error::Error for InvalidCertificateFingerprint {}
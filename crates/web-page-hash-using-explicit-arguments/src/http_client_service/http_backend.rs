@@ -0,0 +1,191 @@
+use crate::configuration_service::HttpClientConfiguration;
+use std::fmt;
+use std::str::FromStr;
+
+/// HTTP client services that can be bound to the HTTP client service
+/// dependency
+///
+/// Services other than `reqwest` are available when the crate is built with
+/// the respective cargo feature.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum HttpBackend {
+    /// [ReqwestHttpClientService](super::ReqwestHttpClientService)
+    #[default]
+    Reqwest,
+    /// [HyperGetUrlService](super::HyperGetUrlService)
+    #[cfg(feature = "hyper")]
+    Hyper,
+    /// [UreqGetUrlService](super::UreqGetUrlService)
+    #[cfg(feature = "ureq")]
+    Ureq,
+    /// [CurlGetUrlService](super::CurlGetUrlService)
+    #[cfg(feature = "curl")]
+    Curl,
+}
+
+impl HttpBackend {
+    /// The backends available in this build
+    pub const ALL: &[HttpBackend] = &[
+        HttpBackend::Reqwest,
+        #[cfg(feature = "hyper")]
+        HttpBackend::Hyper,
+        #[cfg(feature = "ureq")]
+        HttpBackend::Ureq,
+        #[cfg(feature = "curl")]
+        HttpBackend::Curl,
+    ];
+
+    /// Returns the name of the backend (e.g., `reqwest`)
+    pub fn name(self) -> &'static str {
+        match self {
+            HttpBackend::Reqwest => "reqwest",
+            #[cfg(feature = "hyper")]
+            HttpBackend::Hyper => "hyper",
+            #[cfg(feature = "ureq")]
+            HttpBackend::Ureq => "ureq",
+            #[cfg(feature = "curl")]
+            HttpBackend::Curl => "curl",
+        }
+    }
+
+    /// Returns whether the service checks pinned certificates (see
+    /// [HttpClientConfiguration::pinned_certificates])
+    pub fn supports_pinned_certificates(self) -> bool {
+        match self {
+            HttpBackend::Reqwest => true,
+            #[cfg(feature = "hyper")]
+            HttpBackend::Hyper => true,
+            #[cfg(feature = "ureq")]
+            HttpBackend::Ureq => false,
+            #[cfg(feature = "curl")]
+            HttpBackend::Curl => false,
+        }
+    }
+
+    /// Fails, when the configuration pins certificates, which the service
+    /// doesn't check, so that they aren't silently trusted
+    pub fn check_configuration(
+        self,
+        configuration: &impl HttpClientConfiguration,
+    ) -> Result<(), UnsupportedPinnedCertificates> {
+        if configuration.pinned_certificates().is_empty() || self.supports_pinned_certificates() {
+            Ok(())
+        } else {
+            Err(UnsupportedPinnedCertificates(self))
+        }
+    }
+}
+
+impl fmt::Display for HttpBackend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for HttpBackend {
+    type Err = UnknownHttpBackend;
+
+    /// Parses the name of an available backend ignoring case
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        HttpBackend::ALL
+            .iter()
+            .copied()
+            .find(|http_backend| http_backend.name().eq_ignore_ascii_case(name))
+            .ok_or_else(|| UnknownHttpBackend(name.to_string()))
+    }
+}
+
+#[derive(Debug)]
+pub struct UnknownHttpBackend(pub String);
+
+impl fmt::Display for UnknownHttpBackend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let names = HttpBackend::ALL
+            .iter()
+            .map(|http_backend| http_backend.name())
+            .collect::<Vec<_>>();
+
+        write!(
+            f,
+            "unknown HTTP backend {} (expected {})",
+            self.0,
+            names.join(", ")
+        )
+    }
+}
+
+impl std::error::Error for UnknownHttpBackend {}
+
+#[derive(Debug)]
+pub struct UnsupportedPinnedCertificates(pub HttpBackend);
+
+impl fmt::Display for UnsupportedPinnedCertificates {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let names = HttpBackend::ALL
+            .iter()
+            .filter(|http_backend| http_backend.supports_pinned_certificates())
+            .map(|http_backend| http_backend.name())
+            .collect::<Vec<_>>();
+
+        write!(
+            f,
+            "pinned certificates aren't supported by the {} HTTP backend (only by {})",
+            self.0,
+            names.join(", ")
+        )
+    }
+}
+
+impl std::error::Error for UnsupportedPinnedCertificates {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::configuration_service::{ConfigurationLayer, LayeredConfigurationService};
+
+    #[test]
+    fn names_of_available_backends_are_parsed_ignoring_case() {
+        for &http_backend in HttpBackend::ALL {
+            assert_eq!(
+                http_backend.name().parse::<HttpBackend>().unwrap(),
+                http_backend
+            );
+            assert_eq!(
+                http_backend
+                    .name()
+                    .to_ascii_uppercase()
+                    .parse::<HttpBackend>()
+                    .unwrap(),
+                http_backend
+            );
+        }
+
+        let error = "wget".parse::<HttpBackend>().unwrap_err();
+        assert!(
+            error
+                .to_string()
+                .starts_with("unknown HTTP backend wget (expected reqwest"),
+            "{error}"
+        );
+    }
+
+    #[test]
+    fn pinned_certificates_require_a_backend_checking_them() {
+        let pinning_configuration = LayeredConfigurationService::new([ConfigurationLayer {
+            pin_cert_sha256: Some(vec!["00".repeat(32).parse().unwrap()]),
+            ..ConfigurationLayer::default()
+        }]);
+        let configuration = LayeredConfigurationService::new([ConfigurationLayer::default()]);
+
+        for &http_backend in HttpBackend::ALL {
+            assert!(http_backend.check_configuration(&configuration).is_ok());
+            assert_eq!(
+                http_backend
+                    .check_configuration(&pinning_configuration)
+                    .is_ok(),
+                http_backend.supports_pinned_certificates(),
+                "{http_backend}"
+            );
+        }
+    }
+}
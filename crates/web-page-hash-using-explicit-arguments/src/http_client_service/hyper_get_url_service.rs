@@ -0,0 +1,185 @@
+use super::certificate_exposing_connector::{CertificateExposingConnector, PeerCertificates};
+use super::interface::{GetUrl, GetUrlWithHeaders, IntoChunkStream, ResponseMetadata};
+use crate::clock_service::Sleep;
+use crate::configuration_service::HttpClientConfiguration;
+use bytes::Bytes;
+use futures_core::stream::Stream;
+use futures_util::StreamExt;
+use futures_util::future::{self, Either};
+use http::header::{HeaderValue, USER_AGENT};
+use http::{HeaderMap, StatusCode};
+use http_body_util::{BodyStream, Empty};
+use hyper::body::Incoming;
+use hyper_util::client::legacy::{Client, connect::HttpConnector};
+use hyper_util::rt::TokioExecutor;
+use std::fmt;
+use std::pin::pin;
+use std::time::Duration;
+
+/// An injectable HTTP client service implemented using the [hyper] crate
+///
+/// Redirects aren't followed, so that a [super::RedirectingGetUrl] decorator
+/// can follow them according to a policy.  A redirect response is returned
+/// as is.  A configured timeout limits the time until the response headers
+/// are received, waited for with the [Sleeper](crate::clock_service::Sleeper),
+/// and proxies aren't supported.  TLS connections are established with
+/// [rustls], exposing the server certificates, and checking pinned
+/// certificates (see [CertificateExposingConnector]).
+#[derive(Debug)]
+pub struct HyperGetUrlService<Sleeper> {
+    client: Client<CertificateExposingConnector, Empty<Bytes>>,
+    sleeper: Sleeper,
+    timeout: Option<Duration>,
+    user_agent: Option<HeaderValue>,
+}
+
+impl<Sleeper> HyperGetUrlService<Sleeper> {
+    pub fn new(sleeper: Sleeper) -> Self {
+        let mut http_connector = HttpConnector::new();
+        http_connector.enforce_http(false);
+
+        HyperGetUrlService {
+            client: Client::builder(TokioExecutor::new())
+                .build(CertificateExposingConnector::new(http_connector, &[])),
+            sleeper,
+            timeout: None,
+            user_agent: None,
+        }
+    }
+
+    /// Creates a service with timeouts, a user agent, and pinned certificates
+    /// read from a configuration service
+    pub fn from_configuration(
+        configuration: &impl HttpClientConfiguration,
+        sleeper: Sleeper,
+    ) -> Result<Self, HyperGetUrlError> {
+        if configuration.proxy().is_some() {
+            return Err(HyperGetUrlError::ProxyUnsupported);
+        }
+        let user_agent = HeaderValue::from_str(configuration.user_agent())
+            .map_err(|_| HyperGetUrlError::InvalidUserAgent)?;
+        let mut http_connector = HttpConnector::new();
+        // HTTPS URLs are handled by the wrapping connector:
+        http_connector.enforce_http(false);
+        http_connector.set_connect_timeout(configuration.connect_timeout());
+
+        Ok(HyperGetUrlService {
+            client: Client::builder(TokioExecutor::new()).build(CertificateExposingConnector::new(
+                http_connector,
+                configuration.pinned_certificates(),
+            )),
+            sleeper,
+            timeout: configuration.timeout(),
+            user_agent: Some(user_agent),
+        })
+    }
+}
+
+impl<Sleeper: Default> Default for HyperGetUrlService<Sleeper> {
+    fn default() -> Self {
+        HyperGetUrlService::new(Sleeper::default())
+    }
+}
+
+impl<Sleeper: Sleep + Sync> GetUrl for HyperGetUrlService<Sleeper> {
+    type Url = url::Url;
+    type HttpResponse = http::Response<Incoming>;
+    type Error = HyperGetUrlError;
+
+    async fn get_url(&self, url: &Self::Url) -> Result<Self::HttpResponse, Self::Error> {
+        self.get_url_with_headers(url, &HeaderMap::new()).await
+    }
+}
+
+impl<Sleeper: Sleep + Sync> GetUrlWithHeaders for HyperGetUrlService<Sleeper> {
+    async fn get_url_with_headers(
+        &self,
+        url: &Self::Url,
+        headers: &HeaderMap,
+    ) -> Result<Self::HttpResponse, Self::Error> {
+        let mut request = http::Request::get(url.as_str())
+            .body(Empty::new())
+            .map_err(HyperGetUrlError::Request)?;
+        if let Some(user_agent) = &self.user_agent {
+            request.headers_mut().insert(USER_AGENT, user_agent.clone());
+        }
+        request.headers_mut().extend(headers.clone());
+        let response_future = self.client.request(request);
+
+        match self.timeout {
+            Some(timeout) => {
+                match future::select(pin!(response_future), pin!(self.sleeper.sleep(timeout))).await
+                {
+                    Either::Left((response_result, _)) => response_result,
+                    Either::Right(_) => return Err(HyperGetUrlError::TimedOut),
+                }
+            }
+            None => response_future.await,
+        }
+        .map_err(HyperGetUrlError::Client)
+    }
+}
+
+impl IntoChunkStream for http::Response<Incoming> {
+    type Error = hyper::Error;
+
+    fn into_chunk_stream(self) -> impl Stream<Item = Result<Bytes, Self::Error>> + Unpin {
+        // Skip non-data frames, such as trailers:
+        BodyStream::new(self.into_body()).filter_map(|frame_result| {
+            future::ready(match frame_result {
+                Ok(frame) => frame.into_data().ok().map(Ok),
+                Err(error) => Some(Err(error)),
+            })
+        })
+    }
+}
+
+impl ResponseMetadata for http::Response<Incoming> {
+    fn status(&self) -> StatusCode {
+        http::Response::status(self)
+    }
+
+    fn headers(&self) -> &HeaderMap {
+        http::Response::headers(self)
+    }
+
+    fn peer_certificates(&self) -> Vec<Vec<u8>> {
+        self.extensions()
+            .get::<PeerCertificates>()
+            .map(|peer_certificates| peer_certificates.0.clone())
+            .unwrap_or_default()
+    }
+}
+
+#[derive(Debug)]
+pub enum HyperGetUrlError {
+    Request(http::Error),
+    Client(hyper_util::client::legacy::Error),
+    TimedOut,
+    InvalidUserAgent,
+    ProxyUnsupported,
+}
+
+impl fmt::Display for HyperGetUrlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HyperGetUrlError::Request(error) => write!(f, "invalid HTTP request: {}", error),
+            HyperGetUrlError::Client(error) => write!(f, "HTTP request failed: {}", error),
+            HyperGetUrlError::TimedOut => write!(f, "HTTP request timed out"),
+            HyperGetUrlError::InvalidUserAgent => write!(f, "user agent isn't a header value"),
+            HyperGetUrlError::ProxyUnsupported => {
+                write!(f, "proxies aren't supported by the hyper HTTP client")
+            }
+        }
+    }
+}
+
+impl std::error::Error for HyperGetUrlError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            HyperGetUrlError::Request(error) => Some(error),
+            HyperGetUrlError::Client(error) => Some(error),
+            _ => None,
+        }
+    }
+}
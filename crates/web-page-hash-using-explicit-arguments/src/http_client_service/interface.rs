@@ -1,5 +1,8 @@
+use super::certificate_fingerprint::CertificateFingerprint;
+use super::redirect_policy::Redirect;
 use bytes::Bytes;
 use futures_core::stream::Stream;
+use http::{HeaderMap, HeaderValue, StatusCode};
 
 #[trait_variant::make(IntoChunkStream: Send)]
 pub trait ThreadLocalIntoChunkStream {
@@ -8,6 +11,123 @@ pub trait ThreadLocalIntoChunkStream {
     fn into_chunk_stream(self) -> impl Stream<Item = Result<Bytes, Self::Error>> + Unpin;
 }
 
+/// Allows inspecting an HTTP response before its body is streamed
+pub trait ResponseMetadata {
+    fn status(&self) -> StatusCode;
+
+    fn headers(&self) -> &HeaderMap;
+
+    /// Returns the `Content-Type` header value
+    fn content_type(&self) -> Option<&str> {
+        self.headers()
+            .get(http::header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+    }
+
+    /// Returns the `Content-Encoding` header value
+    fn content_encoding(&self) -> Option<&str> {
+        self.headers()
+            .get(http::header::CONTENT_ENCODING)
+            .and_then(|value| value.to_str().ok())
+    }
+
+    /// Returns the `Content-Length` header value
+    fn content_length(&self) -> Option<u64> {
+        self.headers()
+            .get(http::header::CONTENT_LENGTH)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.parse().ok())
+    }
+
+    /// Returns the offset of the first octet of a `206 Partial Content`
+    /// response body from its `Content-Range` header value (e.g.,
+    /// `bytes 1000-1999/2000`)
+    fn content_range_start(&self) -> Option<u64> {
+        let content_range = self
+            .headers()
+            .get(http::header::CONTENT_RANGE)?
+            .to_str()
+            .ok()?;
+        let (start, _) = content_range.strip_prefix("bytes ")?.split_once('-')?;
+
+        start.trim().parse().ok()
+    }
+
+    /// Returns the length of the complete body, i.e., the complete length of
+    /// the `Content-Range` header value of a `206 Partial Content` response
+    /// (e.g., `2000` of `bytes 1000-1999/2000`), and the `Content-Length`
+    /// header value of other responses
+    fn complete_length(&self) -> Option<u64> {
+        if self.status() != StatusCode::PARTIAL_CONTENT {
+            return self.content_length();
+        }
+        let content_range = self
+            .headers()
+            .get(http::header::CONTENT_RANGE)?
+            .to_str()
+            .ok()?;
+        let (_, complete_length) = content_range.split_once('/')?;
+
+        complete_length.trim().parse().ok()
+    }
+
+    /// Returns the URL of the response, after following redirects
+    ///
+    /// Returns [None] when the service doesn't know it, e.g., because it
+    /// doesn't follow redirects.
+    fn final_url(&self) -> Option<String> {
+        None
+    }
+
+    /// Returns the redirects followed before the response, starting with
+    /// the requested URL
+    ///
+    /// Returns an empty list when no redirect was followed, or the service
+    /// doesn't follow redirects.
+    fn redirects(&self) -> Vec<Redirect> {
+        Vec::new()
+    }
+
+    /// Returns the DER-encoded certificates the server presented, starting
+    /// with its own certificate
+    ///
+    /// Returns an empty list for responses received without TLS, or when the
+    /// service doesn't expose certificates.  Some services only expose the
+    /// server's own certificate.
+    fn peer_certificates(&self) -> Vec<Vec<u8>> {
+        Vec::new()
+    }
+
+    /// Returns the SHA-256 fingerprint of the server's own certificate
+    fn certificate_fingerprint(&self) -> Option<CertificateFingerprint> {
+        self.peer_certificates()
+            .first()
+            .map(|certificate| CertificateFingerprint::of(certificate))
+    }
+
+    /// Returns the `ETag`, or else the `Last-Modified` header value
+    ///
+    /// A response body is expected to be the same for the same URL, and
+    /// validator.
+    fn validator(&self) -> Option<&str> {
+        let headers = self.headers();
+
+        headers
+            .get(http::header::ETAG)
+            .or_else(|| headers.get(http::header::LAST_MODIFIED))
+            .and_then(|value| value.to_str().ok())
+    }
+
+    /// Returns the key of the cache entry storing the response, if it's
+    /// cached, e.g., by [CachingGetUrl](super::CachingGetUrl)
+    ///
+    /// Digests are memoized by the key, so that they're memoized for the
+    /// response they're calculated from.
+    fn cache_key(&self) -> Option<&str> {
+        None
+    }
+}
+
 /// A function trait providing a `get_url` function for [HttpClientService]
 ///
 /// See <https://blog.rust-lang.org/2023/12/21/async-fn-rpit-in-traits/>, and
@@ -25,6 +145,62 @@ pub trait ThreadLocalGetUrl {
     ) -> impl Future<Output = Result<Self::HttpResponse, Self::Error>>;
 }
 
+/// A function trait providing a `get_url_with_headers` function for
+/// [HttpClientService]
+///
+/// Allows sending additional request headers, e.g., `If-None-Match`, and
+/// `If-Modified-Since` for conditional requests.
+#[trait_variant::make(GetUrlWithHeaders: Send)]
+pub trait ThreadLocalGetUrlWithHeaders: GetUrl {
+    fn get_url_with_headers(
+        &self,
+        url: &Self::Url,
+        headers: &HeaderMap,
+    ) -> impl Future<Output = Result<Self::HttpResponse, Self::Error>>;
+}
+
+/// An extension trait requesting a response body from an offset with an
+/// HTTP `Range` request, e.g., for resuming an interrupted download
+///
+/// The request is sent with an `If-Range` header, so that a server sends
+/// the full body with `200 OK` instead of `206 Partial Content`, unless the
+/// body still has the given validator (see [ResponseMetadata::validator]).
+/// A server not supporting `Range` requests also sends the full body.
+#[trait_variant::make(GetUrlRange: Send)]
+pub trait ThreadLocalGetUrlRange: GetUrl {
+    fn get_url_range(
+        &self,
+        url: &Self::Url,
+        start: u64,
+        validator: &str,
+    ) -> impl Future<Output = Result<Self::HttpResponse, Self::Error>>;
+}
+
+impl<HttpClientService> GetUrlRange for HttpClientService
+where
+    HttpClientService: GetUrlWithHeaders + Sync,
+    HttpClientService::Url: Sync,
+{
+    async fn get_url_range(
+        &self,
+        url: &Self::Url,
+        start: u64,
+        validator: &str,
+    ) -> Result<Self::HttpResponse, Self::Error> {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            http::header::RANGE,
+            HeaderValue::try_from(format!("bytes={}-", start))
+                .expect("a byte range is a valid header value"),
+        );
+        if let Ok(validator) = HeaderValue::try_from(validator) {
+            headers.insert(http::header::IF_RANGE, validator);
+        }
+
+        self.get_url_with_headers(url, &headers).await
+    }
+}
+
 // The full service is composed of its method, and function traits:
 /// An injectable service that can fetch data with HTTP GET requests
 pub trait HttpClientService: GetUrl
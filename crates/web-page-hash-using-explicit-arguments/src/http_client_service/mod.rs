@@ -1,5 +1,52 @@
 pub mod interface;
 pub use interface::*;
 
+pub mod authenticating_http_client_service;
+pub use authenticating_http_client_service::*;
+
+pub mod caching_http_client_service;
+pub use caching_http_client_service::*;
+
+#[cfg(feature = "hyper")]
+pub mod certificate_exposing_connector;
+#[cfg(feature = "hyper")]
+pub use certificate_exposing_connector::*;
+
+pub mod certificate_fingerprint;
+pub use certificate_fingerprint::*;
+
+#[cfg(feature = "curl")]
+pub mod curl_get_url_service;
+#[cfg(feature = "curl")]
+pub use curl_get_url_service::*;
+
+pub mod http_backend;
+pub use http_backend::*;
+
+#[cfg(feature = "hyper")]
+pub mod hyper_get_url_service;
+#[cfg(feature = "hyper")]
+pub use hyper_get_url_service::*;
+
+pub mod pinned_certificate_verifier;
+pub use pinned_certificate_verifier::*;
+
+pub mod recording_http_client_service;
+pub use recording_http_client_service::*;
+
+pub mod redirect_policy;
+pub use redirect_policy::*;
+
+pub mod redirecting_http_client_service;
+pub use redirecting_http_client_service::*;
+
+pub mod replay_http_client_service;
+pub use replay_http_client_service::*;
+
 pub mod reqwest_http_client_service;
 pub use reqwest_http_client_service::*;
+
+#[cfg(feature = "ureq")]
+pub mod ureq_get_url_service;
+#[cfg(feature = "ureq")]
+pub use ureq_get_url_service::*;
@@ -0,0 +1,120 @@
+use super::certificate_fingerprint::CertificateFingerprint;
+use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use rustls::crypto::{CryptoProvider, verify_tls12_signature, verify_tls13_signature};
+use rustls::pki_types::{CertificateDer, ServerName, UnixTime};
+use rustls::{ClientConfig, DigitallySignedStruct, OtherError, SignatureScheme};
+use std::fmt;
+use std::sync::Arc;
+
+/// Returns a [rustls] client configuration trusting only server
+/// certificates with pinned SHA-256 fingerprints, whoever issued them, e.g.,
+/// self-signed certificates
+pub fn pinned_certificate_tls_config(
+    pinned_certificates: &[CertificateFingerprint],
+) -> ClientConfig {
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+
+    ClientConfig::builder_with_provider(provider.clone())
+        .with_safe_default_protocol_versions()
+        .expect("the ring provider supports the default protocol versions")
+        .dangerous()
+        .with_custom_certificate_verifier(Arc::new(PinnedCertificateVerifier::new(
+            pinned_certificates.to_vec(),
+            provider,
+        )))
+        .with_no_client_auth()
+}
+
+/// A [ServerCertVerifier] trusting server certificates with pinned
+/// fingerprints only
+///
+/// Signatures of handshakes are still verified, so that a server must hold
+/// the private key of a pinned certificate.  A server certificate that isn't
+/// pinned fails the handshake with an [UnpinnedCertificate] error.
+#[derive(Debug)]
+pub struct PinnedCertificateVerifier {
+    pinned_certificates: Vec<CertificateFingerprint>,
+    provider: Arc<CryptoProvider>,
+}
+
+impl PinnedCertificateVerifier {
+    pub fn new(
+        pinned_certificates: Vec<CertificateFingerprint>,
+        provider: Arc<CryptoProvider>,
+    ) -> Self {
+        PinnedCertificateVerifier {
+            pinned_certificates,
+            provider,
+        }
+    }
+}
+
+impl ServerCertVerifier for PinnedCertificateVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        let fingerprint = CertificateFingerprint::of(end_entity);
+        if !self.pinned_certificates.contains(&fingerprint) {
+            return Err(rustls::Error::Other(OtherError(Arc::new(
+                UnpinnedCertificate(fingerprint),
+            ))));
+        }
+
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        certificate: &CertificateDer<'_>,
+        signature: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls12_signature(
+            message,
+            certificate,
+            signature,
+            &self.provider.signature_verification_algorithms,
+        )
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        certificate: &CertificateDer<'_>,
+        signature: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls13_signature(
+            message,
+            certificate,
+            signature,
+            &self.provider.signature_verification_algorithms,
+        )
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.provider
+            .signature_verification_algorithms
+            .supported_schemes()
+    }
+}
+
+/// A server certificate whose fingerprint isn't pinned
+#[derive(Debug)]
+pub struct UnpinnedCertificate(pub CertificateFingerprint);
+
+impl fmt::Display for UnpinnedCertificate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "the server certificate with SHA-256 fingerprint {} isn't pinned",
+            self.0
+        )
+    }
+}
+
+impl std::error::Error for UnpinnedCertificate {}
@@ -0,0 +1,261 @@
+use super::interface::{GetUrl, GetUrlWithHeaders, IntoChunkStream, ResponseMetadata};
+use super::redirect_policy::Redirect;
+use crate::credential_provider_service::redact_url_credentials;
+use crate::http_cassette::{Cassette, CassetteInteraction};
+use bytes::Bytes;
+use futures_core::stream::Stream;
+use futures_util::StreamExt;
+use http::header::{AUTHORIZATION, COOKIE, HeaderName, PROXY_AUTHORIZATION, SET_COOKIE};
+use http::{HeaderMap, StatusCode};
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+
+/// The response headers that may carry credentials, e.g., session cookies,
+/// which aren't recorded
+const UNRECORDED_HEADERS: [HeaderName; 5] = [
+    SET_COOKIE,
+    HeaderName::from_static("set-cookie2"),
+    COOKIE,
+    AUTHORIZATION,
+    PROXY_AUTHORIZATION,
+];
+
+/// A [GetUrl] decorator recording responses into a [Cassette] file
+///
+/// A response is recorded, and the cassette file is written, once its body
+/// has been fully streamed.  Responses recorded earlier for other URLs are
+/// kept.  URLs are recorded without credentials, and responses without
+/// headers that may carry them, such as `Set-Cookie`.
+#[derive(Debug)]
+pub struct RecordingGetUrl<HttpClientService> {
+    http_client_service: HttpClientService,
+    cassette: Arc<Mutex<Cassette>>,
+    cassette_path: PathBuf,
+}
+
+impl<HttpClientService> RecordingGetUrl<HttpClientService> {
+    /// Creates a service adding to the cassette at `cassette_path`, which is
+    /// created, if it doesn't exist
+    pub fn new(http_client_service: HttpClientService, cassette_path: &Path) -> io::Result<Self> {
+        Ok(RecordingGetUrl {
+            http_client_service,
+            cassette: Arc::new(Mutex::new(Cassette::load_or_default(cassette_path)?)),
+            cassette_path: cassette_path.to_path_buf(),
+        })
+    }
+}
+
+impl<HttpClientService> GetUrl for RecordingGetUrl<HttpClientService>
+where
+    HttpClientService: GetUrlWithHeaders + Sync,
+    HttpClientService::Url: AsRef<str> + Sync,
+    HttpClientService::HttpResponse: ResponseMetadata,
+{
+    type Url = HttpClientService::Url;
+    type HttpResponse = RecordingHttpResponse<HttpClientService::HttpResponse>;
+    type Error = HttpClientService::Error;
+
+    async fn get_url(&self, url: &Self::Url) -> Result<Self::HttpResponse, Self::Error> {
+        self.get_url_with_headers(url, &HeaderMap::new()).await
+    }
+}
+
+impl<HttpClientService> GetUrlWithHeaders for RecordingGetUrl<HttpClientService>
+where
+    HttpClientService: GetUrlWithHeaders + Sync,
+    HttpClientService::Url: AsRef<str> + Sync,
+    HttpClientService::HttpResponse: ResponseMetadata,
+{
+    async fn get_url_with_headers(
+        &self,
+        url: &Self::Url,
+        headers: &HeaderMap,
+    ) -> Result<Self::HttpResponse, Self::Error> {
+        let response = self
+            .http_client_service
+            .get_url_with_headers(url, headers)
+            .await?;
+        let recorder = CassetteRecorder {
+            cassette: self.cassette.clone(),
+            cassette_path: self.cassette_path.clone(),
+            url: redact_url_credentials(url.as_ref()).into_owned(),
+            final_url: response
+                .final_url()
+                .map(|final_url| redact_url_credentials(&final_url).into_owned()),
+            redirects: response
+                .redirects()
+                .into_iter()
+                .map(|redirect| Redirect {
+                    url: redact_url_credentials(&redirect.url).into_owned(),
+                    ..redirect
+                })
+                .collect(),
+            peer_certificates: response.peer_certificates(),
+            status: response.status(),
+            headers: recorded_headers(response.headers()),
+            chunks: Vec::new(),
+        };
+
+        Ok(RecordingHttpResponse { response, recorder })
+    }
+}
+
+/// A response returned by [RecordingGetUrl]
+#[derive(Debug)]
+pub struct RecordingHttpResponse<HttpResponse> {
+    response: HttpResponse,
+    recorder: CassetteRecorder,
+}
+
+impl<HttpResponse: ResponseMetadata> ResponseMetadata for RecordingHttpResponse<HttpResponse> {
+    fn status(&self) -> StatusCode {
+        self.response.status()
+    }
+
+    fn headers(&self) -> &HeaderMap {
+        self.response.headers()
+    }
+
+    fn final_url(&self) -> Option<String> {
+        self.response.final_url()
+    }
+
+    fn redirects(&self) -> Vec<Redirect> {
+        self.response.redirects()
+    }
+
+    fn peer_certificates(&self) -> Vec<Vec<u8>> {
+        self.response.peer_certificates()
+    }
+
+    fn cache_key(&self) -> Option<&str> {
+        self.response.cache_key()
+    }
+}
+
+impl<HttpResponse: IntoChunkStream> IntoChunkStream for RecordingHttpResponse<HttpResponse> {
+    type Error = RecordingGetUrlError<HttpResponse::Error>;
+
+    fn into_chunk_stream(self) -> impl Stream<Item = Result<Bytes, Self::Error>> + Unpin {
+        RecordingChunkStream {
+            chunk_stream: self.response.into_chunk_stream(),
+            recorder: Some(self.recorder),
+        }
+    }
+}
+
+/// Returns the response headers without the [UNRECORDED_HEADERS]
+fn recorded_headers(headers: &HeaderMap) -> HeaderMap {
+    let mut recorded_headers = headers.clone();
+    for name in &UNRECORDED_HEADERS {
+        recorded_headers.remove(name);
+    }
+
+    recorded_headers
+}
+
+/// Collects the chunks of a response body, and records the response once
+/// the body is complete
+#[derive(Debug)]
+struct CassetteRecorder {
+    cassette: Arc<Mutex<Cassette>>,
+    cassette_path: PathBuf,
+    url: String,
+    final_url: Option<String>,
+    redirects: Vec<Redirect>,
+    peer_certificates: Vec<Vec<u8>>,
+    status: StatusCode,
+    headers: HeaderMap,
+    chunks: Vec<Bytes>,
+}
+
+impl CassetteRecorder {
+    fn commit(self) -> io::Result<()> {
+        let mut cassette = self
+            .cassette
+            .lock()
+            .expect("the cassette lock isn't poisoned");
+        cassette.record(CassetteInteraction::new(
+            self.url,
+            self.final_url,
+            self.redirects,
+            self.peer_certificates,
+            self.status,
+            self.headers,
+            self.chunks,
+        ));
+
+        cassette.save(&self.cassette_path)
+    }
+}
+
+struct RecordingChunkStream<ChunkStream> {
+    chunk_stream: ChunkStream,
+    /// [None] after the body is recorded, or fails
+    recorder: Option<CassetteRecorder>,
+}
+
+impl<ChunkStream, Error> Stream for RecordingChunkStream<ChunkStream>
+where
+    ChunkStream: Stream<Item = Result<Bytes, Error>> + Unpin,
+{
+    type Item = Result<Bytes, RecordingGetUrlError<Error>>;
+
+    fn poll_next(self: Pin<&mut Self>, context: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let recording_chunk_stream = self.get_mut();
+
+        match recording_chunk_stream.chunk_stream.poll_next_unpin(context) {
+            Poll::Ready(Some(Ok(chunk))) => {
+                if let Some(recorder) = &mut recording_chunk_stream.recorder {
+                    recorder.chunks.push(chunk.clone());
+                }
+                Poll::Ready(Some(Ok(chunk)))
+            }
+            Poll::Ready(Some(Err(error))) => {
+                // An incomplete body isn't recorded:
+                recording_chunk_stream.recorder.take();
+                Poll::Ready(Some(Err(RecordingGetUrlError::Http(error))))
+            }
+            Poll::Ready(None) => match recording_chunk_stream
+                .recorder
+                .take()
+                .map(CassetteRecorder::commit)
+            {
+                Some(Err(error)) => Poll::Ready(Some(Err(RecordingGetUrlError::Io(error)))),
+                _ => Poll::Ready(None),
+            },
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+/// An error streaming a response body, or writing the cassette file
+#[derive(Debug)]
+pub enum RecordingGetUrlError<HttpError> {
+    Http(HttpError),
+    Io(io::Error),
+}
+
+impl<HttpError: fmt::Display> fmt::Display for RecordingGetUrlError<HttpError> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RecordingGetUrlError::Http(error) => error.fmt(f),
+            RecordingGetUrlError::Io(error) => {
+                write!(f, "could not write the cassette file: {}", error)
+            }
+        }
+    }
+}
+
+impl<HttpError: std::error::Error + 'static> std::error::Error for RecordingGetUrlError<HttpError> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            RecordingGetUrlError::Http(error) => Some(error),
+            RecordingGetUrlError::Io(error) => Some(error),
+        }
+    }
+}
@@ -0,0 +1,167 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// The number of redirects followed, unless another limit is configured
+pub const DEFAULT_MAX_REDIRECTS: u32 = 10;
+
+/// Which redirects are followed
+///
+/// It's deserialized from any name that [RedirectMode::from_str] recognizes.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(try_from = "String", into = "&'static str")]
+pub enum RedirectMode {
+    /// Redirects to any URL are followed
+    #[default]
+    Follow,
+    /// Only redirects to the origin of the requested URL are followed
+    SameOrigin,
+    /// No redirect is followed, and a redirect response is an error
+    Refuse,
+}
+
+impl RedirectMode {
+    /// Returns the name of the mode (e.g., `same-origin`)
+    pub fn name(self) -> &'static str {
+        match self {
+            RedirectMode::Follow => "follow",
+            RedirectMode::SameOrigin => "same-origin",
+            RedirectMode::Refuse => "refuse",
+        }
+    }
+}
+
+impl fmt::Display for RedirectMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for RedirectMode {
+    type Err = UnknownRedirectMode;
+
+    /// Parses a mode name ignoring case
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name.to_ascii_lowercase().as_str() {
+            "follow" => Ok(RedirectMode::Follow),
+            "same-origin" => Ok(RedirectMode::SameOrigin),
+            "refuse" => Ok(RedirectMode::Refuse),
+            _ => Err(UnknownRedirectMode(name.to_string())),
+        }
+    }
+}
+
+impl TryFrom<String> for RedirectMode {
+    type Error = UnknownRedirectMode;
+
+    fn try_from(name: String) -> Result<Self, Self::Error> {
+        name.parse()
+    }
+}
+
+impl From<RedirectMode> for &'static str {
+    fn from(redirect_mode: RedirectMode) -> Self {
+        redirect_mode.name()
+    }
+}
+
+#[derive(Debug)]
+pub struct UnknownRedirectMode(pub String);
+
+impl fmt::Display for UnknownRedirectMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "unknown redirect mode {} (expected follow, same-origin, or refuse)",
+            self.0
+        )
+    }
+}
+
+impl std::error::Error for UnknownRedirectMode {}
+
+/// Which redirects are followed, and how many
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct RedirectPolicy {
+    pub mode: RedirectMode,
+    /// The maximum number of redirects followed for one request
+    pub max_redirects: u32,
+}
+
+impl RedirectPolicy {
+    pub fn new(mode: RedirectMode, max_redirects: u32) -> Self {
+        RedirectPolicy {
+            mode,
+            max_redirects,
+        }
+    }
+
+    /// Checks whether a redirect from a requested URL to `location` is
+    /// followed after following `redirect_count` redirects
+    pub fn check_redirect(
+        &self,
+        requested_url: &url::Url,
+        location: &url::Url,
+        redirect_count: usize,
+    ) -> Result<(), RedirectPolicyError> {
+        match self.mode {
+            RedirectMode::Refuse => {
+                return Err(RedirectPolicyError::Refused(location.to_string()));
+            }
+            RedirectMode::SameOrigin if location.origin() != requested_url.origin() => {
+                return Err(RedirectPolicyError::CrossOrigin(location.to_string()));
+            }
+            _ => {}
+        }
+        if redirect_count >= self.max_redirects as usize {
+            return Err(RedirectPolicyError::TooManyRedirects(self.max_redirects));
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for RedirectPolicy {
+    fn default() -> Self {
+        RedirectPolicy::new(RedirectMode::default(), DEFAULT_MAX_REDIRECTS)
+    }
+}
+
+/// A redirect response followed before the final response
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Redirect {
+    /// The URL that was redirected
+    pub url: String,
+    /// The HTTP status code of the redirect response (e.g., `301`)
+    pub status: u16,
+}
+
+/// A redirect that a [RedirectPolicy] doesn't allow following
+#[derive(Debug)]
+pub enum RedirectPolicyError {
+    Refused(String),
+    CrossOrigin(String),
+    TooManyRedirects(u32),
+}
+
+impl fmt::Display for RedirectPolicyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RedirectPolicyError::Refused(location) => {
+                write!(f, "refused to follow a redirect to {}", location)
+            }
+            RedirectPolicyError::CrossOrigin(location) => {
+                write!(
+                    f,
+                    "refused to follow a redirect to another origin: {}",
+                    location
+                )
+            }
+            RedirectPolicyError::TooManyRedirects(max_redirects) => {
+                write!(f, "too many redirects (at most {} allowed)", max_redirects)
+            }
+        }
+    }
+}
+
+impl std::error::Error for RedirectPolicyError {}
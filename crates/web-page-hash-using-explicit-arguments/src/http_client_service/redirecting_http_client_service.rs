@@ -0,0 +1,242 @@
+use super::interface::{GetUrl, GetUrlWithHeaders, IntoChunkStream, ResponseMetadata};
+use super::redirect_policy::{Redirect, RedirectPolicy, RedirectPolicyError};
+use crate::credential_provider_service::{LookUpCredential, NoCredentialProvider};
+use bytes::Bytes;
+use futures_core::stream::Stream;
+use http::header::{AUTHORIZATION, LOCATION};
+use http::{HeaderMap, StatusCode};
+use std::fmt;
+
+/// A [GetUrl] decorator following redirects allowed by a [RedirectPolicy]
+///
+/// The decorated service is expected to return redirect responses as they
+/// are.  `301`, `302`, `303`, `307`, and `308` responses with a `Location`
+/// header are followed with the same request headers to the origin of the
+/// requested URL.  Requests to other origins are sent without any of them,
+/// since custom headers may hold credentials, e.g., API keys, and only with
+/// credentials looked up for their host name (see
+/// [LookUpCredential::look_up_redirect_credential]).  The final URL, and the
+/// redirects followed are returned with the response.
+#[derive(Debug)]
+pub struct RedirectingGetUrl<HttpClientService, CredentialProvider = NoCredentialProvider> {
+    http_client_service: HttpClientService,
+    redirect_policy: RedirectPolicy,
+    credential_provider: CredentialProvider,
+}
+
+impl<HttpClientService> RedirectingGetUrl<HttpClientService> {
+    pub fn new(http_client_service: HttpClientService, redirect_policy: RedirectPolicy) -> Self {
+        RedirectingGetUrl {
+            http_client_service,
+            redirect_policy,
+            credential_provider: NoCredentialProvider::new(),
+        }
+    }
+}
+
+impl<HttpClientService, CredentialProvider>
+    RedirectingGetUrl<HttpClientService, CredentialProvider>
+{
+    /// Sends credentials looked up by a credential provider to other origins
+    /// redirected to
+    pub fn with_credential_provider<OtherCredentialProvider>(
+        self,
+        credential_provider: OtherCredentialProvider,
+    ) -> RedirectingGetUrl<HttpClientService, OtherCredentialProvider> {
+        RedirectingGetUrl {
+            http_client_service: self.http_client_service,
+            redirect_policy: self.redirect_policy,
+            credential_provider,
+        }
+    }
+
+    /// Returns the request headers of a redirect to `location`
+    fn redirect_headers(
+        &self,
+        url: &url::Url,
+        headers: &HeaderMap,
+        location: &url::Url,
+    ) -> Result<HeaderMap, CredentialProvider::Error>
+    where
+        CredentialProvider: LookUpCredential,
+    {
+        if location.origin() == url.origin() {
+            return Ok(headers.clone());
+        }
+
+        let mut redirect_headers = HeaderMap::new();
+        if let Some(credential) = self
+            .credential_provider
+            .look_up_redirect_credential(location.as_str())?
+        {
+            redirect_headers.insert(AUTHORIZATION, credential.authorization().clone());
+        }
+
+        Ok(redirect_headers)
+    }
+}
+
+impl<HttpClientService, CredentialProvider> GetUrl
+    for RedirectingGetUrl<HttpClientService, CredentialProvider>
+where
+    HttpClientService: GetUrlWithHeaders<Url = url::Url> + Sync,
+    HttpClientService::HttpResponse: ResponseMetadata + Send,
+    CredentialProvider: LookUpCredential + Send + Sync,
+{
+    type Url = url::Url;
+    type HttpResponse = RedirectedHttpResponse<HttpClientService::HttpResponse>;
+    type Error = RedirectingGetUrlError<HttpClientService::Error, CredentialProvider::Error>;
+
+    async fn get_url(&self, url: &Self::Url) -> Result<Self::HttpResponse, Self::Error> {
+        self.get_url_with_headers(url, &HeaderMap::new()).await
+    }
+}
+
+impl<HttpClientService, CredentialProvider> GetUrlWithHeaders
+    for RedirectingGetUrl<HttpClientService, CredentialProvider>
+where
+    HttpClientService: GetUrlWithHeaders<Url = url::Url> + Sync,
+    HttpClientService::HttpResponse: ResponseMetadata + Send,
+    CredentialProvider: LookUpCredential + Send + Sync,
+{
+    async fn get_url_with_headers(
+        &self,
+        url: &Self::Url,
+        headers: &HeaderMap,
+    ) -> Result<Self::HttpResponse, Self::Error> {
+        let mut request_url = url.clone();
+        let mut request_headers = headers.clone();
+        let mut redirects = Vec::new();
+
+        loop {
+            let response = self
+                .http_client_service
+                .get_url_with_headers(&request_url, &request_headers)
+                .await
+                .map_err(RedirectingGetUrlError::Http)?;
+            let status = response.status();
+            let location = match status {
+                StatusCode::MOVED_PERMANENTLY
+                | StatusCode::FOUND
+                | StatusCode::SEE_OTHER
+                | StatusCode::TEMPORARY_REDIRECT
+                | StatusCode::PERMANENT_REDIRECT => response.headers().get(LOCATION),
+                _ => None,
+            };
+            let Some(location) = location else {
+                return Ok(RedirectedHttpResponse {
+                    response,
+                    final_url: request_url,
+                    redirects,
+                });
+            };
+
+            let location = location
+                .to_str()
+                .ok()
+                .and_then(|location| request_url.join(location).ok())
+                .ok_or_else(|| {
+                    RedirectingGetUrlError::InvalidLocation(
+                        String::from_utf8_lossy(location.as_bytes()).into_owned(),
+                    )
+                })?;
+            self.redirect_policy
+                .check_redirect(url, &location, redirects.len())
+                .map_err(RedirectingGetUrlError::Policy)?;
+            request_headers = self
+                .redirect_headers(url, headers, &location)
+                .map_err(RedirectingGetUrlError::Credential)?;
+
+            redirects.push(Redirect {
+                url: request_url.to_string(),
+                status: status.as_u16(),
+            });
+            request_url = location;
+        }
+    }
+}
+
+/// A response returned by [RedirectingGetUrl]
+#[derive(Debug)]
+pub struct RedirectedHttpResponse<HttpResponse> {
+    response: HttpResponse,
+    final_url: url::Url,
+    redirects: Vec<Redirect>,
+}
+
+impl<HttpResponse: ResponseMetadata> ResponseMetadata for RedirectedHttpResponse<HttpResponse> {
+    fn status(&self) -> StatusCode {
+        self.response.status()
+    }
+
+    fn headers(&self) -> &HeaderMap {
+        self.response.headers()
+    }
+
+    fn final_url(&self) -> Option<String> {
+        Some(self.final_url.to_string())
+    }
+
+    fn redirects(&self) -> Vec<Redirect> {
+        self.redirects.clone()
+    }
+
+    fn peer_certificates(&self) -> Vec<Vec<u8>> {
+        self.response.peer_certificates()
+    }
+
+    fn cache_key(&self) -> Option<&str> {
+        self.response.cache_key()
+    }
+}
+
+impl<HttpResponse: IntoChunkStream> IntoChunkStream for RedirectedHttpResponse<HttpResponse> {
+    type Error = HttpResponse::Error;
+
+    fn into_chunk_stream(self) -> impl Stream<Item = Result<Bytes, Self::Error>> + Unpin {
+        self.response.into_chunk_stream()
+    }
+}
+
+/// An error from a [RedirectingGetUrl] decorated service, or credential
+/// provider, or a redirect that isn't followed
+#[derive(Debug)]
+pub enum RedirectingGetUrlError<HttpError, CredentialError> {
+    Http(HttpError),
+    Credential(CredentialError),
+    Policy(RedirectPolicyError),
+    InvalidLocation(String),
+}
+
+impl<HttpError: fmt::Display, CredentialError: fmt::Display> fmt::Display
+    for RedirectingGetUrlError<HttpError, CredentialError>
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RedirectingGetUrlError::Http(error) => error.fmt(f),
+            RedirectingGetUrlError::Credential(error) => {
+                write!(f, "looking up credentials failed: {}", error)
+            }
+            RedirectingGetUrlError::Policy(error) => error.fmt(f),
+            RedirectingGetUrlError::InvalidLocation(location) => {
+                write!(f, "invalid redirect location {}", location)
+            }
+        }
+    }
+}
+
+impl<HttpError, CredentialError> std::error::Error
+    for RedirectingGetUrlError<HttpError, CredentialError>
+where
+    HttpError: std::error::Error + 'static,
+    CredentialError: std::error::Error + 'static,
+{
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            RedirectingGetUrlError::Http(error) => Some(error),
+            RedirectingGetUrlError::Credential(error) => Some(error),
+            // The policy error is already displayed:
+            RedirectingGetUrlError::Policy(_) | RedirectingGetUrlError::InvalidLocation(_) => None,
+        }
+    }
+}
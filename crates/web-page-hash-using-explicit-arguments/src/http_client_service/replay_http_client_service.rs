@@ -0,0 +1,176 @@
+use super::certificate_fingerprint::CertificateFingerprint;
+use super::interface::{GetUrl, GetUrlWithHeaders, IntoChunkStream, ResponseMetadata};
+use super::redirect_policy::Redirect;
+use crate::credential_provider_service::redact_url_credentials;
+use crate::http_cassette::{Cassette, CassetteInteraction};
+use bytes::Bytes;
+use futures_core::stream::Stream;
+use http::{HeaderMap, StatusCode};
+use std::convert::Infallible;
+use std::fmt;
+use std::io;
+use std::path::Path;
+
+/// An injectable HTTP client service serving responses recorded in a
+/// [Cassette] by [super::RecordingGetUrl], without sending requests
+///
+/// Response bodies are streamed in the chunks they were received in.
+/// Requesting a URL that wasn't recorded fails.  With pinned certificates,
+/// replaying a response fails, unless the server certificate recorded with
+/// it is pinned, or it was received without TLS.
+#[derive(Debug)]
+pub struct ReplayGetUrl {
+    cassette: Cassette,
+    pinned_certificates: Vec<CertificateFingerprint>,
+}
+
+impl ReplayGetUrl {
+    pub fn new(cassette: Cassette) -> Self {
+        ReplayGetUrl {
+            cassette,
+            pinned_certificates: Vec::new(),
+        }
+    }
+
+    pub fn from_file(cassette_path: &Path) -> io::Result<Self> {
+        Ok(ReplayGetUrl::new(Cassette::load(cassette_path)?))
+    }
+
+    /// Returns this service replaying only responses whose recorded server
+    /// certificates have pinned SHA-256 fingerprints
+    pub fn with_pinned_certificates(
+        self,
+        pinned_certificates: Vec<CertificateFingerprint>,
+    ) -> Self {
+        ReplayGetUrl {
+            pinned_certificates,
+            ..self
+        }
+    }
+
+    fn check_pinned_certificates(
+        &self,
+        url: &str,
+        interaction: &CassetteInteraction,
+    ) -> Result<(), ReplayGetUrlError> {
+        if self.pinned_certificates.is_empty() {
+            return Ok(());
+        }
+
+        let final_url = interaction.final_url().unwrap_or(url);
+        match interaction
+            .peer_certificates()
+            .first()
+            .map(|certificate| CertificateFingerprint::of(certificate))
+        {
+            Some(fingerprint) if self.pinned_certificates.contains(&fingerprint) => Ok(()),
+            Some(fingerprint) => Err(ReplayGetUrlError::UnpinnedCertificate(
+                url.to_string(),
+                fingerprint,
+            )),
+            None if final_url.starts_with("https:") => {
+                Err(ReplayGetUrlError::NoCertificate(url.to_string()))
+            }
+            None => Ok(()),
+        }
+    }
+}
+
+impl GetUrl for ReplayGetUrl {
+    type Url = url::Url;
+    type HttpResponse = ReplayHttpResponse;
+    type Error = ReplayGetUrlError;
+
+    async fn get_url(&self, url: &Self::Url) -> Result<Self::HttpResponse, Self::Error> {
+        self.get_url_with_headers(url, &HeaderMap::new()).await
+    }
+}
+
+impl GetUrlWithHeaders for ReplayGetUrl {
+    /// Request headers are ignored, since they aren't recorded
+    async fn get_url_with_headers(
+        &self,
+        url: &Self::Url,
+        _headers: &HeaderMap,
+    ) -> Result<Self::HttpResponse, Self::Error> {
+        let url = redact_url_credentials(url.as_str());
+        let interaction = self
+            .cassette
+            .interaction(&url)
+            .ok_or_else(|| ReplayGetUrlError::NotRecorded(url.to_string()))?;
+        self.check_pinned_certificates(&url, interaction)?;
+
+        Ok(ReplayHttpResponse {
+            interaction: interaction.clone(),
+        })
+    }
+}
+
+/// A response returned by [ReplayGetUrl]
+#[derive(Debug)]
+pub struct ReplayHttpResponse {
+    interaction: CassetteInteraction,
+}
+
+impl ResponseMetadata for ReplayHttpResponse {
+    fn status(&self) -> StatusCode {
+        self.interaction.status()
+    }
+
+    fn headers(&self) -> &HeaderMap {
+        self.interaction.headers()
+    }
+
+    fn final_url(&self) -> Option<String> {
+        self.interaction.final_url().map(str::to_string)
+    }
+
+    fn redirects(&self) -> Vec<Redirect> {
+        self.interaction.redirects().to_vec()
+    }
+
+    fn peer_certificates(&self) -> Vec<Vec<u8>> {
+        self.interaction.peer_certificates().to_vec()
+    }
+}
+
+impl IntoChunkStream for ReplayHttpResponse {
+    type Error = Infallible;
+
+    fn into_chunk_stream(self) -> impl Stream<Item = Result<Bytes, Self::Error>> + Unpin {
+        futures_util::stream::iter(self.interaction.chunks().to_vec().into_iter().map(Ok))
+    }
+}
+
+#[derive(Debug)]
+pub enum ReplayGetUrlError {
+    /// The cassette has no response for the URL
+    NotRecorded(String),
+    /// The server certificate recorded for the URL isn't pinned
+    UnpinnedCertificate(String, CertificateFingerprint),
+    /// No server certificate is recorded for the URL served over TLS, so
+    /// that pinned certificates can't be checked
+    NoCertificate(String),
+}
+
+impl fmt::Display for ReplayGetUrlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReplayGetUrlError::NotRecorded(url) => {
+                write!(f, "no response is recorded for {}", url)
+            }
+            ReplayGetUrlError::UnpinnedCertificate(url, fingerprint) => write!(
+                f,
+                "the server certificate with SHA-256 fingerprint {} recorded for {} isn't pinned",
+                fingerprint, url
+            ),
+            ReplayGetUrlError::NoCertificate(url) => write!(
+                f,
+                "no server certificate is recorded for {} to check pinned certificates against",
+                url
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ReplayGetUrlError {}
@@ -1,15 +1,62 @@
-use super::interface::{GetUrl, IntoChunkStream};
+use super::interface::{GetUrl, GetUrlWithHeaders, IntoChunkStream, ResponseMetadata};
+use super::pinned_certificate_verifier::pinned_certificate_tls_config;
+use crate::configuration_service::HttpClientConfiguration;
 use bytes::Bytes;
 use futures_core::stream::Stream;
+use http::{HeaderMap, StatusCode};
 use reqwest;
 
 /// An injectable HTTP client service implemented using the [reqwest] crate
+///
+/// Redirects aren't followed, so that a [super::RedirectingGetUrl] decorator
+/// can follow them according to a policy.  A redirect response is returned
+/// as is.  Only the server's own certificate is exposed on responses.
+/// Connections use the system's TLS implementation, unless certificates are
+/// pinned, in which case they use [rustls] trusting only the pinned
+/// certificates (see [pinned_certificate_tls_config]).
 #[derive(Debug, Default)]
-pub struct ReqwestHttpClientService {}
+pub struct ReqwestHttpClientService {
+    client: reqwest::Client,
+}
 
 impl ReqwestHttpClientService {
     pub fn new() -> Self {
-        ReqwestHttpClientService {}
+        ReqwestHttpClientService {
+            client: reqwest::Client::builder()
+                .redirect(reqwest::redirect::Policy::none())
+                .tls_info(true)
+                .build()
+                .expect("a client with default settings can be built"),
+        }
+    }
+
+    /// Creates a service with timeouts, a user agent, a proxy, and pinned
+    /// certificates read from a configuration service
+    pub fn from_configuration(
+        configuration: &impl HttpClientConfiguration,
+    ) -> Result<Self, reqwest::Error> {
+        let mut client_builder = reqwest::Client::builder()
+            .redirect(reqwest::redirect::Policy::none())
+            .tls_info(true)
+            .user_agent(configuration.user_agent());
+        if let Some(connect_timeout) = configuration.connect_timeout() {
+            client_builder = client_builder.connect_timeout(connect_timeout);
+        }
+        if let Some(timeout) = configuration.timeout() {
+            client_builder = client_builder.timeout(timeout);
+        }
+        if let Some(proxy) = configuration.proxy() {
+            client_builder = client_builder.proxy(reqwest::Proxy::all(proxy)?);
+        }
+        if !configuration.pinned_certificates().is_empty() {
+            client_builder = client_builder.use_preconfigured_tls(pinned_certificate_tls_config(
+                configuration.pinned_certificates(),
+            ));
+        }
+
+        Ok(ReqwestHttpClientService {
+            client: client_builder.build()?,
+        })
     }
 }
 
@@ -19,7 +66,21 @@ impl GetUrl for ReqwestHttpClientService {
     type Error = reqwest::Error;
 
     async fn get_url(&self, url: &Self::Url) -> Result<Self::HttpResponse, Self::Error> {
-        reqwest::get(url.clone()).await
+        self.client.get(url.clone()).send().await
+    }
+}
+
+impl GetUrlWithHeaders for ReqwestHttpClientService {
+    async fn get_url_with_headers(
+        &self,
+        url: &Self::Url,
+        headers: &HeaderMap,
+    ) -> Result<Self::HttpResponse, Self::Error> {
+        self.client
+            .get(url.clone())
+            .headers(headers.clone())
+            .send()
+            .await
     }
 }
 
@@ -30,3 +91,25 @@ impl IntoChunkStream for reqwest::Response {
         reqwest::Response::bytes_stream(self)
     }
 }
+
+impl ResponseMetadata for reqwest::Response {
+    fn status(&self) -> StatusCode {
+        reqwest::Response::status(self)
+    }
+
+    fn headers(&self) -> &HeaderMap {
+        reqwest::Response::headers(self)
+    }
+
+    fn final_url(&self) -> Option<String> {
+        Some(reqwest::Response::url(self).to_string())
+    }
+
+    fn peer_certificates(&self) -> Vec<Vec<u8>> {
+        self.extensions()
+            .get::<reqwest::tls::TlsInfo>()
+            .and_then(reqwest::tls::TlsInfo::peer_certificate)
+            .map(|certificate| vec![certificate.to_vec()])
+            .unwrap_or_default()
+    }
+}
@@ -0,0 +1,141 @@
+use super::interface::{GetUrl, GetUrlWithHeaders, IntoChunkStream, ResponseMetadata};
+use crate::configuration_service::HttpClientConfiguration;
+use bytes::Bytes;
+use futures_core::stream::Stream;
+use http::{HeaderMap, StatusCode};
+use std::io::{self, Read};
+use ureq::ResponseExt;
+
+const CHUNK_SIZE: usize = 64 * 1024;
+const CHUNK_CHANNEL_CAPACITY: usize = 4;
+
+/// An injectable HTTP client service implemented using the blocking [ureq]
+/// crate
+///
+/// Blocking calls are made on threads dedicated to blocking operations with
+/// [tokio::task::spawn_blocking].  Redirects aren't followed, so that a
+/// [super::RedirectingGetUrl] decorator can follow them according to a
+/// policy.  A redirect response is returned as is.
+#[derive(Debug)]
+pub struct UreqGetUrlService {
+    agent: ureq::Agent,
+}
+
+impl UreqGetUrlService {
+    pub fn new() -> Self {
+        let config = ureq::Agent::config_builder()
+            // Error statuses are returned as responses, like other services
+            // do:
+            .http_status_as_error(false)
+            .max_redirects(0)
+            .build();
+
+        UreqGetUrlService {
+            agent: ureq::Agent::new_with_config(config),
+        }
+    }
+
+    /// Creates a service with timeouts, a user agent, and a proxy read from
+    /// a configuration service
+    pub fn from_configuration(
+        configuration: &impl HttpClientConfiguration,
+    ) -> Result<Self, ureq::Error> {
+        let proxy = configuration.proxy().map(ureq::Proxy::new).transpose()?;
+        let config = ureq::Agent::config_builder()
+            .http_status_as_error(false)
+            .max_redirects(0)
+            .timeout_connect(configuration.connect_timeout())
+            .timeout_global(configuration.timeout())
+            .user_agent(configuration.user_agent())
+            .proxy(proxy)
+            .build();
+
+        Ok(UreqGetUrlService {
+            agent: ureq::Agent::new_with_config(config),
+        })
+    }
+}
+
+impl Default for UreqGetUrlService {
+    fn default() -> Self {
+        UreqGetUrlService::new()
+    }
+}
+
+impl GetUrl for UreqGetUrlService {
+    type Url = url::Url;
+    type HttpResponse = http::Response<ureq::Body>;
+    type Error = ureq::Error;
+
+    async fn get_url(&self, url: &Self::Url) -> Result<Self::HttpResponse, Self::Error> {
+        self.get_url_with_headers(url, &HeaderMap::new()).await
+    }
+}
+
+impl GetUrlWithHeaders for UreqGetUrlService {
+    async fn get_url_with_headers(
+        &self,
+        url: &Self::Url,
+        headers: &HeaderMap,
+    ) -> Result<Self::HttpResponse, Self::Error> {
+        let agent = self.agent.clone();
+        let url = url.to_string();
+        let headers = headers.clone();
+
+        tokio::task::spawn_blocking(move || {
+            let mut request = agent.get(&url);
+            for (name, value) in &headers {
+                request = request.header(name, value);
+            }
+
+            request.call()
+        })
+        .await
+        .map_err(|join_error| ureq::Error::Io(io::Error::other(join_error)))?
+    }
+}
+
+impl IntoChunkStream for http::Response<ureq::Body> {
+    type Error = io::Error;
+
+    fn into_chunk_stream(self) -> impl Stream<Item = Result<Bytes, Self::Error>> + Unpin {
+        let (sender, mut receiver) = tokio::sync::mpsc::channel(CHUNK_CHANNEL_CAPACITY);
+        let mut body_reader = self.into_body().into_reader();
+
+        tokio::task::spawn_blocking(move || {
+            loop {
+                let mut chunk = vec![0; CHUNK_SIZE];
+                let chunk_result = match body_reader.read(&mut chunk) {
+                    Ok(0) => break,
+                    Ok(length) => {
+                        chunk.truncate(length);
+                        Ok(Bytes::from(chunk))
+                    }
+                    Err(error) => Err(error),
+                };
+                let is_error = chunk_result.is_err();
+
+                // Stop reading if the stream was dropped:
+                if sender.blocking_send(chunk_result).is_err() || is_error {
+                    break;
+                }
+            }
+        });
+
+        futures_util::stream::poll_fn(move |context| receiver.poll_recv(context))
+    }
+}
+
+impl ResponseMetadata for http::Response<ureq::Body> {
+    fn status(&self) -> StatusCode {
+        http::Response::status(self)
+    }
+
+    fn headers(&self) -> &HeaderMap {
+        http::Response::headers(self)
+    }
+
+    fn final_url(&self) -> Option<String> {
+        Some(self.get_uri().to_string())
+    }
+}
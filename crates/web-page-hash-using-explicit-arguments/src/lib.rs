@@ -1,3 +1,24 @@
+pub mod body_sink_service;
 pub mod calculate_web_page_message_digest_service;
+pub mod change_notifier_service;
+pub mod checksum_check_service;
+pub mod clock_service;
+pub mod configuration_service;
+pub mod content_decoder_service;
+pub mod content_transformer_service;
+pub mod crawl_service;
+pub mod credential_provider_service;
+pub mod digest_encoding;
+pub mod digest_memo_service;
+pub mod digest_record;
+pub mod digest_signer_service;
+pub mod http_cache;
+pub mod http_cassette;
 pub mod http_client_service;
+pub mod link_extractor_service;
 pub mod message_digest_service;
+pub mod progress_reporter_service;
+pub mod response_policy_service;
+pub mod subresource_integrity;
+pub mod tracing_decorator;
+pub mod watch_service;
@@ -0,0 +1,94 @@
+use super::interface::{ExtractLinks, LinkExtractor};
+use scraper::{Html, Selector};
+
+/// Elements linking to other Web pages, and their URL attributes
+const LINK_ATTRIBUTES: &[(&str, &str)] = &[
+    ("a", "href"),
+    ("area", "href"),
+    ("frame", "src"),
+    ("iframe", "src"),
+];
+
+/// A [LinkExtractor] parsing HTML links to Web pages, i.e., `a`, and `area`
+/// elements, and frames
+///
+/// A `base` element overrides the base URL.  Fragments are removed, and
+/// links to other schemes than `http`, and `https` are skipped.  Bodies of
+/// other media types than `text/html`, and `application/xhtml+xml` have no
+/// links, while bodies without a `Content-Type` are treated as HTML.
+#[derive(Debug)]
+pub struct HtmlLinkExtractor {
+    base_selector: Selector,
+    link_selectors: Vec<(Selector, &'static str)>,
+}
+
+impl HtmlLinkExtractor {
+    pub fn new() -> Self {
+        HtmlLinkExtractor {
+            base_selector: Selector::parse("base[href]").expect("the selector is valid"),
+            link_selectors: LINK_ATTRIBUTES
+                .iter()
+                .map(|&(element, attribute)| {
+                    let selector = Selector::parse(&format!("{}[{}]", element, attribute))
+                        .expect("the selector is valid");
+
+                    (selector, attribute)
+                })
+                .collect(),
+        }
+    }
+}
+
+impl Default for HtmlLinkExtractor {
+    fn default() -> Self {
+        HtmlLinkExtractor::new()
+    }
+}
+
+impl ExtractLinks for HtmlLinkExtractor {
+    fn extract_links(
+        &self,
+        base_url: &url::Url,
+        content_type: Option<&str>,
+        body: &[u8],
+    ) -> Vec<url::Url> {
+        let is_html = content_type.is_none_or(|content_type| {
+            let media_type = content_type.split(';').next().unwrap_or_default().trim();
+
+            media_type.eq_ignore_ascii_case("text/html")
+                || media_type.eq_ignore_ascii_case("application/xhtml+xml")
+        });
+        if !is_html {
+            return Vec::new();
+        }
+
+        let document = Html::parse_document(&String::from_utf8_lossy(body));
+        let base_url = document
+            .select(&self.base_selector)
+            .next()
+            .and_then(|base| base.value().attr("href"))
+            .and_then(|href| base_url.join(href.trim()).ok())
+            .unwrap_or_else(|| base_url.clone());
+        // Elements are visited once in document order, rather than once per
+        // selector, so that links stay in document order:
+        let mut links = document
+            .root_element()
+            .descendent_elements()
+            .filter_map(|element| {
+                self.link_selectors
+                    .iter()
+                    .find(|(selector, _)| selector.matches(&element))
+                    .and_then(|(_, attribute)| element.value().attr(attribute))
+            })
+            .filter_map(|link| base_url.join(link.trim()).ok())
+            .filter(|url| matches!(url.scheme(), "http" | "https"))
+            .collect::<Vec<_>>();
+        for link in &mut links {
+            link.set_fragment(None);
+        }
+
+        links
+    }
+}
+
+impl LinkExtractor for HtmlLinkExtractor {}
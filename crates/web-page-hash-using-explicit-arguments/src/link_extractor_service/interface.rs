@@ -0,0 +1,18 @@
+/// A function trait extracting the links of a response body
+pub trait ExtractLinks {
+    /// Returns the absolute URLs linked from a body, in document order
+    ///
+    /// Relative links are resolved against `base_url`, which is the URL the
+    /// body was received from, unless the body declares another base URL.
+    fn extract_links(
+        &self,
+        base_url: &url::Url,
+        content_type: Option<&str>,
+        body: &[u8],
+    ) -> Vec<url::Url>;
+}
+
+// The full service is composed of its method, and function traits:
+/// An injectable service finding the Web pages linked from a Web page, e.g.,
+/// for crawling
+pub trait LinkExtractor: ExtractLinks {}
@@ -0,0 +1,5 @@
+pub mod interface;
+pub use interface::*;
+
+pub mod html_link_extractor;
+pub use html_link_extractor::*;
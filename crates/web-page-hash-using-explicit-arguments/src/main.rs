@@ -1,56 +1,1948 @@
-use clap::Parser;
+use anyhow::Context;
+use clap::{Args as ClapArgs, Parser, Subcommand, ValueEnum};
+use http::{HeaderMap, HeaderName, HeaderValue};
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::time::Duration;
+use tracing_subscriber::EnvFilter;
 use web_page_hash_using_explicit_arguments::{
+    body_sink_service::{
+        BodyDestination, FileBodySink, MemoryBodySink, NoBodySink, SelectableBodySink,
+    },
     calculate_web_page_message_digest_service::{
-        CalculateWebPageMessageDigest, CalculateWebPageMessageDigestService,
+        CalculateWebPageMessageDigestService, CalculateWebPageMessageDigestWithMetadata,
+        WebPageMessageDigest,
+    },
+    change_notifier_service::SelectableChangeNotifier,
+    checksum_check_service::ChecksumFileChecker,
+    clock_service::{Now, TokioClock},
+    configuration_service::{
+        ConfigurationLayer, ContentDecoderConfiguration, HttpClientConfiguration,
+        LayeredConfigurationService, MessageDigestConfiguration,
+    },
+    content_decoder_service::{BodyEncoding, SelectableContentDecoder},
+    content_transformer_service::{ChainedContentTransformer, ContentNormalization},
+    crawl_service::{CalculatedPage, CrawlScope, RobotsTxt, WebSiteCrawler},
+    credential_provider_service::{
+        BasicAuthCredentialProvider, BearerTokenCredentialProvider, CredentialError,
+        EnvironmentCredentialProvider, NetrcCredentialProvider, NoCredentialProvider,
+        SelectableCredentialProvider,
+    },
+    digest_encoding::{ChecksumLine, ExpectedDigest},
+    digest_memo_service::{HttpCacheDigestMemoService, NoDigestMemoService},
+    digest_record::{OutputFormat, WebPageDigestRecord},
+    digest_signer_service::{
+        DigestStatement, SelectableDigestSigner, SelectableDigestVerifier, SignDigestStatement,
+        SignatureFileError, SignedDigest, VerifyDigestSignature, write_signature_file,
+    },
+    http_cache::{DEFAULT_HTTP_CACHE_MAX_SIZE, HttpCache},
+    http_client_service::{
+        AuthenticatingGetUrl, CachingGetUrl, CertificateFingerprint, GetUrlWithHeaders,
+        HttpBackend, IntoChunkStream, RecordingGetUrl, RedirectMode, RedirectingGetUrl,
+        ReplayGetUrl, ReqwestHttpClientService, ResponseMetadata,
     },
-    http_client_service::ReqwestHttpClientService,
-    message_digest_service::Sha3_256BitMessageDigestService,
+    link_extractor_service::HtmlLinkExtractor,
+    message_digest_service::{
+        DigestAlgorithmName, FanOutDigest, FanOutMessageDigestService, IntoDigestOctets,
+        MerkleMessageDigestService, MerkleProof, MessageDigestAlgorithm, RestoreDigestCalculator,
+        SaveDigestState, SelectableMessageDigestService,
+    },
+    progress_reporter_service::{
+        IndicatifProgressReporter, NoProgressReporter, ReportBytesWritten, ReportFinished,
+        ReportStarted,
+    },
+    response_policy_service::ConfiguredResponsePolicy,
+    subresource_integrity::{
+        CrossOrigin, HtmlSubresources, SriOutput, calculate_integrity_records, check_sri_algorithms,
+    },
+    tracing_decorator::Traced,
+    watch_service::{WatchState, WebPageWatcher},
 };
 
+#[cfg(feature = "curl")]
+use web_page_hash_using_explicit_arguments::http_client_service::CurlGetUrlService;
+#[cfg(feature = "hyper")]
+use web_page_hash_using_explicit_arguments::http_client_service::HyperGetUrlService;
+#[cfg(feature = "ureq")]
+use web_page_hash_using_explicit_arguments::http_client_service::UreqGetUrlService;
+
+/// Exit status when the digest doesn't match the expected digest
+const MISMATCH_EXIT_CODE: u8 = 1;
+/// Exit status when the digest couldn't be calculated
+const ERROR_EXIT_CODE: u8 = 2;
+/// Traces emitted with `--log-format`, unless `RUST_LOG` is set
+const DEFAULT_LOG_FILTER: &str = "web_page_hash_using_explicit_arguments=info";
+
 /// Prints the 256-bit SHA-3 message digest of a Web page
 ///
 /// This is a tiny demo app using a design option for Depedency Injection in
 /// Rust.
+///
+/// Exits with status 1 when the digest doesn't match `--expect`, and with
+/// status 2 on other errors.
 #[derive(Debug, Parser)]
-#[command(version)]
+#[command(
+    version,
+    args_conflicts_with_subcommands = true,
+    subcommand_negates_reqs = true
+)]
 struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    #[arg(long, required_unless_present_any = ["check", "verify_signature"])]
+    url: Option<String>,
+
+    /// Also check that the digest matches this digest
+    ///
+    /// Hexadecimal digits, base64, `<algorithm>:<digest>`, and Subresource
+    /// Integrity strings (e.g., `sha384-<base64>`) are accepted.
+    #[arg(long)]
+    expect: Option<String>,
+
+    /// Also check that the Web page is served from this URL after following
+    /// redirects, e.g., that a mirror doesn't redirect elsewhere
+    ///
+    /// Exits with status 1 when the final URL differs.  Digests of Web pages
+    /// served from other URLs aren't signed.
+    #[arg(
+        long,
+        value_name = "URL",
+        conflicts_with_all = ["check", "verify_signature"]
+    )]
+    require_final_url: Option<String>,
+
+    /// Sign the digest with this key, writing a detached signature over a
+    /// statement of the URL, algorithm, digest, and time into `--signature`
+    ///
+    /// Digests not matching `--expect` aren't signed.
+    /// Ed25519 keys in PKCS #8 PEM files (`openssl genpkey -algorithm
+    /// ed25519`), and unencrypted minisign secret keys (`minisign -G -W`)
+    /// are accepted.  A single algorithm is required.
+    #[arg(
+        long,
+        value_name = "KEY",
+        requires = "signature",
+        conflicts_with_all = ["check", "merkle_leaf_size"]
+    )]
+    sign_key: Option<PathBuf>,
+
+    /// The file the detached signature is written into with `--sign-key`
+    #[arg(long, value_name = "FILE", requires = "sign_key")]
+    signature: Option<PathBuf>,
+
+    /// Check a detached signature written with `--sign-key` against
+    /// `--public-key`, and check that the Web page still has the signed
+    /// digest
+    ///
+    /// The URL, and the algorithm are taken from the signed statement, and a
+    /// given `--url` must match it.  Exits with status 1 when the digest
+    /// doesn't match, and with status 2 when the signature is invalid.
+    #[arg(
+        long,
+        value_name = "FILE",
+        requires = "public_key",
+        conflicts_with_all = ["check", "expect", "sign_key", "merkle_leaf_size"]
+    )]
+    verify_signature: Option<PathBuf>,
+
+    /// The key checking `--verify-signature`: an Ed25519 public key in a
+    /// PEM file (`openssl pkey -pubout`), or a minisign public key
+    #[arg(long, value_name = "KEY", requires = "verify_signature")]
+    public_key: Option<PathBuf>,
+
+    /// How the digest is printed: `text`, `gnu` checksum lines
+    /// (`<hex>  <url>`), `bsd` checksum lines (`SHA3-256 (<url>) = <hex>`), a
+    /// `json` record, or an array of records with `--check`, or several
+    /// algorithms, or one JSON record per line (`ndjson`)
+    #[arg(long, value_name = "FORMAT", default_value_t = OutputFormat::Text)]
+    output: OutputFormat,
+
+    /// Read checksum lines from FILE (`-` for standard input), and check the
+    /// digest of each listed Web page
+    ///
+    /// Both GNU (`<hex>  <url>`), and BSD-tag (`SHA3-256 (<url>) = <hex>`)
+    /// lines are accepted.  GNU lines are checked with `--algorithm`.  Exits
+    /// with status 1 when any digest doesn't match, or any Web page can't be
+    /// fetched.  With `--output json`, or `ndjson` a record is printed for
+    /// each line instead of OK, or FAILED.
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["url", "expect", "output_file"])]
+    check: Option<PathBuf>,
+
+    /// Don't print OK for each successfully checked Web page
+    #[arg(long, requires = "check")]
+    quiet: bool,
+
+    /// Don't print anything, the exit status shows success
+    #[arg(long, requires = "check")]
+    status: bool,
+
+    /// Emit traces of fetching, streaming, and hashing Web pages on standard
+    /// error in this format
+    ///
+    /// Traces are filtered by the `RUST_LOG` environment variable, with
+    /// `tracing-subscriber` `EnvFilter` syntax (e.g., `debug`), when it's set.
+    #[arg(long, global = true, value_enum)]
+    log_format: Option<LogFormat>,
+
+    #[command(flatten)]
+    service_args: ServiceArgs,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Checks that the message digest of a Web page matches an expected
+    /// digest
+    ///
+    /// Exits with status 1 on a mismatch, and with status 2 on other errors.
+    Verify(VerifyArgs),
+    /// Polls Web pages on an interval, and reports changes of their digests
+    ///
+    /// The last digest of each Web page is kept in a state file, so that
+    /// changes are also detected across runs.  Unchanged Web pages are
+    /// revalidated with conditional requests through the response cache,
+    /// when it's enabled with `--cache`.  Changes are printed on standard
+    /// output, unless `--json-log`, or `--webhook` is given.
+    Watch(WatchArgs),
+    /// Checks a byte range of a file against the root digest of a Merkle
+    /// tree, without hashing the whole file
+    ///
+    /// The leaf digests of a proof file written with `--merkle-proof` are
+    /// checked against the root, and only the leaves covering the byte range
+    /// are read, and hashed.  Exits with status 1 on a mismatch, and with
+    /// status 2 on other errors.
+    VerifyChunk(VerifyChunkArgs),
+    /// Hashes every Web page reachable by links from a Web page, and prints
+    /// a manifest of their digests
+    ///
+    /// HTML links are followed within the origin of the URL, or `--prefix`,
+    /// unless `robots.txt` disallows them.  The manifest lists each Web page
+    /// in the order it was reached.  Pages that can't be hashed are reported
+    /// on standard error, and make the exit status 2.
+    Crawl(CrawlArgs),
+    /// Prints Subresource Integrity metadata of the scripts, and
+    /// stylesheets of a Web page, or the Web page with `integrity`
+    /// attributes
+    ///
+    /// `script` elements with a `src` attribute, and `link` elements to
+    /// stylesheets are hashed with `--algorithm` [default: sha384], where
+    /// only sha256, sha384, and sha512 are allowed.  Subresources are hashed
+    /// decoded from their content encodings, without normalizations, as
+    /// browsers check them.  Only subresources of the origin of the Web page
+    /// are requested with credentials, `--header` headers, and pinned
+    /// certificates.  Subresources that can't be hashed are reported on
+    /// standard error, are left unchanged in the Web page, and make the exit
+    /// status 2.
+    Sri(SriArgs),
+}
+
+#[derive(Debug, ClapArgs)]
+struct VerifyArgs {
+    #[arg(long)]
+    url: String,
+
+    /// The expected digest
+    ///
+    /// Hexadecimal digits, base64, `<algorithm>:<digest>`, and Subresource
+    /// Integrity strings (e.g., `sha384-<base64>`) are accepted.
+    #[arg(long)]
+    expect: String,
+}
+
+#[derive(Debug, ClapArgs)]
+struct VerifyChunkArgs {
+    /// The proof file written with `--merkle-proof`
+    #[arg(long, value_name = "FILE")]
+    proof: PathBuf,
+
+    /// The hexadecimal root digest, which the proof is checked against
+    #[arg(long, value_name = "DIGEST")]
+    root: String,
+
+    /// The file holding the body, or a copy of it at the same offsets
+    #[arg(long, value_name = "FILE")]
+    file: PathBuf,
+
+    /// The offset of the byte range in bytes
+    #[arg(long, default_value_t = 0)]
+    offset: u64,
+
+    /// The length of the byte range in bytes
+    #[arg(long, value_parser = clap::value_parser!(u64).range(1..))]
+    length: u64,
+}
+
+#[derive(Debug, ClapArgs)]
+struct CrawlArgs {
+    /// The Web page the crawl starts at
+    url: String,
+
+    /// Only follow links starting with this URL prefix [default: links to
+    /// the origin of the URL]
     #[arg(long)]
+    prefix: Option<String>,
+
+    /// The maximum number of links followed from the first Web page
+    #[arg(long, default_value_t = 5)]
+    max_depth: u32,
+
+    /// Stop following links after reaching this number of Web pages
+    #[arg(long, default_value_t = 1000, value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..))]
+    max_pages: usize,
+
+    /// The number of Web pages fetched at once
+    #[arg(long, default_value_t = 4, value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..))]
+    concurrency: usize,
+
+    /// How the manifest is printed: `text`, `gnu`, `bsd`, `json`, or
+    /// `ndjson`, as with `--output` without a subcommand
+    #[arg(long, value_name = "FORMAT", default_value_t = OutputFormat::Gnu)]
+    output: OutputFormat,
+}
+
+#[derive(Debug, ClapArgs)]
+struct SriArgs {
+    /// The Web page loading the scripts, and stylesheets
     url: String,
+
+    /// How the subresources are printed: `text`, `json`, or `html` (the Web
+    /// page with `integrity`, and `crossorigin` attributes)
+    #[arg(long, value_name = "FORMAT", default_value_t = SriOutput::Text)]
+    output: SriOutput,
+
+    /// The `crossorigin` attribute value added to elements without one with
+    /// `--output html`: `anonymous`, or `use-credentials`
+    #[arg(long, value_name = "VALUE", default_value_t = CrossOrigin::Anonymous)]
+    crossorigin: CrossOrigin,
+}
+
+#[derive(Debug, ClapArgs)]
+struct WatchArgs {
+    /// A Web page to watch
+    ///
+    /// Can be given more than once.
+    #[arg(long = "url", value_name = "URL", required = true)]
+    urls: Vec<String>,
+
+    /// The time between polls in seconds
+    #[arg(long, default_value_t = 300, value_parser = clap::value_parser!(u64).range(1..))]
+    interval: u64,
+
+    /// Stop after this number of polls, instead of watching until
+    /// interrupted
+    #[arg(long, value_name = "COUNT")]
+    polls: Option<u64>,
+
+    /// The file keeping the last digests [default:
+    /// $XDG_STATE_HOME/web-page-hash/watch.json]
+    #[arg(long, value_name = "FILE")]
+    state_file: Option<PathBuf>,
+
+    /// Append a line of JSON for each change to this file (`-` for standard
+    /// output)
+    #[arg(long, value_name = "FILE", conflicts_with = "webhook")]
+    json_log: Option<PathBuf>,
+
+    /// Post each change as JSON to this URL
+    #[arg(long, value_name = "URL")]
+    webhook: Option<url::Url>,
+}
+
+/// Arguments binding the services, shared by all commands
+///
+/// Settings also read from the configuration file, and `WEB_PAGE_HASH_*`
+/// environment variables override them.
+#[derive(Clone, Debug, ClapArgs)]
+struct ServiceArgs {
+    /// The message digest algorithm, or a comma-separated list of
+    /// algorithms all calculated from one download (e.g.,
+    /// `sha3-256,sha256,blake3`) [default: the algorithm named by the
+    /// expected digest, or the configured algorithm, or sha3-256]
+    ///
+    /// A single algorithm is required with `--expect`, `--check`,
+    /// `--sign-key`, and `verify`.
+    #[arg(
+        long = "algorithm",
+        global = true,
+        value_name = "ALGORITHM",
+        value_delimiter = ','
+    )]
+    algorithms: Vec<MessageDigestAlgorithm>,
+
+    /// A TOML configuration file [default: $WEB_PAGE_HASH_CONFIG, or
+    /// $XDG_CONFIG_HOME/web-page-hash/config.toml]
+    #[arg(long, global = true)]
+    config: Option<PathBuf>,
+
+    /// The maximum time for establishing a connection in seconds, or 0 for
+    /// no limit
+    #[arg(long, global = true)]
+    connect_timeout: Option<u64>,
+
+    /// The maximum time for a request in seconds, or 0 for no limit
+    #[arg(long, global = true)]
+    timeout: Option<u64>,
+
+    /// The `User-Agent` header value sent with requests
+    #[arg(long, global = true)]
+    user_agent: Option<String>,
+
+    /// The URL of a proxy for all requests
+    #[arg(long, global = true)]
+    proxy: Option<String>,
+
+    /// Follow redirects to any URL (`follow`), only to the origin of the
+    /// requested URL (`same-origin`), or fail on redirects (`refuse`)
+    /// [default: follow]
+    #[arg(long, global = true, value_name = "MODE")]
+    redirects: Option<RedirectMode>,
+
+    /// Fail after following this number of redirects [default: 10]
+    #[arg(long, global = true, value_name = "COUNT")]
+    max_redirects: Option<u32>,
+
+    /// Only trust server certificates with this SHA-256 fingerprint, e.g., a
+    /// self-signed certificate, failing before a request is sent to a server
+    /// with another certificate
+    ///
+    /// Hexadecimal digits, optionally separated by colons, as printed by
+    /// `openssl x509 -noout -fingerprint -sha256`, are accepted.  Can be
+    /// given more than once.  Supported by the reqwest, and hyper HTTP
+    /// backends.  Replayed responses are checked against the certificates
+    /// recorded with them.
+    #[arg(long = "pin-cert-sha256", global = true, value_name = "FINGERPRINT")]
+    pinned_certificates: Vec<CertificateFingerprint>,
+
+    /// Fail instead of hashing response bodies larger than this size in
+    /// bytes
+    #[arg(long, global = true)]
+    max_body_size: Option<u64>,
+
+    /// Hash bodies of `4xx`, and `5xx` responses instead of failing
+    #[arg(long, global = true, overrides_with = "no_allow_error_status")]
+    allow_error_status: bool,
+
+    /// Fail on `4xx`, and `5xx` responses, even when the configuration
+    /// file, or environment variables allow them
+    #[arg(long, global = true, overrides_with = "allow_error_status")]
+    no_allow_error_status: bool,
+
+    /// Fail instead of hashing responses of other media types, e.g.,
+    /// `text/html`, or `image/*`
+    ///
+    /// Can be given more than once.
+    #[arg(long = "allow-content-type", global = true, value_name = "MEDIA_TYPE")]
+    allowed_content_types: Vec<String>,
+
+    /// Fail instead of hashing responses of this media type
+    ///
+    /// Can be given more than once.
+    #[arg(long = "deny-content-type", global = true, value_name = "MEDIA_TYPE")]
+    denied_content_types: Vec<String>,
+
+    /// The HTTP client library used for fetching the Web page: `reqwest`,
+    /// or, when built with the respective cargo features, `hyper`, `ureq`,
+    /// or `curl`
+    #[arg(long, global = true, value_name = "BACKEND", default_value_t = HttpBackend::Reqwest)]
+    http_backend: HttpBackend,
+
+    /// Record responses into this cassette file, e.g., a test fixture,
+    /// keeping responses recorded earlier for other URLs
+    ///
+    /// Responses are always fetched, so that cached responses aren't
+    /// recorded as `304 Not Modified`.
+    #[arg(
+        long,
+        global = true,
+        value_name = "CASSETTE",
+        conflicts_with_all = ["replay", "cache", "cache_dir"]
+    )]
+    record: Option<PathBuf>,
+
+    /// Serve responses recorded with `--record` from this cassette file
+    /// instead of sending requests
+    #[arg(
+        long,
+        global = true,
+        value_name = "CASSETTE",
+        conflicts_with_all = ["cache", "cache_dir"]
+    )]
+    replay: Option<PathBuf>,
+
+    /// Also save the response body to this file, once its digest is
+    /// calculated, and matches the expected digest, if one was given
+    ///
+    /// The body is written to `<FILE>.partial` first, and renamed.  A body
+    /// with a strong validator is checkpointed in `<FILE>.checkpoint` every
+    /// MiB, so that an interrupted download resumes with an HTTP `Range`
+    /// request when run again.
+    #[arg(
+        long,
+        global = true,
+        value_name = "FILE",
+        conflicts_with = "output_dir"
+    )]
+    output_file: Option<PathBuf>,
+
+    /// Also save response bodies in this directory, named after the last
+    /// segment of the URL path, or `index.html`, as `--output-file` does
+    #[arg(long, global = true, value_name = "DIRECTORY")]
+    output_dir: Option<PathBuf>,
+
+    /// Hash response bodies as they're transferred (`raw`), or decoded from
+    /// their `gzip`, `deflate`, `br`, or `zstd` content encodings (`decoded`)
+    ///
+    /// Content encodings are only applied as servers choose to, e.g., when
+    /// requested with `--header 'Accept-Encoding: gzip'`.  Decoded bodies
+    /// aren't resumed, and their digests aren't cached.
+    #[arg(long, global = true, value_name = "ENCODING")]
+    encoding: Option<BodyEncoding>,
+
+    /// Normalize response bodies before they're hashed, so that digests of
+    /// dynamic Web pages change only when their content does, e.g.,
+    /// `html,whitespace`
+    ///
+    /// `html` drops comments, and scripts from HTML, and `whitespace`
+    /// collapses whitespace, and normalizes line endings.  Masks configured
+    /// as `[[mask]]` tables are applied in between.
+    #[arg(
+        long = "normalize",
+        global = true,
+        value_name = "NORMALIZATION",
+        value_delimiter = ','
+    )]
+    normalizations: Vec<ContentNormalization>,
+
+    /// Calculate the root digest of a Merkle tree over leaves of this size
+    /// in bytes, instead of the digest of the whole body
+    ///
+    /// Leaves, and inner nodes are hashed with the selected algorithm.
+    #[arg(long, global = true, value_name = "BYTES", value_parser = clap::value_parser!(u64).range(1..))]
+    merkle_leaf_size: Option<u64>,
+
+    /// Also write the leaf digests of the Merkle tree into this proof file,
+    /// so that byte ranges of the body can be checked with `verify-chunk`
+    ///
+    /// A single algorithm is required.
+    #[arg(
+        long,
+        global = true,
+        value_name = "FILE",
+        requires = "merkle_leaf_size"
+    )]
+    merkle_proof: Option<PathBuf>,
+
+    /// How progress of downloading Web pages is reported
+    #[arg(long, global = true, value_enum, default_value_t = ProgressDisplay::Bar)]
+    progress: ProgressDisplay,
+
+    /// Cache response bodies, and their digests, so that unchanged Web pages
+    /// are revalidated with conditional requests, instead of downloaded, and
+    /// hashed again
+    #[arg(long, global = true)]
+    cache: bool,
+
+    /// Directory for caching response bodies, and their digests [default:
+    /// $XDG_CACHE_HOME/web-page-hash]
+    ///
+    /// Implies `--cache`.
+    #[arg(long, global = true)]
+    cache_dir: Option<PathBuf>,
+
+    /// Evict least recently used cache entries beyond this total size in
+    /// bytes
+    #[arg(long, global = true, default_value_t = DEFAULT_HTTP_CACHE_MAX_SIZE)]
+    cache_max_size: u64,
+
+    /// An additional request header, e.g., `X-Api-Key: <key>`
+    ///
+    /// Can be given more than once.  Headers aren't sent along redirects to
+    /// other origins.
+    #[arg(long = "header", global = true, value_name = "NAME:VALUE", value_parser = parse_header)]
+    headers: Vec<(HeaderName, HeaderValue)>,
+
+    /// Send this `Bearer` token with requests
+    ///
+    /// Prefer setting `WEB_PAGE_HASH_BEARER_TOKEN`, since arguments are
+    /// visible to other users.
+    #[arg(long, global = true, conflicts_with_all = ["user", "netrc", "netrc_file"])]
+    bearer_token: Option<String>,
+
+    /// Send this user name, and optional password with requests using
+    /// `Basic` authentication
+    #[arg(long, global = true, value_name = "USER[:PASSWORD]", conflicts_with_all = ["netrc", "netrc_file"])]
+    user: Option<String>,
+
+    /// Look up `Basic` authentication credentials by host name in $NETRC,
+    /// or ~/.netrc
+    #[arg(long, global = true, conflicts_with = "netrc_file")]
+    netrc: bool,
+
+    /// Look up `Basic` authentication credentials by host name in this
+    /// `.netrc` file
+    #[arg(long, global = true)]
+    netrc_file: Option<PathBuf>,
+
+    /// Whether requests are sent without credentials, and additional request
+    /// headers (see [ServiceArgs::anonymous])
+    #[arg(skip)]
+    anonymous: bool,
+}
+
+impl ServiceArgs {
+    /// Returns these arguments sending requests without credentials, and
+    /// additional request headers, e.g., to other origins than the one they
+    /// were given for
+    fn anonymous(&self) -> ServiceArgs {
+        ServiceArgs {
+            anonymous: true,
+            ..self.clone()
+        }
+    }
+
+    /// Returns the credential provider selected by the arguments
+    ///
+    /// Without credential arguments, credentials are read from
+    /// `WEB_PAGE_HASH_BEARER_TOKEN`, or `WEB_PAGE_HASH_USERNAME`, and
+    /// `WEB_PAGE_HASH_PASSWORD`, when they're set.
+    fn credential_provider(&self) -> Result<SelectableCredentialProvider, CredentialError> {
+        if self.anonymous {
+            return Ok(SelectableCredentialProvider::None(
+                NoCredentialProvider::new(),
+            ));
+        }
+
+        Ok(match (&self.bearer_token, &self.user, &self.netrc_file) {
+            (Some(token), _, _) => SelectableCredentialProvider::BearerToken(
+                BearerTokenCredentialProvider::new(token)?,
+            ),
+            (None, Some(user), _) => {
+                let (username, password) = match user.split_once(':') {
+                    Some((username, password)) => (username, Some(password)),
+                    None => (user.as_str(), None),
+                };
+
+                SelectableCredentialProvider::BasicAuth(BasicAuthCredentialProvider::new(
+                    username, password,
+                ))
+            }
+            (None, None, Some(netrc_file)) => {
+                SelectableCredentialProvider::Netrc(NetrcCredentialProvider::from_file(netrc_file)?)
+            }
+            (None, None, None) if self.netrc => {
+                SelectableCredentialProvider::Netrc(NetrcCredentialProvider::from_default_file()?)
+            }
+            (None, None, None) => {
+                SelectableCredentialProvider::Environment(EnvironmentCredentialProvider::new())
+            }
+        })
+    }
+
+    /// Returns the algorithm given with `--algorithm`, if any, and fails, when
+    /// several were given
+    fn single_algorithm(&self) -> Result<Option<MessageDigestAlgorithm>, anyhow::Error> {
+        match self.algorithms.as_slice() {
+            [] => Ok(None),
+            [algorithm] => Ok(Some(*algorithm)),
+            _ => {
+                anyhow::bail!(
+                    "a single --algorithm is required with --expect, --check, --sign-key, and verify"
+                )
+            }
+        }
+    }
+
+    /// Returns the body sink selected by the arguments
+    ///
+    /// A saved body is kept only if its digest matches `expected_digest`, and
+    /// it was served from `required_final_url`.
+    fn body_sink(
+        &self,
+        expected_digest: Option<&ExpectedDigest>,
+        required_final_url: Option<&url::Url>,
+    ) -> SelectableBodySink {
+        let destination = match (&self.output_file, &self.output_dir) {
+            (Some(path), _) => BodyDestination::File(path.clone()),
+            (None, Some(directory)) => BodyDestination::Directory(directory.clone()),
+            (None, None) => return SelectableBodySink::None(NoBodySink::new()),
+        };
+        let file_body_sink = FileBodySink::new(destination, expected_digest.cloned());
+
+        SelectableBodySink::File(match required_final_url {
+            Some(required_final_url) => {
+                file_body_sink.with_required_final_url(required_final_url.clone())
+            }
+            None => file_body_sink,
+        })
+    }
+
+    /// Returns whether the response cache is used
+    fn uses_cache(&self) -> bool {
+        self.cache || self.cache_dir.is_some()
+    }
+
+    /// Returns the additional request headers
+    fn request_headers(&self) -> HeaderMap {
+        let mut request_headers = HeaderMap::new();
+        if self.anonymous {
+            return request_headers;
+        }
+        for (name, value) in &self.headers {
+            request_headers.append(name, value.clone());
+        }
+
+        request_headers
+    }
+
+    /// Returns the settings given as arguments, overriding all other
+    /// configuration layers
+    fn configuration_layer(&self) -> ConfigurationLayer {
+        ConfigurationLayer {
+            connect_timeout: self.connect_timeout,
+            timeout: self.timeout,
+            user_agent: self.user_agent.clone(),
+            proxy: self.proxy.clone(),
+            redirects: self.redirects,
+            max_redirects: self.max_redirects,
+            pin_cert_sha256: (!self.pinned_certificates.is_empty())
+                .then(|| self.pinned_certificates.clone()),
+            max_body_size: self.max_body_size,
+            allow_error_status: self
+                .allow_error_status
+                .then_some(true)
+                .or(self.no_allow_error_status.then_some(false)),
+            allowed_content_types: (!self.allowed_content_types.is_empty())
+                .then(|| self.allowed_content_types.clone()),
+            denied_content_types: (!self.denied_content_types.is_empty())
+                .then(|| self.denied_content_types.clone()),
+            algorithm: self.algorithms.first().copied(),
+            encoding: self.encoding,
+            normalize: (!self.normalizations.is_empty()).then(|| self.normalizations.clone()),
+            masks: None,
+        }
+    }
+}
+
+/// Parses a `NAME:VALUE` request header
+///
+/// Values are marked as sensitive, since they may hold credentials, e.g., API
+/// keys.
+fn parse_header(header: &str) -> Result<(HeaderName, HeaderValue), String> {
+    let (name, value) = header
+        .split_once(':')
+        .ok_or_else(|| "expected NAME:VALUE".to_string())?;
+    let name = HeaderName::try_from(name.trim()).map_err(|error| error.to_string())?;
+    let mut value = HeaderValue::try_from(value.trim()).map_err(|error| error.to_string())?;
+    value.set_sensitive(true);
+
+    Ok((name, value))
+}
+
+/// Formats of traces emitted on standard error
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum LogFormat {
+    /// Multi-line, human-readable events
+    Pretty,
+    /// One JSON object per event, including the spans it's in
+    Json,
+}
+
+/// Progress reporters that can be bound to the progress reporter dependency
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum ProgressDisplay {
+    /// A progress bar on standard error, when it's a terminal
+    Bar,
+    /// No progress reporting
+    None,
+}
+
+async fn calculate_web_page_message_digest<Service>(
+    calculate_web_page_message_digest_service: &Service,
+    url: &url::Url,
+) -> Result<WebPageMessageDigest<FanOutDigest>, anyhow::Error>
+where
+    Service: CalculateWebPageMessageDigestWithMetadata<
+            Url = url::Url,
+            Digest = FanOutDigest,
+            Error = anyhow::Error,
+        >,
+{
+    calculate_web_page_message_digest_service
+        .calculate_web_page_message_digest_with_metadata(url)
+        .await
+}
+
+/// An HTTP client service sending the request headers, and credentials
+/// selected by the arguments
+type AuthenticatingHttpClientService<HttpClientService> =
+    AuthenticatingGetUrl<HttpClientService, SelectableCredentialProvider>;
+
+/// Binds the message digest service to the selected algorithms, hashing
+/// Merkle tree leaves with them, when `--merkle-leaf-size` is given, and
+/// runs the calculate Web page message digest service
+async fn run<HttpClientService, ProgressReporter>(
+    service_args: &ServiceArgs,
+    configuration: &LayeredConfigurationService,
+    algorithms: &[MessageDigestAlgorithm],
+    body_sink: SelectableBodySink,
+    url: &url::Url,
+    http_client_service: HttpClientService,
+    progress_reporter: ProgressReporter,
+) -> Result<WebPageMessageDigest<FanOutDigest>, anyhow::Error>
+where
+    HttpClientService: GetUrlWithHeaders<Url = url::Url> + Sync,
+    HttpClientService::HttpResponse: IntoChunkStream + ResponseMetadata + Sync,
+    HttpClientService::Error: std::error::Error + Send + Sync + 'static,
+    <HttpClientService::HttpResponse as IntoChunkStream>::Error:
+        std::error::Error + Send + Sync + 'static,
+    ProgressReporter: ReportStarted + ReportBytesWritten + ReportFinished + Send + Sync,
+{
+    match (service_args.merkle_leaf_size, &service_args.merkle_proof) {
+        (Some(_), Some(_)) if algorithms.len() > 1 => {
+            anyhow::bail!("a single --algorithm is required with --merkle-proof")
+        }
+        (Some(leaf_size), proof_path) => {
+            let message_digest_service = FanOutMessageDigestService::new(
+                algorithms
+                    .iter()
+                    .map(|&algorithm| {
+                        let merkle_message_digest_service = MerkleMessageDigestService::new(
+                            SelectableMessageDigestService::new(algorithm),
+                            leaf_size,
+                        );
+
+                        Traced::new(match proof_path {
+                            Some(proof_path) => {
+                                merkle_message_digest_service.with_proof_file(proof_path)
+                            }
+                            None => merkle_message_digest_service,
+                        })
+                    })
+                    .collect(),
+            );
+
+            run_with_message_digest_service(
+                service_args,
+                configuration,
+                body_sink,
+                url,
+                http_client_service,
+                message_digest_service,
+                progress_reporter,
+            )
+            .await
+        }
+        (None, _) => {
+            let message_digest_service = FanOutMessageDigestService::new(
+                algorithms
+                    .iter()
+                    .map(|&algorithm| Traced::new(SelectableMessageDigestService::new(algorithm)))
+                    .collect(),
+            );
+
+            run_with_message_digest_service(
+                service_args,
+                configuration,
+                body_sink,
+                url,
+                http_client_service,
+                message_digest_service,
+                progress_reporter,
+            )
+            .await
+        }
+    }
+}
+
+/// Binds the calculate Web page message digest service to an HTTP client
+/// service, and a message digest service calculating the digests of all
+/// selected algorithms from one download, and runs it
+async fn run_with_message_digest_service<
+    HttpClientService,
+    MessageDigestService,
+    ProgressReporter,
+>(
+    service_args: &ServiceArgs,
+    configuration: &LayeredConfigurationService,
+    body_sink: SelectableBodySink,
+    url: &url::Url,
+    http_client_service: HttpClientService,
+    message_digest_service: MessageDigestService,
+    progress_reporter: ProgressReporter,
+) -> Result<WebPageMessageDigest<FanOutDigest>, anyhow::Error>
+where
+    HttpClientService: GetUrlWithHeaders<Url = url::Url> + Sync,
+    HttpClientService::HttpResponse: IntoChunkStream + ResponseMetadata + Sync,
+    HttpClientService::Error: std::error::Error + Send + Sync + 'static,
+    <HttpClientService::HttpResponse as IntoChunkStream>::Error:
+        std::error::Error + Send + Sync + 'static,
+    MessageDigestService: RestoreDigestCalculator + DigestAlgorithmName + Send + Sync,
+    MessageDigestService::Error: std::error::Error + Send + Sync + 'static,
+    MessageDigestService::DigestCalculator:
+        IntoDigestOctets<DigestOctets = FanOutDigest> + SaveDigestState + Write + Send + Sync,
+    <MessageDigestService::DigestCalculator as IntoDigestOctets>::Error:
+        std::error::Error + Send + Sync + 'static,
+    ProgressReporter: ReportStarted + ReportBytesWritten + ReportFinished + Send + Sync,
+{
+    let credential_provider = service_args.credential_provider()?;
+    let cache_dir = service_args
+        .cache_dir
+        .clone()
+        .or_else(HttpCache::default_directory);
+
+    match cache_dir {
+        Some(cache_dir) if service_args.uses_cache() => {
+            let cache = HttpCache::new(cache_dir, service_args.cache_max_size);
+            let calculate_web_page_message_digest_service = Traced::new(
+                CalculateWebPageMessageDigestService::<
+                    url::Url,
+                    FanOutDigest,
+                    anyhow::Error,
+                    Traced<AuthenticatingHttpClientService<CachingGetUrl<HttpClientService>>>,
+                    MessageDigestService,
+                    HttpCacheDigestMemoService,
+                    ProgressReporter,
+                    ConfiguredResponsePolicy,
+                    SelectableBodySink,
+                >::new(
+                    // Responses are cached by request headers, including
+                    // credentials:
+                    Traced::new(AuthenticatingGetUrl::new(
+                        CachingGetUrl::new(http_client_service, cache.clone()),
+                        credential_provider,
+                        service_args.request_headers(),
+                    )),
+                    message_digest_service,
+                    HttpCacheDigestMemoService::new(cache),
+                    progress_reporter,
+                    ConfiguredResponsePolicy::from_configuration(configuration),
+                    body_sink,
+                )
+                .with_content_transformer(ChainedContentTransformer::from_configuration(
+                    configuration,
+                ))
+                .with_content_decoder(SelectableContentDecoder::new(configuration.body_encoding())),
+            );
+
+            calculate_web_page_message_digest(&calculate_web_page_message_digest_service, url).await
+        }
+        _ => {
+            let calculate_web_page_message_digest_service = Traced::new(
+                CalculateWebPageMessageDigestService::<
+                    url::Url,
+                    FanOutDigest,
+                    anyhow::Error,
+                    Traced<AuthenticatingHttpClientService<HttpClientService>>,
+                    MessageDigestService,
+                    NoDigestMemoService,
+                    ProgressReporter,
+                    ConfiguredResponsePolicy,
+                    SelectableBodySink,
+                >::new(
+                    Traced::new(AuthenticatingGetUrl::new(
+                        http_client_service,
+                        credential_provider,
+                        service_args.request_headers(),
+                    )),
+                    message_digest_service,
+                    NoDigestMemoService::new(),
+                    progress_reporter,
+                    ConfiguredResponsePolicy::from_configuration(configuration),
+                    body_sink,
+                )
+                .with_content_transformer(ChainedContentTransformer::from_configuration(
+                    configuration,
+                ))
+                .with_content_decoder(SelectableContentDecoder::new(configuration.body_encoding())),
+            );
+
+            calculate_web_page_message_digest(&calculate_web_page_message_digest_service, url).await
+        }
+    }
+}
+
+/// Binds the progress reporter dependency to the selected progress reporter,
+/// and runs the calculate Web page message digest service
+async fn run_with_progress_reporter<HttpClientService>(
+    service_args: &ServiceArgs,
+    configuration: &LayeredConfigurationService,
+    algorithms: &[MessageDigestAlgorithm],
+    body_sink: SelectableBodySink,
+    url: &url::Url,
+    http_client_service: HttpClientService,
+) -> Result<WebPageMessageDigest<FanOutDigest>, anyhow::Error>
+where
+    HttpClientService: GetUrlWithHeaders<Url = url::Url> + Sync,
+    HttpClientService::HttpResponse: IntoChunkStream + ResponseMetadata + Sync,
+    HttpClientService::Error: std::error::Error + Send + Sync + 'static,
+    <HttpClientService::HttpResponse as IntoChunkStream>::Error:
+        std::error::Error + Send + Sync + 'static,
+{
+    match service_args.progress {
+        ProgressDisplay::Bar => {
+            let progress_reporter = IndicatifProgressReporter::new();
+
+            run(
+                service_args,
+                configuration,
+                algorithms,
+                body_sink,
+                url,
+                http_client_service,
+                progress_reporter,
+            )
+            .await
+        }
+        ProgressDisplay::None => {
+            let progress_reporter = NoProgressReporter::new();
+
+            run(
+                service_args,
+                configuration,
+                algorithms,
+                body_sink,
+                url,
+                http_client_service,
+                progress_reporter,
+            )
+            .await
+        }
+    }
+}
+
+/// Decorates the HTTP client service with a [RecordingGetUrl], when
+/// responses are recorded, and runs the calculate Web page message digest
+/// service
+async fn run_with_recording<HttpClientService>(
+    service_args: &ServiceArgs,
+    configuration: &LayeredConfigurationService,
+    algorithms: &[MessageDigestAlgorithm],
+    body_sink: SelectableBodySink,
+    url: &url::Url,
+    http_client_service: HttpClientService,
+) -> Result<WebPageMessageDigest<FanOutDigest>, anyhow::Error>
+where
+    HttpClientService: GetUrlWithHeaders<Url = url::Url> + Sync,
+    HttpClientService::HttpResponse: IntoChunkStream + ResponseMetadata + Sync,
+    HttpClientService::Error: std::error::Error + Send + Sync + 'static,
+    <HttpClientService::HttpResponse as IntoChunkStream>::Error:
+        std::error::Error + Send + Sync + 'static,
+{
+    match &service_args.record {
+        Some(cassette_path) => {
+            let http_client_service = RecordingGetUrl::new(http_client_service, cassette_path)
+                .with_context(|| format!("couldn't read {}", cassette_path.display()))?;
+
+            run_with_progress_reporter(
+                service_args,
+                configuration,
+                algorithms,
+                body_sink,
+                url,
+                http_client_service,
+            )
+            .await
+        }
+        None => {
+            run_with_progress_reporter(
+                service_args,
+                configuration,
+                algorithms,
+                body_sink,
+                url,
+                http_client_service,
+            )
+            .await
+        }
+    }
+}
+
+/// Calculates the message digests of a Web page with the selected HTTP
+/// backend, or with responses replayed from a cassette
+///
+/// The digest of each algorithm is calculated from the same download, and
+/// the digests are in the order of the algorithms (see
+/// [FanOutMessageDigestService]).  The
+/// body is written into `body_sink`.  Redirects are followed according to the
+/// configured policy (see [HttpClientConfiguration::redirect_policy]), while
+/// replayed responses keep the redirects, and certificates recorded with
+/// them.
+async fn calculate_with_backend(
+    service_args: &ServiceArgs,
+    configuration: &LayeredConfigurationService,
+    algorithms: &[MessageDigestAlgorithm],
+    body_sink: SelectableBodySink,
+    url: &url::Url,
+) -> Result<WebPageMessageDigest<FanOutDigest>, anyhow::Error> {
+    if let Some(cassette_path) = &service_args.replay {
+        let http_client_service = ReplayGetUrl::from_file(cassette_path)
+            .with_context(|| format!("couldn't read {}", cassette_path.display()))?
+            .with_pinned_certificates(configuration.pinned_certificates().to_vec());
+
+        return run_with_progress_reporter(
+            service_args,
+            configuration,
+            algorithms,
+            body_sink,
+            url,
+            http_client_service,
+        )
+        .await;
+    }
+
+    service_args
+        .http_backend
+        .check_configuration(configuration)?;
+
+    match service_args.http_backend {
+        HttpBackend::Reqwest => {
+            run_with_recording(
+                service_args,
+                configuration,
+                algorithms,
+                body_sink,
+                url,
+                RedirectingGetUrl::new(
+                    ReqwestHttpClientService::from_configuration(configuration)?,
+                    configuration.redirect_policy(),
+                )
+                .with_credential_provider(service_args.credential_provider()?),
+            )
+            .await
+        }
+        #[cfg(feature = "hyper")]
+        HttpBackend::Hyper => {
+            run_with_recording(
+                service_args,
+                configuration,
+                algorithms,
+                body_sink,
+                url,
+                RedirectingGetUrl::new(
+                    HyperGetUrlService::from_configuration(configuration, TokioClock::new())?,
+                    configuration.redirect_policy(),
+                )
+                .with_credential_provider(service_args.credential_provider()?),
+            )
+            .await
+        }
+        #[cfg(feature = "ureq")]
+        HttpBackend::Ureq => {
+            run_with_recording(
+                service_args,
+                configuration,
+                algorithms,
+                body_sink,
+                url,
+                RedirectingGetUrl::new(
+                    UreqGetUrlService::from_configuration(configuration)?,
+                    configuration.redirect_policy(),
+                )
+                .with_credential_provider(service_args.credential_provider()?),
+            )
+            .await
+        }
+        #[cfg(feature = "curl")]
+        HttpBackend::Curl => {
+            run_with_recording(
+                service_args,
+                configuration,
+                algorithms,
+                body_sink,
+                url,
+                RedirectingGetUrl::new(
+                    CurlGetUrlService::from_configuration(configuration),
+                    configuration.redirect_policy(),
+                )
+                .with_credential_provider(service_args.credential_provider()?),
+            )
+            .await
+        }
+    }
 }
 
-struct HexFormatted<'a>(&'a [u8]);
+/// Parses the expected digest, and determines the algorithm
+///
+/// An algorithm named by the expected digest takes precedence over the
+/// configured default, but mustn't contradict `--algorithm`.
+fn resolve_algorithm(
+    service_args: &ServiceArgs,
+    configuration: &LayeredConfigurationService,
+    expected_digest: &str,
+) -> Result<(MessageDigestAlgorithm, ExpectedDigest), anyhow::Error> {
+    let given_algorithm = service_args.single_algorithm()?;
+    let default_algorithm = configuration.default_algorithm();
+    let expected_digest = ExpectedDigest::parse(expected_digest, default_algorithm)?;
 
-impl std::fmt::LowerHex for HexFormatted<'_> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
-        for octet in self.0 {
-            write!(f, "{:x}", octet)?;
+    let algorithm = match (given_algorithm, expected_digest.algorithm()) {
+        (Some(algorithm), Some(expected_algorithm)) if algorithm != expected_algorithm => {
+            anyhow::bail!(
+                "the expected digest is a {} digest, but --algorithm is {}",
+                expected_algorithm,
+                algorithm
+            );
         }
+        (_, Some(expected_algorithm)) => expected_algorithm,
+        (_, None) => default_algorithm,
+    };
+
+    Ok((algorithm, expected_digest))
+}
 
-        Ok(())
+/// Compares a digest with the expected digest, and reports a mismatch
+fn check_digest(
+    url: &url::Url,
+    algorithm: MessageDigestAlgorithm,
+    expected_digest: &ExpectedDigest,
+    digest: &[u8],
+) -> ExitCode {
+    if expected_digest.matches(digest) {
+        return ExitCode::SUCCESS;
+    }
+
+    expected_digest
+        .write_mismatch(&mut io::stderr(), url.as_str(), algorithm, digest)
+        .expect("standard error can be written");
+
+    ExitCode::from(MISMATCH_EXIT_CODE)
+}
+
+/// Checks that a Web page was served from the required URL, after following
+/// redirects, and reports a mismatch on standard error
+fn check_final_url(url: &str, required_final_url: &url::Url, final_url: &str) -> ExitCode {
+    if url::Url::parse(final_url).is_ok_and(|final_url| final_url == *required_final_url) {
+        return ExitCode::SUCCESS;
+    }
+
+    eprintln!("Final URL mismatch: {}", url);
+    eprintln!("  Required: {}", required_final_url);
+    eprintln!("  Actual:   {}", final_url);
+
+    ExitCode::from(MISMATCH_EXIT_CODE)
+}
+
+/// Calculates the message digests of a Web page, and records the outcome,
+/// including a failure, and whether the digest matches `expected_digest`,
+/// for each algorithm, timed by `clock`
+async fn calculate_records(
+    clock: &impl Now,
+    service_args: &ServiceArgs,
+    configuration: &LayeredConfigurationService,
+    algorithms: &[MessageDigestAlgorithm],
+    expected_digest: Option<&ExpectedDigest>,
+    body_sink: SelectableBodySink,
+    url: &str,
+) -> Vec<WebPageDigestRecord> {
+    let start = clock.now();
+    let digest_result = match url::Url::parse(url) {
+        Ok(parsed_url) => {
+            calculate_with_backend(
+                service_args,
+                configuration,
+                algorithms,
+                body_sink,
+                &parsed_url,
+            )
+            .await
+        }
+        Err(error) => Err(error.into()),
+    };
+    let elapsed = clock.now().duration_since(start).unwrap_or_default();
+
+    WebPageDigestRecord::for_algorithms(
+        url,
+        algorithms,
+        digest_result.as_ref().map_err(AsRef::as_ref),
+        elapsed,
+        expected_digest,
+    )
+}
+
+/// Signs a statement of a digest calculated at the time of `clock`, and
+/// writes the detached signature into `signature_path`
+fn sign_digest<DigestSigner>(
+    digest_signer: &DigestSigner,
+    clock: &impl Now,
+    signature_path: &Path,
+    url: &str,
+    algorithm: MessageDigestAlgorithm,
+    digest: &[u8],
+) -> Result<(), SignatureFileError>
+where
+    DigestSigner: SignDigestStatement,
+    DigestSigner::Error: std::error::Error + Send + Sync + 'static,
+{
+    let statement = DigestStatement::new(url.to_string(), algorithm, digest, clock.now());
+
+    write_signature_file(digest_signer, &statement, signature_path)
+}
+
+/// Checks a detached signature of a digest statement, and checks that the
+/// Web page still has the signed digest
+///
+/// `url`, when given, must be the signed URL.
+async fn verify_signature<DigestVerifier>(
+    digest_verifier: &DigestVerifier,
+    service_args: &ServiceArgs,
+    configuration: &LayeredConfigurationService,
+    signature_path: &Path,
+    url: Option<&str>,
+) -> Result<ExitCode, anyhow::Error>
+where
+    DigestVerifier: VerifyDigestSignature,
+    DigestVerifier::Error: std::error::Error + Send + Sync + 'static,
+{
+    let signed_digest = SignedDigest::read_signature_file(
+        digest_verifier,
+        signature_path,
+        url,
+        service_args.single_algorithm()?,
+    )?;
+
+    let digest = calculate_with_backend(
+        service_args,
+        configuration,
+        &[signed_digest.algorithm],
+        service_args.body_sink(Some(&signed_digest.expected_digest), None),
+        &signed_digest.url,
+    )
+    .await?
+    .digest;
+
+    Ok(
+        if signed_digest.write_report(&mut io::stdout(), &mut io::stderr(), &digest.digests()[0])? {
+            ExitCode::SUCCESS
+        } else {
+            ExitCode::from(MISMATCH_EXIT_CODE)
+        },
+    )
+}
+
+/// Checks the digests listed in a checksum file, reporting as
+/// `sha3sum --check` does
+async fn check_checksum_file(
+    clock: &impl Now,
+    service_args: &ServiceArgs,
+    configuration: &LayeredConfigurationService,
+    path: &Path,
+    output: OutputFormat,
+    quiet: bool,
+    status: bool,
+) -> Result<ExitCode, anyhow::Error> {
+    let checksum_file = if path == Path::new("-") {
+        io::read_to_string(io::stdin())?
+    } else {
+        fs::read_to_string(path).with_context(|| format!("couldn't read {}", path.display()))?
+    };
+    let checker =
+        ChecksumFileChecker::new(configuration.default_algorithm(), output, quiet, status);
+
+    let summary = checker
+        .check(
+            &path.display().to_string(),
+            &checksum_file,
+            async |checksum_line| {
+                let expected_digest = checksum_line.expected_digest();
+
+                calculate_records(
+                    clock,
+                    service_args,
+                    configuration,
+                    &[checksum_line.algorithm()],
+                    Some(&expected_digest),
+                    service_args.body_sink(Some(&expected_digest), None),
+                    checksum_line.url(),
+                )
+                .await
+                .remove(0)
+            },
+            &mut io::stdout(),
+            &mut io::stderr(),
+        )
+        .await?;
+
+    Ok(if summary.passed() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::from(MISMATCH_EXIT_CODE)
+    })
+}
+
+/// Polls the watched Web pages, and reports changes of their digests
+async fn watch(
+    clock: &TokioClock,
+    service_args: &ServiceArgs,
+    configuration: &LayeredConfigurationService,
+    watch_args: &WatchArgs,
+) -> Result<ExitCode, anyhow::Error> {
+    let algorithms = match service_args.algorithms.as_slice() {
+        [] => vec![configuration.default_algorithm()],
+        algorithms => algorithms.to_vec(),
+    };
+    let state_path = watch_args
+        .state_file
+        .clone()
+        .or_else(WatchState::default_path)
+        .context("no --state-file given, and no state directory found")?;
+    let web_page_watcher = WebPageWatcher::new(
+        clock.clone(),
+        SelectableChangeNotifier::new(watch_args.json_log.as_deref(), watch_args.webhook.as_ref()),
+        Duration::from_secs(watch_args.interval),
+        Some(state_path.clone()),
+    );
+    let mut state = web_page_watcher
+        .load_state()
+        .with_context(|| format!("couldn't read {}", state_path.display()))?;
+
+    web_page_watcher
+        .watch(
+            &mut state,
+            &watch_args.urls,
+            watch_args.polls,
+            async |url| {
+                calculate_records(
+                    clock,
+                    service_args,
+                    configuration,
+                    &algorithms,
+                    None,
+                    service_args.body_sink(None, None),
+                    url,
+                )
+                .await
+            },
+        )
+        .await
+        .with_context(|| format!("couldn't write {}", state_path.display()))?;
+
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Crawls a Web site, and prints the manifest of the digests of its Web
+/// pages
+///
+/// `robots.txt` is fetched through the calculate Web page message digest
+/// service too, and doesn't restrict crawling, when it can't be fetched.
+async fn crawl(
+    clock: &TokioClock,
+    service_args: &ServiceArgs,
+    configuration: &LayeredConfigurationService,
+    crawl_args: &CrawlArgs,
+) -> Result<ExitCode, anyhow::Error> {
+    let start_url = url::Url::parse(&crawl_args.url)?;
+    let algorithms = match service_args.algorithms.as_slice() {
+        [] => vec![configuration.default_algorithm()],
+        algorithms => algorithms.to_vec(),
+    };
+    // The bodies of calculated Web pages are captured, so that links can be
+    // extracted from them:
+    let body_sink = MemoryBodySink::new();
+    let calculate_page = async |url: &url::Url| {
+        let records = calculate_records(
+            clock,
+            service_args,
+            configuration,
+            &algorithms,
+            None,
+            SelectableBodySink::Memory(body_sink.clone()),
+            url.as_str(),
+        )
+        .await;
+        let body = body_sink
+            .take_body(url.as_str())
+            .map(|captured_body| captured_body.body);
+
+        CalculatedPage { records, body }
+    };
+    let robots_txt =
+        RobotsTxt::fetch(&start_url, configuration.user_agent(), &calculate_page).await?;
+
+    let web_site_crawler = WebSiteCrawler::new(
+        HtmlLinkExtractor::new(),
+        CrawlScope::new(&start_url, crawl_args.prefix.clone()),
+        robots_txt,
+        crawl_args.max_depth,
+        crawl_args.max_pages,
+        crawl_args.concurrency,
+    );
+    let crawled_pages = web_site_crawler.crawl(&start_url, calculate_page).await;
+    if crawled_pages.is_empty() {
+        anyhow::bail!("robots.txt disallows crawling {}", start_url);
+    }
+
+    let records = crawled_pages
+        .into_iter()
+        .flat_map(|crawled_page| crawled_page.records)
+        .collect::<Vec<_>>();
+    crawl_args
+        .output
+        .write_records(&mut io::stdout(), &mut io::stderr(), &records)?;
+
+    Ok(if records.iter().any(|record| record.error.is_some()) {
+        ExitCode::from(ERROR_EXIT_CODE)
+    } else {
+        ExitCode::SUCCESS
+    })
+}
+
+/// Prints Subresource Integrity metadata of the scripts, and stylesheets of
+/// a Web page, or the Web page with `integrity` attributes
+async fn sri(
+    service_args: &ServiceArgs,
+    configuration: &LayeredConfigurationService,
+    sri_args: &SriArgs,
+) -> Result<ExitCode, anyhow::Error> {
+    let page_url = url::Url::parse(&sri_args.url)?;
+    let algorithms = match service_args.algorithms.as_slice() {
+        [] => vec![MessageDigestAlgorithm::Sha384],
+        algorithms => algorithms.to_vec(),
+    };
+    check_sri_algorithms(&algorithms)?;
+
+    let body_sink = MemoryBodySink::new();
+    let web_page_message_digest = calculate_with_backend(
+        service_args,
+        configuration,
+        &algorithms[..1],
+        SelectableBodySink::Memory(body_sink.clone()),
+        &page_url,
+    )
+    .await?;
+    let body = body_sink
+        .take_body(page_url.as_str())
+        .map(|captured_body| captured_body.body)
+        .unwrap_or_default();
+    let html = match sri_args.output {
+        // The Web page is printed as it is, except for the added attributes:
+        SriOutput::Html => String::from_utf8(body).context("the Web page isn't UTF-8")?,
+        _ => String::from_utf8_lossy(&body).into_owned(),
+    };
+    let html_subresources = HtmlSubresources::parse(html);
+
+    // Subresources of other origins, e.g., CDNs, are requested without the
+    // credentials, additional headers, and pinned certificates meant for the
+    // Web page, like `crossorigin=anonymous` subresources:
+    let anonymous_service_args = service_args.anonymous();
+    let anonymous_configuration = configuration.without_pinned_certificates();
+    let records = calculate_integrity_records(
+        &html_subresources,
+        &url::Url::parse(&web_page_message_digest.final_url)?,
+        &algorithms,
+        async |url| {
+            let (subresource_service_args, subresource_configuration) =
+                if url.origin() == page_url.origin() {
+                    (service_args, configuration)
+                } else {
+                    (&anonymous_service_args, &anonymous_configuration)
+                };
+            let web_page_message_digest = calculate_with_backend(
+                subresource_service_args,
+                subresource_configuration,
+                &algorithms,
+                SelectableBodySink::None(NoBodySink::new()),
+                url,
+            )
+            .await?;
+            if !web_page_message_digest.status.is_success() {
+                return Err(
+                    format!("the response status is {}", web_page_message_digest.status).into(),
+                );
+            }
+
+            Ok(web_page_message_digest.digest.into_digests())
+        },
+    )
+    .await?;
+
+    sri_args.output.write_records(
+        &mut io::stdout(),
+        &mut io::stderr(),
+        &html_subresources,
+        &records,
+        sri_args.crossorigin,
+    )?;
+
+    Ok(if records.iter().any(|record| record.error.is_some()) {
+        ExitCode::from(ERROR_EXIT_CODE)
+    } else {
+        ExitCode::SUCCESS
+    })
+}
+
+/// Checks a byte range of a file against the root digest of a Merkle tree
+fn verify_chunk(verify_chunk_args: &VerifyChunkArgs) -> Result<ExitCode, anyhow::Error> {
+    let proof = MerkleProof::load(&verify_chunk_args.proof)
+        .with_context(|| format!("couldn't read {}", verify_chunk_args.proof.display()))?;
+    let leaf_hasher = SelectableMessageDigestService::new(proof.algorithm.parse()?);
+    let root = verify_chunk_args.root.trim();
+    let root = hex::decode(root.strip_prefix("0x").unwrap_or(root))
+        .context("the root digest isn't hexadecimal")?;
+    let file = fs::File::open(&verify_chunk_args.file)
+        .with_context(|| format!("couldn't open {}", verify_chunk_args.file.display()))?;
+
+    let chunk_verification = proof.verify_chunk(
+        &leaf_hasher,
+        &root,
+        io::BufReader::new(file),
+        verify_chunk_args.offset,
+        verify_chunk_args.length,
+    )?;
+    chunk_verification.write_report(
+        &mut io::stdout(),
+        &mut io::stderr(),
+        &verify_chunk_args.proof.display().to_string(),
+        &verify_chunk_args.file.display().to_string(),
+        verify_chunk_args.offset,
+        verify_chunk_args.length,
+    )?;
+
+    Ok(if chunk_verification.passed() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::from(MISMATCH_EXIT_CODE)
+    })
+}
+
+async fn run_command(args: Args) -> Result<ExitCode, anyhow::Error> {
+    let configuration = LayeredConfigurationService::load(
+        args.service_args.config.as_deref(),
+        args.service_args.configuration_layer(),
+    )?;
+    let clock = TokioClock::new();
+
+    match (args.command, args.check) {
+        (Some(Command::Verify(verify_args)), _) => {
+            let url = url::Url::parse(&verify_args.url)?;
+            let (algorithm, expected_digest) =
+                resolve_algorithm(&args.service_args, &configuration, &verify_args.expect)?;
+
+            let digest = calculate_with_backend(
+                &args.service_args,
+                &configuration,
+                &[algorithm],
+                args.service_args.body_sink(Some(&expected_digest), None),
+                &url,
+            )
+            .await?
+            .digest;
+            let exit_code = check_digest(&url, algorithm, &expected_digest, &digest.digests()[0]);
+            if exit_code == ExitCode::SUCCESS {
+                println!("{}: OK ({})", url, algorithm.name());
+            }
+
+            Ok(exit_code)
+        }
+        (Some(Command::Watch(watch_args)), _) => {
+            watch(&clock, &args.service_args, &configuration, &watch_args).await
+        }
+        (Some(Command::VerifyChunk(verify_chunk_args)), _) => verify_chunk(&verify_chunk_args),
+        (Some(Command::Crawl(crawl_args)), _) => {
+            crawl(&clock, &args.service_args, &configuration, &crawl_args).await
+        }
+        (Some(Command::Sri(sri_args)), _) => {
+            // Browsers check subresources as they're decoded, and not
+            // normalized:
+            let configuration = LayeredConfigurationService::load(
+                args.service_args.config.as_deref(),
+                ConfigurationLayer {
+                    encoding: Some(BodyEncoding::Decoded),
+                    normalize: Some(Vec::new()),
+                    masks: Some(Vec::new()),
+                    ..args.service_args.configuration_layer()
+                },
+            )?;
+
+            sri(&args.service_args, &configuration, &sri_args).await
+        }
+        (None, Some(path)) => {
+            args.service_args.single_algorithm()?;
+
+            check_checksum_file(
+                &clock,
+                &args.service_args,
+                &configuration,
+                &path,
+                args.output,
+                args.quiet,
+                args.status,
+            )
+            .await
+        }
+        (None, None) if args.verify_signature.is_some() => {
+            let (Some(signature_path), Some(public_key_path)) =
+                (&args.verify_signature, &args.public_key)
+            else {
+                unreachable!("clap requires --public-key with --verify-signature");
+            };
+            let digest_verifier = SelectableDigestVerifier::from_key_file(public_key_path)?;
+
+            verify_signature(
+                &digest_verifier,
+                &args.service_args,
+                &configuration,
+                signature_path,
+                args.url.as_deref(),
+            )
+            .await
+        }
+        (None, None) => {
+            let url = args.url.expect("clap requires --url without a subcommand");
+            let required_final_url = args
+                .require_final_url
+                .as_deref()
+                .map(url::Url::parse)
+                .transpose()
+                .context("invalid --require-final-url")?;
+            // The key is read before fetching, so that an unusable key fails
+            // early:
+            let digest_signer = match (&args.sign_key, &args.signature) {
+                (Some(key_path), Some(signature_path)) => {
+                    args.service_args.single_algorithm()?;
+
+                    Some((
+                        SelectableDigestSigner::from_key_file(key_path)?,
+                        signature_path,
+                    ))
+                }
+                _ => None,
+            };
+            let (algorithms, expected_digest) = match args.expect.as_deref() {
+                Some(expected_digest) => {
+                    let (algorithm, expected_digest) =
+                        resolve_algorithm(&args.service_args, &configuration, expected_digest)?;
+
+                    (vec![algorithm], Some(expected_digest))
+                }
+                None if args.service_args.algorithms.is_empty() => {
+                    (vec![configuration.default_algorithm()], None)
+                }
+                None => (args.service_args.algorithms.clone(), None),
+            };
+
+            if args.output.prints_records() {
+                let records = calculate_records(
+                    &clock,
+                    &args.service_args,
+                    &configuration,
+                    &algorithms,
+                    expected_digest.as_ref(),
+                    args.service_args
+                        .body_sink(expected_digest.as_ref(), required_final_url.as_ref()),
+                    &url,
+                )
+                .await;
+                let final_url_exit_code = match (
+                    &required_final_url,
+                    records
+                        .iter()
+                        .find_map(|record| record.final_url.as_deref()),
+                ) {
+                    (Some(required_final_url), Some(final_url)) => {
+                        check_final_url(&url, required_final_url, final_url)
+                    }
+                    _ => ExitCode::SUCCESS,
+                };
+
+                if let (Some((digest_signer, signature_path)), [record]) =
+                    (&digest_signer, records.as_slice())
+                    && let Some(digest) = &record.digest
+                    && record.matches_expected != Some(false)
+                    && final_url_exit_code == ExitCode::SUCCESS
+                {
+                    sign_digest(
+                        digest_signer,
+                        &clock,
+                        signature_path,
+                        &record.url,
+                        algorithms[0],
+                        &hex::decode(&digest.hex)?,
+                    )?;
+                }
+
+                match (args.output, records.as_slice()) {
+                    (OutputFormat::Json, [record]) => {
+                        args.output.write_record(&mut io::stdout(), record)?
+                    }
+                    _ => {
+                        args.output
+                            .write_records(&mut io::stdout(), &mut io::stderr(), &records)?
+                    }
+                }
+
+                return Ok(if records.iter().any(|record| record.error.is_some()) {
+                    ExitCode::from(ERROR_EXIT_CODE)
+                } else if records
+                    .iter()
+                    .any(|record| record.matches_expected == Some(false))
+                    || final_url_exit_code != ExitCode::SUCCESS
+                {
+                    ExitCode::from(MISMATCH_EXIT_CODE)
+                } else {
+                    ExitCode::SUCCESS
+                });
+            }
+
+            let url = url::Url::parse(&url)?;
+
+            if let OutputFormat::Text = args.output {
+                println!("Hashing content: {}", url);
+            }
+            let web_page_message_digest = calculate_with_backend(
+                &args.service_args,
+                &configuration,
+                &algorithms,
+                args.service_args
+                    .body_sink(expected_digest.as_ref(), required_final_url.as_ref()),
+                &url,
+            )
+            .await?;
+            args.output
+                .write_response_details(&mut io::stdout(), &web_page_message_digest)?;
+            let mut exit_code = match &required_final_url {
+                Some(required_final_url) => check_final_url(
+                    url.as_str(),
+                    required_final_url,
+                    &web_page_message_digest.final_url,
+                ),
+                None => ExitCode::SUCCESS,
+            };
+            for (algorithm, digest) in algorithms
+                .iter()
+                .copied()
+                .zip(web_page_message_digest.digest.digests())
+            {
+                let checksum_line = ChecksumLine::new(algorithm, digest.clone(), url.to_string());
+                args.output.write_digest(
+                    &mut io::stdout(),
+                    &checksum_line,
+                    args.service_args.merkle_leaf_size,
+                )?;
+
+                if let Some(expected_digest) = &expected_digest
+                    && check_digest(&url, algorithm, expected_digest, checksum_line.digest())
+                        != ExitCode::SUCCESS
+                {
+                    exit_code = ExitCode::from(MISMATCH_EXIT_CODE);
+                }
+                if exit_code == ExitCode::SUCCESS
+                    && let Some((digest_signer, signature_path)) = &digest_signer
+                {
+                    sign_digest(
+                        digest_signer,
+                        &clock,
+                        signature_path,
+                        url.as_str(),
+                        algorithm,
+                        checksum_line.digest(),
+                    )?;
+                }
+            }
+
+            Ok(exit_code)
+        }
+    }
+}
+
+/// Installs a [tracing] subscriber printing traces on standard error
+fn init_tracing(log_format: LogFormat) {
+    let env_filter =
+        EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(DEFAULT_LOG_FILTER));
+    let subscriber = tracing_subscriber::fmt()
+        .with_env_filter(env_filter)
+        .with_writer(io::stderr);
+
+    match log_format {
+        LogFormat::Pretty => subscriber.pretty().init(),
+        LogFormat::Json => subscriber.json().with_span_list(true).init(),
     }
 }
 
 #[tokio::main]
-async fn main() -> Result<(), anyhow::Error> {
+async fn main() -> ExitCode {
     let args = Args::parse();
 
-    let url = reqwest::Url::parse(&args.url)?;
-    let http_client_service = ReqwestHttpClientService::new();
-    let message_digest_service = Sha3_256BitMessageDigestService::new();
-    let calculate_web_page_message_digest_service =
-        CalculateWebPageMessageDigestService::<
-            reqwest::Url,
-            [u8; 32],
-            anyhow::Error,
-            ReqwestHttpClientService,
-            Sha3_256BitMessageDigestService,
-        >::new(http_client_service, message_digest_service);
-
-    println!("Hashing content: {}", url);
-    let digest = calculate_web_page_message_digest_service
-        .calculate_web_page_message_digest(&url)
-        .await?;
-    println!("256-bit SHA-3: 0x{:x}", HexFormatted(&digest));
+    if let Some(log_format) = args.log_format {
+        init_tracing(log_format);
+    }
 
-    Ok(())
+    match run_command(args).await {
+        Ok(exit_code) => exit_code,
+        Err(error) => {
+            eprintln!("Error: {:?}", error);
+            ExitCode::from(ERROR_EXIT_CODE)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use web_page_hash_using_explicit_arguments::configuration_service::ResponsePolicyConfiguration;
+
+    /// Returns whether error statuses are allowed by a configuration file
+    /// allowing them, and `arguments`
+    fn allows_error_status(arguments: &[&str]) -> bool {
+        let file_layer = ConfigurationLayer {
+            allow_error_status: Some(true),
+            ..ConfigurationLayer::default()
+        };
+        let args = Args::try_parse_from(
+            ["web-page-hash", "--url", "https://example.com/"]
+                .iter()
+                .chain(arguments),
+        )
+        .unwrap();
+
+        LayeredConfigurationService::new([file_layer, args.service_args.configuration_layer()])
+            .allow_error_status()
+    }
+
+    #[test]
+    fn no_allow_error_status_overrides_the_configuration_file() {
+        assert!(allows_error_status(&[]));
+        assert!(!allows_error_status(&["--no-allow-error-status"]));
+        // The last flag wins:
+        assert!(allows_error_status(&[
+            "--no-allow-error-status",
+            "--allow-error-status"
+        ]));
+    }
+
+    /// Resolves the algorithm of an expected digest given `arguments`
+    fn resolve(
+        arguments: &[&str],
+        expected_digest: &str,
+    ) -> Result<(MessageDigestAlgorithm, ExpectedDigest), anyhow::Error> {
+        let args = Args::try_parse_from(
+            ["web-page-hash", "--url", "https://example.com/"]
+                .iter()
+                .chain(arguments),
+        )
+        .unwrap();
+        let configuration =
+            LayeredConfigurationService::new([args.service_args.configuration_layer()]);
+
+        resolve_algorithm(&args.service_args, &configuration, expected_digest)
+    }
+
+    #[test]
+    fn expected_digests_naming_another_algorithm_than_the_given_one_are_rejected() {
+        let sha256_digest = format!("sha256:{}", "00".repeat(32));
+
+        let (algorithm, _) = resolve(&[], &sha256_digest).unwrap();
+        assert_eq!(algorithm, MessageDigestAlgorithm::Sha256);
+        let (algorithm, _) = resolve(&["--algorithm", "SHA256"], &sha256_digest).unwrap();
+        assert_eq!(algorithm, MessageDigestAlgorithm::Sha256);
+
+        let error = resolve(&["--algorithm", "sha3-256"], &sha256_digest).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "the expected digest is a SHA256 digest, but --algorithm is SHA3-256"
+        );
+        // Digests without a prefix have the given algorithm:
+        let (algorithm, _) = resolve(&["--algorithm", "sha512"], &"00".repeat(64)).unwrap();
+        assert_eq!(algorithm, MessageDigestAlgorithm::Sha512);
+    }
 }
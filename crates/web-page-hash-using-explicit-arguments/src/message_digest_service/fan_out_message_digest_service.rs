@@ -0,0 +1,243 @@
+use super::interface::{
+    DigestAlgorithmName, DigestParts, IntoDigestOctets, MessageDigestService, NewDigestCalculator,
+    RestoreDigestCalculator, SaveDigestState,
+};
+use std::fmt;
+use std::io::{self, Write};
+
+/// A message digest service calculating the digests of several message
+/// digest services from a single pass over the message
+///
+/// Every write to its digest calculators is forwarded to a digest calculator
+/// of each service.  The digests are returned as a [FanOutDigest] in the
+/// order of the services.  The algorithm name is the comma-separated list of
+/// the algorithm names (e.g., `SHA3-256,SHA256`).
+#[derive(Debug)]
+pub struct FanOutMessageDigestService<Service> {
+    message_digest_services: Vec<Service>,
+    algorithm_name: String,
+}
+
+impl<Service: DigestAlgorithmName> FanOutMessageDigestService<Service> {
+    pub fn new(message_digest_services: Vec<Service>) -> Self {
+        let algorithm_name = message_digest_services
+            .iter()
+            .map(DigestAlgorithmName::digest_algorithm_name)
+            .collect::<Vec<_>>()
+            .join(",");
+
+        FanOutMessageDigestService {
+            message_digest_services,
+            algorithm_name,
+        }
+    }
+}
+
+impl<Service: NewDigestCalculator> NewDigestCalculator for FanOutMessageDigestService<Service> {
+    type DigestCalculator = FanOutDigestCalculator<Service::DigestCalculator>;
+    type Error = Service::Error;
+
+    fn new_digest_calculator(&self) -> Result<Self::DigestCalculator, Self::Error> {
+        let digest_calculators = self
+            .message_digest_services
+            .iter()
+            .map(NewDigestCalculator::new_digest_calculator)
+            .collect::<Result<_, _>>()?;
+
+        Ok(FanOutDigestCalculator { digest_calculators })
+    }
+
+    fn needs_message(&self) -> bool {
+        self.message_digest_services
+            .iter()
+            .any(NewDigestCalculator::needs_message)
+    }
+}
+
+impl<Service: RestoreDigestCalculator> RestoreDigestCalculator
+    for FanOutMessageDigestService<Service>
+{
+    fn restore_digest_calculator(&self, digest_state: &[u8]) -> Option<Self::DigestCalculator> {
+        let digest_states = split_length_prefixed(digest_state)?;
+        if digest_states.len() != self.message_digest_services.len() {
+            return None;
+        }
+
+        let digest_calculators = self
+            .message_digest_services
+            .iter()
+            .zip(digest_states)
+            .map(|(message_digest_service, digest_state)| {
+                message_digest_service.restore_digest_calculator(digest_state)
+            })
+            .collect::<Option<_>>()?;
+
+        Some(FanOutDigestCalculator { digest_calculators })
+    }
+}
+
+impl<Service> DigestAlgorithmName for FanOutMessageDigestService<Service> {
+    fn digest_algorithm_name(&self) -> &str {
+        &self.algorithm_name
+    }
+}
+
+impl<Service> MessageDigestService for FanOutMessageDigestService<Service>
+where
+    Service: NewDigestCalculator + DigestAlgorithmName,
+    Service::DigestCalculator: IntoDigestOctets + Write,
+    <Service::DigestCalculator as IntoDigestOctets>::DigestOctets: AsRef<[u8]>,
+{
+}
+
+/// A digest calculator returned by [FanOutMessageDigestService]
+#[derive(Debug)]
+pub struct FanOutDigestCalculator<DigestCalculator> {
+    digest_calculators: Vec<DigestCalculator>,
+}
+
+impl<DigestCalculator: Write> Write for FanOutDigestCalculator<DigestCalculator> {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        for digest_calculator in &mut self.digest_calculators {
+            digest_calculator.write_all(data)?;
+        }
+
+        Ok(data.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        for digest_calculator in &mut self.digest_calculators {
+            digest_calculator.flush()?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<DigestCalculator> IntoDigestOctets for FanOutDigestCalculator<DigestCalculator>
+where
+    DigestCalculator: IntoDigestOctets,
+    DigestCalculator::DigestOctets: AsRef<[u8]>,
+{
+    type DigestOctets = FanOutDigest;
+    type Error = DigestCalculator::Error;
+
+    fn into_digest_octets(self) -> Result<Self::DigestOctets, Self::Error> {
+        let digests = self
+            .digest_calculators
+            .into_iter()
+            .map(|digest_calculator| Ok(digest_calculator.into_digest_octets()?.as_ref().to_vec()))
+            .collect::<Result<_, _>>()?;
+
+        Ok(FanOutDigest::new(digests))
+    }
+}
+
+impl<DigestCalculator: SaveDigestState> SaveDigestState
+    for FanOutDigestCalculator<DigestCalculator>
+{
+    /// Returns the states of all digest calculators, each preceded by its
+    /// length as a 4-octet big-endian number, or [None], when any state
+    /// can't be saved
+    fn save_digest_state(&self) -> Option<Vec<u8>> {
+        let digest_states = self
+            .digest_calculators
+            .iter()
+            .map(SaveDigestState::save_digest_state)
+            .collect::<Option<Vec<_>>>()?;
+
+        join_length_prefixed(&digest_states)
+    }
+}
+
+/// The digests calculated by a [FanOutDigestCalculator], in the order of the
+/// message digest services
+///
+/// Its octets are the digests, each preceded by its length as a 4-octet
+/// big-endian number, so that it can be memoized like a single digest.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FanOutDigest {
+    digests: Vec<Vec<u8>>,
+    octets: Vec<u8>,
+}
+
+impl FanOutDigest {
+    pub fn new(digests: Vec<Vec<u8>>) -> Self {
+        let octets = join_length_prefixed(&digests).expect("digests are shorter than 4 GiB");
+
+        FanOutDigest { digests, octets }
+    }
+
+    /// Returns the digest of each message digest service
+    pub fn digests(&self) -> &[Vec<u8>] {
+        &self.digests
+    }
+
+    pub fn into_digests(self) -> Vec<Vec<u8>> {
+        self.digests
+    }
+}
+
+impl AsRef<[u8]> for FanOutDigest {
+    fn as_ref(&self) -> &[u8] {
+        &self.octets
+    }
+}
+
+impl DigestParts for FanOutDigest {
+    fn digest_parts(&self) -> Vec<&[u8]> {
+        self.digests.iter().map(Vec::as_slice).collect()
+    }
+}
+
+impl TryFrom<&[u8]> for FanOutDigest {
+    type Error = InvalidFanOutDigest;
+
+    /// Parses the octets of a memoized digest
+    fn try_from(octets: &[u8]) -> Result<Self, Self::Error> {
+        let digests = split_length_prefixed(octets).ok_or(InvalidFanOutDigest)?;
+
+        Ok(FanOutDigest {
+            digests: digests.into_iter().map(<[u8]>::to_vec).collect(),
+            octets: octets.to_vec(),
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct InvalidFanOutDigest;
+
+impl fmt::Display for InvalidFanOutDigest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "the digest octets aren't length-prefixed digests")
+    }
+}
+
+impl std::error::Error for InvalidFanOutDigest {}
+
+/// Concatenates parts, each preceded by its length as a 4-octet big-endian
+/// number, or returns [None], when a part is too long
+fn join_length_prefixed(parts: &[Vec<u8>]) -> Option<Vec<u8>> {
+    let mut octets = Vec::new();
+    for part in parts {
+        let length = u32::try_from(part.len()).ok()?;
+        octets.extend_from_slice(&length.to_be_bytes());
+        octets.extend_from_slice(part);
+    }
+
+    Some(octets)
+}
+
+/// Splits parts joined by [join_length_prefixed]
+fn split_length_prefixed(mut octets: &[u8]) -> Option<Vec<&[u8]>> {
+    let mut parts = Vec::new();
+    while !octets.is_empty() {
+        let (length, rest) = octets.split_first_chunk::<4>()?;
+        let length = usize::try_from(u32::from_be_bytes(*length)).ok()?;
+        let (part, rest) = rest.split_at_checked(length)?;
+        parts.push(part);
+        octets = rest;
+    }
+
+    Some(parts)
+}
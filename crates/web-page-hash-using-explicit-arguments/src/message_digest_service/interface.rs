@@ -11,15 +11,65 @@ pub trait IntoDigestOctets {
     fn into_digest_octets(self) -> Result<Self::DigestOctets, Self::Error>;
 }
 
+/// A function trait returning the digests a digest consists of, e.g., the
+/// digest of each algorithm of a fan-out digest
+///
+/// A single digest consists of itself.
+pub trait DigestParts {
+    fn digest_parts(&self) -> Vec<&[u8]>;
+}
+
+impl DigestParts for Vec<u8> {
+    fn digest_parts(&self) -> Vec<&[u8]> {
+        vec![self]
+    }
+}
+
+impl<const N: usize> DigestParts for [u8; N] {
+    fn digest_parts(&self) -> Vec<&[u8]> {
+        vec![self]
+    }
+}
+
 pub trait NewDigestCalculator {
     type DigestCalculator;
     type Error;
 
     fn new_digest_calculator(&self) -> Result<Self::DigestCalculator, Self::Error>;
+
+    /// Returns whether digest calculators need the message even when its
+    /// digest is memoized, e.g., for writing a Merkle proof
+    fn needs_message(&self) -> bool {
+        false
+    }
+}
+
+/// A function trait naming the algorithm of digest calculators returned by a
+/// [NewDigestCalculator]
+///
+/// Names follow the BSD-tag style used by, e.g., `sha3sum` (`SHA3-256`).
+pub trait DigestAlgorithmName {
+    fn digest_algorithm_name(&self) -> &str;
+}
+
+/// A function trait saving the intermediate state of a digest calculator,
+/// e.g., for resuming an interrupted download later
+pub trait SaveDigestState {
+    /// Returns the serialized state, or [None], when the state of the
+    /// algorithm can't be saved
+    fn save_digest_state(&self) -> Option<Vec<u8>>;
+}
+
+/// A function trait restoring a digest calculator from a state saved with
+/// [SaveDigestState]
+pub trait RestoreDigestCalculator: NewDigestCalculator {
+    /// Returns [None], when `digest_state` isn't a state of a digest
+    /// calculator of this service
+    fn restore_digest_calculator(&self, digest_state: &[u8]) -> Option<Self::DigestCalculator>;
 }
 
 // The full service is composed of its method, and function traits:
-pub trait MessageDigestService: NewDigestCalculator
+pub trait MessageDigestService: NewDigestCalculator + DigestAlgorithmName
 where
     <Self as NewDigestCalculator>::DigestCalculator: IntoDigestOctets + std::io::Write,
 {
@@ -0,0 +1,376 @@
+use super::interface::{
+    DigestAlgorithmName, IntoDigestOctets, MessageDigestService, NewDigestCalculator,
+    RestoreDigestCalculator, SaveDigestState,
+};
+use super::merkle_proof::MerkleProof;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// The first octet hashed for leaves, and for inner nodes, so that a leaf
+/// can't be passed off as an inner node (see RFC 6962, section 2.1)
+const LEAF_PREFIX: u8 = 0x00;
+const NODE_PREFIX: u8 = 0x01;
+
+/// A message digest service calculating the root digest of a Merkle tree
+/// over fixed-size leaves of the message
+///
+/// Leaves, and inner nodes are hashed with the injected leaf hasher.  The
+/// last leaf may be shorter, and an empty message has a single empty leaf.
+/// A node without a sibling is carried up to the next level unchanged.  The
+/// leaf digests can be written into a [MerkleProof] file, so that byte
+/// ranges of the message can be checked later without hashing all of it.
+#[derive(Debug)]
+pub struct MerkleMessageDigestService<LeafHasher> {
+    leaf_hasher: Arc<LeafHasher>,
+    leaf_size: u64,
+    proof_path: Option<PathBuf>,
+    algorithm_name: String,
+}
+
+impl<LeafHasher: DigestAlgorithmName> MerkleMessageDigestService<LeafHasher> {
+    /// Returns a service splitting messages into leaves of `leaf_size`
+    /// octets, which must not be zero
+    pub fn new(leaf_hasher: LeafHasher, leaf_size: u64) -> Self {
+        assert!(leaf_size > 0, "Merkle tree leaves can't be empty");
+        let algorithm_name = format!(
+            "MERKLE-{}-{}",
+            leaf_hasher.digest_algorithm_name(),
+            leaf_size
+        );
+
+        MerkleMessageDigestService {
+            leaf_hasher: Arc::new(leaf_hasher),
+            leaf_size,
+            proof_path: None,
+            algorithm_name,
+        }
+    }
+
+    /// Makes digest calculators write a [MerkleProof] file, when the digest
+    /// is calculated
+    pub fn with_proof_file(self, proof_path: impl Into<PathBuf>) -> Self {
+        MerkleMessageDigestService {
+            proof_path: Some(proof_path.into()),
+            ..self
+        }
+    }
+}
+
+impl<LeafHasher> NewDigestCalculator for MerkleMessageDigestService<LeafHasher>
+where
+    LeafHasher: NewDigestCalculator,
+    LeafHasher::DigestCalculator: Write,
+{
+    type DigestCalculator = MerkleDigestCalculator<LeafHasher>;
+    type Error = LeafHasher::Error;
+
+    fn new_digest_calculator(&self) -> Result<Self::DigestCalculator, Self::Error> {
+        let mut leaf_digest_calculator = self.leaf_hasher.new_digest_calculator()?;
+        leaf_digest_calculator
+            .write_all(&[LEAF_PREFIX])
+            .expect("writing to a new digest calculator succeeds");
+
+        Ok(MerkleDigestCalculator {
+            leaf_hasher: self.leaf_hasher.clone(),
+            leaf_size: self.leaf_size,
+            proof_path: self.proof_path.clone(),
+            leaf_digests: Vec::new(),
+            leaf_digest_calculator,
+            byte_count: 0,
+        })
+    }
+
+    fn needs_message(&self) -> bool {
+        self.proof_path.is_some()
+    }
+}
+
+impl<LeafHasher> RestoreDigestCalculator for MerkleMessageDigestService<LeafHasher>
+where
+    LeafHasher: RestoreDigestCalculator,
+    LeafHasher::DigestCalculator: Write,
+{
+    /// Restores a state saved by [MerkleDigestCalculator::save_digest_state]
+    /// with the same leaf size
+    fn restore_digest_calculator(&self, digest_state: &[u8]) -> Option<Self::DigestCalculator> {
+        let (leaf_size, digest_state) = digest_state.split_first_chunk::<8>()?;
+        let (byte_count, digest_state) = digest_state.split_first_chunk::<8>()?;
+        let (leaf_digest_length, digest_state) = digest_state.split_first_chunk::<4>()?;
+        let (leaf_count, digest_state) = digest_state.split_first_chunk::<4>()?;
+        let leaf_digest_length = usize::try_from(u32::from_be_bytes(*leaf_digest_length)).ok()?;
+        let leaf_count = usize::try_from(u32::from_be_bytes(*leaf_count)).ok()?;
+        let (leaf_digests, leaf_digest_state) =
+            digest_state.split_at_checked(leaf_digest_length.checked_mul(leaf_count)?)?;
+
+        let byte_count = u64::from_be_bytes(*byte_count);
+        let leaf_byte_count =
+            byte_count.checked_sub(self.leaf_size.checked_mul(leaf_count as u64)?)?;
+        if u64::from_be_bytes(*leaf_size) != self.leaf_size || leaf_byte_count >= self.leaf_size {
+            return None;
+        }
+
+        Some(MerkleDigestCalculator {
+            leaf_hasher: self.leaf_hasher.clone(),
+            leaf_size: self.leaf_size,
+            proof_path: self.proof_path.clone(),
+            leaf_digests: leaf_digests
+                .chunks(leaf_digest_length.max(1))
+                .map(<[u8]>::to_vec)
+                .collect(),
+            leaf_digest_calculator: self
+                .leaf_hasher
+                .restore_digest_calculator(leaf_digest_state)?,
+            byte_count,
+        })
+    }
+}
+
+impl<LeafHasher> DigestAlgorithmName for MerkleMessageDigestService<LeafHasher> {
+    /// Returns `MERKLE-`, the leaf hasher's algorithm name, and the leaf
+    /// size (e.g., `MERKLE-SHA3-256-1048576`)
+    fn digest_algorithm_name(&self) -> &str {
+        &self.algorithm_name
+    }
+}
+
+impl<LeafHasher> MessageDigestService for MerkleMessageDigestService<LeafHasher>
+where
+    LeafHasher: NewDigestCalculator + DigestAlgorithmName,
+    LeafHasher::Error: std::error::Error + Send + Sync + 'static,
+    LeafHasher::DigestCalculator: IntoDigestOctets + Write,
+    <LeafHasher::DigestCalculator as IntoDigestOctets>::DigestOctets: AsRef<[u8]>,
+    <LeafHasher::DigestCalculator as IntoDigestOctets>::Error:
+        std::error::Error + Send + Sync + 'static,
+{
+}
+
+/// A digest calculator returned by [MerkleMessageDigestService]
+///
+/// It keeps the digests of the finished leaves, and hashes the current leaf
+/// as it's written.
+pub struct MerkleDigestCalculator<LeafHasher: NewDigestCalculator> {
+    leaf_hasher: Arc<LeafHasher>,
+    leaf_size: u64,
+    proof_path: Option<PathBuf>,
+    leaf_digests: Vec<Vec<u8>>,
+    leaf_digest_calculator: LeafHasher::DigestCalculator,
+    byte_count: u64,
+}
+
+impl<LeafHasher> MerkleDigestCalculator<LeafHasher>
+where
+    LeafHasher: NewDigestCalculator,
+    LeafHasher::Error: std::error::Error + Send + Sync + 'static,
+    LeafHasher::DigestCalculator: IntoDigestOctets + Write,
+    <LeafHasher::DigestCalculator as IntoDigestOctets>::DigestOctets: AsRef<[u8]>,
+    <LeafHasher::DigestCalculator as IntoDigestOctets>::Error:
+        std::error::Error + Send + Sync + 'static,
+{
+    /// Returns the number of octets written to the current leaf
+    fn leaf_byte_count(&self) -> io::Result<u64> {
+        self.leaf_size
+            .checked_mul(self.leaf_digests.len() as u64)
+            .and_then(|leaves_byte_count| self.byte_count.checked_sub(leaves_byte_count))
+            .ok_or_else(|| io::Error::other("the Merkle tree leaves overflow the message size"))
+    }
+
+    fn finish_leaf(&mut self) -> io::Result<()> {
+        let mut next_leaf_digest_calculator = self
+            .leaf_hasher
+            .new_digest_calculator()
+            .map_err(io::Error::other)?;
+        next_leaf_digest_calculator.write_all(&[LEAF_PREFIX])?;
+        let leaf_digest_calculator = std::mem::replace(
+            &mut self.leaf_digest_calculator,
+            next_leaf_digest_calculator,
+        );
+        let leaf_digest = leaf_digest_calculator
+            .into_digest_octets()
+            .map_err(io::Error::other)?;
+        self.leaf_digests.push(leaf_digest.as_ref().to_vec());
+
+        Ok(())
+    }
+}
+
+impl<LeafHasher> Write for MerkleDigestCalculator<LeafHasher>
+where
+    LeafHasher: NewDigestCalculator,
+    LeafHasher::Error: std::error::Error + Send + Sync + 'static,
+    LeafHasher::DigestCalculator: IntoDigestOctets + Write,
+    <LeafHasher::DigestCalculator as IntoDigestOctets>::DigestOctets: AsRef<[u8]>,
+    <LeafHasher::DigestCalculator as IntoDigestOctets>::Error:
+        std::error::Error + Send + Sync + 'static,
+{
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        let mut unwritten_data = data;
+        while !unwritten_data.is_empty() {
+            let leaf_capacity = self.leaf_size - self.leaf_byte_count()?;
+            let length = usize::try_from(leaf_capacity)
+                .unwrap_or(usize::MAX)
+                .min(unwritten_data.len());
+            let (leaf_data, rest) = unwritten_data.split_at(length);
+            self.leaf_digest_calculator.write_all(leaf_data)?;
+            self.byte_count += length as u64;
+
+            if self.leaf_byte_count()? == self.leaf_size {
+                self.finish_leaf()?;
+            }
+            unwritten_data = rest;
+        }
+
+        Ok(data.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.leaf_digest_calculator.flush()
+    }
+}
+
+impl<LeafHasher> IntoDigestOctets for MerkleDigestCalculator<LeafHasher>
+where
+    LeafHasher: NewDigestCalculator + DigestAlgorithmName,
+    LeafHasher::Error: std::error::Error + Send + Sync + 'static,
+    LeafHasher::DigestCalculator: IntoDigestOctets + Write,
+    <LeafHasher::DigestCalculator as IntoDigestOctets>::DigestOctets: AsRef<[u8]>,
+    <LeafHasher::DigestCalculator as IntoDigestOctets>::Error:
+        std::error::Error + Send + Sync + 'static,
+{
+    type DigestOctets = MerkleDigest;
+    type Error = io::Error;
+
+    /// Returns the root digest, and writes the proof file, when the service
+    /// has one
+    fn into_digest_octets(mut self) -> Result<Self::DigestOctets, Self::Error> {
+        if self.leaf_byte_count()? > 0 || self.leaf_digests.is_empty() {
+            self.finish_leaf()?;
+        }
+        let root = merkle_root(&*self.leaf_hasher, &self.leaf_digests)?;
+
+        if let Some(proof_path) = &self.proof_path {
+            MerkleProof {
+                algorithm: self.leaf_hasher.digest_algorithm_name().to_string(),
+                leaf_size: self.leaf_size,
+                size: self.byte_count,
+                root: hex::encode(&root),
+                leaves: self.leaf_digests.iter().map(hex::encode).collect(),
+            }
+            .save(proof_path)?;
+        }
+
+        Ok(MerkleDigest {
+            root,
+            proof_file: self.proof_path,
+        })
+    }
+}
+
+impl<LeafHasher> SaveDigestState for MerkleDigestCalculator<LeafHasher>
+where
+    LeafHasher: NewDigestCalculator,
+    LeafHasher::DigestCalculator: SaveDigestState,
+{
+    /// Returns the leaf size, and the number of written octets as 8-octet
+    /// big-endian numbers, the leaf digest length, and the number of leaf
+    /// digests as 4-octet big-endian numbers, the leaf digests, and the state
+    /// of the current leaf's digest calculator
+    fn save_digest_state(&self) -> Option<Vec<u8>> {
+        let leaf_digest_length = self.leaf_digests.first().map_or(0, Vec::len);
+        let mut digest_state = Vec::new();
+        digest_state.extend_from_slice(&self.leaf_size.to_be_bytes());
+        digest_state.extend_from_slice(&self.byte_count.to_be_bytes());
+        digest_state.extend_from_slice(&u32::try_from(leaf_digest_length).ok()?.to_be_bytes());
+        digest_state.extend_from_slice(&u32::try_from(self.leaf_digests.len()).ok()?.to_be_bytes());
+        for leaf_digest in &self.leaf_digests {
+            digest_state.extend_from_slice(leaf_digest);
+        }
+        digest_state.extend_from_slice(&self.leaf_digest_calculator.save_digest_state()?);
+
+        Some(digest_state)
+    }
+}
+
+/// The root digest of a Merkle tree, and the path of the proof file written
+/// with it, if any
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MerkleDigest {
+    pub root: Vec<u8>,
+    pub proof_file: Option<PathBuf>,
+}
+
+impl AsRef<[u8]> for MerkleDigest {
+    fn as_ref(&self) -> &[u8] {
+        &self.root
+    }
+}
+
+/// Returns the leaf digest of a leaf
+pub fn merkle_leaf_digest<LeafHasher>(leaf_hasher: &LeafHasher, leaf: &[u8]) -> io::Result<Vec<u8>>
+where
+    LeafHasher: NewDigestCalculator,
+    LeafHasher::Error: std::error::Error + Send + Sync + 'static,
+    LeafHasher::DigestCalculator: IntoDigestOctets + Write,
+    <LeafHasher::DigestCalculator as IntoDigestOctets>::DigestOctets: AsRef<[u8]>,
+    <LeafHasher::DigestCalculator as IntoDigestOctets>::Error:
+        std::error::Error + Send + Sync + 'static,
+{
+    hash_parts(leaf_hasher, &[&[LEAF_PREFIX], leaf])
+}
+
+/// Returns the root digest of a Merkle tree with the given leaf digests
+///
+/// It's an error, when there are no leaf digests.
+pub fn merkle_root<LeafHasher>(
+    leaf_hasher: &LeafHasher,
+    leaf_digests: &[Vec<u8>],
+) -> io::Result<Vec<u8>>
+where
+    LeafHasher: NewDigestCalculator,
+    LeafHasher::Error: std::error::Error + Send + Sync + 'static,
+    LeafHasher::DigestCalculator: IntoDigestOctets + Write,
+    <LeafHasher::DigestCalculator as IntoDigestOctets>::DigestOctets: AsRef<[u8]>,
+    <LeafHasher::DigestCalculator as IntoDigestOctets>::Error:
+        std::error::Error + Send + Sync + 'static,
+{
+    let mut level = leaf_digests.to_vec();
+    while level.len() > 1 {
+        level = level
+            .chunks(2)
+            .map(|nodes| match nodes {
+                [left, right] => hash_parts(leaf_hasher, &[&[NODE_PREFIX], left, right]),
+                [node] => Ok(node.clone()),
+                _ => unreachable!("chunks have one, or two nodes"),
+            })
+            .collect::<io::Result<_>>()?;
+    }
+
+    level.pop().ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "a Merkle tree has at least one leaf",
+        )
+    })
+}
+
+fn hash_parts<LeafHasher>(leaf_hasher: &LeafHasher, parts: &[&[u8]]) -> io::Result<Vec<u8>>
+where
+    LeafHasher: NewDigestCalculator,
+    LeafHasher::Error: std::error::Error + Send + Sync + 'static,
+    LeafHasher::DigestCalculator: IntoDigestOctets + Write,
+    <LeafHasher::DigestCalculator as IntoDigestOctets>::DigestOctets: AsRef<[u8]>,
+    <LeafHasher::DigestCalculator as IntoDigestOctets>::Error:
+        std::error::Error + Send + Sync + 'static,
+{
+    let mut digest_calculator = leaf_hasher
+        .new_digest_calculator()
+        .map_err(io::Error::other)?;
+    for part in parts {
+        digest_calculator.write_all(part)?;
+    }
+    let digest = digest_calculator
+        .into_digest_octets()
+        .map_err(io::Error::other)?;
+
+    Ok(digest.as_ref().to_vec())
+}
@@ -0,0 +1,223 @@
+use super::interface::{IntoDigestOctets, NewDigestCalculator};
+use super::merkle_message_digest_service::{merkle_leaf_digest, merkle_root};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::ops::Range;
+use std::path::Path;
+
+/// The leaf digests of a Merkle tree, kept in a JSON proof file, so that
+/// byte ranges of a message can be checked against the tree's root without
+/// hashing the whole message
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct MerkleProof {
+    /// The algorithm name of the leaf hasher
+    pub algorithm: String,
+    /// The size of leaves in octets
+    pub leaf_size: u64,
+    /// The size of the message in octets
+    pub size: u64,
+    /// The hexadecimal root digest
+    pub root: String,
+    /// The hexadecimal leaf digests
+    pub leaves: Vec<String>,
+}
+
+/// The outcome of checking a byte range against a Merkle tree's root
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ChunkVerification {
+    /// The leaves covering the byte range match
+    Verified { leaves: Range<u64> },
+    /// The proof's leaf digests don't add up to the expected root
+    ProofMismatch,
+    /// The leaf with this index doesn't match its leaf digest
+    LeafMismatch { leaf: u64 },
+}
+
+impl ChunkVerification {
+    pub fn passed(&self) -> bool {
+        matches!(self, ChunkVerification::Verified { .. })
+    }
+
+    /// Writes `OK`, or `FAILED` for the byte range of the file named
+    /// `file_name`, as `sha3sum --check` does, and the reason of a failure
+    /// into `diagnostics`
+    pub fn write_report(
+        &self,
+        writer: &mut impl Write,
+        diagnostics: &mut impl Write,
+        proof_name: &str,
+        file_name: &str,
+        offset: u64,
+        length: u64,
+    ) -> io::Result<()> {
+        let range = format!("{}: bytes {}-{}", file_name, offset, offset + length - 1);
+
+        match self {
+            ChunkVerification::Verified { leaves } => {
+                let leaf_count = leaves.end - leaves.start;
+
+                return writeln!(
+                    writer,
+                    "{}: OK ({} {})",
+                    range,
+                    leaf_count,
+                    if leaf_count == 1 { "leaf" } else { "leaves" }
+                );
+            }
+            ChunkVerification::ProofMismatch => writeln!(
+                diagnostics,
+                "{}: the proof doesn't match the root digest",
+                proof_name
+            )?,
+            ChunkVerification::LeafMismatch { leaf } => writeln!(
+                diagnostics,
+                "{}: leaf {} doesn't match the proof",
+                range, leaf
+            )?,
+        }
+
+        writeln!(writer, "{}: FAILED", range)
+    }
+}
+
+impl MerkleProof {
+    pub fn load(path: &Path) -> io::Result<Self> {
+        Ok(serde_json::from_slice(&fs::read(path)?)?)
+    }
+
+    /// Replaces a proof file
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let mut partial_path = path.as_os_str().to_owned();
+        partial_path.push(".partial");
+
+        fs::write(&partial_path, serde_json::to_vec_pretty(self)?)?;
+        fs::rename(partial_path, path)
+    }
+
+    /// Returns the indices of the leaves covering a non-empty byte range, or
+    /// `None`, when it isn't within the message
+    pub fn covering_leaves(&self, offset: u64, length: u64) -> Option<Range<u64>> {
+        let end = offset.checked_add(length)?;
+        if length == 0 || end > self.size {
+            return None;
+        }
+
+        Some(offset / self.leaf_size..(end - 1) / self.leaf_size + 1)
+    }
+
+    /// Checks a byte range of a message read from `message` against a root
+    /// digest
+    ///
+    /// The proof's leaf digests are first checked against the root, and then
+    /// only the leaves covering the byte range are read, and hashed.
+    pub fn verify_chunk<LeafHasher>(
+        &self,
+        leaf_hasher: &LeafHasher,
+        root: &[u8],
+        mut message: impl Read + Seek,
+        offset: u64,
+        length: u64,
+    ) -> io::Result<ChunkVerification>
+    where
+        LeafHasher: NewDigestCalculator,
+        LeafHasher::Error: std::error::Error + Send + Sync + 'static,
+        LeafHasher::DigestCalculator: IntoDigestOctets + Write,
+        <LeafHasher::DigestCalculator as IntoDigestOctets>::DigestOctets: AsRef<[u8]>,
+        <LeafHasher::DigestCalculator as IntoDigestOctets>::Error:
+            std::error::Error + Send + Sync + 'static,
+    {
+        let leaves = self.covering_leaves(offset, length).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "the byte range isn't within the {} octets of the message",
+                    self.size
+                ),
+            )
+        })?;
+        let leaf_digests = self
+            .leaves
+            .iter()
+            .map(hex::decode)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+        if self.leaf_size == 0
+            || leaf_digests.len() as u64 != self.size.div_ceil(self.leaf_size).max(1)
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "the number of leaf digests doesn't match the message size",
+            ));
+        }
+
+        if merkle_root(leaf_hasher, &leaf_digests)? != root {
+            return Ok(ChunkVerification::ProofMismatch);
+        }
+
+        message.seek(SeekFrom::Start(leaves.start * self.leaf_size))?;
+        for leaf in leaves.clone() {
+            let leaf_end = ((leaf + 1) * self.leaf_size).min(self.size);
+            let mut leaf_data = Vec::new();
+            (&mut message)
+                .take(leaf_end - leaf * self.leaf_size)
+                .read_to_end(&mut leaf_data)?;
+            if (leaf_data.len() as u64) < leaf_end - leaf * self.leaf_size
+                || merkle_leaf_digest(leaf_hasher, &leaf_data)? != leaf_digests[leaf as usize]
+            {
+                return Ok(ChunkVerification::LeafMismatch { leaf });
+            }
+        }
+
+        Ok(ChunkVerification::Verified { leaves })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report(chunk_verification: ChunkVerification) -> (String, String) {
+        let mut writer = Vec::new();
+        let mut diagnostics = Vec::new();
+
+        chunk_verification
+            .write_report(&mut writer, &mut diagnostics, "proof.json", "body", 4, 8)
+            .unwrap();
+
+        (
+            String::from_utf8(writer).unwrap(),
+            String::from_utf8(diagnostics).unwrap(),
+        )
+    }
+
+    #[test]
+    fn chunk_verifications_are_reported_as_sha3sum_does() {
+        assert_eq!(
+            report(ChunkVerification::Verified { leaves: 1..3 }),
+            (
+                "body: bytes 4-11: OK (2 leaves)\n".to_string(),
+                String::new()
+            )
+        );
+        assert_eq!(
+            report(ChunkVerification::Verified { leaves: 1..2 }),
+            ("body: bytes 4-11: OK (1 leaf)\n".to_string(), String::new())
+        );
+        assert_eq!(
+            report(ChunkVerification::ProofMismatch),
+            (
+                "body: bytes 4-11: FAILED\n".to_string(),
+                "proof.json: the proof doesn't match the root digest\n".to_string()
+            )
+        );
+        assert_eq!(
+            report(ChunkVerification::LeafMismatch { leaf: 2 }),
+            (
+                "body: bytes 4-11: FAILED\n".to_string(),
+                "body: bytes 4-11: leaf 2 doesn't match the proof\n".to_string()
+            )
+        );
+        assert!(!ChunkVerification::ProofMismatch.passed());
+    }
+}
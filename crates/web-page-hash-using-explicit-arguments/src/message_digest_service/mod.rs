@@ -1,5 +1,17 @@
+pub mod fan_out_message_digest_service;
+pub use fan_out_message_digest_service::*;
+
 pub mod interface;
 pub use interface::*;
 
+pub mod merkle_message_digest_service;
+pub use merkle_message_digest_service::*;
+
+pub mod merkle_proof;
+pub use merkle_proof::*;
+
+pub mod selectable_message_digest_service;
+pub use selectable_message_digest_service::*;
+
 pub mod sha3_message_digest_service;
 pub use sha3_message_digest_service::*;
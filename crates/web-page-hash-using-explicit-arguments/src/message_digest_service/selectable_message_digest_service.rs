@@ -0,0 +1,264 @@
+use super::interface::{
+    DigestAlgorithmName, IntoDigestOctets, MessageDigestService, NewDigestCalculator,
+    RestoreDigestCalculator, SaveDigestState,
+};
+use serde::Deserialize;
+use sha2::Digest;
+use sha2::digest::common::hazmat::SerializableState;
+use std::convert::Infallible;
+use std::fmt;
+use std::str::FromStr;
+
+/// Message digest algorithms that [SelectableMessageDigestService] can
+/// calculate
+///
+/// It's deserialized from any name that [MessageDigestAlgorithm::from_str]
+/// recognizes.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq)]
+#[serde(try_from = "String")]
+pub enum MessageDigestAlgorithm {
+    Sha3_256,
+    Sha256,
+    Sha384,
+    Sha512,
+    Blake3,
+}
+
+impl MessageDigestAlgorithm {
+    pub const ALL: [MessageDigestAlgorithm; 5] = [
+        MessageDigestAlgorithm::Sha3_256,
+        MessageDigestAlgorithm::Sha256,
+        MessageDigestAlgorithm::Sha384,
+        MessageDigestAlgorithm::Sha512,
+        MessageDigestAlgorithm::Blake3,
+    ];
+
+    /// Returns the BSD-tag style name of the algorithm (e.g., `SHA3-256`)
+    pub fn name(self) -> &'static str {
+        match self {
+            MessageDigestAlgorithm::Sha3_256 => "SHA3-256",
+            MessageDigestAlgorithm::Sha256 => "SHA256",
+            MessageDigestAlgorithm::Sha384 => "SHA384",
+            MessageDigestAlgorithm::Sha512 => "SHA512",
+            MessageDigestAlgorithm::Blake3 => "BLAKE3",
+        }
+    }
+
+    /// Returns a human-readable description of the algorithm (e.g.,
+    /// `256-bit SHA-3`)
+    pub fn description(self) -> &'static str {
+        match self {
+            MessageDigestAlgorithm::Sha3_256 => "256-bit SHA-3",
+            MessageDigestAlgorithm::Sha256 => "256-bit SHA-2",
+            MessageDigestAlgorithm::Sha384 => "384-bit SHA-2",
+            MessageDigestAlgorithm::Sha512 => "512-bit SHA-2",
+            MessageDigestAlgorithm::Blake3 => "256-bit BLAKE3",
+        }
+    }
+
+    /// Returns the name of the algorithm in Subresource Integrity metadata
+    ///
+    /// Only SHA-2 digests are allowed in Subresource Integrity metadata.
+    pub fn sri_name(self) -> Option<&'static str> {
+        match self {
+            MessageDigestAlgorithm::Sha3_256 => None,
+            MessageDigestAlgorithm::Sha256 => Some("sha256"),
+            MessageDigestAlgorithm::Sha384 => Some("sha384"),
+            MessageDigestAlgorithm::Sha512 => Some("sha512"),
+            MessageDigestAlgorithm::Blake3 => None,
+        }
+    }
+
+    /// Returns the length of the algorithm's message digests in octets
+    pub fn digest_length(self) -> usize {
+        match self {
+            MessageDigestAlgorithm::Sha3_256 => 32,
+            MessageDigestAlgorithm::Sha256 => 32,
+            MessageDigestAlgorithm::Sha384 => 48,
+            MessageDigestAlgorithm::Sha512 => 64,
+            MessageDigestAlgorithm::Blake3 => 32,
+        }
+    }
+}
+
+impl fmt::Display for MessageDigestAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for MessageDigestAlgorithm {
+    type Err = UnknownMessageDigestAlgorithm;
+
+    /// Parses an algorithm name ignoring case, and dashes, so that, e.g.,
+    /// `sha3-256`, `SHA3_256`, `sha256`, `SHA-256`, and `blake3` are all
+    /// recognized
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        let normalized_name = name.to_ascii_lowercase().replace(['-', '_'], "");
+
+        match normalized_name.as_str() {
+            "sha3256" => Ok(MessageDigestAlgorithm::Sha3_256),
+            "sha256" | "sha2256" => Ok(MessageDigestAlgorithm::Sha256),
+            "sha384" | "sha2384" => Ok(MessageDigestAlgorithm::Sha384),
+            "sha512" | "sha2512" => Ok(MessageDigestAlgorithm::Sha512),
+            "blake3" => Ok(MessageDigestAlgorithm::Blake3),
+            _ => Err(UnknownMessageDigestAlgorithm(name.to_string())),
+        }
+    }
+}
+
+impl TryFrom<String> for MessageDigestAlgorithm {
+    type Error = UnknownMessageDigestAlgorithm;
+
+    fn try_from(name: String) -> Result<Self, Self::Error> {
+        name.parse()
+    }
+}
+
+#[derive(Debug)]
+pub struct UnknownMessageDigestAlgorithm(pub String);
+
+impl fmt::Display for UnknownMessageDigestAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown message digest algorithm: {}", self.0)
+    }
+}
+
+impl std::error::Error for UnknownMessageDigestAlgorithm {}
+
+/// A digest calculator for a [MessageDigestAlgorithm] selected at run time
+#[derive(Clone, Debug)]
+pub enum SelectableDigestCalculator {
+    Sha3_256(sha3::Sha3_256),
+    Sha256(sha2::Sha256),
+    Sha384(sha2::Sha384),
+    Sha512(sha2::Sha512),
+    Blake3(Box<blake3::Hasher>),
+}
+
+impl SelectableDigestCalculator {
+    pub fn new(algorithm: MessageDigestAlgorithm) -> Self {
+        match algorithm {
+            MessageDigestAlgorithm::Sha3_256 => {
+                SelectableDigestCalculator::Sha3_256(sha3::Sha3_256::new())
+            }
+            MessageDigestAlgorithm::Sha256 => {
+                SelectableDigestCalculator::Sha256(sha2::Sha256::new())
+            }
+            MessageDigestAlgorithm::Sha384 => {
+                SelectableDigestCalculator::Sha384(sha2::Sha384::new())
+            }
+            MessageDigestAlgorithm::Sha512 => {
+                SelectableDigestCalculator::Sha512(sha2::Sha512::new())
+            }
+            MessageDigestAlgorithm::Blake3 => {
+                SelectableDigestCalculator::Blake3(Box::new(blake3::Hasher::new()))
+            }
+        }
+    }
+}
+
+impl std::io::Write for SelectableDigestCalculator {
+    fn write(&mut self, data: &[u8]) -> std::io::Result<usize> {
+        match self {
+            SelectableDigestCalculator::Sha3_256(calculator) => calculator.update(data),
+            SelectableDigestCalculator::Sha256(calculator) => calculator.update(data),
+            SelectableDigestCalculator::Sha384(calculator) => calculator.update(data),
+            SelectableDigestCalculator::Sha512(calculator) => calculator.update(data),
+            SelectableDigestCalculator::Blake3(calculator) => {
+                calculator.update(data);
+            }
+        }
+
+        Ok(data.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl IntoDigestOctets for SelectableDigestCalculator {
+    type DigestOctets = Vec<u8>;
+    type Error = Infallible;
+
+    fn into_digest_octets(self) -> Result<Self::DigestOctets, Self::Error> {
+        Ok(match self {
+            SelectableDigestCalculator::Sha3_256(calculator) => calculator.finalize().to_vec(),
+            SelectableDigestCalculator::Sha256(calculator) => calculator.finalize().to_vec(),
+            SelectableDigestCalculator::Sha384(calculator) => calculator.finalize().to_vec(),
+            SelectableDigestCalculator::Sha512(calculator) => calculator.finalize().to_vec(),
+            SelectableDigestCalculator::Blake3(calculator) => {
+                calculator.finalize().as_bytes().to_vec()
+            }
+        })
+    }
+}
+
+impl SaveDigestState for SelectableDigestCalculator {
+    /// Returns [None] for BLAKE3, whose state can't be saved
+    fn save_digest_state(&self) -> Option<Vec<u8>> {
+        match self {
+            SelectableDigestCalculator::Sha3_256(calculator) => {
+                Some(calculator.serialize().to_vec())
+            }
+            SelectableDigestCalculator::Sha256(calculator) => Some(calculator.serialize().to_vec()),
+            SelectableDigestCalculator::Sha384(calculator) => Some(calculator.serialize().to_vec()),
+            SelectableDigestCalculator::Sha512(calculator) => Some(calculator.serialize().to_vec()),
+            SelectableDigestCalculator::Blake3(_) => None,
+        }
+    }
+}
+
+/// A message digest service whose algorithm is selected at run time
+#[derive(Debug)]
+pub struct SelectableMessageDigestService {
+    algorithm: MessageDigestAlgorithm,
+}
+
+impl SelectableMessageDigestService {
+    pub fn new(algorithm: MessageDigestAlgorithm) -> Self {
+        SelectableMessageDigestService { algorithm }
+    }
+
+    pub fn algorithm(&self) -> MessageDigestAlgorithm {
+        self.algorithm
+    }
+}
+
+impl NewDigestCalculator for SelectableMessageDigestService {
+    type DigestCalculator = SelectableDigestCalculator;
+    type Error = Infallible;
+
+    fn new_digest_calculator(&self) -> Result<Self::DigestCalculator, Self::Error> {
+        Ok(SelectableDigestCalculator::new(self.algorithm))
+    }
+}
+
+impl RestoreDigestCalculator for SelectableMessageDigestService {
+    fn restore_digest_calculator(&self, digest_state: &[u8]) -> Option<Self::DigestCalculator> {
+        match self.algorithm {
+            MessageDigestAlgorithm::Sha3_256 => Some(SelectableDigestCalculator::Sha3_256(
+                sha3::Sha3_256::deserialize(digest_state.try_into().ok()?).ok()?,
+            )),
+            MessageDigestAlgorithm::Sha256 => Some(SelectableDigestCalculator::Sha256(
+                sha2::Sha256::deserialize(digest_state.try_into().ok()?).ok()?,
+            )),
+            MessageDigestAlgorithm::Sha384 => Some(SelectableDigestCalculator::Sha384(
+                sha2::Sha384::deserialize(digest_state.try_into().ok()?).ok()?,
+            )),
+            MessageDigestAlgorithm::Sha512 => Some(SelectableDigestCalculator::Sha512(
+                sha2::Sha512::deserialize(digest_state.try_into().ok()?).ok()?,
+            )),
+            MessageDigestAlgorithm::Blake3 => None,
+        }
+    }
+}
+
+impl DigestAlgorithmName for SelectableMessageDigestService {
+    fn digest_algorithm_name(&self) -> &str {
+        self.algorithm.name()
+    }
+}
+
+impl MessageDigestService for SelectableMessageDigestService {}
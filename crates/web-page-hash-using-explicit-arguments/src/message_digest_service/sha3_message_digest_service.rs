@@ -1,14 +1,41 @@
-use super::interface::{IntoDigestOctets, MessageDigestService, NewDigestCalculator};
+use super::interface::{
+    DigestAlgorithmName, IntoDigestOctets, MessageDigestService, NewDigestCalculator,
+    RestoreDigestCalculator, SaveDigestState,
+};
 use sha3;
 use sha3::Digest;
+use sha3::digest::common::hazmat::SerializableState;
 use std::convert::Infallible;
+use std::io;
 
-impl IntoDigestOctets for sha3::Sha3_256 {
+/// A 256-bit SHA-3 calculator to which message data can be written
+#[derive(Clone, Debug, Default)]
+pub struct Sha3_256DigestCalculator(sha3::Sha3_256);
+
+impl io::Write for Sha3_256DigestCalculator {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        self.0.update(data);
+
+        Ok(data.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl IntoDigestOctets for Sha3_256DigestCalculator {
     type DigestOctets = [u8; 32];
     type Error = Infallible;
 
     fn into_digest_octets(self) -> Result<Self::DigestOctets, Self::Error> {
-        Ok(sha3::Sha3_256::finalize(self).into())
+        Ok(self.0.finalize().into())
+    }
+}
+
+impl SaveDigestState for Sha3_256DigestCalculator {
+    fn save_digest_state(&self) -> Option<Vec<u8>> {
+        Some(self.0.serialize().to_vec())
     }
 }
 
@@ -22,11 +49,27 @@ impl Sha3_256BitMessageDigestService {
 }
 
 impl NewDigestCalculator for Sha3_256BitMessageDigestService {
-    type DigestCalculator = sha3::Sha3_256;
+    type DigestCalculator = Sha3_256DigestCalculator;
     type Error = Infallible;
 
     fn new_digest_calculator(&self) -> Result<Self::DigestCalculator, Self::Error> {
-        Ok(sha3::Sha3_256::new())
+        Ok(Sha3_256DigestCalculator::default())
+    }
+}
+
+impl RestoreDigestCalculator for Sha3_256BitMessageDigestService {
+    fn restore_digest_calculator(&self, digest_state: &[u8]) -> Option<Self::DigestCalculator> {
+        let digest_state = digest_state.try_into().ok()?;
+
+        sha3::Sha3_256::deserialize(digest_state)
+            .ok()
+            .map(Sha3_256DigestCalculator)
+    }
+}
+
+impl DigestAlgorithmName for Sha3_256BitMessageDigestService {
+    fn digest_algorithm_name(&self) -> &str {
+        "SHA3-256"
     }
 }
 
@@ -0,0 +1,11 @@
+pub mod interface;
+pub use interface::*;
+
+pub mod indicatif_progress_reporter;
+pub use indicatif_progress_reporter::*;
+
+pub mod no_progress_reporter;
+pub use no_progress_reporter::*;
+
+pub mod recording_progress_reporter;
+pub use recording_progress_reporter::*;
@@ -0,0 +1,25 @@
+use super::interface::{ProgressReporter, ReportBytesWritten, ReportFinished, ReportStarted};
+
+/// A [ProgressReporter] that doesn't report anything
+#[derive(Debug, Default)]
+pub struct NoProgressReporter {}
+
+impl NoProgressReporter {
+    pub fn new() -> Self {
+        NoProgressReporter {}
+    }
+}
+
+impl ReportStarted for NoProgressReporter {
+    fn report_started(&self, _url: &str, _expected_length: Option<u64>) {}
+}
+
+impl ReportBytesWritten for NoProgressReporter {
+    fn report_bytes_written(&self, _byte_count: u64) {}
+}
+
+impl ReportFinished for NoProgressReporter {
+    fn report_finished(&self) {}
+}
+
+impl ProgressReporter for NoProgressReporter {}
@@ -0,0 +1,68 @@
+use super::interface::{ProgressReporter, ReportBytesWritten, ReportFinished, ReportStarted};
+use std::sync::{Arc, Mutex, PoisonError};
+
+/// A call to a [ProgressReporter] function
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ProgressEvent {
+    Started {
+        url: String,
+        expected_length: Option<u64>,
+    },
+    BytesWritten(u64),
+    Finished,
+}
+
+/// A [ProgressReporter] that records reported events, e.g., for checking
+/// them in tests
+///
+/// Clones share the recorded events.
+#[derive(Clone, Debug, Default)]
+pub struct RecordingProgressReporter {
+    events: Arc<Mutex<Vec<ProgressEvent>>>,
+}
+
+impl RecordingProgressReporter {
+    pub fn new() -> Self {
+        RecordingProgressReporter {
+            events: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    /// Returns the events reported so far, in order
+    pub fn events(&self) -> Vec<ProgressEvent> {
+        self.events
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    fn record(&self, event: ProgressEvent) {
+        self.events
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push(event);
+    }
+}
+
+impl ReportStarted for RecordingProgressReporter {
+    fn report_started(&self, url: &str, expected_length: Option<u64>) {
+        self.record(ProgressEvent::Started {
+            url: url.to_string(),
+            expected_length,
+        });
+    }
+}
+
+impl ReportBytesWritten for RecordingProgressReporter {
+    fn report_bytes_written(&self, byte_count: u64) {
+        self.record(ProgressEvent::BytesWritten(byte_count));
+    }
+}
+
+impl ReportFinished for RecordingProgressReporter {
+    fn report_finished(&self) {
+        self.record(ProgressEvent::Finished);
+    }
+}
+
+impl ProgressReporter for RecordingProgressReporter {}
@@ -0,0 +1,34 @@
+use super::interface::{CheckBodySize, CheckResponseMetadata, ResponsePolicy};
+use crate::http_client_service::ResponseMetadata;
+use std::convert::Infallible;
+
+/// A [ResponsePolicy] accepting all responses
+#[derive(Debug, Default)]
+pub struct NoResponsePolicy {}
+
+impl NoResponsePolicy {
+    pub fn new() -> Self {
+        NoResponsePolicy {}
+    }
+}
+
+impl CheckResponseMetadata for NoResponsePolicy {
+    type Error = Infallible;
+
+    fn check_response_metadata(
+        &self,
+        _response: &impl ResponseMetadata,
+    ) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+impl CheckBodySize for NoResponsePolicy {
+    type Error = Infallible;
+
+    fn check_body_size(&self, _byte_count: u64) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+impl ResponsePolicy for NoResponsePolicy {}
@@ -0,0 +1,363 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::ops::Range;
+
+/// The kinds of subresources that Subresource Integrity metadata applies to
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SubresourceKind {
+    /// A `script` element with a `src` attribute
+    Script,
+    /// A `link` element with a `stylesheet` relation
+    Stylesheet,
+}
+
+impl SubresourceKind {
+    /// Returns the name of the kind (e.g., `script`)
+    pub fn name(self) -> &'static str {
+        match self {
+            SubresourceKind::Script => "script",
+            SubresourceKind::Stylesheet => "stylesheet",
+        }
+    }
+}
+
+impl fmt::Display for SubresourceKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// A script, or stylesheet loaded by an HTML document
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct HtmlSubresource {
+    pub kind: SubresourceKind,
+    /// The `src`, or `href` attribute value, with character references
+    /// decoded
+    pub url: String,
+    /// The `integrity` attribute value, if any
+    pub integrity: Option<String>,
+    /// The `crossorigin` attribute value, if any
+    pub crossorigin: Option<String>,
+    /// The position of the `integrity` attribute in the document, if any
+    integrity_span: Option<Range<usize>>,
+    /// The position after the last attribute of the start tag
+    attributes_end: usize,
+}
+
+/// An HTML document, and the scripts, and stylesheets it loads
+///
+/// Start tags are found by a tokenizer following the HTML syntax closely
+/// enough for well-formed documents, instead of a full HTML parser, so that
+/// documents are rewritten without changing anything but the attributes of
+/// the subresources.  Comments, and the contents of `script`, and `style`
+/// elements are skipped.
+#[derive(Clone, Debug)]
+pub struct HtmlSubresources {
+    html: String,
+    base_href: Option<String>,
+    subresources: Vec<HtmlSubresource>,
+}
+
+impl HtmlSubresources {
+    /// Finds the subresources of an HTML document
+    pub fn parse(html: String) -> Self {
+        let mut base_href = None;
+        let mut subresources = Vec::new();
+        let mut position = 0;
+
+        while let Some(offset) = html[position..].find('<') {
+            let start = position + offset;
+            let rest = &html[start..];
+            if let Some(comment) = rest.strip_prefix("<!--") {
+                position = comment
+                    .find("-->")
+                    .map_or(html.len(), |end| start + "<!--".len() + end + "-->".len());
+                continue;
+            }
+            // Other markup than start tags, e.g., end tags, and doctypes, is
+            // skipped:
+            if !rest[1..].starts_with(|c: char| c.is_ascii_alphabetic()) {
+                position = start + 1;
+                continue;
+            }
+
+            let name_end = rest[1..]
+                .find(|c: char| c.is_ascii_whitespace() || c == '/' || c == '>')
+                .map_or(html.len(), |end| start + 1 + end);
+            let name = html[start + 1..name_end].to_ascii_lowercase();
+            let start_tag = StartTag::parse(&html, name_end);
+            position = start_tag.end;
+
+            match name.as_str() {
+                "script" => {
+                    if let Some(src) = start_tag.attribute("src") {
+                        subresources.push(start_tag.subresource(SubresourceKind::Script, src));
+                    }
+                    position = raw_text_end(&html, position, "</script");
+                }
+                "style" => position = raw_text_end(&html, position, "</style"),
+                // Only the first `base` element with a URL sets the base URL:
+                "base" if base_href.is_none() => {
+                    base_href = start_tag
+                        .attribute("href")
+                        .map(|href| href.trim().to_string());
+                }
+                "link" => {
+                    let is_stylesheet = start_tag.attribute("rel").is_some_and(|rel| {
+                        rel.split_ascii_whitespace()
+                            .any(|relation| relation.eq_ignore_ascii_case("stylesheet"))
+                    });
+                    if is_stylesheet && let Some(href) = start_tag.attribute("href") {
+                        subresources.push(start_tag.subresource(SubresourceKind::Stylesheet, href));
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        HtmlSubresources {
+            html,
+            base_href,
+            subresources,
+        }
+    }
+
+    /// Returns the `href` attribute value of the `base` element, which
+    /// overrides the base URL of the document, if any
+    pub fn base_href(&self) -> Option<&str> {
+        self.base_href.as_deref()
+    }
+
+    /// Returns the URL that subresource URLs are relative to, given the URL
+    /// the document was served from
+    ///
+    /// It's the [HtmlSubresources::base_href] resolved against the document
+    /// URL, or else the document URL.
+    pub fn base_url(&self, document_url: &url::Url) -> url::Url {
+        self.base_href()
+            .and_then(|href| document_url.join(href).ok())
+            .unwrap_or_else(|| document_url.clone())
+    }
+
+    /// Returns the subresources in document order
+    pub fn subresources(&self) -> &[HtmlSubresource] {
+        &self.subresources
+    }
+
+    /// Returns the document with `integrity` attributes of its subresources
+    /// set to Subresource Integrity metadata, and `crossorigin` attributes
+    /// added where they're missing
+    ///
+    /// `integrities` holds the metadata of each subresource in document
+    /// order, where [None] leaves the element unchanged.
+    pub fn rewrite(&self, integrities: &[Option<String>], crossorigin: &str) -> String {
+        let mut html = String::with_capacity(self.html.len());
+        let mut position = 0;
+
+        for (subresource, integrity) in self.subresources.iter().zip(integrities) {
+            let Some(integrity) = integrity else {
+                continue;
+            };
+            let mut added_attributes = String::new();
+            match &subresource.integrity_span {
+                Some(integrity_span) => {
+                    html.push_str(&self.html[position..integrity_span.start]);
+                    html.push_str(&format!("integrity=\"{}\"", integrity));
+                    position = integrity_span.end;
+                }
+                None => added_attributes.push_str(&format!(" integrity=\"{}\"", integrity)),
+            }
+            if subresource.crossorigin.is_none() {
+                added_attributes.push_str(&format!(" crossorigin=\"{}\"", crossorigin));
+            }
+            html.push_str(&self.html[position..subresource.attributes_end]);
+            html.push_str(&added_attributes);
+            position = subresource.attributes_end;
+        }
+        html.push_str(&self.html[position..]);
+
+        html
+    }
+}
+
+/// The attributes of a start tag
+struct StartTag {
+    attributes: Vec<Attribute>,
+    /// The position after the last attribute
+    attributes_end: usize,
+    /// The position after the `>`
+    end: usize,
+}
+
+struct Attribute {
+    /// The name in lowercase
+    name: String,
+    /// The value with character references decoded
+    value: String,
+    /// The position of the name, and value
+    span: Range<usize>,
+}
+
+impl StartTag {
+    /// Parses the attributes following the tag name at `position`
+    fn parse(html: &str, mut position: usize) -> Self {
+        let bytes = html.as_bytes();
+        let mut attributes = Vec::new();
+        let mut attributes_end = position;
+
+        loop {
+            while position < bytes.len()
+                && (bytes[position].is_ascii_whitespace() || bytes[position] == b'/')
+            {
+                position += 1;
+            }
+            match bytes.get(position) {
+                None => {
+                    return StartTag {
+                        attributes,
+                        attributes_end,
+                        end: position,
+                    };
+                }
+                Some(b'>') => {
+                    return StartTag {
+                        attributes,
+                        attributes_end,
+                        end: position + 1,
+                    };
+                }
+                Some(_) => {}
+            }
+
+            let name_start = position;
+            // A leading `=` belongs to the name:
+            position += 1;
+            while position < bytes.len()
+                && !bytes[position].is_ascii_whitespace()
+                && !matches!(bytes[position], b'/' | b'>' | b'=')
+            {
+                position += 1;
+            }
+            let name = html[name_start..position].to_ascii_lowercase();
+            let mut span_end = position;
+
+            let mut value_start = position;
+            while value_start < bytes.len() && bytes[value_start].is_ascii_whitespace() {
+                value_start += 1;
+            }
+            let mut value = "";
+            if bytes.get(value_start) == Some(&b'=') {
+                value_start += 1;
+                while value_start < bytes.len() && bytes[value_start].is_ascii_whitespace() {
+                    value_start += 1;
+                }
+                match bytes.get(value_start) {
+                    Some(&quote @ (b'"' | b'\'')) => {
+                        let value_end = html[value_start + 1..]
+                            .find(quote as char)
+                            .map_or(html.len(), |end| value_start + 1 + end);
+                        value = &html[value_start + 1..value_end];
+                        span_end = (value_end + 1).min(html.len());
+                    }
+                    _ => {
+                        let value_end = html[value_start..]
+                            .find(|c: char| c.is_ascii_whitespace() || c == '>')
+                            .map_or(html.len(), |end| value_start + end);
+                        value = &html[value_start..value_end];
+                        span_end = value_end;
+                    }
+                }
+            }
+            position = span_end;
+            attributes_end = span_end;
+
+            attributes.push(Attribute {
+                name,
+                value: decode_character_references(value),
+                span: name_start..span_end,
+            });
+        }
+    }
+
+    /// Returns the value of the first attribute with a name, as browsers
+    /// ignore repeated attributes
+    fn attribute(&self, name: &str) -> Option<&str> {
+        self.find_attribute(name)
+            .map(|attribute| attribute.value.as_str())
+    }
+
+    fn find_attribute(&self, name: &str) -> Option<&Attribute> {
+        self.attributes
+            .iter()
+            .find(|attribute| attribute.name == name)
+    }
+
+    fn subresource(&self, kind: SubresourceKind, url: &str) -> HtmlSubresource {
+        let integrity = self.find_attribute("integrity");
+
+        HtmlSubresource {
+            kind,
+            url: url.trim().to_string(),
+            integrity: integrity.map(|attribute| attribute.value.clone()),
+            crossorigin: self.attribute("crossorigin").map(str::to_string),
+            integrity_span: integrity.map(|attribute| attribute.span.clone()),
+            attributes_end: self.attributes_end,
+        }
+    }
+}
+
+/// Returns the position of the end tag of a `script`, or `style` element,
+/// whose contents aren't markup
+fn raw_text_end(html: &str, position: usize, end_tag: &str) -> usize {
+    // Lowercasing ASCII keeps the positions of characters:
+    html[position..]
+        .to_ascii_lowercase()
+        .find(end_tag)
+        .map_or(html.len(), |end| position + end)
+}
+
+/// Decodes the character references common in URLs, e.g., `&amp;`
+fn decode_character_references(value: &str) -> String {
+    let mut decoded = String::with_capacity(value.len());
+    let mut rest = value;
+
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let reference = rest[1..]
+            .find(';')
+            .map(|end| &rest[1..1 + end])
+            .and_then(|name| {
+                let character = match name {
+                    "amp" => Some('&'),
+                    "quot" => Some('"'),
+                    "apos" => Some('\''),
+                    "lt" => Some('<'),
+                    "gt" => Some('>'),
+                    _ => name
+                        .strip_prefix("#x")
+                        .or_else(|| name.strip_prefix("#X"))
+                        .map(|hex| u32::from_str_radix(hex, 16))
+                        .or_else(|| name.strip_prefix('#').map(str::parse))
+                        .and_then(Result::ok)
+                        .and_then(char::from_u32),
+                };
+
+                character.map(|character| (character, name.len() + 2))
+            });
+        match reference {
+            Some((character, length)) => {
+                decoded.push(character);
+                rest = &rest[length..];
+            }
+            None => {
+                decoded.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    decoded.push_str(rest);
+
+    decoded
+}
@@ -0,0 +1,67 @@
+use crate::digest_encoding::to_sri;
+use crate::message_digest_service::MessageDigestAlgorithm;
+
+/// Algorithms allowed in Subresource Integrity metadata, weakest first
+const SRI_ALGORITHMS: &[MessageDigestAlgorithm] = &[
+    MessageDigestAlgorithm::Sha256,
+    MessageDigestAlgorithm::Sha384,
+    MessageDigestAlgorithm::Sha512,
+];
+
+/// Returns Subresource Integrity metadata listing the digests of a
+/// subresource (e.g., `sha384-<base64> sha512-<base64>`)
+///
+/// Returns [None] if any algorithm isn't allowed in Subresource Integrity
+/// metadata.
+pub fn integrity_metadata(digests: &[(MessageDigestAlgorithm, Vec<u8>)]) -> Option<String> {
+    digests
+        .iter()
+        .map(|(algorithm, digest)| to_sri(*algorithm, digest))
+        .collect::<Option<Vec<_>>>()
+        .map(|hashes| hashes.join(" "))
+}
+
+/// Returns whether Subresource Integrity metadata, e.g., an `integrity`
+/// attribute value, matches the digests of a subresource
+///
+/// As browsers check metadata, only the hashes of the strongest algorithm in
+/// the metadata are compared, and any of them matching is enough.  Options
+/// (e.g., `sha384-<base64>?foo`) are ignored.  Returns [None] if the metadata
+/// has no hash of an allowed algorithm, or the digest of its strongest
+/// algorithm wasn't calculated.
+pub fn integrity_matches(
+    integrity: &str,
+    digests: &[(MessageDigestAlgorithm, Vec<u8>)],
+) -> Option<bool> {
+    let hashes = integrity
+        .split_ascii_whitespace()
+        .filter_map(|hash| {
+            let hash = hash.split('?').next().unwrap_or_default();
+            let (name, _) = hash.split_once('-')?;
+            let algorithm = SRI_ALGORITHMS.iter().copied().find(|algorithm| {
+                algorithm.sri_name() == Some(name.to_ascii_lowercase().as_str())
+            })?;
+
+            Some((algorithm, hash))
+        })
+        .collect::<Vec<_>>();
+    let strongest = hashes
+        .iter()
+        .map(|(algorithm, _)| *algorithm)
+        .max_by_key(|algorithm| algorithm.digest_length())?;
+    let (_, digest) = digests
+        .iter()
+        .find(|(algorithm, _)| *algorithm == strongest)?;
+    let expected = to_sri(strongest, digest)?;
+
+    Some(
+        hashes
+            .iter()
+            .filter(|(algorithm, _)| *algorithm == strongest)
+            .any(|(_, hash)| {
+                // Algorithm names are matched ignoring case, but base64 isn't:
+                hash.split_once('-').map(|(_, base64)| base64)
+                    == expected.split_once('-').map(|(_, base64)| base64)
+            }),
+    )
+}
@@ -0,0 +1,166 @@
+use super::html_subresources::HtmlSubresources;
+use super::integrity_metadata::{integrity_matches, integrity_metadata};
+use crate::digest_record::SubresourceIntegrityRecord;
+use crate::message_digest_service::MessageDigestAlgorithm;
+use std::fmt;
+
+/// Calculates the Subresource Integrity metadata of each subresource of a
+/// Web page served from `document_url`, and checks it against existing
+/// `integrity` attributes
+///
+/// `calculate_digests` returns the digests of a subresource for each of
+/// `algorithms`, as [WebPageWatcher] does with records, so that any binding
+/// of the calculate Web page message digest service can be used, e.g., one
+/// without credentials for other origins.  A subresource, whose URL can't be
+/// resolved, or which can't be hashed, gets a failed record.
+///
+/// [WebPageWatcher]: crate::watch_service::WebPageWatcher
+pub async fn calculate_integrity_records<CalculateDigests>(
+    html_subresources: &HtmlSubresources,
+    document_url: &url::Url,
+    algorithms: &[MessageDigestAlgorithm],
+    mut calculate_digests: CalculateDigests,
+) -> Result<Vec<SubresourceIntegrityRecord>, UnsupportedSriAlgorithm>
+where
+    CalculateDigests:
+        AsyncFnMut(&url::Url) -> Result<Vec<Vec<u8>>, Box<dyn std::error::Error + Send + Sync>>,
+{
+    check_sri_algorithms(algorithms)?;
+
+    let base_url = html_subresources.base_url(document_url);
+    let mut records = Vec::new();
+
+    for subresource in html_subresources.subresources() {
+        let url = match base_url.join(&subresource.url) {
+            Ok(url) => url,
+            Err(error) => {
+                records.push(SubresourceIntegrityRecord::failed(
+                    subresource,
+                    subresource.url.clone(),
+                    &error,
+                ));
+                continue;
+            }
+        };
+
+        records.push(match calculate_digests(&url).await {
+            Ok(digests) => {
+                let digests = algorithms.iter().copied().zip(digests).collect::<Vec<_>>();
+
+                SubresourceIntegrityRecord::new(
+                    subresource,
+                    url.to_string(),
+                    integrity_metadata(&digests).expect("the algorithms are allowed"),
+                    subresource
+                        .integrity
+                        .as_deref()
+                        .and_then(|integrity| integrity_matches(integrity, &digests)),
+                )
+            }
+            Err(error) => {
+                SubresourceIntegrityRecord::failed(subresource, url.to_string(), error.as_ref())
+            }
+        });
+    }
+
+    Ok(records)
+}
+
+/// Fails, when any algorithm isn't allowed in Subresource Integrity metadata
+pub fn check_sri_algorithms(
+    algorithms: &[MessageDigestAlgorithm],
+) -> Result<(), UnsupportedSriAlgorithm> {
+    match algorithms
+        .iter()
+        .find(|algorithm| algorithm.sri_name().is_none())
+    {
+        Some(&algorithm) => Err(UnsupportedSriAlgorithm(algorithm)),
+        None => Ok(()),
+    }
+}
+
+#[derive(Debug)]
+pub struct UnsupportedSriAlgorithm(pub MessageDigestAlgorithm);
+
+impl fmt::Display for UnsupportedSriAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} isn't allowed in Subresource Integrity metadata",
+            self.0.name()
+        )
+    }
+}
+
+impl std::error::Error for UnsupportedSriAlgorithm {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io;
+
+    const HTML: &str = r#"<base href="/assets/">
+<script src="app.js" integrity="sha256-AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA="></script>
+<link rel="stylesheet" href="missing.css">
+<script src="http://[::1"></script>"#;
+
+    #[tokio::test]
+    async fn subresources_are_resolved_against_the_base_url_and_hashed() {
+        let html_subresources = HtmlSubresources::parse(HTML.to_string());
+        let document_url = url::Url::parse("https://example.com/page").unwrap();
+        let mut calculated_urls = Vec::new();
+
+        let records = calculate_integrity_records(
+            &html_subresources,
+            &document_url,
+            &[MessageDigestAlgorithm::Sha256],
+            async |url| {
+                calculated_urls.push(url.to_string());
+
+                if url.path().ends_with("missing.css") {
+                    Err(io::Error::other("not found").into())
+                } else {
+                    Ok(vec![vec![0; 32]])
+                }
+            },
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(
+            calculated_urls,
+            [
+                "https://example.com/assets/app.js",
+                "https://example.com/assets/missing.css"
+            ]
+        );
+        assert_eq!(
+            records[0].integrity.as_deref(),
+            Some("sha256-AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=")
+        );
+        assert_eq!(records[0].matches_existing, Some(true));
+        assert_eq!(records[1].error.as_ref().unwrap().message, "not found");
+        assert_eq!(records[2].url, "http://[::1");
+        assert!(records[2].error.is_some());
+    }
+
+    #[tokio::test]
+    async fn algorithms_must_be_allowed_in_integrity_metadata() {
+        let html_subresources = HtmlSubresources::parse(HTML.to_string());
+        let document_url = url::Url::parse("https://example.com/").unwrap();
+
+        let error = calculate_integrity_records(
+            &html_subresources,
+            &document_url,
+            &[MessageDigestAlgorithm::Sha3_256],
+            async |_| Ok(Vec::new()),
+        )
+        .await
+        .unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            "SHA3-256 isn't allowed in Subresource Integrity metadata"
+        );
+    }
+}
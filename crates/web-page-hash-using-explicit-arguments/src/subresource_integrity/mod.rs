@@ -0,0 +1,11 @@
+pub mod html_subresources;
+pub use html_subresources::*;
+
+pub mod integrity_metadata;
+pub use integrity_metadata::*;
+
+pub mod integrity_records;
+pub use integrity_records::*;
+
+pub mod sri_output;
+pub use sri_output::*;
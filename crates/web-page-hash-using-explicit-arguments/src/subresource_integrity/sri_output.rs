@@ -0,0 +1,182 @@
+use super::html_subresources::HtmlSubresources;
+use crate::digest_record::SubresourceIntegrityRecord;
+use std::fmt;
+use std::io::{self, Write};
+use std::str::FromStr;
+
+/// Formats of the subresources printed by `sri`
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum SriOutput {
+    /// The URL, and Subresource Integrity metadata of each subresource, and
+    /// whether an existing `integrity` attribute matches it
+    #[default]
+    Text,
+    /// A pretty-printed JSON array of records (see
+    /// [SubresourceIntegrityRecord])
+    Json,
+    /// The Web page with `integrity`, and `crossorigin` attributes on the
+    /// elements of its subresources
+    Html,
+}
+
+impl SriOutput {
+    /// Returns the name of the format (e.g., `html`)
+    pub fn name(self) -> &'static str {
+        match self {
+            SriOutput::Text => "text",
+            SriOutput::Json => "json",
+            SriOutput::Html => "html",
+        }
+    }
+
+    /// Writes the records of the subresources of a Web page, or the Web page
+    /// with their metadata in the `html` format, where elements without a
+    /// `crossorigin` attribute get `crossorigin`
+    ///
+    /// The errors of failed records are written into `diagnostics`.
+    pub fn write_records(
+        self,
+        writer: &mut impl Write,
+        diagnostics: &mut impl Write,
+        html_subresources: &HtmlSubresources,
+        records: &[SubresourceIntegrityRecord],
+        crossorigin: CrossOrigin,
+    ) -> io::Result<()> {
+        for record in records {
+            if let Some(error) = &record.error {
+                writeln!(diagnostics, "{}: {}", record.url, error.message)?;
+            }
+        }
+
+        match self {
+            SriOutput::Text => {
+                for record in records {
+                    let Some(integrity) = &record.integrity else {
+                        continue;
+                    };
+                    let existing = match record.matches_existing {
+                        Some(true) => " (matches the integrity attribute)",
+                        Some(false) => " (differs from the integrity attribute)",
+                        None if record.existing_integrity.is_some() => {
+                            " (the integrity attribute can't be checked)"
+                        }
+                        None => "",
+                    };
+                    writeln!(
+                        writer,
+                        "{} {}: {}{}",
+                        record.kind, record.url, integrity, existing
+                    )?;
+                }
+
+                Ok(())
+            }
+            SriOutput::Json => {
+                serde_json::to_writer_pretty(&mut *writer, records)?;
+                writeln!(writer)
+            }
+            SriOutput::Html => {
+                let integrities = records
+                    .iter()
+                    .map(|record| record.integrity.clone())
+                    .collect::<Vec<_>>();
+
+                write!(
+                    writer,
+                    "{}",
+                    html_subresources.rewrite(&integrities, crossorigin.attribute_value())
+                )
+            }
+        }
+    }
+}
+
+impl fmt::Display for SriOutput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for SriOutput {
+    type Err = UnknownSriOutput;
+
+    /// Parses a format name ignoring case
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name.to_ascii_lowercase().as_str() {
+            "text" => Ok(SriOutput::Text),
+            "json" => Ok(SriOutput::Json),
+            "html" => Ok(SriOutput::Html),
+            _ => Err(UnknownSriOutput(name.to_string())),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct UnknownSriOutput(pub String);
+
+impl fmt::Display for UnknownSriOutput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "unknown output format {} (expected text, json, or html)",
+            self.0
+        )
+    }
+}
+
+impl std::error::Error for UnknownSriOutput {}
+
+/// Values of `crossorigin` attributes
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum CrossOrigin {
+    /// Requests subresources without credentials, unless they're
+    /// same-origin
+    #[default]
+    Anonymous,
+    /// Requests subresources with credentials
+    UseCredentials,
+}
+
+impl CrossOrigin {
+    /// Returns the attribute value (e.g., `anonymous`)
+    pub fn attribute_value(self) -> &'static str {
+        match self {
+            CrossOrigin::Anonymous => "anonymous",
+            CrossOrigin::UseCredentials => "use-credentials",
+        }
+    }
+}
+
+impl fmt::Display for CrossOrigin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.attribute_value())
+    }
+}
+
+impl FromStr for CrossOrigin {
+    type Err = UnknownCrossOrigin;
+
+    /// Parses an attribute value ignoring case
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_ascii_lowercase().as_str() {
+            "anonymous" => Ok(CrossOrigin::Anonymous),
+            "use-credentials" => Ok(CrossOrigin::UseCredentials),
+            _ => Err(UnknownCrossOrigin(value.to_string())),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct UnknownCrossOrigin(pub String);
+
+impl fmt::Display for UnknownCrossOrigin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "unknown crossorigin value {} (expected anonymous, or use-credentials)",
+            self.0
+        )
+    }
+}
+
+impl std::error::Error for UnknownCrossOrigin {}
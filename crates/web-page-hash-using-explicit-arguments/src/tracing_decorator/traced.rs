@@ -0,0 +1,52 @@
+use std::time::Duration;
+
+/// A decorator emitting [tracing] spans, and events for calls to the service
+/// it decorates
+///
+/// Decorated services emit the following spans, and an event when the
+/// traced phase finishes, with its duration in `duration_ms`, or the error
+/// details in `error`:
+///
+/// * `calculate_web_page_message_digest`, with the `url` field, from
+///   [CalculateWebPageMessageDigest](crate::calculate_web_page_message_digest_service::CalculateWebPageMessageDigest)
+///   services;
+/// * `fetch`, with the `url` field, from [GetUrl](crate::http_client_service::GetUrl)
+///   services, and the `stream` span of streaming the response body, with the
+///   streamed `byte_count`;
+/// * `digest`, with the `algorithm` field, from
+///   [NewDigestCalculator](crate::message_digest_service::NewDigestCalculator)
+///   services, with the hashed `byte_count`.
+///
+/// Spans of services called by a decorated calculate Web page message digest
+/// service are nested in its span.
+#[derive(Debug, Default)]
+pub struct Traced<Inner> {
+    inner: Inner,
+}
+
+impl<Inner> Traced<Inner> {
+    pub fn new(inner: Inner) -> Self {
+        Traced { inner }
+    }
+
+    pub fn inner(&self) -> &Inner {
+        &self.inner
+    }
+}
+
+pub(crate) fn duration_as_millis(duration: Duration) -> u64 {
+    duration.as_millis().try_into().unwrap_or(u64::MAX)
+}
+
+/// Returns the description of an error followed by descriptions of the
+/// errors that caused it, outermost first
+pub(crate) fn error_details(error: &dyn std::error::Error) -> String {
+    std::iter::successors(error.source(), |cause| cause.source()).fold(
+        error.to_string(),
+        |mut details, cause| {
+            details.push_str(": ");
+            details.push_str(&cause.to_string());
+            details
+        },
+    )
+}
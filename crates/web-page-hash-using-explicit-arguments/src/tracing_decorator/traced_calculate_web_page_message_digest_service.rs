@@ -0,0 +1,121 @@
+use super::traced::{Traced, duration_as_millis};
+use crate::calculate_web_page_message_digest_service::{
+    CalculateWebPageMessageDigest, CalculateWebPageMessageDigestWithMetadata, WebPageMessageDigest,
+};
+use crate::credential_provider_service::redact_url_credentials;
+use crate::message_digest_service::DigestParts;
+use std::fmt;
+use std::time::Instant;
+use tracing::Instrument;
+
+/// Returns the hexadecimal digests a digest consists of, separated by commas
+fn hex_digest_parts(digest: &impl DigestParts) -> String {
+    digest
+        .digest_parts()
+        .into_iter()
+        .map(hex::encode)
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+impl<CalculateWebPageMessageDigestService> CalculateWebPageMessageDigest
+    for Traced<CalculateWebPageMessageDigestService>
+where
+    CalculateWebPageMessageDigestService: CalculateWebPageMessageDigest + Sync,
+    CalculateWebPageMessageDigestService::Url: AsRef<str> + Sync,
+    CalculateWebPageMessageDigestService::Digest: DigestParts,
+    CalculateWebPageMessageDigestService::Error: fmt::Display,
+{
+    type Url = CalculateWebPageMessageDigestService::Url;
+    type Digest = CalculateWebPageMessageDigestService::Digest;
+    type Error = CalculateWebPageMessageDigestService::Error;
+
+    async fn calculate_web_page_message_digest(
+        &self,
+        url: &Self::Url,
+    ) -> Result<Self::Digest, Self::Error> {
+        let span = tracing::info_span!(
+            "calculate_web_page_message_digest",
+            url = redact_url_credentials(url.as_ref()).as_ref()
+        );
+
+        async {
+            let started = Instant::now();
+            let digest_result = self.inner().calculate_web_page_message_digest(url).await;
+            let duration_ms = duration_as_millis(started.elapsed());
+
+            match &digest_result {
+                Ok(digest) => tracing::info!(
+                    digest = hex_digest_parts(digest),
+                    duration_ms,
+                    "calculated the Web page message digest"
+                ),
+                Err(error) => trace_error(error, duration_ms),
+            }
+
+            digest_result
+        }
+        .instrument(span)
+        .await
+    }
+}
+
+impl<CalculateWebPageMessageDigestService> CalculateWebPageMessageDigestWithMetadata
+    for Traced<CalculateWebPageMessageDigestService>
+where
+    CalculateWebPageMessageDigestService: CalculateWebPageMessageDigestWithMetadata + Sync,
+    CalculateWebPageMessageDigestService::Url: AsRef<str> + Sync,
+    CalculateWebPageMessageDigestService::Digest: DigestParts,
+    CalculateWebPageMessageDigestService::Error: fmt::Display,
+{
+    async fn calculate_web_page_message_digest_with_metadata(
+        &self,
+        url: &Self::Url,
+    ) -> Result<WebPageMessageDigest<Self::Digest>, Self::Error> {
+        let span = tracing::info_span!(
+            "calculate_web_page_message_digest",
+            url = redact_url_credentials(url.as_ref()).as_ref()
+        );
+
+        async {
+            let started = Instant::now();
+            let digest_result = self
+                .inner()
+                .calculate_web_page_message_digest_with_metadata(url)
+                .await;
+            let duration_ms = duration_as_millis(started.elapsed());
+
+            match &digest_result {
+                Ok(web_page_message_digest) => tracing::info!(
+                    digest = hex_digest_parts(&web_page_message_digest.digest),
+                    final_url = redact_url_credentials(&web_page_message_digest.final_url).as_ref(),
+                    redirect_count = web_page_message_digest.redirects.len(),
+                    certificate_sha256 = web_page_message_digest
+                        .certificate_fingerprint
+                        .map(|fingerprint| fingerprint.to_string()),
+                    status = web_page_message_digest.status.as_u16(),
+                    content_type = web_page_message_digest.content_type,
+                    content_encoding = web_page_message_digest.content_encoding,
+                    body_encoding = web_page_message_digest.body_encoding.name(),
+                    byte_count = web_page_message_digest.byte_count,
+                    duration_ms,
+                    "calculated the Web page message digest"
+                ),
+                Err(error) => trace_error(error, duration_ms),
+            }
+
+            digest_result
+        }
+        .instrument(span)
+        .await
+    }
+}
+
+fn trace_error(error: &impl fmt::Display, duration_ms: u64) {
+    // The alternate format includes causes, e.g., of [anyhow::Error]s:
+    tracing::error!(
+        error = format!("{:#}", error),
+        duration_ms,
+        "calculating the Web page message digest failed"
+    );
+}
@@ -0,0 +1,5 @@
+pub mod watch_state;
+pub use watch_state::*;
+
+pub mod web_page_watcher;
+pub use web_page_watcher::*;
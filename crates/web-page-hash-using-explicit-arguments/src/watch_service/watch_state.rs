@@ -0,0 +1,138 @@
+use crate::change_notifier_service::{ChangeEvent, ChangeKind, format_timestamp};
+use crate::digest_record::WebPageDigestRecord;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// The last digests of watched Web pages, kept in a JSON state file between
+/// polls, and runs
+///
+/// Each Web page has an entry for each algorithm it's watched with.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct WatchState {
+    /// Entries by URL, and algorithm name
+    web_pages: BTreeMap<String, BTreeMap<String, WatchStateEntry>>,
+}
+
+/// The last digest of a watched Web page with one algorithm
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct WatchStateEntry {
+    /// The last calculated hexadecimal digest, or `null`, when it never could
+    /// be calculated
+    pub digest: Option<String>,
+    /// Whether calculating the digest failed in the last poll
+    pub failing: bool,
+    /// The RFC 3339 time of the last poll
+    pub checked_at: String,
+    /// The RFC 3339 time of the poll that found the digest
+    pub changed_at: Option<String>,
+}
+
+impl WatchState {
+    pub fn new() -> Self {
+        WatchState::default()
+    }
+
+    /// Returns `$XDG_STATE_HOME/web-page-hash/watch.json`, or
+    /// `$HOME/.local/state/web-page-hash/watch.json`
+    pub fn default_path() -> Option<PathBuf> {
+        let state_home = std::env::var_os("XDG_STATE_HOME")
+            .filter(|value| !value.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| {
+                std::env::var_os("HOME").map(|home| Path::new(&home).join(".local").join("state"))
+            })?;
+
+        Some(state_home.join("web-page-hash").join("watch.json"))
+    }
+
+    /// Reads a state file, or returns an empty state, when it doesn't exist
+    ///
+    /// A corrupt state file is an error, rather than an empty state, so that
+    /// every watched Web page isn't reported as added.
+    pub fn load(path: &Path) -> io::Result<Self> {
+        match fs::read(path) {
+            Ok(json) => Ok(serde_json::from_slice(&json)?),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(WatchState::new()),
+            Err(error) => Err(error),
+        }
+    }
+
+    /// Replaces a state file, creating its directory, if needed
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(directory) = path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+        {
+            fs::create_dir_all(directory)?;
+        }
+        let mut partial_path = path.as_os_str().to_owned();
+        partial_path.push(".partial");
+
+        fs::write(&partial_path, serde_json::to_vec_pretty(self)?)?;
+        fs::rename(partial_path, path)
+    }
+
+    pub fn entry(&self, url: &str, algorithm: &str) -> Option<&WatchStateEntry> {
+        self.web_pages.get(url)?.get(algorithm)
+    }
+
+    /// Records the outcome of polling a Web page, and returns the change it
+    /// shows, if any
+    ///
+    /// A failure is reported only for the first of consecutive failing polls.
+    pub fn update(
+        &mut self,
+        record: &WebPageDigestRecord,
+        checked_at: SystemTime,
+    ) -> Option<ChangeEvent> {
+        let checked_at = format_timestamp(checked_at);
+        let digest = record.digest.as_ref().map(|digest| digest.hex.clone());
+        let previous_entry = self.entry(&record.url, &record.algorithm).cloned();
+        let previous_digest = previous_entry
+            .as_ref()
+            .and_then(|entry| entry.digest.clone());
+        let was_failing = previous_entry.as_ref().is_some_and(|entry| entry.failing);
+
+        let kind = match (&digest, &previous_digest) {
+            (Some(_), None) => Some(ChangeKind::Added),
+            (Some(digest), Some(previous_digest)) if digest != previous_digest => {
+                Some(ChangeKind::Changed)
+            }
+            (Some(_), Some(_)) if was_failing => Some(ChangeKind::Recovered),
+            (Some(_), Some(_)) => None,
+            (None, _) if was_failing => None,
+            (None, _) => Some(ChangeKind::Failed),
+        };
+        let changed_at = match kind {
+            Some(ChangeKind::Added | ChangeKind::Changed) => Some(checked_at.clone()),
+            _ => previous_entry.and_then(|entry| entry.changed_at),
+        };
+
+        self.web_pages
+            .entry(record.url.clone())
+            .or_default()
+            .insert(
+                record.algorithm.clone(),
+                WatchStateEntry {
+                    digest: digest.clone().or_else(|| previous_digest.clone()),
+                    failing: digest.is_none(),
+                    checked_at: checked_at.clone(),
+                    changed_at,
+                },
+            );
+
+        Some(ChangeEvent {
+            kind: kind?,
+            url: record.url.clone(),
+            algorithm: record.algorithm.clone(),
+            previous_digest,
+            digest,
+            detected_at: checked_at,
+            error: record.error.clone(),
+        })
+    }
+}
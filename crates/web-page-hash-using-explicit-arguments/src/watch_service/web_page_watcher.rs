@@ -0,0 +1,112 @@
+use super::watch_state::WatchState;
+use crate::change_notifier_service::NotifyChange;
+use crate::clock_service::{Now, Sleep};
+use crate::digest_record::WebPageDigestRecord;
+use std::io;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Polls Web pages on an interval, and notifies a [ChangeNotifier] of
+/// changes of their digests
+///
+/// The digests are calculated by a function given to [WebPageWatcher::watch],
+/// so that each poll can use any binding of the calculate Web page message
+/// digest service.  Polls start at multiples of the interval after the first
+/// poll, as told by the [Clock], or right after the previous poll, when it
+/// took longer than the interval.  The clock is also the [Sleeper] waiting
+/// for the next poll, so that watching runs on virtual time with, e.g., a
+/// [ManualClock].  The state is saved after each poll, when a state file is
+/// given.
+///
+/// [ChangeNotifier]: crate::change_notifier_service::ChangeNotifier
+/// [Clock]: crate::clock_service::Clock
+/// [Sleeper]: crate::clock_service::Sleeper
+/// [ManualClock]: crate::clock_service::ManualClock
+#[derive(Debug)]
+pub struct WebPageWatcher<Clock, ChangeNotifier> {
+    clock: Clock,
+    change_notifier: ChangeNotifier,
+    interval: Duration,
+    state_path: Option<PathBuf>,
+}
+
+impl<Clock, ChangeNotifier> WebPageWatcher<Clock, ChangeNotifier>
+where
+    Clock: Now + Sleep,
+    ChangeNotifier: NotifyChange,
+    ChangeNotifier::Error: std::error::Error,
+{
+    pub fn new(
+        clock: Clock,
+        change_notifier: ChangeNotifier,
+        interval: Duration,
+        state_path: Option<PathBuf>,
+    ) -> Self {
+        WebPageWatcher {
+            clock,
+            change_notifier,
+            interval,
+            state_path,
+        }
+    }
+
+    /// Reads the state file, or returns an empty state, when it doesn't
+    /// exist, or no state file is given
+    pub fn load_state(&self) -> io::Result<WatchState> {
+        match &self.state_path {
+            Some(state_path) => WatchState::load(state_path),
+            None => Ok(WatchState::new()),
+        }
+    }
+
+    /// Polls `urls` `poll_count` times, or forever, when it's [None]
+    ///
+    /// `calculate_records` returns the records of a Web page for each
+    /// algorithm.  A failing notification is traced, and doesn't stop
+    /// watching, but failing to save the state does.
+    pub async fn watch<CalculateRecords>(
+        &self,
+        state: &mut WatchState,
+        urls: &[String],
+        poll_count: Option<u64>,
+        mut calculate_records: CalculateRecords,
+    ) -> io::Result<()>
+    where
+        CalculateRecords: AsyncFnMut(&str) -> Vec<WebPageDigestRecord>,
+    {
+        let mut next_poll_at = self.clock.now();
+
+        for poll in 0.. {
+            if poll_count.is_some_and(|poll_count| poll >= poll_count) {
+                break;
+            }
+            if poll > 0 {
+                next_poll_at += self.interval;
+                let delay = next_poll_at
+                    .duration_since(self.clock.now())
+                    .unwrap_or(Duration::ZERO);
+                self.clock.sleep(delay).await;
+            }
+
+            for url in urls {
+                for record in calculate_records(url).await {
+                    if let Some(change_event) = state.update(&record, self.clock.now())
+                        && let Err(error) = self.change_notifier.notify_change(&change_event).await
+                    {
+                        tracing::warn!(
+                            url = change_event.url,
+                            error = %error,
+                            "notifying a change failed"
+                        );
+                    }
+                }
+            }
+
+            if let Some(state_path) = &self.state_path {
+                state.save(state_path)?;
+            }
+        }
+
+        Ok(())
+    }
+}
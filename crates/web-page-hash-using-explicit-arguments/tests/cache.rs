@@ -0,0 +1,227 @@
+mod common;
+
+use common::{TestServer, respond, run};
+use http::{HeaderMap, HeaderValue};
+use web_page_hash_using_explicit_arguments::http_cache::HttpCache;
+
+/// Stores a body with a strong validator for `url` requested with an
+/// `Accept-Language` header, and returns the key of its entry
+fn store(cache: &HttpCache, url: &str, language: &'static str, body: &[u8]) -> String {
+    let request_headers = HeaderMap::from_iter([(
+        http::header::ACCEPT_LANGUAGE,
+        HeaderValue::from_static(language),
+    )]);
+    let response_headers =
+        HeaderMap::from_iter([(http::header::ETAG, HeaderValue::from_static("\"v1\""))]);
+    let mut writer = cache
+        .begin_store(url, &request_headers, &response_headers)
+        .unwrap();
+    writer.write_chunk(body).unwrap();
+    let key = writer.key().to_string();
+    writer.commit().unwrap();
+
+    key
+}
+
+/// Starts a server answering with a page, which has a strong validator, and
+/// `304 Not Modified` to requests revalidating it
+fn start_server() -> TestServer {
+    TestServer::start(|request, stream| match request.header("If-None-Match") {
+        Some("\"v1\"") => respond(stream, "304 Not Modified", &[("ETag", "\"v1\"")], b""),
+        _ => respond(
+            stream,
+            "200 OK",
+            &[("ETag", "\"v1\""), ("Cache-Control", "no-cache")],
+            b"cached page",
+        ),
+    })
+}
+
+#[test]
+fn bodies_of_memoized_digests_are_still_saved() {
+    let home = tempfile::tempdir().unwrap();
+    let server = start_server();
+    let output_path = home.path().join("page");
+
+    let output = run(home.path(), &["--url", &server.url("/"), "--cache"]);
+    assert!(output.status.success(), "{:?}", output);
+    let output = run(
+        home.path(),
+        &[
+            "--url",
+            &server.url("/"),
+            "--cache",
+            "--output-file",
+            output_path.to_str().unwrap(),
+        ],
+    );
+
+    assert!(output.status.success(), "{:?}", output);
+    assert_eq!(std::fs::read(&output_path).unwrap(), b"cached page");
+    let requests = server.requests();
+    assert_eq!(requests.len(), 2);
+    assert_eq!(requests[1].header("If-None-Match"), Some("\"v1\""));
+}
+
+#[test]
+fn merkle_proofs_of_memoized_digests_are_still_written() {
+    let home = tempfile::tempdir().unwrap();
+    let server = start_server();
+    let proof_path = home.path().join("proof");
+    let url = server.url("/");
+    let args = ["--url", &url, "--cache", "--merkle-leaf-size", "4"];
+
+    let output = run(home.path(), &args);
+    assert!(output.status.success(), "{:?}", output);
+    let output = run(
+        home.path(),
+        &[&args[..], &["--merkle-proof", proof_path.to_str().unwrap()]].concat(),
+    );
+
+    assert!(output.status.success(), "{:?}", output);
+    assert!(proof_path.exists());
+    assert_eq!(server.requests().len(), 2);
+}
+
+#[test]
+fn responses_are_cached_by_request_headers_and_credentials() {
+    let home = tempfile::tempdir().unwrap();
+    let server = start_server();
+    let url = server.url("/");
+
+    for (header, bearer_token) in [
+        ("Accept-Language: en", "alice"),
+        ("Accept-Language: fi", "alice"),
+        ("Accept-Language: en", "bob"),
+        ("Accept-Language: en", "alice"),
+    ] {
+        let output = run(
+            home.path(),
+            &[
+                "--url",
+                &url,
+                "--cache",
+                "--header",
+                header,
+                "--bearer-token",
+                bearer_token,
+            ],
+        );
+        assert!(output.status.success(), "{:?}", output);
+    }
+
+    let revalidated = server
+        .requests()
+        .iter()
+        .map(|request| request.header("If-None-Match").is_some())
+        .collect::<Vec<_>>();
+    assert_eq!(revalidated, [false, false, false, true]);
+}
+
+#[test]
+fn responses_are_only_cached_when_enabled() {
+    let home = tempfile::tempdir().unwrap();
+    let cache_dir = home.path().join("cache");
+    let server = start_server();
+    let url = server.url("/");
+
+    for args in [
+        &["--url", &url][..],
+        &["--url", &url],
+        &["--url", &url, "--cache-dir", cache_dir.to_str().unwrap()],
+        &["--url", &url, "--cache-dir", cache_dir.to_str().unwrap()],
+    ] {
+        let output = run(home.path(), args);
+        assert!(output.status.success(), "{:?}", output);
+    }
+
+    let revalidated = server
+        .requests()
+        .iter()
+        .map(|request| request.header("If-None-Match").is_some())
+        .collect::<Vec<_>>();
+    assert_eq!(revalidated, [false, false, false, true]);
+    assert!(!home.path().join(".cache/web-page-hash").exists());
+}
+
+#[test]
+fn digests_are_memoized_for_the_response_they_are_calculated_from() {
+    let home = tempfile::tempdir().unwrap();
+    let server = TestServer::start(|request, stream| {
+        let (etag, body): (&str, &[u8]) = match request.header("Accept-Language") {
+            Some("fi") => ("\"fi\"", b"sivu"),
+            _ => ("\"en\"", b"page"),
+        };
+        match request.header("If-None-Match") {
+            Some(validator) if validator == etag => {
+                respond(stream, "304 Not Modified", &[("ETag", etag)], b"")
+            }
+            _ => respond(stream, "200 OK", &[("ETag", etag)], body),
+        }
+    });
+    let url = server.url("/");
+    let digest = |header| {
+        let output = run(home.path(), &["--url", &url, "--cache", "--header", header]);
+        assert!(output.status.success(), "{:?}", output);
+        output.stdout
+    };
+
+    let en_digest = digest("Accept-Language: en");
+    let fi_digest = digest("Accept-Language: fi");
+
+    assert_ne!(en_digest, fi_digest);
+    assert_eq!(digest("Accept-Language: en"), en_digest);
+    assert_eq!(digest("Accept-Language: fi"), fi_digest);
+    assert_eq!(server.requests().len(), 4);
+}
+
+#[test]
+fn memoized_digests_are_recalled_by_entry_key() {
+    let directory = tempfile::tempdir().unwrap();
+    let cache = HttpCache::new(directory.path(), u64::MAX);
+    let url = "https://example.com/";
+    let en_key = store(&cache, url, "en", b"page");
+    let fi_key = store(&cache, url, "fi", b"sivu");
+
+    cache
+        .memoize_digest(&en_key, "\"v1\"", "SHA3-256", &[1])
+        .unwrap();
+    cache
+        .memoize_digest(&fi_key, "\"v1\"", "SHA3-256", &[2])
+        .unwrap();
+
+    assert_ne!(en_key, fi_key);
+    assert_eq!(
+        cache.recall_digest(&en_key, "\"v1\"", "SHA3-256").unwrap(),
+        Some(vec![1])
+    );
+    assert_eq!(
+        cache.recall_digest(&fi_key, "\"v1\"", "SHA3-256").unwrap(),
+        Some(vec![2])
+    );
+    assert_eq!(
+        cache.recall_digest(&en_key, "\"v2\"", "SHA3-256").unwrap(),
+        None
+    );
+    assert_eq!(
+        cache.recall_digest("..", "\"v1\"", "SHA3-256").unwrap(),
+        None
+    );
+}
+
+#[test]
+fn least_recently_used_entries_are_evicted_on_commit() {
+    let directory = tempfile::tempdir().unwrap();
+    let url = "https://example.com/";
+    let body = [0; 1000];
+    let cache = HttpCache::new(directory.path(), 3000);
+    let en_key = store(&cache, url, "en", &body);
+    std::thread::sleep(std::time::Duration::from_millis(10));
+    let fi_key = store(&cache, url, "fi", &body);
+    std::thread::sleep(std::time::Duration::from_millis(10));
+    let sv_key = store(&cache, url, "sv", &body);
+
+    assert!(!directory.path().join(en_key).exists());
+    assert!(directory.path().join(fi_key).exists());
+    assert!(directory.path().join(sv_key).exists());
+}
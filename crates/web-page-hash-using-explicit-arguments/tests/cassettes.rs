@@ -0,0 +1,101 @@
+mod common;
+
+use common::{TestServer, respond, run};
+use sha3::{Digest, Sha3_256};
+
+/// A cassette recorded from [start_server], with the URL of the server
+/// replaced by `http://example.com/page`
+const CASSETTE: &str = include_str!("fixtures/cassettes/page.json");
+
+const BODY: &[u8] = b"<p>recorded page</p>";
+
+fn start_server() -> TestServer {
+    TestServer::start(|_, stream| {
+        respond(
+            stream,
+            "200 OK",
+            &[
+                ("Content-Type", "text/html"),
+                ("ETag", "\"v1\""),
+                ("Set-Cookie", "session=secret; HttpOnly"),
+            ],
+            BODY,
+        )
+    })
+}
+
+#[test]
+fn recorded_cassettes_have_no_cookies() {
+    let home = tempfile::tempdir().unwrap();
+    let cassette_path = home.path().join("cassette.json");
+    let server = start_server();
+
+    let output = run(
+        home.path(),
+        &[
+            "--url",
+            &server.url("/page"),
+            "--record",
+            cassette_path.to_str().unwrap(),
+        ],
+    );
+
+    assert!(output.status.success(), "{:?}", output);
+    let cassette = std::fs::read_to_string(&cassette_path).unwrap();
+    assert!(!cassette.to_ascii_lowercase().contains("set-cookie"));
+    assert!(!cassette.contains("secret"));
+    assert_eq!(
+        cassette.replace(&server.url("/page"), "http://example.com/page"),
+        CASSETTE.trim_end()
+    );
+}
+
+#[test]
+fn replayed_responses_are_hashed_as_recorded() {
+    let home = tempfile::tempdir().unwrap();
+    let cassette_path = home.path().join("cassette.json");
+    std::fs::write(&cassette_path, CASSETTE).unwrap();
+
+    let output = run(
+        home.path(),
+        &[
+            "--url",
+            "http://example.com/page",
+            "--replay",
+            cassette_path.to_str().unwrap(),
+            "--expect",
+            &hex::encode(Sha3_256::digest(BODY)),
+        ],
+    );
+
+    assert!(output.status.success(), "{:?}", output);
+
+    let output = run(
+        home.path(),
+        &[
+            "--url",
+            "http://example.com/other",
+            "--replay",
+            cassette_path.to_str().unwrap(),
+        ],
+    );
+
+    assert_eq!(output.status.code(), Some(2), "{:?}", output);
+}
+
+#[test]
+fn recorded_responses_replay_with_the_same_digest() {
+    let home = tempfile::tempdir().unwrap();
+    let cassette_path = home.path().join("cassette.json");
+    let cassette = cassette_path.to_str().unwrap();
+    let server = start_server();
+    let url = server.url("/page");
+
+    let recording = run(home.path(), &["--url", &url, "--record", cassette]);
+    let replay = run(home.path(), &["--url", &url, "--replay", cassette]);
+
+    assert!(recording.status.success(), "{:?}", recording);
+    assert!(replay.status.success(), "{:?}", replay);
+    assert_eq!(recording.stdout, replay.stdout);
+    assert_eq!(server.requests().len(), 1);
+}
@@ -0,0 +1,267 @@
+mod common;
+
+use common::{TestServer, command, respond, run};
+use std::io::Write;
+use std::path::Path;
+use std::process::{Output, Stdio};
+
+// The SHA3-256, and SHA-256 digests of the bodies `a`, and `b`:
+const SHA3_256_A: &str = "80084bf2fba02475726feb2cab2d8215eab14bc6bdd8bfb2c8151257032ecd8b";
+const SHA3_256_B: &str = "b039179a8a4ce2c252aa6f2f25798251c19b75fc1508d9d511a191e0487d64a7";
+const SHA256_B: &str = "3e23e8160039594a33894f6564e1b1348bbd7a0088d42c4acb73eeaed59c009d";
+
+/// Starts a server answering `/a`, and `/b` with their names, and other
+/// paths with `404 Not Found`
+fn start_server() -> TestServer {
+    TestServer::start(|request, stream| match request.path.as_str() {
+        "/a" => respond(stream, "200 OK", &[], b"a"),
+        "/b" => respond(stream, "200 OK", &[], b"b"),
+        _ => respond(stream, "404 Not Found", &[], b""),
+    })
+}
+
+/// Checks a checksum file of `lines`
+fn check(home: &Path, lines: &[String], args: &[&str]) -> Output {
+    let checksum_file_path = home.join("SHA3SUMS");
+    std::fs::write(&checksum_file_path, lines.join("\n") + "\n").unwrap();
+
+    run(
+        home,
+        &[&["--check", checksum_file_path.to_str().unwrap()], args].concat(),
+    )
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8(output.stdout.clone()).unwrap()
+}
+
+fn stderr(output: &Output) -> String {
+    String::from_utf8(output.stderr.clone()).unwrap()
+}
+
+#[test]
+fn matching_gnu_and_bsd_lines_are_ok() {
+    let home = tempfile::tempdir().unwrap();
+    let server = start_server();
+
+    let output = check(
+        home.path(),
+        &[
+            format!("{}  {}", SHA3_256_A, server.url("/a")),
+            format!("SHA256 ({}) = {}", server.url("/b"), SHA256_B),
+        ],
+        &[],
+    );
+
+    assert_eq!(output.status.code(), Some(0), "{:?}", output);
+    assert_eq!(
+        stdout(&output),
+        format!("{}: OK\n{}: OK\n", server.url("/a"), server.url("/b"))
+    );
+    assert_eq!(stderr(&output), "");
+}
+
+#[test]
+fn mismatched_lines_fail() {
+    let home = tempfile::tempdir().unwrap();
+    let server = start_server();
+
+    let output = check(
+        home.path(),
+        &[
+            format!("{}  {}", SHA3_256_B, server.url("/a")),
+            format!("{}  {}", SHA3_256_B, server.url("/b")),
+        ],
+        &[],
+    );
+
+    assert_eq!(output.status.code(), Some(1), "{:?}", output);
+    assert_eq!(
+        stdout(&output),
+        format!("{}: FAILED\n{}: OK\n", server.url("/a"), server.url("/b"))
+    );
+    assert_eq!(
+        stderr(&output),
+        "WARNING: 1 computed checksum did NOT match\n"
+    );
+}
+
+#[test]
+fn lines_of_missing_web_pages_fail_to_be_read() {
+    let home = tempfile::tempdir().unwrap();
+    let server = start_server();
+
+    let output = check(
+        home.path(),
+        &[
+            format!("{}  {}", SHA3_256_A, server.url("/missing")),
+            format!("{}  {}", SHA3_256_A, server.url("/a")),
+        ],
+        &[],
+    );
+
+    assert_eq!(output.status.code(), Some(1), "{:?}", output);
+    assert_eq!(
+        stdout(&output),
+        format!(
+            "{}: FAILED open or read\n{}: OK\n",
+            server.url("/missing"),
+            server.url("/a")
+        )
+    );
+    let stderr = stderr(&output);
+    assert!(
+        stderr.starts_with(&format!("{}: ", server.url("/missing"))),
+        "{stderr}"
+    );
+    assert!(
+        stderr.ends_with("WARNING: 1 listed Web page could not be read\n"),
+        "{stderr}"
+    );
+}
+
+#[test]
+fn improperly_formatted_lines_are_counted() {
+    let home = tempfile::tempdir().unwrap();
+    let server = start_server();
+
+    let output = check(
+        home.path(),
+        &[
+            "# Not a checksum line".to_string(),
+            format!("{}  {}", &SHA3_256_A[2..], server.url("/b")),
+            format!("{}  {}", SHA3_256_A, server.url("/a")),
+        ],
+        &[],
+    );
+
+    // Only the properly formatted lines decide the exit status:
+    assert_eq!(output.status.code(), Some(0), "{:?}", output);
+    assert_eq!(stdout(&output), format!("{}: OK\n", server.url("/a")));
+    assert_eq!(
+        stderr(&output),
+        "WARNING: 2 lines are improperly formatted\n"
+    );
+    assert_eq!(server.requests().len(), 1);
+}
+
+#[test]
+fn checksum_files_without_properly_formatted_lines_fail() {
+    let home = tempfile::tempdir().unwrap();
+
+    let output = check(home.path(), &["not a checksum line".to_string()], &[]);
+
+    assert_eq!(output.status.code(), Some(1), "{:?}", output);
+    assert_eq!(stdout(&output), "");
+    assert!(
+        stderr(&output).ends_with(": no properly formatted checksum lines found\n"),
+        "{:?}",
+        output
+    );
+
+    let output = run(
+        home.path(),
+        &["--check", home.path().join("missing").to_str().unwrap()],
+    );
+    assert_eq!(output.status.code(), Some(2), "{:?}", output);
+}
+
+#[test]
+fn quiet_checks_only_print_failures() {
+    let home = tempfile::tempdir().unwrap();
+    let server = start_server();
+    let lines = [
+        format!("{}  {}", SHA3_256_A, server.url("/a")),
+        format!("{}  {}", SHA3_256_A, server.url("/b")),
+    ];
+
+    let output = check(home.path(), &lines, &["--quiet"]);
+
+    assert_eq!(output.status.code(), Some(1), "{:?}", output);
+    assert_eq!(stdout(&output), format!("{}: FAILED\n", server.url("/b")));
+
+    let output = check(home.path(), &lines[..1], &["--quiet"]);
+
+    assert_eq!(output.status.code(), Some(0), "{:?}", output);
+    assert_eq!(stdout(&output), "");
+}
+
+#[test]
+fn status_checks_print_nothing() {
+    let home = tempfile::tempdir().unwrap();
+    let server = start_server();
+
+    for (lines, code) in [
+        (
+            vec![
+                "not a checksum line".to_string(),
+                format!("{}  {}", SHA3_256_A, server.url("/a")),
+            ],
+            0,
+        ),
+        (vec![format!("{}  {}", SHA3_256_A, server.url("/b"))], 1),
+        (
+            vec![format!("{}  {}", SHA3_256_A, server.url("/missing"))],
+            1,
+        ),
+    ] {
+        let output = check(home.path(), &lines, &["--status"]);
+
+        assert_eq!(output.status.code(), Some(code), "{:?}", output);
+        assert_eq!(stdout(&output), "");
+        assert_eq!(stderr(&output), "");
+    }
+}
+
+#[test]
+fn checksum_lines_are_read_from_standard_input() {
+    let home = tempfile::tempdir().unwrap();
+    let server = start_server();
+    let mut child = command(home.path())
+        .args(["--check", "-"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    writeln!(
+        child.stdin.take().unwrap(),
+        "{}  {}",
+        SHA3_256_A,
+        server.url("/a")
+    )
+    .unwrap();
+    let output = child.wait_with_output().unwrap();
+
+    assert_eq!(output.status.code(), Some(0), "{:?}", output);
+    assert_eq!(stdout(&output), format!("{}: OK\n", server.url("/a")));
+}
+
+#[test]
+fn checks_are_printed_as_records() {
+    let home = tempfile::tempdir().unwrap();
+    let server = start_server();
+    let lines = [
+        format!("{}  {}", SHA3_256_A, server.url("/a")),
+        format!("{}  {}", SHA3_256_A, server.url("/b")),
+    ];
+
+    let output = check(home.path(), &lines, &["--output", "json"]);
+
+    assert_eq!(output.status.code(), Some(1), "{:?}", output);
+    let records: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(records[0]["matches_expected"], true);
+    assert_eq!(records[1]["matches_expected"], false);
+
+    let output = check(home.path(), &lines, &["--output", "ndjson"]);
+
+    assert_eq!(output.status.code(), Some(1), "{:?}", output);
+    let matches_expected = stdout(&output)
+        .lines()
+        .map(|line| {
+            serde_json::from_str::<serde_json::Value>(line).unwrap()["matches_expected"].clone()
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(matches_expected, [true, false]);
+}
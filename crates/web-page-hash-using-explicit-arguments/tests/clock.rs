@@ -0,0 +1,147 @@
+use futures_util::future::{self, Either};
+use std::pin::pin;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::task::{Context, Poll, Wake, Waker};
+use std::time::{Duration, SystemTime};
+use web_page_hash_using_explicit_arguments::change_notifier_service::{
+    ChangeKind, MemoryChangeNotifier, format_timestamp,
+};
+use web_page_hash_using_explicit_arguments::clock_service::{ManualClock, Now, Sleep};
+use web_page_hash_using_explicit_arguments::digest_record::{DigestEncodings, WebPageDigestRecord};
+use web_page_hash_using_explicit_arguments::message_digest_service::MessageDigestAlgorithm;
+use web_page_hash_using_explicit_arguments::watch_service::{WatchState, WebPageWatcher};
+
+const INTERVAL: Duration = Duration::from_secs(60);
+
+/// A waker counting the times it's woken
+#[derive(Default)]
+struct CountingWaker {
+    wake_count: AtomicUsize,
+}
+
+impl Wake for CountingWaker {
+    fn wake(self: Arc<Self>) {
+        self.wake_count.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+fn seconds_since_epoch(time: SystemTime) -> u64 {
+    time.duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+/// Returns a record of a Web page with a one-octet digest
+fn record(url: &str, digest: u8) -> WebPageDigestRecord {
+    let algorithm = MessageDigestAlgorithm::Sha3_256;
+    let error = std::io::Error::other("not calculated");
+    let mut record =
+        WebPageDigestRecord::failed(url.to_string(), algorithm, &error, Duration::ZERO);
+    record.digest = Some(DigestEncodings::new(algorithm, &[digest]));
+    record.error = None;
+
+    record
+}
+
+/// Runs `future` while advancing `clock` a second at a time, whenever
+/// `future` is waiting
+async fn run_advancing<Output>(
+    clock: &ManualClock,
+    future: impl Future<Output = Output>,
+) -> Output {
+    let advancing = async {
+        loop {
+            tokio::task::yield_now().await;
+            clock.advance(Duration::from_secs(1));
+        }
+    };
+
+    match future::select(pin!(future), pin!(advancing)).await {
+        Either::Left((output, _)) => output,
+        Either::Right(_) => unreachable!("advancing the clock never finishes"),
+    }
+}
+
+#[tokio::test]
+async fn web_pages_are_polled_on_the_interval() {
+    let clock = ManualClock::default();
+    let change_notifier = MemoryChangeNotifier::new();
+    let watcher = WebPageWatcher::new(clock.clone(), change_notifier.clone(), INTERVAL, None);
+    let mut state = WatchState::new();
+    let mut poll_times = Vec::new();
+
+    run_advancing(
+        &clock,
+        watcher.watch(
+            &mut state,
+            &["https://example.com/".to_string()],
+            Some(4),
+            async |url: &str| {
+                poll_times.push(seconds_since_epoch(clock.now()));
+                let poll = poll_times.len();
+                // The second poll takes longer than the interval:
+                if poll == 2 {
+                    clock.advance(Duration::from_secs(90));
+                }
+
+                vec![record(url, if poll < 3 { 1 } else { 2 })]
+            },
+        ),
+    )
+    .await
+    .unwrap();
+
+    // The third poll starts right after the slow one, and the fourth at the
+    // next multiple of the interval:
+    assert_eq!(poll_times, [0, 60, 150, 180]);
+    let change_events = change_notifier
+        .change_events()
+        .into_iter()
+        .map(|change_event| (change_event.kind, change_event.detected_at))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        change_events,
+        [
+            (ChangeKind::Added, format_timestamp(SystemTime::UNIX_EPOCH)),
+            (
+                ChangeKind::Changed,
+                format_timestamp(SystemTime::UNIX_EPOCH + Duration::from_secs(150))
+            ),
+        ]
+    );
+}
+
+#[test]
+fn sleeps_finish_once_the_time_is_advanced_past_their_end() {
+    let clock = ManualClock::default();
+    let waker = Arc::new(CountingWaker::default());
+    let mut context = Context::from_waker(Waker::noop());
+    let mut sleep = pin!(clock.sleep(INTERVAL));
+
+    assert!(sleep.as_mut().poll(&mut context).is_pending());
+    let waker_of_latest_poll = Waker::from(waker.clone());
+    assert!(
+        sleep
+            .as_mut()
+            .poll(&mut Context::from_waker(&waker_of_latest_poll))
+            .is_pending()
+    );
+
+    clock.advance(INTERVAL - Duration::from_secs(1));
+    assert_eq!(waker.wake_count.load(Ordering::SeqCst), 0);
+    assert!(sleep.as_mut().poll(&mut context).is_pending());
+    // Only the waker of the latest poll is woken:
+    let waker_of_latest_poll = Waker::from(waker.clone());
+    assert!(
+        sleep
+            .as_mut()
+            .poll(&mut Context::from_waker(&waker_of_latest_poll))
+            .is_pending()
+    );
+    clock.advance(Duration::from_secs(1));
+
+    assert_eq!(waker.wake_count.load(Ordering::SeqCst), 1);
+    assert_eq!(sleep.as_mut().poll(&mut context), Poll::Ready(()));
+    assert_eq!(seconds_since_epoch(clock.now()), 60);
+}
@@ -0,0 +1,160 @@
+use std::io::{self, Write};
+use web_page_hash_using_explicit_arguments::content_decoder_service::{
+    DecodeBody, NewBodyDecoder, StreamingContentDecoder,
+};
+
+type Encode = fn(&[u8]) -> Vec<u8>;
+
+/// Encoders of the supported content codings, by their name in a
+/// `Content-Encoding` header
+const ENCODERS: [(&str, Encode); 5] = [
+    ("gzip", gzip),
+    // With, and without the zlib wrapper:
+    ("deflate", zlib),
+    ("deflate", deflate),
+    ("br", brotli),
+    ("zstd", zstd),
+];
+
+fn gzip(octets: &[u8]) -> Vec<u8> {
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(octets).unwrap();
+    encoder.finish().unwrap()
+}
+
+fn zlib(octets: &[u8]) -> Vec<u8> {
+    let mut encoder = flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(octets).unwrap();
+    encoder.finish().unwrap()
+}
+
+fn deflate(octets: &[u8]) -> Vec<u8> {
+    let mut encoder =
+        flate2::write::DeflateEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(octets).unwrap();
+    encoder.finish().unwrap()
+}
+
+fn brotli(octets: &[u8]) -> Vec<u8> {
+    let mut encoder = brotli::CompressorWriter::new(Vec::new(), 4096, 5, 22);
+    encoder.write_all(octets).unwrap();
+    encoder.into_inner()
+}
+
+fn zstd(octets: &[u8]) -> Vec<u8> {
+    zstd::encode_all(octets, 0).unwrap()
+}
+
+/// Returns a body of text, which compresses well, but not entirely
+fn page(length: usize) -> Vec<u8> {
+    (0..)
+        .flat_map(|line: u32| format!("<p>Line {line}</p>\n").into_bytes())
+        .take(length)
+        .collect()
+}
+
+/// Decodes a body streamed in chunks of `chunk_size` octets
+fn decode(content_encoding: &str, encoded: &[u8], chunk_size: usize) -> io::Result<Vec<u8>> {
+    let mut body_decoder = StreamingContentDecoder::new()
+        .new_body_decoder(Some(content_encoding))
+        .unwrap()
+        .unwrap();
+    let mut decoded = Vec::new();
+    let mut sink = |octets: &[u8]| {
+        decoded.extend_from_slice(octets);
+        Ok::<_, io::Error>(())
+    };
+
+    for chunk in encoded.chunks(chunk_size) {
+        body_decoder.decode_chunk(chunk, &mut sink)?;
+    }
+    body_decoder.finish_body(&mut sink)?;
+
+    Ok(decoded)
+}
+
+#[test]
+fn bodies_are_decoded_from_chunks_of_any_size() {
+    let body = page(100_000);
+
+    for (content_encoding, encode) in ENCODERS {
+        let encoded = encode(&body);
+
+        for chunk_size in [1, 7, 4096, encoded.len()] {
+            assert_eq!(
+                decode(content_encoding, &encoded, chunk_size).unwrap(),
+                body,
+                "{content_encoding} in chunks of {chunk_size} octets"
+            );
+        }
+    }
+}
+
+#[test]
+fn codings_are_decoded_in_the_reverse_order_of_their_application() {
+    let body = page(10_000);
+
+    assert_eq!(
+        decode("br, gzip", &gzip(&brotli(&body)), 100).unwrap(),
+        body
+    );
+}
+
+#[test]
+fn concatenated_zstd_frames_are_decoded() {
+    let encoded = [zstd(b"<p>first"), zstd(b"</p>")].concat();
+
+    assert_eq!(decode("zstd", &encoded, 3).unwrap(), b"<p>first</p>");
+}
+
+#[test]
+fn truncated_bodies_fail_to_decode() {
+    let body = page(100_000);
+
+    for (content_encoding, encode) in ENCODERS {
+        let encoded = encode(&body);
+
+        for length in [encoded.len() / 2, encoded.len() - 1] {
+            assert!(
+                decode(content_encoding, &encoded[..length], 4096).is_err(),
+                "{content_encoding} truncated to {length} of {} octets",
+                encoded.len()
+            );
+        }
+    }
+    let error = decode("zstd", &zstd(&body)[..100], 4096).unwrap_err();
+    assert_eq!(error.kind(), io::ErrorKind::UnexpectedEof);
+}
+
+#[test]
+fn decoding_stops_once_the_sink_fails() {
+    const LIMIT: usize = 1024 * 1024;
+    let body = vec![0; 16 * LIMIT];
+
+    for (content_encoding, encode) in ENCODERS {
+        let encoded = encode(&body);
+        let mut body_decoder = StreamingContentDecoder::new()
+            .new_body_decoder(Some(content_encoding))
+            .unwrap()
+            .unwrap();
+        let mut decoded_length = 0;
+        let mut longest_piece = 0;
+
+        // The whole encoded body is a single chunk:
+        let result = body_decoder.decode_chunk(&encoded, &mut |octets: &[u8]| {
+            decoded_length += octets.len();
+            longest_piece = longest_piece.max(octets.len());
+            match decoded_length > LIMIT {
+                true => Err(io::Error::other("body size limit exceeded")),
+                false => Ok(()),
+            }
+        });
+
+        assert!(result.is_err(), "{content_encoding}");
+        assert!(longest_piece <= 64 * 1024, "{content_encoding}");
+        assert!(
+            decoded_length <= LIMIT + longest_piece,
+            "{content_encoding}"
+        );
+    }
+}
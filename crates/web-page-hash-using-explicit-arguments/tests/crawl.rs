@@ -0,0 +1,307 @@
+mod common;
+
+use common::{TestServer, respond, run};
+use std::net::TcpStream;
+use std::process::Output;
+use web_page_hash_using_explicit_arguments::crawl_service::RobotsTxt;
+
+/// The Web pages of a fixture site by their paths, and their media types,
+/// where `/c` links to another origin, and `/notes.txt` has no links
+const SITE: &[(&str, &str, &str)] = &[
+    (
+        "/",
+        "text/html",
+        r#"<a href="/a">A</a> <a href="b#top">B</a> <a href="/">Home</a>"#,
+    ),
+    (
+        "/a",
+        "text/html; charset=utf-8",
+        r#"<a href="/c">C</a> <a href="mailto:a@example.com">Mail</a> <a href="/b">B</a>"#,
+    ),
+    (
+        "/b",
+        "text/html",
+        r#"<map><area href="/private/d"></map> <iframe src="/e"></iframe>"#,
+    ),
+    (
+        "/c",
+        "application/xhtml+xml",
+        r#"<a href="http://other.invalid/">Other</a> <a href="/f">F</a>"#,
+    ),
+    ("/private/d", "text/html", "<p>Private</p>"),
+    (
+        "/e",
+        "text/html",
+        r#"<base href="/docs/"><a href="../g">G</a>"#,
+    ),
+    ("/f", "text/html", "<p>F</p>"),
+    ("/g", "text/html", r#"<a href="/notes.txt">Notes</a>"#),
+    ("/notes.txt", "text/plain", r#"<a href="/h">H</a>"#),
+];
+
+/// Answers a request with a Web page of the fixture site, or `404 Not
+/// Found`
+fn answer(request: &common::Request, stream: &mut TcpStream) {
+    match SITE.iter().find(|(path, _, _)| *path == request.path) {
+        Some((_, media_type, body)) => respond(
+            stream,
+            "200 OK",
+            &[("Content-Type", media_type)],
+            body.as_bytes(),
+        ),
+        None => respond(stream, "404 Not Found", &[], b""),
+    }
+}
+
+/// Starts a server of the fixture site with a `robots.txt`
+fn serve_site_with_robots_txt(robots_txt: &'static str) -> TestServer {
+    TestServer::start(move |request, stream| match request.path.as_str() {
+        "/robots.txt" => respond(
+            stream,
+            "200 OK",
+            &[("Content-Type", "text/plain")],
+            robots_txt.as_bytes(),
+        ),
+        _ => answer(request, stream),
+    })
+}
+
+/// Crawls the site of `server` from `start_path`
+fn crawl(server: &TestServer, start_path: &str, args: &[&str]) -> Output {
+    let home = tempfile::tempdir().unwrap();
+    let start_url = server.url(start_path);
+
+    run(
+        home.path(),
+        &[&["crawl", start_url.as_str()], args].concat(),
+    )
+}
+
+/// Returns the paths of the GNU-style manifest of a crawl
+fn manifest_paths(output: &Output) -> Vec<String> {
+    String::from_utf8(output.stdout.clone())
+        .unwrap()
+        .lines()
+        .map(|line| {
+            let (_, url) = line.split_once("  ").expect("a GNU-style checksum line");
+
+            url::Url::parse(url).unwrap().path().to_string()
+        })
+        .collect()
+}
+
+/// Returns the paths requested from `server`, except `/robots.txt`
+fn requested_paths(server: &TestServer) -> Vec<String> {
+    server
+        .requests()
+        .into_iter()
+        .map(|request| request.path)
+        .filter(|path| path != "/robots.txt")
+        .collect()
+}
+
+#[test]
+fn web_pages_are_crawled_breadth_first_once() {
+    let server = TestServer::start(answer);
+
+    for concurrency in ["1", "4"] {
+        let output = crawl(&server, "/#top", &["--concurrency", concurrency]);
+
+        assert!(output.status.success(), "{:?}", output);
+        assert_eq!(
+            manifest_paths(&output),
+            [
+                "/",
+                "/a",
+                "/b",
+                "/c",
+                "/private/d",
+                "/e",
+                "/f",
+                "/g",
+                "/notes.txt"
+            ]
+        );
+    }
+    // Links of other media types than HTML aren't followed:
+    assert!(!requested_paths(&server).contains(&"/h".to_string()));
+    let mut requested_paths = requested_paths(&server);
+    requested_paths.sort();
+    requested_paths.dedup();
+    assert_eq!(requested_paths.len(), 9);
+}
+
+#[test]
+fn links_beyond_the_maximum_depth_arent_followed() {
+    let server = TestServer::start(answer);
+
+    let output = crawl(&server, "/", &["--max-depth", "1"]);
+
+    assert!(output.status.success(), "{:?}", output);
+    assert_eq!(manifest_paths(&output), ["/", "/a", "/b"]);
+
+    let output = crawl(&server, "/", &["--max-depth", "0"]);
+
+    assert!(output.status.success(), "{:?}", output);
+    assert_eq!(manifest_paths(&output), ["/"]);
+}
+
+#[test]
+fn web_pages_beyond_the_page_limit_arent_calculated() {
+    let server = TestServer::start(answer);
+
+    let output = crawl(&server, "/", &["--max-pages", "4"]);
+
+    assert!(output.status.success(), "{:?}", output);
+    assert_eq!(manifest_paths(&output), ["/", "/a", "/b", "/c"]);
+    assert_eq!(requested_paths(&server), ["/", "/a", "/b", "/c"]);
+}
+
+#[test]
+fn web_pages_disallowed_by_robots_txt_arent_calculated() {
+    let server = serve_site_with_robots_txt(
+        "User-agent: *\nDisallow: /private/\n\nUser-agent: other-bot\nDisallow: /\n",
+    );
+
+    let output = crawl(&server, "/", &[]);
+
+    assert!(output.status.success(), "{:?}", output);
+    assert_eq!(
+        manifest_paths(&output),
+        ["/", "/a", "/b", "/c", "/e", "/f", "/g", "/notes.txt"]
+    );
+    assert!(!requested_paths(&server).contains(&"/private/d".to_string()));
+
+    let output = crawl(&server, "/private/d", &[]);
+
+    assert!(!output.status.success());
+    assert!(manifest_paths(&output).is_empty());
+    assert!(
+        String::from_utf8_lossy(&output.stderr).contains("robots.txt disallows crawling"),
+        "{:?}",
+        output
+    );
+}
+
+#[test]
+fn links_out_of_scope_arent_followed() {
+    let server = TestServer::start(answer);
+    let prefix = server.url("/a");
+
+    // The start URL is calculated even if it's out of scope:
+    let output = crawl(&server, "/", &["--prefix", &prefix]);
+
+    assert!(output.status.success(), "{:?}", output);
+    assert_eq!(manifest_paths(&output), ["/", "/a"]);
+}
+
+#[test]
+fn links_of_web_pages_failing_to_be_fetched_arent_followed() {
+    // `/a` closes the connection without a response:
+    let server = TestServer::start(|request, stream| {
+        if request.path != "/a" {
+            answer(request, stream);
+        }
+    });
+
+    let output = crawl(&server, "/", &[]);
+
+    assert_eq!(output.status.code(), Some(2), "{:?}", output);
+    assert_eq!(
+        manifest_paths(&output),
+        ["/", "/b", "/private/d", "/e", "/g", "/notes.txt"]
+    );
+    assert!(
+        String::from_utf8_lossy(&output.stderr).contains(&server.url("/a")),
+        "{:?}",
+        output
+    );
+}
+
+#[test]
+fn manifests_are_printed_as_json() {
+    let server = TestServer::start(answer);
+
+    let output = crawl(&server, "/", &["--max-depth", "1", "--output", "json"]);
+
+    assert!(output.status.success(), "{:?}", output);
+    let records: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let records = records.as_array().unwrap();
+    assert_eq!(records.len(), 3);
+    assert_eq!(records[0]["url"], server.url("/"));
+    assert_eq!(records[1]["url"], server.url("/a"));
+    assert_eq!(records[1]["content_type"], "text/html; charset=utf-8");
+    assert_eq!(records[2]["status"], 200);
+}
+
+/// Returns a URL of `https://example.com`
+fn url(path: &str) -> url::Url {
+    url::Url::parse("https://example.com")
+        .unwrap()
+        .join(path)
+        .unwrap()
+}
+
+#[test]
+fn robots_txt_url_is_at_the_root_of_the_origin() {
+    assert_eq!(
+        RobotsTxt::url(&url("/docs/page.html?q=1")).unwrap(),
+        url("/robots.txt")
+    );
+}
+
+#[test]
+fn robots_txt_groups_of_the_product_token_take_precedence() {
+    let text = "\
+        # Comments, and unknown lines are ignored\n\
+        Sitemap: https://example.com/sitemap.xml\n\
+        User-agent: *\n\
+        Disallow: /\n\
+        \n\
+        User-agent: other-bot\n\
+        User-agent: Web-Page-Hash # Product tokens ignore case\n\
+        Disallow: /private\n";
+
+    let robots_txt = RobotsTxt::parse(text, "web-page-hash");
+    assert!(robots_txt.allows(&url("/public")));
+    assert!(!robots_txt.allows(&url("/private/page")));
+
+    let robots_txt = RobotsTxt::parse(text, "another-bot");
+    assert!(!robots_txt.allows(&url("/public")));
+    // `robots.txt` itself is always allowed:
+    assert!(robots_txt.allows(&url("/robots.txt")));
+}
+
+#[test]
+fn robots_txt_longest_matching_rules_decide() {
+    let robots_txt = RobotsTxt::parse(
+        "User-agent: *\n\
+         Disallow: /docs/\n\
+         Allow: /docs/public/\n\
+         Disallow: /tie\n\
+         Allow: /tie\n\
+         Disallow: /*.pdf$\n\
+         Disallow: /search?*q=\n\
+         Disallow:\n",
+        "web-page-hash",
+    );
+
+    assert!(!robots_txt.allows(&url("/docs/private.html")));
+    assert!(robots_txt.allows(&url("/docs/public/page.html")));
+    // `allow` wins ties:
+    assert!(robots_txt.allows(&url("/tie")));
+    assert!(!robots_txt.allows(&url("/files/report.pdf")));
+    assert!(robots_txt.allows(&url("/files/report.pdf.html")));
+    assert!(!robots_txt.allows(&url("/search?lang=en&q=rust")));
+    assert!(robots_txt.allows(&url("/search?lang=en")));
+    assert!(robots_txt.allows(&url("/other")));
+}
+
+#[test]
+fn robots_txt_without_rules_allows_everything() {
+    assert!(RobotsTxt::default().allows(&url("/private")));
+    assert!(RobotsTxt::parse("User-agent: *\nDisallow:\n", "web-page-hash").allows(&url("/")));
+    assert!(
+        RobotsTxt::parse("User-agent: other-bot\nDisallow: /\n", "web-page-hash").allows(&url("/"))
+    );
+}
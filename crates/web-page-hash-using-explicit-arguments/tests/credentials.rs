@@ -0,0 +1,158 @@
+mod common;
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use common::{TestServer, command, respond};
+use web_page_hash_using_explicit_arguments::credential_provider_service::Credential;
+
+fn start_server() -> TestServer {
+    TestServer::start(|_, stream| respond(stream, "200 OK", &[], b"page"))
+}
+
+fn basic(user_pass: &str) -> String {
+    format!("Basic {}", STANDARD.encode(user_pass))
+}
+
+/// Requests the server with the arguments, and environment variables, and
+/// returns the `Authorization` header value it received
+fn authorization(args: &[&str], env: &[(&str, &str)]) -> Option<String> {
+    let home = tempfile::tempdir().unwrap();
+    std::fs::write(
+        home.path().join(".netrc"),
+        "machine 127.0.0.1 login carol password netrc-password\n",
+    )
+    .unwrap();
+    let server = start_server();
+
+    let output = command(home.path())
+        .args(["--url", &server.url("/")])
+        .args(args)
+        .envs(env.iter().copied())
+        .output()
+        .unwrap();
+
+    assert!(output.status.success(), "{:?}", output);
+    server.requests()[0]
+        .header("Authorization")
+        .map(str::to_string)
+}
+
+#[test]
+fn bearer_tokens_are_sent() {
+    assert_eq!(
+        authorization(&["--bearer-token", "token"], &[]),
+        Some("Bearer token".to_string())
+    );
+}
+
+#[test]
+fn basic_authentication_credentials_are_sent() {
+    assert_eq!(
+        authorization(&["--user", "alice:secret"], &[]),
+        Some(basic("alice:secret"))
+    );
+    assert_eq!(
+        authorization(&["--user", "alice"], &[]),
+        Some(basic("alice:"))
+    );
+}
+
+#[test]
+fn netrc_credentials_are_looked_up_by_host_name() {
+    assert_eq!(
+        authorization(&["--netrc"], &[]),
+        Some(basic("carol:netrc-password"))
+    );
+
+    let netrc_file = tempfile::NamedTempFile::new().unwrap();
+    std::fs::write(
+        netrc_file.path(),
+        "machine example.com login alice password other-host\n\
+         default login dave password default-password\n",
+    )
+    .unwrap();
+    let netrc_path = netrc_file.path().to_str().unwrap();
+    assert_eq!(
+        authorization(&["--netrc-file", netrc_path], &[]),
+        Some(basic("dave:default-password"))
+    );
+    assert_eq!(
+        authorization(&["--netrc"], &[("NETRC", netrc_path)]),
+        Some(basic("dave:default-password"))
+    );
+}
+
+#[test]
+fn credentials_are_read_from_environment_variables() {
+    assert_eq!(authorization(&[], &[]), None);
+    assert_eq!(
+        authorization(&[], &[("WEB_PAGE_HASH_BEARER_TOKEN", "token")]),
+        Some("Bearer token".to_string())
+    );
+    assert_eq!(
+        authorization(
+            &[],
+            &[
+                ("WEB_PAGE_HASH_USERNAME", "alice"),
+                ("WEB_PAGE_HASH_PASSWORD", "secret")
+            ]
+        ),
+        Some(basic("alice:secret"))
+    );
+    // A token takes precedence over a user name:
+    assert_eq!(
+        authorization(
+            &[],
+            &[
+                ("WEB_PAGE_HASH_BEARER_TOKEN", "token"),
+                ("WEB_PAGE_HASH_USERNAME", "alice")
+            ]
+        ),
+        Some("Bearer token".to_string())
+    );
+    // Arguments take precedence over environment variables:
+    assert_eq!(
+        authorization(
+            &["--user", "bob:password"],
+            &[("WEB_PAGE_HASH_BEARER_TOKEN", "token")]
+        ),
+        Some(basic("bob:password"))
+    );
+}
+
+#[test]
+fn authorization_headers_take_precedence_over_credentials() {
+    assert_eq!(
+        authorization(&["--header", "Authorization: Token header", "--netrc"], &[]),
+        Some("Token header".to_string())
+    );
+}
+
+#[test]
+fn credentials_are_redacted() {
+    let credential = Credential::bearer("secret-token").unwrap();
+    assert_eq!(format!("{:?}", credential), "Credential(<redacted>)");
+    assert!(credential.authorization().is_sensitive());
+    let credential = Credential::basic("alice", Some("secret"));
+    assert_eq!(format!("{:?}", credential), "Credential(<redacted>)");
+
+    let home = tempfile::tempdir().unwrap();
+    let server = start_server();
+    let output = command(home.path())
+        .args(["--url", &server.url("/")])
+        .args(["--progress", "none", "--log-format", "json"])
+        .env("WEB_PAGE_HASH_BEARER_TOKEN", "secret-token")
+        .env("RUST_LOG", "trace")
+        .output()
+        .unwrap();
+
+    assert!(output.status.success(), "{:?}", output);
+    assert_eq!(
+        server.requests()[0].header("Authorization"),
+        Some("Bearer secret-token")
+    );
+    let traces = String::from_utf8_lossy(&output.stderr);
+    assert!(traces.contains("127.0.0.1"), "{}", traces);
+    assert!(!traces.contains("secret-token"), "{}", traces);
+    assert!(!String::from_utf8_lossy(&output.stdout).contains("secret-token"));
+}
@@ -0,0 +1,66 @@
+use std::io::Write;
+use web_page_hash_using_explicit_arguments::message_digest_service::{
+    DigestAlgorithmName, DigestParts, FanOutDigest, FanOutMessageDigestService, IntoDigestOctets,
+    MessageDigestAlgorithm, NewDigestCalculator, SelectableMessageDigestService,
+};
+
+const ALGORITHMS: [MessageDigestAlgorithm; 2] = [
+    MessageDigestAlgorithm::Sha3_256,
+    MessageDigestAlgorithm::Sha512,
+];
+
+/// Returns the digest of `message` calculated with `algorithm` alone
+fn digest(algorithm: MessageDigestAlgorithm, message: &[u8]) -> Vec<u8> {
+    let mut digest_calculator = SelectableMessageDigestService::new(algorithm)
+        .new_digest_calculator()
+        .unwrap();
+    digest_calculator.write_all(message).unwrap();
+
+    digest_calculator.into_digest_octets().unwrap().to_vec()
+}
+
+#[test]
+fn the_digest_of_each_algorithm_is_returned_in_order() {
+    let message_digest_service = FanOutMessageDigestService::new(
+        ALGORITHMS
+            .into_iter()
+            .map(SelectableMessageDigestService::new)
+            .collect(),
+    );
+    let mut digest_calculator = message_digest_service.new_digest_calculator().unwrap();
+    digest_calculator.write_all(b"<p>page").unwrap();
+    digest_calculator.write_all(b"</p>").unwrap();
+
+    let fan_out_digest = digest_calculator.into_digest_octets().unwrap();
+
+    assert_eq!(
+        message_digest_service.digest_algorithm_name(),
+        "SHA3-256,SHA512"
+    );
+    assert_eq!(
+        fan_out_digest.digests(),
+        ALGORITHMS.map(|algorithm| digest(algorithm, b"<p>page</p>"))
+    );
+    assert_eq!(
+        fan_out_digest.digest_parts(),
+        fan_out_digest
+            .digests()
+            .iter()
+            .map(Vec::as_slice)
+            .collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn memoized_digest_octets_are_parsed_into_the_same_digests() {
+    let fan_out_digest = FanOutDigest::new(vec![vec![1, 2, 3], Vec::new(), vec![4]]);
+
+    assert_eq!(
+        FanOutDigest::try_from(fan_out_digest.as_ref()).unwrap(),
+        fan_out_digest
+    );
+    // A length beyond the octets:
+    assert!(FanOutDigest::try_from(&[0, 0, 0, 2, 1][..]).is_err());
+    // A truncated length:
+    assert!(FanOutDigest::try_from(&[0, 0, 1][..]).is_err());
+}
@@ -0,0 +1,49 @@
+#![cfg(feature = "hyper")]
+
+mod common;
+
+use common::TestServer;
+use futures_util::future::{self, Either};
+use std::pin::pin;
+use std::thread;
+use std::time::Duration;
+use web_page_hash_using_explicit_arguments::clock_service::{ManualClock, Now};
+use web_page_hash_using_explicit_arguments::configuration_service::{
+    ConfigurationLayer, LayeredConfigurationService,
+};
+use web_page_hash_using_explicit_arguments::http_client_service::{
+    GetUrl, HyperGetUrlError, HyperGetUrlService,
+};
+
+#[tokio::test]
+async fn requests_time_out_on_the_sleeper() {
+    // The server never answers before the test finishes:
+    let server = TestServer::start(|_, _| thread::sleep(Duration::from_secs(10)));
+    let clock = ManualClock::default();
+    let configuration = LayeredConfigurationService::new([ConfigurationLayer {
+        timeout: Some(60),
+        ..ConfigurationLayer::default()
+    }]);
+    let http_client_service =
+        HyperGetUrlService::from_configuration(&configuration, clock.clone()).unwrap();
+    let url = url::Url::parse(&server.url("/")).unwrap();
+
+    let advancing = async {
+        loop {
+            tokio::task::yield_now().await;
+            clock.advance(Duration::from_secs(1));
+        }
+    };
+    let response_result =
+        match future::select(pin!(http_client_service.get_url(&url)), pin!(advancing)).await {
+            Either::Left((response_result, _)) => response_result,
+            Either::Right(_) => unreachable!("advancing the clock never finishes"),
+        };
+
+    assert!(matches!(response_result, Err(HyperGetUrlError::TimedOut)));
+    let elapsed = clock
+        .now()
+        .duration_since(std::time::SystemTime::UNIX_EPOCH)
+        .unwrap();
+    assert!(elapsed >= Duration::from_secs(60));
+}
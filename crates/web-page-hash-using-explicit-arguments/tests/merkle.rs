@@ -0,0 +1,83 @@
+use std::io::Write;
+use web_page_hash_using_explicit_arguments::message_digest_service::{
+    IntoDigestOctets, MerkleMessageDigestService, NewDigestCalculator, RestoreDigestCalculator,
+    SaveDigestState, Sha3_256BitMessageDigestService,
+};
+
+/// Returns a saved Merkle digest state of `leaf_count` leaves with zeroed
+/// digests, and a new leaf digest calculator
+fn digest_state(leaf_size: u64, byte_count: u64, leaf_count: u32) -> Vec<u8> {
+    let leaf_digest_state = Sha3_256BitMessageDigestService::new()
+        .new_digest_calculator()
+        .unwrap()
+        .save_digest_state()
+        .unwrap();
+
+    [
+        &leaf_size.to_be_bytes()[..],
+        &byte_count.to_be_bytes(),
+        &32u32.to_be_bytes(),
+        &leaf_count.to_be_bytes(),
+        &vec![0; 32 * leaf_count as usize],
+        &leaf_digest_state,
+    ]
+    .concat()
+}
+
+#[test]
+fn restored_digests_match_uninterrupted_digests() {
+    let message_digest_service =
+        MerkleMessageDigestService::new(Sha3_256BitMessageDigestService::new(), 4);
+    let mut digest_calculator = message_digest_service.new_digest_calculator().unwrap();
+    digest_calculator.write_all(b"0123456").unwrap();
+
+    let digest_state = digest_calculator.save_digest_state().unwrap();
+    let mut restored_digest_calculator = message_digest_service
+        .restore_digest_calculator(&digest_state)
+        .unwrap();
+    digest_calculator.write_all(b"789").unwrap();
+    restored_digest_calculator.write_all(b"789").unwrap();
+
+    assert_eq!(
+        restored_digest_calculator.into_digest_octets().unwrap(),
+        digest_calculator.into_digest_octets().unwrap()
+    );
+}
+
+#[test]
+fn inconsistent_digest_states_arent_restored() {
+    let message_digest_service =
+        MerkleMessageDigestService::new(Sha3_256BitMessageDigestService::new(), 4);
+
+    assert!(
+        message_digest_service
+            .restore_digest_calculator(&digest_state(4, 9, 2))
+            .is_some()
+    );
+    // Another leaf size:
+    assert!(
+        message_digest_service
+            .restore_digest_calculator(&digest_state(8, 9, 1))
+            .is_none()
+    );
+    // More octets than the leaves, and a partial leaf:
+    assert!(
+        message_digest_service
+            .restore_digest_calculator(&digest_state(4, 12, 2))
+            .is_none()
+    );
+    // Fewer octets than the leaves:
+    assert!(
+        message_digest_service
+            .restore_digest_calculator(&digest_state(4, 7, 2))
+            .is_none()
+    );
+    // Leaves larger than 2^64 octets:
+    let message_digest_service =
+        MerkleMessageDigestService::new(Sha3_256BitMessageDigestService::new(), u64::MAX);
+    assert!(
+        message_digest_service
+            .restore_digest_calculator(&digest_state(u64::MAX, u64::MAX, 2))
+            .is_none()
+    );
+}
@@ -0,0 +1,232 @@
+mod common;
+
+use common::run;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer, PrivatePkcs8KeyDer};
+use rustls::{ServerConfig, ServerConnection, StreamOwned};
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use web_page_hash_using_explicit_arguments::http_client_service::CertificateFingerprint;
+
+/// A fingerprint of no certificate of the tests
+const OTHER_FINGERPRINT: &str = "00:11:22:33:44:55:66:77:88:99:aa:bb:cc:dd:ee:ff:\
+    00:11:22:33:44:55:66:77:88:99:aa:bb:cc:dd:ee:ff";
+
+/// An HTTPS server on a local port with a self-signed certificate for
+/// `localhost`, counting the requests it answers
+struct TlsTestServer {
+    port: u16,
+    fingerprint: CertificateFingerprint,
+    request_count: Arc<AtomicUsize>,
+}
+
+impl TlsTestServer {
+    fn start() -> Self {
+        let rcgen::CertifiedKey { cert, signing_key } =
+            rcgen::generate_simple_self_signed(vec!["localhost".to_string()]).unwrap();
+        let certificate = CertificateDer::from(cert.der().to_vec());
+        let fingerprint = CertificateFingerprint::of(&certificate);
+        let private_key =
+            PrivateKeyDer::Pkcs8(PrivatePkcs8KeyDer::from(signing_key.serialize_der()));
+        let config = Arc::new(
+            ServerConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+                .with_safe_default_protocol_versions()
+                .unwrap()
+                .with_no_client_auth()
+                .with_single_cert(vec![certificate], private_key)
+                .unwrap(),
+        );
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let request_count = Arc::new(AtomicUsize::new(0));
+        let answered_request_count = request_count.clone();
+
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(stream) = stream else {
+                    continue;
+                };
+                let connection = ServerConnection::new(config.clone()).unwrap();
+                let mut tls_stream = StreamOwned::new(connection, stream);
+                // Handshakes with unpinned certificates fail here:
+                if !read_request_head(&mut tls_stream) {
+                    continue;
+                }
+                answered_request_count.fetch_add(1, Ordering::SeqCst);
+                let _ = tls_stream.write_all(
+                    b"HTTP/1.1 200 OK\r\nContent-Length: 6\r\nConnection: close\r\n\r\npinned",
+                );
+                tls_stream.conn.send_close_notify();
+                let _ = tls_stream.flush();
+            }
+        });
+
+        TlsTestServer {
+            port,
+            fingerprint,
+            request_count,
+        }
+    }
+
+    fn url(&self) -> String {
+        format!("https://localhost:{}/", self.port)
+    }
+
+    fn request_count(&self) -> usize {
+        self.request_count.load(Ordering::SeqCst)
+    }
+}
+
+/// Reads a request up to the empty line after its headers
+fn read_request_head(stream: &mut impl std::io::Read) -> bool {
+    let mut reader = BufReader::new(stream);
+    loop {
+        let mut line = String::new();
+        match reader.read_line(&mut line) {
+            Ok(0) | Err(_) => return false,
+            Ok(_) if line.trim_end().is_empty() => return true,
+            Ok(_) => {}
+        }
+    }
+}
+
+fn backends() -> Vec<&'static str> {
+    let mut backends = vec!["reqwest"];
+    if cfg!(feature = "hyper") {
+        backends.push("hyper");
+    }
+
+    backends
+}
+
+#[test]
+fn pinned_self_signed_certificates_are_trusted() {
+    let home = tempfile::tempdir().unwrap();
+    let server = TlsTestServer::start();
+
+    for backend in backends() {
+        let output = run(
+            home.path(),
+            &[
+                "--url",
+                &server.url(),
+                "--http-backend",
+                backend,
+                "--pin-cert-sha256",
+                &server.fingerprint.to_string(),
+            ],
+        );
+
+        assert!(output.status.success(), "{}: {:?}", backend, output);
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(
+            stdout.contains(&format!("Certificate SHA-256: {}", server.fingerprint)),
+            "{}: {}",
+            backend,
+            stdout
+        );
+    }
+    assert_eq!(server.request_count(), backends().len());
+}
+
+#[test]
+fn certificates_that_arent_pinned_fail_before_a_request_is_sent() {
+    let home = tempfile::tempdir().unwrap();
+    let server = TlsTestServer::start();
+
+    for backend in backends() {
+        let output = run(
+            home.path(),
+            &[
+                "--url",
+                &server.url(),
+                "--http-backend",
+                backend,
+                "--pin-cert-sha256",
+                OTHER_FINGERPRINT,
+            ],
+        );
+
+        assert_eq!(output.status.code(), Some(2), "{}: {:?}", backend, output);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(
+            stderr.contains(&format!(
+                "the server certificate with SHA-256 fingerprint {} isn't pinned",
+                server.fingerprint
+            )),
+            "{}: {}",
+            backend,
+            stderr
+        );
+    }
+    assert_eq!(server.request_count(), 0);
+}
+
+#[test]
+fn self_signed_certificates_arent_trusted_without_pins() {
+    let home = tempfile::tempdir().unwrap();
+    let server = TlsTestServer::start();
+
+    for backend in backends() {
+        let output = run(
+            home.path(),
+            &["--url", &server.url(), "--http-backend", backend],
+        );
+
+        assert_eq!(output.status.code(), Some(2), "{}: {:?}", backend, output);
+    }
+    assert_eq!(server.request_count(), 0);
+}
+
+#[test]
+fn replayed_responses_are_checked_against_their_recorded_certificates() {
+    let home = tempfile::tempdir().unwrap();
+    let cassette_path = home.path().join("cassette.json");
+    let cassette = cassette_path.to_str().unwrap();
+    let server = TlsTestServer::start();
+    let fingerprint = server.fingerprint.to_string();
+    let replay = |pin: &str| {
+        run(
+            home.path(),
+            &[
+                "--url",
+                &server.url(),
+                "--replay",
+                cassette,
+                "--pin-cert-sha256",
+                pin,
+            ],
+        )
+    };
+
+    let recording = run(
+        home.path(),
+        &[
+            "--url",
+            &server.url(),
+            "--record",
+            cassette,
+            "--pin-cert-sha256",
+            &fingerprint,
+        ],
+    );
+    assert!(recording.status.success(), "{:?}", recording);
+
+    let output = replay(&fingerprint);
+    assert!(output.status.success(), "{:?}", output);
+
+    let output = replay(OTHER_FINGERPRINT);
+    assert_eq!(output.status.code(), Some(2), "{:?}", output);
+    assert!(
+        String::from_utf8_lossy(&output.stderr).contains(&format!(
+            "the server certificate with SHA-256 fingerprint {} recorded for {} isn't pinned",
+            server.fingerprint,
+            server.url()
+        )),
+        "{:?}",
+        output
+    );
+    assert_eq!(server.request_count(), 1);
+}
@@ -0,0 +1,109 @@
+use bytes::Bytes;
+use http::{HeaderMap, HeaderValue, StatusCode};
+use web_page_hash_using_explicit_arguments::body_sink_service::NoBodySink;
+use web_page_hash_using_explicit_arguments::calculate_web_page_message_digest_service::{
+    CalculateWebPageMessageDigest, CalculateWebPageMessageDigestService,
+};
+use web_page_hash_using_explicit_arguments::configuration_service::{
+    ConfigurationLayer, LayeredConfigurationService,
+};
+use web_page_hash_using_explicit_arguments::digest_memo_service::NoDigestMemoService;
+use web_page_hash_using_explicit_arguments::http_cassette::{Cassette, CassetteInteraction};
+use web_page_hash_using_explicit_arguments::http_client_service::ReplayGetUrl;
+use web_page_hash_using_explicit_arguments::message_digest_service::Sha3_256BitMessageDigestService;
+use web_page_hash_using_explicit_arguments::progress_reporter_service::{
+    ProgressEvent, RecordingProgressReporter,
+};
+use web_page_hash_using_explicit_arguments::response_policy_service::ConfiguredResponsePolicy;
+
+const URL: &str = "http://example.com/page";
+
+/// Calculates the digest of a Web page replayed in chunks, and returns the
+/// progress events reported meanwhile
+async fn report_progress(
+    configuration_layer: ConfigurationLayer,
+    headers: HeaderMap,
+) -> (Result<[u8; 32], anyhow::Error>, Vec<ProgressEvent>) {
+    let mut cassette = Cassette::default();
+    cassette.record(CassetteInteraction::new(
+        URL.to_string(),
+        None,
+        Vec::new(),
+        Vec::new(),
+        StatusCode::OK,
+        headers,
+        vec![Bytes::from_static(b"<p>page"), Bytes::from_static(b"</p>")],
+    ));
+    let progress_reporter = RecordingProgressReporter::new();
+    let calculate_web_page_message_digest_service = CalculateWebPageMessageDigestService::<
+        url::Url,
+        [u8; 32],
+        anyhow::Error,
+        _,
+        _,
+        _,
+        _,
+        _,
+        _,
+    >::new(
+        ReplayGetUrl::new(cassette),
+        Sha3_256BitMessageDigestService::new(),
+        NoDigestMemoService::new(),
+        progress_reporter.clone(),
+        ConfiguredResponsePolicy::from_configuration(&LayeredConfigurationService::new([
+            configuration_layer,
+        ])),
+        NoBodySink::new(),
+    );
+
+    let digest_result = calculate_web_page_message_digest_service
+        .calculate_web_page_message_digest(&url::Url::parse(URL).unwrap())
+        .await;
+
+    (digest_result, progress_reporter.events())
+}
+
+#[tokio::test]
+async fn progress_is_reported_for_each_chunk() {
+    let headers =
+        HeaderMap::from_iter([(http::header::CONTENT_LENGTH, HeaderValue::from_static("11"))]);
+
+    let (digest_result, events) = report_progress(ConfigurationLayer::default(), headers).await;
+
+    assert!(digest_result.is_ok());
+    assert_eq!(
+        events,
+        [
+            ProgressEvent::Started {
+                url: URL.to_string(),
+                expected_length: Some(11),
+            },
+            ProgressEvent::BytesWritten(7),
+            ProgressEvent::BytesWritten(4),
+            ProgressEvent::Finished,
+        ]
+    );
+}
+
+#[tokio::test]
+async fn progress_is_finished_when_streaming_fails() {
+    let configuration_layer = ConfigurationLayer {
+        max_body_size: Some(8),
+        ..ConfigurationLayer::default()
+    };
+
+    let (digest_result, events) = report_progress(configuration_layer, HeaderMap::new()).await;
+
+    assert!(digest_result.is_err());
+    assert_eq!(
+        events,
+        [
+            ProgressEvent::Started {
+                url: URL.to_string(),
+                expected_length: None,
+            },
+            ProgressEvent::BytesWritten(7),
+            ProgressEvent::Finished,
+        ]
+    );
+}
@@ -0,0 +1,147 @@
+mod common;
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use common::{TestServer, respond, run};
+
+/// Starts a server on `localhost` answering with a page, and a server on
+/// `127.0.0.1`, another origin, redirecting `/start` to `/hop`, and `/hop`
+/// to the first server
+fn start_servers() -> (TestServer, TestServer) {
+    let target = TestServer::start(|_, stream| respond(stream, "200 OK", &[], b"page"));
+    let target_url = format!("http://localhost:{}/page", target.port());
+    let origin = TestServer::start(move |request, stream| {
+        let location = match request.path.as_str() {
+            "/start" => "/hop",
+            _ => target_url.as_str(),
+        };
+
+        respond(stream, "302 Found", &[("Location", location)], b"");
+    });
+
+    (origin, target)
+}
+
+#[test]
+fn cross_origin_redirects_drop_request_headers_and_credentials() {
+    let home = tempfile::tempdir().unwrap();
+    let (origin, target) = start_servers();
+
+    let output = run(
+        home.path(),
+        &[
+            "--url",
+            &origin.url("/start"),
+            "--header",
+            "X-Api-Key: secret",
+            "--bearer-token",
+            "token",
+        ],
+    );
+
+    assert!(output.status.success(), "{:?}", output);
+    let origin_requests = origin.requests();
+    assert_eq!(origin_requests.len(), 2);
+    for request in &origin_requests {
+        assert_eq!(request.header("X-Api-Key"), Some("secret"));
+        assert_eq!(request.header("Authorization"), Some("Bearer token"));
+    }
+    let target_requests = target.requests();
+    assert_eq!(target_requests.len(), 1);
+    assert_eq!(target_requests[0].header("X-Api-Key"), None);
+    assert_eq!(target_requests[0].header("Authorization"), None);
+}
+
+#[test]
+fn cross_origin_redirects_send_netrc_credentials_of_their_host() {
+    let home = tempfile::tempdir().unwrap();
+    let netrc_path = home.path().join("netrc");
+    std::fs::write(
+        &netrc_path,
+        "machine 127.0.0.1 login alice password origin-password\n\
+         machine localhost login bob password target-password\n",
+    )
+    .unwrap();
+    let (origin, target) = start_servers();
+
+    let output = run(
+        home.path(),
+        &[
+            "--url",
+            &origin.url("/start"),
+            "--netrc-file",
+            netrc_path.to_str().unwrap(),
+        ],
+    );
+
+    assert!(output.status.success(), "{:?}", output);
+    let origin_authorization = format!("Basic {}", STANDARD.encode("alice:origin-password"));
+    for request in origin.requests() {
+        assert_eq!(
+            request.header("Authorization"),
+            Some(origin_authorization.as_str())
+        );
+    }
+    let target_authorization = format!("Basic {}", STANDARD.encode("bob:target-password"));
+    assert_eq!(
+        target.requests()[0].header("Authorization"),
+        Some(target_authorization.as_str())
+    );
+}
+
+#[test]
+fn cross_origin_redirects_dont_send_default_netrc_credentials() {
+    let home = tempfile::tempdir().unwrap();
+    let netrc_path = home.path().join("netrc");
+    std::fs::write(&netrc_path, "default login alice password secret\n").unwrap();
+    let (origin, target) = start_servers();
+
+    let output = run(
+        home.path(),
+        &[
+            "--url",
+            &origin.url("/start"),
+            "--netrc-file",
+            netrc_path.to_str().unwrap(),
+        ],
+    );
+
+    assert!(output.status.success(), "{:?}", output);
+    assert!(origin.requests()[0].header("Authorization").is_some());
+    assert_eq!(target.requests()[0].header("Authorization"), None);
+}
+
+#[test]
+fn output_files_are_kept_only_when_served_from_the_required_final_url() {
+    let home = tempfile::tempdir().unwrap();
+    let (origin, target) = start_servers();
+    let final_url = format!("http://localhost:{}/page", target.port());
+
+    for (required_final_url, output, exit_code, kept) in [
+        (origin.url("/start"), "text", 1, false),
+        (origin.url("/start"), "json", 1, false),
+        (final_url.clone(), "text", 0, true),
+        (final_url.clone(), "json", 0, true),
+    ] {
+        let output_path = home.path().join(format!("{}-{}", output, kept));
+        let result = run(
+            home.path(),
+            &[
+                "--url",
+                &origin.url("/start"),
+                "--output",
+                output,
+                "--require-final-url",
+                &required_final_url,
+                "--output-file",
+                output_path.to_str().unwrap(),
+            ],
+        );
+
+        assert_eq!(result.status.code(), Some(exit_code), "{:?}", result);
+        assert_eq!(output_path.exists(), kept, "{:?}", result);
+        if kept {
+            assert_eq!(std::fs::read(&output_path).unwrap(), b"page");
+        }
+    }
+}
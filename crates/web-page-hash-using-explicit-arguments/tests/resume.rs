@@ -0,0 +1,120 @@
+mod common;
+
+use common::{Request, TestServer, respond, run};
+use sha3::{Digest, Sha3_256};
+use std::io::Write;
+
+/// The length of the body, which is checkpointed every MiB
+const BODY_LENGTH: usize = 3 * 1024 * 1024;
+
+/// The number of body octets sent before the connection is dropped
+const INTERRUPTED_LENGTH: usize = 2 * 1024 * 1024 + 1000;
+
+fn body() -> Vec<u8> {
+    (0..BODY_LENGTH).map(|index| (index % 251) as u8).collect()
+}
+
+/// Returns the start of the `Range` header value of a request for the rest
+/// of a body (e.g., `bytes=1000-`)
+fn range_start(request: &Request) -> Option<usize> {
+    request
+        .header("Range")?
+        .strip_prefix("bytes=")?
+        .strip_suffix('-')?
+        .parse()
+        .ok()
+}
+
+/// Starts a server dropping the connection in the middle of the body, and
+/// answering `Range` requests with the rest of the body
+fn start_server() -> TestServer {
+    TestServer::start(|request, stream| {
+        let body = body();
+
+        match range_start(request) {
+            Some(start) if request.header("If-Range") == Some("\"v1\"") => {
+                let content_range = format!("bytes {}-{}/{}", start, BODY_LENGTH - 1, BODY_LENGTH);
+
+                respond(
+                    stream,
+                    "206 Partial Content",
+                    &[("ETag", "\"v1\""), ("Content-Range", &content_range)],
+                    &body[start..],
+                )
+            }
+            _ => {
+                let head = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nETag: \"v1\"\r\n\r\n",
+                    BODY_LENGTH
+                );
+                let _ = stream.write_all(head.as_bytes());
+                let _ = stream.write_all(&body[..INTERRUPTED_LENGTH]);
+            }
+        }
+    })
+}
+
+#[test]
+fn interrupted_downloads_resume_from_their_checkpoint() {
+    let home = tempfile::tempdir().unwrap();
+    let server = start_server();
+    let output_path = home.path().join("body");
+    let checkpoint_path = home.path().join("body.checkpoint");
+    let digest = hex::encode(Sha3_256::digest(body()));
+    let args = [
+        "--url",
+        &server.url("/"),
+        "--output-file",
+        output_path.to_str().unwrap(),
+        "--expect",
+        &digest,
+    ];
+
+    let output = run(home.path(), &args);
+    assert_eq!(output.status.code(), Some(2), "{:?}", output);
+    assert!(checkpoint_path.exists());
+    assert!(!output_path.exists());
+
+    let output = run(home.path(), &args);
+    assert!(output.status.success(), "{:?}", output);
+    assert_eq!(std::fs::read(&output_path).unwrap(), body());
+    assert!(!checkpoint_path.exists());
+    let requests = server.requests();
+    assert_eq!(requests.len(), 2);
+    assert_eq!(requests[0].header("Range"), None);
+    // Checkpoints are saved after the chunk reaching each MiB:
+    let resumed_start = range_start(&requests[1]).unwrap();
+    assert!((1024 * 1024..=INTERRUPTED_LENGTH).contains(&resumed_start));
+}
+
+#[test]
+fn resumed_responses_are_checked_against_the_complete_body_size() {
+    let home = tempfile::tempdir().unwrap();
+    let server = start_server();
+    let output_path = home.path().join("body");
+    let max_body_size = (BODY_LENGTH - 1).to_string();
+    let args = [
+        "--url",
+        &server.url("/"),
+        "--output-file",
+        output_path.to_str().unwrap(),
+    ];
+
+    let output = run(home.path(), &args);
+    assert_eq!(output.status.code(), Some(2), "{:?}", output);
+
+    let output = run(
+        home.path(),
+        &[&args[..], &["--max-body-size", &max_body_size]].concat(),
+    );
+    assert_eq!(output.status.code(), Some(2), "{:?}", output);
+    assert!(
+        String::from_utf8_lossy(&output.stderr).contains(&format!(
+            "the response Content-Length of {} bytes is larger than the maximum body size of {} bytes",
+            BODY_LENGTH, max_body_size
+        )),
+        "{:?}",
+        output
+    );
+    assert_eq!(server.requests().len(), 2);
+}
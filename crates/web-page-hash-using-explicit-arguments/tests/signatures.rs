@@ -0,0 +1,417 @@
+mod common;
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use common::{TestServer, respond, run};
+use ed25519_dalek::SigningKey;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use web_page_hash_using_explicit_arguments::digest_signer_service::{
+    DigestStatement, Ed25519DigestSigner, Ed25519DigestVerifier, MinisignDigestSigner,
+    MinisignDigestVerifier, SignDigestStatement, SignatureError, VerifyDigestSignature,
+};
+use web_page_hash_using_explicit_arguments::message_digest_service::MessageDigestAlgorithm;
+
+/// The key ID of the minisign keys of the tests
+const KEY_ID: [u8; 8] = *b"\x1f\xe8\xb4\x42\x18\x0f\x62\xe7";
+
+fn fixture(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures/signatures")
+        .join(name)
+}
+
+fn statement() -> DigestStatement {
+    DigestStatement::new(
+        "https://example.com/".to_string(),
+        MessageDigestAlgorithm::Sha3_256,
+        &[0xa7, 0x0f, 0x01],
+        SystemTime::UNIX_EPOCH + Duration::from_secs(1_735_689_600),
+    )
+}
+
+fn signing_key() -> SigningKey {
+    SigningKey::from_bytes(&[7; 32])
+}
+
+/// Writes a minisign key pair as written by `minisign -G -W`, and returns
+/// the paths of the secret, and the public key files
+///
+/// A decoded secret key has 158 octets: the `Ed` signature algorithm, no
+/// KDF, the `B2` checksum algorithm, the unused KDF salt, and limits, the key
+/// ID, the Ed25519 key pair, and the checksum, which isn't checked.
+fn write_minisign_keys(directory: &Path, signing_key: &SigningKey) -> (PathBuf, PathBuf) {
+    let secret_key = [
+        &b"Ed"[..],
+        &[0, 0],
+        b"B2",
+        &[0; 32 + 8 + 8],
+        &KEY_ID,
+        &signing_key.to_keypair_bytes(),
+        &[0; 32],
+    ]
+    .concat();
+    assert_eq!(secret_key.len(), 158);
+    let public_key = [&b"Ed"[..], &KEY_ID, signing_key.verifying_key().as_bytes()].concat();
+    let secret_key_path = directory.join("minisign.key");
+    let public_key_path = directory.join("minisign.pub");
+    std::fs::write(
+        &secret_key_path,
+        format!(
+            "untrusted comment: minisign encrypted secret key\n{}\n",
+            STANDARD.encode(secret_key)
+        ),
+    )
+    .unwrap();
+    std::fs::write(
+        &public_key_path,
+        format!(
+            "untrusted comment: minisign public key E7620F1842B4E81F\n{}\n",
+            STANDARD.encode(public_key)
+        ),
+    )
+    .unwrap();
+
+    (secret_key_path, public_key_path)
+}
+
+/// Returns a minisign signer, and verifier of a key pair of key files
+fn minisign_keys(signing_key: &SigningKey) -> (MinisignDigestSigner, MinisignDigestVerifier) {
+    let directory = tempfile::tempdir().unwrap();
+    let (secret_key_path, public_key_path) = write_minisign_keys(directory.path(), signing_key);
+
+    (
+        MinisignDigestSigner::from_key_file(&secret_key_path).unwrap(),
+        MinisignDigestVerifier::from_key_file(&public_key_path).unwrap(),
+    )
+}
+
+/// Replaces the `line_index`th line of a minisign signature
+fn replace_line(signature: &str, line_index: usize, line: &str) -> String {
+    let mut lines = signature.lines().collect::<Vec<_>>();
+    lines[line_index] = line;
+
+    lines.join("\n") + "\n"
+}
+
+#[test]
+fn ed25519_signatures_round_trip() {
+    let signer = Ed25519DigestSigner::from_pem_file(&fixture("ed25519.pem")).unwrap();
+    let verifier = Ed25519DigestVerifier::from_pem_file(&fixture("ed25519.pub.pem")).unwrap();
+
+    let signature = signer.sign_digest_statement(&statement()).unwrap();
+
+    assert_eq!(
+        verifier.verify_digest_signature(&signature).unwrap(),
+        statement()
+    );
+}
+
+#[test]
+fn ed25519_signatures_of_openssl_are_verified() {
+    // Signed with `openssl pkeyutl -sign -rawin` over the canonical statement:
+    let signature = std::fs::read_to_string(fixture("ed25519.signature.json")).unwrap();
+    let verifier = Ed25519DigestVerifier::from_pem_file(&fixture("ed25519.pub.pem")).unwrap();
+
+    let statement = verifier.verify_digest_signature(&signature).unwrap();
+
+    assert_eq!(statement.url, "https://example.com/");
+    assert_eq!(statement.timestamp, "2025-01-01T00:00:00Z");
+}
+
+#[test]
+fn ed25519_signatures_of_tampered_statements_are_bad() {
+    let signature = std::fs::read_to_string(fixture("ed25519.signature.json")).unwrap();
+    let verifier = Ed25519DigestVerifier::from_pem_file(&fixture("ed25519.pub.pem")).unwrap();
+
+    let tampered_signature = signature.replace("a7ffc6f8", "a7ffc6f9");
+    assert!(matches!(
+        verifier.verify_digest_signature(&tampered_signature),
+        Err(SignatureError::BadSignature)
+    ));
+
+    let other_verifier = Ed25519DigestVerifier::new(signing_key().verifying_key());
+    assert!(matches!(
+        other_verifier.verify_digest_signature(&signature),
+        Err(SignatureError::BadSignature)
+    ));
+}
+
+#[test]
+fn malformed_ed25519_signatures_are_invalid() {
+    let signature = std::fs::read_to_string(fixture("ed25519.signature.json")).unwrap();
+    let verifier = Ed25519DigestVerifier::from_pem_file(&fixture("ed25519.pub.pem")).unwrap();
+
+    for malformed_signature in [
+        signature.replace("8pn3", "8p!3"),
+        signature.replace("AQ==", "AQ"),
+        signature.replace("\"url\"", "\"uri\""),
+        "{}".to_string(),
+    ] {
+        assert!(
+            matches!(
+                verifier.verify_digest_signature(&malformed_signature),
+                Err(SignatureError::InvalidSignature(_))
+            ),
+            "{malformed_signature}"
+        );
+    }
+}
+
+#[test]
+fn ed25519_keys_of_the_other_kind_are_invalid() {
+    assert!(matches!(
+        Ed25519DigestSigner::from_pem_file(&fixture("ed25519.pub.pem")),
+        Err(SignatureError::InvalidKey(..))
+    ));
+    assert!(matches!(
+        Ed25519DigestVerifier::from_pem_file(&fixture("ed25519.pem")),
+        Err(SignatureError::InvalidKey(..))
+    ));
+    assert!(matches!(
+        Ed25519DigestVerifier::from_pem_file(&fixture("missing.pem")),
+        Err(SignatureError::ReadKey(..))
+    ));
+}
+
+#[test]
+fn minisign_signatures_round_trip() {
+    let (signer, verifier) = minisign_keys(&signing_key());
+
+    let signature = signer.sign_digest_statement(&statement()).unwrap();
+
+    assert_eq!(
+        verifier.verify_digest_signature(&signature).unwrap(),
+        statement()
+    );
+    let lines = signature.lines().collect::<Vec<_>>();
+    assert_eq!(lines.len(), 4);
+    // The legacy `Ed` algorithm, and the key ID start the signature:
+    assert!(lines[1].starts_with("RWQf6LRCGA9i5"), "{}", lines[1]);
+    assert_eq!(
+        lines[2],
+        format!(
+            "trusted comment: {}",
+            String::from_utf8(statement().canonical_bytes()).unwrap()
+        )
+    );
+}
+
+#[test]
+fn minisign_signatures_are_verified_by_another_implementation() {
+    let directory = tempfile::tempdir().unwrap();
+    let (secret_key_path, public_key_path) = write_minisign_keys(directory.path(), &signing_key());
+    let signer = MinisignDigestSigner::from_key_file(&secret_key_path).unwrap();
+
+    let signature = signer.sign_digest_statement(&statement()).unwrap();
+
+    let public_key =
+        minisign_verify::PublicKey::decode(&std::fs::read_to_string(public_key_path).unwrap())
+            .unwrap();
+    let signature = minisign_verify::Signature::decode(&signature).unwrap();
+    // Signatures of the legacy `Ed` algorithm are only verified when allowed:
+    public_key
+        .verify(&statement().canonical_bytes(), &signature, true)
+        .unwrap();
+    assert!(
+        public_key
+            .verify(&statement().canonical_bytes(), &signature, false)
+            .is_err()
+    );
+}
+
+#[test]
+fn minisign_signatures_with_tampered_trusted_comments_are_bad() {
+    let (signer, verifier) = minisign_keys(&signing_key());
+    let signature = signer.sign_digest_statement(&statement()).unwrap();
+    let trusted_comment = signature.lines().nth(2).unwrap();
+
+    // The statement, and the signature of the message are the same, but the
+    // global signature covers the trusted comment as it is:
+    let tampered_signature = replace_line(
+        &signature,
+        2,
+        &trusted_comment.replacen("{\"url\"", "{ \"url\"", 1),
+    );
+    assert!(matches!(
+        verifier.verify_digest_signature(&tampered_signature),
+        Err(SignatureError::BadSignature)
+    ));
+
+    let tampered_signature = replace_line(
+        &signature,
+        2,
+        &trusted_comment.replace("2025-01-01", "2025-01-02"),
+    );
+    assert!(matches!(
+        verifier.verify_digest_signature(&tampered_signature),
+        Err(SignatureError::BadSignature)
+    ));
+}
+
+#[test]
+fn minisign_signatures_with_tampered_digests_are_bad() {
+    let (signer, verifier) = minisign_keys(&signing_key());
+    let signature = signer.sign_digest_statement(&statement()).unwrap();
+
+    let tampered_signature = signature.replace("\"digest\":\"a70f01\"", "\"digest\":\"a70f02\"");
+    assert_ne!(tampered_signature, signature);
+    assert!(matches!(
+        verifier.verify_digest_signature(&tampered_signature),
+        Err(SignatureError::BadSignature)
+    ));
+}
+
+#[test]
+fn minisign_signatures_of_other_keys_mismatch() {
+    let (signer, _) = minisign_keys(&signing_key());
+    let signature = signer.sign_digest_statement(&statement()).unwrap();
+
+    let other_key_id_verifier =
+        MinisignDigestVerifier::new(*b"otherkey", signing_key().verifying_key());
+    assert!(matches!(
+        other_key_id_verifier.verify_digest_signature(&signature),
+        Err(SignatureError::KeyMismatch)
+    ));
+
+    // The key ID is the same, but the key isn't:
+    let (_, other_key_verifier) = minisign_keys(&SigningKey::from_bytes(&[8; 32]));
+    assert!(matches!(
+        other_key_verifier.verify_digest_signature(&signature),
+        Err(SignatureError::BadSignature)
+    ));
+}
+
+#[test]
+fn malformed_minisign_signatures_are_invalid() {
+    let (signer, verifier) = minisign_keys(&signing_key());
+    let signature = signer.sign_digest_statement(&statement()).unwrap();
+    let signature_line = signature.lines().nth(1).unwrap();
+
+    for malformed_signature in [
+        replace_line(&signature, 1, &signature_line.replacen('R', "!", 1)),
+        replace_line(&signature, 1, &signature_line[..signature_line.len() - 4]),
+        replace_line(&signature, 3, "not base64"),
+        replace_line(&signature, 0, "comment"),
+        replace_line(&signature, 2, "comment"),
+        signature.lines().take(3).collect::<Vec<_>>().join("\n"),
+    ] {
+        assert!(
+            matches!(
+                verifier.verify_digest_signature(&malformed_signature),
+                Err(SignatureError::InvalidSignature(_))
+            ),
+            "{malformed_signature}"
+        );
+    }
+}
+
+#[test]
+fn unusable_minisign_secret_keys_are_rejected() {
+    let directory = tempfile::tempdir().unwrap();
+    let (secret_key_path, public_key_path) = write_minisign_keys(directory.path(), &signing_key());
+    let secret_key = STANDARD
+        .decode(
+            std::fs::read_to_string(&secret_key_path)
+                .unwrap()
+                .lines()
+                .nth(1)
+                .unwrap(),
+        )
+        .unwrap();
+    let write_secret_key = |secret_key: &[u8]| {
+        std::fs::write(&secret_key_path, STANDARD.encode(secret_key)).unwrap();
+    };
+
+    // The `Sc` KDF encrypts the key:
+    let mut encrypted_secret_key = secret_key.clone();
+    encrypted_secret_key[2..4].copy_from_slice(b"Sc");
+    write_secret_key(&encrypted_secret_key);
+    assert!(matches!(
+        MinisignDigestSigner::from_key_file(&secret_key_path),
+        Err(SignatureError::EncryptedKey(_))
+    ));
+
+    // The public half of the key pair is another key's:
+    let mut mismatched_secret_key = secret_key.clone();
+    mismatched_secret_key[94..126]
+        .copy_from_slice(SigningKey::from_bytes(&[8; 32]).verifying_key().as_bytes());
+    write_secret_key(&mismatched_secret_key);
+    assert!(matches!(
+        MinisignDigestSigner::from_key_file(&secret_key_path),
+        Err(SignatureError::InvalidKey(..))
+    ));
+
+    assert!(matches!(
+        MinisignDigestSigner::from_key_file(&public_key_path),
+        Err(SignatureError::InvalidKey(..))
+    ));
+    assert!(matches!(
+        MinisignDigestVerifier::from_key_file(&secret_key_path),
+        Err(SignatureError::InvalidKey(..))
+    ));
+}
+
+#[test]
+fn signed_digests_are_verified_against_the_web_page() {
+    let home = tempfile::tempdir().unwrap();
+    let (secret_key_path, public_key_path) = write_minisign_keys(home.path(), &signing_key());
+    let server = TestServer::start(|request, stream| {
+        let body: &[u8] = match request.path.as_str() {
+            "/changed" => b"changed",
+            _ => b"page",
+        };
+
+        respond(stream, "200 OK", &[], body)
+    });
+    let signature_path = home.path().join("page.minisig");
+
+    for (secret_key_path, public_key_path) in [
+        (fixture("ed25519.pem"), fixture("ed25519.pub.pem")),
+        (secret_key_path, public_key_path),
+    ] {
+        let output = run(
+            home.path(),
+            &[
+                "--url",
+                &server.url("/page"),
+                "--sign-key",
+                secret_key_path.to_str().unwrap(),
+                "--signature",
+                signature_path.to_str().unwrap(),
+            ],
+        );
+        assert!(output.status.success(), "{:?}", output);
+
+        let verify = |signature_path: &Path| {
+            run(
+                home.path(),
+                &[
+                    "--verify-signature",
+                    signature_path.to_str().unwrap(),
+                    "--public-key",
+                    public_key_path.to_str().unwrap(),
+                ],
+            )
+        };
+        let output = verify(&signature_path);
+        assert!(output.status.success(), "{:?}", output);
+        assert!(
+            String::from_utf8_lossy(&output.stdout).contains(": OK (SHA3-256, signed at "),
+            "{:?}",
+            output
+        );
+
+        // Signatures of tampered statements are invalid:
+        let tampered_signature_path = home.path().join("tampered.minisig");
+        std::fs::write(
+            &tampered_signature_path,
+            std::fs::read_to_string(&signature_path)
+                .unwrap()
+                .replace("/page", "/changed"),
+        )
+        .unwrap();
+        let output = verify(&tampered_signature_path);
+        assert_eq!(output.status.code(), Some(2), "{:?}", output);
+    }
+}
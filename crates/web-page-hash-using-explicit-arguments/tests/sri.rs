@@ -0,0 +1,193 @@
+mod common;
+
+use common::{TestServer, command, respond};
+use sha2::{Digest, Sha256, Sha384};
+use web_page_hash_using_explicit_arguments::digest_encoding::to_sri;
+use web_page_hash_using_explicit_arguments::message_digest_service::MessageDigestAlgorithm;
+use web_page_hash_using_explicit_arguments::subresource_integrity::{
+    HtmlSubresources, SubresourceKind, integrity_matches,
+};
+
+const PAGE: &str = include_str!("fixtures/sri/page.html");
+const REWRITTEN_PAGE: &str = include_str!("fixtures/sri/page.rewritten.html");
+
+/// Starts a server on `localhost` serving a script, like a CDN, and a
+/// server on `127.0.0.1` serving a Web page loading a script of its own, and
+/// the script of the first server
+fn start_servers() -> (TestServer, TestServer) {
+    let cdn = TestServer::start(|_, stream| respond(stream, "200 OK", &[], b"cdn();"));
+    let page = format!(
+        "<script src=\"/app.js\"></script>\
+         <script src=\"http://localhost:{}/lib.js\"></script>",
+        cdn.port()
+    );
+    let origin = TestServer::start(move |request, stream| match request.path.as_str() {
+        "/app.js" => respond(stream, "200 OK", &[], b"app();"),
+        _ => respond(
+            stream,
+            "200 OK",
+            &[("Content-Type", "text/html")],
+            page.as_bytes(),
+        ),
+    });
+
+    (origin, cdn)
+}
+
+#[test]
+fn only_subresources_of_the_origin_of_the_web_page_are_requested_with_credentials() {
+    let home = tempfile::tempdir().unwrap();
+    let (origin, cdn) = start_servers();
+
+    let output = command(home.path())
+        .args(["sri", &origin.url("/"), "--header", "X-Api-Key: secret"])
+        .env("WEB_PAGE_HASH_BEARER_TOKEN", "token")
+        .output()
+        .unwrap();
+
+    assert!(output.status.success(), "{:?}", output);
+    let origin_requests = origin.requests();
+    assert_eq!(origin_requests.len(), 2);
+    for request in &origin_requests {
+        assert_eq!(request.header("X-Api-Key"), Some("secret"));
+        assert_eq!(request.header("Authorization"), Some("Bearer token"));
+    }
+    let cdn_requests = cdn.requests();
+    assert_eq!(cdn_requests.len(), 1);
+    assert_eq!(cdn_requests[0].header("X-Api-Key"), None);
+    assert_eq!(cdn_requests[0].header("Authorization"), None);
+}
+
+#[test]
+fn parse_finds_scripts_and_stylesheets_outside_comments_and_raw_text() {
+    let html_subresources = HtmlSubresources::parse(PAGE.to_string());
+
+    let subresources = html_subresources
+        .subresources()
+        .iter()
+        .map(|subresource| {
+            (
+                subresource.kind,
+                subresource.url.as_str(),
+                subresource.integrity.as_deref(),
+                subresource.crossorigin.as_deref(),
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        subresources,
+        [
+            (
+                SubresourceKind::Script,
+                "app.js?v=1&lang=en",
+                Some("sha384-outdated"),
+                None
+            ),
+            (SubresourceKind::Stylesheet, "theme.css", None, None),
+            (
+                SubresourceKind::Script,
+                "https://cdn.example/lib.js",
+                None,
+                Some("use-credentials")
+            ),
+            (SubresourceKind::Script, "/footer.js", None, None),
+        ]
+    );
+}
+
+#[test]
+fn the_first_base_href_is_resolved_against_the_document_url() {
+    let html_subresources = HtmlSubresources::parse(PAGE.to_string());
+    let document_url = url::Url::parse("https://example.com/docs/page.html").unwrap();
+
+    assert_eq!(html_subresources.base_href(), Some("/static/"));
+    let base_url = html_subresources.base_url(&document_url);
+    let urls = html_subresources
+        .subresources()
+        .iter()
+        .map(|subresource| base_url.join(&subresource.url).unwrap().to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        urls,
+        [
+            "https://example.com/static/app.js?v=1&lang=en",
+            "https://example.com/static/theme.css",
+            "https://cdn.example/lib.js",
+            "https://example.com/footer.js",
+        ]
+    );
+}
+
+#[test]
+fn documents_without_base_href_are_their_own_base_url() {
+    let html_subresources = HtmlSubresources::parse("<script src=\"app.js\"></script>".to_string());
+    let document_url = url::Url::parse("https://example.com/docs/page.html").unwrap();
+
+    assert_eq!(html_subresources.base_href(), None);
+    assert_eq!(html_subresources.base_url(&document_url), document_url);
+}
+
+#[test]
+fn rewrite_only_changes_integrity_and_crossorigin_attributes() {
+    let html_subresources = HtmlSubresources::parse(PAGE.to_string());
+    let integrities = [
+        Some("sha384-app".to_string()),
+        Some("sha384-theme".to_string()),
+        None,
+        Some("sha384-footer".to_string()),
+    ];
+
+    assert_eq!(
+        html_subresources.rewrite(&integrities, "anonymous"),
+        REWRITTEN_PAGE
+    );
+    assert_eq!(
+        html_subresources.rewrite(&[None, None, None, None], "anonymous"),
+        PAGE
+    );
+}
+
+#[test]
+fn integrity_matches_compares_the_strongest_algorithm_only() {
+    let body = b"app();";
+    let sha256 = to_sri(MessageDigestAlgorithm::Sha256, &Sha256::digest(body)).unwrap();
+    let sha384 = to_sri(MessageDigestAlgorithm::Sha384, &Sha384::digest(body)).unwrap();
+    let digests = [
+        (
+            MessageDigestAlgorithm::Sha256,
+            Sha256::digest(body).to_vec(),
+        ),
+        (
+            MessageDigestAlgorithm::Sha384,
+            Sha384::digest(body).to_vec(),
+        ),
+    ];
+
+    assert_eq!(integrity_matches(&sha384, &digests), Some(true));
+    assert_eq!(integrity_matches(&sha256, &digests), Some(true));
+    // A matching weaker hash doesn't make up for a mismatched stronger one:
+    assert_eq!(
+        integrity_matches(&format!("{} sha384-wrong", sha256), &digests),
+        Some(false)
+    );
+    assert_eq!(
+        integrity_matches(&format!("sha256-wrong {}", sha384), &digests),
+        Some(true)
+    );
+    // Any hash of the strongest algorithm may match:
+    assert_eq!(
+        integrity_matches(&format!("sha384-wrong {}", sha384), &digests),
+        Some(true)
+    );
+    // Algorithm names ignore case, and options are ignored:
+    assert_eq!(
+        integrity_matches(
+            &format!("SHA384{}?ct=text/javascript", &sha384[6..]),
+            &digests
+        ),
+        Some(true)
+    );
+    assert_eq!(integrity_matches("sha512-notcalculated", &digests), None);
+    assert_eq!(integrity_matches("md5-unknown", &digests), None);
+    assert_eq!(integrity_matches("", &digests), None);
+}
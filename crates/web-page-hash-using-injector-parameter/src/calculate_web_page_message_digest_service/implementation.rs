@@ -1,7 +1,9 @@
 use super::interface::CalculateWebPageMessageDigest;
-use crate::http_client_service::{GetUrl, IntoChunkStream};
+use crate::http_client_service::{GetUrl, IntoChunkStream, ResponseMetadata};
 use crate::injector::InjectRef;
 use crate::message_digest_service::{IntoDigestOctets, NewDigestCalculator};
+use crate::progress_reporter_service::{ReportBytesWritten, ReportFinished, ReportStarted};
+use crate::response_policy_service::{CheckBodySize, CheckResponseMetadata};
 use futures_util::StreamExt;
 use std::io::Write;
 use std::marker::PhantomData;
@@ -13,12 +15,16 @@ pub struct CalculateWebPageMessageDigestService<
     Error,
     HttpClientService: GetUrl<Url = Url>,
     MessageDigestService: NewDigestCalculator + Send,
+    ProgressReporter,
+    ResponsePolicy,
 > {
     _url_type_marker: PhantomData<Url>,
     _digest_type_marker: PhantomData<Digest>,
     _error_type_marker: PhantomData<Error>,
     _http_client_service_type_marker: PhantomData<HttpClientService>,
     _message_digest_service: PhantomData<MessageDigestService>,
+    _progress_reporter: PhantomData<ProgressReporter>,
+    _response_policy: PhantomData<ResponsePolicy>,
 }
 
 impl<
@@ -27,6 +33,8 @@ impl<
     Error,
     HttpClientService: GetUrl<Url = Url>,
     MessageDigestService: NewDigestCalculator + Send,
+    ProgressReporter,
+    ResponsePolicy,
 >
     CalculateWebPageMessageDigestService<
         Url,
@@ -34,6 +42,8 @@ impl<
         Error,
         HttpClientService,
         MessageDigestService,
+        ProgressReporter,
+        ResponsePolicy,
     >
 {
     pub fn new() -> Self {
@@ -43,32 +53,50 @@ impl<
             _error_type_marker: PhantomData::<Error>,
             _http_client_service_type_marker: PhantomData::<HttpClientService>,
             _message_digest_service: PhantomData::<MessageDigestService>,
+            _progress_reporter: PhantomData::<ProgressReporter>,
+            _response_policy: PhantomData::<ResponsePolicy>,
         }
     }
 }
 
 impl<
-    Url:Send+Sync,
+    'fn_call,
+    Injector: InjectRef<'fn_call, HttpClientService>
+        + InjectRef<'fn_call, MessageDigestService>
+        + InjectRef<'fn_call, ProgressReporter>
+        + InjectRef<'fn_call, ResponsePolicy>
+        + 'fn_call,
+    Url: AsRef<str> + Send + Sync,
     Digest: Send+Sync,
     Error: Sync,
     HttpClientService: GetUrl<Url = Url>+Sync,
     MessageDigestService: NewDigestCalculator + Send+Sync,
-> CalculateWebPageMessageDigest
+    ProgressReporter: ReportStarted + ReportBytesWritten + ReportFinished + Send + Sync,
+    ResponsePolicy: CheckResponseMetadata + CheckBodySize + Send + Sync,
+> CalculateWebPageMessageDigest<'fn_call, Injector>
     for CalculateWebPageMessageDigestService<
         Url,
         Digest,
         Error,
         HttpClientService,
         MessageDigestService,
+        ProgressReporter,
+        ResponsePolicy,
     >
 where
+    HttpClientService: 'fn_call,
+    MessageDigestService: 'fn_call,
+    ProgressReporter: 'fn_call,
+    ResponsePolicy: 'fn_call,
     Error: From<<HttpClientService as GetUrl>::Error>
         + From<<<HttpClientService as GetUrl>::HttpResponse as IntoChunkStream>::Error>
         + From<<MessageDigestService as NewDigestCalculator>::Error>
         + From<<<MessageDigestService as NewDigestCalculator>::DigestCalculator as IntoDigestOctets>::Error>
+        + From<<ResponsePolicy as CheckResponseMetadata>::Error>
+        + From<<ResponsePolicy as CheckBodySize>::Error>
         + From<std::io::Error>
         + Send,
-    HttpClientService::HttpResponse: IntoChunkStream,
+    HttpClientService::HttpResponse: IntoChunkStream + ResponseMetadata,
     <HttpClientService as GetUrl>::HttpResponse: Sync,
     <HttpClientService as GetUrl>::Error: Sync,
     MessageDigestService::DigestCalculator: IntoDigestOctets<DigestOctets = Digest> + Write + Send + Sync,
@@ -76,35 +104,49 @@ where
     type Url = Url;
     type Digest = Digest;
     type Error = Error;
-    type HttpClientService = HttpClientService;
-    type MessageDigestService = MessageDigestService;
 
-    async fn calculate_web_page_message_digest<
-        'fn_call,
-        Injector: InjectRef<'fn_call, HttpClientService> + InjectRef<'fn_call, MessageDigestService>,
-    >(
+    async fn calculate_web_page_message_digest(
         &self,
         injector: &'fn_call Injector,
         url: &Self::Url,
     ) -> Result<Self::Digest, Self::Error>
     where
-        HttpClientService: 'fn_call,
-        MessageDigestService: 'fn_call,
-        &'fn_call Injector: Send
+        &'fn_call Injector: Send,
     {
         let message_digest_service: &MessageDigestService = InjectRef::inject_ref(injector);
         let mut digest_calculator = message_digest_service.new_digest_calculator()?;
         let http_client_service: &HttpClientService = InjectRef::inject_ref(injector);
-        let mut chunk_stream = http_client_service
-            .get_url(url)
-            .await?
-            .into_chunk_stream();
+        let progress_reporter: &ProgressReporter = InjectRef::inject_ref(injector);
+        let response_policy: &ResponsePolicy = InjectRef::inject_ref(injector);
+        let response = http_client_service.get_url(url).await?;
+        response_policy.check_response_metadata(&response)?;
+        let content_length = response.content_length();
+        let mut chunk_stream = response.into_chunk_stream();
+        progress_reporter.report_started(url.as_ref(), content_length);
+        let finish_progress_on_drop = FinishProgressOnDrop(progress_reporter);
+
+        let mut byte_count = 0;
 
         while let Some(chunk_result) = chunk_stream.next().await {
             let chunk = chunk_result?;
+            byte_count += chunk.len() as u64;
+            response_policy.check_body_size(byte_count)?;
             digest_calculator.write_all(&chunk)?;
+            progress_reporter.report_bytes_written(chunk.len() as u64);
         }
 
+        drop(finish_progress_on_drop);
+
         Ok(digest_calculator.into_digest_octets()?)
     }
 }
+
+/// Reports that streaming finished when dropped, so that it's reported also
+/// when streaming fails
+struct FinishProgressOnDrop<'a, ProgressReporter: ReportFinished>(&'a ProgressReporter);
+
+impl<ProgressReporter: ReportFinished> Drop for FinishProgressOnDrop<'_, ProgressReporter> {
+    fn drop(&mut self) {
+        self.0.report_finished();
+    }
+}
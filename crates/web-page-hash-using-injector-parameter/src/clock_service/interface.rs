@@ -0,0 +1,26 @@
+use std::time::{Duration, SystemTime};
+
+/// A function trait returning the current wall-clock time
+pub trait Now {
+    fn now(&self) -> SystemTime;
+}
+
+/// A function trait waiting for a duration, e.g., before retrying a request
+#[trait_variant::make(Sleep: Send)]
+pub trait ThreadLocalSleep {
+    fn sleep(&self, duration: Duration) -> impl Future<Output = ()>;
+}
+
+// The full services are composed of their method, and function traits:
+/// An injectable service telling the time, e.g., for measuring elapsed time
+pub trait Clock: Now {}
+
+/// A [Clock] of any type, e.g., injected into a decorator, which doesn't
+/// name the types of the services it requires
+pub type DynClock = dyn Clock + Sync;
+
+/// An injectable service waiting, e.g., for timeouts
+///
+/// Time-dependent behavior runs on virtual time, when both the [Clock], and
+/// the sleeper follow it (see [ManualClock](super::ManualClock)).
+pub trait Sleeper: Sleep {}
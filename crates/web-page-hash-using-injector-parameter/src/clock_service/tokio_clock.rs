@@ -0,0 +1,46 @@
+use super::interface::{Clock, Now, Sleep, Sleeper};
+use std::time::{Duration, SystemTime};
+use tokio::time::Instant;
+
+/// A [Clock], and [Sleeper] backed by the Tokio timer
+///
+/// The current time advances with the Tokio clock from the wall-clock time
+/// when the clock was created.  Both waiting, and the current time follow
+/// virtual time, when the Tokio clock is paused (see `tokio::time::pause`),
+/// e.g., in tests.
+#[derive(Clone, Debug)]
+pub struct TokioClock {
+    created_at: SystemTime,
+    created_instant: Instant,
+}
+
+impl TokioClock {
+    pub fn new() -> Self {
+        TokioClock {
+            created_at: SystemTime::now(),
+            created_instant: Instant::now(),
+        }
+    }
+}
+
+impl Default for TokioClock {
+    fn default() -> Self {
+        TokioClock::new()
+    }
+}
+
+impl Now for TokioClock {
+    fn now(&self) -> SystemTime {
+        self.created_at + self.created_instant.elapsed()
+    }
+}
+
+impl Sleep for TokioClock {
+    async fn sleep(&self, duration: Duration) {
+        tokio::time::sleep(duration).await
+    }
+}
+
+impl Clock for TokioClock {}
+
+impl Sleeper for TokioClock {}
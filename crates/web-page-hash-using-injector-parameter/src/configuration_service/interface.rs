@@ -0,0 +1,41 @@
+use std::time::Duration;
+
+/// A function trait providing settings of HTTP client services
+pub trait HttpClientConfiguration {
+    /// The maximum time for establishing a connection, or [None] for no limit
+    fn connect_timeout(&self) -> Option<Duration>;
+
+    /// The maximum time for a request, including streaming the response
+    /// body, or [None] for no limit
+    fn timeout(&self) -> Option<Duration>;
+
+    /// The `User-Agent` header value sent with requests
+    fn user_agent(&self) -> &str;
+
+    /// The URL of a proxy for all requests, or [None] for the system default
+    fn proxy(&self) -> Option<&str>;
+}
+
+/// A function trait providing settings of response policy services
+pub trait ResponsePolicyConfiguration {
+    /// The maximum number of response body octets hashed, or [None] for no
+    /// limit
+    fn max_body_size(&self) -> Option<u64>;
+
+    /// Whether bodies of `4xx`, and `5xx` responses are hashed
+    fn allow_error_status(&self) -> bool;
+
+    /// Media types of the responses hashed (e.g., `text/html`, or `image/*`),
+    /// or an empty list for any media type
+    fn allowed_content_types(&self) -> &[String];
+
+    /// Media types of the responses that are never hashed
+    fn denied_content_types(&self) -> &[String];
+}
+
+// The full service is composed of its method, and function traits:
+/// An injectable service providing settings of other services
+///
+/// No message digest settings are provided, since only 256-bit SHA-3
+/// digests are calculated.
+pub trait ConfigurationService: HttpClientConfiguration + ResponsePolicyConfiguration {}
@@ -0,0 +1,295 @@
+use super::interface::{
+    ConfigurationService, HttpClientConfiguration, ResponsePolicyConfiguration,
+};
+use serde::Deserialize;
+use std::ffi::OsString;
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// The prefix of environment variables overriding configuration file settings
+pub const ENVIRONMENT_VARIABLE_PREFIX: &str = "WEB_PAGE_HASH_";
+
+/// The environment variable naming a configuration file
+pub const CONFIGURATION_FILE_ENVIRONMENT_VARIABLE: &str = "WEB_PAGE_HASH_CONFIG";
+
+/// The `User-Agent` header value sent, unless another one is configured
+pub const DEFAULT_USER_AGENT: &str = concat!("web-page-hash/", env!("CARGO_PKG_VERSION"));
+
+/// Settings from a single source, such as a TOML file, environment
+/// variables, or command line arguments
+///
+/// Unset settings are [None], so that they don't override settings from
+/// lower layers.  Timeouts are in seconds, and `0` means no limit.  Lists
+/// of media types in environment variables are comma-separated.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct ConfigurationLayer {
+    pub connect_timeout: Option<u64>,
+    pub timeout: Option<u64>,
+    pub user_agent: Option<String>,
+    pub proxy: Option<String>,
+    pub max_body_size: Option<u64>,
+    pub allow_error_status: Option<bool>,
+    pub allowed_content_types: Option<Vec<String>>,
+    pub denied_content_types: Option<Vec<String>>,
+}
+
+impl ConfigurationLayer {
+    /// Reads a layer from a TOML file with kebab-case keys (e.g.,
+    /// `connect-timeout = 10`)
+    pub fn from_toml_file(path: &Path) -> Result<Self, ConfigurationError> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|error| ConfigurationError::Read(path.to_path_buf(), error))?;
+
+        toml::from_str(&text).map_err(|error| ConfigurationError::Parse(path.to_path_buf(), error))
+    }
+
+    /// Reads a layer from `WEB_PAGE_HASH_*` variables (e.g.,
+    /// `WEB_PAGE_HASH_CONNECT_TIMEOUT`)
+    ///
+    /// Empty variables, and variables without the prefix are ignored.
+    pub fn from_environment(
+        variables: impl IntoIterator<Item = (OsString, OsString)>,
+    ) -> Result<Self, ConfigurationError> {
+        let mut layer = ConfigurationLayer::default();
+
+        for (name, value) in variables {
+            let (Some(name), Some(value)) = (name.to_str(), value.to_str()) else {
+                continue;
+            };
+            let Some(setting) = name.strip_prefix(ENVIRONMENT_VARIABLE_PREFIX) else {
+                continue;
+            };
+            if value.is_empty() {
+                continue;
+            }
+            let invalid = |message: String| ConfigurationError::InvalidEnvironmentVariable {
+                name: name.to_string(),
+                message,
+            };
+            let parse_u64 = || {
+                value
+                    .parse::<u64>()
+                    .map_err(|error| invalid(error.to_string()))
+            };
+            let parse_bool = || {
+                value
+                    .parse::<bool>()
+                    .map_err(|error| invalid(error.to_string()))
+            };
+            let parse_list = || {
+                value
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|item| !item.is_empty())
+                    .map(str::to_string)
+                    .collect::<Vec<_>>()
+            };
+
+            match setting {
+                "CONNECT_TIMEOUT" => layer.connect_timeout = Some(parse_u64()?),
+                "TIMEOUT" => layer.timeout = Some(parse_u64()?),
+                "USER_AGENT" => layer.user_agent = Some(value.to_string()),
+                "PROXY" => layer.proxy = Some(value.to_string()),
+                "MAX_BODY_SIZE" => layer.max_body_size = Some(parse_u64()?),
+                "ALLOW_ERROR_STATUS" => layer.allow_error_status = Some(parse_bool()?),
+                "ALLOWED_CONTENT_TYPES" => layer.allowed_content_types = Some(parse_list()),
+                "DENIED_CONTENT_TYPES" => layer.denied_content_types = Some(parse_list()),
+                _ => {}
+            }
+        }
+
+        Ok(layer)
+    }
+
+    /// Reads a layer from the environment of this process
+    pub fn from_process_environment() -> Result<Self, ConfigurationError> {
+        ConfigurationLayer::from_environment(std::env::vars_os())
+    }
+
+    /// Returns this layer with its unset settings taken from a lower layer
+    pub fn over(self, lower: ConfigurationLayer) -> ConfigurationLayer {
+        ConfigurationLayer {
+            connect_timeout: self.connect_timeout.or(lower.connect_timeout),
+            timeout: self.timeout.or(lower.timeout),
+            user_agent: self.user_agent.or(lower.user_agent),
+            proxy: self.proxy.or(lower.proxy),
+            max_body_size: self.max_body_size.or(lower.max_body_size),
+            allow_error_status: self.allow_error_status.or(lower.allow_error_status),
+            allowed_content_types: self.allowed_content_types.or(lower.allowed_content_types),
+            denied_content_types: self.denied_content_types.or(lower.denied_content_types),
+        }
+    }
+}
+
+/// An injectable configuration service merging [ConfigurationLayer]s
+///
+/// Settings unset in all layers have defaults: no timeouts, no proxy other
+/// than the system's, no body size limit, [DEFAULT_USER_AGENT], and hashing
+/// responses of any media type, unless they have an error status.
+#[derive(Clone, Debug)]
+pub struct LayeredConfigurationService {
+    connect_timeout: Option<Duration>,
+    timeout: Option<Duration>,
+    user_agent: String,
+    proxy: Option<String>,
+    max_body_size: Option<u64>,
+    allow_error_status: bool,
+    allowed_content_types: Vec<String>,
+    denied_content_types: Vec<String>,
+}
+
+impl LayeredConfigurationService {
+    /// Merges layers, where each layer overrides the ones before it
+    pub fn new(layers: impl IntoIterator<Item = ConfigurationLayer>) -> Self {
+        let merged = layers
+            .into_iter()
+            .fold(ConfigurationLayer::default(), |lower, layer| {
+                layer.over(lower)
+            });
+        let seconds = |timeout: Option<u64>| {
+            timeout
+                .filter(|seconds| *seconds != 0)
+                .map(Duration::from_secs)
+        };
+
+        LayeredConfigurationService {
+            connect_timeout: seconds(merged.connect_timeout),
+            timeout: seconds(merged.timeout),
+            user_agent: merged
+                .user_agent
+                .unwrap_or_else(|| DEFAULT_USER_AGENT.to_string()),
+            proxy: merged.proxy,
+            max_body_size: merged.max_body_size,
+            allow_error_status: merged.allow_error_status.unwrap_or(false),
+            allowed_content_types: merged.allowed_content_types.unwrap_or_default(),
+            denied_content_types: merged.denied_content_types.unwrap_or_default(),
+        }
+    }
+
+    /// Returns `web-page-hash/config.toml` in `$XDG_CONFIG_HOME`, or in
+    /// `~/.config`
+    pub fn default_file_path() -> Option<PathBuf> {
+        let configuration_directory = std::env::var_os("XDG_CONFIG_HOME")
+            .filter(|directory| !directory.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+
+        Some(
+            configuration_directory
+                .join("web-page-hash")
+                .join("config.toml"),
+        )
+    }
+
+    /// Merges a TOML file, `WEB_PAGE_HASH_*` environment variables, and a
+    /// command line arguments layer, in order of increasing precedence
+    ///
+    /// The file is `file_path` if given, else the one named by
+    /// `WEB_PAGE_HASH_CONFIG`, which must exist.  Otherwise, it's
+    /// [LayeredConfigurationService::default_file_path], if it exists.
+    pub fn load(
+        file_path: Option<&Path>,
+        command_line_layer: ConfigurationLayer,
+    ) -> Result<Self, ConfigurationError> {
+        let file_path = match file_path {
+            Some(file_path) => Some(file_path.to_path_buf()),
+            None => std::env::var_os(CONFIGURATION_FILE_ENVIRONMENT_VARIABLE)
+                .filter(|file_path| !file_path.is_empty())
+                .map(PathBuf::from)
+                .or_else(|| {
+                    LayeredConfigurationService::default_file_path()
+                        .filter(|file_path| file_path.is_file())
+                }),
+        };
+        let file_layer = match file_path {
+            Some(file_path) => ConfigurationLayer::from_toml_file(&file_path)?,
+            None => ConfigurationLayer::default(),
+        };
+
+        Ok(LayeredConfigurationService::new([
+            file_layer,
+            ConfigurationLayer::from_process_environment()?,
+            command_line_layer,
+        ]))
+    }
+}
+
+impl Default for LayeredConfigurationService {
+    fn default() -> Self {
+        LayeredConfigurationService::new([])
+    }
+}
+
+impl HttpClientConfiguration for LayeredConfigurationService {
+    fn connect_timeout(&self) -> Option<Duration> {
+        self.connect_timeout
+    }
+
+    fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    fn user_agent(&self) -> &str {
+        &self.user_agent
+    }
+
+    fn proxy(&self) -> Option<&str> {
+        self.proxy.as_deref()
+    }
+}
+
+impl ResponsePolicyConfiguration for LayeredConfigurationService {
+    fn max_body_size(&self) -> Option<u64> {
+        self.max_body_size
+    }
+
+    fn allow_error_status(&self) -> bool {
+        self.allow_error_status
+    }
+
+    fn allowed_content_types(&self) -> &[String] {
+        &self.allowed_content_types
+    }
+
+    fn denied_content_types(&self) -> &[String] {
+        &self.denied_content_types
+    }
+}
+
+impl ConfigurationService for LayeredConfigurationService {}
+
+#[derive(Debug)]
+pub enum ConfigurationError {
+    Read(PathBuf, io::Error),
+    Parse(PathBuf, toml::de::Error),
+    InvalidEnvironmentVariable { name: String, message: String },
+}
+
+impl fmt::Display for ConfigurationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigurationError::Read(path, _) => {
+                write!(f, "could not read configuration file {}", path.display())
+            }
+            ConfigurationError::Parse(path, _) => {
+                write!(f, "invalid configuration file {}", path.display())
+            }
+            ConfigurationError::InvalidEnvironmentVariable { name, message } => {
+                write!(f, "invalid environment variable {}: {}", name, message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConfigurationError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ConfigurationError::Read(_, error) => Some(error),
+            ConfigurationError::Parse(_, error) => Some(error),
+            ConfigurationError::InvalidEnvironmentVariable { .. } => None,
+        }
+    }
+}
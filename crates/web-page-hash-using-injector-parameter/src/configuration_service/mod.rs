@@ -0,0 +1,5 @@
+pub mod interface;
+pub use interface::*;
+
+pub mod layered_configuration_service;
+pub use layered_configuration_service::*;
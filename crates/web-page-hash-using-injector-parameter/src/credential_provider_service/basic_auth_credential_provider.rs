@@ -0,0 +1,27 @@
+use super::interface::{Credential, CredentialProvider, LookUpCredential};
+use std::convert::Infallible;
+
+/// A [CredentialProvider] sending the same user name, and password with all
+/// requests using `Basic` authentication
+#[derive(Debug)]
+pub struct BasicAuthCredentialProvider {
+    credential: Credential,
+}
+
+impl BasicAuthCredentialProvider {
+    pub fn new(username: &str, password: Option<&str>) -> Self {
+        BasicAuthCredentialProvider {
+            credential: Credential::basic(username, password),
+        }
+    }
+}
+
+impl LookUpCredential for BasicAuthCredentialProvider {
+    type Error = Infallible;
+
+    fn look_up_credential(&self, _url: &str) -> Result<Option<Credential>, Self::Error> {
+        Ok(Some(self.credential.clone()))
+    }
+}
+
+impl CredentialProvider for BasicAuthCredentialProvider {}
@@ -0,0 +1,26 @@
+use super::interface::{Credential, CredentialError, CredentialProvider, LookUpCredential};
+use std::convert::Infallible;
+
+/// A [CredentialProvider] sending the same `Bearer` token with all requests
+#[derive(Debug)]
+pub struct BearerTokenCredentialProvider {
+    credential: Credential,
+}
+
+impl BearerTokenCredentialProvider {
+    pub fn new(token: &str) -> Result<Self, CredentialError> {
+        Ok(BearerTokenCredentialProvider {
+            credential: Credential::bearer(token)?,
+        })
+    }
+}
+
+impl LookUpCredential for BearerTokenCredentialProvider {
+    type Error = Infallible;
+
+    fn look_up_credential(&self, _url: &str) -> Result<Option<Credential>, Self::Error> {
+        Ok(Some(self.credential.clone()))
+    }
+}
+
+impl CredentialProvider for BearerTokenCredentialProvider {}
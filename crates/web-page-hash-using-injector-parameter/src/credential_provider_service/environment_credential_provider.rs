@@ -0,0 +1,45 @@
+use super::interface::{Credential, CredentialError, CredentialProvider, LookUpCredential};
+
+/// The environment variable holding a `Bearer` token
+pub const BEARER_TOKEN_ENVIRONMENT_VARIABLE: &str = "WEB_PAGE_HASH_BEARER_TOKEN";
+/// The environment variable holding a `Basic` authentication user name
+pub const USERNAME_ENVIRONMENT_VARIABLE: &str = "WEB_PAGE_HASH_USERNAME";
+/// The environment variable holding a `Basic` authentication password
+pub const PASSWORD_ENVIRONMENT_VARIABLE: &str = "WEB_PAGE_HASH_PASSWORD";
+
+/// A [CredentialProvider] reading credentials from environment variables
+/// for each request
+///
+/// A token in `WEB_PAGE_HASH_BEARER_TOKEN` takes precedence over a user
+/// name in `WEB_PAGE_HASH_USERNAME`, and a password in
+/// `WEB_PAGE_HASH_PASSWORD`.  Requests are sent without credentials when
+/// neither is set.
+#[derive(Debug, Default)]
+pub struct EnvironmentCredentialProvider {}
+
+impl EnvironmentCredentialProvider {
+    pub fn new() -> Self {
+        EnvironmentCredentialProvider {}
+    }
+}
+
+impl LookUpCredential for EnvironmentCredentialProvider {
+    type Error = CredentialError;
+
+    fn look_up_credential(&self, _url: &str) -> Result<Option<Credential>, Self::Error> {
+        let variable = |name| std::env::var(name).ok().filter(|value| !value.is_empty());
+
+        if let Some(token) = variable(BEARER_TOKEN_ENVIRONMENT_VARIABLE) {
+            return Credential::bearer(&token).map(Some);
+        }
+
+        Ok(variable(USERNAME_ENVIRONMENT_VARIABLE).map(|username| {
+            Credential::basic(
+                &username,
+                variable(PASSWORD_ENVIRONMENT_VARIABLE).as_deref(),
+            )
+        }))
+    }
+}
+
+impl CredentialProvider for EnvironmentCredentialProvider {}
@@ -0,0 +1,20 @@
+pub mod interface;
+pub use interface::*;
+
+pub mod basic_auth_credential_provider;
+pub use basic_auth_credential_provider::*;
+
+pub mod bearer_token_credential_provider;
+pub use bearer_token_credential_provider::*;
+
+pub mod environment_credential_provider;
+pub use environment_credential_provider::*;
+
+pub mod netrc_credential_provider;
+pub use netrc_credential_provider::*;
+
+pub mod no_credential_provider;
+pub use no_credential_provider::*;
+
+pub mod selectable_credential_provider;
+pub use selectable_credential_provider::*;
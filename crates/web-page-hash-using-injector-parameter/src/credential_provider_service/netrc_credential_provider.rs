@@ -0,0 +1,147 @@
+use super::interface::{Credential, CredentialError, CredentialProvider, LookUpCredential};
+use std::convert::Infallible;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// The environment variable naming a `.netrc` file
+pub const NETRC_ENVIRONMENT_VARIABLE: &str = "NETRC";
+
+/// A [CredentialProvider] looking up `Basic` authentication credentials by
+/// host name in a `.netrc` file
+///
+/// The `machine`, `default`, `login`, and `password` tokens are used.
+/// `account` tokens, and `macdef` macro definitions are skipped.
+pub struct NetrcCredentialProvider {
+    entries: Vec<NetrcEntry>,
+}
+
+/// A `machine`, or `default` entry of a `.netrc` file
+#[derive(Default)]
+struct NetrcEntry {
+    /// [None] for the `default` entry
+    machine: Option<String>,
+    login: Option<String>,
+    password: Option<String>,
+}
+
+impl NetrcCredentialProvider {
+    /// Parses a `.netrc` file
+    pub fn from_file(path: &Path) -> Result<Self, CredentialError> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|error| CredentialError::ReadNetrc(path.to_path_buf(), error))?;
+
+        NetrcCredentialProvider::parse(&text)
+            .map_err(|message| CredentialError::InvalidNetrc(path.to_path_buf(), message))
+    }
+
+    /// Returns `$NETRC`, or `~/.netrc`
+    pub fn default_file_path() -> Option<PathBuf> {
+        std::env::var_os(NETRC_ENVIRONMENT_VARIABLE)
+            .filter(|path| !path.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".netrc")))
+    }
+
+    /// Parses the [NetrcCredentialProvider::default_file_path] file, when
+    /// it exists, or else provides no credentials
+    pub fn from_default_file() -> Result<Self, CredentialError> {
+        match NetrcCredentialProvider::default_file_path() {
+            Some(path) if path.is_file() => NetrcCredentialProvider::from_file(&path),
+            _ => Ok(NetrcCredentialProvider {
+                entries: Vec::new(),
+            }),
+        }
+    }
+
+    fn parse(text: &str) -> Result<Self, String> {
+        let mut entries = Vec::new();
+        let mut in_macro_definition = false;
+        let mut tokens = Vec::new();
+
+        for line in text.lines() {
+            // A macro definition ends with an empty line:
+            if in_macro_definition {
+                in_macro_definition = !line.trim().is_empty();
+                continue;
+            }
+            for token in line.split_whitespace() {
+                if token == "macdef" {
+                    in_macro_definition = true;
+                    break;
+                }
+                tokens.push(token);
+            }
+        }
+
+        let mut tokens = tokens.into_iter();
+        while let Some(token) = tokens.next() {
+            let mut value = || {
+                tokens
+                    .next()
+                    .ok_or_else(|| format!("missing value after `{}`", token))
+            };
+
+            match token {
+                "machine" => entries.push(NetrcEntry {
+                    machine: Some(value()?.to_string()),
+                    ..NetrcEntry::default()
+                }),
+                "default" => entries.push(NetrcEntry::default()),
+                "login" | "password" | "account" => {
+                    let value = value()?.to_string();
+                    let entry = entries
+                        .last_mut()
+                        .ok_or_else(|| format!("`{}` before `machine`, or `default`", token))?;
+                    match token {
+                        "login" => entry.login = Some(value),
+                        "password" => entry.password = Some(value),
+                        _ => {}
+                    }
+                }
+                _ => return Err(format!("unknown token `{}`", token)),
+            }
+        }
+
+        Ok(NetrcCredentialProvider { entries })
+    }
+}
+
+impl fmt::Debug for NetrcCredentialProvider {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NetrcCredentialProvider")
+            .field("entry_count", &self.entries.len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl LookUpCredential for NetrcCredentialProvider {
+    type Error = Infallible;
+
+    fn look_up_credential(&self, url: &str) -> Result<Option<Credential>, Self::Error> {
+        let Some(host) = reqwest::Url::parse(url)
+            .ok()
+            .and_then(|url| url.host_str().map(str::to_string))
+        else {
+            return Ok(None);
+        };
+        let entry = self
+            .entries
+            .iter()
+            .find(|entry| {
+                entry
+                    .machine
+                    .as_deref()
+                    .is_some_and(|machine| machine.eq_ignore_ascii_case(&host))
+            })
+            .or_else(|| self.entries.iter().find(|entry| entry.machine.is_none()));
+
+        Ok(entry.and_then(|entry| {
+            entry
+                .login
+                .as_deref()
+                .map(|login| Credential::basic(login, entry.password.as_deref()))
+        }))
+    }
+}
+
+impl CredentialProvider for NetrcCredentialProvider {}
@@ -0,0 +1,22 @@
+use super::interface::{Credential, CredentialProvider, LookUpCredential};
+use std::convert::Infallible;
+
+/// A [CredentialProvider] that sends requests without credentials
+#[derive(Debug, Default)]
+pub struct NoCredentialProvider {}
+
+impl NoCredentialProvider {
+    pub fn new() -> Self {
+        NoCredentialProvider {}
+    }
+}
+
+impl LookUpCredential for NoCredentialProvider {
+    type Error = Infallible;
+
+    fn look_up_credential(&self, _url: &str) -> Result<Option<Credential>, Self::Error> {
+        Ok(None)
+    }
+}
+
+impl CredentialProvider for NoCredentialProvider {}
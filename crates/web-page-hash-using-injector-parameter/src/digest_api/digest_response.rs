@@ -0,0 +1,176 @@
+use crate::response_policy_service::ResponsePolicyViolation;
+use axum::Json;
+use axum::extract::rejection::{JsonRejection, QueryRejection};
+use axum::response::{IntoResponse, Response};
+use http::StatusCode;
+use serde::{Deserialize, Serialize};
+
+/// A request for the message digest of a Web page, e.g., the query of
+/// `GET /digest?url=...&alg=...`, or an item of a `POST /digest` batch
+#[derive(Clone, Debug, Deserialize)]
+pub struct DigestQuery {
+    pub url: String,
+    /// The message digest algorithm, or the default algorithm, when absent
+    pub alg: Option<String>,
+}
+
+/// The message digest of a Web page, or the error calculating it
+#[derive(Clone, Debug, Serialize)]
+pub struct DigestResponse {
+    pub url: String,
+    pub algorithm: String,
+    /// The lowercase hexadecimal message digest
+    pub digest: Option<String>,
+    /// The number of hashed response body octets
+    pub size: Option<u64>,
+    pub error: Option<DigestError>,
+}
+
+impl DigestResponse {
+    pub fn calculated(url: String, algorithm: String, digest: &[u8], size: u64) -> Self {
+        DigestResponse {
+            url,
+            algorithm,
+            digest: Some(hex::encode(digest)),
+            size: Some(size),
+            error: None,
+        }
+    }
+
+    pub fn failed(url: String, algorithm: String, error: DigestError) -> Self {
+        DigestResponse {
+            url,
+            algorithm,
+            digest: None,
+            size: None,
+            error: Some(error),
+        }
+    }
+
+    /// Returns the HTTP status of the response to a single digest request
+    pub fn status(&self) -> StatusCode {
+        self.error
+            .as_ref()
+            .map_or(StatusCode::OK, |error| error.kind.status())
+    }
+}
+
+impl IntoResponse for DigestResponse {
+    fn into_response(self) -> Response {
+        (self.status(), Json(self)).into_response()
+    }
+}
+
+/// The structured error of a failed [DigestResponse]
+#[derive(Clone, Debug, Serialize)]
+pub struct DigestError {
+    pub kind: DigestErrorKind,
+    /// A description of the error
+    pub message: String,
+    /// Descriptions of the errors that caused it, outermost first
+    pub causes: Vec<String>,
+}
+
+impl DigestError {
+    pub fn new(kind: DigestErrorKind, message: impl Into<String>) -> Self {
+        DigestError {
+            kind,
+            message: message.into(),
+            causes: Vec::new(),
+        }
+    }
+
+    /// Describes an error of calculating a message digest, classified by
+    /// the errors in its chain of causes
+    pub fn from_anyhow(error: &anyhow::Error) -> Self {
+        DigestError {
+            kind: DigestErrorKind::classify(error),
+            message: error.to_string(),
+            causes: error.chain().skip(1).map(ToString::to_string).collect(),
+        }
+    }
+}
+
+impl From<QueryRejection> for DigestError {
+    fn from(rejection: QueryRejection) -> Self {
+        DigestError::new(DigestErrorKind::InvalidRequest, rejection.body_text())
+    }
+}
+
+impl From<JsonRejection> for DigestError {
+    fn from(rejection: JsonRejection) -> Self {
+        let kind = match rejection {
+            JsonRejection::MissingJsonContentType(_) => DigestErrorKind::UnsupportedMediaType,
+            _ => DigestErrorKind::InvalidRequest,
+        };
+
+        DigestError::new(kind, rejection.body_text())
+    }
+}
+
+impl IntoResponse for DigestError {
+    fn into_response(self) -> Response {
+        (self.kind.status(), Json(self)).into_response()
+    }
+}
+
+/// Kinds of [DigestError]s, each mapped to an HTTP status
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DigestErrorKind {
+    /// The query, or the body of the request couldn't be parsed
+    InvalidRequest,
+    /// A `POST /digest` body isn't declared as JSON
+    UnsupportedMediaType,
+    /// The URL isn't a valid absolute URL
+    InvalidUrl,
+    UnsupportedAlgorithm,
+    /// A `POST /digest` batch has more Web pages than allowed
+    BatchTooLarge,
+    /// The Web page was served with a `4xx`, or `5xx` status
+    UpstreamStatus,
+    /// The Web page was rejected by the response policy, e.g., because of
+    /// its media type, or size
+    ResponseRejected,
+    /// Fetching the Web page timed out
+    UpstreamTimeout,
+    /// Fetching the Web page failed, e.g., because the connection failed
+    Upstream,
+    Internal,
+}
+
+impl DigestErrorKind {
+    fn classify(error: &anyhow::Error) -> Self {
+        for cause in error.chain() {
+            if let Some(violation) = cause.downcast_ref::<ResponsePolicyViolation>() {
+                return match violation {
+                    ResponsePolicyViolation::ErrorStatus(_) => DigestErrorKind::UpstreamStatus,
+                    _ => DigestErrorKind::ResponseRejected,
+                };
+            }
+            if let Some(reqwest_error) = cause.downcast_ref::<reqwest::Error>() {
+                return if reqwest_error.is_timeout() {
+                    DigestErrorKind::UpstreamTimeout
+                } else {
+                    DigestErrorKind::Upstream
+                };
+            }
+        }
+
+        DigestErrorKind::Internal
+    }
+
+    pub fn status(self) -> StatusCode {
+        match self {
+            DigestErrorKind::InvalidRequest
+            | DigestErrorKind::InvalidUrl
+            | DigestErrorKind::UnsupportedAlgorithm => StatusCode::BAD_REQUEST,
+            DigestErrorKind::UnsupportedMediaType => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            DigestErrorKind::BatchTooLarge => StatusCode::PAYLOAD_TOO_LARGE,
+            DigestErrorKind::UpstreamStatus | DigestErrorKind::Upstream => StatusCode::BAD_GATEWAY,
+            DigestErrorKind::ResponseRejected => StatusCode::UNPROCESSABLE_ENTITY,
+            DigestErrorKind::UpstreamTimeout => StatusCode::GATEWAY_TIMEOUT,
+            DigestErrorKind::Internal => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
@@ -0,0 +1,188 @@
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use bytes::Bytes;
+use http::header::{HeaderName, HeaderValue};
+use http::{HeaderMap, StatusCode};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// A file of recorded HTTP responses, which can be replayed instead of
+/// sending requests
+///
+/// A cassette is a pretty-printed JSON file, so that it can be reviewed, and
+/// committed alongside tests:
+///
+/// ```json
+/// {
+///   "interactions": [
+///     {
+///       "url": "https://example.com/",
+///       "status": 200,
+///       "headers": [["content-type", "text/html"]],
+///       "chunks": ["<standard, padded base64>"]
+///     }
+///   ]
+/// }
+/// ```
+///
+/// Each response body is stored as the chunks it was received in.  Request
+/// headers aren't recorded, since they may contain credentials, and neither
+/// are response headers setting cookies.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct Cassette {
+    interactions: Vec<CassetteInteraction>,
+}
+
+impl Cassette {
+    pub fn load(path: &Path) -> io::Result<Self> {
+        Ok(serde_json::from_slice(&fs::read(path)?)?)
+    }
+
+    /// Loads a cassette, or returns an empty one, when the file doesn't
+    /// exist
+    pub fn load_or_default(path: &Path) -> io::Result<Self> {
+        match Cassette::load(path) {
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(Cassette::default()),
+            result => result,
+        }
+    }
+
+    /// Writes the cassette, replacing the file only once it's fully written
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let mut partial_path = path.as_os_str().to_owned();
+        partial_path.push(".partial");
+
+        fs::write(&partial_path, serde_json::to_vec_pretty(self)?)?;
+        fs::rename(partial_path, path)
+    }
+
+    /// Returns the recorded response for `url`, if there's one
+    pub fn interaction(&self, url: &str) -> Option<&CassetteInteraction> {
+        self.interactions
+            .iter()
+            .find(|interaction| interaction.url == url)
+    }
+
+    /// Adds an interaction, replacing any recorded for the same URL
+    pub fn record(&mut self, interaction: CassetteInteraction) {
+        match self
+            .interactions
+            .iter_mut()
+            .find(|recorded_interaction| recorded_interaction.url == interaction.url)
+        {
+            Some(recorded_interaction) => *recorded_interaction = interaction,
+            None => self.interactions.push(interaction),
+        }
+    }
+}
+
+/// A recorded response to a GET request
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(
+    try_from = "StoredCassetteInteraction",
+    into = "StoredCassetteInteraction"
+)]
+pub struct CassetteInteraction {
+    url: String,
+    status: StatusCode,
+    headers: HeaderMap,
+    chunks: Vec<Bytes>,
+}
+
+impl CassetteInteraction {
+    pub fn new(url: String, status: StatusCode, headers: HeaderMap, chunks: Vec<Bytes>) -> Self {
+        CassetteInteraction {
+            url,
+            status,
+            headers,
+            chunks,
+        }
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    pub fn status(&self) -> StatusCode {
+        self.status
+    }
+
+    pub fn headers(&self) -> &HeaderMap {
+        &self.headers
+    }
+
+    /// Returns the response body in the chunks it was received in
+    pub fn chunks(&self) -> &[Bytes] {
+        &self.chunks
+    }
+}
+
+/// The JSON representation of a [CassetteInteraction]
+///
+/// Header values that aren't text are dropped when recording.  Fields this
+/// client doesn't record, such as the `final_url` of a response after
+/// redirects, are ignored.
+#[derive(Deserialize, Serialize)]
+struct StoredCassetteInteraction {
+    url: String,
+    status: u16,
+    headers: Vec<(String, String)>,
+    /// Base64-encoded body chunks
+    chunks: Vec<String>,
+}
+
+impl From<CassetteInteraction> for StoredCassetteInteraction {
+    fn from(interaction: CassetteInteraction) -> Self {
+        StoredCassetteInteraction {
+            url: interaction.url,
+            status: interaction.status.as_u16(),
+            headers: interaction
+                .headers
+                .iter()
+                .filter_map(|(name, value)| {
+                    value
+                        .to_str()
+                        .ok()
+                        .map(|value| (name.to_string(), value.to_string()))
+                })
+                .collect(),
+            chunks: interaction
+                .chunks
+                .iter()
+                .map(|chunk| STANDARD.encode(chunk))
+                .collect(),
+        }
+    }
+}
+
+impl TryFrom<StoredCassetteInteraction> for CassetteInteraction {
+    type Error = String;
+
+    fn try_from(stored_interaction: StoredCassetteInteraction) -> Result<Self, Self::Error> {
+        let status =
+            StatusCode::from_u16(stored_interaction.status).map_err(|error| error.to_string())?;
+        let mut headers = HeaderMap::new();
+        for (name, value) in stored_interaction.headers {
+            headers.append(
+                HeaderName::try_from(name).map_err(|error| error.to_string())?,
+                HeaderValue::try_from(value).map_err(|error| error.to_string())?,
+            );
+        }
+        let chunks = stored_interaction
+            .chunks
+            .iter()
+            .map(|chunk| STANDARD.decode(chunk).map(Bytes::from))
+            .collect::<Result<_, _>>()
+            .map_err(|error| error.to_string())?;
+
+        Ok(CassetteInteraction {
+            url: stored_interaction.url,
+            status,
+            headers,
+            chunks,
+        })
+    }
+}
@@ -0,0 +1,2 @@
+pub mod cassette;
+pub use cassette::*;
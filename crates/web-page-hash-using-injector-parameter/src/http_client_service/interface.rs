@@ -1,5 +1,6 @@
 use bytes::Bytes;
 use futures_core::stream::Stream;
+use http::{HeaderMap, StatusCode};
 
 #[trait_variant::make(IntoChunkStream: Send)]
 pub trait ThreadLocalIntoChunkStream {
@@ -8,6 +9,28 @@ pub trait ThreadLocalIntoChunkStream {
     fn into_chunk_stream(self) -> impl Stream<Item = Result<Bytes, Self::Error>> + Unpin;
 }
 
+/// Allows inspecting an HTTP response before its body is streamed
+pub trait ResponseMetadata {
+    fn status(&self) -> StatusCode;
+
+    fn headers(&self) -> &HeaderMap;
+
+    /// Returns the `Content-Type` header value
+    fn content_type(&self) -> Option<&str> {
+        self.headers()
+            .get(http::header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+    }
+
+    /// Returns the `Content-Length` header value
+    fn content_length(&self) -> Option<u64> {
+        self.headers()
+            .get(http::header::CONTENT_LENGTH)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.parse().ok())
+    }
+}
+
 /// A function trait providing a `get_url` function for [HttpClientService]
 ///
 /// See <https://blog.rust-lang.org/2023/12/21/async-fn-rpit-in-traits/>, and
@@ -25,6 +48,17 @@ pub trait ThreadLocalGetUrl {
     ) -> impl Future<Output = Result<Self::HttpResponse, Self::Error>>;
 }
 
+/// A function trait providing a `get_url_with_headers` function, which
+/// sends additional request headers
+#[trait_variant::make(GetUrlWithHeaders: Send)]
+pub trait ThreadLocalGetUrlWithHeaders: GetUrl {
+    fn get_url_with_headers(
+        &self,
+        url: &Self::Url,
+        headers: &HeaderMap,
+    ) -> impl Future<Output = Result<Self::HttpResponse, Self::Error>>;
+}
+
 // The full service is composed of its method, and function traits:
 /// An injectable service that can fetch data with HTTP GET requests
 pub trait HttpClientService: GetUrl
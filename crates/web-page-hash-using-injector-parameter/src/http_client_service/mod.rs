@@ -1,5 +1,17 @@
 pub mod interface;
 pub use interface::*;
 
+pub mod authenticating_http_client_service;
+pub use authenticating_http_client_service::*;
+
+pub mod recording_http_client_service;
+pub use recording_http_client_service::*;
+
+pub mod replay_http_client_service;
+pub use replay_http_client_service::*;
+
 pub mod reqwest_http_client_service;
 pub use reqwest_http_client_service::*;
+
+pub mod selectable_http_client_service;
+pub use selectable_http_client_service::*;
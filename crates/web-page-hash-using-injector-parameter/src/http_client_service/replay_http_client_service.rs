@@ -0,0 +1,99 @@
+use super::interface::{GetUrl, GetUrlWithHeaders, IntoChunkStream, ResponseMetadata};
+use crate::credential_provider_service::redact_url_credentials;
+use crate::http_cassette::{Cassette, CassetteInteraction};
+use bytes::Bytes;
+use futures_core::stream::Stream;
+use http::{HeaderMap, StatusCode};
+use std::convert::Infallible;
+use std::fmt;
+use std::io;
+use std::path::Path;
+
+/// An injectable HTTP client service serving responses recorded in a
+/// [Cassette] by [super::RecordingGetUrl], without sending requests
+///
+/// Response bodies are streamed in the chunks they were received in.
+/// Requesting a URL that wasn't recorded fails.
+#[derive(Debug)]
+pub struct ReplayGetUrl {
+    cassette: Cassette,
+}
+
+impl ReplayGetUrl {
+    pub fn new(cassette: Cassette) -> Self {
+        ReplayGetUrl { cassette }
+    }
+
+    pub fn from_file(cassette_path: &Path) -> io::Result<Self> {
+        Ok(ReplayGetUrl::new(Cassette::load(cassette_path)?))
+    }
+}
+
+impl GetUrl for ReplayGetUrl {
+    type Url = reqwest::Url;
+    type HttpResponse = ReplayHttpResponse;
+    type Error = ReplayGetUrlError;
+
+    async fn get_url(&self, url: &Self::Url) -> Result<Self::HttpResponse, Self::Error> {
+        self.get_url_with_headers(url, &HeaderMap::new()).await
+    }
+}
+
+impl GetUrlWithHeaders for ReplayGetUrl {
+    /// Request headers are ignored, since they aren't recorded
+    async fn get_url_with_headers(
+        &self,
+        url: &Self::Url,
+        _headers: &HeaderMap,
+    ) -> Result<Self::HttpResponse, Self::Error> {
+        let url = redact_url_credentials(url.as_str());
+
+        self.cassette
+            .interaction(&url)
+            .cloned()
+            .map(|interaction| ReplayHttpResponse { interaction })
+            .ok_or_else(|| ReplayGetUrlError::NotRecorded(url.into_owned()))
+    }
+}
+
+/// A response returned by [ReplayGetUrl]
+#[derive(Debug)]
+pub struct ReplayHttpResponse {
+    interaction: CassetteInteraction,
+}
+
+impl ResponseMetadata for ReplayHttpResponse {
+    fn status(&self) -> StatusCode {
+        self.interaction.status()
+    }
+
+    fn headers(&self) -> &HeaderMap {
+        self.interaction.headers()
+    }
+}
+
+impl IntoChunkStream for ReplayHttpResponse {
+    type Error = Infallible;
+
+    fn into_chunk_stream(self) -> impl Stream<Item = Result<Bytes, Self::Error>> + Unpin {
+        futures_util::stream::iter(self.interaction.chunks().to_vec().into_iter().map(Ok))
+    }
+}
+
+#[derive(Debug)]
+pub enum ReplayGetUrlError {
+    /// The cassette has no response for the URL
+    NotRecorded(String),
+}
+
+impl fmt::Display for ReplayGetUrlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReplayGetUrlError::NotRecorded(url) => {
+                write!(f, "no response is recorded for {}", url)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ReplayGetUrlError {}
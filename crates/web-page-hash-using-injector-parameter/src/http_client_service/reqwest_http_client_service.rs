@@ -1,15 +1,42 @@
-use super::interface::{GetUrl, IntoChunkStream};
+use super::interface::{GetUrl, GetUrlWithHeaders, IntoChunkStream, ResponseMetadata};
+use crate::configuration_service::HttpClientConfiguration;
 use bytes::Bytes;
 use futures_core::stream::Stream;
+use http::{HeaderMap, StatusCode};
 use reqwest;
 
 /// An injectable HTTP client service implemented using the [reqwest] crate
 #[derive(Debug, Default)]
-pub struct ReqwestHttpClientService {}
+pub struct ReqwestHttpClientService {
+    client: reqwest::Client,
+}
 
 impl ReqwestHttpClientService {
     pub fn new() -> Self {
-        ReqwestHttpClientService {}
+        ReqwestHttpClientService {
+            client: reqwest::Client::new(),
+        }
+    }
+
+    /// Creates a service with timeouts, a user agent, and a proxy read from
+    /// a configuration service
+    pub fn from_configuration(
+        configuration: &impl HttpClientConfiguration,
+    ) -> Result<Self, reqwest::Error> {
+        let mut client_builder = reqwest::Client::builder().user_agent(configuration.user_agent());
+        if let Some(connect_timeout) = configuration.connect_timeout() {
+            client_builder = client_builder.connect_timeout(connect_timeout);
+        }
+        if let Some(timeout) = configuration.timeout() {
+            client_builder = client_builder.timeout(timeout);
+        }
+        if let Some(proxy) = configuration.proxy() {
+            client_builder = client_builder.proxy(reqwest::Proxy::all(proxy)?);
+        }
+
+        Ok(ReqwestHttpClientService {
+            client: client_builder.build()?,
+        })
     }
 }
 
@@ -19,7 +46,21 @@ impl GetUrl for ReqwestHttpClientService {
     type Error = reqwest::Error;
 
     async fn get_url(&self, url: &Self::Url) -> Result<Self::HttpResponse, Self::Error> {
-        reqwest::get(url.clone()).await
+        self.client.get(url.clone()).send().await
+    }
+}
+
+impl GetUrlWithHeaders for ReqwestHttpClientService {
+    async fn get_url_with_headers(
+        &self,
+        url: &Self::Url,
+        headers: &HeaderMap,
+    ) -> Result<Self::HttpResponse, Self::Error> {
+        self.client
+            .get(url.clone())
+            .headers(headers.clone())
+            .send()
+            .await
     }
 }
 
@@ -30,3 +71,13 @@ impl IntoChunkStream for reqwest::Response {
         reqwest::Response::bytes_stream(self)
     }
 }
+
+impl ResponseMetadata for reqwest::Response {
+    fn status(&self) -> StatusCode {
+        reqwest::Response::status(self)
+    }
+
+    fn headers(&self) -> &HeaderMap {
+        reqwest::Response::headers(self)
+    }
+}
@@ -0,0 +1,134 @@
+use super::interface::{GetUrl, GetUrlWithHeaders, IntoChunkStream, ResponseMetadata};
+use super::recording_http_client_service::{
+    RecordingGetUrl, RecordingGetUrlError, RecordingHttpResponse,
+};
+use super::replay_http_client_service::{ReplayGetUrl, ReplayGetUrlError, ReplayHttpResponse};
+use super::reqwest_http_client_service::ReqwestHttpClientService;
+use bytes::Bytes;
+use futures_core::stream::Stream;
+use futures_util::StreamExt;
+use futures_util::future::Either;
+use http::{HeaderMap, StatusCode};
+use std::fmt;
+
+/// An HTTP client service selected at run time: [ReqwestHttpClientService],
+/// optionally recording responses, or a [ReplayGetUrl] replaying them
+#[derive(Debug)]
+pub enum SelectableGetUrl {
+    Reqwest(ReqwestHttpClientService),
+    Recording(RecordingGetUrl<ReqwestHttpClientService>),
+    Replay(ReplayGetUrl),
+}
+
+impl GetUrl for SelectableGetUrl {
+    type Url = reqwest::Url;
+    type HttpResponse = SelectableHttpResponse;
+    type Error = SelectableGetUrlError;
+
+    async fn get_url(&self, url: &Self::Url) -> Result<Self::HttpResponse, Self::Error> {
+        self.get_url_with_headers(url, &HeaderMap::new()).await
+    }
+}
+
+impl GetUrlWithHeaders for SelectableGetUrl {
+    async fn get_url_with_headers(
+        &self,
+        url: &Self::Url,
+        headers: &HeaderMap,
+    ) -> Result<Self::HttpResponse, Self::Error> {
+        match self {
+            SelectableGetUrl::Reqwest(service) => service
+                .get_url_with_headers(url, headers)
+                .await
+                .map(SelectableHttpResponse::Reqwest)
+                .map_err(SelectableGetUrlError::Reqwest),
+            SelectableGetUrl::Recording(service) => service
+                .get_url_with_headers(url, headers)
+                .await
+                .map(SelectableHttpResponse::Recording)
+                .map_err(SelectableGetUrlError::Reqwest),
+            SelectableGetUrl::Replay(service) => service
+                .get_url_with_headers(url, headers)
+                .await
+                .map(SelectableHttpResponse::Replay)
+                .map_err(SelectableGetUrlError::Replay),
+        }
+    }
+}
+
+/// A response returned by [SelectableGetUrl]
+#[derive(Debug)]
+pub enum SelectableHttpResponse {
+    Reqwest(reqwest::Response),
+    Recording(RecordingHttpResponse<reqwest::Response>),
+    Replay(ReplayHttpResponse),
+}
+
+impl ResponseMetadata for SelectableHttpResponse {
+    fn status(&self) -> StatusCode {
+        match self {
+            SelectableHttpResponse::Reqwest(response) => response.status(),
+            SelectableHttpResponse::Recording(response) => response.status(),
+            SelectableHttpResponse::Replay(response) => response.status(),
+        }
+    }
+
+    fn headers(&self) -> &HeaderMap {
+        match self {
+            SelectableHttpResponse::Reqwest(response) => ResponseMetadata::headers(response),
+            SelectableHttpResponse::Recording(response) => response.headers(),
+            SelectableHttpResponse::Replay(response) => response.headers(),
+        }
+    }
+}
+
+impl IntoChunkStream for SelectableHttpResponse {
+    type Error = SelectableGetUrlError;
+
+    fn into_chunk_stream(self) -> impl Stream<Item = Result<Bytes, Self::Error>> + Unpin {
+        match self {
+            SelectableHttpResponse::Reqwest(response) => Either::Left(
+                response
+                    .into_chunk_stream()
+                    .map(|chunk_result| chunk_result.map_err(SelectableGetUrlError::Reqwest)),
+            ),
+            SelectableHttpResponse::Recording(response) => {
+                Either::Right(Either::Left(response.into_chunk_stream().map(
+                    |chunk_result| chunk_result.map_err(SelectableGetUrlError::Recording),
+                )))
+            }
+            SelectableHttpResponse::Replay(response) => Either::Right(Either::Right(
+                response
+                    .into_chunk_stream()
+                    .map(|chunk_result| chunk_result.map_err(|error| match error {})),
+            )),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum SelectableGetUrlError {
+    Reqwest(reqwest::Error),
+    Recording(RecordingGetUrlError<reqwest::Error>),
+    Replay(ReplayGetUrlError),
+}
+
+impl fmt::Display for SelectableGetUrlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SelectableGetUrlError::Reqwest(error) => error.fmt(f),
+            SelectableGetUrlError::Recording(error) => error.fmt(f),
+            SelectableGetUrlError::Replay(error) => error.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for SelectableGetUrlError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SelectableGetUrlError::Reqwest(error) => Some(error),
+            SelectableGetUrlError::Recording(error) => Some(error),
+            SelectableGetUrlError::Replay(error) => Some(error),
+        }
+    }
+}
@@ -1,3 +1,5 @@
-pub trait InjectRef<'self_lifetime, T> {
+/// `T` may be unsized, e.g., a trait object of a service, so that services
+/// can require it without naming its type.
+pub trait InjectRef<'self_lifetime, T: ?Sized> {
     fn inject_ref(&'self_lifetime self) -> &'self_lifetime T;
 }
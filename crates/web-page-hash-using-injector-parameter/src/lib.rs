@@ -1,4 +1,12 @@
 pub mod calculate_web_page_message_digest_service;
+pub mod clock_service;
+pub mod configuration_service;
+pub mod credential_provider_service;
+pub mod digest_api;
+pub mod http_cassette;
 pub mod http_client_service;
 pub mod injector;
 pub mod message_digest_service;
+pub mod progress_reporter_service;
+pub mod response_policy_service;
+pub mod tracing_decorator;
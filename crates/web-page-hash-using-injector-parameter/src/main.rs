@@ -1,66 +1,160 @@
-use clap::Parser;
+use anyhow::Context;
+use axum::extract::rejection::{JsonRejection, QueryRejection};
+use axum::extract::{Json, Query, State};
+use axum::routing::get;
+use clap::{Args as ClapArgs, Parser, Subcommand, ValueEnum};
+use futures_util::StreamExt;
+use futures_util::future::BoxFuture;
+use http::{HeaderMap, HeaderName, HeaderValue};
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::sync::Semaphore;
+use tracing_subscriber::EnvFilter;
 use web_page_hash_using_injector_parameter::{
     calculate_web_page_message_digest_service::{self, CalculateWebPageMessageDigest},
-    http_client_service::{GetUrl, ReqwestHttpClientService},
+    clock_service::{DynClock, TokioClock},
+    configuration_service::{ConfigurationLayer, LayeredConfigurationService},
+    credential_provider_service::{
+        BasicAuthCredentialProvider, BearerTokenCredentialProvider, CredentialError,
+        EnvironmentCredentialProvider, NetrcCredentialProvider, SelectableCredentialProvider,
+    },
+    digest_api::{DigestError, DigestErrorKind, DigestQuery, DigestResponse},
+    http_client_service::{
+        AuthenticatingGetUrl, GetUrl, RecordingGetUrl, ReplayGetUrl, ReqwestHttpClientService,
+        SelectableGetUrl,
+    },
     injector::InjectRef,
     message_digest_service::{
         IntoDigestOctets, NewDigestCalculator, Sha3_256BitMessageDigestService,
     },
+    progress_reporter_service::{
+        ByteCountingProgressReporter, IndicatifProgressReporter, NoProgressReporter,
+        ReportBytesWritten, ReportFinished, ReportStarted,
+    },
+    response_policy_service::ConfiguredResponsePolicy,
+    tracing_decorator::Traced,
 };
 
-type HttpClientService = ReqwestHttpClientService;
-type MessageDigestService = Sha3_256BitMessageDigestService;
+/// Traces emitted with `--log-format`, unless `RUST_LOG` is set
+const DEFAULT_LOG_FILTER: &str = "web_page_hash_using_injector_parameter=info";
+
+/// The name of the only message digest algorithm served by `serve`
+const ALGORITHM: &str = "sha3-256";
+
+type Clock = TokioClock;
+type ResponsePolicy = ConfiguredResponsePolicy;
+type HttpClientService =
+    Traced<AuthenticatingGetUrl<SelectableGetUrl, SelectableCredentialProvider>>;
+type MessageDigestService = Traced<Sha3_256BitMessageDigestService>;
 type Url = <HttpClientService as GetUrl>::Url;
 type Digest = <<MessageDigestService as NewDigestCalculator>::DigestCalculator as IntoDigestOctets>::DigestOctets;
-type CalculateWebPageMessageDigestService =
+type CalculateWebPageMessageDigestService<ProgressReporter> = Traced<
     calculate_web_page_message_digest_service::CalculateWebPageMessageDigestService<
         Url,
         Digest,
         anyhow::Error,
         HttpClientService,
         MessageDigestService,
-    >;
+        ProgressReporter,
+        ResponsePolicy,
+    >,
+>;
 
 /// Contains services that don't depend on other services
-struct Stage1Injector {
+///
+/// The progress reporter is a type parameter, so that it can be selected at
+/// run time.  The HTTP client service, and the response policy are created
+/// from the configuration service, and their settings are fixed at creation.
+struct Stage1Injector<ProgressReporter> {
+    clock: Clock,
+    response_policy: ResponsePolicy,
     http_client_service: HttpClientService,
     message_digest_service: MessageDigestService,
+    progress_reporter: ProgressReporter,
 }
 
-impl Stage1Injector {
+impl<ProgressReporter> Stage1Injector<ProgressReporter> {
     fn from_services(
+        clock: Clock,
+        response_policy: ResponsePolicy,
         http_client_service: HttpClientService,
         message_digest_service: MessageDigestService,
+        progress_reporter: ProgressReporter,
     ) -> Self {
         Stage1Injector {
+            clock,
+            response_policy,
             http_client_service,
             message_digest_service,
+            progress_reporter,
         }
     }
 }
 
-impl<'self_lifetime> InjectRef<'self_lifetime, HttpClientService> for Stage1Injector {
+impl<'self_lifetime, ProgressReporter> InjectRef<'self_lifetime, DynClock>
+    for Stage1Injector<ProgressReporter>
+{
+    fn inject_ref(&'self_lifetime self) -> &'self_lifetime DynClock {
+        &self.clock
+    }
+}
+
+impl<'self_lifetime, ProgressReporter> InjectRef<'self_lifetime, ResponsePolicy>
+    for Stage1Injector<ProgressReporter>
+{
+    fn inject_ref(&'self_lifetime self) -> &'self_lifetime ResponsePolicy {
+        &self.response_policy
+    }
+}
+
+impl<'self_lifetime, ProgressReporter> InjectRef<'self_lifetime, HttpClientService>
+    for Stage1Injector<ProgressReporter>
+{
     fn inject_ref(&'self_lifetime self) -> &'self_lifetime HttpClientService {
         &self.http_client_service
     }
 }
 
-impl<'self_lifetime> InjectRef<'self_lifetime, MessageDigestService> for Stage1Injector {
+impl<'self_lifetime, ProgressReporter> InjectRef<'self_lifetime, MessageDigestService>
+    for Stage1Injector<ProgressReporter>
+{
     fn inject_ref(&'self_lifetime self) -> &'self_lifetime MessageDigestService {
         &self.message_digest_service
     }
 }
 
+// A blanket implementation for any progress reporter would overlap with the
+// other implementations, so it's implemented for each selectable one:
+impl<'self_lifetime> InjectRef<'self_lifetime, IndicatifProgressReporter>
+    for Stage1Injector<IndicatifProgressReporter>
+{
+    fn inject_ref(&'self_lifetime self) -> &'self_lifetime IndicatifProgressReporter {
+        &self.progress_reporter
+    }
+}
+
+impl<'self_lifetime> InjectRef<'self_lifetime, NoProgressReporter>
+    for Stage1Injector<NoProgressReporter>
+{
+    fn inject_ref(&'self_lifetime self) -> &'self_lifetime NoProgressReporter {
+        &self.progress_reporter
+    }
+}
+
 /// Contains a [Stage1Injector], and services that depend on [Stage1Injector]
-struct Stage2Injector {
-    dependency_injector: Stage1Injector,
-    calculate_web_page_message_digest_service: CalculateWebPageMessageDigestService,
+struct Stage2Injector<ProgressReporter> {
+    dependency_injector: Stage1Injector<ProgressReporter>,
+    calculate_web_page_message_digest_service:
+        CalculateWebPageMessageDigestService<ProgressReporter>,
 }
 
-impl Stage2Injector {
+impl<ProgressReporter> Stage2Injector<ProgressReporter> {
     fn from_dependency_injector_and_services(
-        dependency_injector: Stage1Injector,
-        calculate_web_page_message_digest_service: CalculateWebPageMessageDigestService,
+        dependency_injector: Stage1Injector<ProgressReporter>,
+        calculate_web_page_message_digest_service: CalculateWebPageMessageDigestService<
+            ProgressReporter,
+        >,
     ) -> Self {
         Stage2Injector {
             dependency_injector,
@@ -69,35 +163,375 @@ impl Stage2Injector {
     }
 }
 
-impl<'self_lifetime> InjectRef<'self_lifetime, HttpClientService> for Stage2Injector {
+impl<'self_lifetime, ProgressReporter> InjectRef<'self_lifetime, DynClock>
+    for Stage2Injector<ProgressReporter>
+{
+    fn inject_ref(&'self_lifetime self) -> &'self_lifetime DynClock {
+        InjectRef::<DynClock>::inject_ref(&self.dependency_injector)
+    }
+}
+
+impl<'self_lifetime, ProgressReporter> InjectRef<'self_lifetime, ResponsePolicy>
+    for Stage2Injector<ProgressReporter>
+{
+    fn inject_ref(&'self_lifetime self) -> &'self_lifetime ResponsePolicy {
+        InjectRef::<ResponsePolicy>::inject_ref(&self.dependency_injector)
+    }
+}
+
+impl<'self_lifetime, ProgressReporter> InjectRef<'self_lifetime, HttpClientService>
+    for Stage2Injector<ProgressReporter>
+{
     fn inject_ref(&'self_lifetime self) -> &'self_lifetime HttpClientService {
-        InjectRef::inject_ref(&self.dependency_injector)
+        InjectRef::<HttpClientService>::inject_ref(&self.dependency_injector)
     }
 }
 
-impl<'self_lifetime> InjectRef<'self_lifetime, MessageDigestService> for Stage2Injector {
+impl<'self_lifetime, ProgressReporter> InjectRef<'self_lifetime, MessageDigestService>
+    for Stage2Injector<ProgressReporter>
+{
     fn inject_ref(&'self_lifetime self) -> &'self_lifetime MessageDigestService {
-        InjectRef::inject_ref(&self.dependency_injector)
+        InjectRef::<MessageDigestService>::inject_ref(&self.dependency_injector)
     }
 }
 
-impl<'self_lifetime> InjectRef<'self_lifetime, CalculateWebPageMessageDigestService>
-    for Stage2Injector
+impl<'self_lifetime> InjectRef<'self_lifetime, IndicatifProgressReporter>
+    for Stage2Injector<IndicatifProgressReporter>
 {
-    fn inject_ref(&'self_lifetime self) -> &'self_lifetime CalculateWebPageMessageDigestService {
+    fn inject_ref(&'self_lifetime self) -> &'self_lifetime IndicatifProgressReporter {
+        InjectRef::<IndicatifProgressReporter>::inject_ref(&self.dependency_injector)
+    }
+}
+
+impl<'self_lifetime> InjectRef<'self_lifetime, NoProgressReporter>
+    for Stage2Injector<NoProgressReporter>
+{
+    fn inject_ref(&'self_lifetime self) -> &'self_lifetime NoProgressReporter {
+        InjectRef::<NoProgressReporter>::inject_ref(&self.dependency_injector)
+    }
+}
+
+impl<'self_lifetime, ProgressReporter>
+    InjectRef<'self_lifetime, CalculateWebPageMessageDigestService<ProgressReporter>>
+    for Stage2Injector<ProgressReporter>
+{
+    fn inject_ref(
+        &'self_lifetime self,
+    ) -> &'self_lifetime CalculateWebPageMessageDigestService<ProgressReporter> {
         &self.calculate_web_page_message_digest_service
     }
 }
 
+/// The injector shared by the requests handled by `serve`, as the router
+/// state
+///
+/// Its progress reporter isn't used, since each request injects its own
+/// through a [RequestInjector].
+type ServeInjector = Stage2Injector<ByteCountingProgressReporter>;
+
+/// Contains the progress reporter of a single request handled by `serve`,
+/// counting the size of its Web page, and injects the other services from
+/// the shared [ServeInjector]
+struct RequestInjector {
+    dependency_injector: Arc<ServeInjector>,
+    progress_reporter: ByteCountingProgressReporter,
+}
+
+impl RequestInjector {
+    fn from_dependency_injector(dependency_injector: Arc<ServeInjector>) -> Self {
+        RequestInjector {
+            dependency_injector,
+            progress_reporter: ByteCountingProgressReporter::new(),
+        }
+    }
+}
+
+impl<'self_lifetime> InjectRef<'self_lifetime, DynClock> for RequestInjector {
+    fn inject_ref(&'self_lifetime self) -> &'self_lifetime DynClock {
+        InjectRef::<DynClock>::inject_ref(&*self.dependency_injector)
+    }
+}
+
+impl<'self_lifetime> InjectRef<'self_lifetime, ResponsePolicy> for RequestInjector {
+    fn inject_ref(&'self_lifetime self) -> &'self_lifetime ResponsePolicy {
+        InjectRef::<ResponsePolicy>::inject_ref(&*self.dependency_injector)
+    }
+}
+
+impl<'self_lifetime> InjectRef<'self_lifetime, HttpClientService> for RequestInjector {
+    fn inject_ref(&'self_lifetime self) -> &'self_lifetime HttpClientService {
+        InjectRef::<HttpClientService>::inject_ref(&*self.dependency_injector)
+    }
+}
+
+impl<'self_lifetime> InjectRef<'self_lifetime, MessageDigestService> for RequestInjector {
+    fn inject_ref(&'self_lifetime self) -> &'self_lifetime MessageDigestService {
+        InjectRef::<MessageDigestService>::inject_ref(&*self.dependency_injector)
+    }
+}
+
+impl<'self_lifetime> InjectRef<'self_lifetime, ByteCountingProgressReporter> for RequestInjector {
+    fn inject_ref(&'self_lifetime self) -> &'self_lifetime ByteCountingProgressReporter {
+        &self.progress_reporter
+    }
+}
+
 /// Prints the 256-bit SHA-3 message digest of a Web page
 ///
 /// This is a tiny demo app using the injector parameter design option for
 /// Depedency Injection in Rust.
 #[derive(Debug, Parser)]
-#[command(version)]
+#[command(version, subcommand_negates_reqs = true)]
 struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    #[arg(long, required = true)]
+    url: Option<String>,
+
+    /// How progress of downloading the Web page is reported
+    #[arg(long, value_enum, default_value_t = ProgressDisplay::Bar)]
+    progress: ProgressDisplay,
+
+    /// Emit traces of fetching, streaming, and hashing the Web page on
+    /// standard error in this format
+    ///
+    /// Traces are filtered by the `RUST_LOG` environment variable, with
+    /// `tracing-subscriber` `EnvFilter` syntax (e.g., `debug`), when it's set.
+    #[arg(long, value_enum)]
+    log_format: Option<LogFormat>,
+
+    /// Record the response into this cassette file, e.g., a test fixture,
+    /// keeping responses recorded earlier for other URLs
+    #[arg(long, value_name = "CASSETTE", conflicts_with = "replay")]
+    record: Option<PathBuf>,
+
+    /// Serve the response recorded with `--record` from this cassette file
+    /// instead of sending a request
+    #[arg(long, value_name = "CASSETTE")]
+    replay: Option<PathBuf>,
+
+    #[command(flatten)]
+    configuration_args: ConfigurationArgs,
+
+    #[command(flatten)]
+    request_args: RequestArgs,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Serves message digests of Web pages over HTTP, e.g., as a sidecar
+    ///
+    /// `GET /digest?url=URL[&alg=sha3-256]` returns the digest of a Web page
+    /// as a JSON object with the `url`, `algorithm`, `digest`, `size`, and
+    /// `error` fields.  `POST /digest` with a JSON array of `{"url": URL,
+    /// "alg": ALGORITHM}` objects returns an array of digests.  `GET /healthz`
+    /// returns `{"status": "ok"}`.  Requests that can't be parsed are answered
+    /// with an `error` object alone.
+    ///
+    /// Other options, e.g., `--timeout`, are given before `serve`.
+    Serve(ServeArgs),
+}
+
+#[derive(Debug, ClapArgs)]
+struct ServeArgs {
+    /// The address to listen on
+    #[arg(long, default_value = "127.0.0.1:8080")]
+    listen: SocketAddr,
+
+    /// The maximum number of Web pages in a `POST /digest` batch
+    #[arg(long, default_value_t = 100, value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..))]
+    max_batch_size: usize,
+
+    /// The number of Web pages fetched at once across all requests
+    #[arg(long, default_value_t = 4, value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..))]
+    concurrency: usize,
+}
+
+/// Settings overriding the configuration file, and `WEB_PAGE_HASH_*`
+/// environment variables
+#[derive(Debug, ClapArgs)]
+struct ConfigurationArgs {
+    /// A TOML configuration file [default: $WEB_PAGE_HASH_CONFIG, or
+    /// $XDG_CONFIG_HOME/web-page-hash/config.toml]
+    #[arg(long)]
+    config: Option<PathBuf>,
+
+    /// The maximum time for establishing a connection in seconds, or 0 for
+    /// no limit
+    #[arg(long)]
+    connect_timeout: Option<u64>,
+
+    /// The maximum time for a request in seconds, or 0 for no limit
+    #[arg(long)]
+    timeout: Option<u64>,
+
+    /// The `User-Agent` header value sent with requests
+    #[arg(long)]
+    user_agent: Option<String>,
+
+    /// The URL of a proxy for all requests
+    #[arg(long)]
+    proxy: Option<String>,
+
+    /// Fail instead of hashing response bodies larger than this size in
+    /// bytes
+    #[arg(long)]
+    max_body_size: Option<u64>,
+
+    /// Hash bodies of `4xx`, and `5xx` responses instead of failing
+    #[arg(long, overrides_with = "no_allow_error_status")]
+    allow_error_status: bool,
+
+    /// Fail on `4xx`, and `5xx` responses, even when the configuration
+    /// file, or environment variables allow them
+    #[arg(long, overrides_with = "allow_error_status")]
+    no_allow_error_status: bool,
+
+    /// Fail instead of hashing responses of other media types, e.g.,
+    /// `text/html`, or `image/*`
+    ///
+    /// Can be given more than once.
+    #[arg(long = "allow-content-type", value_name = "MEDIA_TYPE")]
+    allowed_content_types: Vec<String>,
+
+    /// Fail instead of hashing responses of this media type
+    ///
+    /// Can be given more than once.
+    #[arg(long = "deny-content-type", value_name = "MEDIA_TYPE")]
+    denied_content_types: Vec<String>,
+}
+
+impl ConfigurationArgs {
+    /// Returns the settings given as arguments, overriding all other
+    /// configuration layers
+    fn configuration_layer(&self) -> ConfigurationLayer {
+        ConfigurationLayer {
+            connect_timeout: self.connect_timeout,
+            timeout: self.timeout,
+            user_agent: self.user_agent.clone(),
+            proxy: self.proxy.clone(),
+            max_body_size: self.max_body_size,
+            allow_error_status: self
+                .allow_error_status
+                .then_some(true)
+                .or(self.no_allow_error_status.then_some(false)),
+            allowed_content_types: (!self.allowed_content_types.is_empty())
+                .then(|| self.allowed_content_types.clone()),
+            denied_content_types: (!self.denied_content_types.is_empty())
+                .then(|| self.denied_content_types.clone()),
+        }
+    }
+}
+
+/// Additional request headers, and credentials
+#[derive(Debug, ClapArgs)]
+struct RequestArgs {
+    /// An additional request header, e.g., `X-Api-Key: <key>`
+    ///
+    /// Can be given more than once.
+    #[arg(long = "header", value_name = "NAME:VALUE", value_parser = parse_header)]
+    headers: Vec<(HeaderName, HeaderValue)>,
+
+    /// Send this `Bearer` token with requests
+    ///
+    /// Prefer setting `WEB_PAGE_HASH_BEARER_TOKEN`, since arguments are
+    /// visible to other users.
+    #[arg(long, conflicts_with_all = ["user", "netrc", "netrc_file"])]
+    bearer_token: Option<String>,
+
+    /// Send this user name, and optional password with requests using
+    /// `Basic` authentication
+    #[arg(long, value_name = "USER[:PASSWORD]", conflicts_with_all = ["netrc", "netrc_file"])]
+    user: Option<String>,
+
+    /// Look up `Basic` authentication credentials by host name in $NETRC,
+    /// or ~/.netrc
+    #[arg(long, conflicts_with = "netrc_file")]
+    netrc: bool,
+
+    /// Look up `Basic` authentication credentials by host name in this
+    /// `.netrc` file
     #[arg(long)]
-    url: String,
+    netrc_file: Option<PathBuf>,
+}
+
+impl RequestArgs {
+    /// Returns the credential provider selected by the arguments
+    ///
+    /// Without credential arguments, credentials are read from
+    /// `WEB_PAGE_HASH_BEARER_TOKEN`, or `WEB_PAGE_HASH_USERNAME`, and
+    /// `WEB_PAGE_HASH_PASSWORD`, when they're set.
+    fn credential_provider(&self) -> Result<SelectableCredentialProvider, CredentialError> {
+        Ok(match (&self.bearer_token, &self.user, &self.netrc_file) {
+            (Some(token), _, _) => SelectableCredentialProvider::BearerToken(
+                BearerTokenCredentialProvider::new(token)?,
+            ),
+            (None, Some(user), _) => {
+                let (username, password) = match user.split_once(':') {
+                    Some((username, password)) => (username, Some(password)),
+                    None => (user.as_str(), None),
+                };
+
+                SelectableCredentialProvider::BasicAuth(BasicAuthCredentialProvider::new(
+                    username, password,
+                ))
+            }
+            (None, None, Some(netrc_file)) => {
+                SelectableCredentialProvider::Netrc(NetrcCredentialProvider::from_file(netrc_file)?)
+            }
+            (None, None, None) if self.netrc => {
+                SelectableCredentialProvider::Netrc(NetrcCredentialProvider::from_default_file()?)
+            }
+            (None, None, None) => {
+                SelectableCredentialProvider::Environment(EnvironmentCredentialProvider::new())
+            }
+        })
+    }
+
+    /// Returns the additional request headers
+    fn request_headers(&self) -> HeaderMap {
+        let mut request_headers = HeaderMap::new();
+        for (name, value) in &self.headers {
+            request_headers.append(name, value.clone());
+        }
+
+        request_headers
+    }
+}
+
+/// Parses a `NAME:VALUE` request header
+///
+/// Values are marked as sensitive, since they may hold credentials, e.g., API
+/// keys.
+fn parse_header(header: &str) -> Result<(HeaderName, HeaderValue), String> {
+    let (name, value) = header
+        .split_once(':')
+        .ok_or_else(|| "expected NAME:VALUE".to_string())?;
+    let name = HeaderName::try_from(name.trim()).map_err(|error| error.to_string())?;
+    let mut value = HeaderValue::try_from(value.trim()).map_err(|error| error.to_string())?;
+    value.set_sensitive(true);
+
+    Ok((name, value))
+}
+
+/// Formats of traces emitted on standard error
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum LogFormat {
+    /// Multi-line, human-readable events
+    Pretty,
+    /// One JSON object per event, including the spans it's in
+    Json,
+}
+
+/// Progress reporters that can be injected as the progress reporter
+/// dependency
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum ProgressDisplay {
+    /// A progress bar on standard error, when it's a terminal
+    Bar,
+    /// No progress reporting
+    None,
 }
 
 struct HexFormatted<'a>(&'a [u8]);
@@ -105,34 +539,812 @@ struct HexFormatted<'a>(&'a [u8]);
 impl std::fmt::LowerHex for HexFormatted<'_> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
         for octet in self.0 {
-            write!(f, "{:x}", octet)?;
+            write!(f, "{:02x}", octet)?;
         }
 
         Ok(())
     }
 }
 
-#[tokio::main]
-async fn main() -> Result<(), anyhow::Error> {
-    let args = Args::parse();
+/// Returns the HTTP client service selected by `--record`, and `--replay`
+fn selectable_http_client_service(
+    configuration_service: &LayeredConfigurationService,
+    record: Option<&Path>,
+    replay: Option<&Path>,
+) -> Result<SelectableGetUrl, anyhow::Error> {
+    Ok(match (record, replay) {
+        (_, Some(cassette_path)) => SelectableGetUrl::Replay(
+            ReplayGetUrl::from_file(cassette_path)
+                .with_context(|| format!("couldn't read {}", cassette_path.display()))?,
+        ),
+        (Some(cassette_path), None) => SelectableGetUrl::Recording(
+            RecordingGetUrl::new(
+                ReqwestHttpClientService::from_configuration(configuration_service)?,
+                cassette_path,
+            )
+            .with_context(|| format!("couldn't read {}", cassette_path.display()))?,
+        ),
+        (None, None) => SelectableGetUrl::Reqwest(ReqwestHttpClientService::from_configuration(
+            configuration_service,
+        )?),
+    })
+}
 
-    let url = reqwest::Url::parse(&args.url)?;
+/// Builds the injectors with a progress reporter
+fn build_injector<ProgressReporter>(
+    configuration_service: &LayeredConfigurationService,
+    selectable_http_client_service: SelectableGetUrl,
+    request_args: &RequestArgs,
+    progress_reporter: ProgressReporter,
+) -> Result<Stage2Injector<ProgressReporter>, anyhow::Error> {
+    let http_client_service = AuthenticatingGetUrl::new(
+        selectable_http_client_service,
+        request_args.credential_provider()?,
+        request_args.request_headers(),
+    );
     let stage1_injector = Stage1Injector::from_services(
-        ReqwestHttpClientService::new(),
-        Sha3_256BitMessageDigestService::new(),
+        TokioClock::new(),
+        ConfiguredResponsePolicy::from_configuration(configuration_service),
+        Traced::new(http_client_service),
+        Traced::new(Sha3_256BitMessageDigestService::new()),
+        progress_reporter,
     );
-    let injector = Stage2Injector::from_dependency_injector_and_services(
+
+    Ok(Stage2Injector::from_dependency_injector_and_services(
         stage1_injector,
-        CalculateWebPageMessageDigestService::new(),
-    );
-    let calculate_web_page_message_digest_service: &CalculateWebPageMessageDigestService =
-        InjectRef::inject_ref(&injector);
+        Traced::new(
+            calculate_web_page_message_digest_service::CalculateWebPageMessageDigestService::new(),
+        ),
+    ))
+}
+
+/// Builds the injectors with a progress reporter, and prints the digest
+async fn print_web_page_message_digest<ProgressReporter>(
+    configuration_service: LayeredConfigurationService,
+    selectable_http_client_service: SelectableGetUrl,
+    request_args: &RequestArgs,
+    url: &Url,
+    progress_reporter: ProgressReporter,
+) -> Result<(), anyhow::Error>
+where
+    ProgressReporter: ReportStarted + ReportBytesWritten + ReportFinished + Send + Sync,
+    Stage2Injector<ProgressReporter>: for<'a> InjectRef<'a, ProgressReporter>,
+{
+    let injector = build_injector(
+        &configuration_service,
+        selectable_http_client_service,
+        request_args,
+        progress_reporter,
+    )?;
+    let calculate_web_page_message_digest_service =
+        InjectRef::<CalculateWebPageMessageDigestService<ProgressReporter>>::inject_ref(&injector);
 
     println!("Fetching: {}", url);
     let digest = calculate_web_page_message_digest_service
-        .calculate_web_page_message_digest(&injector, &url)
+        .calculate_web_page_message_digest(&injector, url)
         .await?;
     println!("256-bit SHA-3: 0x{:x}", HexFormatted(&digest));
 
     Ok(())
 }
+
+/// Serves message digests calculated by the calculate Web page message
+/// digest service injected from the shared [ServeInjector]
+async fn serve(
+    configuration_service: LayeredConfigurationService,
+    selectable_http_client_service: SelectableGetUrl,
+    request_args: &RequestArgs,
+    serve_args: &ServeArgs,
+) -> Result<(), anyhow::Error> {
+    let injector = build_injector(
+        &configuration_service,
+        selectable_http_client_service,
+        request_args,
+        ByteCountingProgressReporter::new(),
+    )?;
+    let router = router(Arc::new(ServeState {
+        injector: Arc::new(injector),
+        max_batch_size: serve_args.max_batch_size,
+        concurrency: serve_args.concurrency,
+        permits: Semaphore::new(serve_args.concurrency),
+    }));
+
+    let listener = tokio::net::TcpListener::bind(serve_args.listen)
+        .await
+        .with_context(|| format!("couldn't listen on {}", serve_args.listen))?;
+    eprintln!("Listening on: http://{}", listener.local_addr()?);
+    axum::serve(listener, router).await?;
+
+    Ok(())
+}
+
+fn router(serve_state: Arc<ServeState>) -> axum::Router {
+    axum::Router::new()
+        .route("/digest", get(get_digest).post(post_digests))
+        .route("/healthz", get(get_health))
+        .with_state(serve_state)
+}
+
+/// The state shared by the handlers of [serve]
+struct ServeState {
+    injector: Arc<ServeInjector>,
+    max_batch_size: usize,
+    /// The number of digests of a batch calculated at once
+    concurrency: usize,
+    /// Permits of calculating a digest, so that concurrent requests don't
+    /// fetch more than `concurrency` Web pages at once
+    permits: Semaphore,
+}
+
+async fn get_digest(
+    State(serve_state): State<Arc<ServeState>>,
+    digest_query: Result<Query<DigestQuery>, QueryRejection>,
+) -> Result<DigestResponse, DigestError> {
+    let Query(digest_query) = digest_query?;
+
+    Ok(calculate_digest_response(&serve_state, digest_query).await)
+}
+
+/// Calculates the digests of a batch of Web pages concurrently, and returns
+/// them in the order of the batch
+///
+/// Each digest has its own error, so the batch succeeds even when some
+/// digests fail.  Batches longer than the maximum batch size are rejected
+/// as a whole.
+async fn post_digests(
+    State(serve_state): State<Arc<ServeState>>,
+    digest_queries: Result<Json<Vec<DigestQuery>>, JsonRejection>,
+) -> Result<Json<Vec<DigestResponse>>, DigestError> {
+    let Json(digest_queries) = digest_queries?;
+    if digest_queries.len() > serve_state.max_batch_size {
+        return Err(DigestError::new(
+            DigestErrorKind::BatchTooLarge,
+            format!(
+                "the batch has {} Web pages (at most {} are allowed)",
+                digest_queries.len(),
+                serve_state.max_batch_size
+            ),
+        ));
+    }
+
+    let digest_responses = futures_util::stream::iter(digest_queries)
+        .map(|digest_query| calculate_digest_response(&serve_state, digest_query))
+        .buffered(serve_state.concurrency)
+        .collect()
+        .await;
+
+    Ok(Json(digest_responses))
+}
+
+async fn get_health() -> Json<serde_json::Value> {
+    Json(serde_json::json!({ "status": "ok" }))
+}
+
+async fn calculate_digest_response(
+    serve_state: &ServeState,
+    digest_query: DigestQuery,
+) -> DigestResponse {
+    let DigestQuery { url, alg } = digest_query;
+    let algorithm = alg.unwrap_or_else(|| ALGORITHM.to_string());
+    if !algorithm.eq_ignore_ascii_case(ALGORITHM) {
+        let error = DigestError::new(
+            DigestErrorKind::UnsupportedAlgorithm,
+            format!(
+                "unsupported algorithm: {} (only {} is supported)",
+                algorithm, ALGORITHM
+            ),
+        );
+        return DigestResponse::failed(url, algorithm, error);
+    }
+    let algorithm = ALGORITHM.to_string();
+    let parsed_url = match Url::parse(&url) {
+        Ok(parsed_url) => parsed_url,
+        Err(error) => {
+            let error = DigestError::new(
+                DigestErrorKind::InvalidUrl,
+                format!("invalid URL: {}", error),
+            );
+            return DigestResponse::failed(url, algorithm, error);
+        }
+    };
+
+    let injector = &serve_state.injector;
+    let _permit = serve_state
+        .permits
+        .acquire()
+        .await
+        .expect("the semaphore is never closed");
+    let request_injector = RequestInjector::from_dependency_injector(injector.clone());
+    let calculate_web_page_message_digest_service = InjectRef::<
+        CalculateWebPageMessageDigestService<ByteCountingProgressReporter>,
+    >::inject_ref(&**injector);
+
+    match boxed_calculate_web_page_message_digest(
+        calculate_web_page_message_digest_service,
+        &request_injector,
+        &parsed_url,
+    )
+    .await
+    {
+        Ok(digest) => DigestResponse::calculated(
+            url,
+            algorithm,
+            &digest,
+            request_injector.progress_reporter.byte_count(),
+        ),
+        Err(error) => DigestResponse::failed(url, algorithm, DigestError::from_anyhow(&error)),
+    }
+}
+
+/// Calls a calculate Web page message digest service, and boxes its future
+///
+/// The concrete future is hidden behind the `Send` bound of the service
+/// trait, since proving that it's `Send` in an axum handler hits a compiler
+/// limitation with higher-ranked lifetimes (see
+/// <https://github.com/rust-lang/rust/issues/100013>).
+fn boxed_calculate_web_page_message_digest<'a, Service, Injector>(
+    calculate_web_page_message_digest_service: &'a Service,
+    injector: &'a Injector,
+    url: &'a Service::Url,
+) -> BoxFuture<'a, Result<Service::Digest, Service::Error>>
+where
+    Service: CalculateWebPageMessageDigest<'a, Injector>,
+    &'a Injector: Send,
+{
+    Box::pin(
+        calculate_web_page_message_digest_service.calculate_web_page_message_digest(injector, url),
+    )
+}
+
+/// Installs a [tracing] subscriber printing traces on standard error
+fn init_tracing(log_format: LogFormat) {
+    let env_filter =
+        EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(DEFAULT_LOG_FILTER));
+    let subscriber = tracing_subscriber::fmt()
+        .with_env_filter(env_filter)
+        .with_writer(std::io::stderr);
+
+    match log_format {
+        LogFormat::Pretty => subscriber.pretty().init(),
+        LogFormat::Json => subscriber.json().with_span_list(true).init(),
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<(), anyhow::Error> {
+    let args = Args::parse();
+
+    if let Some(log_format) = args.log_format {
+        init_tracing(log_format);
+    }
+
+    let configuration_service = LayeredConfigurationService::load(
+        args.configuration_args.config.as_deref(),
+        args.configuration_args.configuration_layer(),
+    )?;
+    let http_client_service = selectable_http_client_service(
+        &configuration_service,
+        args.record.as_deref(),
+        args.replay.as_deref(),
+    )?;
+
+    if let Some(Command::Serve(serve_args)) = &args.command {
+        return serve(
+            configuration_service,
+            http_client_service,
+            &args.request_args,
+            serve_args,
+        )
+        .await;
+    }
+    let url = reqwest::Url::parse(
+        args.url
+            .as_deref()
+            .expect("clap requires --url without a subcommand"),
+    )?;
+
+    match args.progress {
+        ProgressDisplay::Bar => {
+            print_web_page_message_digest(
+                configuration_service,
+                http_client_service,
+                &args.request_args,
+                &url,
+                IndicatifProgressReporter::new(),
+            )
+            .await
+        }
+        ProgressDisplay::None => {
+            print_web_page_message_digest(
+                configuration_service,
+                http_client_service,
+                &args.request_args,
+                &url,
+                NoProgressReporter::new(),
+            )
+            .await
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytes::Bytes;
+    use http::StatusCode;
+    use tower::ServiceExt;
+    use web_page_hash_using_injector_parameter::credential_provider_service::{
+        Credential, LookUpCredential,
+    };
+    use web_page_hash_using_injector_parameter::http_cassette::{Cassette, CassetteInteraction};
+    use web_page_hash_using_injector_parameter::progress_reporter_service::{
+        ProgressEvent, RecordingProgressReporter,
+    };
+
+    const URL: &str = "http://example.com/page";
+
+    impl<'self_lifetime> InjectRef<'self_lifetime, RecordingProgressReporter>
+        for Stage1Injector<RecordingProgressReporter>
+    {
+        fn inject_ref(&'self_lifetime self) -> &'self_lifetime RecordingProgressReporter {
+            &self.progress_reporter
+        }
+    }
+
+    impl<'self_lifetime> InjectRef<'self_lifetime, RecordingProgressReporter>
+        for Stage2Injector<RecordingProgressReporter>
+    {
+        fn inject_ref(&'self_lifetime self) -> &'self_lifetime RecordingProgressReporter {
+            InjectRef::<RecordingProgressReporter>::inject_ref(&self.dependency_injector)
+        }
+    }
+
+    /// Calculates the digest of a Web page replayed in chunks, and returns
+    /// the progress events reported meanwhile
+    async fn report_progress(
+        configuration_layer: ConfigurationLayer,
+        headers: HeaderMap,
+    ) -> (Result<Digest, anyhow::Error>, Vec<ProgressEvent>) {
+        let mut cassette = Cassette::default();
+        cassette.record(CassetteInteraction::new(
+            URL.to_string(),
+            StatusCode::OK,
+            headers,
+            vec![Bytes::from_static(b"<p>page"), Bytes::from_static(b"</p>")],
+        ));
+        let request_args = request_args();
+        let injector = build_injector(
+            &LayeredConfigurationService::new([configuration_layer]),
+            SelectableGetUrl::Replay(ReplayGetUrl::new(cassette)),
+            &request_args,
+            RecordingProgressReporter::new(),
+        )
+        .unwrap();
+
+        let digest_result =
+            InjectRef::<CalculateWebPageMessageDigestService<_>>::inject_ref(&injector)
+                .calculate_web_page_message_digest(&injector, &Url::parse(URL).unwrap())
+                .await;
+
+        (
+            digest_result,
+            InjectRef::<RecordingProgressReporter>::inject_ref(&injector).events(),
+        )
+    }
+
+    #[tokio::test]
+    async fn progress_is_reported_for_each_chunk() {
+        let headers =
+            HeaderMap::from_iter([(http::header::CONTENT_LENGTH, HeaderValue::from_static("11"))]);
+
+        let (digest_result, events) = report_progress(ConfigurationLayer::default(), headers).await;
+
+        assert!(digest_result.is_ok());
+        assert_eq!(
+            events,
+            [
+                ProgressEvent::Started {
+                    url: URL.to_string(),
+                    expected_length: Some(11),
+                },
+                ProgressEvent::BytesWritten(7),
+                ProgressEvent::BytesWritten(4),
+                ProgressEvent::Finished,
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn progress_is_finished_when_streaming_fails() {
+        let configuration_layer = ConfigurationLayer {
+            max_body_size: Some(8),
+            ..ConfigurationLayer::default()
+        };
+
+        let (digest_result, events) = report_progress(configuration_layer, HeaderMap::new()).await;
+
+        assert!(digest_result.is_err());
+        assert_eq!(
+            events,
+            [
+                ProgressEvent::Started {
+                    url: URL.to_string(),
+                    expected_length: None,
+                },
+                ProgressEvent::BytesWritten(7),
+                ProgressEvent::Finished,
+            ]
+        );
+    }
+
+    /// Returns the state of a server replaying a Web page
+    fn serve_state(max_batch_size: usize, concurrency: usize) -> Arc<ServeState> {
+        let mut cassette = Cassette::default();
+        cassette.record(CassetteInteraction::new(
+            URL.to_string(),
+            StatusCode::OK,
+            HeaderMap::new(),
+            vec![Bytes::from_static(b"<p>page</p>")],
+        ));
+        let request_args = request_args();
+        let injector = build_injector(
+            &LayeredConfigurationService::new([ConfigurationLayer::default()]),
+            SelectableGetUrl::Replay(ReplayGetUrl::new(cassette)),
+            &request_args,
+            ByteCountingProgressReporter::new(),
+        )
+        .unwrap();
+
+        Arc::new(ServeState {
+            injector: Arc::new(injector),
+            max_batch_size,
+            concurrency,
+            permits: Semaphore::new(concurrency),
+        })
+    }
+
+    fn digest_query(url: &str) -> DigestQuery {
+        DigestQuery {
+            url: url.to_string(),
+            alg: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn batch_digests_are_returned_in_the_order_of_the_batch() {
+        let digest_queries = vec![
+            digest_query(URL),
+            digest_query("not a URL"),
+            digest_query(URL),
+        ];
+
+        let Json(digest_responses) =
+            post_digests(State(serve_state(3, 2)), Ok(Json(digest_queries)))
+                .await
+                .unwrap();
+
+        let urls_and_error_kinds = digest_responses
+            .iter()
+            .map(|digest_response| {
+                (
+                    digest_response.url.as_str(),
+                    digest_response.error.as_ref().map(|error| error.kind),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            urls_and_error_kinds,
+            [
+                (URL, None),
+                ("not a URL", Some(DigestErrorKind::InvalidUrl)),
+                (URL, None),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn batches_longer_than_the_maximum_batch_size_are_rejected() {
+        let digest_queries = vec![digest_query(URL), digest_query(URL)];
+
+        let Err(error) = post_digests(State(serve_state(1, 2)), Ok(Json(digest_queries))).await
+        else {
+            panic!("the batch wasn't rejected");
+        };
+
+        assert_eq!(error.kind, DigestErrorKind::BatchTooLarge);
+        assert_eq!(error.kind.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    }
+
+    /// Sends a request to the router of a server, and returns the status,
+    /// and the JSON body of the response
+    async fn send(
+        serve_state: Arc<ServeState>,
+        request: http::Request<axum::body::Body>,
+    ) -> (StatusCode, serde_json::Value) {
+        let response = router(serve_state).oneshot(request).await.unwrap();
+        let status = response.status();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    fn get(uri: &str) -> http::Request<axum::body::Body> {
+        http::Request::get(uri)
+            .body(axum::body::Body::empty())
+            .unwrap()
+    }
+
+    fn post(content_type: Option<&str>, body: &str) -> http::Request<axum::body::Body> {
+        let mut request = http::Request::post("/digest");
+        if let Some(content_type) = content_type {
+            request = request.header(http::header::CONTENT_TYPE, content_type);
+        }
+
+        request
+            .body(axum::body::Body::from(body.to_string()))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn health_is_reported() {
+        let (status, body) = send(serve_state(1, 1), get("/healthz")).await;
+
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, serde_json::json!({ "status": "ok" }));
+    }
+
+    #[tokio::test]
+    async fn digests_are_served() {
+        let (status, body) = send(
+            serve_state(1, 1),
+            get("/digest?url=http%3A%2F%2Fexample.com%2Fpage&alg=SHA3-256"),
+        )
+        .await;
+
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["url"], URL);
+        assert_eq!(body["algorithm"], ALGORITHM);
+        assert_eq!(body["size"], 11);
+        assert_eq!(body["digest"].as_str().unwrap().len(), 64);
+        assert_eq!(body["error"], serde_json::Value::Null);
+    }
+
+    #[tokio::test]
+    async fn digest_errors_are_mapped_to_statuses() {
+        let (status, body) = send(serve_state(1, 1), get("/digest?url=not%20a%20URL")).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["url"], "not a URL");
+        assert_eq!(body["error"]["kind"], "invalid_url");
+
+        let (status, body) = send(
+            serve_state(1, 1),
+            get("/digest?url=http%3A%2F%2Fexample.com%2Fpage&alg=md5"),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"]["kind"], "unsupported_algorithm");
+
+        // The cassette has no other Web page:
+        let (status, body) = send(
+            serve_state(1, 1),
+            get("/digest?url=http%3A%2F%2Fexample.com%2Fother"),
+        )
+        .await;
+        assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(body["error"]["kind"], "internal");
+    }
+
+    #[tokio::test]
+    async fn unparsable_requests_are_answered_with_digest_errors() {
+        for (request, expected_status, expected_kind) in [
+            (get("/digest"), StatusCode::BAD_REQUEST, "invalid_request"),
+            (
+                post(Some("application/json"), "[{\"alg\": \"sha3-256\"}]"),
+                StatusCode::BAD_REQUEST,
+                "invalid_request",
+            ),
+            (
+                post(Some("application/json"), "[{"),
+                StatusCode::BAD_REQUEST,
+                "invalid_request",
+            ),
+            (
+                post(None, "[]"),
+                StatusCode::UNSUPPORTED_MEDIA_TYPE,
+                "unsupported_media_type",
+            ),
+        ] {
+            let (status, body) = send(serve_state(1, 1), request).await;
+
+            assert_eq!(status, expected_status, "{body}");
+            assert_eq!(body["kind"], expected_kind, "{body}");
+            assert!(!body["message"].as_str().unwrap().is_empty(), "{body}");
+        }
+    }
+
+    #[tokio::test]
+    async fn batches_are_served_in_order() {
+        let (status, body) = send(
+            serve_state(3, 2),
+            post(
+                Some("application/json"),
+                &serde_json::json!([
+                    { "url": URL },
+                    { "url": "not a URL" },
+                    { "url": URL, "alg": "sha3-256" },
+                ])
+                .to_string(),
+            ),
+        )
+        .await;
+
+        assert_eq!(status, StatusCode::OK);
+        let urls_and_error_kinds = body
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|digest_response| {
+                (
+                    digest_response["url"].as_str().unwrap(),
+                    digest_response["error"]["kind"].as_str(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            urls_and_error_kinds,
+            [(URL, None), ("not a URL", Some("invalid_url")), (URL, None)]
+        );
+
+        let (status, body) = send(
+            serve_state(1, 2),
+            post(
+                Some("application/json"),
+                "[{\"url\": \"a\"}, {\"url\": \"b\"}]",
+            ),
+        )
+        .await;
+        assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+        assert_eq!(body["kind"], "batch_too_large");
+    }
+
+    #[tokio::test]
+    async fn web_pages_are_fetched_within_the_concurrency_across_requests() {
+        let serve_state = serve_state(1, 1);
+        let permit = serve_state.permits.acquire().await.unwrap();
+
+        let mut response = Box::pin(send(
+            serve_state.clone(),
+            get("/digest?url=http%3A%2F%2Fexample.com%2Fpage"),
+        ));
+        // Another request holds the only permit:
+        assert!(
+            tokio::time::timeout(std::time::Duration::from_millis(100), &mut response)
+                .await
+                .is_err()
+        );
+        drop(permit);
+
+        let (status, _) = response.await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    #[test]
+    fn hex_formatted_pads_octets_below_0x10() {
+        let octets = [0x00, 0x01, 0x0f, 0x10, 0xab, 0xff];
+
+        assert_eq!(format!("{:x}", HexFormatted(&octets)), "00010f10abff");
+    }
+
+    fn request_args() -> RequestArgs {
+        RequestArgs {
+            headers: Vec::new(),
+            bearer_token: None,
+            user: None,
+            netrc: false,
+            netrc_file: None,
+        }
+    }
+
+    /// Writes a `.netrc` file named after a test into the temporary
+    /// directory
+    fn netrc_file(name: &str, text: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!(
+            "web-page-hash-injector-{}-{}.netrc",
+            name,
+            std::process::id()
+        ));
+        std::fs::write(&path, text).unwrap();
+
+        path
+    }
+
+    /// Returns the `Authorization` header value sent to `url` with the
+    /// credentials selected by the arguments
+    fn authorization(request_args: &RequestArgs, url: &str) -> Option<HeaderValue> {
+        request_args
+            .credential_provider()
+            .unwrap()
+            .look_up_credential(url)
+            .unwrap()
+            .map(|credential| credential.authorization().clone())
+    }
+
+    fn basic(username: &str, password: Option<&str>) -> Option<HeaderValue> {
+        Some(
+            Credential::basic(username, password)
+                .authorization()
+                .clone(),
+        )
+    }
+
+    #[test]
+    fn netrc_credentials_are_looked_up_by_host_name_or_default() {
+        let path = netrc_file(
+            "host",
+            "machine EXAMPLE.com login alice password secret\n\
+             macdef init\n\
+             machine example.net login mallory\n\
+             \n\
+             default login anonymous\n",
+        );
+        let request_args = RequestArgs {
+            netrc_file: Some(path.clone()),
+            ..request_args()
+        };
+
+        assert_eq!(
+            authorization(&request_args, URL),
+            basic("alice", Some("secret"))
+        );
+        assert_eq!(
+            authorization(&request_args, "http://example.net/"),
+            basic("anonymous", None)
+        );
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn invalid_netrc_files_are_rejected() {
+        let path = netrc_file("invalid", "login alice\n");
+        let request_args = RequestArgs {
+            netrc_file: Some(path.clone()),
+            ..request_args()
+        };
+
+        assert!(matches!(
+            request_args.credential_provider(),
+            Err(CredentialError::InvalidNetrc(_, _))
+        ));
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn credential_arguments_take_precedence_over_netrc_files() {
+        let path = netrc_file("precedence", "default login alice password secret\n");
+        let user_args = RequestArgs {
+            user: Some("bob:hunter2".to_string()),
+            netrc: true,
+            netrc_file: Some(path.clone()),
+            ..request_args()
+        };
+        let bearer_token_args = RequestArgs {
+            bearer_token: Some("token".to_string()),
+            user: Some("bob:hunter2".to_string()),
+            netrc: true,
+            netrc_file: Some(path.clone()),
+            ..request_args()
+        };
+
+        assert_eq!(
+            authorization(&user_args, URL),
+            basic("bob", Some("hunter2"))
+        );
+        assert_eq!(
+            authorization(&bearer_token_args, URL),
+            Some(Credential::bearer("token").unwrap().authorization().clone())
+        );
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,38 @@
+use super::interface::{ProgressReporter, ReportBytesWritten, ReportFinished, ReportStarted};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// A [ProgressReporter] that counts the hashed response body octets, e.g.,
+/// for reporting the size of a Web page
+#[derive(Debug, Default)]
+pub struct ByteCountingProgressReporter {
+    byte_count: AtomicU64,
+}
+
+impl ByteCountingProgressReporter {
+    pub fn new() -> Self {
+        ByteCountingProgressReporter {
+            byte_count: AtomicU64::new(0),
+        }
+    }
+
+    /// Returns the number of octets reported so far
+    pub fn byte_count(&self) -> u64 {
+        self.byte_count.load(Ordering::Relaxed)
+    }
+}
+
+impl ReportStarted for ByteCountingProgressReporter {
+    fn report_started(&self, _url: &str, _expected_length: Option<u64>) {}
+}
+
+impl ReportBytesWritten for ByteCountingProgressReporter {
+    fn report_bytes_written(&self, byte_count: u64) {
+        self.byte_count.fetch_add(byte_count, Ordering::Relaxed);
+    }
+}
+
+impl ReportFinished for ByteCountingProgressReporter {
+    fn report_finished(&self) {}
+}
+
+impl ProgressReporter for ByteCountingProgressReporter {}
@@ -0,0 +1,75 @@
+use super::interface::{ProgressReporter, ReportBytesWritten, ReportFinished, ReportStarted};
+use crate::credential_provider_service::redact_url_credentials;
+use indicatif::{ProgressBar, ProgressStyle};
+use std::sync::{Mutex, PoisonError};
+
+const BAR_TEMPLATE: &str =
+    "{msg}\n{wide_bar} {bytes}/{total_bytes} ({bytes_per_sec}, {eta} remaining)";
+const SPINNER_TEMPLATE: &str = "{spinner} {msg} {bytes} ({bytes_per_sec})";
+
+/// A [ProgressReporter] drawing a progress bar on the terminal with the
+/// [indicatif] crate
+///
+/// A spinner is drawn instead, when the length of the response body isn't
+/// known.  The bar is drawn on standard error, only when it's a terminal, and
+/// it's cleared when streaming finishes.
+#[derive(Debug, Default)]
+pub struct IndicatifProgressReporter {
+    progress_bar: Mutex<Option<ProgressBar>>,
+}
+
+impl IndicatifProgressReporter {
+    pub fn new() -> Self {
+        IndicatifProgressReporter {
+            progress_bar: Mutex::new(None),
+        }
+    }
+}
+
+impl ReportStarted for IndicatifProgressReporter {
+    fn report_started(&self, url: &str, expected_length: Option<u64>) {
+        let progress_bar = match expected_length {
+            Some(expected_length) => ProgressBar::new(expected_length).with_style(
+                ProgressStyle::with_template(BAR_TEMPLATE)
+                    .unwrap_or_else(|_| ProgressStyle::default_bar()),
+            ),
+            None => ProgressBar::new_spinner().with_style(
+                ProgressStyle::with_template(SPINNER_TEMPLATE)
+                    .unwrap_or_else(|_| ProgressStyle::default_spinner()),
+            ),
+        };
+        progress_bar.set_message(redact_url_credentials(url).into_owned());
+
+        *self
+            .progress_bar
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner) = Some(progress_bar);
+    }
+}
+
+impl ReportBytesWritten for IndicatifProgressReporter {
+    fn report_bytes_written(&self, byte_count: u64) {
+        if let Some(progress_bar) = &*self
+            .progress_bar
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+        {
+            progress_bar.inc(byte_count);
+        }
+    }
+}
+
+impl ReportFinished for IndicatifProgressReporter {
+    fn report_finished(&self) {
+        if let Some(progress_bar) = self
+            .progress_bar
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .take()
+        {
+            progress_bar.finish_and_clear();
+        }
+    }
+}
+
+impl ProgressReporter for IndicatifProgressReporter {}
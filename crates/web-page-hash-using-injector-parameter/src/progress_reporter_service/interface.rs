@@ -0,0 +1,20 @@
+/// A function trait reporting that streaming a response body started
+pub trait ReportStarted {
+    /// `expected_length` is the `Content-Length` of the response, if known
+    fn report_started(&self, url: &str, expected_length: Option<u64>);
+}
+
+/// A function trait reporting that a chunk of a response body was hashed
+pub trait ReportBytesWritten {
+    fn report_bytes_written(&self, byte_count: u64);
+}
+
+/// A function trait reporting that streaming a response body finished,
+/// successfully, or not
+pub trait ReportFinished {
+    fn report_finished(&self);
+}
+
+// The full service is composed of its method, and function traits:
+/// An injectable service that reports progress of streaming response bodies
+pub trait ProgressReporter: ReportStarted + ReportBytesWritten + ReportFinished {}
@@ -0,0 +1,109 @@
+use super::interface::{
+    CheckBodySize, CheckResponseMetadata, ResponsePolicy, ResponsePolicyViolation,
+};
+use crate::configuration_service::ResponsePolicyConfiguration;
+use crate::http_client_service::ResponseMetadata;
+
+/// A [ResponsePolicy] checking the status, the media type, and the body size
+/// of responses
+///
+/// Media types are matched case-insensitively, ignoring parameters, such as
+/// `charset`.  A `type/*` pattern matches all subtypes of `type`.  Denied
+/// media types take precedence over allowed ones.
+#[derive(Clone, Debug, Default)]
+pub struct ConfiguredResponsePolicy {
+    max_body_size: Option<u64>,
+    allow_error_status: bool,
+    allowed_content_types: Vec<String>,
+    denied_content_types: Vec<String>,
+}
+
+impl ConfiguredResponsePolicy {
+    pub fn from_configuration(configuration: &impl ResponsePolicyConfiguration) -> Self {
+        ConfiguredResponsePolicy {
+            max_body_size: configuration.max_body_size(),
+            allow_error_status: configuration.allow_error_status(),
+            allowed_content_types: configuration.allowed_content_types().to_vec(),
+            denied_content_types: configuration.denied_content_types().to_vec(),
+        }
+    }
+}
+
+/// Returns whether a `Content-Type` header value matches a media type
+/// pattern, such as `text/html`, or `image/*`
+fn media_type_matches(pattern: &str, content_type: &str) -> bool {
+    let media_type = content_type.split(';').next().unwrap_or_default().trim();
+
+    match pattern.trim().strip_suffix("/*") {
+        Some(pattern_type) => media_type
+            .split_once('/')
+            .is_some_and(|(media_type_type, _)| media_type_type.eq_ignore_ascii_case(pattern_type)),
+        None => media_type.eq_ignore_ascii_case(pattern.trim()),
+    }
+}
+
+impl CheckResponseMetadata for ConfiguredResponsePolicy {
+    type Error = ResponsePolicyViolation;
+
+    fn check_response_metadata(&self, response: &impl ResponseMetadata) -> Result<(), Self::Error> {
+        let status = response.status();
+        if !self.allow_error_status && (status.is_client_error() || status.is_server_error()) {
+            return Err(ResponsePolicyViolation::ErrorStatus(status));
+        }
+
+        match response.content_type() {
+            Some(content_type)
+                if self
+                    .denied_content_types
+                    .iter()
+                    .any(|pattern| media_type_matches(pattern, content_type)) =>
+            {
+                return Err(ResponsePolicyViolation::ContentTypeDenied(
+                    content_type.to_string(),
+                ));
+            }
+            Some(content_type)
+                if !self.allowed_content_types.is_empty()
+                    && !self
+                        .allowed_content_types
+                        .iter()
+                        .any(|pattern| media_type_matches(pattern, content_type)) =>
+            {
+                return Err(ResponsePolicyViolation::ContentTypeNotAllowed(
+                    content_type.to_string(),
+                ));
+            }
+            None if !self.allowed_content_types.is_empty() => {
+                return Err(ResponsePolicyViolation::MissingContentType);
+            }
+            _ => {}
+        }
+
+        if let (Some(max_body_size), Some(content_length)) =
+            (self.max_body_size, response.content_length())
+            && content_length > max_body_size
+        {
+            return Err(ResponsePolicyViolation::ContentLengthExceeded {
+                content_length,
+                max_body_size,
+            });
+        }
+
+        Ok(())
+    }
+}
+
+impl CheckBodySize for ConfiguredResponsePolicy {
+    type Error = ResponsePolicyViolation;
+
+    fn check_body_size(&self, byte_count: u64) -> Result<(), Self::Error> {
+        match self.max_body_size {
+            Some(max_body_size) if byte_count > max_body_size => {
+                Err(ResponsePolicyViolation::BodySizeLimitExceeded { max_body_size })
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+impl ResponsePolicy for ConfiguredResponsePolicy {}
@@ -0,0 +1,8 @@
+pub mod interface;
+pub use interface::*;
+
+pub mod configured_response_policy;
+pub use configured_response_policy::*;
+
+pub mod no_response_policy;
+pub use no_response_policy::*;
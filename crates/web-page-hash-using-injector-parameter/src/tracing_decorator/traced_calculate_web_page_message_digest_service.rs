@@ -0,0 +1,64 @@
+use super::traced::{Traced, duration_as_millis};
+use crate::calculate_web_page_message_digest_service::CalculateWebPageMessageDigest;
+use crate::clock_service::DynClock;
+use crate::credential_provider_service::redact_url_credentials;
+use crate::injector::InjectRef;
+use std::fmt;
+use tracing::Instrument;
+
+/// Measures durations with the [Clock](crate::clock_service::Clock) of the
+/// injector, which only the decorator requires, not the service it decorates
+impl<'fn_call, Injector, CalculateWebPageMessageDigestService>
+    CalculateWebPageMessageDigest<'fn_call, Injector>
+    for Traced<CalculateWebPageMessageDigestService>
+where
+    Injector: InjectRef<'fn_call, DynClock> + 'fn_call,
+    CalculateWebPageMessageDigestService: CalculateWebPageMessageDigest<'fn_call, Injector> + Sync,
+    CalculateWebPageMessageDigestService::Url: AsRef<str> + Sync,
+    CalculateWebPageMessageDigestService::Digest: AsRef<[u8]>,
+    CalculateWebPageMessageDigestService::Error: fmt::Display,
+{
+    type Url = CalculateWebPageMessageDigestService::Url;
+    type Digest = CalculateWebPageMessageDigestService::Digest;
+    type Error = CalculateWebPageMessageDigestService::Error;
+
+    async fn calculate_web_page_message_digest(
+        &self,
+        injector: &'fn_call Injector,
+        url: &Self::Url,
+    ) -> Result<Self::Digest, Self::Error>
+    where
+        &'fn_call Injector: Send,
+    {
+        let span = tracing::info_span!(
+            "calculate_web_page_message_digest",
+            url = redact_url_credentials(url.as_ref()).as_ref()
+        );
+
+        let clock: &DynClock = InjectRef::inject_ref(injector);
+        let started = clock.now();
+        let digest_result = self
+            .inner()
+            .calculate_web_page_message_digest(injector, url)
+            .instrument(span.clone())
+            .await;
+        let duration_ms =
+            duration_as_millis(clock.now().duration_since(started).unwrap_or_default());
+
+        span.in_scope(|| match &digest_result {
+            Ok(digest) => tracing::info!(
+                digest = hex::encode(digest),
+                duration_ms,
+                "calculated the Web page message digest"
+            ),
+            // The alternate format includes causes, e.g., of [anyhow::Error]s:
+            Err(error) => tracing::error!(
+                error = format!("{:#}", error),
+                duration_ms,
+                "calculating the Web page message digest failed"
+            ),
+        });
+
+        digest_result
+    }
+}
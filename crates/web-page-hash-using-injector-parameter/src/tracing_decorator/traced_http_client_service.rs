@@ -0,0 +1,145 @@
+use super::traced::{Traced, duration_as_millis, error_details};
+use crate::credential_provider_service::redact_url_credentials;
+use crate::http_client_service::{GetUrl, IntoChunkStream, ResponseMetadata};
+use bytes::Bytes;
+use futures_core::stream::Stream;
+use futures_util::StreamExt;
+use http::{HeaderMap, StatusCode};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+use tracing::{Instrument, Span};
+
+impl<HttpClientService> GetUrl for Traced<HttpClientService>
+where
+    HttpClientService: GetUrl + Sync,
+    HttpClientService::Url: AsRef<str> + Sync,
+    HttpClientService::HttpResponse: ResponseMetadata,
+    HttpClientService::Error: std::error::Error,
+{
+    type Url = HttpClientService::Url;
+    type HttpResponse = TracedHttpResponse<HttpClientService::HttpResponse>;
+    type Error = HttpClientService::Error;
+
+    async fn get_url(&self, url: &Self::Url) -> Result<Self::HttpResponse, Self::Error> {
+        trace_fetch(url.as_ref(), self.inner().get_url(url)).await
+    }
+}
+
+async fn trace_fetch<HttpResponse, Error>(
+    url: &str,
+    response_future: impl Future<Output = Result<HttpResponse, Error>>,
+) -> Result<TracedHttpResponse<HttpResponse>, Error>
+where
+    HttpResponse: ResponseMetadata,
+    Error: std::error::Error,
+{
+    let url = redact_url_credentials(url);
+
+    async {
+        let started = Instant::now();
+        let response_result = response_future.await;
+        let duration_ms = duration_as_millis(started.elapsed());
+
+        match response_result {
+            Ok(response) => {
+                tracing::info!(
+                    status = response.status().as_u16(),
+                    duration_ms,
+                    "fetched the response headers"
+                );
+
+                Ok(TracedHttpResponse {
+                    response,
+                    url: url.to_string(),
+                })
+            }
+            Err(error) => {
+                tracing::error!(error = error_details(&error), duration_ms, "fetch failed");
+
+                Err(error)
+            }
+        }
+    }
+    .instrument(tracing::info_span!("fetch", url = url.as_ref()))
+    .await
+}
+
+/// A response returned by a [Traced] HTTP client service
+///
+/// Its chunk stream is traced in a `stream` span.
+#[derive(Debug)]
+pub struct TracedHttpResponse<HttpResponse> {
+    response: HttpResponse,
+    url: String,
+}
+
+impl<HttpResponse: ResponseMetadata> ResponseMetadata for TracedHttpResponse<HttpResponse> {
+    fn status(&self) -> StatusCode {
+        self.response.status()
+    }
+
+    fn headers(&self) -> &HeaderMap {
+        self.response.headers()
+    }
+}
+
+impl<HttpResponse> IntoChunkStream for TracedHttpResponse<HttpResponse>
+where
+    HttpResponse: IntoChunkStream,
+    HttpResponse::Error: std::error::Error,
+{
+    type Error = HttpResponse::Error;
+
+    fn into_chunk_stream(self) -> impl Stream<Item = Result<Bytes, Self::Error>> + Unpin {
+        TracedChunkStream {
+            chunk_stream: self.response.into_chunk_stream(),
+            span: tracing::info_span!("stream", url = self.url),
+            byte_count: 0,
+            streaming_duration: Duration::ZERO,
+        }
+    }
+}
+
+struct TracedChunkStream<ChunkStream> {
+    chunk_stream: ChunkStream,
+    span: Span,
+    byte_count: u64,
+    /// The time spent polling the stream, excluding the time spent
+    /// processing its chunks
+    streaming_duration: Duration,
+}
+
+impl<ChunkStream, Error> Stream for TracedChunkStream<ChunkStream>
+where
+    ChunkStream: Stream<Item = Result<Bytes, Error>> + Unpin,
+    Error: std::error::Error,
+{
+    type Item = Result<Bytes, Error>;
+
+    fn poll_next(self: Pin<&mut Self>, context: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let traced_chunk_stream = self.get_mut();
+        let _entered_span = traced_chunk_stream.span.enter();
+        let started = Instant::now();
+        let poll = traced_chunk_stream.chunk_stream.poll_next_unpin(context);
+        traced_chunk_stream.streaming_duration += started.elapsed();
+        let byte_count = traced_chunk_stream.byte_count;
+        let duration_ms = duration_as_millis(traced_chunk_stream.streaming_duration);
+
+        match &poll {
+            Poll::Ready(Some(Ok(chunk))) => traced_chunk_stream.byte_count += chunk.len() as u64,
+            Poll::Ready(Some(Err(error))) => tracing::error!(
+                byte_count,
+                error = error_details(error),
+                duration_ms,
+                "streaming the response body failed"
+            ),
+            Poll::Ready(None) => {
+                tracing::info!(byte_count, duration_ms, "streamed the response body")
+            }
+            Poll::Pending => {}
+        }
+
+        poll
+    }
+}